@@ -11,11 +11,18 @@
  * - Output: JSON lines on stdout
  * 
  * Request format:
- *   { "type": "message", "content": "...", "model": "..." }
+ *   { "type": "message", "content": "...", "model": "...", "system_prompt": "...", "stream": true }
+ *
+ * "system_prompt" is the conversation's persona; it is sent with every turn
+ * and applied before the message (absent when the conversation has none).
  * 
  * Response format:
  *   { "status": "success", "data": "..." }
  *   { "status": "error", "message": "..." }
+ *
+ * With "stream": true, each piece of text is also sent as it arrives, as
+ *   { "status": "chunk", "data": "..." }
+ * before the final response (whose "data" is still the whole reply).
  */
 
 import { GeminiChat, Config, AuthType, DEFAULT_GEMINI_FLASH_MODEL } from '@google/gemini-cli-core';
@@ -30,6 +37,8 @@ const rl = readline.createInterface({
 // Initialize GeminiChat instance once (maintains conversation state)
 let chat = null;
 let config = null;
+// System prompt currently applied to the chat
+let currentSystemPrompt = '';
 
 async function initializeChat() {
   if (chat) {
@@ -84,7 +93,7 @@ async function initializeChat() {
 async function handleMessage(request) {
   try {
     // Validate content before trying to initialize chat
-    const { content, model } = request;
+    const { content, model, system_prompt: systemPrompt, stream: streamChunks } = request;
     
     if (!content || typeof content !== 'string' || content.trim().length === 0) {
       return {
@@ -98,6 +107,19 @@ async function handleMessage(request) {
       await initializeChat();
     }
 
+    // Apply the conversation's system prompt (or clear one that was removed)
+    const requestedSystemPrompt = typeof systemPrompt === 'string' ? systemPrompt : '';
+    let message = content;
+    if (typeof chat.setSystemInstruction === 'function') {
+      if (requestedSystemPrompt !== currentSystemPrompt) {
+        chat.setSystemInstruction(requestedSystemPrompt);
+        currentSystemPrompt = requestedSystemPrompt;
+      }
+    } else if (requestedSystemPrompt) {
+      // Older SDKs can't set it on the chat; prepend it to the turn instead
+      message = `${requestedSystemPrompt}\n\n${content}`;
+    }
+
     // Get effective model from config or use provided model
     // If model is provided, use it; otherwise use config's model or default
     const effectiveModel = model || config?.getModel() || 'gemini-2.5-flash';
@@ -110,7 +132,7 @@ async function handleMessage(request) {
     try {
       stream = await chat.sendMessageStream(
         effectiveModel,
-        { message: content },
+        { message },
         `bridge-${Date.now()}`
       );
     } catch (error) {
@@ -129,6 +151,9 @@ async function handleMessage(request) {
           for (const part of chunk.candidates[0].content.parts) {
             if (part.text) {
               fullResponse += part.text;
+              if (streamChunks === true) {
+                console.log(JSON.stringify({ status: 'chunk', data: part.text }));
+              }
             }
           }
         }
@@ -0,0 +1,18 @@
+-- Audit log schema
+-- Durable record of every orchestration: goal, plan and outcome
+
+-- Audit table (raw goal text is only stored when explicitly enabled)
+CREATE TABLE IF NOT EXISTS audit (
+    id TEXT PRIMARY KEY,
+    created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+    goal_hash TEXT NOT NULL,
+    goal TEXT,
+    plan_hash TEXT,
+    step_count INTEGER NOT NULL DEFAULT 0,
+    success INTEGER NOT NULL DEFAULT 0,
+    duration_ms INTEGER NOT NULL DEFAULT 0,
+    error TEXT
+);
+
+-- Index for time-range queries
+CREATE INDEX IF NOT EXISTS idx_audit_created_at ON audit(created_at);
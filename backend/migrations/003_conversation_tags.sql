@@ -0,0 +1,10 @@
+-- Conversation tags and pinning
+-- Tags are stored as a JSON array of strings; pinned conversations list first
+
+-- ADD COLUMN is not idempotent in SQLite; re-runs report "duplicate column name",
+-- which run_migration treats as already applied
+ALTER TABLE conversations ADD COLUMN tags TEXT NOT NULL DEFAULT '[]';
+ALTER TABLE conversations ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
+
+-- Index for pinned-first listing
+CREATE INDEX IF NOT EXISTS idx_conversations_pinned_updated ON conversations(pinned, updated_at);
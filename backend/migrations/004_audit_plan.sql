@@ -0,0 +1,6 @@
+-- Retained plans for replay
+-- The executed plan is stored as JSON only when audit_store_plan is enabled
+
+-- ADD COLUMN is not idempotent in SQLite; re-runs report "duplicate column name",
+-- which run_migration treats as already applied
+ALTER TABLE audit ADD COLUMN plan TEXT;
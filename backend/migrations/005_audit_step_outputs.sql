@@ -0,0 +1,6 @@
+-- Retained step outputs for restarting a run from a step
+-- Stored as a JSON object alongside the plan, only when audit_store_plan is enabled
+
+-- ADD COLUMN is not idempotent in SQLite; re-runs report "duplicate column name",
+-- which run_migration treats as already applied
+ALTER TABLE audit ADD COLUMN step_outputs TEXT;
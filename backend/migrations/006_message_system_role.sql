@@ -0,0 +1,23 @@
+-- Allow 'system' messages (conversation summaries)
+-- SQLite can't change a CHECK constraint in place, so the table is rebuilt.
+-- Not idempotent: ChatDb only applies it while the old constraint is in place,
+-- inside a transaction
+
+CREATE TABLE messages_new (
+    id TEXT PRIMARY KEY,
+    conversation_id TEXT NOT NULL,
+    role TEXT NOT NULL CHECK(role IN ('user', 'assistant', 'system')),
+    content TEXT NOT NULL,
+    created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+    FOREIGN KEY (conversation_id) REFERENCES conversations(id) ON DELETE CASCADE
+);
+
+INSERT INTO messages_new (id, conversation_id, role, content, created_at)
+    SELECT id, conversation_id, role, content, created_at FROM messages ORDER BY created_at, rowid;
+
+DROP TABLE messages;
+ALTER TABLE messages_new RENAME TO messages;
+
+-- Indexes are dropped with the old table
+CREATE INDEX IF NOT EXISTS idx_messages_conversation_id ON messages(conversation_id);
+CREATE INDEX IF NOT EXISTS idx_messages_created_at ON messages(created_at);
@@ -0,0 +1,6 @@
+-- Mark assistant messages whose generation was stopped by the user
+-- Applied after 006, which rebuilds the messages table without this column
+
+-- ADD COLUMN is not idempotent in SQLite; re-runs report "duplicate column name",
+-- which run_migration treats as already applied
+ALTER TABLE messages ADD COLUMN stopped INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,6 @@
+-- Per-conversation system prompt (persona), sent to the bridge with every turn
+-- NULL means the conversation has none
+
+-- ADD COLUMN is not idempotent in SQLite; re-runs report "duplicate column name",
+-- which run_migration treats as already applied
+ALTER TABLE conversations ADD COLUMN system_prompt TEXT;
@@ -0,0 +1,6 @@
+-- Execution summary (step results, counts and token usage) as JSON
+-- Stored alongside the plan, only when audit_store_plan is enabled
+
+-- ADD COLUMN is not idempotent in SQLite; re-runs report "duplicate column name",
+-- which run_migration treats as already applied
+ALTER TABLE audit ADD COLUMN summary TEXT;
@@ -0,0 +1,236 @@
+//! Agent log API handlers
+//!
+//! Exposes each agent's recent output lines, with an optional `since`
+//! filter for catching up and a `follow` SSE mode for live tailing.
+
+use crate::api::utils::RouterState;
+use crate::error::AppError;
+use crate::orchestrator::constants::SSE_DONE_SIGNAL;
+use crate::state::{AgentId, LogLine};
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+
+/// Query parameters for GET /api/agents/:id/logs
+#[derive(Debug, Default, Deserialize)]
+pub struct LogsQuery {
+    /// Only return lines recorded after this time (Unix milliseconds)
+    pub since: Option<i64>,
+    /// Stream new lines via SSE instead of returning a snapshot
+    #[serde(default)]
+    pub follow: bool,
+}
+
+/// Agent logs snapshot response
+#[derive(Debug, Serialize)]
+pub struct AgentLogsResponse {
+    /// Agent the lines belong to
+    pub agent_id: AgentId,
+    /// Log lines, oldest first
+    pub lines: Vec<LogLine>,
+    /// Number of lines returned
+    pub count: usize,
+}
+
+/// GET /api/agents/:id/logs - Get or follow an agent's recent log lines
+///
+/// With `follow=true` the response is an SSE stream: buffered lines after
+/// `since` are sent first, then new lines as they are produced. The stream
+/// ends with `[DONE]` when the agent is deleted.
+pub async fn get_agent_logs(
+    State((state, _, _)): State<RouterState>,
+    Path(id): Path<AgentId>,
+    Query(query): Query<LogsQuery>,
+) -> Result<Response, AppError> {
+    if !query.follow {
+        let lines = state
+            .read()
+            .await
+            .agent_log_lines(&id, query.since)
+            .ok_or_else(|| AppError::AgentNotFound(id.clone()))?;
+
+        return Ok(Json(AgentLogsResponse {
+            agent_id: id,
+            count: lines.len(),
+            lines,
+        })
+        .into_response());
+    }
+
+    // Snapshot and subscribe under one lock so no line is missed or duplicated
+    let (backlog, mut receiver) = state
+        .write()
+        .await
+        .follow_agent_log(&id, query.since)
+        .ok_or_else(|| AppError::AgentNotFound(id.clone()))?;
+
+    let stream = async_stream::stream! {
+        for line in backlog {
+            yield format_log_event(&line);
+        }
+
+        loop {
+            match receiver.recv().await {
+                Ok(line) => {
+                    yield format_log_event(&line);
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!(agent_id = %id, skipped, "Log follower lagged, lines skipped");
+                }
+                // Sender dropped: the agent was deleted
+                Err(RecvError::Closed) => break,
+            }
+        }
+
+        yield format!("data: {}\n\n", SSE_DONE_SIGNAL);
+    };
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/event-stream")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .header(header::CONNECTION, "keep-alive")
+        .body(Body::from_stream(stream.map(Ok::<_, std::io::Error>)))
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to build SSE response: {}", e)))
+}
+
+/// Format a log line as an SSE data frame
+fn format_log_event(line: &LogLine) -> String {
+    let data = serde_json::to_string(line).unwrap_or_else(|e| {
+        tracing::error!("Failed to serialize log line: {}", e);
+        "{}".to_string()
+    });
+    format!("data: {}\n\n", data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::ChatDb;
+    use crate::state::{Agent, AgentType, AppState};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tempfile::TempDir;
+    use tokio::sync::RwLock;
+
+    async fn create_test_router_state() -> (RouterState, TempDir) {
+        let mut app_state = AppState::new();
+        app_state.add_agent(Agent::new(
+            "agent-1".to_string(),
+            "Log Agent".to_string(),
+            AgentType::Gemini,
+        ));
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let chat_db = ChatDb::new(db_path.to_str().unwrap())
+            .await
+            .expect("Failed to create test database");
+        let bridge_manager = Arc::new(crate::chat::BridgeManager::new());
+        (
+            (
+                Arc::new(RwLock::new(app_state)),
+                Arc::new(chat_db),
+                bridge_manager,
+            ),
+            temp_dir,
+        )
+    }
+
+    async fn next_frame(stream: &mut axum::body::BodyDataStream) -> Option<String> {
+        tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("Timed out waiting for SSE frame")
+            .map(|chunk| String::from_utf8(chunk.unwrap().to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_get_agent_logs_since_filter() {
+        let (router_state, _temp_dir) = create_test_router_state().await;
+        let id = "agent-1".to_string();
+        {
+            let mut state = router_state.0.write().await;
+            let log = state.agent_logs.entry(id.clone()).or_default();
+            log.push_with_timestamp(1_000, "before".to_string());
+            log.push_with_timestamp(2_000, "after".to_string());
+        }
+
+        let response = get_agent_logs(
+            State(router_state),
+            Path(id),
+            Query(LogsQuery {
+                since: Some(1_000),
+                follow: false,
+            }),
+        )
+        .await
+        .unwrap();
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["count"], 1);
+        assert_eq!(json["lines"][0]["line"], "after");
+    }
+
+    #[tokio::test]
+    async fn test_get_agent_logs_unknown_agent() {
+        let (router_state, _temp_dir) = create_test_router_state().await;
+        let result = get_agent_logs(
+            State(router_state),
+            Path("missing".to_string()),
+            Query(LogsQuery::default()),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::AgentNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_follow_delivers_new_line_and_ends_on_delete() {
+        let (router_state, _temp_dir) = create_test_router_state().await;
+        let id = "agent-1".to_string();
+        router_state
+            .0
+            .write()
+            .await
+            .append_agent_log(&id, "existing".to_string());
+
+        let response = get_agent_logs(
+            State(router_state.clone()),
+            Path(id.clone()),
+            Query(LogsQuery {
+                since: None,
+                follow: true,
+            }),
+        )
+        .await
+        .unwrap();
+        let mut stream = response.into_body().into_data_stream();
+
+        assert!(next_frame(&mut stream).await.unwrap().contains("existing"));
+
+        router_state
+            .0
+            .write()
+            .await
+            .append_agent_log(&id, "fresh line".to_string());
+        assert!(next_frame(&mut stream)
+            .await
+            .unwrap()
+            .contains("fresh line"));
+
+        // Deleting the agent closes the stream cleanly
+        router_state.0.write().await.remove_agent(&id);
+        assert!(next_frame(&mut stream)
+            .await
+            .unwrap()
+            .contains(SSE_DONE_SIGNAL));
+        assert!(next_frame(&mut stream).await.is_none());
+    }
+}
@@ -4,9 +4,10 @@
 
 use crate::api::utils::RouterState;
 use crate::error::AppError;
-use crate::state::{Agent, AgentId, AgentStatus, AgentType};
+use crate::services::working_dir::{WorkingDir, WorkingDirRoots};
+use crate::state::{Agent, AgentConfig, AgentId, AgentStatus, AgentType, LastError, StatusChange};
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::Json,
 };
@@ -23,6 +24,8 @@ pub struct AgentResponse {
     pub agent_type: AgentType,
     /// Current status of the agent (Running, Stopped, etc.)
     pub status: AgentStatus,
+    /// Reason for the most recent failed execution, if it hasn't succeeded since
+    pub last_error: Option<LastError>,
 }
 
 impl From<&Agent> for AgentResponse {
@@ -32,10 +35,314 @@ impl From<&Agent> for AgentResponse {
             name: agent.name.clone(),
             agent_type: agent.agent_type.clone(),
             status: agent.status,
+            last_error: agent.last_error.clone(),
         }
     }
 }
 
+/// Query parameters for GET /api/agents/:id
+#[derive(Debug, Default, Deserialize)]
+pub struct GetAgentQuery {
+    /// Comma-separated extra sections to include (currently only `config`)
+    pub include: Option<String>,
+}
+
+impl GetAgentQuery {
+    fn includes(&self, section: &str) -> bool {
+        self.include
+            .as_deref()
+            .is_some_and(|include| include.split(',').any(|s| s.trim() == section))
+    }
+}
+
+/// Placeholder returned in place of secret-looking values
+const MASKED_VALUE: &str = "********";
+
+/// Version of the agent export format
+const AGENT_EXPORT_VERSION: u32 = 1;
+
+/// Agent response with optional expanded sections
+#[derive(Debug, Serialize)]
+pub struct AgentDetailResponse {
+    /// Lean agent fields
+    #[serde(flatten)]
+    pub agent: AgentResponse,
+    /// Agent configuration (only with `?include=config`), secrets masked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<AgentConfig>,
+}
+
+/// Copy an agent config with secret-looking values replaced by a mask
+fn masked_config(config: &AgentConfig) -> AgentConfig {
+    let mask_map = |map: &std::collections::HashMap<String, String>| {
+        map.iter()
+            .map(|(key, value)| {
+                let value = if looks_like_secret(key, value) {
+                    MASKED_VALUE.to_string()
+                } else {
+                    value.clone()
+                };
+                (key.clone(), value)
+            })
+            .collect()
+    };
+
+    AgentConfig {
+        command: config.command.clone(),
+        args: config
+            .args
+            .iter()
+            .zip(secret_args(&config.args))
+            .map(|(arg, secret)| {
+                if secret {
+                    MASKED_VALUE.to_string()
+                } else {
+                    arg.clone()
+                }
+            })
+            .collect(),
+        env_vars: mask_map(&config.env_vars),
+        working_dir: config.working_dir.clone(),
+        options: mask_map(&config.options),
+        max_concurrency: config.max_concurrency,
+    }
+}
+
+/// Whether an arg is a flag without an attached `=value`
+fn is_bare_flag(arg: &str) -> bool {
+    arg.starts_with('-') && !arg.contains('=')
+}
+
+/// Flag each arg that holds a secret
+///
+/// That is a credential-looking value, the value attached to a flag naming a
+/// secret (`--api-key=...`), or the value following one (`--api-key ...`).
+fn secret_args(args: &[String]) -> Vec<bool> {
+    let names_secret =
+        |flag: &str| looks_like_secret(&flag.trim_start_matches('-').replace('-', "_"), "");
+
+    args.iter()
+        .enumerate()
+        .map(|(i, arg)| {
+            let attached = arg.starts_with('-')
+                && arg
+                    .split_once('=')
+                    .is_some_and(|(flag, _)| names_secret(flag));
+            let follows_secret_flag = i > 0
+                && !arg.starts_with('-')
+                && is_bare_flag(&args[i - 1])
+                && names_secret(&args[i - 1]);
+            looks_like_secret("", arg) || attached || follows_secret_flag
+        })
+        .collect()
+}
+
+/// Copy an agent config with secret-looking values left out entirely
+///
+/// A secret arg is dropped together with the flag in front of it, so no
+/// `--api-key` is left without its value.
+fn config_without_secrets(config: &AgentConfig) -> AgentConfig {
+    let keep_map = |map: &std::collections::HashMap<String, String>| {
+        map.iter()
+            .filter(|(key, value)| !looks_like_secret(key, value))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    };
+
+    AgentConfig {
+        command: config.command.clone(),
+        args: {
+            let secret = secret_args(&config.args);
+            let flags_secret = |i: usize| {
+                is_bare_flag(&config.args[i])
+                    && secret.get(i + 1).copied().unwrap_or(false)
+                    && !config.args[i + 1].starts_with('-')
+            };
+            config
+                .args
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| !secret[i] && !flags_secret(i))
+                .map(|(_, arg)| arg.clone())
+                .collect()
+        },
+        env_vars: keep_map(&config.env_vars),
+        working_dir: config.working_dir.clone(),
+        options: keep_map(&config.options),
+        max_concurrency: config.max_concurrency,
+    }
+}
+
+/// How secret-looking config values appear in an export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportSecrets {
+    /// Replace them with a mask (the export can't be imported as-is)
+    #[default]
+    Mask,
+    /// Leave them out (the export can be imported, minus the secrets)
+    Exclude,
+}
+
+/// Query parameters for GET /api/agents/export
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportAgentsQuery {
+    /// How to treat secret-looking values (default: mask)
+    #[serde(default)]
+    pub secrets: ExportSecrets,
+}
+
+/// An agent's portable definition: everything but its runtime state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentDefinition {
+    /// Unique identifier for the agent
+    pub id: AgentId,
+    /// Human-readable name of the agent
+    pub name: String,
+    /// Type of agent
+    pub agent_type: AgentType,
+    /// Agent configuration
+    pub config: AgentConfig,
+}
+
+/// Agents export response (also the body `POST /api/agents/import` accepts)
+#[derive(Debug, Serialize)]
+pub struct AgentsExportResponse {
+    /// Export format version
+    pub version: u32,
+    /// Agent definitions, ordered by ID
+    pub agents: Vec<AgentDefinition>,
+    /// Number of agents exported
+    pub count: usize,
+}
+
+/// What to do when an imported agent's ID already exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Keep the existing agent
+    #[default]
+    Skip,
+    /// Overwrite the existing agent's name, type and config
+    Replace,
+}
+
+/// Import agents request
+#[derive(Debug, Deserialize)]
+pub struct ImportAgentsRequest {
+    /// How ID collisions are handled (default: skip)
+    #[serde(default)]
+    pub mode: ImportMode,
+    /// Agent definitions to import
+    pub agents: Vec<AgentDefinition>,
+}
+
+/// What happened to one imported agent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportOutcome {
+    /// Added as a new agent
+    Imported,
+    /// Overwrote an existing agent with the same ID
+    Replaced,
+    /// Left out because an agent with the same ID exists
+    Skipped,
+    /// Rejected by validation
+    Failed,
+}
+
+/// Result of importing one agent
+#[derive(Debug, Serialize)]
+pub struct AgentImportResult {
+    /// ID of the agent in the request
+    pub id: AgentId,
+    /// What happened to it
+    pub outcome: ImportOutcome,
+    /// Why it failed (only for `failed`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Import agents response
+#[derive(Debug, Serialize)]
+pub struct ImportAgentsResponse {
+    /// One result per agent in the request, in request order
+    pub results: Vec<AgentImportResult>,
+    /// Number of agents added
+    pub imported: usize,
+    /// Number of existing agents overwritten
+    pub replaced: usize,
+    /// Number of agents skipped on ID collision
+    pub skipped: usize,
+    /// Number of agents rejected
+    pub failed: usize,
+}
+
+/// Check an imported agent definition and build the config to store
+///
+/// Only the name, type, args, env vars, options and concurrency are taken
+/// from the definition, as with `create_agent` and `update_agent`: the
+/// command always comes from `AgentConfig::for_type`, environment variables
+/// get the same checks as `POST /api/agents/:id/env` (secret-looking ones are
+/// refused; set those through that endpoint), and a working directory must
+/// pass the same root checks as any other.
+fn imported_config(
+    definition: &AgentDefinition,
+    roots: &WorkingDirRoots,
+) -> Result<AgentConfig, String> {
+    if definition.id.trim().is_empty() {
+        return Err("Agent ID cannot be empty".to_string());
+    }
+
+    let config = &definition.config;
+    let masked = config.args.iter().any(|arg| arg == MASKED_VALUE)
+        || config
+            .env_vars
+            .values()
+            .chain(config.options.values())
+            .any(|value| value == MASKED_VALUE);
+    if masked {
+        return Err(
+            "Config contains masked secrets; export with secrets=exclude or fill them in"
+                .to_string(),
+        );
+    }
+
+    for (key, value) in &config.env_vars {
+        validate_env_key(key).map_err(|e| e.to_string())?;
+        if looks_like_secret(key, value) {
+            return Err(format!(
+                "Environment variable '{}' looks like a secret; set it with POST /api/agents/:id/env",
+                key
+            ));
+        }
+    }
+
+    let working_dir = config
+        .working_dir
+        .as_deref()
+        .map(|dir| WorkingDir::new(dir, roots).map(|dir| dir.to_string()))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    let imported = AgentConfig {
+        command: AgentConfig::for_type(&definition.agent_type).command,
+        args: config.args.clone(),
+        env_vars: config.env_vars.clone(),
+        working_dir,
+        options: config.options.clone(),
+        max_concurrency: config.max_concurrency,
+    };
+
+    Agent::with_config(
+        definition.id.clone(),
+        definition.name.clone(),
+        definition.agent_type.clone(),
+        imported.clone(),
+    )
+    .validate()?;
+    Ok(imported)
+}
+
 /// Agents list response
 #[derive(Serialize)]
 pub struct AgentsListResponse {
@@ -64,6 +371,9 @@ pub struct CreateAgentRequest {
 }
 
 /// Update agent request
+///
+/// Omitted fields are left unchanged. See `update_agent` for how a type
+/// change treats the existing config.
 #[derive(Deserialize)]
 pub struct UpdateAgentRequest {
     /// New name for the agent (optional)
@@ -72,6 +382,90 @@ pub struct UpdateAgentRequest {
     pub agent_type: Option<AgentType>,
     /// New status for the agent (optional)
     pub status: Option<AgentStatus>,
+    /// Replace the config with the new type's defaults instead of merging
+    #[serde(default)]
+    pub reset_config: bool,
+    /// How many executions of the agent may run at once (optional)
+    pub max_concurrency: Option<usize>,
+}
+
+/// Set environment variable request
+#[derive(Deserialize)]
+pub struct SetEnvVarRequest {
+    /// Environment variable name (e.g. `HTTP_PROXY`)
+    pub key: String,
+    /// Environment variable value
+    pub value: String,
+    /// Allow storing a value that looks like a secret (persisted in plain text)
+    #[serde(default)]
+    pub allow_secret: bool,
+}
+
+/// Agent environment variables response
+///
+/// Only the variable names are returned so secrets are never echoed back.
+#[derive(Debug, Serialize)]
+pub struct AgentEnvVarsResponse {
+    /// Agent the variables belong to
+    pub id: AgentId,
+    /// Names of the configured environment variables (sorted)
+    pub keys: Vec<String>,
+}
+
+impl From<&Agent> for AgentEnvVarsResponse {
+    fn from(agent: &Agent) -> Self {
+        let mut keys: Vec<String> = agent.config.env_vars.keys().cloned().collect();
+        keys.sort();
+        Self {
+            id: agent.id.clone(),
+            keys,
+        }
+    }
+}
+
+/// Agent status history response
+#[derive(Debug, Serialize)]
+pub struct AgentStatusHistoryResponse {
+    /// Agent the transitions belong to
+    pub agent_id: AgentId,
+    /// Status the agent is in now
+    pub current_status: AgentStatus,
+    /// Recorded transitions, oldest first
+    pub history: Vec<StatusChange>,
+    /// Number of transitions returned
+    pub count: usize,
+}
+
+/// Key name fragments that indicate the value is a credential
+const SECRET_KEY_MARKERS: &[&str] = &["SECRET", "TOKEN", "PASSWORD", "PASSWD", "API_KEY", "APIKEY"];
+
+/// Value prefixes of well-known credential formats
+const SECRET_VALUE_PREFIXES: &[&str] = &["sk-", "AIza", "ghp_", "github_pat_", "xoxb-", "AKIA"];
+
+/// Validate an environment variable name (`[A-Za-z_][A-Za-z0-9_]*`)
+fn validate_env_key(key: &str) -> Result<(), AppError> {
+    let mut chars = key.chars();
+    let valid = match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    };
+
+    if !valid {
+        return Err(AppError::InvalidAgentConfig(format!(
+            "Invalid environment variable name: '{}'",
+            key
+        )));
+    }
+    Ok(())
+}
+
+/// Check whether an environment variable looks like it holds a secret
+fn looks_like_secret(key: &str, value: &str) -> bool {
+    let upper = key.to_ascii_uppercase();
+    SECRET_KEY_MARKERS.iter().any(|m| upper.contains(m))
+        || SECRET_VALUE_PREFIXES.iter().any(|p| value.starts_with(p))
 }
 
 /// GET /api/agents - List all agents
@@ -92,17 +486,26 @@ pub async fn list_agents(
 }
 
 /// GET /api/agents/:id - Get a specific agent
+///
+/// With `?include=config` the response also carries the agent's config,
+/// with secret-looking values masked.
 pub async fn get_agent(
     State((state, _, _)): State<RouterState>,
     Path(id): Path<AgentId>,
-) -> Result<Json<AgentResponse>, AppError> {
+    Query(query): Query<GetAgentQuery>,
+) -> Result<Json<AgentDetailResponse>, AppError> {
     let state = state.read().await;
     let agent = state
         .agents
         .get(&id)
         .ok_or_else(|| AppError::AgentNotFound(id.clone()))?;
 
-    Ok(Json(AgentResponse::from(agent)))
+    Ok(Json(AgentDetailResponse {
+        agent: AgentResponse::from(agent),
+        config: query
+            .includes("config")
+            .then(|| masked_config(&agent.config)),
+    }))
 }
 
 /// POST /api/agents - Create a new agent
@@ -131,7 +534,13 @@ pub async fn create_agent(
     Ok((StatusCode::CREATED, Json(AgentResponse::from(agent))))
 }
 
-/// PUT /api/agents/:id - Update an agent
+/// PUT/PATCH /api/agents/:id - Update an agent
+///
+/// Only the fields present in the request change. When `agent_type` changes,
+/// the config is merged via `AgentConfig::merged_for_type`: the command and
+/// default args switch to the new type, while custom args, env vars, options
+/// and the working directory are kept. Set `reset_config` to discard them and
+/// start from the new type's defaults.
 pub async fn update_agent(
     State((state, _, _)): State<RouterState>,
     Path(id): Path<AgentId>,
@@ -148,14 +557,22 @@ pub async fn update_agent(
     }
 
     if let Some(agent_type) = request.agent_type {
-        agent.agent_type = agent_type.clone();
-        agent.config = crate::state::AgentConfig::for_type(&agent_type);
+        agent.config = if request.reset_config {
+            AgentConfig::for_type(&agent_type)
+        } else {
+            agent.config.merged_for_type(&agent.agent_type, &agent_type)
+        };
+        agent.agent_type = agent_type;
     }
 
     if let Some(status) = request.status {
         agent.status = status;
     }
 
+    if let Some(max_concurrency) = request.max_concurrency {
+        agent.config.max_concurrency = max_concurrency;
+    }
+
     // Validate updated agent
     agent.validate().map_err(AppError::InvalidAgentConfig)?;
 
@@ -219,6 +636,200 @@ pub async fn stop_agent(
     Ok(Json(AgentResponse::from(agent)))
 }
 
+/// POST /api/agents/:id/reset - Recover an agent stuck in Error or Running
+///
+/// Aborts any in-flight query for the agent, clears its last error and sets it
+/// back to Idle.
+pub async fn reset_agent(
+    State((state, _, _)): State<RouterState>,
+    Path(id): Path<AgentId>,
+) -> Result<Json<AgentResponse>, AppError> {
+    let mut state = state.write().await;
+    let aborted = state
+        .reset_agent(&id)
+        .ok_or_else(|| AppError::AgentNotFound(id.clone()))?;
+    if aborted > 0 {
+        tracing::info!(
+            "Reset agent {}: aborted {} in-flight execution(s)",
+            id,
+            aborted
+        );
+    }
+
+    let agent = state
+        .agents
+        .get(&id)
+        .ok_or_else(|| AppError::AgentNotFound(id.clone()))?;
+
+    Ok(Json(AgentResponse::from(agent)))
+}
+
+/// GET /api/agents/:id/status-history - Get an agent's recent status transitions
+pub async fn get_agent_status_history(
+    State((state, _, _)): State<RouterState>,
+    Path(id): Path<AgentId>,
+) -> Result<Json<AgentStatusHistoryResponse>, AppError> {
+    let state = state.read().await;
+    let current_status = state
+        .agents
+        .get(&id)
+        .map(|agent| agent.status)
+        .ok_or_else(|| AppError::AgentNotFound(id.clone()))?;
+    let history = state.agent_status_history(&id).unwrap_or_default();
+
+    Ok(Json(AgentStatusHistoryResponse {
+        agent_id: id,
+        current_status,
+        count: history.len(),
+        history,
+    }))
+}
+
+/// POST /api/agents/:id/env - Set a single environment variable on an agent
+///
+/// Overwrites the value if the key already exists. Secret-looking values are
+/// rejected unless `allow_secret` is set, since agent configs are persisted.
+pub async fn set_agent_env_var(
+    State((state, _, _)): State<RouterState>,
+    Path(id): Path<AgentId>,
+    Json(request): Json<SetEnvVarRequest>,
+) -> Result<Json<AgentEnvVarsResponse>, AppError> {
+    validate_env_key(&request.key)?;
+
+    if !request.allow_secret && looks_like_secret(&request.key, &request.value) {
+        return Err(AppError::InvalidAgentConfig(format!(
+            "Environment variable '{}' looks like a secret; set allow_secret to store it",
+            request.key
+        )));
+    }
+
+    let mut state = state.write().await;
+    let agent = state
+        .get_agent_mut(&id)
+        .ok_or_else(|| AppError::AgentNotFound(id.clone()))?;
+
+    agent.config.env_vars.insert(request.key, request.value);
+
+    Ok(Json(AgentEnvVarsResponse::from(&*agent)))
+}
+
+/// DELETE /api/agents/:id/env/:key - Remove a single environment variable from an agent
+pub async fn delete_agent_env_var(
+    State((state, _, _)): State<RouterState>,
+    Path((id, key)): Path<(AgentId, String)>,
+) -> Result<Json<AgentEnvVarsResponse>, AppError> {
+    validate_env_key(&key)?;
+
+    let mut state = state.write().await;
+    let agent = state
+        .get_agent_mut(&id)
+        .ok_or_else(|| AppError::AgentNotFound(id.clone()))?;
+
+    if agent.config.env_vars.remove(&key).is_none() {
+        return Err(AppError::InvalidAgentConfig(format!(
+            "Environment variable '{}' is not set on agent {}",
+            key, id
+        )));
+    }
+
+    Ok(Json(AgentEnvVarsResponse::from(&*agent)))
+}
+
+/// GET /api/agents/export - Export every agent's definition as JSON
+///
+/// Secret-looking values are masked by default; `?secrets=exclude` leaves
+/// them out instead, which gives an export that can be imported directly.
+pub async fn export_agents(
+    State((state, _, _)): State<RouterState>,
+    Query(query): Query<ExportAgentsQuery>,
+) -> Result<Json<AgentsExportResponse>, AppError> {
+    let state = state.read().await;
+    let mut agents: Vec<AgentDefinition> = state
+        .agents_list()
+        .into_iter()
+        .map(|agent| AgentDefinition {
+            id: agent.id.clone(),
+            name: agent.name.clone(),
+            agent_type: agent.agent_type.clone(),
+            config: match query.secrets {
+                ExportSecrets::Mask => masked_config(&agent.config),
+                ExportSecrets::Exclude => config_without_secrets(&agent.config),
+            },
+        })
+        .collect();
+    agents.sort_by(|a, b| a.id.cmp(&b.id));
+
+    Ok(Json(AgentsExportResponse {
+        version: AGENT_EXPORT_VERSION,
+        count: agents.len(),
+        agents,
+    }))
+}
+
+/// POST /api/agents/import - Import agent definitions
+///
+/// Each agent is validated and imported on its own, so one bad definition
+/// doesn't stop the rest. See `imported_config` for what an import may set.
+/// On an ID collision `mode` decides whether the existing agent is kept
+/// (`skip`) or overwritten (`replace`); a replaced agent keeps its runtime
+/// status. The registry is saved afterwards.
+pub async fn import_agents(
+    State((state, _, _)): State<RouterState>,
+    Json(request): Json<ImportAgentsRequest>,
+) -> Result<Json<ImportAgentsResponse>, AppError> {
+    let mut state = state.write().await;
+    let roots = state.working_dir_roots();
+    let mut results = Vec::with_capacity(request.agents.len());
+
+    for definition in request.agents {
+        let id = definition.id.clone();
+        let config = match imported_config(&definition, &roots) {
+            Ok(config) => config,
+            Err(error) => {
+                results.push(AgentImportResult {
+                    id,
+                    outcome: ImportOutcome::Failed,
+                    error: Some(error),
+                });
+                continue;
+            }
+        };
+
+        let outcome = match state.agents.get(&id) {
+            Some(_) if request.mode == ImportMode::Skip => ImportOutcome::Skipped,
+            existing => {
+                let mut agent =
+                    Agent::with_config(id.clone(), definition.name, definition.agent_type, config);
+                if let Some(existing) = existing {
+                    agent.status = existing.status;
+                    agent.last_error = existing.last_error.clone();
+                    state.update_agent(&id, agent);
+                    ImportOutcome::Replaced
+                } else {
+                    state.add_agent(agent);
+                    ImportOutcome::Imported
+                }
+            }
+        };
+        results.push(AgentImportResult {
+            id,
+            outcome,
+            error: None,
+        });
+    }
+
+    state.save_registry()?;
+
+    let count = |outcome| results.iter().filter(|r| r.outcome == outcome).count();
+    Ok(Json(ImportAgentsResponse {
+        imported: count(ImportOutcome::Imported),
+        replaced: count(ImportOutcome::Replaced),
+        skipped: count(ImportOutcome::Skipped),
+        failed: count(ImportOutcome::Failed),
+        results,
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -278,7 +889,12 @@ mod tests {
     #[tokio::test]
     async fn test_get_agent_not_found() {
         let router_state = create_test_router_state().await;
-        let result = get_agent(State(router_state), Path("nonexistent".to_string())).await;
+        let result = get_agent(
+            State(router_state),
+            Path("nonexistent".to_string()),
+            Query(GetAgentQuery::default()),
+        )
+        .await;
         assert!(result.is_err());
         match result.unwrap_err() {
             AppError::AgentNotFound(_) => {
@@ -289,4 +905,642 @@ mod tests {
             }
         }
     }
+
+    async fn create_gemini_agent(router_state: &RouterState) -> AgentId {
+        let request = CreateAgentRequest {
+            name: "Env Agent".to_string(),
+            agent_type: AgentType::Gemini,
+        };
+        let (_, response) = create_agent(State(router_state.clone()), Json(request))
+            .await
+            .unwrap();
+        response.id.clone()
+    }
+
+    async fn env_var(router_state: &RouterState, id: &AgentId, key: &str) -> Option<String> {
+        let state = router_state.0.read().await;
+        state.agents[id].config.env_vars.get(key).cloned()
+    }
+
+    fn set_request(key: &str, value: &str) -> SetEnvVarRequest {
+        SetEnvVarRequest {
+            key: key.to_string(),
+            value: value.to_string(),
+            allow_secret: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_set_agent_env_var() {
+        let router_state = create_test_router_state().await;
+        let id = create_gemini_agent(&router_state).await;
+
+        let response = set_agent_env_var(
+            State(router_state.clone()),
+            Path(id.clone()),
+            Json(set_request("HTTP_PROXY", "http://proxy:8080")),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.keys, vec!["HTTP_PROXY".to_string()]);
+        assert_eq!(
+            env_var(&router_state, &id, "HTTP_PROXY").await.as_deref(),
+            Some("http://proxy:8080")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_agent_env_var_overwrites() {
+        let router_state = create_test_router_state().await;
+        let id = create_gemini_agent(&router_state).await;
+
+        for value in ["first", "second"] {
+            set_agent_env_var(
+                State(router_state.clone()),
+                Path(id.clone()),
+                Json(set_request("LOG_LEVEL", value)),
+            )
+            .await
+            .unwrap();
+        }
+
+        assert_eq!(
+            env_var(&router_state, &id, "LOG_LEVEL").await.as_deref(),
+            Some("second")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_agent_env_var() {
+        let router_state = create_test_router_state().await;
+        let id = create_gemini_agent(&router_state).await;
+
+        set_agent_env_var(
+            State(router_state.clone()),
+            Path(id.clone()),
+            Json(set_request("LOG_LEVEL", "debug")),
+        )
+        .await
+        .unwrap();
+
+        let response = delete_agent_env_var(
+            State(router_state.clone()),
+            Path((id.clone(), "LOG_LEVEL".to_string())),
+        )
+        .await
+        .unwrap();
+
+        assert!(response.keys.is_empty());
+        assert!(env_var(&router_state, &id, "LOG_LEVEL").await.is_none());
+
+        // Deleting again reports the missing key
+        let result = delete_agent_env_var(
+            State(router_state.clone()),
+            Path((id, "LOG_LEVEL".to_string())),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::InvalidAgentConfig(_))));
+    }
+
+    #[tokio::test]
+    async fn test_set_agent_env_var_rejects_invalid_key_and_secrets() {
+        let router_state = create_test_router_state().await;
+        let id = create_gemini_agent(&router_state).await;
+
+        for request in [
+            set_request("1BAD", "x"),
+            set_request("BAD-KEY", "x"),
+            set_request("GITHUB_TOKEN", "abc"),
+            set_request("PROXY", "sk-abcdef"),
+        ] {
+            let result =
+                set_agent_env_var(State(router_state.clone()), Path(id.clone()), Json(request))
+                    .await;
+            assert!(matches!(result, Err(AppError::InvalidAgentConfig(_))));
+        }
+
+        // Explicit opt-in stores the secret
+        let mut request = set_request("GITHUB_TOKEN", "abc");
+        request.allow_secret = true;
+        set_agent_env_var(State(router_state.clone()), Path(id.clone()), Json(request))
+            .await
+            .unwrap();
+        assert!(env_var(&router_state, &id, "GITHUB_TOKEN").await.is_some());
+    }
+
+    async fn router_state_with_configured_agent() -> RouterState {
+        let router_state = create_test_router_state().await;
+        let mut agent = Agent::new(
+            "agent-1".to_string(),
+            "Configured".to_string(),
+            AgentType::Gemini,
+        );
+        agent.config.args = vec![
+            "--verbose".to_string(),
+            "--api-key".to_string(),
+            "sk-live-abc".to_string(),
+        ];
+        agent
+            .config
+            .env_vars
+            .insert("GEMINI_API_KEY".to_string(), "AIzaSecret".to_string());
+        agent
+            .config
+            .env_vars
+            .insert("LOG_LEVEL".to_string(), "debug".to_string());
+        agent.config.working_dir = Some("/tmp/work".to_string());
+        router_state.0.write().await.add_agent(agent);
+        router_state
+    }
+
+    #[tokio::test]
+    async fn test_get_agent_default_is_lean() {
+        let router_state = router_state_with_configured_agent().await;
+        let Json(response) = get_agent(
+            State(router_state),
+            Path("agent-1".to_string()),
+            Query(GetAgentQuery::default()),
+        )
+        .await
+        .unwrap();
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["id"], "agent-1");
+        assert!(json.get("config").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_agent_surfaces_last_error() {
+        let router_state = router_state_with_configured_agent().await;
+        router_state
+            .0
+            .write()
+            .await
+            .record_agent_execution(&"agent-1".to_string(), Some("exit code 1".to_string()));
+
+        let Json(response) = get_agent(
+            State(router_state),
+            Path("agent-1".to_string()),
+            Query(GetAgentQuery::default()),
+        )
+        .await
+        .unwrap();
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["last_error"]["message"], "exit code 1");
+        assert!(json["last_error"]["timestamp_ms"].as_i64().unwrap() > 0);
+    }
+
+    #[tokio::test]
+    async fn test_reset_errored_agent_returns_to_idle() {
+        let router_state = router_state_with_configured_agent().await;
+        let id = "agent-1".to_string();
+        {
+            let mut state = router_state.0.write().await;
+            state.update_agent_status(&id, AgentStatus::Error);
+            state.record_agent_execution(&id, Some("exit code 1".to_string()));
+        }
+
+        let Json(response) = reset_agent(State(router_state.clone()), Path(id.clone()))
+            .await
+            .unwrap();
+        assert_eq!(response.status, AgentStatus::Idle);
+        assert!(response.last_error.is_none());
+
+        let state = router_state.0.read().await;
+        let agent = state.agents.get(&id).unwrap();
+        assert_eq!(agent.status, AgentStatus::Idle);
+        assert!(agent.last_error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reset_missing_agent_is_not_found() {
+        let router_state = create_test_router_state().await;
+        let result = reset_agent(State(router_state), Path("missing".to_string())).await;
+        assert!(matches!(result, Err(AppError::AgentNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_get_agent_include_config_masks_secrets() {
+        let router_state = router_state_with_configured_agent().await;
+        let Json(response) = get_agent(
+            State(router_state),
+            Path("agent-1".to_string()),
+            Query(GetAgentQuery {
+                include: Some("config".to_string()),
+            }),
+        )
+        .await
+        .unwrap();
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["name"], "Configured");
+        let config = &json["config"];
+        assert_eq!(config["working_dir"], "/tmp/work");
+        assert_eq!(config["env_vars"]["LOG_LEVEL"], "debug");
+        assert_eq!(config["env_vars"]["GEMINI_API_KEY"], MASKED_VALUE);
+        assert_eq!(config["args"][0], "--verbose");
+        assert_eq!(config["args"][1], "--api-key");
+        assert_eq!(config["args"][2], MASKED_VALUE);
+    }
+
+    fn update_request(name: Option<&str>, agent_type: Option<AgentType>) -> UpdateAgentRequest {
+        UpdateAgentRequest {
+            name: name.map(str::to_string),
+            agent_type,
+            status: None,
+            reset_config: false,
+        }
+    }
+
+    /// Give an agent a custom env var and arg
+    async fn customize_agent(router_state: &RouterState, id: &AgentId) {
+        set_agent_env_var(
+            State(router_state.clone()),
+            Path(id.clone()),
+            Json(set_request("HTTP_PROXY", "http://proxy:8080")),
+        )
+        .await
+        .unwrap();
+        let mut state = router_state.0.write().await;
+        let agent = state.agents.get_mut(id).unwrap();
+        agent.config.args.push("--debug".to_string());
+    }
+
+    #[tokio::test]
+    async fn test_update_agent_name_only_keeps_config() {
+        let router_state = create_test_router_state().await;
+        let id = create_gemini_agent(&router_state).await;
+        customize_agent(&router_state, &id).await;
+
+        let response = update_agent(
+            State(router_state.clone()),
+            Path(id.clone()),
+            Json(update_request(Some("Renamed"), None)),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.name, "Renamed");
+        assert_eq!(
+            env_var(&router_state, &id, "HTTP_PROXY").await.as_deref(),
+            Some("http://proxy:8080")
+        );
+        let state = router_state.0.read().await;
+        assert!(state.agents[&id]
+            .config
+            .args
+            .contains(&"--debug".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_get_agent_status_history() {
+        let router_state = create_test_router_state().await;
+        let id = create_gemini_agent(&router_state).await;
+
+        start_agent(State(router_state.clone()), Path(id.clone()))
+            .await
+            .unwrap();
+        stop_agent(State(router_state.clone()), Path(id.clone()))
+            .await
+            .unwrap();
+
+        let Json(response) = get_agent_status_history(State(router_state.clone()), Path(id))
+            .await
+            .unwrap();
+        assert_eq!(response.current_status, AgentStatus::Stopped);
+        assert_eq!(response.count, 2);
+        assert_eq!(response.history[0].from, AgentStatus::Idle);
+        assert_eq!(response.history[0].to, AgentStatus::Running);
+        assert_eq!(response.history[1].to, AgentStatus::Stopped);
+        assert!(response.history[0].timestamp_ms <= response.history[1].timestamp_ms);
+
+        let result =
+            get_agent_status_history(State(router_state), Path("missing".to_string())).await;
+        assert!(matches!(result, Err(AppError::AgentNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_update_agent_type_merges_config_unless_reset() {
+        let router_state = create_test_router_state().await;
+        let id = create_gemini_agent(&router_state).await;
+        customize_agent(&router_state, &id).await;
+
+        update_agent(
+            State(router_state.clone()),
+            Path(id.clone()),
+            Json(update_request(None, Some(AgentType::ClaudeCode))),
+        )
+        .await
+        .unwrap();
+
+        {
+            let state = router_state.0.read().await;
+            let config = &state.agents[&id].config;
+            assert_eq!(config.command, "claude");
+            assert_eq!(config.args, vec!["--debug".to_string()]);
+        }
+        assert_eq!(
+            env_var(&router_state, &id, "HTTP_PROXY").await.as_deref(),
+            Some("http://proxy:8080")
+        );
+
+        // An explicit reset starts over from the type's defaults
+        let mut request = update_request(None, Some(AgentType::Gemini));
+        request.reset_config = true;
+        update_agent(State(router_state.clone()), Path(id.clone()), Json(request))
+            .await
+            .unwrap();
+        assert_eq!(env_var(&router_state, &id, "HTTP_PROXY").await, None);
+        let state = router_state.0.read().await;
+        assert_eq!(state.agents[&id].config.args, vec!["--yolo".to_string()]);
+    }
+
+    fn import_request(mode: ImportMode, agents: Vec<AgentDefinition>) -> ImportAgentsRequest {
+        ImportAgentsRequest { mode, agents }
+    }
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let source = router_state_with_configured_agent().await;
+        let Json(masked) =
+            export_agents(State(source.clone()), Query(ExportAgentsQuery::default()))
+                .await
+                .unwrap();
+        assert_eq!(masked.version, AGENT_EXPORT_VERSION);
+        assert_eq!(masked.count, 1);
+        let config = &masked.agents[0].config;
+        assert_eq!(config.env_vars["GEMINI_API_KEY"], MASKED_VALUE);
+        assert_eq!(config.env_vars["LOG_LEVEL"], "debug");
+
+        // A masked export can't be imported as-is
+        let target = create_test_router_state().await;
+        let Json(response) = import_agents(
+            State(target.clone()),
+            Json(import_request(ImportMode::Skip, masked.agents)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.failed, 1);
+        assert!(response.results[0]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("secrets=exclude"));
+        assert!(target.0.read().await.agents.is_empty());
+
+        let Json(exported) = export_agents(
+            State(source),
+            Query(ExportAgentsQuery {
+                secrets: ExportSecrets::Exclude,
+            }),
+        )
+        .await
+        .unwrap();
+        let definition = &exported.agents[0];
+        assert!(!definition.config.env_vars.contains_key("GEMINI_API_KEY"));
+        assert_eq!(definition.config.env_vars["LOG_LEVEL"], "debug");
+        // The secret goes together with its flag
+        assert_eq!(definition.config.args, vec!["--verbose".to_string()]);
+
+        let work_dir = TempDir::new().unwrap();
+        let mut exported = exported;
+        exported.agents[0].config.working_dir = Some(
+            work_dir
+                .path()
+                .canonicalize()
+                .unwrap()
+                .to_string_lossy()
+                .to_string(),
+        );
+
+        // Through JSON, as a client would send it back
+        let body = serde_json::to_value(&exported).unwrap();
+        let request: ImportAgentsRequest = serde_json::from_value(body).unwrap();
+        assert_eq!(request.mode, ImportMode::Skip);
+        let Json(response) = import_agents(State(target.clone()), Json(request))
+            .await
+            .unwrap();
+        assert_eq!(response.imported, 1);
+        assert_eq!(response.results[0].outcome, ImportOutcome::Imported);
+
+        let Json(reexported) =
+            export_agents(State(target.clone()), Query(ExportAgentsQuery::default()))
+                .await
+                .unwrap();
+        assert_eq!(reexported.agents, exported.agents);
+        assert_eq!(
+            target.0.read().await.agents["agent-1"].status,
+            AgentStatus::Idle
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_without_secrets_round_trips_env_vars() {
+        let source = create_test_router_state().await;
+        let id = create_gemini_agent(&source).await;
+        set_agent_env_var(
+            State(source.clone()),
+            Path(id.clone()),
+            Json(set_request("HTTP_PROXY", "http://proxy:8080")),
+        )
+        .await
+        .unwrap();
+        let mut request = set_request("GITHUB_TOKEN", "abc");
+        request.allow_secret = true;
+        set_agent_env_var(State(source.clone()), Path(id.clone()), Json(request))
+            .await
+            .unwrap();
+
+        let Json(exported) = export_agents(
+            State(source),
+            Query(ExportAgentsQuery {
+                secrets: ExportSecrets::Exclude,
+            }),
+        )
+        .await
+        .unwrap();
+
+        let target = create_test_router_state().await;
+        let Json(response) = import_agents(
+            State(target.clone()),
+            Json(import_request(ImportMode::Skip, exported.agents)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.imported, 1, "{:?}", response.results);
+        assert_eq!(
+            env_var(&target, &id, "HTTP_PROXY").await.as_deref(),
+            Some("http://proxy:8080")
+        );
+        assert_eq!(env_var(&target, &id, "GITHUB_TOKEN").await, None);
+
+        // A secret added to the definition by hand is still refused
+        let mut definition = AgentDefinition {
+            id: "agent-2".to_string(),
+            name: "Hand-edited".to_string(),
+            agent_type: AgentType::Gemini,
+            config: AgentConfig::for_type(&AgentType::Gemini),
+        };
+        definition
+            .config
+            .env_vars
+            .insert("GITHUB_TOKEN".to_string(), "abc".to_string());
+        let Json(response) = import_agents(
+            State(target),
+            Json(import_request(ImportMode::Skip, vec![definition])),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.failed, 1);
+        assert!(response.results[0]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("GITHUB_TOKEN"));
+    }
+
+    #[test]
+    fn test_secret_args_cover_flag_values() {
+        let args: Vec<String> = ["--verbose", "--api-key", "plain", "--token=abc", "-m", "x"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        assert_eq!(
+            secret_args(&args),
+            vec![false, false, true, true, false, false]
+        );
+
+        let mut config = AgentConfig::for_type(&AgentType::Gemini);
+        config.args = args;
+        assert_eq!(
+            config_without_secrets(&config).args,
+            vec!["--verbose".to_string(), "-m".to_string(), "x".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_collisions_follow_mode() {
+        let router_state = router_state_with_configured_agent().await;
+        router_state
+            .0
+            .write()
+            .await
+            .update_agent_status(&"agent-1".to_string(), AgentStatus::Running);
+
+        let mut incoming = AgentDefinition {
+            id: "agent-1".to_string(),
+            name: "Incoming".to_string(),
+            agent_type: AgentType::ClaudeCode,
+            config: AgentConfig::for_type(&AgentType::ClaudeCode),
+        };
+        let fresh = AgentDefinition {
+            id: "agent-2".to_string(),
+            ..incoming.clone()
+        };
+        let invalid = AgentDefinition {
+            id: "agent-3".to_string(),
+            config: AgentConfig::new(String::new()),
+            ..incoming.clone()
+        };
+
+        let Json(response) = import_agents(
+            State(router_state.clone()),
+            Json(import_request(
+                ImportMode::Skip,
+                vec![incoming.clone(), fresh, invalid],
+            )),
+        )
+        .await
+        .unwrap();
+        let outcomes: Vec<ImportOutcome> = response.results.iter().map(|r| r.outcome).collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                ImportOutcome::Skipped,
+                ImportOutcome::Imported,
+                ImportOutcome::Failed
+            ]
+        );
+        assert_eq!(
+            (response.imported, response.skipped, response.failed),
+            (1, 1, 1)
+        );
+        assert_eq!(
+            router_state.0.read().await.agents["agent-1"].name,
+            "Configured"
+        );
+
+        incoming
+            .config
+            .env_vars
+            .insert("bad key".to_string(), "x".to_string());
+        let Json(response) = import_agents(
+            State(router_state.clone()),
+            Json(import_request(ImportMode::Replace, vec![incoming.clone()])),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.results[0].outcome, ImportOutcome::Failed);
+
+        incoming.config.env_vars.clear();
+        let Json(response) = import_agents(
+            State(router_state.clone()),
+            Json(import_request(ImportMode::Replace, vec![incoming])),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.replaced, 1);
+        let state = router_state.0.read().await;
+        let agent = &state.agents["agent-1"];
+        assert_eq!(agent.name, "Incoming");
+        assert_eq!(agent.agent_type, AgentType::ClaudeCode);
+        assert_eq!(agent.config.command, "claude");
+        // Runtime state survives the replacement
+        assert_eq!(agent.status, AgentStatus::Running);
+        assert_eq!(state.agents.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_import_cannot_choose_command_or_escape_roots() {
+        let router_state = create_test_router_state().await;
+        let root = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        router_state.0.write().await.workspace_root =
+            Some(root.path().to_string_lossy().to_string());
+
+        let mut config = AgentConfig::for_type(&AgentType::ClaudeCode);
+        config.command = "/bin/sh".to_string();
+        config.args = vec!["-c".to_string(), "touch /tmp/pwned".to_string()];
+        let shell = AgentDefinition {
+            id: "agent-1".to_string(),
+            name: "Shell".to_string(),
+            agent_type: AgentType::ClaudeCode,
+            config,
+        };
+        let mut escaping = AgentDefinition {
+            id: "agent-2".to_string(),
+            ..shell.clone()
+        };
+        escaping.config.working_dir = Some(outside.path().to_string_lossy().to_string());
+
+        let Json(response) = import_agents(
+            State(router_state.clone()),
+            Json(import_request(ImportMode::Skip, vec![shell, escaping])),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.results[0].outcome, ImportOutcome::Imported);
+        assert_eq!(response.results[1].outcome, ImportOutcome::Failed);
+        assert!(response.results[1]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("outside the workspace root"));
+
+        let state = router_state.0.read().await;
+        assert_eq!(state.agents["agent-1"].config.command, "claude");
+        assert!(!state.agents.contains_key("agent-2"));
+    }
 }
@@ -0,0 +1,131 @@
+//! Audit log API endpoints
+//!
+//! Exposes the durable orchestration audit log stored in SQLite.
+
+use crate::api::utils::RouterState;
+use crate::chat::AuditEntry;
+use crate::error::AppError;
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+
+/// Default number of audit entries returned when no limit is given
+const DEFAULT_AUDIT_LIMIT: i64 = 100;
+
+/// Maximum number of audit entries returned in a single request
+const MAX_AUDIT_LIMIT: i64 = 1000;
+
+/// Query parameters for listing audit entries
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    /// Maximum number of entries to return (default: 100, max: 1000)
+    pub limit: Option<i64>,
+    /// Only return entries created at or after this Unix timestamp
+    pub since: Option<i64>,
+}
+
+/// Audit log response
+#[derive(Debug, Serialize)]
+pub struct AuditListResponse {
+    /// Audit entries, most recent first
+    pub entries: Vec<AuditEntry>,
+    /// Number of entries returned
+    pub count: usize,
+}
+
+/// GET /api/audit - List orchestration audit entries
+pub async fn list_audit_entries(
+    State((_, chat_db, _)): State<RouterState>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<AuditListResponse>, AppError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_AUDIT_LIMIT)
+        .clamp(1, MAX_AUDIT_LIMIT);
+
+    let entries = chat_db.get_audit_entries(limit, query.since).await?;
+    let count = entries.len();
+
+    Ok(Json(AuditListResponse { entries, count }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::{BridgeManager, ChatDb};
+    use crate::state::AppState;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tempfile::TempDir;
+    use tokio::sync::RwLock;
+
+    async fn create_test_router_state() -> (RouterState, TempDir) {
+        let app_state = Arc::new(RwLock::new(AppState::new()));
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let chat_db = ChatDb::new(db_path.to_str().unwrap())
+            .await
+            .expect("Failed to create test database");
+        let bridge_manager = Arc::new(BridgeManager::new());
+        ((app_state, Arc::new(chat_db), bridge_manager), temp_dir)
+    }
+
+    fn entry_at(created_at: i64) -> AuditEntry {
+        let mut entry = AuditEntry::new("deadbeef".to_string(), None);
+        entry.created_at = created_at;
+        entry.finish(true, None, Duration::from_millis(5));
+        entry
+    }
+
+    #[tokio::test]
+    async fn test_list_audit_entries_empty() {
+        let (router_state, _temp_dir) = create_test_router_state().await;
+        let query = AuditQuery {
+            limit: None,
+            since: None,
+        };
+
+        let response = list_audit_entries(State(router_state), Query(query))
+            .await
+            .unwrap()
+            .0;
+        assert_eq!(response.count, 0);
+        assert!(response.entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_audit_entries_limit_and_since() {
+        let (router_state, _temp_dir) = create_test_router_state().await;
+        let chat_db = router_state.1.clone();
+        for ts in [100, 200, 300] {
+            chat_db.add_audit_entry(&entry_at(ts)).await.unwrap();
+        }
+
+        let query = AuditQuery {
+            limit: Some(10),
+            since: Some(200),
+        };
+        let response = list_audit_entries(State(router_state.clone()), Query(query))
+            .await
+            .unwrap()
+            .0;
+        assert_eq!(response.count, 2);
+        assert_eq!(response.entries[0].created_at, 300);
+        assert_eq!(response.entries[1].created_at, 200);
+
+        let query = AuditQuery {
+            limit: Some(1),
+            since: None,
+        };
+        let response = list_audit_entries(State(router_state), Query(query))
+            .await
+            .unwrap()
+            .0;
+        assert_eq!(response.count, 1);
+        assert_eq!(response.entries[0].created_at, 300);
+        assert!(response.entries[0].success);
+        assert_eq!(response.entries[0].duration_ms, 5);
+    }
+}
@@ -0,0 +1,136 @@
+//! Capabilities API endpoint
+//!
+//! Describes what the running backend supports (task types, agent types,
+//! limits and optional features) in one document, so frontends can adapt
+//! without probing individual endpoints.
+
+use crate::api::orchestrator::SUPPORTED_STREAM_PROTOCOLS;
+use crate::api::utils::RouterState;
+use crate::orchestrator::plan_types::TASK_NAMES;
+use crate::state::AgentType;
+use axum::{extract::State, response::Json};
+use serde::Serialize;
+
+/// Agent type entry
+#[derive(Debug, Serialize)]
+pub struct AgentTypeInfo {
+    /// Agent type as accepted by the agents API (e.g. "Gemini")
+    pub id: AgentType,
+    /// Human-readable name
+    pub display_name: String,
+}
+
+/// Limits enforced on orchestration requests
+#[derive(Debug, Serialize)]
+pub struct CapabilityLimits {
+    /// Maximum goal length in characters
+    pub max_goal_length: usize,
+    /// Maximum prompt length in characters
+    pub max_prompt_length: usize,
+    /// Maximum number of steps in a plan
+    pub max_chain_length: usize,
+    /// Maximum number of steps run in parallel within one plan
+    pub max_parallel_tasks: usize,
+    /// Estimated token budget per orchestration (None = unlimited)
+    pub max_estimated_tokens: Option<usize>,
+    /// Estimated cost budget per orchestration (None = unlimited)
+    pub max_estimated_cost: Option<f64>,
+}
+
+/// Optional features and whether they are enabled
+#[derive(Debug, Serialize)]
+pub struct CapabilityFeatures {
+    /// `/api/orchestrate` streams planning and execution progress over SSE
+    pub streaming_orchestration: bool,
+    /// SSE event protocol versions accepted by the orchestrate endpoints
+    pub stream_protocols: Vec<u32>,
+    /// Plans are retained so executions can be replayed
+    pub plan_replay: bool,
+}
+
+/// Capabilities response
+#[derive(Debug, Serialize)]
+pub struct CapabilitiesResponse {
+    /// Crate version from Cargo.toml
+    pub version: String,
+    /// Task types plan steps may use
+    pub task_types: Vec<String>,
+    /// Supported agent types
+    pub agent_types: Vec<AgentTypeInfo>,
+    /// Agent type used when none is given
+    pub default_agent_type: AgentType,
+    /// Active orchestration limits
+    pub limits: CapabilityLimits,
+    /// Optional features
+    pub features: CapabilityFeatures,
+}
+
+/// GET /api/capabilities - Features and limits of the running backend
+pub async fn get_capabilities(
+    State((state, _, _)): State<RouterState>,
+) -> Json<CapabilitiesResponse> {
+    let state = state.read().await;
+    let config = &state.orchestrator_config;
+
+    Json(CapabilitiesResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        task_types: TASK_NAMES.iter().map(|task| task.to_string()).collect(),
+        agent_types: AgentType::available_types()
+            .into_iter()
+            .map(|agent_type| AgentTypeInfo {
+                display_name: agent_type.display_name(),
+                id: agent_type,
+            })
+            .collect(),
+        default_agent_type: state.default_agent_type().clone(),
+        limits: CapabilityLimits {
+            max_goal_length: config.max_goal_length,
+            max_prompt_length: config.max_prompt_length,
+            max_chain_length: config.max_chain_length,
+            max_parallel_tasks: config.max_parallel_tasks,
+            max_estimated_tokens: config.max_estimated_tokens,
+            max_estimated_cost: config.max_estimated_cost,
+        },
+        features: CapabilityFeatures {
+            streaming_orchestration: true,
+            stream_protocols: SUPPORTED_STREAM_PROTOCOLS.to_vec(),
+            plan_replay: config.audit_store_plan,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::{BridgeManager, ChatDb};
+    use crate::state::AppState;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+    use tokio::sync::RwLock;
+
+    #[tokio::test]
+    async fn test_capabilities_lists_tasks_and_configured_limits() {
+        let mut app_state = AppState::new();
+        app_state.orchestrator_config.max_goal_length = 1234;
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let chat_db = ChatDb::new(db_path.to_str().unwrap()).await.unwrap();
+        let router_state = (
+            Arc::new(RwLock::new(app_state)),
+            Arc::new(chat_db),
+            Arc::new(BridgeManager::new()),
+        );
+
+        let Json(response) = get_capabilities(State(router_state)).await;
+
+        assert!(response.task_types.contains(&"run_gemini".to_string()));
+        assert!(response.task_types.contains(&"create_file".to_string()));
+        // The test-only ping task is never advertised
+        assert!(!response.task_types.contains(&"ping".to_string()));
+        assert_eq!(response.limits.max_goal_length, 1234);
+        assert!(response
+            .agent_types
+            .iter()
+            .any(|info| info.id == AgentType::Gemini));
+    }
+}
@@ -3,10 +3,10 @@
 //! Handles HTTP requests for chat conversations and messages.
 
 use crate::api::utils::RouterState;
-use crate::chat::Conversation;
+use crate::chat::{Conversation, ConversationSummary};
 use crate::error::AppError;
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     response::Json,
 };
 use serde::{Deserialize, Serialize};
@@ -34,6 +34,43 @@ pub struct UpdateTitleRequest {
     pub title: String,
 }
 
+/// Request to replace conversation tags
+#[derive(Debug, Deserialize)]
+pub struct UpdateTagsRequest {
+    /// New tags (trimmed and de-duplicated; an empty list clears them)
+    pub tags: Vec<String>,
+}
+
+/// Request to pin or unpin a conversation
+#[derive(Debug, Deserialize)]
+pub struct UpdatePinnedRequest {
+    /// Whether the conversation should be pinned
+    pub pinned: bool,
+}
+
+/// Request to set or clear a conversation's system prompt
+#[derive(Debug, Deserialize)]
+pub struct UpdateSystemPromptRequest {
+    /// New system prompt (null or blank clears it)
+    pub system_prompt: Option<String>,
+}
+
+/// Query parameters for GET /api/chat/conversations
+#[derive(Debug, Default, Deserialize)]
+pub struct ListConversationsQuery {
+    /// Only return conversations carrying this tag
+    pub tag: Option<String>,
+}
+
+/// Maximum number of tags per conversation
+pub const MAX_CONVERSATION_TAGS: usize = 20;
+
+/// Maximum length of a single tag in characters
+pub const MAX_TAG_LENGTH: usize = 50;
+
+/// Maximum length of a conversation's system prompt in characters
+pub const MAX_SYSTEM_PROMPT_LENGTH: usize = 10_000;
+
 /// Conversation response
 #[derive(Debug, Serialize)]
 pub struct ConversationResponse {
@@ -45,6 +82,48 @@ pub struct ConversationResponse {
     pub created_at: i64,
     /// Unix timestamp when conversation was last updated
     pub updated_at: i64,
+    /// User-assigned tags
+    pub tags: Vec<String>,
+    /// Whether the conversation is pinned
+    pub pinned: bool,
+    /// Persona sent to the model with every turn
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// Number of messages (included in list responses)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_count: Option<i64>,
+    /// Rough token count of all messages (included in list responses)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_tokens: Option<i64>,
+}
+
+/// Characters per token used for `estimated_tokens` (rough, English-text average)
+const CHARS_PER_TOKEN: i64 = 4;
+
+impl From<Conversation> for ConversationResponse {
+    fn from(conversation: Conversation) -> Self {
+        Self {
+            id: conversation.id,
+            title: conversation.title,
+            created_at: conversation.created_at,
+            updated_at: conversation.updated_at,
+            tags: conversation.tags,
+            pinned: conversation.pinned,
+            system_prompt: conversation.system_prompt,
+            message_count: None,
+            estimated_tokens: None,
+        }
+    }
+}
+
+impl From<ConversationSummary> for ConversationResponse {
+    fn from(summary: ConversationSummary) -> Self {
+        Self {
+            message_count: Some(summary.message_count),
+            estimated_tokens: Some(summary.content_chars / CHARS_PER_TOKEN),
+            ..Self::from(summary.conversation)
+        }
+    }
 }
 
 /// Message response
@@ -60,6 +139,17 @@ pub struct MessageResponse {
     pub content: String,
     /// Unix timestamp when message was created
     pub created_at: i64,
+    /// True if the user stopped generation and `content` is the partial reply
+    pub stopped: bool,
+}
+
+/// Response to a stop request
+#[derive(Debug, Serialize)]
+pub struct StopReplyResponse {
+    /// Conversation the request was for
+    pub conversation_id: String,
+    /// Whether a reply was being streamed and has been stopped
+    pub stopped: bool,
 }
 
 /// Conversation with messages response
@@ -71,25 +161,57 @@ pub struct ConversationWithMessagesResponse {
     pub messages: Vec<MessageResponse>,
 }
 
-/// GET /api/chat/conversations - List all conversations
+/// GET /api/chat/conversations - List conversations (pinned first, optional `?tag=` filter)
 pub async fn list_conversations(
     State((_, chat_db, _)): State<RouterState>,
+    Query(query): Query<ListConversationsQuery>,
 ) -> Result<Json<Vec<ConversationResponse>>, AppError> {
-    let conversations = chat_db.get_conversations().await?;
+    let tag = query
+        .tag
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty());
+    let conversations = chat_db.get_conversations(tag).await?;
 
     let responses: Vec<ConversationResponse> = conversations
         .into_iter()
-        .map(|c| ConversationResponse {
-            id: c.id,
-            title: c.title,
-            created_at: c.created_at,
-            updated_at: c.updated_at,
-        })
+        .map(ConversationResponse::from)
         .collect();
 
     Ok(Json(responses))
 }
 
+/// Trim, validate and de-duplicate tags (first occurrence wins)
+fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>, AppError> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return Err(AppError::InvalidAgentConfig(
+                "Tags cannot be empty".to_string(),
+            ));
+        }
+        if tag.chars().count() > MAX_TAG_LENGTH {
+            return Err(AppError::InvalidAgentConfig(format!(
+                "Tag exceeds maximum length of {} characters",
+                MAX_TAG_LENGTH
+            )));
+        }
+        if !normalized.iter().any(|t| t == tag) {
+            normalized.push(tag.to_string());
+        }
+    }
+
+    if normalized.len() > MAX_CONVERSATION_TAGS {
+        return Err(AppError::InvalidAgentConfig(format!(
+            "Too many tags ({} > {})",
+            normalized.len(),
+            MAX_CONVERSATION_TAGS
+        )));
+    }
+    Ok(normalized)
+}
+
 /// Generate a title from message content
 /// Truncates to first sentence or 50 characters, whichever comes first
 pub fn generate_title_from_message(content: &str) -> String {
@@ -122,12 +244,7 @@ pub async fn create_conversation(
     let conversation = Conversation::new(id.clone(), title.clone());
     chat_db.create_conversation(&conversation).await?;
 
-    Ok(Json(ConversationResponse {
-        id: conversation.id,
-        title: conversation.title,
-        created_at: conversation.created_at,
-        updated_at: conversation.updated_at,
-    }))
+    Ok(Json(ConversationResponse::from(conversation)))
 }
 
 /// GET /api/chat/conversations/:id - Get conversation with messages
@@ -142,12 +259,7 @@ pub async fn get_conversation(
 
     let messages = chat_db.get_messages(&id).await?;
 
-    let conversation_response = ConversationResponse {
-        id: conversation.id.clone(),
-        title: conversation.title,
-        created_at: conversation.created_at,
-        updated_at: conversation.updated_at,
-    };
+    let conversation_response = ConversationResponse::from(conversation);
 
     let message_responses: Vec<MessageResponse> = messages
         .into_iter()
@@ -157,6 +269,7 @@ pub async fn get_conversation(
             role: m.role,
             content: m.content,
             created_at: m.created_at,
+            stopped: m.stopped,
         })
         .collect();
 
@@ -217,10 +330,110 @@ pub async fn update_conversation_title(
     chat_db.update_conversation(&id, &request.title).await?;
 
     Ok(Json(ConversationResponse {
-        id: conversation.id,
         title: request.title,
-        created_at: conversation.created_at,
         updated_at: chrono::Utc::now().timestamp(),
+        ..ConversationResponse::from(conversation)
+    }))
+}
+
+/// PUT /api/chat/conversations/:id/tags - Replace conversation tags
+pub async fn update_conversation_tags(
+    State((_, chat_db, _)): State<RouterState>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateTagsRequest>,
+) -> Result<Json<ConversationResponse>, AppError> {
+    let tags = normalize_tags(request.tags)?;
+
+    let conversation = chat_db
+        .get_conversation(&id)
+        .await?
+        .ok_or_else(|| AppError::FileNotFound(format!("Conversation not found: {}", id)))?;
+
+    chat_db.set_conversation_tags(&id, &tags).await?;
+
+    Ok(Json(ConversationResponse {
+        tags,
+        ..ConversationResponse::from(conversation)
+    }))
+}
+
+/// PUT /api/chat/conversations/:id/pinned - Pin or unpin a conversation
+pub async fn update_conversation_pinned(
+    State((_, chat_db, _)): State<RouterState>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdatePinnedRequest>,
+) -> Result<Json<ConversationResponse>, AppError> {
+    let conversation = chat_db
+        .get_conversation(&id)
+        .await?
+        .ok_or_else(|| AppError::FileNotFound(format!("Conversation not found: {}", id)))?;
+
+    chat_db.set_conversation_pinned(&id, request.pinned).await?;
+
+    Ok(Json(ConversationResponse {
+        pinned: request.pinned,
+        ..ConversationResponse::from(conversation)
+    }))
+}
+
+/// PUT /api/chat/conversations/:id/system-prompt - Set or clear the system prompt
+///
+/// The prompt is sent to the bridge with every following message of the
+/// conversation; null or a blank prompt removes it.
+pub async fn update_conversation_system_prompt(
+    State((_, chat_db, _)): State<RouterState>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateSystemPromptRequest>,
+) -> Result<Json<ConversationResponse>, AppError> {
+    let system_prompt = request
+        .system_prompt
+        .map(|prompt| prompt.trim().to_string())
+        .filter(|prompt| !prompt.is_empty());
+    if let Some(prompt) = &system_prompt {
+        if prompt.chars().count() > MAX_SYSTEM_PROMPT_LENGTH {
+            return Err(AppError::InvalidAgentConfig(format!(
+                "System prompt exceeds {} characters",
+                MAX_SYSTEM_PROMPT_LENGTH
+            )));
+        }
+    }
+
+    let conversation = chat_db
+        .get_conversation(&id)
+        .await?
+        .ok_or_else(|| AppError::FileNotFound(format!("Conversation not found: {}", id)))?;
+
+    chat_db
+        .set_conversation_system_prompt(&id, system_prompt.as_deref())
+        .await?;
+
+    Ok(Json(ConversationResponse {
+        system_prompt,
+        ..ConversationResponse::from(conversation)
+    }))
+}
+
+/// POST /api/chat/conversations/:id/stop - Stop the reply being streamed
+///
+/// The stream ends with `[STOPPED]` and the partial reply is saved with
+/// `stopped: true`. Stopping when no reply is in flight is a no-op.
+pub async fn stop_conversation_reply(
+    State((_, chat_db, bridge_manager)): State<RouterState>,
+    Path(id): Path<String>,
+) -> Result<Json<StopReplyResponse>, AppError> {
+    chat_db
+        .get_conversation(&id)
+        .await?
+        .ok_or_else(|| AppError::FileNotFound(format!("Conversation not found: {}", id)))?;
+
+    let stopped = bridge_manager
+        .stop_reply(&id)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to stop reply: {}", e)))?;
+
+    Ok(Json(StopReplyResponse {
+        conversation_id: id,
+        stopped,
     }))
 }
 
@@ -248,7 +461,11 @@ mod tests {
     #[tokio::test]
     async fn test_list_conversations_empty() {
         let (router_state, _temp_dir) = create_test_router_state().await;
-        let result = list_conversations(State(router_state)).await;
+        let result = list_conversations(
+            State(router_state),
+            Query(ListConversationsQuery::default()),
+        )
+        .await;
         assert!(result.is_ok());
         let conversations = result.unwrap().0;
         assert!(conversations.is_empty());
@@ -417,4 +634,239 @@ mod tests {
         .await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_update_conversation_tags() {
+        let (router_state, _temp_dir) = create_test_router_state().await;
+        let (_, chat_db, _) = &router_state;
+        let conv = Conversation::new(Uuid::new_v4().to_string(), "Tagged".to_string());
+        chat_db.create_conversation(&conv).await.unwrap();
+
+        let request = UpdateTagsRequest {
+            tags: vec![" work ".to_string(), "rust".to_string(), "work".to_string()],
+        };
+        let updated = update_conversation_tags(
+            State(router_state.clone()),
+            Path(conv.id.clone()),
+            Json(request),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert_eq!(updated.tags, vec!["work", "rust"]);
+
+        let conv_from_db = chat_db.get_conversation(&conv.id).await.unwrap().unwrap();
+        assert_eq!(conv_from_db.tags, vec!["work", "rust"]);
+
+        let request = UpdateTagsRequest {
+            tags: vec!["  ".to_string()],
+        };
+        let result =
+            update_conversation_tags(State(router_state), Path(conv.id), Json(request)).await;
+        assert!(matches!(result, Err(AppError::InvalidAgentConfig(_))));
+    }
+
+    #[tokio::test]
+    async fn test_list_conversations_includes_message_stats() {
+        let (router_state, _temp_dir) = create_test_router_state().await;
+        let (_, chat_db, _) = &router_state;
+
+        let busy = Conversation::new("busy".to_string(), "Busy".to_string());
+        let mut empty = Conversation::new("empty".to_string(), "Empty".to_string());
+        empty.updated_at -= 100;
+        chat_db.create_conversation(&busy).await.unwrap();
+        chat_db.create_conversation(&empty).await.unwrap();
+        for content in ["Hello", "Hi there!", "How are you?"] {
+            let message = Message::new(
+                Uuid::new_v4().to_string(),
+                busy.id.clone(),
+                MessageRole::User,
+                content.to_string(),
+            );
+            chat_db.add_message(&message).await.unwrap();
+        }
+
+        let listed = list_conversations(
+            State(router_state),
+            Query(ListConversationsQuery::default()),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].id, "busy");
+        assert_eq!(listed[0].message_count, Some(3));
+        // 26 characters at ~4 characters per token
+        assert_eq!(listed[0].estimated_tokens, Some(6));
+        assert_eq!(listed[1].id, "empty");
+        assert_eq!(listed[1].message_count, Some(0));
+        assert_eq!(listed[1].estimated_tokens, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_update_conversation_system_prompt_sets_and_clears() {
+        let (router_state, _temp_dir) = create_test_router_state().await;
+        let (_, chat_db, _) = &router_state;
+        let conversation = Conversation::new("conv-1".to_string(), "Persona".to_string());
+        chat_db.create_conversation(&conversation).await.unwrap();
+
+        let updated = update_conversation_system_prompt(
+            State(router_state.clone()),
+            Path("conv-1".to_string()),
+            Json(UpdateSystemPromptRequest {
+                system_prompt: Some("  You are a pirate \n".to_string()),
+            }),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert_eq!(updated.system_prompt.as_deref(), Some("You are a pirate"));
+        let stored = chat_db.get_conversation("conv-1").await.unwrap().unwrap();
+        assert_eq!(stored.system_prompt.as_deref(), Some("You are a pirate"));
+
+        // A blank prompt clears it
+        let cleared = update_conversation_system_prompt(
+            State(router_state.clone()),
+            Path("conv-1".to_string()),
+            Json(UpdateSystemPromptRequest {
+                system_prompt: Some("   ".to_string()),
+            }),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert!(cleared.system_prompt.is_none());
+        let stored = chat_db.get_conversation("conv-1").await.unwrap().unwrap();
+        assert!(stored.system_prompt.is_none());
+
+        let too_long = update_conversation_system_prompt(
+            State(router_state.clone()),
+            Path("conv-1".to_string()),
+            Json(UpdateSystemPromptRequest {
+                system_prompt: Some("x".repeat(MAX_SYSTEM_PROMPT_LENGTH + 1)),
+            }),
+        )
+        .await;
+        assert!(matches!(too_long, Err(AppError::InvalidAgentConfig(_))));
+
+        let missing = update_conversation_system_prompt(
+            State(router_state),
+            Path("missing".to_string()),
+            Json(UpdateSystemPromptRequest {
+                system_prompt: None,
+            }),
+        )
+        .await;
+        assert!(matches!(missing, Err(AppError::FileNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_list_conversations_pinned_first() {
+        let (router_state, _temp_dir) = create_test_router_state().await;
+        let (_, chat_db, _) = &router_state;
+
+        let mut older = Conversation::new("older".to_string(), "Older".to_string());
+        older.updated_at -= 100;
+        let newer = Conversation::new("newer".to_string(), "Newer".to_string());
+        chat_db.create_conversation(&older).await.unwrap();
+        chat_db.create_conversation(&newer).await.unwrap();
+
+        let pinned = update_conversation_pinned(
+            State(router_state.clone()),
+            Path("older".to_string()),
+            Json(UpdatePinnedRequest { pinned: true }),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert!(pinned.pinned);
+
+        let listed = list_conversations(
+            State(router_state),
+            Query(ListConversationsQuery::default()),
+        )
+        .await
+        .unwrap()
+        .0;
+        let ids: Vec<&str> = listed.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["older", "newer"]);
+        assert!(listed[0].pinned);
+    }
+
+    #[tokio::test]
+    async fn test_list_conversations_tag_filter() {
+        let (router_state, _temp_dir) = create_test_router_state().await;
+        let (_, chat_db, _) = &router_state;
+
+        for (id, tags) in [
+            ("a", vec!["work"]),
+            ("b", vec!["home", "work"]),
+            ("c", vec![]),
+        ] {
+            let conv = Conversation::new(id.to_string(), id.to_string());
+            chat_db.create_conversation(&conv).await.unwrap();
+            let tags: Vec<String> = tags.into_iter().map(str::to_string).collect();
+            chat_db.set_conversation_tags(id, &tags).await.unwrap();
+        }
+
+        let list = |tag: &str| {
+            list_conversations(
+                State(router_state.clone()),
+                Query(ListConversationsQuery {
+                    tag: Some(tag.to_string()),
+                }),
+            )
+        };
+
+        let mut work: Vec<String> = list("work")
+            .await
+            .unwrap()
+            .0
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        work.sort();
+        assert_eq!(work, vec!["a", "b"]);
+
+        let home: Vec<String> = list("home")
+            .await
+            .unwrap()
+            .0
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(home, vec!["b"]);
+
+        // Tags match exactly, not as substrings
+        assert!(list("wor").await.unwrap().0.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stop_conversation_reply_without_reply_in_flight() {
+        let (router_state, _temp_dir) = create_test_router_state().await;
+        let result =
+            stop_conversation_reply(State(router_state.clone()), Path("missing".to_string())).await;
+        assert!(matches!(result, Err(AppError::FileNotFound(_))));
+
+        let (_, chat_db, _) = &router_state;
+        chat_db
+            .create_conversation(&Conversation::new("c".to_string(), "Chat".to_string()))
+            .await
+            .unwrap();
+        let response = stop_conversation_reply(State(router_state), Path("c".to_string()))
+            .await
+            .unwrap()
+            .0;
+        assert!(!response.stopped);
+    }
+
+    #[tokio::test]
+    async fn test_migrations_are_rerunnable() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let path = db_path.to_str().unwrap();
+        drop(ChatDb::new(path).await.unwrap());
+        // Re-opening re-runs the ADD COLUMN migration without failing
+        assert!(ChatDb::new(path).await.is_ok());
+    }
 }
@@ -6,8 +6,9 @@
 use crate::api::utils::RouterState;
 use crate::error::AppError;
 use crate::services::files::FileService;
+use crate::services::working_dir::WorkingDir;
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     response::Json,
 };
 use serde::{Deserialize, Serialize};
@@ -30,6 +31,37 @@ pub struct ListFilesResponse {
 pub struct SetWorkingDirectoryRequest {
     /// Path to set as working directory (None to clear)
     pub path: Option<String>,
+    /// Name of a saved profile to activate instead of giving a path
+    #[serde(default)]
+    pub profile: Option<String>,
+}
+
+/// Maximum length of a profile name in characters
+pub const MAX_PROFILE_NAME_LENGTH: usize = 64;
+
+/// Request to save a working directory profile
+#[derive(Deserialize)]
+pub struct CreateProfileRequest {
+    /// Profile name (replaces an existing profile with the same name)
+    pub name: String,
+    /// Directory the profile points to
+    pub path: String,
+}
+
+/// A saved working directory profile
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct ProfileResponse {
+    /// Profile name
+    pub name: String,
+    /// Canonical directory path
+    pub path: String,
+}
+
+/// Response for listing profiles
+#[derive(Debug, Serialize)]
+pub struct ListProfilesResponse {
+    /// Saved profiles, sorted by name
+    pub profiles: Vec<ProfileResponse>,
 }
 
 /// Response for working directory
@@ -70,29 +102,105 @@ pub async fn get_working_directory(
     State((state, _, _)): State<RouterState>,
 ) -> Result<Json<WorkingDirectoryResponse>, AppError> {
     let state = state.read().await;
-    let path = state.working_directory().cloned();
+    let path = state.working_directory().map(WorkingDir::to_string);
     Ok(Json(WorkingDirectoryResponse { path }))
 }
 
 /// POST /api/files/working-directory - Set working directory context
+///
+/// Takes either a `path` or the name of a saved `profile`.
 pub async fn set_working_directory(
-    State((state, _, _)): State<RouterState>,
+    State((state, chat_db, bridge_manager)): State<RouterState>,
     Json(request): Json<SetWorkingDirectoryRequest>,
 ) -> Result<Json<WorkingDirectoryResponse>, AppError> {
-    // Validate and canonicalize path if provided using service layer
-    let canonical_path = if let Some(ref path_str) = request.path {
-        let canonical = FileService::validate_directory_path(path_str)?;
-        Some(canonical.to_string_lossy().to_string())
-    } else {
-        None
+    if let Some(name) = request.profile {
+        if request.path.is_some() {
+            return Err(AppError::InvalidPath(
+                "Specify either a path or a profile, not both".to_string(),
+            ));
+        }
+        return activate_profile(State((state, chat_db, bridge_manager)), Path(name)).await;
+    }
+
+    let mut state = state.write().await;
+
+    // Validate and canonicalize path if provided
+    let dir = match request.path {
+        Some(ref path_str) => Some(WorkingDir::new(path_str, &state.working_dir_roots())?),
+        None => None,
     };
+    let path = dir.as_ref().map(WorkingDir::to_string);
+    state.set_working_directory(dir);
+
+    Ok(Json(WorkingDirectoryResponse { path }))
+}
+
+/// GET /api/files/profiles - List saved working directory profiles
+pub async fn list_profiles(
+    State((state, _, _)): State<RouterState>,
+) -> Result<Json<ListProfilesResponse>, AppError> {
+    let state = state.read().await;
+    let mut profiles: Vec<ProfileResponse> = state
+        .profiles
+        .iter()
+        .map(|(name, path)| ProfileResponse {
+            name: name.clone(),
+            path: path.clone(),
+        })
+        .collect();
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(Json(ListProfilesResponse { profiles }))
+}
+
+/// POST /api/files/profiles - Save a named working directory profile
+///
+/// The directory must exist; it is stored canonicalized and saved with the
+/// agent registry.
+pub async fn create_profile(
+    State((state, _, _)): State<RouterState>,
+    Json(request): Json<CreateProfileRequest>,
+) -> Result<Json<ProfileResponse>, AppError> {
+    let name = request.name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::InvalidPath(
+            "Profile name cannot be empty".to_string(),
+        ));
+    }
+    if name.chars().count() > MAX_PROFILE_NAME_LENGTH {
+        return Err(AppError::InvalidPath(format!(
+            "Profile name is longer than {} characters",
+            MAX_PROFILE_NAME_LENGTH
+        )));
+    }
+
+    let canonical = FileService::validate_directory_path(&request.path)?;
+    let path = canonical.to_string_lossy().to_string();
 
     let mut state = state.write().await;
-    state.set_working_directory(canonical_path.clone());
+    state.set_profile(name.clone(), path.clone());
+    state.save_registry()?;
 
-    Ok(Json(WorkingDirectoryResponse {
-        path: canonical_path,
-    }))
+    Ok(Json(ProfileResponse { name, path }))
+}
+
+/// POST /api/files/profiles/:name/activate - Set the working directory from a profile
+pub async fn activate_profile(
+    State((state, _, _)): State<RouterState>,
+    Path(name): Path<String>,
+) -> Result<Json<WorkingDirectoryResponse>, AppError> {
+    let mut state = state.write().await;
+    let path = state
+        .profiles
+        .get(&name)
+        .cloned()
+        .ok_or_else(|| AppError::FileNotFound(format!("Profile not found: {}", name)))?;
+
+    // The directory may have been removed since the profile was saved
+    let dir = WorkingDir::new(&path, &state.working_dir_roots())?;
+    let path = dir.to_string();
+    state.set_working_directory(Some(dir));
+
+    Ok(Json(WorkingDirectoryResponse { path: Some(path) }))
 }
 
 #[cfg(test)]
@@ -192,6 +300,7 @@ mod tests {
         let router_state = create_test_router_state().await;
         let request = SetWorkingDirectoryRequest {
             path: Some(temp_path.clone()),
+            profile: None,
         };
 
         // Set working directory
@@ -214,6 +323,7 @@ mod tests {
         let router_state = create_test_router_state().await;
         let request = SetWorkingDirectoryRequest {
             path: Some("/nonexistent/path/12345".to_string()),
+            profile: None,
         };
 
         let result = set_working_directory(State(router_state.clone()), Json(request)).await;
@@ -228,6 +338,60 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_set_working_directory_under_allowed_root() {
+        let root = tempdir().expect("Failed to create temp dir");
+        let inside = root.path().join("project");
+        std::fs::create_dir(&inside).expect("Failed to create subdir");
+
+        let router_state = create_test_router_state().await;
+        router_state.0.write().await.allowed_working_dirs =
+            vec![root.path().to_str().unwrap().to_string()];
+        let request = SetWorkingDirectoryRequest {
+            path: Some(inside.to_str().unwrap().to_string()),
+            profile: None,
+        };
+
+        let response = set_working_directory(State(router_state.clone()), Json(request))
+            .await
+            .expect("Path under an allowed root should be accepted");
+        let expected = inside.canonicalize().unwrap();
+        assert_eq!(response.path.as_deref(), expected.to_str());
+    }
+
+    #[tokio::test]
+    async fn test_set_working_directory_outside_allowed_roots() {
+        let root = tempdir().expect("Failed to create temp dir");
+        let outside = tempdir().expect("Failed to create temp dir");
+
+        let router_state = create_test_router_state().await;
+        router_state.0.write().await.allowed_working_dirs =
+            vec![root.path().to_str().unwrap().to_string()];
+        let request = SetWorkingDirectoryRequest {
+            path: Some(outside.path().to_str().unwrap().to_string()),
+            profile: None,
+        };
+
+        let result = set_working_directory(State(router_state.clone()), Json(request)).await;
+        match result.unwrap_err() {
+            AppError::PermissionDenied(msg) => assert!(msg.contains("outside the allowed")),
+            other => panic!("Expected PermissionDenied error, got: {:?}", other),
+        }
+        assert!(router_state.0.read().await.working_directory().is_none());
+
+        // The workspace root applies to the global working directory too
+        let mut state = router_state.0.write().await;
+        state.allowed_working_dirs.clear();
+        state.workspace_root = Some(root.path().to_str().unwrap().to_string());
+        drop(state);
+        let request = SetWorkingDirectoryRequest {
+            path: Some(outside.path().to_str().unwrap().to_string()),
+            profile: None,
+        };
+        let result = set_working_directory(State(router_state.clone()), Json(request)).await;
+        assert!(matches!(result, Err(AppError::PermissionDenied(_))));
+    }
+
     #[tokio::test]
     async fn test_set_working_directory_file_not_dir() {
         let temp_dir = tempdir().expect("Failed to create temp dir");
@@ -237,6 +401,7 @@ mod tests {
         let router_state = create_test_router_state().await;
         let request = SetWorkingDirectoryRequest {
             path: Some(file_path.to_str().unwrap().to_string()),
+            profile: None,
         };
 
         let result = set_working_directory(State(router_state.clone()), Json(request)).await;
@@ -261,11 +426,15 @@ mod tests {
         // Set working directory first
         let request = SetWorkingDirectoryRequest {
             path: Some(temp_path),
+            profile: None,
         };
         let _ = set_working_directory(State(router_state.clone()), Json(request)).await;
 
         // Clear working directory
-        let request = SetWorkingDirectoryRequest { path: None };
+        let request = SetWorkingDirectoryRequest {
+            path: None,
+            profile: None,
+        };
         let result = set_working_directory(State(router_state.clone()), Json(request)).await;
         assert!(result.is_ok());
         let response = result.unwrap();
@@ -277,4 +446,72 @@ mod tests {
         let response = result.unwrap();
         assert!(response.path.is_none());
     }
+
+    #[tokio::test]
+    async fn test_create_list_and_activate_profiles() {
+        let work = tempdir().expect("Failed to create temp dir");
+        let home = tempdir().expect("Failed to create temp dir");
+        let registry = tempdir().expect("Failed to create temp dir");
+        let registry_path = registry.path().join("agents.json");
+
+        let router_state = create_test_router_state().await;
+        router_state.0.write().await.registry_path = Some(registry_path.clone());
+
+        for (name, dir) in [("work", &work), ("home", &home)] {
+            let request = CreateProfileRequest {
+                name: name.to_string(),
+                path: dir.path().to_str().unwrap().to_string(),
+            };
+            create_profile(State(router_state.clone()), Json(request))
+                .await
+                .unwrap();
+        }
+
+        let listed = list_profiles(State(router_state.clone())).await.unwrap().0;
+        let names: Vec<&str> = listed.profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["home", "work"]);
+
+        // Activating a profile updates the working directory
+        let canonical_work = work.path().canonicalize().unwrap();
+        let response = activate_profile(State(router_state.clone()), Path("work".to_string()))
+            .await
+            .unwrap()
+            .0;
+        assert_eq!(
+            response.path.as_deref(),
+            Some(canonical_work.to_str().unwrap())
+        );
+        let current = get_working_directory(State(router_state.clone()))
+            .await
+            .unwrap()
+            .0;
+        assert_eq!(current.path, response.path);
+
+        // set_working_directory accepts a profile name too
+        let request = SetWorkingDirectoryRequest {
+            path: None,
+            profile: Some("home".to_string()),
+        };
+        let response = set_working_directory(State(router_state.clone()), Json(request))
+            .await
+            .unwrap()
+            .0;
+        let canonical_home = home.path().canonicalize().unwrap();
+        assert_eq!(
+            response.path.as_deref(),
+            Some(canonical_home.to_str().unwrap())
+        );
+
+        // Profiles are saved with the registry
+        let mut reloaded = AppState::new();
+        reloaded.load_agents(&registry_path).unwrap();
+        assert_eq!(reloaded.profiles.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_activate_unknown_profile() {
+        let router_state = create_test_router_state().await;
+        let result = activate_profile(State(router_state), Path("missing".to_string())).await;
+        assert!(matches!(result, Err(AppError::FileNotFound(_))));
+    }
 }
@@ -0,0 +1,27 @@
+//! Metrics API endpoint
+//!
+//! Live gauges for observability and backpressure decisions. Values are
+//! sampled when the request is served.
+
+use crate::api::utils::RouterState;
+use crate::orchestrator::api_client::gemini_api_in_flight;
+use axum::{extract::State, response::Json};
+use serde::Serialize;
+
+/// Current values of the server's gauges
+#[derive(Debug, Serialize)]
+pub struct MetricsResponse {
+    /// Gemini API requests awaiting a response
+    pub gemini_api_in_flight: usize,
+    /// Orchestrations holding an execution slot
+    pub orchestrations_in_flight: usize,
+}
+
+/// GET /api/metrics - Live gauges
+pub async fn get_metrics(State((state, _, _)): State<RouterState>) -> Json<MetricsResponse> {
+    let orchestrations_in_flight = state.read().await.execution_limiter.in_flight();
+    Json(MetricsResponse {
+        gemini_api_in_flight: gemini_api_in_flight(),
+        orchestrations_in_flight,
+    })
+}
@@ -2,9 +2,13 @@
 //!
 //! Contains HTTP request handlers for agent management endpoints
 
+pub mod agent_logs;
 pub mod agents;
+pub mod audit;
+pub mod capabilities;
 pub mod chat;
 pub mod files;
+pub mod metrics;
 pub mod orchestrator;
 pub mod orchestrator_graph;
 pub mod queries;
@@ -12,6 +16,7 @@ pub mod simple_chat;
 pub mod simple_chat_multipart;
 pub mod streaming;
 pub mod utils;
+pub mod version;
 
 // Re-export file API for convenience (used by main.rs)
 pub use files::*;
@@ -7,30 +7,47 @@
 //! The orchestration uses SSE (Server-Sent Events) to stream status updates
 //! to the frontend, allowing real-time feedback on multi-step operations.
 
+use crate::api::streaming::{cap_stream_duration, sse_retry_frame, stream_duration_exceeded};
 use crate::api::utils::RouterState;
+use crate::chat::{AuditEntry, ChatDb};
 use crate::error::AppError;
+use crate::orchestrator::cancellation;
 use crate::orchestrator::config::{
-    validate_and_apply_config_update, ConfigUpdateRequest, OrchestratorConfig,
+    config_schema, validate_and_apply_config_update, ConfigUpdateRequest, OrchestratorConfig,
 };
 use crate::orchestrator::constants::SSE_DONE_SIGNAL;
-use crate::orchestrator::graph_executor::execute_plan;
+use crate::orchestrator::graph_executor::{
+    check_write_target, execute_plan_retaining_outputs, ExecutionSummary, RunFrom, StepOutputs,
+    StepResult,
+};
+use crate::orchestrator::graph_validation::validate_graph;
+use crate::orchestrator::plan_explain::{self, StepExplanation};
 use crate::orchestrator::plan_optimizer::{
-    analyze_bottlenecks, estimate_execution_time, estimate_token_usage, BottleneckAnalysis,
+    analyze_bottlenecks, check_budget, estimate_cost, estimate_execution_time,
+    estimate_token_usage, validate_chain_length, BottleneckAnalysis,
 };
+use crate::orchestrator::plan_types::{FieldError, Plan};
 use crate::orchestrator::primitives::{
     internal_create_file, internal_run_gemini, internal_run_planner,
+    internal_run_planner_cancellable,
 };
+use crate::orchestrator::step_progress::StepProgress;
+use crate::orchestrator::token_usage::{TokenUsage, UsageByModel};
+use crate::state::AppState;
 #[allow(unused_imports)] // Used in map_err on lines 179 and 289
 use anyhow::anyhow;
 use axum::{
     body::Body,
-    extract::State,
+    extract::{Path, Query, State},
     http::{header, StatusCode},
     response::Response,
     Json,
 };
 use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, RwLock};
 
 /// Helper function to serialize an OrchestrationEvent to JSON string
 ///
@@ -44,29 +61,104 @@ use serde::{Deserialize, Serialize};
 /// * `String` - JSON string representation of the event (or fallback on error)
 fn serialize_event_or_fallback(event: &OrchestrationEvent) -> String {
     serde_json::to_string(event).unwrap_or_else(|e| {
-        tracing::error!("Failed to serialize OrchestrationEvent: {} - Event: {:?}", e, event);
-        // Return a minimal error event as fallback
-        format!(
-            r#"{{"type": "serialization_error", "message": "Event serialization failed: {}", "status": "error"}}"#,
-            e
-        )
+        tracing::error!(
+            "Failed to serialize OrchestrationEvent: {} - Event: {:?}",
+            e,
+            event
+        );
+        // Return a minimal execution_error event as fallback so clients only
+        // ever see the OrchestrationEvent schema
+        serde_json::json!({
+            "type": "execution_error",
+            "error": format!("Event serialization failed: {}", e),
+        })
+        .to_string()
     })
 }
 
 /// Helper function to format a stream into SSE (Server-Sent Events) format
 ///
 /// Takes a stream of `Result<String, axum::Error>` and converts it to SSE format
-/// where each item is formatted as "data: <content>\n\n"
+/// where each item is formatted as "data: <content>\n\n". Stream errors are
+/// reported as `OrchestrationEvent::ExecutionError` events. The stream opens
+/// with a `retry: <sse_retry_ms>` directive unless `sse_retry_ms` is 0. A
+/// stream still open after `max_duration_secs` (0 = no cap) ends with an
+/// `ExecutionError` event and `[DONE]`.
 fn format_sse_stream(
     stream: impl futures_util::Stream<Item = Result<String, axum::Error>> + Send + 'static,
+    sse_retry_ms: u64,
+    max_duration_secs: u64,
 ) -> impl futures_util::Stream<Item = Result<String, std::io::Error>> {
-    stream.map(|event_result| {
+    let timeout_event = OrchestrationEvent::ExecutionError {
+        error: stream_duration_exceeded(max_duration_secs),
+        block_reason: None,
+    };
+    let stream = cap_stream_duration(
+        stream,
+        max_duration_secs,
+        vec![
+            serialize_event_or_fallback(&timeout_event),
+            SSE_DONE_SIGNAL.to_string(),
+        ],
+    );
+    let retry = futures_util::stream::iter(sse_retry_frame(sse_retry_ms).map(Ok));
+    retry.chain(stream.map(|event_result| {
         let sse_text = match event_result {
             Ok(data) => format!("data: {}\n\n", data),
-            Err(e) => format!("data: [ERROR] {}\n\n", e),
+            Err(e) => {
+                let error_event = OrchestrationEvent::ExecutionError {
+                    error: e.to_string(),
+                    block_reason: None,
+                };
+                format!("data: {}\n\n", serialize_event_or_fallback(&error_event))
+            }
         };
         Ok::<_, std::io::Error>(sse_text)
-    })
+    }))
+}
+
+/// SSE event schema version emitted when the client doesn't ask for one
+pub const DEFAULT_STREAM_PROTOCOL: u32 = 1;
+
+/// SSE event schema versions the server can emit
+///
+/// Version 1 is the `OrchestrationEvent` schema. An incompatible schema change
+/// adds a new version here; older versions keep their serialization so
+/// existing clients don't break.
+pub const SUPPORTED_STREAM_PROTOCOLS: &[u32] = &[1];
+
+/// Query parameters for the orchestration SSE endpoints
+#[derive(Debug, Default, Deserialize)]
+pub struct StreamProtocolQuery {
+    /// Requested event schema version (default: `DEFAULT_STREAM_PROTOCOL`)
+    pub protocol: Option<u32>,
+}
+
+impl StreamProtocolQuery {
+    /// Pick the schema version for this stream
+    ///
+    /// # Returns
+    /// * `Ok(u32)` - The requested version, or the default if none was given
+    /// * `Err(AppError::UnsupportedProtocol)` - If the version isn't supported
+    pub fn negotiate(&self) -> Result<u32, AppError> {
+        let version = self.protocol.unwrap_or(DEFAULT_STREAM_PROTOCOL);
+        if SUPPORTED_STREAM_PROTOCOLS.contains(&version) {
+            Ok(version)
+        } else {
+            Err(AppError::UnsupportedProtocol(format!(
+                "protocol={} is not supported; supported versions: {:?}",
+                version, SUPPORTED_STREAM_PROTOCOLS
+            )))
+        }
+    }
+}
+
+/// Query parameters for POST /api/orchestrate
+#[derive(Debug, Default, Deserialize)]
+pub struct OrchestrateQuery {
+    /// Stop after emitting the plan (`plan_generated`, `plan_details`) without executing it
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 /// Orchestration request
@@ -74,6 +166,226 @@ fn format_sse_stream(
 pub struct OrchestrationRequest {
     /// The goal or prompt for the orchestration
     pub goal: String,
+    /// Run the plan even if it exceeds `max_estimated_tokens`/`max_estimated_cost`
+    #[serde(default)]
+    pub allow_over_budget: bool,
+    /// Plan execution timeout for this run, in place of `plan_timeout_secs`
+    /// (at most `max_plan_timeout_secs`)
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+impl OrchestrationRequest {
+    /// The config to run this request with, applying its `timeout_secs`
+    ///
+    /// # Returns
+    /// * `Ok(OrchestratorConfig)` - `config`, with the timeout overridden if requested
+    /// * `Err(AppError)` - If `timeout_secs` is 0 or above `max_plan_timeout_secs`
+    fn apply_timeout(
+        &self,
+        mut config: OrchestratorConfig,
+    ) -> Result<OrchestratorConfig, AppError> {
+        if let Some(timeout) = self.timeout_secs {
+            if timeout == 0 || timeout > config.max_plan_timeout_secs {
+                return Err(AppError::InvalidRequest(format!(
+                    "timeout_secs must be between 1 and {} (max_plan_timeout_secs), got {}",
+                    config.max_plan_timeout_secs, timeout
+                )));
+            }
+            config.plan_timeout_secs = timeout;
+        }
+        Ok(config)
+    }
+}
+
+/// Enforce the configured budget on a plan unless the request overrides it
+fn check_request_budget(
+    plan: &crate::orchestrator::plan_types::Plan,
+    config: &OrchestratorConfig,
+    allow_over_budget: bool,
+) -> Result<(), AppError> {
+    if allow_over_budget {
+        return Ok(());
+    }
+    check_budget(plan, config)
+}
+
+/// Build a `step_complete` event, truncating output longer than `max_output_chars`
+///
+/// Only the event is truncated; `result` keeps the full output.
+fn step_complete_event(result: &StepResult, max_output_chars: usize) -> OrchestrationEvent {
+    let full = result.output.as_deref().unwrap_or_default();
+    let (output, truncated) = match full.char_indices().nth(max_output_chars) {
+        Some((byte_idx, _)) => (full[..byte_idx].to_string(), true),
+        None => (full.to_string(), false),
+    };
+
+    OrchestrationEvent::StepComplete {
+        step_id: result.step_id.clone(),
+        step_number: result.step_number,
+        task: result.task.clone(),
+        output,
+        truncated,
+        model: result.model.clone(),
+    }
+}
+
+/// Starts each line of streamed step output with the step it came from
+///
+/// Chunks can end mid-line, so whether a step's next chunk starts a new line
+/// is tracked per step.
+#[derive(Debug, Default)]
+struct StepLinePrefixer {
+    /// Steps whose last chunk ended part-way through a line
+    mid_line: HashSet<String>,
+}
+
+impl StepLinePrefixer {
+    /// `chunk` with `[step_id] ` at the start of each of its lines
+    fn prefix(&mut self, step_id: &str, chunk: &str) -> String {
+        let mut prefixed = String::with_capacity(chunk.len());
+        let mut line_start = !self.mid_line.contains(step_id);
+        for line in chunk.split_inclusive('\n') {
+            if line_start {
+                prefixed.push_str(&format!("[{}] ", step_id));
+            }
+            prefixed.push_str(line);
+            line_start = line.ends_with('\n');
+        }
+        if line_start {
+            self.mid_line.remove(step_id);
+        } else {
+            self.mid_line.insert(step_id.to_string());
+        }
+        prefixed
+    }
+}
+
+/// Build a `step_progress` event, prefixing its lines when a prefixer is given
+///
+/// A reset becomes a `step_progress_reset` event.
+fn step_progress_event(
+    progress: StepProgress,
+    prefixer: Option<&mut StepLinePrefixer>,
+) -> OrchestrationEvent {
+    if progress.reset {
+        if let Some(prefixer) = prefixer {
+            prefixer.mid_line.remove(&progress.step_id);
+        }
+        return OrchestrationEvent::StepProgressReset {
+            step_id: progress.step_id,
+        };
+    }
+    let chunk = match prefixer {
+        Some(prefixer) => prefixer.prefix(&progress.step_id, &progress.chunk),
+        None => progress.chunk,
+    };
+    OrchestrationEvent::StepProgress {
+        step_id: progress.step_id,
+        chunk,
+    }
+}
+
+/// Build the terminal `execution_summary` event from a run's summary
+fn execution_summary_event(summary: &ExecutionSummary) -> OrchestrationEvent {
+    let failed: Vec<FailedStep> = summary
+        .results
+        .iter()
+        .filter(|r| !r.success)
+        .map(|r| FailedStep {
+            step_id: r.step_id.clone(),
+            step_number: r.step_number,
+            error: r
+                .error
+                .clone()
+                .unwrap_or_else(|| "Unknown error".to_string()),
+        })
+        .collect();
+
+    OrchestrationEvent::ExecutionSummary {
+        total_steps: summary.total_steps,
+        successful_steps: summary.successful,
+        failed,
+        usage: summary.token_usage,
+        usage_by_model: summary.usage_by_model.clone(),
+    }
+}
+
+/// Maximum characters of a prompt shown in a step's params summary
+const PARAMS_SUMMARY_PROMPT_CHARS: usize = 80;
+
+/// A step as described in the `plan_details` event
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StepSummary {
+    /// Unique identifier for the step
+    pub id: String,
+    /// Task type (e.g., "run_gemini", "create_file")
+    pub task: String,
+    /// IDs of the steps this step waits for
+    pub dependencies: Vec<String>,
+    /// Short human-readable description of the step's parameters
+    pub params_summary: String,
+}
+
+/// Build the `plan_details` event listing every step of the plan
+fn plan_details_event(plan: &crate::orchestrator::plan_types::Plan) -> OrchestrationEvent {
+    OrchestrationEvent::PlanDetails {
+        steps: plan
+            .steps
+            .iter()
+            .map(|step| StepSummary {
+                id: step.id.clone(),
+                task: step.task.clone(),
+                dependencies: step.dependencies.clone(),
+                params_summary: summarize_params(&step.params),
+            })
+            .collect(),
+    }
+}
+
+/// Describe a step's key parameters in one line (prompts are shortened)
+fn summarize_params(params: &crate::orchestrator::plan_types::StepParams) -> String {
+    let mut parts = Vec::new();
+    if let Some(ref prompt) = params.prompt {
+        let mut shown: String = prompt.chars().take(PARAMS_SUMMARY_PROMPT_CHARS).collect();
+        if prompt.chars().count() > PARAMS_SUMMARY_PROMPT_CHARS {
+            shown.push('…');
+        }
+        parts.push(format!("prompt: {}", shown));
+    }
+    if let Some(ref filename) = params.filename {
+        parts.push(format!("filename: {}", filename));
+    }
+    if let Some(ref filename_from) = params.filename_from {
+        parts.push(format!("filename from {}", filename_from));
+    }
+    if let Some(ref content_from) = params.content_from {
+        parts.push(format!("content from {}", content_from));
+    }
+    if let Some(ref items) = params.items {
+        parts.push(format!("{} items", items.len()));
+    }
+    if let Some(ref items_from) = params.items_from {
+        parts.push(format!("items from {}", items_from));
+    }
+    if let Some(ref template) = params.template {
+        parts.push(format!("template: {}", template.task));
+    }
+    if let Some(delay_ms) = params.delay_ms {
+        parts.push(format!("delay: {}ms", delay_ms));
+    }
+    parts.join(", ")
+}
+
+/// A failed step listed in the `execution_summary` event
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FailedStep {
+    /// Unique identifier for the step
+    pub step_id: String,
+    /// Sequential step number (1-indexed)
+    pub step_number: u32,
+    /// Error message describing the failure
+    pub error: String,
 }
 
 /// Orchestration status update
@@ -90,9 +402,20 @@ pub struct OrchestrationStatus {
 }
 
 /// Phase 6.3: Structured orchestration events for live graph updates
-#[derive(Debug, Serialize, Clone)]
+///
+/// All SSE error reporting uses the `StepError` and `ExecutionError` variants,
+/// so clients only need to handle this one schema (plus the `[DONE]` sentinel).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum OrchestrationEvent {
+    /// First event of every stream, naming the negotiated schema version
+    StreamStart {
+        /// Event schema version used for the rest of the stream
+        protocol_version: u32,
+        /// ID of this execution, usable with the replay and run-from endpoints (not poem runs)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        execution_id: Option<String>,
+    },
     /// Plan generated with analysis
     PlanGenerated {
         /// Number of steps in the plan
@@ -102,6 +425,16 @@ pub enum OrchestrationEvent {
         /// Estimated execution time in seconds
         estimated_time_secs: usize,
     },
+    /// Full plan structure, sent once after `PlanGenerated`
+    PlanDetails {
+        /// Every step in plan order
+        steps: Vec<StepSummary>,
+    },
+    /// Working directory the run is bound to (snapshotted at execution start)
+    WorkingDirBound {
+        /// Directory file-writing steps use (None = server's current directory)
+        working_dir: Option<String>,
+    },
     /// Step started executing
     StepStart {
         /// Unique identifier for the step
@@ -111,14 +444,38 @@ pub enum OrchestrationEvent {
         /// Task type being executed (e.g., "run_gemini", "create_file")
         task: String,
     },
+    /// Partial output from a step that is still running (run_gemini steps only)
+    ///
+    /// Chunks arrive in order and concatenate to the step's raw output, counting
+    /// from its last `StepProgressReset`; the step's `StepComplete` or
+    /// `StepError` always follows them.
+    StepProgress {
+        /// Unique identifier for the step
+        step_id: String,
+        /// New output since the previous progress event for this step
+        chunk: String,
+    },
+    /// A running step started over (e.g. on a fallback model): discard its chunks so far
+    StepProgressReset {
+        /// Unique identifier for the step
+        step_id: String,
+    },
     /// Step completed successfully
     StepComplete {
         /// Unique identifier for the step
         step_id: String,
         /// Sequential step number (1-indexed)
         step_number: u32,
-        /// Output from the step execution
+        /// Task type that was executed (matches `StepStart.task`)
+        task: String,
+        /// Output from the step execution (truncated to `max_event_output_chars`)
         output: String,
+        /// Whether `output` was truncated (the full value stays in the step results)
+        #[serde(default)]
+        truncated: bool,
+        /// Model that served the step (run_gemini steps only)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        model: Option<String>,
     },
     /// Step failed
     StepError {
@@ -126,6 +483,8 @@ pub enum OrchestrationEvent {
         step_id: String,
         /// Sequential step number (1-indexed)
         step_number: u32,
+        /// Task type that failed (matches `StepStart.task`)
+        task: String,
         /// Error message describing the failure
         error: String,
     },
@@ -136,13 +495,41 @@ pub enum OrchestrationEvent {
         /// Number of steps that completed successfully
         successful_steps: usize,
     },
+    /// Final tally of an execution, sent last before `[DONE]` whenever steps ran
+    ExecutionSummary {
+        /// Total number of steps in the plan
+        total_steps: usize,
+        /// Number of steps that completed successfully
+        successful_steps: usize,
+        /// Steps that failed, in step order
+        failed: Vec<FailedStep>,
+        /// Tokens used by all steps (sum of `usage_by_model`)
+        #[serde(default)]
+        usage: TokenUsage,
+        /// Tokens used, by model (plans with fallbacks or per-step models mix models)
+        #[serde(default)]
+        usage_by_model: UsageByModel,
+    },
     /// Execution failed
     ExecutionError {
         /// Error message describing the failure
         error: String,
+        /// Gemini's reason for blocking the prompt, when that caused the failure
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        block_reason: Option<String>,
     },
 }
 
+/// ExecutionError event for a failure caused by `cause`
+///
+/// Carries Gemini's block reason so clients can tell a refused prompt from a crash.
+fn execution_error_event(error: String, cause: &AppError) -> OrchestrationEvent {
+    OrchestrationEvent::ExecutionError {
+        error,
+        block_reason: cause.block_reason().map(str::to_string),
+    }
+}
+
 /// POST /api/orchestrate/poem - Hard-coded orchestrator example
 ///
 /// Creates a poem using Gemini and saves it to a file.
@@ -156,6 +543,7 @@ pub enum OrchestrationEvent {
 ///
 /// # Arguments
 /// * `State(state)` - Application state
+/// * `Query(protocol)` - Requested event schema version (`?protocol=1`)
 /// * `Json(request)` - Orchestration request with goal/prompt
 ///
 /// # Returns
@@ -163,9 +551,18 @@ pub enum OrchestrationEvent {
 /// * `Err(AppError)` - If orchestration fails
 pub async fn orchestrate_poem(
     State((state, _, _)): State<RouterState>,
+    Query(protocol): Query<StreamProtocolQuery>,
     Json(request): Json<OrchestrationRequest>,
 ) -> Result<Response, AppError> {
-    let config = OrchestratorConfig::default();
+    let protocol_version = protocol.negotiate()?;
+    let (config, sse_retry_ms, max_sse_duration_secs) = {
+        let state_read = state.read().await;
+        (
+            state_read.orchestrator_config.clone(),
+            state_read.sse_retry_ms,
+            state_read.max_sse_duration_secs,
+        )
+    };
 
     // Validate input size
     if request.goal.len() > config.max_goal_length {
@@ -180,7 +577,7 @@ pub async fn orchestrate_poem(
     // Get working directory from state
     let working_dir = {
         let state_read = state.read().await;
-        let wd = state_read.working_directory().cloned();
+        let wd = state_read.working_directory().map(|dir| dir.to_string());
         tracing::debug!(
             working_dir = ?wd,
             "Orchestrator: Retrieved working directory from state"
@@ -196,6 +593,12 @@ pub async fn orchestrate_poem(
     let working_dir_clone = working_dir.clone();
 
     let stream = stream! {
+        let start_event = OrchestrationEvent::StreamStart {
+            protocol_version,
+            execution_id: None,
+        };
+        yield Ok::<String, axum::Error>(serialize_event_or_fallback(&start_event));
+
         // Step 1: Status update - asking Gemini
         yield Ok::<String, axum::Error>(
             r#"{"step": 1, "message": "Task 1: Asking Gemini for a poem...", "status": "running"}"#
@@ -240,10 +643,13 @@ pub async fn orchestrate_poem(
                     }
                     Err(e) => {
                         // Error saving file
-                        yield Ok::<String, axum::Error>(format!(
-                            r#"{{"step": 2, "message": "Error saving file: {}", "status": "error"}}"#,
-                            e
-                        ));
+                        let error_event = OrchestrationEvent::StepError {
+                            step_id: "step_2".to_string(),
+                            step_number: 2,
+                            task: "create_file".to_string(),
+                            error: format!("Error saving file: {}", e),
+                        };
+                        yield Ok::<String, axum::Error>(serialize_event_or_fallback(&error_event));
                         // Signal stream completion
                         use crate::orchestrator::constants::SSE_DONE_SIGNAL;
                         yield Ok::<String, axum::Error>(SSE_DONE_SIGNAL.to_string());
@@ -252,10 +658,13 @@ pub async fn orchestrate_poem(
             }
             Err(e) => {
                 // Error running Gemini
-                yield Ok::<String, axum::Error>(format!(
-                    r#"{{"step": 1, "message": "Error: {}", "status": "error"}}"#,
-                    e
-                ));
+                let error_event = OrchestrationEvent::StepError {
+                    step_id: "step_1".to_string(),
+                    step_number: 1,
+                    task: "run_gemini".to_string(),
+                    error: format!("Error: {}", e),
+                };
+                yield Ok::<String, axum::Error>(serialize_event_or_fallback(&error_event));
                 // Signal stream completion
                 yield Ok::<String, axum::Error>(SSE_DONE_SIGNAL.to_string());
             }
@@ -263,7 +672,7 @@ pub async fn orchestrate_poem(
     };
 
     // Convert stream to SSE format
-    let sse_stream = format_sse_stream(stream);
+    let sse_stream = format_sse_stream(stream, sse_retry_ms, max_sse_duration_secs);
 
     Response::builder()
         .status(StatusCode::OK)
@@ -289,18 +698,21 @@ pub async fn orchestrate_poem(
 ///
 /// # Arguments
 /// * `State(state)` - Application state
+/// * `Query(protocol)` - Requested event schema version (`?protocol=1`)
+/// * `Query(options)` - `?dry_run=true` streams the plan and stops before executing it
 /// * `Json(request)` - Orchestration request with goal
 ///
 /// # Returns
 /// * `Ok(Response)` - SSE stream with status updates
-/// * `Err(AppError)` - If orchestration fails
+/// * `Err(AppError)` - If orchestration fails or the protocol is unsupported
 pub async fn orchestrate(
-    State((state, _, _)): State<RouterState>,
+    State((state, chat_db, _)): State<RouterState>,
+    Query(protocol): Query<StreamProtocolQuery>,
+    Query(options): Query<OrchestrateQuery>,
     Json(request): Json<OrchestrationRequest>,
 ) -> Result<Response, AppError> {
-    use async_stream::stream;
-
-    let config = OrchestratorConfig::default();
+    let protocol_version = protocol.negotiate()?;
+    let config = request.apply_timeout(state.read().await.orchestrator_config.clone())?;
 
     // Validate input size
     if request.goal.len() > config.max_goal_length {
@@ -312,32 +724,333 @@ pub async fn orchestrate(
         )));
     }
 
-    let state_clone = state.clone();
-    let goal = request.goal;
+    // Take a global execution slot (429 or wait, per EXECUTION_QUEUE_POLICY).
+    // It is moved into the stream and released when the stream is dropped:
+    // on completion, on error, or when the client disconnects.
+    let limiter = state.read().await.execution_limiter.clone();
+    let execution_permit = limiter.acquire().await?;
 
-    // Create execution ID for tracing
-    let execution_id = uuid::Uuid::new_v4().to_string();
     use crate::orchestrator::utils::hash_goal;
-    let goal_hash = hash_goal(&goal);
+    let goal_hash = hash_goal(&request.goal);
+
+    // Audit entry for this run (raw goal only stored when enabled)
+    let audit = AuditEntry::new(
+        goal_hash,
+        config.audit_store_goal.then(|| request.goal.clone()),
+    );
+
+    stream_orchestration(
+        state,
+        chat_db,
+        config,
+        execution_permit,
+        OrchestrationRun {
+            protocol_version,
+            source: PlanSource::Planner(request.goal),
+            allow_over_budget: request.allow_over_budget,
+            audit,
+            run_from: None,
+            dry_run: options.dry_run,
+        },
+    )
+    .await
+}
+
+/// Query parameters for replaying an execution
+#[derive(Debug, Default, Deserialize)]
+pub struct ReplayQuery {
+    /// Run the plan even if it exceeds `max_estimated_tokens`/`max_estimated_cost`
+    #[serde(default)]
+    pub allow_over_budget: bool,
+}
+
+/// POST /api/orchestrate/:execution_id/replay - Re-run an earlier execution's plan
+///
+/// Loads the plan retained in the audit log (see `audit_store_plan`) and
+/// executes it fresh, without calling the planner. Streams the same events as
+/// `/api/orchestrate`; the `stream_start` event carries the new execution ID.
+///
+/// # Returns
+/// * `Ok(Response)` - SSE stream for the new execution
+/// * `Err(AppError::FileNotFound)` - If the execution is unknown or its plan wasn't retained
+pub async fn replay_execution(
+    State((state, chat_db, _)): State<RouterState>,
+    Path(execution_id): Path<String>,
+    Query(protocol): Query<StreamProtocolQuery>,
+    Query(options): Query<ReplayQuery>,
+) -> Result<Response, AppError> {
+    let protocol_version = protocol.negotiate()?;
+    let config = state.read().await.orchestrator_config.clone();
+    let (original, plan) = load_retained_plan(&chat_db, &execution_id).await?;
+
+    let limiter = state.read().await.execution_limiter.clone();
+    let execution_permit = limiter.acquire().await?;
+
+    // The replay is audited under the original goal, as a new execution
+    let audit = AuditEntry::new(
+        original.goal_hash,
+        original.goal.filter(|_| config.audit_store_goal),
+    );
+    tracing::info!(
+        replayed_from = %execution_id,
+        execution_id = %audit.id,
+        "Replaying retained plan"
+    );
+
+    stream_orchestration(
+        state,
+        chat_db,
+        config,
+        execution_permit,
+        OrchestrationRun {
+            protocol_version,
+            source: PlanSource::Replay(plan),
+            allow_over_budget: options.allow_over_budget,
+            audit,
+            run_from: None,
+            dry_run: false,
+        },
+    )
+    .await
+}
+
+/// POST /api/orchestrate/:execution_id/run-from/:step_id - Re-run an execution from a step
+///
+/// Loads the plan and step outputs retained for the execution (see
+/// `audit_store_plan`), then runs `step_id` and every step downstream of it.
+/// All other steps keep their earlier outputs. Streams the same events as
+/// `/api/orchestrate`; the `stream_start` event carries the new execution ID.
+///
+/// # Returns
+/// * `Ok(Response)` - SSE stream for the new execution
+/// * `Err(AppError::FileNotFound)` - If the plan wasn't retained or has no such step
+/// * `Err(AppError::InvalidPlan)` - If a dependency of the step has no stored output
+pub async fn run_from_step(
+    State((state, chat_db, _)): State<RouterState>,
+    Path((execution_id, step_id)): Path<(String, String)>,
+    Query(protocol): Query<StreamProtocolQuery>,
+    Query(options): Query<ReplayQuery>,
+) -> Result<Response, AppError> {
+    let protocol_version = protocol.negotiate()?;
+    let config = state.read().await.orchestrator_config.clone();
+    let (original, plan) = load_retained_plan(&chat_db, &execution_id).await?;
+
+    let outputs: StepOutputs = match original.step_outputs.as_deref() {
+        Some(json) => serde_json::from_str(json).map_err(|e| {
+            AppError::Internal(anyhow!(
+                "Retained step outputs for execution {} are unreadable: {}",
+                execution_id,
+                e
+            ))
+        })?,
+        None => StepOutputs::new(),
+    };
+    let run_from = RunFrom::new(&plan, &step_id, outputs)?;
+
+    let limiter = state.read().await.execution_limiter.clone();
+    let execution_permit = limiter.acquire().await?;
+
+    let audit = AuditEntry::new(
+        original.goal_hash,
+        original.goal.filter(|_| config.audit_store_goal),
+    );
+    tracing::info!(
+        restarted_from = %execution_id,
+        step_id = %step_id,
+        execution_id = %audit.id,
+        "Re-running retained plan from step"
+    );
+
+    stream_orchestration(
+        state,
+        chat_db,
+        config,
+        execution_permit,
+        OrchestrationRun {
+            protocol_version,
+            source: PlanSource::Replay(plan),
+            allow_over_budget: options.allow_over_budget,
+            audit,
+            run_from: Some(run_from),
+            dry_run: false,
+        },
+    )
+    .await
+}
+
+/// Response for cancelling an orchestration
+#[derive(Debug, Serialize)]
+pub struct CancelExecutionResponse {
+    /// The execution the cancel was addressed to
+    pub execution_id: String,
+    /// Whether a run with that ID was in flight
+    pub cancelled: bool,
+}
+
+/// POST /api/orchestrate/:execution_id/cancel - Stop a running orchestration
+///
+/// Works during planning as well as execution: the in-flight planner or step
+/// is aborted and the stream ends with an `execution_error` event.
+/// Cancelling an execution that has already finished is a no-op.
+pub async fn cancel_execution(Path(execution_id): Path<String>) -> Json<CancelExecutionResponse> {
+    let cancelled = cancellation::cancel(&execution_id);
+    if cancelled {
+        tracing::info!(execution_id = %execution_id, "Orchestration cancelled");
+    }
+    Json(CancelExecutionResponse {
+        execution_id,
+        cancelled,
+    })
+}
+
+/// Load an execution's audit entry together with its retained plan
+///
+/// # Returns
+/// * `Ok((AuditEntry, Plan))` - The original entry and its parsed plan
+/// * `Err(AppError::FileNotFound)` - If the execution is unknown or its plan wasn't retained
+async fn load_retained_plan(
+    chat_db: &ChatDb,
+    execution_id: &str,
+) -> Result<(AuditEntry, Plan), AppError> {
+    let not_retained =
+        || AppError::FileNotFound(format!("No retained plan for execution: {}", execution_id));
+    let original = chat_db
+        .get_audit_entry(execution_id)
+        .await?
+        .ok_or_else(not_retained)?;
+    let plan_json = original.plan.as_deref().ok_or_else(not_retained)?;
+    let plan: Plan = serde_json::from_str(plan_json).map_err(|e| {
+        AppError::Internal(anyhow!(
+            "Retained plan for execution {} is unreadable: {}",
+            execution_id,
+            e
+        ))
+    })?;
+    Ok((original, plan))
+}
+
+/// GET /api/orchestrate/:execution_id/results - Stored summary of an execution
+///
+/// Returns the same `ExecutionSummary` the run's `execution_summary` event
+/// was built from, including every step's full output. Summaries are only
+/// kept when `audit_store_plan` is enabled.
+///
+/// # Returns
+/// * `Ok(Json<ExecutionSummary>)` - The stored summary
+/// * `Err(AppError::FileNotFound)` - If the execution is unknown or kept no summary
+pub async fn get_execution_results(
+    State((_, chat_db, _)): State<RouterState>,
+    Path(execution_id): Path<String>,
+) -> Result<Json<ExecutionSummary>, AppError> {
+    let not_retained =
+        || AppError::FileNotFound(format!("No stored results for execution: {}", execution_id));
+    let entry = chat_db
+        .get_audit_entry(&execution_id)
+        .await?
+        .ok_or_else(not_retained)?;
+    let summary_json = entry.summary.as_deref().ok_or_else(not_retained)?;
+    let summary = serde_json::from_str(summary_json).map_err(|e| {
+        AppError::Internal(anyhow!(
+            "Stored results for execution {} are unreadable: {}",
+            execution_id,
+            e
+        ))
+    })?;
+    Ok(Json(summary))
+}
+
+/// Where an orchestration's plan comes from
+enum PlanSource {
+    /// Generate a plan for this goal with the planner agent
+    Planner(String),
+    /// Re-run a plan retained from an earlier execution
+    Replay(Plan),
+}
+
+/// One orchestration, ready to be streamed
+struct OrchestrationRun {
+    /// Negotiated event schema version
+    protocol_version: u32,
+    /// Where the plan comes from
+    source: PlanSource,
+    /// Skip the token/cost budget check
+    allow_over_budget: bool,
+    /// Audit entry recorded when the run ends; its ID is the execution ID
+    audit: AuditEntry,
+    /// Restart the plan from this step instead of running it from the start
+    run_from: Option<RunFrom>,
+    /// Stop once the plan has been emitted, without executing or auditing it
+    dry_run: bool,
+}
+
+/// Plan (or load) and execute an orchestration, streaming events as SSE
+async fn stream_orchestration(
+    state: Arc<RwLock<AppState>>,
+    chat_db: Arc<ChatDb>,
+    config: OrchestratorConfig,
+    execution_permit: OwnedSemaphorePermit,
+    run: OrchestrationRun,
+) -> Result<Response, AppError> {
+    use async_stream::stream;
+
+    let OrchestrationRun {
+        protocol_version,
+        source,
+        allow_over_budget,
+        audit,
+        run_from,
+        dry_run,
+    } = run;
+    let execution_id = audit.id.clone();
+    let (sse_retry_ms, max_sse_duration_secs) = {
+        let state_read = state.read().await;
+        (state_read.sse_retry_ms, state_read.max_sse_duration_secs)
+    };
 
     let span = tracing::info_span!(
         "orchestrate",
         execution_id = %execution_id,
-        goal_len = goal.len(),
-        goal_hash = %goal_hash,
+        goal_hash = %audit.goal_hash,
     );
     let _enter = span.enter();
+    let started_at = std::time::Instant::now();
+    // Created outside the stream, so a client that never reads it is audited too
+    let mut audit = PendingAudit::new(audit, chat_db, started_at, !dry_run);
+    // Registered before the response goes out, so a cancel can't arrive too early
+    let cancel_registration = cancellation::register(&execution_id);
 
     let stream = stream! {
-        // Step 1: Planning
-        yield Ok::<String, axum::Error>(
-            r#"{"step": 0, "step_id": "planning", "message": "Planning: Generating execution plan...", "status": "running"}"#
-                .to_string(),
-        );
+        let _execution_permit = execution_permit;
+        let cancel = cancel_registration.token().clone();
+        let _cancel_registration = cancel_registration;
+
+        let start_event = OrchestrationEvent::StreamStart {
+            protocol_version,
+            execution_id: Some(execution_id),
+        };
+        yield Ok::<String, axum::Error>(serialize_event_or_fallback(&start_event));
+
+        let planned = match source {
+            PlanSource::Planner(goal) => {
+                // Step 1: Planning
+                yield Ok::<String, axum::Error>(
+                    r#"{"step": 0, "step_id": "planning", "message": "Planning: Generating execution plan...", "status": "running"}"#
+                        .to_string(),
+                );
 
-        // Generate plan using planner agent (via CLI)
-        let plan = match internal_run_planner(&state_clone, &goal).await {
+                // Generate plan using planner agent (via CLI); a cancel aborts it
+                internal_run_planner_cancellable(&state, &goal, &cancel).await
+            }
+            PlanSource::Replay(plan) => Ok(plan),
+        };
+        let plan = match planned {
             Ok(plan) => {
+                audit.plan_hash = Some(crate::orchestrator::utils::hash_plan(&plan));
+                audit.step_count = plan.steps.len() as i64;
+                if config.audit_store_plan {
+                    audit.plan = serde_json::to_string(&plan).ok();
+                }
+
                 // Phase 6.3: Emit structured event for plan generation
                 let plan_event = OrchestrationEvent::PlanGenerated {
                     step_count: plan.steps.len(),
@@ -345,18 +1058,63 @@ pub async fn orchestrate(
                     estimated_time_secs: crate::orchestrator::plan_optimizer::estimate_execution_time(&plan),
                 };
                 yield Ok::<String, axum::Error>(serialize_event_or_fallback(&plan_event));
+                yield Ok::<String, axum::Error>(serialize_event_or_fallback(&plan_details_event(&plan)));
                 plan
             }
+            Err(e) if dry_run => {
+                let error_event = execution_error_event(format!("Planning failed: {}", e), &e);
+                yield Ok::<String, axum::Error>(serialize_event_or_fallback(&error_event));
+                yield Ok::<String, axum::Error>(SSE_DONE_SIGNAL.to_string());
+                return;
+            }
             Err(e) => {
-                let error_event = OrchestrationEvent::ExecutionError {
-                    error: format!("Planning failed: {}", e),
-                };
+                let error = format!("Planning failed: {}", e);
+                audit.record(false, Some(error.clone()), started_at.elapsed()).await;
+
+                let error_event = execution_error_event(error, &e);
                 yield Ok::<String, axum::Error>(serialize_event_or_fallback(&error_event));
                 yield Ok::<String, axum::Error>(SSE_DONE_SIGNAL.to_string());
                 return;
             }
         };
 
+        // Dry run: the plan is all the client asked for
+        if dry_run {
+            tracing::info!(step_count = plan.steps.len(), "Dry run: skipping execution");
+            yield Ok::<String, axum::Error>(SSE_DONE_SIGNAL.to_string());
+            return;
+        }
+
+        // Refuse plans over the configured token/cost budget before any step runs
+        if let Err(e) = check_request_budget(&plan, &config, allow_over_budget) {
+            let error = format!("Execution rejected: {}", e);
+            audit.record(false, Some(error.clone()), started_at.elapsed()).await;
+
+            let error_event = execution_error_event(error, &e);
+            yield Ok::<String, axum::Error>(serialize_event_or_fallback(&error_event));
+            yield Ok::<String, axum::Error>(SSE_DONE_SIGNAL.to_string());
+            return;
+        }
+
+        // Snapshot the working directory; later changes don't affect this run
+        let working_dir = state.read().await.working_directory().map(|dir| dir.to_string());
+
+        // Reject file-writing plans with no target before any step starts
+        if let Err(e) = check_write_target(&plan, &config, working_dir.as_deref()) {
+            let error = format!("Execution failed: {}", e);
+            audit.record(false, Some(error.clone()), started_at.elapsed()).await;
+
+            let error_event = execution_error_event(error, &e);
+            yield Ok::<String, axum::Error>(serialize_event_or_fallback(&error_event));
+            yield Ok::<String, axum::Error>(SSE_DONE_SIGNAL.to_string());
+            return;
+        }
+
+        let bound_event = OrchestrationEvent::WorkingDirBound {
+            working_dir: working_dir.clone(),
+        };
+        yield Ok::<String, axum::Error>(serialize_event_or_fallback(&bound_event));
+
         // Phase 6.3: Emit StepStart events for all steps (before execution)
         // This gives the frontend a "map" of all steps that will run
         for (idx, step) in plan.steps.iter().enumerate() {
@@ -369,44 +1127,106 @@ pub async fn orchestrate(
         }
 
         // Step 2: Execution - stream events as steps execute
-        // Note: execute_plan returns results after all steps complete,
-        // but we can still stream completion events for each step
-        match execute_plan(&plan, &state_clone).await {
+        // Note: execute_plan_retaining_outputs returns results after all steps complete,
+        // so only progress events are sent while it runs; completion events follow
+        let mut step_outputs = StepOutputs::new();
+        let mut prefixer = config.prefix_step_output.then(StepLinePrefixer::default);
+        let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
+        let executed = {
+            let execution = cancel.run(
+                "Execution",
+                execute_plan_retaining_outputs(
+                    &plan,
+                    &state,
+                    &config,
+                    working_dir,
+                    run_from,
+                    &mut step_outputs,
+                    Some(progress_tx),
+                ),
+            );
+            tokio::pin!(execution);
+            loop {
+                let progress = tokio::select! {
+                    Some(progress) = progress_rx.recv() => progress,
+                    executed = &mut execution => break executed,
+                };
+                let progress_event = step_progress_event(progress, prefixer.as_mut());
+                yield Ok::<String, axum::Error>(serialize_event_or_fallback(&progress_event));
+            }
+        };
+        // Progress sent just before the run finished
+        while let Ok(progress) = progress_rx.try_recv() {
+            let progress_event = step_progress_event(progress, prefixer.as_mut());
+            yield Ok::<String, axum::Error>(serialize_event_or_fallback(&progress_event));
+        }
+        if config.audit_store_plan {
+            audit.step_outputs = serde_json::to_string(&step_outputs).ok();
+        }
+        match executed {
             Ok(results) => {
+                let elapsed = started_at.elapsed();
+                let summary = ExecutionSummary::new(
+                    audit.id.clone(),
+                    audit.goal_hash.clone(),
+                    audit.plan_hash.clone().unwrap_or_default(),
+                    results,
+                    elapsed,
+                );
+
                 // Stream results from each step with structured events
-                for result in &results {
+                let mut first_error = None;
+                for result in &summary.results {
                     if result.success {
-                        let complete_event = OrchestrationEvent::StepComplete {
-                            step_id: result.step_id.clone(),
-                            step_number: result.step_number,
-                            output: result.output.clone().unwrap_or_default(),
-                        };
+                        let complete_event =
+                            step_complete_event(result, config.max_event_output_chars);
                         yield Ok::<String, axum::Error>(serialize_event_or_fallback(&complete_event));
                     } else {
+                        let error = result.error.clone().unwrap_or_else(|| "Unknown error".to_string());
+                        first_error.get_or_insert_with(|| error.clone());
+
                         let error_event = OrchestrationEvent::StepError {
                             step_id: result.step_id.clone(),
                             step_number: result.step_number,
-                            error: result.error.clone().unwrap_or_else(|| "Unknown error".to_string()),
+                            task: result.task.clone(),
+                            error,
                         };
                         yield Ok::<String, axum::Error>(serialize_event_or_fallback(&error_event));
-                        use crate::orchestrator::constants::SSE_DONE_SIGNAL;
-                        yield Ok::<String, axum::Error>(SSE_DONE_SIGNAL.to_string());
-                        return;
                     }
                 }
 
-                // All steps completed successfully
-                let complete_event = OrchestrationEvent::ExecutionComplete {
-                    total_steps: results.len(),
-                    successful_steps: results.iter().filter(|r| r.success).count(),
-                };
-                yield Ok::<String, axum::Error>(serialize_event_or_fallback(&complete_event));
+                if config.audit_store_plan {
+                    audit.summary = serde_json::to_string(&summary).ok();
+                }
+                audit.record(first_error.is_none(), first_error.clone(), elapsed).await;
+
+                if first_error.is_none() {
+                    let complete_event = OrchestrationEvent::ExecutionComplete {
+                        total_steps: summary.total_steps,
+                        successful_steps: summary.successful,
+                    };
+                    yield Ok::<String, axum::Error>(serialize_event_or_fallback(&complete_event));
+                }
+
+                // Terminal summary so clients needn't replay every step event
+                let summary_event = execution_summary_event(&summary);
+                yield Ok::<String, axum::Error>(serialize_event_or_fallback(&summary_event));
                 yield Ok::<String, axum::Error>(SSE_DONE_SIGNAL.to_string());
             }
             Err(e) => {
-                let error_event = OrchestrationEvent::ExecutionError {
-                    error: format!("Execution failed: {}", e),
-                };
+                // Steps that finished before a budget abort still report their output
+                if let AppError::BudgetExceeded { completed, .. } = &e {
+                    for result in completed {
+                        let complete_event =
+                            step_complete_event(result, config.max_event_output_chars);
+                        yield Ok::<String, axum::Error>(serialize_event_or_fallback(&complete_event));
+                    }
+                }
+
+                let error = format!("Execution failed: {}", e);
+                audit.record(false, Some(error.clone()), started_at.elapsed()).await;
+
+                let error_event = execution_error_event(error, &e);
                 yield Ok::<String, axum::Error>(serialize_event_or_fallback(&error_event));
                 yield Ok::<String, axum::Error>(SSE_DONE_SIGNAL.to_string());
             }
@@ -414,7 +1234,7 @@ pub async fn orchestrate(
     };
 
     // Convert stream to SSE format
-    let sse_stream = format_sse_stream(stream);
+    let sse_stream = format_sse_stream(stream, sse_retry_ms, max_sse_duration_secs);
 
     Response::builder()
         .status(StatusCode::OK)
@@ -425,32 +1245,127 @@ pub async fn orchestrate(
         .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to build response: {}", e)))
 }
 
-/// Plan analysis response (Phase 6.1: Pre-flight Check)
-#[derive(Debug, Serialize)]
-pub struct PlanAnalysisResponse {
-    /// The generated plan
-    pub plan: crate::orchestrator::plan_types::Plan,
-    /// Estimated token usage
-    pub estimated_tokens: usize,
-    /// Estimated execution time in seconds
-    pub estimated_time_secs: usize,
-    /// Bottleneck analysis
-    pub bottlenecks: BottleneckAnalysis,
+/// Append an orchestration outcome to the audit log
+///
+/// Audit failures are logged but never interrupt the SSE stream.
+async fn record_audit_entry(chat_db: &ChatDb, entry: &AuditEntry) {
+    if let Err(e) = chat_db.add_audit_entry(entry).await {
+        tracing::warn!(audit_id = %entry.id, error = %e, "Failed to record audit entry");
+    }
 }
 
-/// POST /api/plan - Pre-flight check: Plan + Optimizer (Phase 6.1)
-///
-/// This endpoint generates a plan and runs optimization analysis
-/// WITHOUT executing it. This allows users to see cost/time estimates
-/// and bottlenecks before committing to execution.
-///
-/// # Flow
-/// 1. Call planner agent to generate a JSON plan
-/// 2. Run optimizer functions (token usage, execution time, bottlenecks)
-/// 3. Return plan + analysis (NO execution)
+/// Audit entry of a running orchestration, recorded even if the run never ends
 ///
-/// # Arguments
-/// * `State(state)` - Application state
+/// The SSE stream is dropped when the client disconnects. If that happens
+/// before `record` is called, the entry is recorded as failed from a
+/// background task instead of being lost.
+struct PendingAudit {
+    entry: AuditEntry,
+    chat_db: Arc<ChatDb>,
+    started_at: std::time::Instant,
+    /// False for dry runs, which are never audited
+    enabled: bool,
+    /// The outcome has been set by `record`
+    finished: bool,
+    /// The entry has been written (or needs no writing)
+    written: bool,
+}
+
+impl PendingAudit {
+    fn new(
+        entry: AuditEntry,
+        chat_db: Arc<ChatDb>,
+        started_at: std::time::Instant,
+        enabled: bool,
+    ) -> Self {
+        Self {
+            entry,
+            chat_db,
+            started_at,
+            enabled,
+            finished: false,
+            written: false,
+        }
+    }
+
+    /// Set the run's outcome and append the entry to the audit log
+    async fn record(&mut self, success: bool, error: Option<String>, elapsed: std::time::Duration) {
+        self.entry.finish(success, error, elapsed);
+        self.finished = true;
+        record_audit_entry(&self.chat_db, &self.entry).await;
+        self.written = true;
+    }
+}
+
+impl std::ops::Deref for PendingAudit {
+    type Target = AuditEntry;
+
+    fn deref(&self) -> &AuditEntry {
+        &self.entry
+    }
+}
+
+impl std::ops::DerefMut for PendingAudit {
+    fn deref_mut(&mut self) -> &mut AuditEntry {
+        &mut self.entry
+    }
+}
+
+impl Drop for PendingAudit {
+    fn drop(&mut self) {
+        if !self.enabled || self.written {
+            return;
+        }
+        let mut entry = self.entry.clone();
+        if !self.finished {
+            entry.finish(
+                false,
+                Some("Client disconnected before the run finished".to_string()),
+                self.started_at.elapsed(),
+            );
+        }
+        let chat_db = self.chat_db.clone();
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => {
+                runtime.spawn(async move { record_audit_entry(&chat_db, &entry).await });
+            }
+            Err(_) => {
+                tracing::warn!(audit_id = %entry.id, "No runtime to record audit entry on");
+            }
+        }
+    }
+}
+
+/// Plan analysis response (Phase 6.1: Pre-flight Check)
+#[derive(Debug, Serialize)]
+pub struct PlanAnalysisResponse {
+    /// The generated plan
+    pub plan: crate::orchestrator::plan_types::Plan,
+    /// Estimated token usage
+    pub estimated_tokens: usize,
+    /// Estimated cost in USD
+    pub estimated_cost: f64,
+    /// Estimated execution time in seconds
+    pub estimated_time_secs: usize,
+    /// Bottleneck analysis
+    pub bottlenecks: BottleneckAnalysis,
+    /// Step IDs grouped by execution level (steps in a level run in parallel)
+    pub execution_levels: Vec<Vec<String>>,
+}
+
+/// POST /api/plan - Pre-flight check: Plan + Optimizer (Phase 6.1)
+///
+/// This endpoint generates a plan and runs optimization analysis
+/// WITHOUT executing it. This allows users to see cost/time estimates
+/// and bottlenecks before committing to execution.
+///
+/// # Flow
+/// 1. Call planner agent to generate a JSON plan
+/// 2. Run optimizer functions (token usage, execution time, bottlenecks)
+/// 3. Return plan + analysis (NO execution)
+///
+/// # Arguments
+/// * `State(state)` - Application state
 /// * `Json(request)` - Orchestration request with goal
 ///
 /// # Returns
@@ -460,7 +1375,7 @@ pub async fn plan_with_analysis(
     State((state, _, _)): State<RouterState>,
     Json(request): Json<OrchestrationRequest>,
 ) -> Result<Json<PlanAnalysisResponse>, AppError> {
-    let config = OrchestratorConfig::default();
+    let config = state.read().await.orchestrator_config.clone();
 
     // Validate input size
     if request.goal.len() > config.max_goal_length {
@@ -475,50 +1390,216 @@ pub async fn plan_with_analysis(
     // Generate plan using planner agent (via CLI)
     let plan = internal_run_planner(&state, &request.goal).await?;
 
-    // Run optimizer functions
+    analyze_plan(plan, &config).map(Json)
+}
+
+/// POST /api/orchestrate/estimate - Pre-flight check for a directly-submitted plan
+///
+/// Also served at `/api/plan/estimate`.
+///
+/// Returns the same analysis as `/api/plan` for a hand-authored or exported
+/// plan, without calling the planner. The plan is validated first; an invalid
+/// plan yields the same 400 as `/api/plan/validate`.
+///
+/// # Returns
+/// * `Ok(Json<PlanAnalysisResponse>)` - Plan + analysis
+/// * `Err(AppError::PlanValidationFailed)` - With all field errors
+/// * `Err(AppError::InvalidPlan)` - If the dependency chain is too long
+pub async fn estimate_plan(
+    State((state, _, _)): State<RouterState>,
+    Json(plan): Json<Plan>,
+) -> Result<Json<PlanAnalysisResponse>, AppError> {
+    validate_graph(&plan).map_err(|issues| {
+        AppError::PlanValidationFailed(issues.into_iter().map(FieldError::from).collect())
+    })?;
+
+    let config = state.read().await.orchestrator_config.clone();
+    analyze_plan(plan, &config).map(Json)
+}
+
+/// Run the optimizer over a plan
+///
+/// # Returns
+/// * `Ok(PlanAnalysisResponse)` - Estimates, bottlenecks and execution levels
+/// * `Err(AppError::InvalidPlan)` - If the chain is too long or the graph is invalid
+fn analyze_plan(plan: Plan, config: &OrchestratorConfig) -> Result<PlanAnalysisResponse, AppError> {
     let estimated_tokens = estimate_token_usage(&plan);
     let estimated_time_secs = estimate_execution_time(&plan);
     let bottlenecks = analyze_bottlenecks(&plan);
+    validate_chain_length(&bottlenecks, config.max_chain_length)?;
+    let execution_levels = plan
+        .execution_levels()
+        .map_err(|e| AppError::InvalidPlan(format!("Plan validation failed: {}", e)))?;
 
-    Ok(Json(PlanAnalysisResponse {
+    Ok(PlanAnalysisResponse {
         plan,
         estimated_tokens,
+        estimated_cost: estimate_cost(estimated_tokens),
         estimated_time_secs,
         bottlenecks,
+        execution_levels,
+    })
+}
+
+/// Request for a plan explanation: a goal to plan, or a ready-made plan
+#[derive(Debug, Deserialize)]
+pub struct PlanExplainRequest {
+    /// Goal to generate the plan from (calls the planner)
+    #[serde(default)]
+    pub goal: Option<String>,
+    /// Plan to explain as-is (validated first)
+    #[serde(default)]
+    pub plan: Option<Plan>,
+}
+
+/// Plain-language description of a plan
+#[derive(Debug, Serialize)]
+pub struct PlanExplanationResponse {
+    /// The explained plan (generated from the goal, or as submitted)
+    pub plan: Plan,
+    /// What each step does and what it waits for, in plan order
+    pub steps: Vec<StepExplanation>,
+    /// The step descriptions as numbered lines
+    pub explanation: String,
+}
+
+/// POST /api/plan/explain - Describe what a plan will do before running it
+///
+/// Takes either a `goal` (a plan is generated, as for `/api/plan`) or a
+/// `plan` (validated, as for `/api/plan/validate`). The explanation comes
+/// from the plan's structure, so no further model call is made.
+///
+/// # Returns
+/// * `Ok(Json<PlanExplanationResponse>)` - The plan and its explanation
+/// * `Err(AppError::InvalidPlan)` - If neither or both of `goal` and `plan` are given
+/// * `Err(AppError::PlanValidationFailed)` - If the submitted plan is invalid
+/// * `Err(AppError)` - If planning fails
+pub async fn explain_plan(
+    State((state, _, _)): State<RouterState>,
+    Json(request): Json<PlanExplainRequest>,
+) -> Result<Json<PlanExplanationResponse>, AppError> {
+    let plan = match (request.goal, request.plan) {
+        (None, Some(plan)) => {
+            let errors = plan.field_errors();
+            if !errors.is_empty() {
+                return Err(AppError::PlanValidationFailed(errors));
+            }
+            plan
+        }
+        (Some(goal), None) => {
+            let max_goal_length = state.read().await.orchestrator_config.max_goal_length;
+            if goal.len() > max_goal_length {
+                return Err(AppError::InvalidPlan(format!(
+                    "Goal too long ({} > {} characters)",
+                    goal.len(),
+                    max_goal_length
+                )));
+            }
+            internal_run_planner(&state, &goal).await?
+        }
+        _ => {
+            return Err(AppError::InvalidPlan(
+                "Provide either a goal or a plan to explain".to_string(),
+            ))
+        }
+    };
+
+    let steps = plan_explain::explain_plan(&plan);
+    Ok(Json(PlanExplanationResponse {
+        explanation: plan_explain::render_explanation(&steps),
+        steps,
+        plan,
+    }))
+}
+
+/// Response for a submitted plan that passed validation
+#[derive(Debug, Serialize)]
+pub struct PlanValidationResponse {
+    /// Always true (invalid plans are returned as errors)
+    pub valid: bool,
+    /// Number of steps in the plan
+    pub step_count: usize,
+}
+
+/// POST /api/plan/validate - Validate a directly-submitted plan
+///
+/// Runs the same checks as plan execution without running anything. An
+/// invalid plan yields a 400 whose `errors` array lists every problem as
+/// `{ pointer, message }`, with `pointer` a JSON pointer into the submitted plan.
+///
+/// # Returns
+/// * `Ok(Json<PlanValidationResponse>)` - If the plan is valid
+/// * `Err(AppError::PlanValidationFailed)` - With all field errors
+pub async fn validate_plan(
+    Json(plan): Json<crate::orchestrator::plan_types::Plan>,
+) -> Result<Json<PlanValidationResponse>, AppError> {
+    validate_graph(&plan).map_err(|issues| {
+        AppError::PlanValidationFailed(issues.into_iter().map(FieldError::from).collect())
+    })?;
+
+    Ok(Json(PlanValidationResponse {
+        valid: true,
+        step_count: plan.steps.len(),
     }))
 }
 
 /// Phase 6.4: Settings Panel - Get current config
 /// GET /api/config
-pub async fn get_config() -> Json<OrchestratorConfig> {
-    Json(OrchestratorConfig::default())
+pub async fn get_config(State((state, _, _)): State<RouterState>) -> Json<OrchestratorConfig> {
+    Json(state.read().await.orchestrator_config.clone())
 }
 
 /// Phase 6.4: Settings Panel - Update config
 /// POST /api/config
 ///
-/// Note: This updates the default config. For a production system,
-/// config should be persisted (e.g., in a database or config file).
+/// Applies the update on top of the active config and saves the result, so
+/// it is restored on restart. Invalid updates leave the active config
+/// unchanged.
 pub async fn update_config(
+    State((state, _, _)): State<RouterState>,
     Json(request): Json<ConfigUpdateRequest>,
 ) -> Result<Json<OrchestratorConfig>, AppError> {
-    let config = OrchestratorConfig::default();
+    let mut state = state.write().await;
 
     // Validate and apply updates using the helper function
-    let updated_config = validate_and_apply_config_update(config, request)?;
-
-    // TODO: Persist config to database or config file
-    // For now, this just validates and returns the updated config
-    // The actual OrchestratorConfig::default() is still used in other endpoints
+    let updated_config =
+        validate_and_apply_config_update(state.orchestrator_config.clone(), request)?;
+    state.set_orchestrator_config(updated_config.clone())?;
 
     Ok(Json(updated_config))
 }
 
+/// JSON Schema of the config, for building settings forms
+/// GET /api/config/schema
+///
+/// Types, bounds and defaults match what `POST /api/config` accepts.
+pub async fn get_config_schema() -> Json<serde_json::Value> {
+    Json(config_schema())
+}
+
+/// Reset config to defaults
+/// POST /api/config/reset
+///
+/// Restores `OrchestratorConfig::default()` as the active (and saved) config
+/// and returns it.
+pub async fn reset_config(
+    State((state, _, _)): State<RouterState>,
+) -> Result<Json<OrchestratorConfig>, AppError> {
+    let config = OrchestratorConfig::default();
+    state
+        .write()
+        .await
+        .set_orchestrator_config(config.clone())?;
+    tracing::info!("Orchestrator config reset to defaults");
+    Ok(Json(config))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::api::utils::RouterState;
     use crate::chat::ChatDb;
+    use crate::services::working_dir::{WorkingDir, WorkingDirRoots};
     use crate::state::AppState;
     use std::sync::Arc;
     use tempfile::TempDir;
@@ -526,6 +1607,8 @@ mod tests {
 
     async fn create_test_router_state() -> RouterState {
         let app_state = Arc::new(RwLock::new(AppState::new()));
+        // Test plans use the ping task instead of calling Gemini
+        app_state.write().await.orchestrator_config.enable_ping_task = true;
         let temp_dir = TempDir::new().unwrap();
         let db_path = temp_dir.path().join("test.db");
         let chat_db = ChatDb::new(db_path.to_str().unwrap())
@@ -542,11 +1625,18 @@ mod tests {
         let router_state = create_test_router_state().await;
         let request = OrchestrationRequest {
             goal: "Write a test poem".to_string(),
+            allow_over_budget: false,
+            timeout_secs: None,
         };
 
         // This will fail if Gemini CLI is not available, but we can at least
         // test that the endpoint structure is correct
-        let result = orchestrate_poem(State(router_state), Json(request)).await;
+        let result = orchestrate_poem(
+            State(router_state),
+            Query(StreamProtocolQuery::default()),
+            Json(request),
+        )
+        .await;
 
         // Should return Ok(Response) even if Gemini fails internally
         // The response should be an SSE stream
@@ -575,9 +1665,16 @@ mod tests {
         let router_state = create_test_router_state().await;
         let request = OrchestrationRequest {
             goal: String::new(),
+            allow_over_budget: false,
+            timeout_secs: None,
         };
 
-        let result = orchestrate_poem(State(router_state), Json(request)).await;
+        let result = orchestrate_poem(
+            State(router_state),
+            Query(StreamProtocolQuery::default()),
+            Json(request),
+        )
+        .await;
 
         // Should return SSE response (even if Gemini fails)
         assert!(result.is_ok());
@@ -603,6 +1700,415 @@ mod tests {
         assert!(json_str.contains("\"status\":\"running\""));
     }
 
+    #[tokio::test]
+    async fn test_orchestrate_appends_audit_entry() {
+        let router_state = create_test_router_state().await;
+        let chat_db = router_state.1.clone();
+        let temp_dir = TempDir::new().unwrap();
+        add_planner_mock(
+            &router_state,
+            &temp_dir,
+            &serde_json::json!({
+                "steps": [{"id": "step_1", "task": "ping", "params": {}}]
+            }),
+        )
+        .await;
+
+        let run = |goal: &str| {
+            orchestrate(
+                State(router_state.clone()),
+                Query(StreamProtocolQuery::default()),
+                Query(OrchestrateQuery::default()),
+                Json(OrchestrationRequest {
+                    goal: goal.to_string(),
+                    allow_over_budget: false,
+                    timeout_secs: None,
+                }),
+            )
+        };
+
+        let goal = "Ping once";
+        let response = run(goal)
+            .await
+            .expect("Endpoint should return an SSE stream");
+        // Drain the stream so the orchestration runs to completion
+        let _ = axum::body::to_bytes(response.into_body(), usize::MAX).await;
+
+        let entries = chat_db.get_audit_entries(10, None).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.goal_hash, crate::orchestrator::utils::hash_goal(goal));
+        // Raw goal text is not stored by default
+        assert!(entry.goal.is_none());
+        assert!(entry.success, "{:?}", entry.error);
+        assert!(entry.error.is_none());
+        assert!(entry.plan_hash.is_some());
+        assert_eq!(entry.step_count, 1);
+
+        // With audit_store_goal on, the raw goal is kept
+        router_state
+            .0
+            .write()
+            .await
+            .orchestrator_config
+            .audit_store_goal = true;
+        let response = run("Ping again").await.unwrap();
+        let _ = axum::body::to_bytes(response.into_body(), usize::MAX).await;
+
+        let entries = chat_db.get_audit_entries(10, None).await.unwrap();
+        assert_eq!(entries.len(), 2);
+        let stored = entries
+            .iter()
+            .find(|entry| entry.goal.is_some())
+            .expect("the second run should store its goal");
+        assert_eq!(stored.goal.as_deref(), Some("Ping again"));
+    }
+
+    #[tokio::test]
+    async fn test_orchestrate_audits_client_disconnect() {
+        let router_state = create_test_router_state().await;
+        let chat_db = router_state.1.clone();
+        let temp_dir = TempDir::new().unwrap();
+        add_planner_mock(
+            &router_state,
+            &temp_dir,
+            &serde_json::json!({
+                "steps": [{"id": "step_1", "task": "ping", "params": {"delay_ms": 5000}}]
+            }),
+        )
+        .await;
+
+        let response = orchestrate(
+            State(router_state),
+            Query(StreamProtocolQuery::default()),
+            Query(OrchestrateQuery::default()),
+            Json(OrchestrationRequest {
+                goal: "Ping slowly".to_string(),
+                allow_over_budget: false,
+                timeout_secs: None,
+            }),
+        )
+        .await
+        .unwrap();
+
+        // Read the first frames, then hang up mid-run
+        let mut body = response.into_body().into_data_stream();
+        body.next().await.unwrap().unwrap();
+        drop(body);
+
+        // The entry is written from a background task
+        let mut entries = Vec::new();
+        for _ in 0..50 {
+            entries = chat_db.get_audit_entries(10, None).await.unwrap();
+            if !entries.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert_eq!(entries.len(), 1);
+        assert!(!entries[0].success);
+        assert_eq!(
+            entries[0].error.as_deref(),
+            Some("Client disconnected before the run finished")
+        );
+    }
+
+    #[test]
+    fn test_step_complete_event_truncates_large_output() {
+        let full = "é".repeat(50_000);
+        let result = StepResult {
+            step_id: "step_1".to_string(),
+            step_number: 1,
+            task: "run_gemini".to_string(),
+            success: true,
+            output: Some(full.clone()),
+            error: None,
+            model: None,
+            usage: UsageByModel::new(),
+        };
+
+        match step_complete_event(&result, 1_000) {
+            OrchestrationEvent::StepComplete {
+                output, truncated, ..
+            } => {
+                assert!(truncated);
+                assert_eq!(output.chars().count(), 1_000);
+            }
+            other => panic!("Expected StepComplete, got: {:?}", other),
+        }
+        // The stored result keeps the full output
+        assert_eq!(result.output.as_deref(), Some(full.as_str()));
+
+        // Outputs within the limit are sent unchanged
+        match step_complete_event(&result, 50_000) {
+            OrchestrationEvent::StepComplete {
+                output, truncated, ..
+            } => {
+                assert!(!truncated);
+                assert_eq!(output, full);
+            }
+            other => panic!("Expected StepComplete, got: {:?}", other),
+        }
+    }
+
+    /// Summary of a run with the given results
+    fn summary_of(results: Vec<StepResult>) -> ExecutionSummary {
+        ExecutionSummary::new(
+            "run-1".to_string(),
+            "deadbeef".to_string(),
+            "cafef00d".to_string(),
+            results,
+            std::time::Duration::from_millis(42),
+        )
+    }
+
+    #[test]
+    fn test_execution_summary_lists_failed_steps() {
+        let result = |step_number: u32, error: Option<&str>| StepResult {
+            step_id: format!("step_{}", step_number),
+            step_number,
+            task: "run_gemini".to_string(),
+            success: error.is_none(),
+            output: error.is_none().then(|| "ok".to_string()),
+            error: error.map(str::to_string),
+            model: None,
+            usage: UsageByModel::new(),
+        };
+        let results = vec![
+            result(1, None),
+            result(2, Some("Gemini timed out")),
+            result(3, None),
+            result(4, Some("Step 4 (step_4) did not produce output")),
+        ];
+
+        let event = execution_summary_event(&summary_of(results.clone()));
+        assert_eq!(
+            event,
+            OrchestrationEvent::ExecutionSummary {
+                total_steps: 4,
+                successful_steps: 2,
+                failed: vec![
+                    FailedStep {
+                        step_id: "step_2".to_string(),
+                        step_number: 2,
+                        error: "Gemini timed out".to_string(),
+                    },
+                    FailedStep {
+                        step_id: "step_4".to_string(),
+                        step_number: 4,
+                        error: "Step 4 (step_4) did not produce output".to_string(),
+                    },
+                ],
+                usage: TokenUsage::default(),
+                usage_by_model: UsageByModel::new(),
+            }
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&serialize_event_or_fallback(&event)).unwrap();
+        assert_eq!(json["type"], "execution_summary");
+        assert_eq!(json["failed"][1]["step_id"], "step_4");
+
+        // A fully successful run has an empty failed list
+        match execution_summary_event(&summary_of(results[..1].to_vec())) {
+            OrchestrationEvent::ExecutionSummary { failed, .. } => assert!(failed.is_empty()),
+            other => panic!("Expected ExecutionSummary, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_execution_summary_breaks_usage_down_by_model() {
+        let tokens = |prompt_tokens: u64, output_tokens: u64| TokenUsage {
+            prompt_tokens,
+            output_tokens,
+            total_tokens: prompt_tokens + output_tokens,
+        };
+        let result = |step_number: u32, usage: Vec<(&str, TokenUsage)>| StepResult {
+            step_id: format!("step_{}", step_number),
+            step_number,
+            task: "run_gemini".to_string(),
+            success: true,
+            output: Some("ok".to_string()),
+            error: None,
+            model: usage.last().map(|(model, _)| model.to_string()),
+            usage: usage
+                .into_iter()
+                .map(|(model, tokens)| (model.to_string(), tokens))
+                .collect(),
+        };
+        // step_1 pinned to pro, step_2 on flash, step_3 fell back from pro to flash
+        let results = vec![
+            result(1, vec![("gemini-2.5-pro", tokens(100, 40))]),
+            result(2, vec![("gemini-2.5-flash", tokens(30, 10))]),
+            result(
+                3,
+                vec![
+                    ("gemini-2.5-pro", tokens(50, 0)),
+                    ("gemini-2.5-flash", tokens(50, 20)),
+                ],
+            ),
+        ];
+
+        let OrchestrationEvent::ExecutionSummary {
+            usage,
+            usage_by_model,
+            ..
+        } = execution_summary_event(&summary_of(results))
+        else {
+            panic!("Expected ExecutionSummary");
+        };
+
+        assert_eq!(usage_by_model.len(), 2);
+        assert_eq!(usage_by_model["gemini-2.5-pro"], tokens(150, 40));
+        assert_eq!(usage_by_model["gemini-2.5-flash"], tokens(80, 30));
+        let mut summed = TokenUsage::default();
+        for model_usage in usage_by_model.values() {
+            summed.add(model_usage);
+        }
+        assert_eq!(summed, usage);
+        assert_eq!(usage.total_tokens, 300);
+    }
+
+    #[test]
+    fn test_plan_details_event_lists_all_steps_with_dependencies() {
+        use crate::orchestrator::plan_types::{Plan, Step, StepParams};
+
+        let long_prompt = "a".repeat(200);
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                Step {
+                    id: "step_1".to_string(),
+                    task: "run_gemini".to_string(),
+                    params: StepParams {
+                        prompt: Some(long_prompt),
+                        ..Default::default()
+                    },
+                    dependencies: vec![],
+                },
+                Step {
+                    id: "step_2".to_string(),
+                    task: "run_gemini".to_string(),
+                    params: StepParams {
+                        prompt: Some("Review it".to_string()),
+                        ..Default::default()
+                    },
+                    dependencies: vec!["step_1".to_string()],
+                },
+                Step {
+                    id: "step_3".to_string(),
+                    task: "create_file".to_string(),
+                    params: StepParams {
+                        filename: Some("out.txt".to_string()),
+                        content_from: Some("step_2.output".to_string()),
+                        ..Default::default()
+                    },
+                    dependencies: vec!["step_1".to_string(), "step_2".to_string()],
+                },
+            ],
+        };
+
+        let OrchestrationEvent::PlanDetails { steps } = plan_details_event(&plan) else {
+            panic!("Expected PlanDetails");
+        };
+        let ids: Vec<&str> = steps.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["step_1", "step_2", "step_3"]);
+        assert!(steps[0].dependencies.is_empty());
+        assert_eq!(steps[1].dependencies, vec!["step_1"]);
+        assert_eq!(steps[2].dependencies, vec!["step_1", "step_2"]);
+        assert_eq!(steps[2].task, "create_file");
+        assert_eq!(
+            steps[2].params_summary,
+            "filename: out.txt, content from step_2.output"
+        );
+        // Long prompts are shortened
+        assert!(steps[0].params_summary.chars().count() < 100);
+        assert!(steps[0].params_summary.ends_with('…'));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&serialize_event_or_fallback(&plan_details_event(&plan))).unwrap();
+        assert_eq!(json["type"], "plan_details");
+        assert_eq!(json["steps"][2]["dependencies"][1], "step_2");
+    }
+
+    #[test]
+    fn test_error_events_round_trip() {
+        // Error frames must deserialize back into OrchestrationEvent
+        let events = vec![
+            OrchestrationEvent::StepError {
+                step_id: "step_1".to_string(),
+                step_number: 1,
+                task: "run_gemini".to_string(),
+                error: "Error: \"quoted\" failure".to_string(),
+            },
+            OrchestrationEvent::StepComplete {
+                step_id: "step_2".to_string(),
+                step_number: 2,
+                task: "create_file".to_string(),
+                output: "/tmp/out.txt".to_string(),
+                truncated: false,
+                model: None,
+            },
+            OrchestrationEvent::ExecutionError {
+                error: "Planning failed: boom".to_string(),
+                block_reason: None,
+            },
+            OrchestrationEvent::ExecutionError {
+                error: "Planning failed: blocked".to_string(),
+                block_reason: Some("SAFETY".to_string()),
+            },
+        ];
+
+        for event in events {
+            let json = serialize_event_or_fallback(&event);
+            let parsed: OrchestrationEvent = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed, event);
+        }
+    }
+
+    #[test]
+    fn test_execution_error_event_carries_block_reason() {
+        let blocked = AppError::PromptBlocked {
+            reason: "SAFETY".to_string(),
+        };
+        let json = serialize_event_or_fallback(&execution_error_event(
+            format!("Planning failed: {}", blocked),
+            &blocked,
+        ));
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["type"], "execution_error");
+        assert_eq!(parsed["block_reason"], "SAFETY");
+
+        // Other failures leave the field out
+        let other = AppError::Internal(anyhow::anyhow!("boom"));
+        let json = serialize_event_or_fallback(&execution_error_event("boom".to_string(), &other));
+        assert!(!json.contains("block_reason"));
+    }
+
+    #[tokio::test]
+    async fn test_format_sse_stream_error_is_execution_error_event() {
+        let stream = futures_util::stream::iter(vec![Err::<String, axum::Error>(
+            axum::Error::new(std::io::Error::other("stream broke")),
+        )]);
+
+        let frames: Vec<String> = format_sse_stream(stream, 0, 0)
+            .map(|frame| frame.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(frames.len(), 1);
+        let data = frames[0]
+            .strip_prefix("data: ")
+            .and_then(|f| f.strip_suffix("\n\n"))
+            .expect("Frame should be SSE formatted");
+        match serde_json::from_str::<OrchestrationEvent>(data).unwrap() {
+            OrchestrationEvent::ExecutionError { error, .. } => {
+                assert!(error.contains("stream broke"));
+            }
+            other => panic!("Expected ExecutionError event, got: {:?}", other),
+        }
+    }
+
     // ============================================================================
     // Config Endpoint Tests (Phase 6.4)
     // ============================================================================
@@ -610,7 +2116,8 @@ mod tests {
     #[tokio::test]
     async fn test_get_config() {
         // Test that get_config returns the default config
-        let response = get_config().await;
+        let router_state = create_test_router_state().await;
+        let response = get_config(State(router_state)).await;
         let config = response.0;
 
         // Verify default values
@@ -620,6 +2127,46 @@ mod tests {
         assert_eq!(config.max_parallel_tasks, 10);
     }
 
+    #[tokio::test]
+    async fn test_config_schema_matches_validation() {
+        let schema = get_config_schema().await.0;
+        let properties = schema["properties"].as_object().unwrap();
+        assert_eq!(properties["max_parallel_tasks"]["type"], "integer");
+        assert_eq!(properties["max_parallel_tasks"]["minimum"], 1);
+        assert_eq!(properties["max_parallel_tasks"]["default"], 10);
+
+        // Every config field is described
+        let config = serde_json::to_value(OrchestratorConfig::default()).unwrap();
+        for field in config.as_object().unwrap().keys() {
+            assert!(
+                properties.contains_key(field),
+                "{} missing from schema",
+                field
+            );
+        }
+
+        // Each field the schema requires to be positive is rejected at 0, and accepted at 1
+        for (field, property) in properties {
+            if property["minimum"] != 1 {
+                continue;
+            }
+            let zero: ConfigUpdateRequest =
+                serde_json::from_value(serde_json::json!({ field.as_str(): 0 })).unwrap();
+            assert!(
+                validate_and_apply_config_update(OrchestratorConfig::default(), zero).is_err(),
+                "{} accepted 0",
+                field
+            );
+            let one: ConfigUpdateRequest =
+                serde_json::from_value(serde_json::json!({ field.as_str(): 1 })).unwrap();
+            assert!(
+                validate_and_apply_config_update(OrchestratorConfig::default(), one).is_ok(),
+                "{} rejected 1",
+                field
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_update_config_valid() {
         // Test updating config with valid values
@@ -628,10 +2175,27 @@ mod tests {
             max_parallel_tasks: Some(5),
             gemini_model: Some("gemini-2.0-flash".to_string()),
             max_goal_length: Some(5000),
+            max_prompt_length: None,
             plan_timeout_secs: Some(600),
+            max_chain_length: None,
+            max_event_output_chars: None,
+            model_fallbacks: None,
+            prompt_denylist: None,
+            output_dir: None,
+            max_estimated_tokens: None,
+            max_estimated_cost: None,
+            max_actual_tokens: None,
+            audit_store_goal: None,
+            audit_store_plan: None,
+            allow_empty_gemini_response: None,
+            prefix_step_output: None,
+            max_plan_timeout_secs: None,
+            allow_unreachable_steps: None,
+            gemini_cache_ttl_secs: None,
+            planner_examples: None,
         };
 
-        let result = update_config(Json(request)).await;
+        let result = update_config(State(create_test_router_state().await), Json(request)).await;
         assert!(result.is_ok());
         let config = result.unwrap().0;
 
@@ -650,10 +2214,27 @@ mod tests {
             max_parallel_tasks: Some(20),
             gemini_model: None,
             max_goal_length: None,
+            max_prompt_length: None,
             plan_timeout_secs: None,
+            max_chain_length: None,
+            max_event_output_chars: None,
+            model_fallbacks: None,
+            prompt_denylist: None,
+            output_dir: None,
+            max_estimated_tokens: None,
+            max_estimated_cost: None,
+            max_actual_tokens: None,
+            audit_store_goal: None,
+            audit_store_plan: None,
+            allow_empty_gemini_response: None,
+            prefix_step_output: None,
+            max_plan_timeout_secs: None,
+            allow_unreachable_steps: None,
+            gemini_cache_ttl_secs: None,
+            planner_examples: None,
         };
 
-        let result = update_config(Json(request)).await;
+        let result = update_config(State(create_test_router_state().await), Json(request)).await;
         assert!(result.is_ok());
         let config = result.unwrap().0;
 
@@ -665,6 +2246,72 @@ mod tests {
         assert_eq!(config.plan_timeout_secs, 300);
     }
 
+    #[tokio::test]
+    async fn test_updated_config_is_saved_and_restored() {
+        use crate::orchestrator::config::{ConfigUpdateRequest, CONFIG_FILE_NAME};
+
+        let data_dir = TempDir::new().unwrap();
+        let path = data_dir.path().join(CONFIG_FILE_NAME);
+        let router_state = create_test_router_state().await;
+        router_state.0.write().await.config_path = Some(path.clone());
+
+        let request: ConfigUpdateRequest = serde_json::from_value(serde_json::json!({
+            "gemini_model": "gemini-2.5-pro",
+            "plan_timeout_secs": 900,
+            "model_fallbacks": ["gemini-2.5-flash"],
+            "max_estimated_tokens": 5000,
+            "audit_store_goal": true,
+            "audit_store_plan": true
+        }))
+        .unwrap();
+        let Json(updated) = update_config(State(router_state.clone()), Json(request))
+            .await
+            .unwrap();
+        assert!(path.exists(), "update should write the config file");
+
+        // A restart loads the same config
+        let restored = OrchestratorConfig::load(&path);
+        assert_eq!(
+            serde_json::to_value(&restored).unwrap(),
+            serde_json::to_value(&updated).unwrap()
+        );
+        assert_eq!(restored.gemini_model, "gemini-2.5-pro");
+        assert_eq!(restored.plan_timeout_secs, 900);
+        assert_eq!(restored.max_estimated_tokens, Some(5000));
+
+        // Resetting saves the defaults too
+        reset_config(State(router_state)).await.unwrap();
+        assert_eq!(
+            OrchestratorConfig::load(&path).gemini_model,
+            "gemini-2.5-flash"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_saved_config_falls_back_to_defaults() {
+        let data_dir = TempDir::new().unwrap();
+        let defaults = serde_json::to_value(OrchestratorConfig::default()).unwrap();
+        let load = |contents: &str| {
+            let path = data_dir.path().join("config.json");
+            std::fs::write(&path, contents).unwrap();
+            serde_json::to_value(OrchestratorConfig::load(&path)).unwrap()
+        };
+
+        // Missing, truncated, and well-formed but invalid
+        assert_eq!(
+            serde_json::to_value(OrchestratorConfig::load(
+                &data_dir.path().join("missing.json")
+            ))
+            .unwrap(),
+            defaults
+        );
+        assert_eq!(load("{\"gemini_model\": \"gemini-2."), defaults);
+        assert_eq!(
+            load("{\"max_parallel_tasks\": 0, \"gemini_model\": \"x\"}"),
+            defaults
+        );
+    }
+
     #[tokio::test]
     async fn test_update_config_invalid_max_parallel_zero() {
         // Test that max_parallel_tasks = 0 is rejected
@@ -673,10 +2320,27 @@ mod tests {
             max_parallel_tasks: Some(0),
             gemini_model: None,
             max_goal_length: None,
+            max_prompt_length: None,
             plan_timeout_secs: None,
+            max_chain_length: None,
+            max_event_output_chars: None,
+            model_fallbacks: None,
+            prompt_denylist: None,
+            output_dir: None,
+            max_estimated_tokens: None,
+            max_estimated_cost: None,
+            max_actual_tokens: None,
+            audit_store_goal: None,
+            audit_store_plan: None,
+            allow_empty_gemini_response: None,
+            prefix_step_output: None,
+            max_plan_timeout_secs: None,
+            allow_unreachable_steps: None,
+            gemini_cache_ttl_secs: None,
+            planner_examples: None,
         };
 
-        let result = update_config(Json(request)).await;
+        let result = update_config(State(create_test_router_state().await), Json(request)).await;
         assert!(result.is_err());
         let error = result.unwrap_err();
         assert!(error.to_string().contains("max_parallel_tasks must be > 0"));
@@ -690,15 +2354,221 @@ mod tests {
             max_parallel_tasks: None,
             gemini_model: Some(String::new()),
             max_goal_length: None,
+            max_prompt_length: None,
             plan_timeout_secs: None,
+            max_chain_length: None,
+            max_event_output_chars: None,
+            model_fallbacks: None,
+            prompt_denylist: None,
+            output_dir: None,
+            max_estimated_tokens: None,
+            max_estimated_cost: None,
+            max_actual_tokens: None,
+            audit_store_goal: None,
+            audit_store_plan: None,
+            allow_empty_gemini_response: None,
+            prefix_step_output: None,
+            max_plan_timeout_secs: None,
+            allow_unreachable_steps: None,
+            gemini_cache_ttl_secs: None,
+            planner_examples: None,
         };
 
-        let result = update_config(Json(request)).await;
+        let result = update_config(State(create_test_router_state().await), Json(request)).await;
         assert!(result.is_err());
         let error = result.unwrap_err();
         assert!(error.to_string().contains("gemini_model cannot be empty"));
     }
 
+    #[tokio::test]
+    async fn test_update_config_invalid_empty_fallback_model() {
+        // Test that blank fallback model names are rejected
+        use crate::orchestrator::config::ConfigUpdateRequest;
+        let request = ConfigUpdateRequest {
+            max_parallel_tasks: None,
+            gemini_model: None,
+            max_goal_length: None,
+            max_prompt_length: None,
+            plan_timeout_secs: None,
+            max_chain_length: None,
+            max_event_output_chars: None,
+            model_fallbacks: Some(vec!["gemini-2.0-flash".to_string(), " ".to_string()]),
+            prompt_denylist: None,
+            output_dir: None,
+            max_estimated_tokens: None,
+            max_estimated_cost: None,
+            max_actual_tokens: None,
+            audit_store_goal: None,
+            audit_store_plan: None,
+            allow_empty_gemini_response: None,
+            prefix_step_output: None,
+            max_plan_timeout_secs: None,
+            allow_unreachable_steps: None,
+            gemini_cache_ttl_secs: None,
+            planner_examples: None,
+        };
+
+        let result = update_config(State(create_test_router_state().await), Json(request)).await;
+        assert!(result.is_err());
+        let error = result.unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("model_fallbacks cannot contain empty model names"));
+    }
+
+    #[tokio::test]
+    async fn test_update_config_invalid_prompt_denylist_regex() {
+        // Test that denylist patterns must be valid regexes
+        use crate::orchestrator::config::ConfigUpdateRequest;
+        let request = ConfigUpdateRequest {
+            max_parallel_tasks: None,
+            gemini_model: None,
+            max_goal_length: None,
+            max_prompt_length: None,
+            plan_timeout_secs: None,
+            max_chain_length: None,
+            max_event_output_chars: None,
+            model_fallbacks: None,
+            prompt_denylist: Some(vec!["password".to_string(), "(unclosed".to_string()]),
+            output_dir: None,
+            max_estimated_tokens: None,
+            max_estimated_cost: None,
+            max_actual_tokens: None,
+            audit_store_goal: None,
+            audit_store_plan: None,
+            allow_empty_gemini_response: None,
+            prefix_step_output: None,
+            max_plan_timeout_secs: None,
+            allow_unreachable_steps: None,
+            gemini_cache_ttl_secs: None,
+            planner_examples: None,
+        };
+
+        let result = update_config(State(create_test_router_state().await), Json(request)).await;
+        assert!(result.is_err());
+        let error = result.unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("prompt_denylist contains an invalid regex '(unclosed'"));
+    }
+
+    #[tokio::test]
+    async fn test_update_config_rejects_output_dir_outside_working_dir() {
+        use crate::orchestrator::config::ConfigUpdateRequest;
+        let request = |output_dir: &str| ConfigUpdateRequest {
+            max_parallel_tasks: None,
+            gemini_model: None,
+            max_goal_length: None,
+            max_prompt_length: None,
+            plan_timeout_secs: None,
+            max_chain_length: None,
+            max_event_output_chars: None,
+            model_fallbacks: None,
+            prompt_denylist: None,
+            output_dir: Some(output_dir.to_string()),
+            max_estimated_tokens: None,
+            max_estimated_cost: None,
+            max_actual_tokens: None,
+            audit_store_goal: None,
+            audit_store_plan: None,
+            allow_empty_gemini_response: None,
+            prefix_step_output: None,
+            max_plan_timeout_secs: None,
+            allow_unreachable_steps: None,
+            gemini_cache_ttl_secs: None,
+            planner_examples: None,
+        };
+
+        let router_state = create_test_router_state().await;
+        for output_dir in ["/etc", "../outside", "generated/../../outside"] {
+            let result =
+                update_config(State(router_state.clone()), Json(request(output_dir))).await;
+            assert!(
+                matches!(result, Err(AppError::InvalidPath(_))),
+                "{}",
+                output_dir
+            );
+        }
+        assert_eq!(
+            router_state.0.read().await.orchestrator_config.output_dir,
+            None
+        );
+
+        update_config(
+            State(router_state.clone()),
+            Json(request("generated/reports")),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            router_state
+                .0
+                .read()
+                .await
+                .orchestrator_config
+                .output_dir
+                .as_deref(),
+            Some("generated/reports")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_config_rejects_invalid_planner_example() {
+        // Example plans must themselves be valid plans
+        use crate::orchestrator::config::{ConfigUpdateRequest, PlannerExample};
+        let plan: Plan = serde_json::from_value(serde_json::json!({
+            "steps": [
+                {"id": "step_1", "task": "create_file", "params": {"filename": "out.txt", "content_from": "step_9.output"}, "dependencies": []}
+            ]
+        }))
+        .unwrap();
+        let request = ConfigUpdateRequest {
+            max_parallel_tasks: None,
+            gemini_model: None,
+            max_goal_length: None,
+            max_prompt_length: None,
+            plan_timeout_secs: None,
+            max_chain_length: None,
+            max_event_output_chars: None,
+            model_fallbacks: None,
+            prompt_denylist: None,
+            output_dir: None,
+            max_estimated_tokens: None,
+            max_estimated_cost: None,
+            max_actual_tokens: None,
+            audit_store_goal: None,
+            audit_store_plan: None,
+            allow_empty_gemini_response: None,
+            prefix_step_output: None,
+            max_plan_timeout_secs: None,
+            allow_unreachable_steps: None,
+            gemini_cache_ttl_secs: None,
+            planner_examples: Some(vec![PlannerExample {
+                goal: "Save a summary".to_string(),
+                plan,
+            }]),
+        };
+
+        let router_state = create_test_router_state().await;
+        let error = update_config(State(router_state.clone()), Json(request))
+            .await
+            .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("planner_examples[0] has an invalid plan"),
+            "got: {}",
+            error
+        );
+        assert!(router_state
+            .0
+            .read()
+            .await
+            .orchestrator_config
+            .planner_examples
+            .is_empty());
+    }
+
     #[tokio::test]
     async fn test_update_config_invalid_max_goal_zero() {
         // Test that max_goal_length = 0 is rejected
@@ -707,15 +2577,100 @@ mod tests {
             max_parallel_tasks: None,
             gemini_model: None,
             max_goal_length: Some(0),
+            max_prompt_length: None,
             plan_timeout_secs: None,
+            max_chain_length: None,
+            max_event_output_chars: None,
+            model_fallbacks: None,
+            prompt_denylist: None,
+            output_dir: None,
+            max_estimated_tokens: None,
+            max_estimated_cost: None,
+            max_actual_tokens: None,
+            audit_store_goal: None,
+            audit_store_plan: None,
+            allow_empty_gemini_response: None,
+            prefix_step_output: None,
+            max_plan_timeout_secs: None,
+            allow_unreachable_steps: None,
+            gemini_cache_ttl_secs: None,
+            planner_examples: None,
         };
 
-        let result = update_config(Json(request)).await;
+        let result = update_config(State(create_test_router_state().await), Json(request)).await;
         assert!(result.is_err());
         let error = result.unwrap_err();
         assert!(error.to_string().contains("max_goal_length must be > 0"));
     }
 
+    #[tokio::test]
+    async fn test_update_config_invalid_max_prompt_zero() {
+        // Test that max_prompt_length = 0 is rejected
+        use crate::orchestrator::config::ConfigUpdateRequest;
+        let request = ConfigUpdateRequest {
+            max_parallel_tasks: None,
+            gemini_model: None,
+            max_goal_length: None,
+            max_prompt_length: Some(0),
+            plan_timeout_secs: None,
+            max_chain_length: None,
+            max_event_output_chars: None,
+            model_fallbacks: None,
+            prompt_denylist: None,
+            output_dir: None,
+            max_estimated_tokens: None,
+            max_estimated_cost: None,
+            max_actual_tokens: None,
+            audit_store_goal: None,
+            audit_store_plan: None,
+            allow_empty_gemini_response: None,
+            prefix_step_output: None,
+            max_plan_timeout_secs: None,
+            allow_unreachable_steps: None,
+            gemini_cache_ttl_secs: None,
+            planner_examples: None,
+        };
+
+        let result = update_config(State(create_test_router_state().await), Json(request)).await;
+        assert!(result.is_err());
+        let error = result.unwrap_err();
+        assert!(error.to_string().contains("max_prompt_length must be > 0"));
+    }
+
+    #[tokio::test]
+    async fn test_update_config_invalid_max_chain_zero() {
+        // Test that max_chain_length = 0 is rejected
+        use crate::orchestrator::config::ConfigUpdateRequest;
+        let request = ConfigUpdateRequest {
+            max_parallel_tasks: None,
+            gemini_model: None,
+            max_goal_length: None,
+            max_prompt_length: None,
+            plan_timeout_secs: None,
+            max_chain_length: Some(0),
+            max_event_output_chars: None,
+            model_fallbacks: None,
+            prompt_denylist: None,
+            output_dir: None,
+            max_estimated_tokens: None,
+            max_estimated_cost: None,
+            max_actual_tokens: None,
+            audit_store_goal: None,
+            audit_store_plan: None,
+            allow_empty_gemini_response: None,
+            prefix_step_output: None,
+            max_plan_timeout_secs: None,
+            allow_unreachable_steps: None,
+            gemini_cache_ttl_secs: None,
+            planner_examples: None,
+        };
+
+        let result = update_config(State(create_test_router_state().await), Json(request)).await;
+        assert!(result.is_err());
+        let error = result.unwrap_err();
+        assert!(error.to_string().contains("max_chain_length must be > 0"));
+    }
+
     #[tokio::test]
     async fn test_update_config_invalid_timeout_zero() {
         // Test that plan_timeout_secs = 0 is rejected
@@ -724,12 +2679,1157 @@ mod tests {
             max_parallel_tasks: None,
             gemini_model: None,
             max_goal_length: None,
+            max_prompt_length: None,
             plan_timeout_secs: Some(0),
+            max_chain_length: None,
+            max_event_output_chars: None,
+            model_fallbacks: None,
+            prompt_denylist: None,
+            output_dir: None,
+            max_estimated_tokens: None,
+            max_estimated_cost: None,
+            max_actual_tokens: None,
+            audit_store_goal: None,
+            audit_store_plan: None,
+            allow_empty_gemini_response: None,
+            prefix_step_output: None,
+            max_plan_timeout_secs: None,
+            allow_unreachable_steps: None,
+            gemini_cache_ttl_secs: None,
+            planner_examples: None,
         };
 
-        let result = update_config(Json(request)).await;
+        let result = update_config(State(create_test_router_state().await), Json(request)).await;
         assert!(result.is_err());
         let error = result.unwrap_err();
         assert!(error.to_string().contains("plan_timeout_secs must be > 0"));
     }
+
+    #[tokio::test]
+    async fn test_update_config_is_applied_and_reset_restores_defaults() {
+        use crate::orchestrator::config::ConfigUpdateRequest;
+        let router_state = create_test_router_state().await;
+
+        let request = ConfigUpdateRequest {
+            max_parallel_tasks: Some(3),
+            gemini_model: Some("gemini-2.0-flash".to_string()),
+            max_goal_length: None,
+            max_prompt_length: None,
+            plan_timeout_secs: Some(60),
+            max_chain_length: None,
+            max_event_output_chars: None,
+            model_fallbacks: None,
+            prompt_denylist: None,
+            output_dir: None,
+            max_estimated_tokens: None,
+            max_estimated_cost: None,
+            max_actual_tokens: None,
+            audit_store_goal: None,
+            audit_store_plan: None,
+            allow_empty_gemini_response: None,
+            prefix_step_output: None,
+            max_plan_timeout_secs: None,
+            allow_unreachable_steps: None,
+            gemini_cache_ttl_secs: None,
+            planner_examples: None,
+        };
+        update_config(State(router_state.clone()), Json(request))
+            .await
+            .unwrap();
+
+        let updated = get_config(State(router_state.clone())).await.0;
+        assert_eq!(updated.max_parallel_tasks, 3);
+        assert_eq!(updated.gemini_model, "gemini-2.0-flash");
+
+        let restored = reset_config(State(router_state.clone())).await.unwrap().0;
+        let current = get_config(State(router_state)).await.0;
+        let defaults = OrchestratorConfig::default();
+
+        for config in [restored, current] {
+            assert_eq!(config.gemini_model, defaults.gemini_model);
+            assert_eq!(config.max_parallel_tasks, defaults.max_parallel_tasks);
+            assert_eq!(config.max_goal_length, defaults.max_goal_length);
+            assert_eq!(config.max_prompt_length, defaults.max_prompt_length);
+            assert_eq!(config.plan_timeout_secs, defaults.plan_timeout_secs);
+            assert_eq!(config.max_chain_length, defaults.max_chain_length);
+            assert_eq!(config.audit_store_goal, defaults.audit_store_goal);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate_plan_returns_pointer_errors() {
+        use axum::response::IntoResponse;
+
+        let plan: crate::orchestrator::plan_types::Plan = serde_json::from_value(serde_json::json!({
+            "steps": [
+                {"id": "step_1", "task": "run_gemini", "params": {"prompt": "Hi"}, "dependencies": []},
+                {"id": "step_2", "task": "create_file", "params": {"filename": "a.txt", "content_from": "step_1.output"}, "dependencies": ["step_7"]}
+            ]
+        }))
+        .unwrap();
+
+        let error = validate_plan(Json(plan)).await.unwrap_err();
+        let response = error.into_response();
+        assert_eq!(response.status(), axum::http::StatusCode::BAD_REQUEST);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let pointers: Vec<&str> = json["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["pointer"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            pointers,
+            vec!["/steps/1/dependencies/0", "/steps/1/params/content_from"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_orchestrate_rejects_when_execution_slots_full() {
+        use crate::orchestrator::execution_limiter::{ExecutionLimiter, QueuePolicy};
+        use axum::response::IntoResponse;
+
+        let router_state = create_test_router_state().await;
+        let limiter = ExecutionLimiter::new(1, QueuePolicy::Reject);
+        router_state.0.write().await.execution_limiter = limiter.clone();
+
+        // One orchestration already running
+        let _running = limiter.acquire().await.unwrap();
+
+        let request = OrchestrationRequest {
+            goal: "Write a haiku".to_string(),
+            allow_over_budget: false,
+            timeout_secs: None,
+        };
+        let error = orchestrate(
+            State(router_state),
+            Query(StreamProtocolQuery::default()),
+            Query(OrchestrateQuery::default()),
+            Json(request),
+        )
+        .await
+        .expect_err("N+1th execution should be rejected");
+        assert!(matches!(error, AppError::TooManyRequests(_)));
+        assert_eq!(
+            error.into_response().status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+
+    #[tokio::test]
+    async fn test_orchestrate_queues_when_execution_slots_full() {
+        use crate::orchestrator::execution_limiter::{ExecutionLimiter, QueuePolicy};
+
+        let router_state = create_test_router_state().await;
+        let limiter = ExecutionLimiter::new(1, QueuePolicy::Queue);
+        router_state.0.write().await.execution_limiter = limiter.clone();
+
+        let running = limiter.acquire().await.unwrap();
+
+        let queued = tokio::spawn(async move {
+            let request = OrchestrationRequest {
+                goal: "Write a haiku".to_string(),
+                allow_over_budget: false,
+                timeout_secs: None,
+            };
+            orchestrate(
+                State(router_state),
+                Query(StreamProtocolQuery::default()),
+                Query(OrchestrateQuery::default()),
+                Json(request),
+            )
+            .await
+            .map(|response| response.status())
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!queued.is_finished(), "N+1th execution should wait");
+
+        drop(running);
+        let status = tokio::time::timeout(std::time::Duration::from_secs(1), queued)
+            .await
+            .expect("queued execution should start once a slot frees")
+            .unwrap()
+            .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        // The permit moved into the (now dropped) stream has been released
+        assert_eq!(limiter.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_orchestrate_stream_starts_with_default_protocol() {
+        let router_state = create_test_router_state().await;
+        let request = OrchestrationRequest {
+            goal: "Write a haiku".to_string(),
+            allow_over_budget: false,
+            timeout_secs: None,
+        };
+
+        let response = orchestrate(
+            State(router_state),
+            Query(StreamProtocolQuery::default()),
+            Query(OrchestrateQuery::default()),
+            Json(request),
+        )
+        .await
+        .expect("Endpoint should return an SSE stream");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        let first = body
+            .split("\n\n")
+            .next()
+            .and_then(|frame| frame.strip_prefix("data: "))
+            .expect("Stream should start with an SSE frame");
+        match serde_json::from_str::<OrchestrationEvent>(first).unwrap() {
+            OrchestrationEvent::StreamStart {
+                protocol_version,
+                execution_id,
+            } => {
+                assert_eq!(protocol_version, DEFAULT_STREAM_PROTOCOL);
+                assert!(execution_id.is_some());
+            }
+            other => panic!("Expected StreamStart, got: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_orchestrate_rejects_unsupported_protocol() {
+        use axum::response::IntoResponse;
+
+        let router_state = create_test_router_state().await;
+        let request = OrchestrationRequest {
+            goal: "Write a haiku".to_string(),
+            allow_over_budget: false,
+            timeout_secs: None,
+        };
+
+        let error = orchestrate(
+            State(router_state),
+            Query(StreamProtocolQuery { protocol: Some(99) }),
+            Query(OrchestrateQuery::default()),
+            Json(request),
+        )
+        .await
+        .expect_err("Unknown protocol versions should be rejected");
+        assert!(matches!(error, AppError::UnsupportedProtocol(_)));
+        assert!(error.to_string().contains("99"));
+        assert_eq!(error.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_overrides_plan_timeout() {
+        use crate::orchestrator::graph_executor::execute_plan_with_config;
+
+        let router_state = create_test_router_state().await;
+        let request = OrchestrationRequest {
+            goal: "Ping slowly".to_string(),
+            allow_over_budget: false,
+            timeout_secs: Some(1),
+        };
+        let active_config = router_state.0.read().await.orchestrator_config.clone();
+        let config = request.apply_timeout(active_config).unwrap();
+        assert_eq!(config.plan_timeout_secs, 1);
+
+        // A plan that fits the default 300s times out under the override
+        let plan: Plan = serde_json::from_value(serde_json::json!({
+            "steps": [{"id": "step_1", "task": "ping", "params": {"delay_ms": 3000}}]
+        }))
+        .unwrap();
+        let started = std::time::Instant::now();
+        let result = execute_plan_with_config(&plan, &router_state.0, &config).await;
+        assert!(matches!(result, Err(AppError::Timeout(_))), "{:?}", result);
+        assert!(started.elapsed() < std::time::Duration::from_millis(2500));
+
+        // Without an override the configured timeout is kept
+        let request = OrchestrationRequest {
+            timeout_secs: None,
+            ..request
+        };
+        let config = request
+            .apply_timeout(OrchestratorConfig::default())
+            .unwrap();
+        assert_eq!(config.plan_timeout_secs, 300);
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_above_max_is_rejected() {
+        use axum::response::IntoResponse;
+
+        let router_state = create_test_router_state().await;
+        let max = router_state
+            .0
+            .read()
+            .await
+            .orchestrator_config
+            .max_plan_timeout_secs;
+
+        for timeout_secs in [0, max + 1] {
+            let request = OrchestrationRequest {
+                goal: "Write a haiku".to_string(),
+                allow_over_budget: false,
+                timeout_secs: Some(timeout_secs),
+            };
+            let error = orchestrate(
+                State(router_state.clone()),
+                Query(StreamProtocolQuery::default()),
+                Query(OrchestrateQuery::default()),
+                Json(request),
+            )
+            .await
+            .expect_err("Out-of-range timeouts should be rejected");
+            assert!(matches!(error, AppError::InvalidRequest(_)), "{:?}", error);
+            assert_eq!(
+                error.to_string(),
+                format!(
+                    "Invalid request: timeout_secs must be between 1 and {} (max_plan_timeout_secs), got {}",
+                    max, timeout_secs
+                )
+            );
+            assert_eq!(error.into_response().status(), StatusCode::BAD_REQUEST);
+        }
+
+        // The maximum itself is allowed
+        let request = OrchestrationRequest {
+            goal: "Write a haiku".to_string(),
+            allow_over_budget: false,
+            timeout_secs: Some(max),
+        };
+        assert!(request.apply_timeout(OrchestratorConfig::default()).is_ok());
+    }
+
+    /// Parse the JSON events of a drained SSE response (skipping `[DONE]`)
+    async fn collect_events(response: Response) -> Vec<OrchestrationEvent> {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(body.to_vec())
+            .unwrap()
+            .split("\n\n")
+            .filter_map(|frame| frame.strip_prefix("data: "))
+            .filter_map(|data| serde_json::from_str::<OrchestrationEvent>(data).ok())
+            .collect()
+    }
+
+    /// Register a planner mock that always answers with `plan`
+    ///
+    /// The mock script lives in `temp_dir`, which must outlive the test's planner calls.
+    async fn add_planner_mock(
+        router_state: &RouterState,
+        temp_dir: &TempDir,
+        plan: &serde_json::Value,
+    ) {
+        use crate::state::{Agent, AgentType};
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut planner = Agent::new(
+            "planner-mock".to_string(),
+            "Planner Mock".to_string(),
+            AgentType::Gemini,
+        );
+        let plan_path = temp_dir.path().join("planner-mock.json");
+        std::fs::write(&plan_path, plan.to_string()).unwrap();
+        let script = temp_dir.path().join("planner-mock.sh");
+        std::fs::write(
+            &script,
+            format!("#!/bin/sh\ncat '{}'\n", plan_path.display()),
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        planner.config.command = script.to_string_lossy().to_string();
+        planner.config.args = Vec::new();
+        router_state.0.write().await.add_agent(planner);
+    }
+
+    #[tokio::test]
+    async fn test_stalled_stream_closes_at_max_duration() {
+        // A producer that never yields and never finishes
+        let stream = futures_util::stream::pending::<Result<String, axum::Error>>();
+
+        let started = std::time::Instant::now();
+        let frames: Vec<String> = tokio::time::timeout(
+            std::time::Duration::from_secs(10),
+            format_sse_stream(stream, 0, 1)
+                .map(|frame| frame.unwrap())
+                .collect(),
+        )
+        .await
+        .expect("a capped stream must close on its own");
+        assert!(started.elapsed() >= std::time::Duration::from_secs(1));
+
+        assert_eq!(frames.len(), 2, "{:?}", frames);
+        let data = frames[0]
+            .strip_prefix("data: ")
+            .and_then(|f| f.strip_suffix("\n\n"))
+            .unwrap();
+        match serde_json::from_str::<OrchestrationEvent>(data).unwrap() {
+            OrchestrationEvent::ExecutionError { error, .. } => {
+                assert!(error.contains("maximum duration of 1 seconds"), "{}", error);
+            }
+            other => panic!("Expected ExecutionError event, got: {:?}", other),
+        }
+        assert_eq!(frames[1], format!("data: {}\n\n", SSE_DONE_SIGNAL));
+    }
+
+    #[tokio::test]
+    async fn test_orchestrate_stream_opens_with_retry_directive() {
+        let router_state = create_test_router_state().await;
+        router_state.0.write().await.sse_retry_ms = 1234;
+        let temp_dir = TempDir::new().unwrap();
+        add_planner_mock(
+            &router_state,
+            &temp_dir,
+            &serde_json::json!({
+                "steps": [{"id": "step_1", "task": "ping", "params": {}}]
+            }),
+        )
+        .await;
+
+        let response = orchestrate(
+            State(router_state),
+            Query(StreamProtocolQuery::default()),
+            Query(OrchestrateQuery { dry_run: true }),
+            Json(OrchestrationRequest {
+                goal: "Ping".to_string(),
+                allow_over_budget: false,
+                timeout_secs: None,
+            }),
+        )
+        .await
+        .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        assert!(body.starts_with("retry: 1234\n\ndata: "), "got: {}", body);
+        assert_eq!(body.matches("retry:").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_orchestrate_dry_run_stops_after_plan() {
+        let router_state = create_test_router_state().await;
+
+        // Planner mock: a valid plan whose step would leave a file behind
+        let temp_dir = TempDir::new().unwrap();
+        add_planner_mock(
+            &router_state,
+            &temp_dir,
+            &serde_json::json!({
+                "steps": [{"id": "step_1", "task": "create_file", "params": {"filename": "dry.txt", "content": "x"}}]
+            }),
+        )
+        .await;
+        router_state.0.write().await.set_working_directory(Some(
+            WorkingDir::new(
+                temp_dir.path().to_str().unwrap(),
+                &WorkingDirRoots::default(),
+            )
+            .unwrap(),
+        ));
+
+        let response = orchestrate(
+            State(router_state.clone()),
+            Query(StreamProtocolQuery::default()),
+            Query(OrchestrateQuery { dry_run: true }),
+            Json(OrchestrationRequest {
+                goal: "Save x to dry.txt".to_string(),
+                allow_over_budget: false,
+                timeout_secs: None,
+            }),
+        )
+        .await
+        .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let frames: Vec<String> = String::from_utf8(body.to_vec())
+            .unwrap()
+            .split("\n\n")
+            .filter_map(|frame| frame.strip_prefix("data: "))
+            .map(str::to_string)
+            .collect();
+
+        // The stream ends with [DONE] right after the plan
+        assert_eq!(frames.last().map(String::as_str), Some(SSE_DONE_SIGNAL));
+        let events: Vec<OrchestrationEvent> = frames
+            .iter()
+            .filter_map(|data| serde_json::from_str(data).ok())
+            .collect();
+        assert!(matches!(
+            events.last(),
+            Some(OrchestrationEvent::PlanDetails { .. })
+        ));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            OrchestrationEvent::PlanGenerated { step_count: 1, .. }
+        )));
+        assert!(!events.iter().any(|event| matches!(
+            event,
+            OrchestrationEvent::StepStart { .. }
+                | OrchestrationEvent::StepComplete { .. }
+                | OrchestrationEvent::ExecutionComplete { .. }
+        )));
+
+        // Nothing ran and nothing was audited
+        assert!(!temp_dir.path().join("dry.txt").exists());
+        assert!(router_state
+            .1
+            .get_audit_entries(10, None)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_estimate_plan_matches_goal_generated_plan() {
+        let router_state = create_test_router_state().await;
+        let plan_json = serde_json::json!({
+            "steps": [
+                {"id": "step_1", "task": "run_gemini", "params": {"prompt": "Outline a story"}},
+                {"id": "step_2", "task": "run_gemini", "params": {"prompt": "Write chapter one"}, "dependencies": ["step_1"]},
+                {"id": "step_3", "task": "run_gemini", "params": {"prompt": "Write chapter two"}, "dependencies": ["step_1"]},
+                {"id": "step_4", "task": "create_file", "params": {"filename": "story.txt", "content_from": "step_2"}, "dependencies": ["step_2", "step_3"]}
+            ]
+        });
+        let temp_dir = TempDir::new().unwrap();
+        add_planner_mock(&router_state, &temp_dir, &plan_json).await;
+
+        let Json(planned) = plan_with_analysis(
+            State(router_state.clone()),
+            Json(OrchestrationRequest {
+                goal: "Write a two-chapter story".to_string(),
+                allow_over_budget: false,
+                timeout_secs: None,
+            }),
+        )
+        .await
+        .expect("planner mock should produce a plan");
+        let Json(estimated) = estimate_plan(
+            State(router_state),
+            Json(serde_json::from_value(plan_json).unwrap()),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(estimated.estimated_tokens, planned.estimated_tokens);
+        assert_eq!(estimated.estimated_cost, planned.estimated_cost);
+        assert_eq!(estimated.estimated_time_secs, planned.estimated_time_secs);
+        assert_eq!(estimated.execution_levels, planned.execution_levels);
+        assert_eq!(
+            serde_json::to_value(&estimated.bottlenecks).unwrap(),
+            serde_json::to_value(&planned.bottlenecks).unwrap()
+        );
+        assert_eq!(estimated.execution_levels.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_explain_plan_from_plan_and_from_goal() {
+        let router_state = create_test_router_state().await;
+        let plan_json = serde_json::json!({
+            "steps": [
+                {"id": "step_1", "task": "run_gemini", "params": {"prompt": "Outline a story"}},
+                {"id": "step_2", "task": "run_gemini", "params": {"prompt": "Write chapter one"}, "dependencies": ["step_1"]},
+                {"id": "step_3", "task": "create_file", "params": {"filename": "story.txt", "content_from": "step_2"}, "dependencies": ["step_1", "step_2"]}
+            ]
+        });
+
+        let Json(explained) = explain_plan(
+            State(router_state.clone()),
+            Json(PlanExplainRequest {
+                goal: None,
+                plan: Some(serde_json::from_value(plan_json.clone()).unwrap()),
+            }),
+        )
+        .await
+        .unwrap();
+        let lines: Vec<&str> = explained.explanation.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("1. [run_gemini] step_1:"));
+        assert!(lines[1].contains("Runs after step 1 (step_1)"));
+        assert!(lines[2].starts_with("3. [create_file] step_3:"));
+        assert!(lines[2].contains("Runs after step 1 (step_1) and step 2 (step_2)"));
+        assert_eq!(explained.steps[2].dependencies, vec!["step_1", "step_2"]);
+
+        // A goal is planned first, then explained the same way
+        let temp_dir = TempDir::new().unwrap();
+        add_planner_mock(&router_state, &temp_dir, &plan_json).await;
+        let Json(planned) = explain_plan(
+            State(router_state.clone()),
+            Json(PlanExplainRequest {
+                goal: Some("Write a story".to_string()),
+                plan: None,
+            }),
+        )
+        .await
+        .expect("planner mock should produce a plan");
+        assert_eq!(planned.explanation, explained.explanation);
+        assert_eq!(planned.plan.steps.len(), 3);
+
+        // Exactly one of goal and plan
+        let error = explain_plan(
+            State(router_state),
+            Json(PlanExplainRequest {
+                goal: None,
+                plan: None,
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(error, AppError::InvalidPlan(_)));
+    }
+
+    #[tokio::test]
+    async fn test_estimate_plan_rejects_invalid_plan() {
+        let router_state = create_test_router_state().await;
+        let plan: Plan = serde_json::from_value(serde_json::json!({
+            "steps": [{"id": "step_1", "task": "run_gemini", "params": {}, "dependencies": ["step_0"]}]
+        }))
+        .unwrap();
+
+        let error = estimate_plan(State(router_state), Json(plan))
+            .await
+            .unwrap_err();
+        match error {
+            AppError::PlanValidationFailed(errors) => assert_eq!(errors.len(), 2),
+            other => panic!("Expected PlanValidationFailed, got: {:?}", other),
+        }
+    }
+
+    /// Record a finished execution whose plan was retained, returning its ID
+    async fn retained_ping_execution(chat_db: &ChatDb) -> String {
+        let plan: Plan = serde_json::from_value(serde_json::json!({
+            "steps": [
+                {"id": "step_1", "task": "ping", "params": {"message": "first"}},
+                {"id": "step_2", "task": "ping", "params": {"message": "second"}, "dependencies": ["step_1"]}
+            ]
+        }))
+        .unwrap();
+        let mut entry = AuditEntry::new("deadbeef".to_string(), None);
+        entry.plan_hash = Some(crate::orchestrator::utils::hash_plan(&plan));
+        entry.plan = Some(serde_json::to_string(&plan).unwrap());
+        entry.finish(false, Some("flaky".to_string()), std::time::Duration::ZERO);
+        chat_db.add_audit_entry(&entry).await.unwrap();
+        entry.id
+    }
+
+    #[tokio::test]
+    async fn test_replay_reuses_stored_plan_without_planning() {
+        use crate::state::{Agent, AgentType};
+        use std::os::unix::fs::PermissionsExt;
+
+        let router_state = create_test_router_state().await;
+        router_state
+            .0
+            .write()
+            .await
+            .orchestrator_config
+            .audit_store_plan = true;
+
+        // Planner mock: any planner call leaves a marker file behind
+        let temp_dir = TempDir::new().unwrap();
+        let marker = temp_dir.path().join("planner-called");
+        let mut planner = Agent::new(
+            "planner-mock".to_string(),
+            "Planner Mock".to_string(),
+            AgentType::Gemini,
+        );
+        let script = temp_dir.path().join("planner-mock.sh");
+        std::fs::write(
+            &script,
+            format!("#!/bin/sh\ntouch '{}'\n", marker.display()),
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        planner.config.command = script.to_string_lossy().to_string();
+        planner.config.args = Vec::new();
+        router_state.0.write().await.add_agent(planner);
+
+        // Sanity check: a normal orchestration does reach the mock
+        let request = OrchestrationRequest {
+            goal: "Write a haiku".to_string(),
+            allow_over_budget: false,
+            timeout_secs: None,
+        };
+        let response = orchestrate(
+            State(router_state.clone()),
+            Query(StreamProtocolQuery::default()),
+            Query(OrchestrateQuery::default()),
+            Json(request),
+        )
+        .await
+        .unwrap();
+        let _ = collect_events(response).await;
+        assert!(
+            marker.exists(),
+            "planner mock should be called by /orchestrate"
+        );
+        std::fs::remove_file(&marker).unwrap();
+
+        let original_id = retained_ping_execution(&router_state.1).await;
+        let response = replay_execution(
+            State(router_state.clone()),
+            Path(original_id.clone()),
+            Query(StreamProtocolQuery::default()),
+            Query(ReplayQuery::default()),
+        )
+        .await
+        .expect("Replay should stream");
+        let events = collect_events(response).await;
+        assert!(!marker.exists(), "replay must not call the planner");
+
+        // A fresh execution ID is announced up front
+        let new_id = match &events[0] {
+            OrchestrationEvent::StreamStart {
+                execution_id: Some(id),
+                ..
+            } => id.clone(),
+            other => panic!(
+                "Expected StreamStart with an execution id, got: {:?}",
+                other
+            ),
+        };
+        assert_ne!(new_id, original_id);
+
+        // The stored plan ran as-is
+        assert!(events.iter().any(|event| matches!(
+            event,
+            OrchestrationEvent::ExecutionComplete { total_steps: 2, .. }
+        )));
+
+        // The replay is audited under its own ID and is itself replayable
+        let replayed = router_state
+            .1
+            .get_audit_entry(&new_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(replayed.success);
+        assert_eq!(replayed.goal_hash, "deadbeef");
+        assert!(replayed.plan.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_stored_results_match_the_streamed_summary() {
+        let router_state = create_test_router_state().await;
+        router_state
+            .0
+            .write()
+            .await
+            .orchestrator_config
+            .audit_store_plan = true;
+
+        // An execution that never ran its steps has no results
+        let original_id = retained_ping_execution(&router_state.1).await;
+        let missing = get_execution_results(State(router_state.clone()), Path(original_id.clone()))
+            .await
+            .unwrap_err();
+        assert!(matches!(missing, AppError::FileNotFound(_)));
+
+        let response = replay_execution(
+            State(router_state.clone()),
+            Path(original_id),
+            Query(StreamProtocolQuery::default()),
+            Query(ReplayQuery::default()),
+        )
+        .await
+        .unwrap();
+        let events = collect_events(response).await;
+        let execution_id = match &events[0] {
+            OrchestrationEvent::StreamStart {
+                execution_id: Some(id),
+                ..
+            } => id.clone(),
+            other => panic!("Expected StreamStart, got: {:?}", other),
+        };
+        let streamed = events
+            .iter()
+            .find(|event| matches!(event, OrchestrationEvent::ExecutionSummary { .. }))
+            .cloned()
+            .expect("a run whose steps ran should end with a summary");
+
+        let Json(stored) =
+            get_execution_results(State(router_state.clone()), Path(execution_id.clone()))
+                .await
+                .unwrap();
+        assert_eq!(execution_summary_event(&stored), streamed);
+
+        let audit = router_state
+            .1
+            .get_audit_entry(&execution_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.execution_id, execution_id);
+        assert_eq!(stored.goal_hash, "deadbeef");
+        assert_eq!(audit.plan_hash.as_deref(), Some(stored.plan_hash.as_str()));
+        assert_eq!(stored.duration_ms as i64, audit.duration_ms);
+        assert_eq!(
+            (stored.total_steps, stored.successful, stored.failed),
+            (2, 2, 0)
+        );
+        let outputs: Vec<Option<&str>> = stored
+            .results
+            .iter()
+            .map(|result| result.output.as_deref())
+            .collect();
+        assert_eq!(outputs, vec![Some("first"), Some("second")]);
+    }
+
+    #[tokio::test]
+    async fn test_run_gemini_progress_precedes_step_completion() {
+        use crate::state::{Agent, AgentType};
+        use std::os::unix::fs::PermissionsExt;
+
+        // A Gemini CLI stand-in streaming its answer in two parts
+        let router_state = create_test_router_state().await;
+        let temp_dir = TempDir::new().unwrap();
+        let script = temp_dir.path().join("gemini-stream-mock.sh");
+        std::fs::write(
+            &script,
+            concat!(
+                "#!/bin/sh\n",
+                "echo '{\"type\":\"init\",\"model\":\"mock-model\"}'\n",
+                "echo '{\"type\":\"message\",\"role\":\"assistant\",\"content\":\"Roses \",\"delta\":true}'\n",
+                "sleep 0.2\n",
+                "echo '{\"type\":\"message\",\"role\":\"assistant\",\"content\":\"are red\",\"delta\":true}'\n",
+                "echo '{\"type\":\"result\",\"status\":\"success\",\"stats\":{\"input_tokens\":3,\"output_tokens\":4,\"total_tokens\":7}}'\n",
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let mut gemini = Agent::new(
+            "gemini-mock".to_string(),
+            "Gemini Mock".to_string(),
+            AgentType::Gemini,
+        );
+        gemini.config.command = script.to_string_lossy().to_string();
+        gemini.config.args = Vec::new();
+        router_state.0.write().await.add_agent(gemini);
+
+        let plan: Plan = serde_json::from_value(serde_json::json!({
+            "steps": [{"id": "step_1", "task": "run_gemini", "params": {"prompt": "write a poem"}}]
+        }))
+        .unwrap();
+        let mut entry = AuditEntry::new("deadbeef".to_string(), None);
+        entry.plan_hash = Some(crate::orchestrator::utils::hash_plan(&plan));
+        entry.plan = Some(serde_json::to_string(&plan).unwrap());
+        entry.finish(false, None, std::time::Duration::ZERO);
+        router_state.1.add_audit_entry(&entry).await.unwrap();
+
+        let response = replay_execution(
+            State(router_state.clone()),
+            Path(entry.id.clone()),
+            Query(StreamProtocolQuery::default()),
+            Query(ReplayQuery::default()),
+        )
+        .await
+        .unwrap();
+        let events = collect_events(response).await;
+
+        let progress: Vec<(usize, &str)> = events
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, event)| match event {
+                OrchestrationEvent::StepProgress { step_id, chunk } if step_id == "step_1" => {
+                    Some((idx, chunk.as_str()))
+                }
+                _ => None,
+            })
+            .collect();
+        let complete_idx = events
+            .iter()
+            .position(|event| matches!(event, OrchestrationEvent::StepComplete { step_id, .. } if step_id == "step_1"))
+            .expect("step_1 should complete");
+
+        assert_eq!(
+            progress.iter().map(|(_, chunk)| *chunk).collect::<Vec<_>>(),
+            vec!["Roses ", "are red"]
+        );
+        assert!(progress.iter().all(|(idx, _)| *idx < complete_idx));
+        assert!(matches!(
+            &events[complete_idx],
+            OrchestrationEvent::StepComplete { output, .. } if output == "Roses are red"
+        ));
+    }
+
+    #[test]
+    fn test_step_line_prefixer_tracks_lines_per_step() {
+        let mut prefixer = StepLinePrefixer::default();
+        assert_eq!(prefixer.prefix("a", "one\ntw"), "[a] one\n[a] tw");
+        // Another step's chunk doesn't end a's line
+        assert_eq!(prefixer.prefix("b", "x\n"), "[b] x\n");
+        assert_eq!(prefixer.prefix("a", "o\nthree"), "o\n[a] three");
+        assert_eq!(prefixer.prefix("a", ""), "");
+        assert_eq!(prefixer.prefix("a", "\n"), "\n");
+        assert_eq!(prefixer.prefix("a", "four"), "[a] four");
+
+        // A reset starts the step's output over on a new line
+        let reset = StepProgress {
+            step_id: "a".to_string(),
+            chunk: String::new(),
+            reset: true,
+        };
+        assert!(matches!(
+            step_progress_event(reset, Some(&mut prefixer)),
+            OrchestrationEvent::StepProgressReset { step_id } if step_id == "a"
+        ));
+        assert_eq!(prefixer.prefix("a", "five"), "[a] five");
+    }
+
+    #[tokio::test]
+    async fn test_parallel_step_progress_is_prefixed_with_step_ids() {
+        use crate::state::{Agent, AgentType};
+        use std::os::unix::fs::PermissionsExt;
+
+        // A Gemini CLI stand-in streaming two lines, split mid-line, that name its prompt
+        let router_state = create_test_router_state().await;
+        router_state
+            .0
+            .write()
+            .await
+            .orchestrator_config
+            .prefix_step_output = true;
+        let temp_dir = TempDir::new().unwrap();
+        let script = temp_dir.path().join("gemini-stream-mock.sh");
+        std::fs::write(
+            &script,
+            concat!(
+                "#!/bin/sh\n",
+                "case \"$*\" in *alpha*) w=alpha ;; *) w=beta ;; esac\n",
+                "printf '%s\\n' '{\"type\":\"message\",\"role\":\"assistant\",\"content\":\"'\"$w\"' one\\n'\"$w\"' \"}'\n",
+                "sleep 0.2\n",
+                "printf '%s\\n' '{\"type\":\"message\",\"role\":\"assistant\",\"content\":\"two\\n\"}'\n",
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let mut gemini = Agent::new(
+            "gemini-mock".to_string(),
+            "Gemini Mock".to_string(),
+            AgentType::Gemini,
+        );
+        gemini.config.command = script.to_string_lossy().to_string();
+        gemini.config.args = Vec::new();
+        router_state.0.write().await.add_agent(gemini);
+
+        // Two independent steps, so they stream at the same time
+        let plan: Plan = serde_json::from_value(serde_json::json!({
+            "steps": [
+                {"id": "step_a", "task": "run_gemini", "params": {"prompt": "say alpha"}},
+                {"id": "step_b", "task": "run_gemini", "params": {"prompt": "say beta"}}
+            ]
+        }))
+        .unwrap();
+        let mut entry = AuditEntry::new("deadbeef".to_string(), None);
+        entry.plan = Some(serde_json::to_string(&plan).unwrap());
+        entry.finish(false, None, std::time::Duration::ZERO);
+        router_state.1.add_audit_entry(&entry).await.unwrap();
+
+        let response = replay_execution(
+            State(router_state.clone()),
+            Path(entry.id.clone()),
+            Query(StreamProtocolQuery::default()),
+            Query(ReplayQuery::default()),
+        )
+        .await
+        .unwrap();
+        let events = collect_events(response).await;
+
+        for (step, word) in [("step_a", "alpha"), ("step_b", "beta")] {
+            let streamed: String = events
+                .iter()
+                .filter_map(|event| match event {
+                    OrchestrationEvent::StepProgress { step_id, chunk } if step_id == step => {
+                        Some(chunk.as_str())
+                    }
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(
+                streamed,
+                format!("[{0}] {1} one\n[{0}] {1} two\n", step, word)
+            );
+            // The step's own output is left unprefixed
+            assert!(events.iter().any(|event| matches!(
+                event,
+                OrchestrationEvent::StepComplete { step_id, output, .. }
+                    if step_id == step && output.trim_end() == format!("{0} one\n{0} two", word)
+            )));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_without_retained_plan_is_not_found() {
+        use axum::response::IntoResponse;
+
+        let router_state = create_test_router_state().await;
+        let mut entry = AuditEntry::new("deadbeef".to_string(), None);
+        entry.finish(true, None, std::time::Duration::ZERO);
+        router_state.1.add_audit_entry(&entry).await.unwrap();
+
+        for execution_id in [entry.id.clone(), "unknown".to_string()] {
+            let error = replay_execution(
+                State(router_state.clone()),
+                Path(execution_id),
+                Query(StreamProtocolQuery::default()),
+                Query(ReplayQuery::default()),
+            )
+            .await
+            .expect_err("Replay needs a retained plan");
+            assert!(error.to_string().contains("No retained plan"));
+            assert_eq!(error.into_response().status(), StatusCode::NOT_FOUND);
+        }
+    }
+
+    /// Record a failed execution whose `step_1` output was retained and whose
+    /// `step_2` writes that output to `out.txt`, returning its ID
+    async fn retained_two_step_execution(chat_db: &ChatDb, step_1_output: Option<&str>) -> String {
+        let plan: Plan = serde_json::from_value(serde_json::json!({
+            "steps": [
+                {"id": "step_1", "task": "ping", "params": {"message": "fresh"}},
+                {"id": "step_2", "task": "create_file", "params": {"filename": "out.txt", "content_from": "step_1"}, "dependencies": ["step_1"]}
+            ]
+        }))
+        .unwrap();
+        let outputs: StepOutputs = step_1_output
+            .map(|output| [("step_1.output".to_string(), output.to_string())].into())
+            .unwrap_or_default();
+        let mut entry = AuditEntry::new("deadbeef".to_string(), None);
+        entry.plan = Some(serde_json::to_string(&plan).unwrap());
+        entry.step_outputs = Some(serde_json::to_string(&outputs).unwrap());
+        entry.finish(
+            false,
+            Some("disk full".to_string()),
+            std::time::Duration::ZERO,
+        );
+        chat_db.add_audit_entry(&entry).await.unwrap();
+        entry.id
+    }
+
+    #[tokio::test]
+    async fn test_run_from_step_reuses_stored_upstream_outputs() {
+        let router_state = create_test_router_state().await;
+        let temp_dir = TempDir::new().unwrap();
+        {
+            let mut state = router_state.0.write().await;
+            state.orchestrator_config.audit_store_plan = true;
+            state.set_working_directory(Some(
+                WorkingDir::new(
+                    temp_dir.path().to_str().unwrap(),
+                    &WorkingDirRoots::default(),
+                )
+                .unwrap(),
+            ));
+        }
+        let original_id = retained_two_step_execution(&router_state.1, Some("stored")).await;
+
+        let response = run_from_step(
+            State(router_state.clone()),
+            Path((original_id, "step_2".to_string())),
+            Query(StreamProtocolQuery::default()),
+            Query(ReplayQuery::default()),
+        )
+        .await
+        .expect("Restart should stream");
+        let events = collect_events(response).await;
+        assert!(events.iter().any(|event| matches!(
+            event,
+            OrchestrationEvent::ExecutionComplete { total_steps: 2, .. }
+        )));
+
+        // step_1 was not re-run: step_2 wrote its stored output
+        let written = std::fs::read_to_string(temp_dir.path().join("out.txt")).unwrap();
+        assert_eq!(written, "stored");
+
+        // The restart retains outputs of its own, so it can be restarted too
+        let new_id = match &events[0] {
+            OrchestrationEvent::StreamStart {
+                execution_id: Some(id),
+                ..
+            } => id.clone(),
+            other => panic!("Expected StreamStart, got: {:?}", other),
+        };
+        let restarted = router_state
+            .1
+            .get_audit_entry(&new_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(restarted.success);
+        let outputs: StepOutputs =
+            serde_json::from_str(restarted.step_outputs.as_deref().unwrap()).unwrap();
+        assert_eq!(
+            outputs.get("step_1.output").map(String::as_str),
+            Some("stored")
+        );
+        assert!(outputs.contains_key("step_2.output"));
+    }
+
+    #[tokio::test]
+    async fn test_run_from_step_rejects_missing_prerequisites() {
+        use axum::response::IntoResponse;
+
+        let router_state = create_test_router_state().await;
+        let original_id = retained_two_step_execution(&router_state.1, None).await;
+
+        let error = run_from_step(
+            State(router_state.clone()),
+            Path((original_id.clone(), "step_2".to_string())),
+            Query(StreamProtocolQuery::default()),
+            Query(ReplayQuery::default()),
+        )
+        .await
+        .expect_err("step_1 has no stored output");
+        assert!(error.to_string().contains("step_1"), "got: {}", error);
+        assert_eq!(error.into_response().status(), StatusCode::BAD_REQUEST);
+
+        let error = run_from_step(
+            State(router_state),
+            Path((original_id, "step_9".to_string())),
+            Query(StreamProtocolQuery::default()),
+            Query(ReplayQuery::default()),
+        )
+        .await
+        .expect_err("Unknown steps can't be restarted");
+        assert_eq!(error.into_response().status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_request_budget_rejects_over_budget_plan_unless_overridden() {
+        use crate::orchestrator::plan_types::{Plan, Step, StepParams};
+
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![Step {
+                id: "step_1".to_string(),
+                task: "run_gemini".to_string(),
+                params: StepParams {
+                    prompt: Some("Write a haiku about budgets".to_string()),
+                    ..Default::default()
+                },
+                dependencies: vec![],
+            }],
+        };
+        let estimated = estimate_token_usage(&plan);
+
+        // Within budget: proceeds
+        let mut config = OrchestratorConfig {
+            max_estimated_tokens: Some(estimated),
+            ..OrchestratorConfig::default()
+        };
+        assert!(check_request_budget(&plan, &config, false).is_ok());
+
+        // Over budget: rejected unless the request opts in
+        config.max_estimated_tokens = Some(estimated - 1);
+        let error = check_request_budget(&plan, &config, false).unwrap_err();
+        assert!(matches!(error, AppError::PolicyViolation(_)));
+        assert!(error.to_string().contains("max_estimated_tokens"));
+        assert!(check_request_budget(&plan, &config, true).is_ok());
+    }
 }
@@ -6,9 +6,18 @@
 use crate::api::utils::RouterState;
 use crate::error::AppError;
 use crate::orchestrator::plan_to_graph::build_graph_from_plan;
-use axum::{extract::State, response::Json};
+use crate::orchestrator::plan_types::Plan;
+use crate::orchestrator::plan_utils::plan_to_dot;
+use axum::{
+    extract::State,
+    http::header,
+    response::{IntoResponse, Json, Response},
+};
 use serde::Serialize;
 
+/// Content type of DOT responses
+const DOT_CONTENT_TYPE: &str = "text/vnd.graphviz; charset=utf-8";
+
 /// Graph structure representation for visualization
 #[derive(Debug, Serialize)]
 pub struct GraphStructure {
@@ -20,6 +29,8 @@ pub struct GraphStructure {
     pub task_ids: Vec<String>,
     /// Edges (dependencies) in the graph
     pub edges: Vec<GraphEdge>,
+    /// Task IDs grouped by execution level (tasks in a level run in parallel)
+    pub execution_levels: Vec<Vec<String>>,
 }
 
 /// Represents an edge (dependency) in the graph
@@ -66,15 +77,63 @@ pub async fn get_graph_structure(
         .into_iter()
         .map(|(from, to)| GraphEdge { from, to })
         .collect();
+    let execution_levels = plan
+        .execution_levels()
+        .map_err(|e| AppError::InvalidPlan(format!("Plan validation failed: {}", e)))?;
 
     Ok(Json(GraphStructure {
         graph_id: graph.id.clone(),
         task_count: task_ids.len(),
         task_ids,
         edges,
+        execution_levels,
     }))
 }
 
+/// GET /api/orchestrate/graph.dot - Render the graph for a goal as Graphviz DOT
+///
+/// # Query Parameters
+/// * `goal` - The goal string to build a plan from (passed as query param)
+///
+/// # Returns
+/// * `Ok(Response)` - A `digraph` with one node per step and one edge per dependency
+/// * `Err(AppError)` - If plan generation fails
+pub async fn get_graph_dot(
+    State((state, _, _)): State<RouterState>,
+    axum::extract::Query(params): axum::extract::Query<std::collections::HashMap<String, String>>,
+) -> Result<Response, AppError> {
+    use crate::orchestrator::primitives::internal_run_planner;
+
+    let goal = params
+        .get("goal")
+        .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Missing 'goal' query parameter")))?;
+
+    let plan = internal_run_planner(&state, goal).await?;
+    Ok(dot_response(&plan))
+}
+
+/// POST /api/orchestrate/graph.dot - Render a submitted plan as Graphviz DOT
+///
+/// # Returns
+/// * `Ok(Response)` - A `digraph` with one node per step and one edge per dependency
+/// * `Err(AppError::PlanValidationFailed)` - If the plan is invalid
+pub async fn plan_graph_dot(Json(plan): Json<Plan>) -> Result<Response, AppError> {
+    let errors = plan.field_errors();
+    if !errors.is_empty() {
+        return Err(AppError::PlanValidationFailed(errors));
+    }
+    Ok(dot_response(&plan))
+}
+
+/// Build a DOT response for a plan
+fn dot_response(plan: &Plan) -> Response {
+    (
+        [(header::CONTENT_TYPE, DOT_CONTENT_TYPE)],
+        plan_to_dot(plan),
+    )
+        .into_response()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -278,4 +337,89 @@ mod tests {
         assert!(edges.iter().any(|e| e.from == "step_2" && e.to == "step_4"));
         assert!(edges.iter().any(|e| e.from == "step_3" && e.to == "step_4"));
     }
+
+    #[tokio::test]
+    async fn test_plan_graph_dot_nodes_and_edges() {
+        use crate::orchestrator::plan_types::{Step, StepParams};
+
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                Step {
+                    id: "step_1".to_string(),
+                    task: "run_gemini".to_string(),
+                    params: StepParams {
+                        prompt: Some("Write a poem".to_string()),
+                        ..Default::default()
+                    },
+                    dependencies: vec![],
+                },
+                Step {
+                    id: "step_2".to_string(),
+                    task: "create_file".to_string(),
+                    params: StepParams {
+                        filename: Some("poem.txt".to_string()),
+                        content_from: Some("step_1.output".to_string()),
+                        ..Default::default()
+                    },
+                    dependencies: vec!["step_1".to_string()],
+                },
+            ],
+        };
+
+        let response = plan_graph_dot(Json(plan)).await.unwrap();
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            DOT_CONTENT_TYPE
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let dot = String::from_utf8(body.to_vec()).unwrap();
+
+        assert!(dot.starts_with("digraph plan {"));
+        assert!(dot.trim_end().ends_with('}'));
+        assert!(dot.contains(r#""step_1" [label="step_1\nrun_gemini"];"#));
+        assert!(dot.contains(r#""step_2" [label="step_2\ncreate_file"];"#));
+        assert!(dot.contains(r#""step_1" -> "step_2";"#));
+        assert_eq!(dot.matches("->").count(), 1);
+    }
+
+    #[test]
+    fn test_plan_to_dot_escapes_labels() {
+        use crate::orchestrator::plan_types::Step;
+
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![Step {
+                id: r#"say "hi"\now"#.to_string(),
+                task: "run_gemini".to_string(),
+                params: Default::default(),
+                dependencies: vec![],
+            }],
+        };
+
+        let dot = plan_to_dot(&plan);
+        assert!(dot.contains(r#""say \"hi\"\\now" [label="say \"hi\"\\now\nrun_gemini"];"#));
+    }
+
+    #[tokio::test]
+    async fn test_plan_graph_dot_rejects_invalid_plan() {
+        use crate::orchestrator::plan_types::{Step, StepParams};
+
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![Step {
+                id: "step_1".to_string(),
+                task: "run_gemini".to_string(),
+                params: StepParams {
+                    prompt: Some("Write a poem".to_string()),
+                    ..Default::default()
+                },
+                dependencies: vec!["missing".to_string()],
+            }],
+        };
+        let result = plan_graph_dot(Json(plan)).await;
+        assert!(matches!(result, Err(AppError::PlanValidationFailed(_))));
+    }
 }
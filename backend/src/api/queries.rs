@@ -4,20 +4,32 @@
 //! and streaming responses using Server-Sent Events (SSE).
 
 use crate::api::utils::{
-    apply_working_directory_context, create_executor, update_agent_status, validate_query,
-    RouterState,
+    acquire_agent_slot, apply_working_directory_context, create_executor, set_output_format,
+    update_agent_status, validate_extra_args, validate_query, RouterState,
 };
 use crate::chat::{Message, MessageRole};
 use crate::error::AppError;
-use crate::state::{AgentId, AgentStatus};
+use crate::executor::ExecutionError;
+use crate::orchestrator::primitives::parse_gemini_cli_response;
+use crate::services::working_dir::WorkingDir;
+use crate::state::{AgentId, AgentStatus, AgentType, AppState};
 use axum::{
     extract::{Path, State},
     response::{Json, Response},
 };
 use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Arc;
 use std::time::Instant;
+use tokio::sync::{RwLock, Semaphore};
 use uuid::Uuid;
 
+/// Default number of batch queries executed at once
+pub const DEFAULT_BATCH_QUERY_CONCURRENCY: usize = 4;
+
+/// Maximum number of queries accepted in one batch
+pub const MAX_BATCH_QUERIES: usize = 100;
+
 /// Query request
 #[derive(Deserialize)]
 pub struct QueryRequest {
@@ -25,6 +37,62 @@ pub struct QueryRequest {
     pub query: String,
     /// Optional conversation ID to associate this query with a chat conversation
     pub conversation_id: Option<String>,
+    /// Extra CLI arguments for this query only (e.g. `["--model", "gemini-2.5-pro"]`)
+    ///
+    /// Validated against an allowlist and appended after the agent's configured args.
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+    /// Directory to run this query in instead of the global working directory
+    ///
+    /// Must exist and lie inside `WORKSPACE_ROOT` when one is configured. The
+    /// global working directory is left unchanged. Used by `query_agent` only.
+    #[serde(default)]
+    pub working_dir: Option<String>,
+    /// How to shape `QueryResponse.response`: "text" or "json" (default: raw output)
+    ///
+    /// Gemini agents are run with `--output-format json` either way; "text"
+    /// returns just the answer, "json" the CLI's whole JSON reply. Output from
+    /// other agents is returned as-is for "text" and, for "json", as-is if it
+    /// is JSON, otherwise wrapped as `{"response": ...}`. Used by `query_agent`.
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// Response shape requested with `QueryRequest.format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResponseFormat {
+    /// Plain text, without the Gemini CLI's JSON envelope
+    Text,
+    /// A JSON document
+    Json,
+}
+
+impl ResponseFormat {
+    /// Parse the optional `format` field (case-insensitive)
+    fn parse(format: Option<&str>) -> Result<Option<Self>, AppError> {
+        let Some(format) = format else {
+            return Ok(None);
+        };
+        match format.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(Some(Self::Text)),
+            "json" => Ok(Some(Self::Json)),
+            _ => Err(AppError::InvalidAgentConfig(format!(
+                "Unknown format '{}' (expected \"text\" or \"json\")",
+                format
+            ))),
+        }
+    }
+
+    /// Shape an agent's raw output
+    fn apply(self, output: &str, agent_type: &AgentType) -> String {
+        match (self, agent_type) {
+            (Self::Text, AgentType::Gemini) => parse_gemini_cli_response(output).response,
+            (Self::Text, _) => output.to_string(),
+            (Self::Json, _) => serde_json::from_str::<serde_json::Value>(output.trim())
+                .unwrap_or_else(|_| serde_json::json!({ "response": output.trim() }))
+                .to_string(),
+        }
+    }
 }
 
 /// Query response
@@ -38,38 +106,204 @@ pub struct QueryResponse {
     pub execution_time_ms: u64,
 }
 
+/// A single query within a batch
+#[derive(Deserialize)]
+pub struct BatchQueryItem {
+    /// Agent to run the query with
+    pub agent_id: AgentId,
+    /// The query itself (same fields as a single query request)
+    #[serde(flatten)]
+    pub request: QueryRequest,
+}
+
+/// Batch query request
+#[derive(Deserialize)]
+pub struct BatchQueryRequest {
+    /// Queries to execute, at most `MAX_BATCH_QUERIES`
+    pub queries: Vec<BatchQueryItem>,
+}
+
+/// Outcome of one query in a batch
+#[derive(Debug, Serialize)]
+pub struct BatchQueryResult {
+    /// Agent the query was sent to
+    pub agent_id: AgentId,
+    /// Response if the query succeeded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<QueryResponse>,
+    /// Error message if the query failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Batch query response
+#[derive(Debug, Serialize)]
+pub struct BatchQueryResponse {
+    /// One result per query, in request order
+    pub results: Vec<BatchQueryResult>,
+    /// Number of queries that succeeded
+    pub succeeded: usize,
+    /// Number of queries that failed
+    pub failed: usize,
+}
+
 /// POST /api/agents/:id/query - Execute a query with the agent
 pub async fn query_agent(
     State((state, _, _)): State<RouterState>,
     Path(id): Path<AgentId>,
     Json(request): Json<QueryRequest>,
 ) -> Result<Json<QueryResponse>, AppError> {
+    run_agent_query(&state, id, request).await.map(Json)
+}
+
+/// POST /api/query/batch - Execute several queries concurrently
+///
+/// At most `batch_query_concurrency` queries run at once; the rest wait for a
+/// slot. Results are returned in request order, and one query failing does
+/// not fail the batch.
+pub async fn query_batch(
+    State((state, _, _)): State<RouterState>,
+    Json(request): Json<BatchQueryRequest>,
+) -> Result<Json<BatchQueryResponse>, AppError> {
+    if request.queries.is_empty() {
+        return Err(AppError::InvalidAgentConfig(
+            "Batch must contain at least one query".to_string(),
+        ));
+    }
+    if request.queries.len() > MAX_BATCH_QUERIES {
+        return Err(AppError::InvalidAgentConfig(format!(
+            "Batch too long ({} queries, max {})",
+            request.queries.len(),
+            MAX_BATCH_QUERIES
+        )));
+    }
+
+    let concurrency = state.read().await.batch_query_concurrency;
+    let results = run_with_concurrency_limit(request.queries, concurrency, |item| {
+        let state = state.clone();
+        async move {
+            let agent_id = item.agent_id.clone();
+            match run_agent_query(&state, item.agent_id, item.request).await {
+                Ok(response) => BatchQueryResult {
+                    agent_id,
+                    response: Some(response),
+                    error: None,
+                },
+                Err(e) => BatchQueryResult {
+                    agent_id,
+                    response: None,
+                    error: Some(e.to_string()),
+                },
+            }
+        }
+    })
+    .await;
+
+    let succeeded = results.iter().filter(|r| r.error.is_none()).count();
+    Ok(Json(BatchQueryResponse {
+        failed: results.len() - succeeded,
+        succeeded,
+        results,
+    }))
+}
+
+/// Run `f` on every item with at most `limit` futures in flight
+///
+/// Outputs are returned in the same order as `items`. A `limit` of 0 is
+/// treated as 1.
+async fn run_with_concurrency_limit<T, F, Fut>(
+    items: Vec<T>,
+    limit: usize,
+    f: F,
+) -> Vec<Fut::Output>
+where
+    F: Fn(T) -> Fut,
+    Fut: Future,
+{
+    let semaphore = Arc::new(Semaphore::new(limit.max(1)));
+    futures_util::future::join_all(items.into_iter().map(|item| {
+        let semaphore = semaphore.clone();
+        let future = f(item);
+        async move {
+            // The semaphore is never closed, so acquiring only waits
+            let _permit = semaphore.acquire_owned().await;
+            future.await
+        }
+    }))
+    .await
+}
+
+/// Execute one query with an agent, updating its status, logs and last error
+async fn run_agent_query(
+    state: &Arc<RwLock<AppState>>,
+    id: AgentId,
+    request: QueryRequest,
+) -> Result<QueryResponse, AppError> {
+    // Validate query and per-query arguments before touching agent state
+    validate_query(&request.query)?;
+    validate_extra_args(&request.extra_args)?;
+    let format = ResponseFormat::parse(request.format.as_deref())?;
+
     // Get agent and apply working directory context
-    let agent = {
+    let mut agent = {
         let state = state.read().await;
         let mut agent = state
             .agents
             .get(&id)
             .ok_or_else(|| AppError::AgentNotFound(id.clone()))?
             .clone();
-        // Apply working directory context
-        apply_working_directory_context(&mut agent, &state);
+        // Apply working directory context; a per-query directory takes precedence
+        match request.working_dir.as_deref() {
+            Some(dir) => {
+                let dir = WorkingDir::new(dir, &state.working_dir_roots())?;
+                agent.config.working_dir = Some(dir.to_string());
+            }
+            None => apply_working_directory_context(&mut agent, &state),
+        }
         agent
     };
 
-    // Validate query
-    validate_query(&request.query)?;
+    // Per-query arguments apply to this execution only
+    agent.config.args.extend(request.extra_args);
+    // A requested format is extracted from Gemini's JSON reply
+    if format.is_some() && agent.agent_type == AgentType::Gemini {
+        set_output_format(&mut agent, "json");
+    }
 
-    // Update agent status to Running
-    update_agent_status(&state, &id, AgentStatus::Running).await;
+    // Track the execution so a reset can abort it, even while it waits for a slot
+    let abort = state.write().await.track_execution(&id);
 
     // Create executor and execute query
-    let executor = create_executor(None);
-    let start = Instant::now();
+    let (output_limit, fallback_dir) = {
+        let state_read = state.read().await;
+        (
+            state_read.cli_output_limit,
+            state_read.cli_fallback_working_dir.clone(),
+        )
+    };
+    let executor = create_executor(None)
+        .with_output_limit(output_limit)
+        .with_fallback_working_dir(fallback_dir);
 
-    let result = executor.execute(&agent, &request.query).await;
+    let (result, duration) = tokio::select! {
+        biased;
+        _ = abort.notified() => {
+            // The reset already put the agent back to Idle; dropping the
+            // execution future kills the process
+            return Err(ExecutionError::Aborted(format!("agent '{}' was reset", id)).into());
+        }
+        result = async {
+            // An agent with a concurrency limit runs queries beyond it in turn;
+            // a query is only Running, and timed, once it holds a slot
+            let _slot = acquire_agent_slot(state, &agent).await;
+            update_agent_status(state, &id, AgentStatus::Running).await;
+            let start = Instant::now();
+            let result = executor.execute(&agent, &request.query).await;
+            (result, start.elapsed())
+        } => result,
+    };
+    state.write().await.finish_execution(&id, &abort);
 
-    let duration = start.elapsed();
     let execution_time_ms = duration.as_millis() as u64;
 
     // Update agent status based on result
@@ -78,16 +312,35 @@ pub async fn query_agent(
     } else {
         AgentStatus::Error
     };
-    update_agent_status(&state, &id, final_status).await;
+    update_agent_status(state, &id, final_status).await;
+
+    // Record output in the agent's log buffer and remember the failure reason
+    {
+        let mut state = state.write().await;
+        state.record_agent_execution(&id, result.as_ref().err().map(|e| e.to_string()));
+        match &result {
+            Ok(output) => {
+                for line in output.lines() {
+                    state.append_agent_log(&id, line.to_string());
+                }
+            }
+            Err(e) => {
+                state.append_agent_log(&id, format!("error: {}", e));
+            }
+        }
+    }
 
     // Convert execution error to AppError if needed
-    let response = result?;
+    let mut response = result?;
+    if let Some(format) = format {
+        response = format.apply(&response, &agent.agent_type);
+    }
 
-    Ok(Json(QueryResponse {
+    Ok(QueryResponse {
         response,
         agent_id: id,
         execution_time_ms,
-    }))
+    })
 }
 
 /// POST /api/query/stream - Stream query response using Server-Sent Events
@@ -151,8 +404,10 @@ mod tests {
     use super::*;
     use crate::api::utils::{RouterState, MAX_QUERY_LENGTH};
     use crate::chat::ChatDb;
+    use crate::services::working_dir::WorkingDirRoots;
     use crate::state::{Agent, AgentType, AppState};
     use std::sync::Arc;
+    use std::time::Duration;
     use tempfile::TempDir;
     use tokio::sync::RwLock;
 
@@ -184,6 +439,9 @@ mod tests {
         let request = QueryRequest {
             query: "".to_string(),
             conversation_id: None,
+            extra_args: vec![],
+            working_dir: None,
+            format: None,
         };
 
         let result = query_agent(
@@ -212,6 +470,9 @@ mod tests {
         let request = QueryRequest {
             query: "a".repeat(MAX_QUERY_LENGTH + 1),
             conversation_id: None,
+            extra_args: vec![],
+            working_dir: None,
+            format: None,
         };
 
         let result = query_agent(
@@ -222,4 +483,476 @@ mod tests {
         .await;
         assert!(result.is_err(), "Should fail with too long query");
     }
+
+    async fn router_state_with_echo_agent() -> RouterState {
+        let router_state = create_test_router_state().await;
+        let mut agent = Agent::new(
+            "echo-1".to_string(),
+            "Echo Agent".to_string(),
+            AgentType::Generic,
+        );
+        agent.config.command = "echo".to_string();
+        router_state.0.write().await.add_agent(agent);
+        router_state
+    }
+
+    /// Add a Gemini stand-in answering in JSON only when asked to with
+    /// `--output-format json`, and plain text (with a log line) otherwise
+    async fn add_gemini_mock(router_state: &RouterState, temp_dir: &TempDir) {
+        use std::os::unix::fs::PermissionsExt;
+
+        let script = temp_dir.path().join("gemini-mock.sh");
+        std::fs::write(
+            &script,
+            concat!(
+                "#!/bin/sh\n",
+                "case \"$*\" in\n",
+                "  *\"--output-format json\"*) echo '{\"response\": \"clean answer\", \"stats\": {}}' ;;\n",
+                "  *) echo 'Loaded cached credentials.'; echo 'raw answer' ;;\n",
+                "esac\n",
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let mut agent = Agent::new(
+            "gemini-1".to_string(),
+            "Gemini Mock".to_string(),
+            AgentType::Gemini,
+        );
+        agent.config.command = script.to_string_lossy().to_string();
+        agent.config.args = vec![];
+        router_state.0.write().await.add_agent(agent);
+    }
+
+    async fn query_with_format(
+        router_state: &RouterState,
+        agent_id: &str,
+        query: &str,
+        format: Option<&str>,
+    ) -> Result<String, AppError> {
+        let request = QueryRequest {
+            query: query.to_string(),
+            conversation_id: None,
+            extra_args: vec![],
+            working_dir: None,
+            format: format.map(str::to_string),
+        };
+        query_agent(
+            State(router_state.clone()),
+            Path(agent_id.to_string()),
+            Json(request),
+        )
+        .await
+        .map(|Json(response)| response.response)
+    }
+
+    #[tokio::test]
+    async fn test_query_agent_text_format_returns_clean_text() {
+        let router_state = router_state_with_echo_agent().await;
+        let temp_dir = TempDir::new().unwrap();
+        add_gemini_mock(&router_state, &temp_dir).await;
+
+        // Without a format the Gemini output is passed through untouched
+        let raw = query_with_format(&router_state, "gemini-1", "hi", None)
+            .await
+            .unwrap();
+        assert_eq!(raw, "Loaded cached credentials.\nraw answer\n");
+
+        let text = query_with_format(&router_state, "gemini-1", "hi", Some("text"))
+            .await
+            .unwrap();
+        assert_eq!(text, "clean answer");
+
+        // Other agents' output is already text
+        let echoed = query_with_format(&router_state, "echo-1", "hello", Some("TEXT"))
+            .await
+            .unwrap();
+        assert_eq!(echoed, "hello\n");
+    }
+
+    #[tokio::test]
+    async fn test_query_agent_json_format_returns_json_document() {
+        let router_state = router_state_with_echo_agent().await;
+        let temp_dir = TempDir::new().unwrap();
+        add_gemini_mock(&router_state, &temp_dir).await;
+
+        let gemini = query_with_format(&router_state, "gemini-1", "hi", Some("json"))
+            .await
+            .unwrap();
+        let gemini: serde_json::Value = serde_json::from_str(&gemini).unwrap();
+        assert_eq!(gemini["response"], "clean answer");
+        assert!(gemini["stats"].is_object());
+
+        // JSON output is kept, anything else is wrapped
+        let echoed = query_with_format(&router_state, "echo-1", r#"{"a":1}"#, Some("json"))
+            .await
+            .unwrap();
+        assert_eq!(echoed, r#"{"a":1}"#);
+        let wrapped = query_with_format(&router_state, "echo-1", "hello", Some("json"))
+            .await
+            .unwrap();
+        assert_eq!(wrapped, r#"{"response":"hello"}"#);
+
+        let result = query_with_format(&router_state, "echo-1", "hello", Some("yaml")).await;
+        assert!(matches!(result, Err(AppError::InvalidAgentConfig(_))));
+    }
+
+    #[tokio::test]
+    async fn test_query_agent_appends_allowed_extra_args() {
+        let router_state = router_state_with_echo_agent().await;
+        let request = QueryRequest {
+            query: "hello".to_string(),
+            conversation_id: None,
+            extra_args: vec!["--model".to_string(), "gemini-2.5-pro".to_string()],
+            working_dir: None,
+            format: None,
+        };
+
+        let Json(response) = query_agent(
+            State(router_state.clone()),
+            Path("echo-1".to_string()),
+            Json(request),
+        )
+        .await
+        .expect("echo query should succeed");
+        assert!(
+            response.response.contains("hello --model gemini-2.5-pro"),
+            "got: {}",
+            response.response
+        );
+
+        // Extra args don't persist on the stored agent
+        let state = router_state.0.read().await;
+        assert!(state.agents.get("echo-1").unwrap().config.args.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_query_agent_records_and_clears_last_error() {
+        let router_state = router_state_with_echo_agent().await;
+        let query = |router_state: RouterState| async move {
+            query_agent(
+                State(router_state),
+                Path("echo-1".to_string()),
+                Json(QueryRequest {
+                    query: "hello".to_string(),
+                    conversation_id: None,
+                    extra_args: vec![],
+                    working_dir: None,
+                    format: None,
+                }),
+            )
+            .await
+        };
+
+        // A command that doesn't exist fails to spawn
+        router_state
+            .0
+            .write()
+            .await
+            .agents
+            .get_mut("echo-1")
+            .unwrap()
+            .config
+            .command = "definitely-not-a-real-command-xyz".to_string();
+        assert!(query(router_state.clone()).await.is_err());
+        {
+            let state = router_state.0.read().await;
+            let agent = state.agents.get("echo-1").unwrap();
+            assert_eq!(agent.status, AgentStatus::Error);
+            let last_error = agent
+                .last_error
+                .as_ref()
+                .expect("failure should be recorded");
+            assert!(!last_error.message.is_empty());
+            assert!(last_error.timestamp_ms > 0);
+        }
+
+        // The next successful query clears it
+        router_state
+            .0
+            .write()
+            .await
+            .agents
+            .get_mut("echo-1")
+            .unwrap()
+            .config
+            .command = "echo".to_string();
+        assert!(query(router_state.clone()).await.is_ok());
+        let state = router_state.0.read().await;
+        let agent = state.agents.get("echo-1").unwrap();
+        assert_eq!(agent.status, AgentStatus::Idle);
+        assert!(agent.last_error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_run_with_concurrency_limit_bounds_in_flight_and_keeps_order() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Counting mock executor: tracks how many calls are in flight at once
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let outputs = run_with_concurrency_limit((0..12).collect(), 3, |n: u64| {
+            let in_flight = in_flight.clone();
+            let max_in_flight = max_in_flight.clone();
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(now, Ordering::SeqCst);
+                // Later items finish first, so order must not depend on completion
+                tokio::time::sleep(Duration::from_millis(5 * (12 - n))).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                n * 10
+            }
+        })
+        .await;
+
+        assert_eq!(outputs, (0..12).map(|n| n * 10).collect::<Vec<_>>());
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 3);
+        assert_eq!(in_flight.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_query_batch_returns_results_in_request_order() {
+        let router_state = router_state_with_echo_agent().await;
+        router_state.0.write().await.batch_query_concurrency = 2;
+        let item = |agent_id: &str, query: &str| BatchQueryItem {
+            agent_id: agent_id.to_string(),
+            request: QueryRequest {
+                query: query.to_string(),
+                conversation_id: None,
+                extra_args: vec![],
+                working_dir: None,
+                format: None,
+            },
+        };
+
+        let Json(response) = query_batch(
+            State(router_state),
+            Json(BatchQueryRequest {
+                queries: vec![
+                    item("echo-1", "first"),
+                    item("missing", "second"),
+                    item("echo-1", "third"),
+                ],
+            }),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.succeeded, 2);
+        assert_eq!(response.failed, 1);
+        let first = response.results[0].response.as_ref().unwrap();
+        assert_eq!(first.response.trim(), "first");
+        assert_eq!(response.results[1].agent_id, "missing");
+        assert!(response.results[1].error.is_some());
+        let third = response.results[2].response.as_ref().unwrap();
+        assert_eq!(third.response.trim(), "third");
+    }
+
+    #[tokio::test]
+    async fn test_query_batch_rejects_empty_and_oversized_batches() {
+        let router_state = create_test_router_state().await;
+        let result = query_batch(
+            State(router_state.clone()),
+            Json(BatchQueryRequest { queries: vec![] }),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::InvalidAgentConfig(_))));
+
+        let queries = (0..=MAX_BATCH_QUERIES)
+            .map(|_| BatchQueryItem {
+                agent_id: "echo-1".to_string(),
+                request: QueryRequest {
+                    query: "hi".to_string(),
+                    conversation_id: None,
+                    extra_args: vec![],
+                    working_dir: None,
+                    format: None,
+                },
+            })
+            .collect();
+        let result = query_batch(State(router_state), Json(BatchQueryRequest { queries })).await;
+        assert!(matches!(result, Err(AppError::InvalidAgentConfig(_))));
+    }
+
+    /// Turn the echo agent into one that prints its working directory
+    ///
+    /// Generic agents run `<command> <query> <args>`, so querying "-c" runs
+    /// `sh -c "pwd -P"`.
+    async fn use_pwd_agent(router_state: &RouterState) {
+        let mut state = router_state.0.write().await;
+        let agent = state.agents.get_mut("echo-1").unwrap();
+        agent.config.command = "sh".to_string();
+        agent.config.args = vec!["pwd -P".to_string()];
+    }
+
+    #[tokio::test]
+    async fn test_query_agent_per_query_working_dir_overrides_global() {
+        let router_state = router_state_with_echo_agent().await;
+        use_pwd_agent(&router_state).await;
+        let global_dir = TempDir::new().unwrap();
+        let query_dir = TempDir::new().unwrap();
+        let global = WorkingDir::new(
+            global_dir.path().to_str().unwrap(),
+            &WorkingDirRoots::default(),
+        )
+        .unwrap();
+        router_state
+            .0
+            .write()
+            .await
+            .set_working_directory(Some(global.clone()));
+
+        let request = QueryRequest {
+            query: "-c".to_string(),
+            conversation_id: None,
+            extra_args: vec![],
+            working_dir: Some(query_dir.path().to_string_lossy().to_string()),
+            format: None,
+        };
+        let Json(response) = query_agent(
+            State(router_state.clone()),
+            Path("echo-1".to_string()),
+            Json(request),
+        )
+        .await
+        .expect("pwd query should succeed");
+        assert_eq!(
+            std::path::PathBuf::from(response.response.trim()),
+            query_dir.path().canonicalize().unwrap()
+        );
+
+        // The override applied to this query only
+        let state = router_state.0.read().await;
+        assert_eq!(state.working_directory(), Some(&global));
+        assert!(state
+            .agents
+            .get("echo-1")
+            .unwrap()
+            .config
+            .working_dir
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_query_agent_rejects_working_dir_outside_workspace_root() {
+        let router_state = router_state_with_echo_agent().await;
+        use_pwd_agent(&router_state).await;
+        let root = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        router_state.0.write().await.workspace_root =
+            Some(root.path().to_string_lossy().to_string());
+
+        let request = QueryRequest {
+            query: "-c".to_string(),
+            conversation_id: None,
+            extra_args: vec![],
+            working_dir: Some(outside.path().to_string_lossy().to_string()),
+            format: None,
+        };
+        let result = query_agent(
+            State(router_state.clone()),
+            Path("echo-1".to_string()),
+            Json(request),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::PermissionDenied(_))));
+    }
+
+    /// Time two overlapping half-second queries to the echo agent turned sleeper
+    async fn time_overlapping_queries(max_concurrency: usize) -> Duration {
+        let router_state = router_state_with_echo_agent().await;
+        {
+            let mut state = router_state.0.write().await;
+            let agent = state.agents.get_mut("echo-1").unwrap();
+            agent.config.command = "sh".to_string();
+            agent.config.args = vec!["sleep 0.5".to_string()];
+            agent.config.max_concurrency = max_concurrency;
+        }
+
+        let query = || query_with_format(&router_state, "echo-1", "-c", None);
+        let started = Instant::now();
+        let (first, second) = tokio::join!(query(), query());
+        first.unwrap();
+        second.unwrap();
+        started.elapsed()
+    }
+
+    #[tokio::test]
+    async fn test_agent_max_concurrency_bounds_overlapping_queries() {
+        let serialized = time_overlapping_queries(1).await;
+        assert!(
+            serialized >= Duration::from_millis(1000),
+            "max_concurrency 1 should run the queries in turn, took {:?}",
+            serialized
+        );
+
+        let concurrent = time_overlapping_queries(2).await;
+        assert!(
+            concurrent < Duration::from_millis(900),
+            "max_concurrency 2 should run the queries together, took {:?}",
+            concurrent
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_waiting_for_a_slot_is_not_timed() {
+        let router_state = router_state_with_echo_agent().await;
+        {
+            let mut state = router_state.0.write().await;
+            let agent = state.agents.get_mut("echo-1").unwrap();
+            agent.config.command = "sh".to_string();
+            agent.config.args = vec!["sleep 0.5".to_string()];
+            agent.config.max_concurrency = 1;
+        }
+
+        let query = || {
+            query_agent(
+                State(router_state.clone()),
+                Path("echo-1".to_string()),
+                Json(QueryRequest {
+                    query: "-c".to_string(),
+                    conversation_id: None,
+                    extra_args: vec![],
+                    working_dir: None,
+                    format: None,
+                }),
+            )
+        };
+        let (first, second) = tokio::join!(query(), query());
+
+        // The second query waits about half a second for the first, but only
+        // its own run counts towards its execution time
+        for response in [first.unwrap().0, second.unwrap().0] {
+            assert!(
+                response.execution_time_ms < 900,
+                "execution time should exclude the wait for a slot, got {}ms",
+                response.execution_time_ms
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_agent_rejects_disallowed_extra_args() {
+        let router_state = router_state_with_echo_agent().await;
+        for extra_args in [
+            vec!["--yolo".to_string()],
+            vec!["--model".to_string(), "x; rm -rf /".to_string()],
+            vec!["stray-value".to_string()],
+        ] {
+            let request = QueryRequest {
+                query: "hello".to_string(),
+                conversation_id: None,
+                extra_args,
+                working_dir: None,
+                format: None,
+            };
+            let result = query_agent(
+                State(router_state.clone()),
+                Path("echo-1".to_string()),
+                Json(request),
+            )
+            .await;
+            assert!(matches!(result, Err(AppError::InvalidAgentConfig(_))));
+        }
+    }
 }
@@ -8,13 +8,39 @@
 //! - GeminiChat (from @google/gemini-cli-core) manages conversation history internally
 //! - Messages are also persisted to SQLite for UI display and cross-restart recovery
 //! - No manual history formatting needed - the bridge handles context automatically
+//! - A conversation's system prompt (if set) is sent along with every message
+//!
+//! `POST /api/simple-chat/stream` forwards the reply over SSE as it is produced
+//! and persists the assembled message once the producer finishes.
+//! `POST /api/chat/conversations/:id/stop` interrupts it.
 
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{
+    extract::State,
+    http::{HeaderValue, StatusCode},
+    response::Response,
+    Json,
+};
+use futures_util::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Arc;
 use tracing::{error, info, warn};
 
+use crate::api::streaming::{cap_stream_duration, sse_response, stream_duration_exceeded};
 use crate::api::utils::RouterState;
 use crate::chat::models::{Conversation, Message, MessageRole};
+use crate::chat::ChatDb;
+use crate::error::AppError;
+use crate::orchestrator::constants::{SSE_DONE_SIGNAL, SSE_ERROR_PREFIX};
+
+/// Response header carrying the conversation ID of a streamed reply
+pub const CONVERSATION_ID_HEADER: &str = "x-conversation-id";
+
+/// Final SSE payload of a reply the user stopped (sent instead of `[DONE]`)
+pub const SSE_STOPPED_SIGNAL: &str = "[STOPPED]";
+
+/// Reply chunks buffered between the bridge and the SSE stream
+const BRIDGE_CHUNK_BUFFER: usize = 32;
 
 #[allow(missing_docs)]
 #[derive(Deserialize)]
@@ -42,6 +68,25 @@ pub struct SimpleChatResponse {
     pub conversation_id: String,
 }
 
+/// Load the conversation, creating it titled after the first message if it does not exist yet
+async fn ensure_conversation(
+    chat_db: &ChatDb,
+    conversation_id: &str,
+    message: &str,
+) -> Result<Conversation, AppError> {
+    if let Some(conversation) = chat_db.get_conversation(conversation_id).await? {
+        return Ok(conversation);
+    }
+    let title = if message.chars().count() > 50 {
+        format!("{}...", message.chars().take(47).collect::<String>())
+    } else {
+        message.to_string()
+    };
+    let conversation = Conversation::new(conversation_id.to_string(), title);
+    chat_db.create_conversation(&conversation).await?;
+    Ok(conversation)
+}
+
 /// Internal function that handles the actual chat logic
 /// This is shared between JSON and multipart endpoints
 pub async fn simple_chat_internal(
@@ -56,31 +101,13 @@ pub async fn simple_chat_internal(
     let conversation_id = conversation_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
 
     // Ensure conversation exists in database
-    let conversation_exists = chat_db
-        .get_conversation(&conversation_id)
+    let conversation = ensure_conversation(chat_db, &conversation_id, &message)
         .await
         .map_err(|e| {
-            error!("Failed to check conversation existence: {}", e);
+            error!("Failed to prepare conversation: {}", e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    if conversation_exists.is_none() {
-        // Create new conversation
-        let title = if message.len() > 50 {
-            format!("{}...", &message[..47])
-        } else {
-            message.clone()
-        };
-        let conversation = Conversation::new(conversation_id.clone(), title);
-        chat_db
-            .create_conversation(&conversation)
-            .await
-            .map_err(|e| {
-                error!("Failed to create conversation: {}", e);
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
-    }
-
     info!(
         "Simple chat request received (conversation_id: {}): {}",
         conversation_id, message
@@ -101,7 +128,12 @@ pub async fn simple_chat_internal(
     // No need to format conversation history - GeminiChat handles it
     let model_name = model.as_deref();
     let response_text = bridge_manager
-        .send_message(&conversation_id, &message, model_name)
+        .send_message(
+            &conversation_id,
+            &message,
+            model_name,
+            conversation.system_prompt.as_deref(),
+        )
         .await
         .map_err(|e| {
             error!(
@@ -170,3 +202,463 @@ pub async fn simple_chat(
     )
     .await
 }
+
+/// Forward reply chunks as SSE payloads and persist the assembled reply
+///
+/// Each chunk from `producer` is yielded as soon as it arrives. When the
+/// producer finishes, the concatenated reply is saved as an assistant message
+/// and `[DONE]` is sent. If the producer fails mid-stream, the partial reply is
+/// saved with an `[ERROR] <error>` marker appended and an `[ERROR]` frame is
+/// sent instead of `[DONE]`. If `stop` resolves first, the producer is dropped,
+/// the partial reply is saved marked `stopped` and `[STOPPED]` is sent.
+///
+/// # Arguments
+/// * `producer` - Reply chunks, or an error that ends the reply
+/// * `stop` - Resolves when the user stops the reply
+/// * `chat_db` - Chat database for saving the assistant message
+/// * `conversation_id` - Conversation the reply belongs to
+pub fn stream_chat_reply(
+    producer: impl Stream<Item = Result<String, String>> + Send + 'static,
+    stop: impl Future<Output = ()> + Send + 'static,
+    chat_db: Arc<ChatDb>,
+    conversation_id: String,
+) -> impl Stream<Item = Result<String, axum::Error>> {
+    use async_stream::stream;
+
+    stream! {
+        let mut full_response = String::new();
+        let mut failure = None;
+        let mut stopped = false;
+
+        futures_util::pin_mut!(producer);
+        futures_util::pin_mut!(stop);
+        loop {
+            // Stop wins over the error the killed bridge request ends with
+            let chunk = tokio::select! {
+                biased;
+                _ = &mut stop => {
+                    stopped = true;
+                    break;
+                }
+                chunk = producer.next() => chunk,
+            };
+            match chunk {
+                Some(Ok(chunk)) => {
+                    full_response.push_str(&chunk);
+                    yield Ok(chunk);
+                }
+                Some(Err(e)) => {
+                    failure = Some(e);
+                    break;
+                }
+                None => break,
+            }
+        }
+
+        let content = match &failure {
+            None => full_response.trim_end().to_string(),
+            Some(e) => format!("{}\n\n{} {}", full_response.trim_end(), SSE_ERROR_PREFIX, e)
+                .trim_start()
+                .to_string(),
+        };
+
+        if !content.is_empty() {
+            let mut assistant_message = Message::new(
+                uuid::Uuid::new_v4().to_string(),
+                conversation_id.clone(),
+                MessageRole::Assistant,
+                content,
+            );
+            assistant_message.stopped = stopped;
+            // Save message (the client already has the streamed text)
+            if let Err(e) = chat_db.add_message(&assistant_message).await {
+                error!(
+                    conversation_id = %conversation_id,
+                    error = %e,
+                    "Failed to save streamed assistant message"
+                );
+            }
+        }
+
+        if stopped {
+            info!(
+                conversation_id = %conversation_id,
+                partial_len = full_response.len(),
+                "Chat reply stopped by user"
+            );
+            yield Ok(SSE_STOPPED_SIGNAL.to_string());
+            return;
+        }
+
+        match failure {
+            None => yield Ok(SSE_DONE_SIGNAL.to_string()),
+            Some(e) => {
+                warn!(
+                    conversation_id = %conversation_id,
+                    error = %e,
+                    partial_len = full_response.len(),
+                    "Chat reply interrupted"
+                );
+                yield Ok(format!("{} {}", SSE_ERROR_PREFIX, e));
+            }
+        }
+    }
+}
+
+/// The bridge's reply to `message`, in the pieces it produces them
+///
+/// Ends with an error if the request fails. A bridge that sends no chunks
+/// yields its whole reply as one piece.
+fn bridge_reply_chunks(
+    bridge_manager: Arc<crate::chat::BridgeManager>,
+    conversation_id: String,
+    message: String,
+    model: Option<String>,
+    system_prompt: Option<String>,
+) -> impl Stream<Item = Result<String, String>> + Send + 'static {
+    use async_stream::stream;
+
+    stream! {
+        let (chunk_tx, mut chunk_rx) = tokio::sync::mpsc::channel(BRIDGE_CHUNK_BUFFER);
+        let mut streamed = false;
+        let result = {
+            let send = bridge_manager.send_message_streaming(
+                &conversation_id,
+                &message,
+                model.as_deref(),
+                system_prompt.as_deref(),
+                &chunk_tx,
+            );
+            tokio::pin!(send);
+            loop {
+                // `yield` can't sit inside `select!`, so pick the outcome first
+                let chunk = tokio::select! {
+                    Some(chunk) = chunk_rx.recv() => chunk,
+                    result = &mut send => break result,
+                };
+                streamed = true;
+                yield Ok(chunk);
+            }
+        };
+        // Chunks read just before the final response
+        while let Ok(chunk) = chunk_rx.try_recv() {
+            streamed = true;
+            yield Ok(chunk);
+        }
+        match result {
+            Ok(reply) if !streamed => yield Ok(reply),
+            Ok(_) => {}
+            Err(e) => yield Err(e),
+        }
+    }
+}
+
+/// Streaming simple chat endpoint (SSE)
+///
+/// Same flow as `simple_chat`, but the reply is sent as SSE `data:` frames
+/// ending with `[DONE]` (or `[ERROR] <error>`). The conversation ID is returned
+/// in the `X-Conversation-Id` header. Chunks are forwarded as the bridge
+/// produces them; a bridge that doesn't stream answers in one chunk. The reply
+/// can be interrupted with `POST /api/chat/conversations/:id/stop`, which ends
+/// the stream with `[STOPPED]`.
+pub async fn simple_chat_stream(
+    State((state, chat_db, bridge_manager)): State<RouterState>,
+    Json(request): Json<SimpleChatRequest>,
+) -> Result<Response, AppError> {
+    if request.message.trim().is_empty() {
+        return Err(AppError::InvalidAgentConfig(
+            "message cannot be empty".to_string(),
+        ));
+    }
+    if request.image_filenames.is_some() {
+        warn!("Image support not yet implemented in bridge approach, ignoring images");
+    }
+
+    let conversation_id = request
+        .conversation_id
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let conversation = ensure_conversation(&chat_db, &conversation_id, &request.message).await?;
+
+    let user_message = Message::new(
+        uuid::Uuid::new_v4().to_string(),
+        conversation_id.clone(),
+        MessageRole::User,
+        request.message.clone(),
+    );
+    chat_db.add_message(&user_message).await?;
+
+    info!(
+        conversation_id = %conversation_id,
+        "Streaming simple chat request received"
+    );
+
+    let stop_guard = bridge_manager.register_reply(&conversation_id);
+    let stop = async move { stop_guard.stopped().await };
+    let producer = bridge_reply_chunks(
+        bridge_manager,
+        conversation_id.clone(),
+        request.message,
+        request.model,
+        conversation.system_prompt,
+    );
+
+    let (sse_retry_ms, max_sse_duration_secs) = {
+        let state_read = state.read().await;
+        (state_read.sse_retry_ms, state_read.max_sse_duration_secs)
+    };
+    let reply = cap_stream_duration(
+        stream_chat_reply(producer, stop, chat_db, conversation_id.clone()),
+        max_sse_duration_secs,
+        vec![
+            format!(
+                "{} {}",
+                SSE_ERROR_PREFIX,
+                stream_duration_exceeded(max_sse_duration_secs)
+            ),
+            SSE_DONE_SIGNAL.to_string(),
+        ],
+    );
+    let mut response = sse_response(reply, sse_retry_ms)?;
+    if let Ok(value) = HeaderValue::from_str(&conversation_id) {
+        response.headers_mut().insert(CONVERSATION_ID_HEADER, value);
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    async fn create_test_db() -> (Arc<ChatDb>, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let chat_db = ChatDb::new(db_path.to_str().unwrap())
+            .await
+            .expect("Failed to create test database");
+        let conversation = Conversation::new("conv-1".to_string(), "Test".to_string());
+        chat_db.create_conversation(&conversation).await.unwrap();
+        (Arc::new(chat_db), temp_dir)
+    }
+
+    async fn collect_frames(
+        stream: impl Stream<Item = Result<String, axum::Error>>,
+    ) -> Vec<String> {
+        stream.map(|frame| frame.unwrap()).collect().await
+    }
+
+    #[tokio::test]
+    async fn test_system_prompt_sent_with_every_turn() {
+        let (chat_db, temp_dir) = create_test_db().await;
+        chat_db
+            .set_conversation_system_prompt("conv-1", Some("You are a pirate"))
+            .await
+            .unwrap();
+
+        // A stand-in bridge that records each request it receives
+        let log = temp_dir.path().join("requests.log");
+        let script = temp_dir.path().join("stub-bridge.sh");
+        std::fs::write(
+            &script,
+            format!(
+                "while read line; do printf '%s\\n' \"$line\" >> '{}'; \
+                 echo '{{\"status\":\"success\",\"data\":\"ok\"}}'; done\n",
+                log.display()
+            ),
+        )
+        .unwrap();
+        let bridge_manager = crate::chat::BridgeManager::with_command("sh", script);
+
+        for message in ["Hello", "Where is the treasure?"] {
+            let response = simple_chat_internal(
+                message.to_string(),
+                Some("conv-1".to_string()),
+                None,
+                None,
+                &chat_db,
+                &bridge_manager,
+            )
+            .await
+            .unwrap();
+            assert_eq!(response.0.response, "ok");
+        }
+
+        let requests: Vec<serde_json::Value> = std::fs::read_to_string(&log)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(requests.len(), 2);
+        for request in &requests {
+            assert_eq!(request["system_prompt"], "You are a pirate");
+        }
+        assert_eq!(requests[1]["content"], "Where is the treasure?");
+
+        // Turns don't disturb the stored prompt
+        let conversation = chat_db.get_conversation("conv-1").await.unwrap().unwrap();
+        assert_eq!(
+            conversation.system_prompt.as_deref(),
+            Some("You are a pirate")
+        );
+        bridge_manager.kill_all_processes().await;
+    }
+
+    #[tokio::test]
+    async fn test_stream_chat_reply_forwards_chunks_and_persists() {
+        let (chat_db, _temp_dir) = create_test_db().await;
+        let producer = futures_util::stream::iter(vec![
+            Ok("Hello, ".to_string()),
+            Ok("world".to_string()),
+            Ok("!\n".to_string()),
+        ]);
+
+        let frames = collect_frames(stream_chat_reply(
+            producer,
+            std::future::pending(),
+            chat_db.clone(),
+            "conv-1".to_string(),
+        ))
+        .await;
+
+        assert_eq!(frames, vec!["Hello, ", "world", "!\n", SSE_DONE_SIGNAL]);
+
+        let messages = chat_db.get_messages("conv-1").await.unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].role, MessageRole::Assistant.as_str());
+        assert_eq!(messages[0].content, "Hello, world!");
+    }
+
+    #[tokio::test]
+    async fn test_stream_chat_reply_persists_partial_on_error() {
+        let (chat_db, _temp_dir) = create_test_db().await;
+        let producer = futures_util::stream::iter(vec![
+            Ok("Partial answer".to_string()),
+            Err("bridge process exited".to_string()),
+            Ok("never sent".to_string()),
+        ]);
+
+        let frames = collect_frames(stream_chat_reply(
+            producer,
+            std::future::pending(),
+            chat_db.clone(),
+            "conv-1".to_string(),
+        ))
+        .await;
+
+        assert_eq!(
+            frames,
+            vec![
+                "Partial answer".to_string(),
+                format!("{} bridge process exited", SSE_ERROR_PREFIX),
+            ]
+        );
+
+        let messages = chat_db.get_messages("conv-1").await.unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(
+            messages[0].content,
+            format!(
+                "Partial answer\n\n{} bridge process exited",
+                SSE_ERROR_PREFIX
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bridge_reply_chunks_follow_the_bridge() {
+        let temp_dir = TempDir::new().unwrap();
+        let streaming = temp_dir.path().join("streaming-bridge.sh");
+        std::fs::write(
+            &streaming,
+            r#"while read line; do
+  printf '%s\n' '{"status":"chunk","data":"Hel"}'
+  printf '%s\n' '{"status":"chunk","data":"lo\nworld"}'
+  printf '%s\n' '{"status":"success","data":"Hello\nworld"}'
+done
+"#,
+        )
+        .unwrap();
+        let plain = temp_dir.path().join("plain-bridge.sh");
+        std::fs::write(
+            &plain,
+            r#"while read line; do printf '%s\n' '{"status":"success","data":"Hello"}'; done
+"#,
+        )
+        .unwrap();
+
+        let collect = |script: std::path::PathBuf| async move {
+            let bridge_manager = Arc::new(crate::chat::BridgeManager::with_command("sh", script));
+            let chunks: Vec<Result<String, String>> = bridge_reply_chunks(
+                bridge_manager.clone(),
+                "conv-1".to_string(),
+                "Hi".to_string(),
+                None,
+                None,
+            )
+            .collect()
+            .await;
+            bridge_manager.kill_all_processes().await;
+            chunks
+        };
+
+        // Streamed pieces are passed on as they are; the final reply isn't repeated
+        assert_eq!(
+            collect(streaming).await,
+            vec![Ok("Hel".to_string()), Ok("lo\nworld".to_string())]
+        );
+        // A bridge that doesn't stream answers in one piece
+        assert_eq!(collect(plain).await, vec![Ok("Hello".to_string())]);
+    }
+
+    #[test]
+    fn test_multiline_chunks_keep_sse_framing() {
+        use crate::api::streaming::sse_data_frame;
+
+        assert_eq!(sse_data_frame("Hello"), "data: Hello\n\n");
+        assert_eq!(
+            sse_data_frame("line 1\n\nline 3\r\n"),
+            "data: line 1\ndata: \ndata: line 3\ndata: \n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_conversation_title_truncates_on_char_boundaries() {
+        let (chat_db, _temp_dir) = create_test_db().await;
+        let message = "é".repeat(60);
+
+        let conversation = ensure_conversation(&chat_db, "conv-2", &message)
+            .await
+            .unwrap();
+        assert_eq!(conversation.title, format!("{}...", "é".repeat(47)));
+    }
+
+    #[tokio::test]
+    async fn test_stop_mid_generation_ends_stream_and_persists_partial() {
+        let (chat_db, _temp_dir) = create_test_db().await;
+        let bridge_manager = crate::chat::BridgeManager::new();
+        let guard = bridge_manager.register_reply("conv-1");
+        // A reply that never finishes on its own
+        let producer = futures_util::stream::iter(vec![Ok("Partial answer".to_string())])
+            .chain(futures_util::stream::pending());
+
+        let mut stream = Box::pin(stream_chat_reply(
+            producer,
+            async move { guard.stopped().await },
+            chat_db.clone(),
+            "conv-1".to_string(),
+        ));
+        assert_eq!(stream.next().await.unwrap().unwrap(), "Partial answer");
+
+        assert!(bridge_manager.stop_reply("conv-1").await.unwrap());
+        let rest: Vec<String> = stream.map(|frame| frame.unwrap()).collect().await;
+        assert_eq!(rest, vec![SSE_STOPPED_SIGNAL]);
+
+        let messages = chat_db.get_messages("conv-1").await.unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].content, "Partial answer");
+        assert!(messages[0].stopped);
+
+        // The registration ends with the stream
+        assert!(!bridge_manager.stop_reply("conv-1").await.unwrap());
+    }
+}
@@ -20,6 +20,121 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+/// Default reconnect delay advertised to SSE clients, in milliseconds
+pub const DEFAULT_SSE_RETRY_MS: u64 = 3_000;
+
+/// Default longest time an SSE stream may stay open, in seconds
+pub const DEFAULT_MAX_SSE_DURATION_SECS: u64 = 2 * 60 * 60;
+
+/// SSE `retry:` directive telling clients how long to wait before reconnecting
+///
+/// Returns `None` when `retry_ms` is 0 (no directive, clients use their default).
+pub fn sse_retry_frame(retry_ms: u64) -> Option<String> {
+    (retry_ms > 0).then(|| format!("retry: {}\n\n", retry_ms))
+}
+
+/// Message sent when a stream is closed for exceeding its maximum duration
+pub fn stream_duration_exceeded(max_duration_secs: u64) -> String {
+    format!(
+        "Stream exceeded the maximum duration of {} seconds",
+        max_duration_secs
+    )
+}
+
+/// End `stream` once it has been open for `max_duration_secs` (0 = no cap)
+///
+/// When the cap is hit, `timeout_events` are sent in place of the rest of
+/// the stream, which is dropped along with whatever work was producing it.
+/// This catches producers that stall where no other timeout applies.
+pub fn cap_stream_duration(
+    stream: impl Stream<Item = Result<String, axum::Error>> + Send + 'static,
+    max_duration_secs: u64,
+    timeout_events: Vec<String>,
+) -> impl Stream<Item = Result<String, axum::Error>> + Send + 'static {
+    use async_stream::stream;
+
+    stream! {
+        let deadline = async {
+            match max_duration_secs {
+                0 => std::future::pending().await,
+                secs => tokio::time::sleep(std::time::Duration::from_secs(secs)).await,
+            }
+        };
+        tokio::pin!(deadline);
+        tokio::pin!(stream);
+        loop {
+            // `yield` can't sit inside `select!`, so pick the outcome first
+            let next = tokio::select! {
+                item = stream.next() => Some(item),
+                _ = &mut deadline => None,
+            };
+            match next {
+                Some(Some(item)) => yield item,
+                Some(None) => break,
+                None => {
+                    tracing::warn!(
+                        max_duration_secs,
+                        "SSE stream hit its maximum duration, closing it"
+                    );
+                    for event in timeout_events {
+                        yield Ok(event);
+                    }
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// SSE frame carrying `data`, one `data:` line per line of it
+///
+/// Clients join the lines back together with `\n`, so payloads containing
+/// line breaks arrive intact instead of ending the frame early.
+pub fn sse_data_frame(data: &str) -> String {
+    let mut frame = String::with_capacity(data.len() + 8);
+    for line in data.replace("\r\n", "\n").split(['\n', '\r']) {
+        frame.push_str("data: ");
+        frame.push_str(line);
+        frame.push('\n');
+    }
+    frame.push('\n');
+    frame
+}
+
+/// Wrap a stream of event payloads in an SSE HTTP response
+///
+/// Each item is sent as a `data: <payload>` frame (see `sse_data_frame`);
+/// stream errors are sent as `data: [ERROR] <error>` frames. The stream opens
+/// with a `retry:` directive unless `retry_ms` is 0.
+///
+/// # Arguments
+/// * `stream` - Stream of event payloads
+/// * `retry_ms` - Reconnect delay advertised to the client (0 = none)
+///
+/// # Returns
+/// * `Result<Response, AppError>` - SSE HTTP response or error
+pub fn sse_response(
+    stream: impl Stream<Item = Result<String, axum::Error>> + Send + 'static,
+    retry_ms: u64,
+) -> Result<Response, AppError> {
+    let retry = futures_util::stream::iter(sse_retry_frame(retry_ms).map(Ok));
+    let sse_stream = retry.chain(stream.map(|event_result| {
+        let sse_text = match event_result {
+            Ok(data) => sse_data_frame(&data),
+            Err(e) => sse_data_frame(&format!("{} {}", SSE_ERROR_PREFIX, e)),
+        };
+        Ok::<_, std::io::Error>(sse_text)
+    }));
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/event-stream")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .header(header::CONNECTION, "keep-alive")
+        .body(Body::from_stream(sse_stream))
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to build SSE response: {}", e)))
+}
+
 /// Create an SSE stream from a streaming executor
 ///
 /// # Arguments
@@ -27,6 +142,7 @@ use uuid::Uuid;
 /// * `agent` - Agent to execute
 /// * `query` - Query string
 /// * `app_state` - Application state
+/// * `retry_ms` - Reconnect delay advertised to the client (0 = none)
 ///
 /// # Returns
 /// * `Result<Response, AppError>` - SSE HTTP response or error
@@ -36,24 +152,11 @@ pub fn create_sse_stream(
     agent: Agent,
     query: String,
     app_state: Arc<RwLock<AppState>>,
+    retry_ms: u64,
 ) -> Result<Response, AppError> {
     let stream = create_stream(executor, agent, query, app_state);
 
-    let sse_stream = stream.map(|event_result| {
-        let sse_text = match event_result {
-            Ok(data) => format!("data: {}\n\n", data),
-            Err(e) => format!("data: {} {}\n\n", SSE_ERROR_PREFIX, e),
-        };
-        Ok::<_, std::io::Error>(sse_text)
-    });
-
-    Response::builder()
-        .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, "text/event-stream")
-        .header(header::CACHE_CONTROL, "no-cache")
-        .header(header::CONNECTION, "keep-alive")
-        .body(Body::from_stream(sse_stream))
-        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to build SSE response: {}", e)))
+    sse_response(stream, retry_ms)
 }
 
 /// Create a stream from executor results
@@ -109,6 +212,7 @@ fn create_stream(
 /// * `app_state` - Application state
 /// * `chat_db` - Chat database for saving messages
 /// * `conversation_id` - Optional conversation ID to save assistant message
+/// * `retry_ms` - Reconnect delay advertised to the client (0 = none)
 ///
 /// # Returns
 /// * `Result<Response, AppError>` - SSE HTTP response or error
@@ -120,30 +224,18 @@ pub fn create_sse_stream_with_chat(
     app_state: Arc<RwLock<AppState>>,
     chat_db: Arc<ChatDb>,
     conversation_id: Option<String>,
+    retry_ms: u64,
 ) -> Result<Response, AppError> {
     let stream =
         create_stream_with_chat(executor, agent, query, app_state, chat_db, conversation_id);
 
-    let sse_stream = stream.map(|event_result| {
-        let sse_text = match event_result {
-            Ok(data) => format!("data: {}\n\n", data),
-            Err(e) => format!("data: {} {}\n\n", SSE_ERROR_PREFIX, e),
-        };
-        Ok::<_, std::io::Error>(sse_text)
-    });
-
-    Response::builder()
-        .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, "text/event-stream")
-        .header(header::CACHE_CONTROL, "no-cache")
-        .header(header::CONNECTION, "keep-alive")
-        .body(Body::from_stream(sse_stream))
-        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to build SSE response: {}", e)))
+    sse_response(stream, retry_ms)
 }
 
 /// Create a stream from executor results with chat support
 ///
 /// Collects all streamed content and saves it as an assistant message when done.
+/// The model and token usage a Gemini agent reports are logged with it.
 ///
 /// # Arguments
 /// * `executor` - Streaming executor
@@ -171,8 +263,8 @@ fn create_stream_with_chat(
         let mut full_response = String::new();
 
         // Start execution and get receiver
-        match executor.execute_streaming(&agent, &query).await {
-            Ok(mut rx) => {
+        match executor.execute_streaming_with_metadata(&agent, &query).await {
+            Ok((mut rx, metadata)) => {
                 // Stream chunks as they come and collect them
                 while let Some(chunk) = rx.recv().await {
                     full_response.push_str(&chunk);
@@ -183,6 +275,16 @@ fn create_stream_with_chat(
                 // Process completed successfully
                 update_agent_status(&app_state, &agent_id, AgentStatus::Idle).await;
 
+                // Only Gemini agents with JSON output report metadata
+                if let Ok(metadata) = metadata.await {
+                    tracing::info!(
+                        agent_id = %agent_id,
+                        model = metadata.model.as_deref().unwrap_or("unknown"),
+                        total_tokens = metadata.usage.values().map(|usage| usage.total_tokens).sum::<u64>(),
+                        "Agent reply metadata"
+                    );
+                }
+
                 // Save assistant message if conversation_id is provided
                 if let Some(conv_id) = conversation_id {
                     // Trim trailing newline from collected response
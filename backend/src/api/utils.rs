@@ -9,7 +9,7 @@ use crate::error::AppError;
 use crate::executor::CliExecutor;
 use crate::state::{Agent, AgentId, AgentStatus, AppState};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{OwnedSemaphorePermit, RwLock};
 
 /// Router state type containing AppState, ChatDb, and BridgeManager
 pub type RouterState = (Arc<RwLock<AppState>>, Arc<ChatDb>, Arc<BridgeManager>);
@@ -41,6 +41,77 @@ pub fn validate_query(query: &str) -> Result<(), AppError> {
     Ok(())
 }
 
+/// Flags callers may pass per query via `extra_args`
+pub const ALLOWED_EXTRA_ARG_FLAGS: &[&str] = &["--model", "-m", "--temperature", "--output-format"];
+
+/// Maximum number of per-query extra arguments
+pub const MAX_EXTRA_ARGS: usize = 8;
+
+/// Characters rejected in extra arguments (shell metacharacters and quotes)
+const SHELL_METACHARACTERS: &[char] = &[
+    ';', '&', '|', '`', '$', '<', '>', '(', ')', '{', '}', '\\', '"', '\'', '*', '?', '!', '\n',
+    '\r',
+];
+
+/// Validate per-query extra CLI arguments
+///
+/// Each flag must be in `ALLOWED_EXTRA_ARG_FLAGS` (as `--flag value` or
+/// `--flag=value`); bare values are only accepted right after a flag.
+/// No argument may contain shell metacharacters or control characters.
+///
+/// # Arguments
+/// * `extra_args` - Arguments to validate
+///
+/// # Returns
+/// * `Ok(())` - All arguments are allowed
+/// * `Err(AppError)` - An argument is disallowed or malformed
+pub fn validate_extra_args(extra_args: &[String]) -> Result<(), AppError> {
+    if extra_args.len() > MAX_EXTRA_ARGS {
+        return Err(AppError::InvalidAgentConfig(format!(
+            "Too many extra_args ({} > {})",
+            extra_args.len(),
+            MAX_EXTRA_ARGS
+        )));
+    }
+
+    let mut previous_was_flag = false;
+    for arg in extra_args {
+        if arg.is_empty()
+            || arg.contains(SHELL_METACHARACTERS)
+            || arg.chars().any(|c| c.is_control())
+        {
+            return Err(AppError::InvalidAgentConfig(format!(
+                "extra_args value '{}' contains disallowed characters",
+                arg.escape_default()
+            )));
+        }
+
+        if arg.starts_with('-') {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, _)) => (flag, true),
+                None => (arg.as_str(), false),
+            };
+            if !ALLOWED_EXTRA_ARG_FLAGS.contains(&flag) {
+                return Err(AppError::InvalidAgentConfig(format!(
+                    "extra_args flag '{}' is not allowed (allowed: {})",
+                    flag,
+                    ALLOWED_EXTRA_ARG_FLAGS.join(", ")
+                )));
+            }
+            previous_was_flag = !inline_value;
+        } else if previous_was_flag {
+            previous_was_flag = false;
+        } else {
+            return Err(AppError::InvalidAgentConfig(format!(
+                "extra_args value '{}' must follow an allowed flag",
+                arg
+            )));
+        }
+    }
+
+    Ok(())
+}
+
 /// Update agent status in application state
 ///
 /// # Arguments
@@ -63,7 +134,7 @@ pub async fn update_agent_status(
 /// * `state` - Application state containing working directory
 pub fn apply_working_directory_context(agent: &mut Agent, state: &AppState) {
     if let Some(dir) = state.working_directory() {
-        agent.config.working_dir = Some(dir.clone());
+        agent.config.working_dir = Some(dir.to_string());
     }
 }
 
@@ -75,54 +146,58 @@ pub fn apply_working_directory_context(agent: &mut Agent, state: &AppState) {
 /// # Returns
 /// * `CliExecutor` - Configured executor
 pub fn create_executor(config: Option<&Config>) -> CliExecutor {
-    let timeout = config
-        .map(|c| c.execution.default_timeout_secs)
-        .unwrap_or(30);
-    CliExecutor::new(timeout)
+    match config {
+        Some(c) => CliExecutor::new(c.execution.default_timeout_secs)
+            .with_output_limit(c.execution.output_limit)
+            .with_fallback_working_dir(c.execution.fallback_working_dir.clone()),
+        None => CliExecutor::new(30),
+    }
 }
 
-/// Find or create a Gemini agent specifically for the planner (with JSON output)
+/// Find or create the agent the planner runs on
 ///
-/// The planner requires JSON output format, which is different from regular Gemini tasks
-/// that return plain text. This function creates an agent with `--output-format json` flag.
+/// The planner parses the Gemini CLI's JSON reply, so a Gemini agent always
+/// gets `--output-format json`. Other agent types are left as configured:
+/// the flag is Gemini-specific, and their plain output is parsed as-is.
 ///
 /// # Arguments
 /// * `state` - Application state
 ///
 /// # Returns
-/// * `Agent` - Gemini agent configured for planner use (JSON output)
+/// * `Agent` - Agent of the default type, set up for planner use
 pub async fn find_or_create_planner_agent(state: &Arc<RwLock<AppState>>) -> Agent {
-    let mut agent = find_or_create_gemini_agent(state).await;
-
-    // Add JSON output flag for planner (only if not already present)
-    if !agent.config.args.iter().any(|arg| arg == "--output-format") {
-        agent.config.args.push("--output-format".to_string());
-        agent.config.args.push("json".to_string());
+    let mut agent = find_or_create_default_agent(state).await;
+    if matches!(agent.agent_type, crate::state::AgentType::Gemini) {
+        set_output_format(&mut agent, "json");
     }
-
     agent
 }
 
-/// Find or create a Gemini agent for general use (with JSON output format)
+/// Find or create an agent of the default type for general use
 ///
 /// Regular Gemini tasks now use `--output-format json` to ensure we get structured output
 /// with the actual content in the "response" field, not status messages in stdout.
 ///
+/// The agent type is taken from `AppState::default_agent_type` (Gemini unless the
+/// deployment configured `DEFAULT_AGENT_TYPE`). The Gemini-specific argument tweaks
+/// are only applied to Gemini agents.
+///
 /// # Arguments
 /// * `state` - Application state
 ///
 /// # Returns
-/// * `Agent` - Gemini agent (existing or newly created) with JSON output format
-pub async fn find_or_create_gemini_agent(state: &Arc<RwLock<AppState>>) -> Agent {
+/// * `Agent` - Agent of the default type (existing or newly created)
+pub async fn find_or_create_default_agent(state: &Arc<RwLock<AppState>>) -> Agent {
     let state_read = state.read().await;
-    // Try to find a Gemini agent
-    let gemini_agent = state_read
+    let agent_type = state_read.default_agent_type().clone();
+    // Try to find an agent of the default type
+    let existing_agent = state_read
         .agents
         .values()
-        .find(|a| matches!(a.agent_type, crate::state::AgentType::Gemini))
+        .find(|a| a.agent_type == agent_type)
         .cloned();
 
-    if let Some(mut agent) = gemini_agent {
+    if let Some(mut agent) = existing_agent {
         drop(state_read);
         // Apply working directory context
         let state_read = state.read().await;
@@ -137,43 +212,151 @@ pub async fn find_or_create_gemini_agent(state: &Arc<RwLock<AppState>>) -> Agent
             );
         }
 
-        // For regular Gemini tasks, we want pipe behavior (output content) not agent behavior (write files)
-        // Remove --yolo flag (which makes Gemini act as an agent) and add --output-format json
-        // This ensures the "response" field contains the actual content, not status messages
-        agent.config.args.retain(|arg| arg != "--yolo");
-        if !agent.config.args.iter().any(|arg| arg == "--output-format") {
-            agent.config.args.push("--output-format".to_string());
-            agent.config.args.push("json".to_string());
-        }
-
+        apply_gemini_pipe_args(&mut agent);
         agent
     } else {
-        // Auto-create a Gemini agent if none exists
+        // Auto-create an agent of the default type if none exists
         drop(state_read);
         let mut state_write = state.write().await;
-        let mut agent = Agent::new(
-            uuid::Uuid::new_v4().to_string(),
-            "Gemini Agent".to_string(),
-            crate::state::AgentType::Gemini,
-        );
+        let name = match agent_type {
+            crate::state::AgentType::Gemini => "Gemini Agent".to_string(),
+            ref other => format!("{} Agent", other.display_name()),
+        };
+        let mut agent = Agent::new(uuid::Uuid::new_v4().to_string(), name, agent_type);
+        // Parallel plan steps share this agent
+        agent.config.max_concurrency = state_write.orchestrator_config.max_parallel_tasks;
         // Apply working directory context
         apply_working_directory_context(&mut agent, &state_write);
-
-        // For regular Gemini tasks, we want pipe behavior (output content) not agent behavior (write files)
-        // Remove --yolo flag (which makes Gemini act as an agent) and add --output-format json
-        // This ensures the "response" field contains the actual content, not status messages
-        agent.config.args.retain(|arg| arg != "--yolo");
-        if !agent.config.args.iter().any(|arg| arg == "--output-format") {
-            agent.config.args.push("--output-format".to_string());
-            agent.config.args.push("json".to_string());
-        }
+        apply_gemini_pipe_args(&mut agent);
 
         tracing::debug!(
             agent_id = %agent.id,
+            agent_type = ?agent.agent_type,
             working_dir = ?agent.config.working_dir,
-            "Created new Gemini agent with working directory context"
+            "Created new default agent with working directory context"
         );
         state_write.add_agent(agent.clone());
         agent
     }
 }
+
+/// Wait for a free execution slot of `agent` (see `AppState::execution_slots`)
+///
+/// Hold the returned permit while the agent's process runs. It is only None
+/// if the semaphore was closed, which never happens.
+pub async fn acquire_agent_slot(
+    state: &Arc<RwLock<AppState>>,
+    agent: &Agent,
+) -> Option<OwnedSemaphorePermit> {
+    let slots = state.write().await.execution_slots(agent);
+    slots.acquire_owned().await.ok()
+}
+
+/// Configure a Gemini agent for pipe behavior (no-op for other agent types)
+///
+/// For regular Gemini tasks, we want pipe behavior (output content) not agent behavior (write files).
+/// Removes the --yolo flag (which makes Gemini act as an agent) and sets --output-format json,
+/// replacing any format the agent was configured with.
+/// This ensures the "response" field contains the actual content, not status messages.
+fn apply_gemini_pipe_args(agent: &mut Agent) {
+    if !matches!(agent.agent_type, crate::state::AgentType::Gemini) {
+        return;
+    }
+    agent.config.args.retain(|arg| arg != "--yolo");
+    // Always JSON, even if the agent was configured with another format, so
+    // every caller can parse the reply the same way
+    set_output_format(agent, "json");
+}
+
+/// Replace the value of the agent's `--output-format` argument (adding it if missing)
+pub fn set_output_format(agent: &mut Agent, format: &str) {
+    let args = &mut agent.config.args;
+    args.retain(|arg| !arg.starts_with("--output-format="));
+    match args.iter().position(|arg| arg == "--output-format") {
+        Some(flag) if flag + 1 < args.len() => args[flag + 1] = format.to_string(),
+        Some(_) => args.push(format.to_string()),
+        None => args.extend(["--output-format".to_string(), format.to_string()]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::AgentType;
+
+    #[tokio::test]
+    async fn test_find_or_create_default_agent_defaults_to_gemini() {
+        let state = Arc::new(RwLock::new(AppState::new()));
+        let agent = find_or_create_default_agent(&state).await;
+
+        assert_eq!(agent.agent_type, AgentType::Gemini);
+        assert!(!agent.config.args.contains(&"--yolo".to_string()));
+        assert!(agent.config.args.contains(&"--output-format".to_string()));
+        assert_eq!(state.read().await.agent_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_find_or_create_default_agent_uses_configured_default_type() {
+        let state = Arc::new(RwLock::new(AppState::new()));
+        state
+            .write()
+            .await
+            .set_default_agent_type(AgentType::ClaudeCode);
+
+        let agent = find_or_create_default_agent(&state).await;
+        assert_eq!(agent.agent_type, AgentType::ClaudeCode);
+        assert_eq!(agent.config.command, "claude");
+        assert!(!agent.config.args.contains(&"--output-format".to_string()));
+
+        // A second call reuses the auto-created agent
+        let again = find_or_create_default_agent(&state).await;
+        assert_eq!(again.id, agent.id);
+        assert_eq!(state.read().await.agent_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_planner_agent_only_gets_json_output_on_gemini() {
+        let state = Arc::new(RwLock::new(AppState::new()));
+        let agent = find_or_create_planner_agent(&state).await;
+        assert_eq!(agent.agent_type, AgentType::Gemini);
+        let flag = agent
+            .config
+            .args
+            .iter()
+            .position(|arg| arg == "--output-format")
+            .expect("Gemini planner should ask for JSON");
+        assert_eq!(agent.config.args[flag + 1], "json");
+
+        let state = Arc::new(RwLock::new(AppState::new()));
+        state
+            .write()
+            .await
+            .set_default_agent_type(AgentType::ClaudeCode);
+        let agent = find_or_create_planner_agent(&state).await;
+        assert_eq!(agent.agent_type, AgentType::ClaudeCode);
+        assert!(!agent
+            .config
+            .args
+            .iter()
+            .any(|arg| arg.starts_with("--output-format")));
+    }
+
+    #[test]
+    fn test_pipe_args_override_configured_output_format() {
+        for configured in [
+            vec!["--output-format", "text"],
+            vec!["--output-format=text"],
+            vec!["--yolo", "--output-format"],
+        ] {
+            let mut agent = Agent::new("g".to_string(), "Gemini".to_string(), AgentType::Gemini);
+            agent.config.args = configured.iter().map(|arg| arg.to_string()).collect();
+            apply_gemini_pipe_args(&mut agent);
+            assert_eq!(
+                agent.config.args,
+                vec!["--output-format".to_string(), "json".to_string()],
+                "configured with {:?}",
+                configured
+            );
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Version API endpoint
+//!
+//! Reports the crate version together with build metadata recorded by
+//! `build.rs` (git commit, build time and compiler version).
+
+use axum::response::Json;
+use serde::Serialize;
+
+/// Build information response
+#[derive(Debug, Serialize)]
+pub struct VersionResponse {
+    /// Crate version from Cargo.toml
+    pub version: String,
+    /// Short git commit the binary was built from ("unknown" if unavailable)
+    pub git_sha: String,
+    /// Build time as RFC 3339 ("unknown" if unavailable)
+    pub build_timestamp: String,
+    /// Output of `rustc --version` for the compiler that built the binary
+    pub rustc_version: String,
+}
+
+/// Format the `BUILD_TIMESTAMP` seconds recorded by the build script
+fn format_build_timestamp(secs: &str) -> String {
+    secs.parse::<i64>()
+        .ok()
+        .filter(|secs| *secs > 0)
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        .map(|time| time.to_rfc3339())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// GET /api/version - Crate version and build metadata
+pub async fn get_version() -> Json<VersionResponse> {
+    Json(VersionResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_sha: env!("BUILD_GIT_SHA").to_string(),
+        build_timestamp: format_build_timestamp(env!("BUILD_TIMESTAMP")),
+        rustc_version: env!("BUILD_RUSTC_VERSION").to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_version_reports_crate_version_and_build_info() {
+        let Json(response) = get_version().await;
+
+        assert_eq!(response.version, env!("CARGO_PKG_VERSION"));
+        assert!(!response.git_sha.is_empty());
+        assert!(!response.build_timestamp.is_empty());
+        assert_ne!(response.build_timestamp, "unknown");
+        assert!(
+            response.rustc_version.starts_with("rustc"),
+            "got: {}",
+            response.rustc_version
+        );
+    }
+
+    #[test]
+    fn test_format_build_timestamp() {
+        assert_eq!(format_build_timestamp("0"), "unknown");
+        assert_eq!(format_build_timestamp("garbage"), "unknown");
+        assert_eq!(
+            format_build_timestamp("1700000000"),
+            "2023-11-14T22:13:20+00:00"
+        );
+    }
+}
@@ -4,67 +4,297 @@
 //! Uses the sidecar architecture: one Node.js process per conversation that
 //! uses @google/gemini-cli-core SDK directly instead of wrapping the CLI.
 
-use super::bridge_session::BridgeSession;
+use super::bridge_session::{BridgeSession, BridgeSpawnError, NODE_COMMAND};
 use std::collections::HashMap;
+use std::future::Future;
 use std::path::PathBuf;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Notify, RwLock};
 use tracing::{debug, error, info, warn};
 
+/// Default number of times a bridge spawn is attempted before giving up
+pub const DEFAULT_BRIDGE_SPAWN_ATTEMPTS: u32 = 3;
+
+/// Default delay between bridge spawn attempts
+pub const DEFAULT_BRIDGE_SPAWN_RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// Spawn a bridge session, retrying transient failures
+///
+/// Errors that won't go away on their own (Node.js or the script missing)
+/// are returned immediately.
+///
+/// # Arguments
+/// * `conversation_id` - ID of the conversation (for logging)
+/// * `attempts` - Maximum number of spawn attempts (at least one is made)
+/// * `delay` - Pause between attempts
+/// * `spawn` - Performs one spawn attempt
+pub async fn spawn_with_retry<F, Fut>(
+    conversation_id: &str,
+    attempts: u32,
+    delay: Duration,
+    mut spawn: F,
+) -> Result<BridgeSession, BridgeSpawnError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<BridgeSession, BridgeSpawnError>>,
+{
+    let attempts = attempts.max(1);
+    let mut attempt = 1;
+    loop {
+        match spawn().await {
+            Ok(session) => return Ok(session),
+            Err(e) if e.is_transient() && attempt < attempts => {
+                warn!(
+                    conversation_id = %conversation_id,
+                    attempt,
+                    max_attempts = attempts,
+                    error = %e,
+                    "Transient bridge spawn failure, retrying"
+                );
+                attempt += 1;
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Default maximum number of bridge processes kept alive at once
+pub const DEFAULT_MAX_BRIDGE_SESSIONS: usize = 32;
+
+/// Default time a bridge may sit unused before the sweeper kills it
+pub const DEFAULT_BRIDGE_IDLE_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// Longest pause between idle sweeps, however long the timeout
+const MAX_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A live bridge session and when it was last used
+struct SessionEntry {
+    /// The bridge process
+    session: Arc<BridgeSession>,
+    /// Value of the manager's use counter at the last access (higher = more recent)
+    last_used: u64,
+    /// When the session was last handed out or finished a request
+    last_activity: Instant,
+}
+
+impl SessionEntry {
+    /// True if nothing outside the map holds the session, i.e. no request is in flight
+    fn is_idle(&self) -> bool {
+        Arc::strong_count(&self.session) == 1
+    }
+}
+
+/// Per-conversation locks serializing session creation, by conversation ID
+type CreateLocks = Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>;
+
+/// A session slot reserved under the cap while its process is spawned
+///
+/// Releases the reservation when dropped, including when the create is
+/// cancelled or fails.
+struct SlotReservation<'a>(&'a AtomicUsize);
+
+impl Drop for SlotReservation<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Stop signals of replies currently being streamed, by conversation ID
+type ReplyStops = Arc<Mutex<HashMap<String, Arc<Notify>>>>;
+
+/// Registration of a streamed reply that `BridgeManager::stop_reply` can interrupt
+///
+/// The registration is removed when the guard is dropped.
+pub struct ReplyStopGuard {
+    conversation_id: String,
+    signal: Arc<Notify>,
+    registry: ReplyStops,
+}
+
+impl ReplyStopGuard {
+    /// Resolves once the user asks to stop this reply
+    pub async fn stopped(&self) {
+        self.signal.notified().await
+    }
+}
+
+impl Drop for ReplyStopGuard {
+    fn drop(&mut self) {
+        let mut stops = self.registry.lock().unwrap_or_else(|e| e.into_inner());
+        // A newer reply in the same conversation may have replaced this one
+        if stops
+            .get(&self.conversation_id)
+            .is_some_and(|signal| Arc::ptr_eq(signal, &self.signal))
+        {
+            stops.remove(&self.conversation_id);
+        }
+    }
+}
+
 /// Manages persistent bridge processes for conversations
 ///
-/// One BridgeSession per conversation ID. Sessions are created on demand
-/// and persist for the lifetime of the conversation.
+/// One BridgeSession per conversation ID. Sessions are created on demand and
+/// kept until the conversation is deleted or, once `max_sessions` processes
+/// are alive, evicted least-recently-used first to make room for a new one.
+/// Sessions idle longer than the idle timeout are reaped by
+/// `spawn_idle_sweeper`. An evicted or reaped conversation gets a fresh
+/// process on its next message.
 pub struct BridgeManager {
-    /// Map from conversation_id to BridgeSession
-    sessions: Arc<RwLock<HashMap<String, Arc<BridgeSession>>>>,
+    /// Map from conversation_id to its session
+    sessions: Arc<RwLock<HashMap<String, SessionEntry>>>,
     /// Path to the bridge script (stored for new session creation)
-    #[allow(dead_code)]
     bridge_script_path: PathBuf,
+    /// Executable that runs the bridge script
+    command: String,
+    /// Maximum number of live sessions before idle ones are evicted
+    max_sessions: usize,
+    /// Spawn attempts per new session before giving up
+    spawn_attempts: u32,
+    /// Pause between spawn attempts
+    spawn_retry_delay: Duration,
+    /// Monotonic counter used to order sessions by last use
+    use_counter: AtomicU64,
+    /// Slots reserved by creates still spawning (changed with `sessions` locked)
+    reserved_slots: AtomicUsize,
+    /// Locks serializing creates for the same conversation
+    create_locks: CreateLocks,
+    /// Replies in flight that can be stopped
+    reply_stops: ReplyStops,
 }
 
 impl BridgeManager {
     /// Create a new bridge manager
     pub fn new() -> Self {
-        let bridge_script_path = BridgeSession::get_bridge_script_path();
+        Self::with_command(NODE_COMMAND, BridgeSession::get_bridge_script_path())
+    }
+
+    /// Create a bridge manager that runs `bridge_script_path` with `command`
+    pub fn with_command(command: &str, bridge_script_path: PathBuf) -> Self {
         Self {
             sessions: Arc::new(RwLock::new(HashMap::new())),
             bridge_script_path,
+            command: command.to_string(),
+            max_sessions: DEFAULT_MAX_BRIDGE_SESSIONS,
+            spawn_attempts: DEFAULT_BRIDGE_SPAWN_ATTEMPTS,
+            spawn_retry_delay: DEFAULT_BRIDGE_SPAWN_RETRY_DELAY,
+            use_counter: AtomicU64::new(0),
+            reserved_slots: AtomicUsize::new(0),
+            create_locks: Mutex::new(HashMap::new()),
+            reply_stops: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Set the maximum number of live bridge processes (at least one)
+    pub fn with_max_sessions(mut self, max_sessions: usize) -> Self {
+        self.max_sessions = max_sessions.max(1);
+        self
+    }
+
+    /// Set how many times a spawn is attempted (at least one) and the pause between attempts
+    pub fn with_spawn_retry(mut self, attempts: u32, delay: Duration) -> Self {
+        self.spawn_attempts = attempts.max(1);
+        self.spawn_retry_delay = delay;
+        self
+    }
+
+    /// Next value of the use counter
+    fn next_use(&self) -> u64 {
+        self.use_counter.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
     /// Get or create a bridge session for a conversation
     ///
+    /// When the cap is reached, the least-recently-used idle session is killed
+    /// first. Sessions with a request in flight are never evicted. Creates for
+    /// the same conversation are serialized, so it never gets two processes.
+    /// The session map is only locked to look up, reserve a slot under the cap
+    /// and insert; killing the evicted process and spawning (with its retry
+    /// delays) happen unlocked, so other conversations aren't held up.
+    ///
     /// # Arguments
     /// * `conversation_id` - ID of the conversation
     ///
     /// # Returns
-    /// * `Result<Arc<BridgeSession>, String>` - Existing or new session
+    /// * `Result<Arc<BridgeSession>, String>` - Existing or new session, or an
+    ///   error if spawning fails or every session at the cap is busy
     pub async fn get_or_create_session(
         &self,
         conversation_id: &str,
     ) -> Result<Arc<BridgeSession>, String> {
+        let create_lock = self.create_lock(conversation_id);
+        let result = {
+            let _creating = create_lock.lock().await;
+            self.get_or_spawn_session(conversation_id).await
+        };
+        self.release_create_lock(conversation_id, create_lock);
+        result
+    }
+
+    /// Lock used to serialize creates for `conversation_id`
+    fn create_lock(&self, conversation_id: &str) -> Arc<tokio::sync::Mutex<()>> {
+        self.create_locks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(conversation_id.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Forget a conversation's create lock once no other create is waiting on it
+    fn release_create_lock(&self, conversation_id: &str, lock: Arc<tokio::sync::Mutex<()>>) {
+        let mut locks = self.create_locks.lock().unwrap_or_else(|e| e.into_inner());
+        // One reference in the map plus ours: nobody else is waiting
+        if Arc::strong_count(&lock) == 2 {
+            locks.remove(conversation_id);
+        }
+    }
+
+    /// Reuse the conversation's live session or spawn a new one
+    ///
+    /// Must be called with the conversation's create lock held.
+    async fn get_or_spawn_session(
+        &self,
+        conversation_id: &str,
+    ) -> Result<Arc<BridgeSession>, String> {
+        let mut sessions = self.sessions.write().await;
+
         // Check if session already exists
-        {
-            let sessions = self.sessions.read().await;
-            if let Some(session) = sessions.get(conversation_id) {
-                // Check if process is still running
-                if session.is_running().await {
-                    debug!(
-                        conversation_id = %conversation_id,
-                        "Reusing existing bridge session"
-                    );
-                    return Ok(session.clone());
-                } else {
-                    warn!(
-                        conversation_id = %conversation_id,
-                        "Existing session process has died, removing and creating new one"
-                    );
-                    // Remove dead session from map
-                    drop(sessions);
-                    let mut sessions = self.sessions.write().await;
-                    sessions.remove(conversation_id);
-                }
+        if let Some(entry) = sessions.get_mut(conversation_id) {
+            // Check if process is still running
+            if entry.session.is_running().await {
+                debug!(
+                    conversation_id = %conversation_id,
+                    "Reusing existing bridge session"
+                );
+                entry.last_used = self.next_use();
+                entry.last_activity = Instant::now();
+                return Ok(entry.session.clone());
+            } else {
+                warn!(
+                    conversation_id = %conversation_id,
+                    "Existing session process has died, removing and creating new one"
+                );
+                // Remove dead session from map
+                sessions.remove(conversation_id);
+            }
+        }
+
+        // Make room under the cap and hold the slot while spawning unlocked
+        let evicted = self.evict_for_new_session(&mut sessions)?;
+        self.reserved_slots.fetch_add(1, Ordering::SeqCst);
+        let reservation = SlotReservation(&self.reserved_slots);
+        drop(sessions);
+
+        if let Some(evicted) = evicted {
+            if let Err(e) = evicted.kill().await {
+                warn!(
+                    conversation_id = %evicted.conversation_id(),
+                    error = %e,
+                    "Failed to kill evicted bridge process"
+                );
             }
         }
 
@@ -75,23 +305,41 @@ impl BridgeManager {
         );
 
         let session = Arc::new(
-            BridgeSession::new(conversation_id.to_string(), self.bridge_script_path.clone())
-                .await
-                .map_err(|e| {
-                    error!(
-                        conversation_id = %conversation_id,
-                        error = %e,
-                        "Failed to create bridge session"
-                    );
-                    e
-                })?,
+            spawn_with_retry(
+                conversation_id,
+                self.spawn_attempts,
+                self.spawn_retry_delay,
+                || {
+                    BridgeSession::new_with_command(
+                        conversation_id.to_string(),
+                        &self.command,
+                        self.bridge_script_path.clone(),
+                    )
+                },
+            )
+            .await
+            .map_err(|e| {
+                error!(
+                    conversation_id = %conversation_id,
+                    error = %e,
+                    "Failed to create bridge session"
+                );
+                e.to_string()
+            })?,
         );
 
-        // Store session
-        {
-            let mut sessions = self.sessions.write().await;
-            sessions.insert(conversation_id.to_string(), session.clone());
-        }
+        // Store session, then hand back the reservation it replaces
+        let mut sessions = self.sessions.write().await;
+        sessions.insert(
+            conversation_id.to_string(),
+            SessionEntry {
+                session: session.clone(),
+                last_used: self.next_use(),
+                last_activity: Instant::now(),
+            },
+        );
+        drop(reservation);
+        drop(sessions);
 
         info!(
             conversation_id = %conversation_id,
@@ -101,12 +349,54 @@ impl BridgeManager {
         Ok(session)
     }
 
+    /// Remove the least-recently-used idle session if the map is at the cap
+    ///
+    /// Slots reserved by creates still spawning count towards the cap.
+    ///
+    /// # Arguments
+    /// * `sessions` - The locked session map
+    ///
+    /// # Returns
+    /// * `Ok(Some(session))` - The evicted session, still to be killed by the caller
+    /// * `Ok(None)` - There was room, nothing evicted
+    /// * `Err(String)` - At the cap and every session has a request in flight
+    fn evict_for_new_session(
+        &self,
+        sessions: &mut HashMap<String, SessionEntry>,
+    ) -> Result<Option<Arc<BridgeSession>>, String> {
+        if sessions.len() + self.reserved_slots.load(Ordering::SeqCst) < self.max_sessions {
+            return Ok(None);
+        }
+
+        let victim = sessions
+            .iter()
+            .filter(|(_, entry)| entry.is_idle())
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(id, _)| id.clone());
+
+        match victim.and_then(|id| sessions.remove(&id).map(|entry| (id, entry))) {
+            Some((id, entry)) => {
+                info!(
+                    conversation_id = %id,
+                    max_sessions = self.max_sessions,
+                    "Evicting least-recently-used bridge session"
+                );
+                Ok(Some(entry.session))
+            }
+            None => Err(format!(
+                "All {} bridge sessions are busy; try again shortly",
+                self.max_sessions
+            )),
+        }
+    }
+
     /// Send a message to a conversation's bridge session
     ///
     /// # Arguments
     /// * `conversation_id` - ID of the conversation
     /// * `content` - Message content
     /// * `model` - Optional model to use
+    /// * `system_prompt` - Conversation's system prompt, sent with every turn
     ///
     /// # Returns
     /// * `Result<String, String>` - Response text or error
@@ -115,9 +405,118 @@ impl BridgeManager {
         conversation_id: &str,
         content: &str,
         model: Option<&str>,
+        system_prompt: Option<&str>,
+    ) -> Result<String, String> {
+        self.exchange(conversation_id, content, model, system_prompt, None)
+            .await
+    }
+
+    /// Send a message, passing the reply on in pieces as the bridge produces it
+    ///
+    /// Same as `send_message`, but each piece is also sent on `chunks` (see
+    /// `BridgeSession::send_message_streaming`).
+    pub async fn send_message_streaming(
+        &self,
+        conversation_id: &str,
+        content: &str,
+        model: Option<&str>,
+        system_prompt: Option<&str>,
+        chunks: &mpsc::Sender<String>,
+    ) -> Result<String, String> {
+        self.exchange(conversation_id, content, model, system_prompt, Some(chunks))
+            .await
+    }
+
+    /// Send a message on the conversation's session, streaming it if `chunks` is set
+    async fn exchange(
+        &self,
+        conversation_id: &str,
+        content: &str,
+        model: Option<&str>,
+        system_prompt: Option<&str>,
+        chunks: Option<&mpsc::Sender<String>>,
     ) -> Result<String, String> {
         let session = self.get_or_create_session(conversation_id).await?;
-        session.send_message(content, model).await
+        let result = match chunks {
+            Some(chunks) => {
+                session
+                    .send_message_streaming(content, model, system_prompt, chunks)
+                    .await
+            }
+            None => session.send_message(content, model, system_prompt).await,
+        };
+
+        // A long request shouldn't count as idle time
+        if let Some(entry) = self.sessions.write().await.get_mut(conversation_id) {
+            if Arc::ptr_eq(&entry.session, &session) {
+                entry.last_activity = Instant::now();
+            }
+        }
+        result
+    }
+
+    /// Kill sessions that have had no activity for longer than `idle_timeout`
+    ///
+    /// Sessions with a request in flight are skipped. Reaped conversations get
+    /// a new process on their next message.
+    ///
+    /// # Returns
+    /// * `usize` - Number of sessions killed
+    pub async fn reap_idle_sessions(&self, idle_timeout: Duration) -> usize {
+        let reaped: Vec<(String, Arc<BridgeSession>)> = {
+            let mut sessions = self.sessions.write().await;
+            let expired: Vec<String> = sessions
+                .iter()
+                .filter(|(_, entry)| {
+                    entry.is_idle() && entry.last_activity.elapsed() > idle_timeout
+                })
+                .map(|(id, _)| id.clone())
+                .collect();
+            expired
+                .into_iter()
+                .filter_map(|id| sessions.remove(&id).map(|entry| (id, entry.session)))
+                .collect()
+        };
+
+        for (conversation_id, session) in &reaped {
+            info!(
+                conversation_id = %conversation_id,
+                idle_timeout_secs = idle_timeout.as_secs(),
+                "Reaping idle bridge session"
+            );
+            if let Err(e) = session.kill().await {
+                warn!(
+                    conversation_id = %conversation_id,
+                    error = %e,
+                    "Failed to kill idle bridge process"
+                );
+            }
+        }
+
+        reaped.len()
+    }
+
+    /// Start a background task that reaps sessions idle beyond `idle_timeout`
+    ///
+    /// The task holds only a weak reference and exits once the manager is dropped.
+    pub fn spawn_idle_sweeper(
+        manager: &Arc<Self>,
+        idle_timeout: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let manager: Weak<Self> = Arc::downgrade(manager);
+        let interval = (idle_timeout / 2)
+            .min(MAX_SWEEP_INTERVAL)
+            .max(Duration::from_millis(10));
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+                manager.reap_idle_sessions(idle_timeout).await;
+            }
+        })
     }
 
     /// Kill a process for a conversation
@@ -134,8 +533,8 @@ impl BridgeManager {
         );
 
         let mut sessions = self.sessions.write().await;
-        if let Some(session) = sessions.remove(conversation_id) {
-            session.kill().await.map_err(|e| {
+        if let Some(entry) = sessions.remove(conversation_id) {
+            entry.session.kill().await.map_err(|e| {
                 error!(
                     conversation_id = %conversation_id,
                     error = %e,
@@ -158,6 +557,48 @@ impl BridgeManager {
         Ok(())
     }
 
+    /// Register a streamed reply for a conversation so it can be stopped
+    ///
+    /// Keep the guard alive for as long as the reply is being streamed.
+    pub fn register_reply(&self, conversation_id: &str) -> ReplyStopGuard {
+        let signal = Arc::new(Notify::new());
+        self.reply_stops
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(conversation_id.to_string(), signal.clone());
+        ReplyStopGuard {
+            conversation_id: conversation_id.to_string(),
+            signal,
+            registry: self.reply_stops.clone(),
+        }
+    }
+
+    /// Stop the reply being streamed for a conversation
+    ///
+    /// Signals the streaming reply, then kills the conversation's bridge
+    /// process so the in-flight request ends. The next message starts a fresh
+    /// process.
+    ///
+    /// # Returns
+    /// * `Result<bool, String>` - Whether a reply was in flight, or a kill error
+    pub async fn stop_reply(&self, conversation_id: &str) -> Result<bool, String> {
+        let signal = self
+            .reply_stops
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(conversation_id)
+            .cloned();
+        let Some(signal) = signal else {
+            return Ok(false);
+        };
+
+        info!(conversation_id = %conversation_id, "Stopping streamed reply");
+        // Stores a permit if the stream isn't waiting yet, so the stop isn't lost
+        signal.notify_one();
+        self.kill_process(conversation_id).await?;
+        Ok(true)
+    }
+
     /// Kill all processes (for graceful shutdown)
     pub async fn kill_all_processes(&self) {
         info!("Killing all bridge processes");
@@ -166,8 +607,8 @@ impl BridgeManager {
         let conversation_ids: Vec<String> = sessions.keys().cloned().collect();
 
         for conversation_id in conversation_ids {
-            if let Some(session) = sessions.remove(&conversation_id) {
-                if let Err(e) = session.kill().await {
+            if let Some(entry) = sessions.remove(&conversation_id) {
+                if let Err(e) = entry.session.kill().await {
                     error!(
                         conversation_id = %conversation_id,
                         error = %e,
@@ -193,3 +634,325 @@ impl Default for BridgeManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use tempfile::TempDir;
+
+    /// Write a stand-in bridge script that answers every request with "ok"
+    fn write_stub_script(dir: &TempDir) -> PathBuf {
+        let path = dir.path().join("stub-bridge.sh");
+        std::fs::write(
+            &path,
+            "while read line; do echo '{\"status\":\"success\",\"data\":\"ok\"}'; done\n",
+        )
+        .unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn test_missing_script_error_names_path() {
+        let missing = PathBuf::from("/nonexistent/bridge/gemini-bridge.js");
+
+        let err = BridgeSession::new("conv-1".to_string(), missing)
+            .await
+            .err()
+            .expect("spawn should fail for a missing script");
+
+        assert!(matches!(err, BridgeSpawnError::ScriptNotFound(_)));
+        assert_eq!(
+            err.to_string(),
+            "Bridge script not found at /nonexistent/bridge/gemini-bridge.js"
+        );
+        assert!(!err.is_transient());
+    }
+
+    #[tokio::test]
+    async fn test_missing_node_is_reported_separately() {
+        let dir = TempDir::new().unwrap();
+        let script = write_stub_script(&dir);
+
+        let err = BridgeSession::new_with_command(
+            "conv-1".to_string(),
+            "definitely-not-node-3f9a",
+            script,
+        )
+        .await
+        .err()
+        .expect("spawn should fail for a missing executable");
+
+        assert!(matches!(err, BridgeSpawnError::NodeNotFound(_)));
+        assert!(err.to_string().contains("Node.js is not installed"));
+    }
+
+    #[tokio::test]
+    async fn test_spawn_succeeds_after_transient_failure() {
+        let dir = TempDir::new().unwrap();
+        let script = write_stub_script(&dir);
+        let calls = AtomicU32::new(0);
+
+        let session = spawn_with_retry("conv-1", 3, Duration::from_millis(1), || {
+            let attempt = calls.fetch_add(1, Ordering::SeqCst);
+            let script = script.clone();
+            async move {
+                if attempt == 0 {
+                    Err(BridgeSpawnError::SpawnFailed(
+                        "Resource temporarily unavailable".to_string(),
+                    ))
+                } else {
+                    BridgeSession::new_with_command("conv-1".to_string(), "sh", script).await
+                }
+            }
+        })
+        .await
+        .expect("second attempt should succeed");
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(session.is_running().await);
+        assert_eq!(session.send_message("hi", None, None).await.unwrap(), "ok");
+        session.kill().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_spawn_does_not_retry_permanent_failure() {
+        let calls = AtomicU32::new(0);
+
+        let result = spawn_with_retry("conv-1", 3, Duration::from_millis(1), || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Err(BridgeSpawnError::NodeNotFound("node".to_string())) }
+        })
+        .await;
+
+        assert!(matches!(result, Err(BridgeSpawnError::NodeNotFound(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cap_evicts_least_recently_used_idle_session() {
+        let dir = TempDir::new().unwrap();
+        let manager =
+            BridgeManager::with_command("sh", write_stub_script(&dir)).with_max_sessions(2);
+
+        assert_eq!(
+            manager
+                .send_message("conv-1", "hi", None, None)
+                .await
+                .unwrap(),
+            "ok"
+        );
+        assert_eq!(
+            manager
+                .send_message("conv-2", "hi", None, None)
+                .await
+                .unwrap(),
+            "ok"
+        );
+        // Touch conv-1 so conv-2 becomes the least recently used
+        assert_eq!(
+            manager
+                .send_message("conv-1", "hi", None, None)
+                .await
+                .unwrap(),
+            "ok"
+        );
+
+        assert_eq!(
+            manager
+                .send_message("conv-3", "hi", None, None)
+                .await
+                .unwrap(),
+            "ok"
+        );
+        assert_eq!(manager.session_count().await, 2);
+        {
+            let sessions = manager.sessions.read().await;
+            assert!(sessions.contains_key("conv-1"));
+            assert!(!sessions.contains_key("conv-2"));
+            assert!(sessions.contains_key("conv-3"));
+        }
+
+        // The evicted conversation gets a fresh process on its next message
+        assert_eq!(
+            manager
+                .send_message("conv-2", "again", None, None)
+                .await
+                .unwrap(),
+            "ok"
+        );
+        assert_eq!(manager.session_count().await, 2);
+        assert!(manager.sessions.read().await.contains_key("conv-2"));
+
+        manager.kill_all_processes().await;
+    }
+
+    #[tokio::test]
+    async fn test_cap_never_evicts_session_with_request_in_flight() {
+        let dir = TempDir::new().unwrap();
+        let manager =
+            BridgeManager::with_command("sh", write_stub_script(&dir)).with_max_sessions(1);
+
+        // Holding the session stands in for a request that hasn't returned yet
+        let busy = manager.get_or_create_session("conv-1").await.unwrap();
+        let err = manager.get_or_create_session("conv-2").await.unwrap_err();
+        assert!(err.contains("busy"), "got: {}", err);
+        assert!(busy.is_running().await);
+
+        // Once the request completes the session can be evicted
+        drop(busy);
+        assert_eq!(
+            manager
+                .send_message("conv-2", "hi", None, None)
+                .await
+                .unwrap(),
+            "ok"
+        );
+        assert_eq!(manager.session_count().await, 1);
+
+        manager.kill_all_processes().await;
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_creates_respect_cap_and_share_sessions() {
+        let dir = TempDir::new().unwrap();
+        let manager = Arc::new(
+            BridgeManager::with_command("sh", write_stub_script(&dir)).with_max_sessions(2),
+        );
+
+        // Racing creates for one conversation all get the same process
+        let same: Vec<Arc<BridgeSession>> =
+            futures_util::future::join_all((0..8).map(|_| manager.get_or_create_session("conv-1")))
+                .await
+                .into_iter()
+                .map(Result::unwrap)
+                .collect();
+        assert!(same.iter().all(|session| Arc::ptr_eq(session, &same[0])));
+        assert_eq!(manager.session_count().await, 1);
+        drop(same);
+
+        // Racing creates for different conversations never exceed the cap
+        let creates = (0..8).map(|i| {
+            let manager = manager.clone();
+            tokio::spawn(async move {
+                manager
+                    .get_or_create_session(&format!("conv-{}", i + 2))
+                    .await
+                    .map(drop)
+            })
+        });
+        for create in futures_util::future::join_all(creates).await {
+            // A create can only fail because every session was briefly held
+            if let Err(e) = create.unwrap() {
+                assert!(e.contains("busy"), "unexpected error: {}", e);
+            }
+        }
+        assert_eq!(manager.session_count().await, 2);
+
+        manager.kill_all_processes().await;
+    }
+
+    #[tokio::test]
+    async fn test_slow_spawn_does_not_block_other_conversations() {
+        let dir = TempDir::new().unwrap();
+        let script = write_stub_script(&dir);
+        // Not executable: every spawn fails transiently and is retried after a delay
+        let not_executable = dir.path().join("not-executable");
+        std::fs::write(&not_executable, "").unwrap();
+        let manager = Arc::new(
+            BridgeManager::with_command(not_executable.to_str().unwrap(), script)
+                .with_spawn_retry(3, Duration::from_millis(300)),
+        );
+
+        let slow_create = {
+            let manager = manager.clone();
+            tokio::spawn(async move { manager.get_or_create_session("conv-1").await.map(drop) })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // The session map stays usable while conv-1 sits in its retry delays
+        let count = tokio::time::timeout(Duration::from_millis(100), manager.session_count())
+            .await
+            .expect("session map should not be locked during a spawn");
+        assert_eq!(count, 0);
+        assert!(!slow_create.is_finished());
+
+        assert!(slow_create.await.unwrap().is_err());
+        // The failed create gave its reserved slot back
+        assert_eq!(manager.reserved_slots.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_idle_sweeper_reaps_and_next_message_respawns() {
+        let dir = TempDir::new().unwrap();
+        let manager = Arc::new(BridgeManager::with_command("sh", write_stub_script(&dir)));
+        let sweeper = BridgeManager::spawn_idle_sweeper(&manager, Duration::from_millis(50));
+
+        assert_eq!(
+            manager
+                .send_message("conv-1", "hi", None, None)
+                .await
+                .unwrap(),
+            "ok"
+        );
+        let first = manager.get_or_create_session("conv-1").await.unwrap();
+        assert_eq!(manager.session_count().await, 1);
+
+        // Held sessions count as in flight and survive past the timeout
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert_eq!(manager.session_count().await, 1);
+
+        drop(first);
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert_eq!(
+            manager.session_count().await,
+            0,
+            "idle session should be reaped"
+        );
+
+        // The next message spawns a fresh process
+        assert_eq!(
+            manager
+                .send_message("conv-1", "again", None, None)
+                .await
+                .unwrap(),
+            "ok"
+        );
+        assert_eq!(manager.session_count().await, 1);
+
+        sweeper.abort();
+        manager.kill_all_processes().await;
+    }
+
+    #[tokio::test]
+    async fn test_stop_reply_kills_in_flight_request() {
+        let dir = TempDir::new().unwrap();
+        // Reads the request, then never answers
+        let script = dir.path().join("hung-bridge.sh");
+        std::fs::write(&script, "read line; exec sleep 30\n").unwrap();
+        let manager = Arc::new(BridgeManager::with_command("sh", script));
+
+        // Nothing to stop before a reply is registered
+        assert!(!manager.stop_reply("conv-1").await.unwrap());
+
+        let guard = manager.register_reply("conv-1");
+        let request = {
+            let manager = manager.clone();
+            tokio::spawn(async move { manager.send_message("conv-1", "hi", None, None).await })
+        };
+        while manager.session_count().await == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert!(manager.stop_reply("conv-1").await.unwrap());
+        tokio::time::timeout(Duration::from_secs(1), guard.stopped())
+            .await
+            .expect("stop signal should fire");
+        let result = tokio::time::timeout(Duration::from_secs(5), request)
+            .await
+            .expect("in-flight request should end")
+            .unwrap();
+        assert!(result.is_err());
+        assert_eq!(manager.session_count().await, 0);
+    }
+}
@@ -6,9 +6,10 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::process::Stdio;
+use thiserror::Error;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, ChildStdin, ChildStdout, Command};
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, Mutex};
 use tracing::{debug, error, info};
 
 /// Request sent to the bridge process
@@ -21,12 +22,21 @@ pub struct BridgeRequest {
     pub content: Option<String>,
     /// Model to use (optional)
     pub model: Option<String>,
+    /// Conversation's system prompt, applied before the message (omitted when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// Ask for `"chunk"` responses while the reply is generated (omitted when false)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stream: bool,
 }
 
 /// Response received from the bridge process
+///
+/// A streamed request gets any number of `"chunk"` responses carrying part
+/// of the reply before its final `"success"` or `"error"` response.
 #[derive(Debug, Deserialize)]
 pub struct BridgeResponse {
-    /// Status of the response
+    /// Status of the response ("success", "error" or "chunk")
     pub status: String,
     /// Response data (for success)
     pub data: Option<String>,
@@ -34,6 +44,35 @@ pub struct BridgeResponse {
     pub message: Option<String>,
 }
 
+/// Command used to run the bridge script
+pub const NODE_COMMAND: &str = "node";
+
+/// Errors that can occur while spawning a bridge process
+#[derive(Error, Debug)]
+pub enum BridgeSpawnError {
+    /// The Node.js executable could not be found
+    #[error("Node.js is not installed: '{0}' was not found in PATH")]
+    NodeNotFound(String),
+
+    /// The bridge script does not exist
+    #[error("Bridge script not found at {}", .0.display())]
+    ScriptNotFound(PathBuf),
+
+    /// The process could not be started or its pipes could not be attached
+    #[error("Failed to spawn bridge process: {0}")]
+    SpawnFailed(String),
+}
+
+impl BridgeSpawnError {
+    /// Whether spawning again might succeed
+    ///
+    /// A missing executable or script will not fix itself, so only generic
+    /// spawn failures (e.g. temporary resource exhaustion) are retried.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, BridgeSpawnError::SpawnFailed(_))
+    }
+}
+
 /// Handle to a persistent bridge subprocess
 ///
 /// Each BridgeSession manages one Node.js bridge process that maintains
@@ -62,35 +101,62 @@ impl BridgeSession {
     /// * `bridge_script_path` - Path to the Node.js bridge script
     ///
     /// # Returns
-    /// * `Result<Self, String>` - New BridgeSession or error
-    pub async fn new(conversation_id: String, bridge_script_path: PathBuf) -> Result<Self, String> {
+    /// * `Result<Self, BridgeSpawnError>` - New BridgeSession or error
+    pub async fn new(
+        conversation_id: String,
+        bridge_script_path: PathBuf,
+    ) -> Result<Self, BridgeSpawnError> {
+        Self::new_with_command(conversation_id, NODE_COMMAND, bridge_script_path).await
+    }
+
+    /// Create a new bridge session that runs the script with a specific command
+    ///
+    /// # Arguments
+    /// * `conversation_id` - ID of the conversation this session belongs to
+    /// * `command` - Executable that runs the script (normally `node`)
+    /// * `bridge_script_path` - Path to the bridge script
+    ///
+    /// # Returns
+    /// * `Result<Self, BridgeSpawnError>` - New BridgeSession or error
+    pub async fn new_with_command(
+        conversation_id: String,
+        command: &str,
+        bridge_script_path: PathBuf,
+    ) -> Result<Self, BridgeSpawnError> {
         debug!(
             conversation_id = %conversation_id,
+            command = %command,
             "Creating new bridge session"
         );
 
+        // Check the script up front: otherwise node starts and exits with a
+        // module-not-found error that only surfaces on the first message
+        if !bridge_script_path.is_file() {
+            return Err(BridgeSpawnError::ScriptNotFound(bridge_script_path));
+        }
+
         // Spawn the Node.js bridge process
-        let mut child = Command::new("node")
+        let mut child = Command::new(command)
             .arg(&bridge_script_path)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
-            .map_err(|e| format!("Failed to spawn bridge process: {}", e))?;
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => BridgeSpawnError::NodeNotFound(command.to_string()),
+                _ => BridgeSpawnError::SpawnFailed(e.to_string()),
+            })?;
 
         // Extract stdin/stdout/stderr handles
-        let stdin = child
-            .stdin
-            .take()
-            .ok_or_else(|| "Failed to get stdin handle".to_string())?;
-        let stdout = child
-            .stdout
-            .take()
-            .ok_or_else(|| "Failed to get stdout handle".to_string())?;
-        let stderr = child
-            .stderr
-            .take()
-            .ok_or_else(|| "Failed to get stderr handle".to_string())?;
+        let stdin = child.stdin.take().ok_or_else(|| {
+            BridgeSpawnError::SpawnFailed("Failed to get stdin handle".to_string())
+        })?;
+        let stdout = child.stdout.take().ok_or_else(|| {
+            BridgeSpawnError::SpawnFailed("Failed to get stdout handle".to_string())
+        })?;
+        let stderr = child.stderr.take().ok_or_else(|| {
+            BridgeSpawnError::SpawnFailed("Failed to get stderr handle".to_string())
+        })?;
 
         info!(
             conversation_id = %conversation_id,
@@ -147,6 +213,7 @@ impl BridgeSession {
     /// # Arguments
     /// * `content` - Message content to send
     /// * `model` - Optional model to use
+    /// * `system_prompt` - Conversation's system prompt, sent with every turn
     ///
     /// # Returns
     /// * `Result<String, String>` - Response text or error
@@ -154,10 +221,43 @@ impl BridgeSession {
     /// # Timeout
     /// This operation has a timeout of 120 seconds. If the bridge process
     /// doesn't respond within this time, an error is returned.
-    pub async fn send_message(&self, content: &str, model: Option<&str>) -> Result<String, String> {
+    pub async fn send_message(
+        &self,
+        content: &str,
+        model: Option<&str>,
+        system_prompt: Option<&str>,
+    ) -> Result<String, String> {
+        self.exchange(content, model, system_prompt, None).await
+    }
+
+    /// Send a message, passing the reply on in pieces as the bridge produces it
+    ///
+    /// Same as `send_message`, but each piece is sent on `chunks` while the
+    /// reply is generated. The returned text is still the whole reply; a bridge
+    /// that doesn't stream sends no chunks.
+    pub async fn send_message_streaming(
+        &self,
+        content: &str,
+        model: Option<&str>,
+        system_prompt: Option<&str>,
+        chunks: &mpsc::Sender<String>,
+    ) -> Result<String, String> {
+        self.exchange(content, model, system_prompt, Some(chunks))
+            .await
+    }
+
+    /// Send one request and read responses until the final one
+    async fn exchange(
+        &self,
+        content: &str,
+        model: Option<&str>,
+        system_prompt: Option<&str>,
+        chunks: Option<&mpsc::Sender<String>>,
+    ) -> Result<String, String> {
         debug!(
             conversation_id = %self.conversation_id,
             content_len = content.len(),
+            streaming = chunks.is_some(),
             "Sending message to bridge"
         );
 
@@ -166,6 +266,8 @@ impl BridgeSession {
             request_type: "message".to_string(),
             content: Some(content.to_string()),
             model: model.map(|s| s.to_string()),
+            system_prompt: system_prompt.map(|s| s.to_string()),
+            stream: chunks.is_some(),
         };
 
         // Serialize request
@@ -193,9 +295,9 @@ impl BridgeSession {
                 .map_err(|e| format!("Failed to flush stdin: {}", e))?;
         }
 
-        // Read response from stdout with timeout
+        // Read responses from stdout with timeout
         let timeout_duration = tokio::time::Duration::from_secs(120);
-        let response_line = tokio::time::timeout(timeout_duration, async {
+        let response = tokio::time::timeout(timeout_duration, async {
             // Check if process is still alive before reading
             {
                 let mut child_guard = self.child.lock().await;
@@ -232,53 +334,60 @@ impl BridgeSession {
                 .as_mut()
                 .ok_or_else(|| "Stdout handle not available".to_string())?;
 
-            // Read one line from stdout
-            let mut response_buffer = String::new();
-            let bytes_read = stdout_reader
-                .read_line(&mut response_buffer)
-                .await
-                .map_err(|e| format!("Failed to read response: {}", e))?;
-
-            if bytes_read == 0 {
-                // EOF - process might have exited
-                let mut child_guard = self.child.lock().await;
-                if let Some(child) = child_guard.as_mut() {
-                    if let Ok(Some(status)) = child.try_wait() {
-                        // Process exited, get stderr
-                        let stderr_handle = self.stderr.lock().await.take();
-                        if let Some(handle) = stderr_handle {
-                            if let Ok(stderr_output) = handle.await {
-                                error!(
-                                    conversation_id = %self.conversation_id,
-                                    stderr = %stderr_output,
-                                    exit_status = ?status,
-                                    "Bridge process exited (EOF)"
-                                );
-                                return Err(format!(
-                                    "Bridge process exited with status {:?}. Stderr: {}",
-                                    status, stderr_output
-                                ));
+            loop {
+                // Read one line from stdout
+                let mut response_buffer = String::new();
+                let bytes_read = stdout_reader
+                    .read_line(&mut response_buffer)
+                    .await
+                    .map_err(|e| format!("Failed to read response: {}", e))?;
+
+                if bytes_read == 0 {
+                    // EOF - process might have exited
+                    let mut child_guard = self.child.lock().await;
+                    if let Some(child) = child_guard.as_mut() {
+                        if let Ok(Some(status)) = child.try_wait() {
+                            // Process exited, get stderr
+                            let stderr_handle = self.stderr.lock().await.take();
+                            if let Some(handle) = stderr_handle {
+                                if let Ok(stderr_output) = handle.await {
+                                    error!(
+                                        conversation_id = %self.conversation_id,
+                                        stderr = %stderr_output,
+                                        exit_status = ?status,
+                                        "Bridge process exited (EOF)"
+                                    );
+                                    return Err(format!(
+                                        "Bridge process exited with status {:?}. Stderr: {}",
+                                        status, stderr_output
+                                    ));
+                                }
                             }
+                            return Err(format!(
+                                "Bridge process exited unexpectedly with status {:?} (EOF)",
+                                status
+                            ));
                         }
-                        return Err(format!(
-                            "Bridge process exited unexpectedly with status {:?} (EOF)",
-                            status
-                        ));
                     }
+                    return Err("EOF while reading response (process may have exited)".to_string());
                 }
-                return Err("EOF while reading response (process may have exited)".to_string());
-            }
 
-            Ok::<String, String>(response_buffer.trim().to_string())
+                // Parse response
+                let response: BridgeResponse = serde_json::from_str(response_buffer.trim())
+                    .map_err(|e| format!("Failed to parse response: {}", e))?;
+                if response.status != "chunk" {
+                    return Ok::<BridgeResponse, String>(response);
+                }
+                if let (Some(chunks), Some(data)) = (chunks, response.data) {
+                    // A closed receiver just means nobody is following along any more
+                    let _ = chunks.send(data).await;
+                }
+            }
         })
         .await
         .map_err(|_| "Request timed out after 120 seconds".to_string())
         .and_then(|r| r)?;
 
-        // Parse response
-        let response: BridgeResponse = serde_json::from_str(&response_line)
-            .map_err(|e| format!("Failed to parse response: {}", e))?;
-
         match response.status.as_str() {
             "success" => {
                 debug!(
@@ -350,7 +459,6 @@ impl BridgeSession {
     }
 
     /// Get the conversation ID
-    #[allow(dead_code)] // Will be used in Phase 4 for metrics/monitoring
     pub fn conversation_id(&self) -> &str {
         &self.conversation_id
     }
@@ -2,7 +2,8 @@
 //!
 //! Handles all database interactions for conversations and messages.
 
-use crate::chat::models::{Conversation, Message};
+use crate::chat::models::{AuditEntry, Conversation, ConversationSummary, Message, MessageRole};
+use crate::chat::summarize::{build_summary, SummarizePolicy};
 use crate::error::AppError;
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use sqlx::SqlitePool;
@@ -13,6 +14,10 @@ use tracing::{debug, info};
 /// Database connection pool for chat operations
 pub struct ChatDb {
     pool: SqlitePool,
+    /// Summarize older messages once a conversation grows past a threshold (None = off)
+    summarize_policy: Option<SummarizePolicy>,
+    /// Most conversations kept; the least recently updated unpinned ones are evicted (None = no cap)
+    max_conversations: Option<usize>,
 }
 
 impl ChatDb {
@@ -54,67 +59,147 @@ impl ChatDb {
 
         info!("Connected to SQLite database at: {}", db_path);
 
-        let db = Self { pool };
+        let db = Self {
+            pool,
+            summarize_policy: None,
+            max_conversations: None,
+        };
         db.run_migrations().await?;
 
         Ok(db)
     }
 
+    /// Summarize and truncate long conversations as messages are added
+    ///
+    /// With `Some(policy)`, every `add_message` that takes a conversation past
+    /// `policy.threshold` messages replaces all but the most recent
+    /// `policy.keep_recent` with a single system summary message.
+    pub fn with_summarize_policy(mut self, policy: Option<SummarizePolicy>) -> Self {
+        self.summarize_policy = policy;
+        self
+    }
+
+    /// Cap the number of stored conversations
+    ///
+    /// With `Some(max)`, every `create_conversation` that takes the count past
+    /// `max` deletes the least recently updated unpinned conversations (and
+    /// their messages) until it is back at `max`. Pinned conversations are
+    /// never evicted, so the count can stay above `max` when they fill it.
+    pub fn with_max_conversations(mut self, max_conversations: Option<usize>) -> Self {
+        self.max_conversations = max_conversations;
+        self
+    }
+
     /// Run database migrations
     async fn run_migrations(&self) -> Result<(), AppError> {
         info!("Running database migrations...");
 
-        // Read migration file
-        let migration_sql = include_str!("../../migrations/001_create_chats.sql");
+        // Migration files, applied in order (all statements are idempotent)
+        const MIGRATIONS: &[&str] = &[
+            include_str!("../../migrations/001_create_chats.sql"),
+            include_str!("../../migrations/002_create_audit.sql"),
+            include_str!("../../migrations/003_conversation_tags.sql"),
+            include_str!("../../migrations/004_audit_plan.sql"),
+            include_str!("../../migrations/005_audit_step_outputs.sql"),
+        ];
+
+        for migration_sql in MIGRATIONS {
+            self.run_migration(migration_sql).await?;
+        }
+        self.migrate_message_roles().await?;
+        // Adds a messages column, so it must follow the messages table rebuild
+        self.run_migration(include_str!("../../migrations/007_message_stopped.sql"))
+            .await?;
+        self.run_migration(include_str!(
+            "../../migrations/008_conversation_system_prompt.sql"
+        ))
+        .await?;
+        self.run_migration(include_str!("../../migrations/009_audit_summary.sql"))
+            .await?;
 
-        // Remove comments (lines starting with --) and normalize whitespace
-        let mut cleaned_sql = String::new();
-        for line in migration_sql.lines() {
-            let trimmed = line.trim();
-            // Skip empty lines and comment-only lines
-            if trimmed.is_empty() || trimmed.starts_with("--") {
-                continue;
-            }
-            // Remove inline comments (everything after --)
-            let without_comments = if let Some(comment_pos) = trimmed.find("--") {
-                &trimmed[..comment_pos]
-            } else {
-                trimmed
-            };
-            cleaned_sql.push_str(without_comments.trim());
-            cleaned_sql.push(' ');
+        info!("Database migrations completed successfully");
+        Ok(())
+    }
+
+    /// Rebuild the messages table to accept 'system' messages, if not done yet
+    ///
+    /// The rebuild isn't idempotent, so it only runs while the original role
+    /// constraint is in place, and all in one transaction.
+    async fn migrate_message_roles(&self) -> Result<(), AppError> {
+        let table_sql: Option<String> = sqlx::query_scalar(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'messages'",
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::Internal(anyhow::anyhow!("Failed to inspect messages table: {}", e))
+        })?;
+        if table_sql.map_or(true, |sql| sql.contains("'system'")) {
+            return Ok(());
         }
 
-        // Split by semicolon and filter out empty statements
-        let statements: Vec<&str> = cleaned_sql
-            .split(';')
-            .map(|s| s.trim())
-            .filter(|s| !s.is_empty())
-            .collect();
+        info!("Rebuilding messages table to allow system messages");
+        let migration_error =
+            |e: sqlx::Error| AppError::Internal(anyhow::anyhow!("Migration failed: {}", e));
+        let mut tx = self.pool.begin().await.map_err(migration_error)?;
+        for statement in
+            migration_statements(include_str!("../../migrations/006_message_system_role.sql"))
+        {
+            sqlx::query(&statement)
+                .execute(&mut *tx)
+                .await
+                .map_err(migration_error)?;
+        }
+        tx.commit().await.map_err(migration_error)
+    }
 
+    /// Run a single migration file, statement by statement
+    async fn run_migration(&self, migration_sql: &str) -> Result<(), AppError> {
         // Execute each statement separately
-        for statement in statements {
-            sqlx::query(statement)
-                .execute(&self.pool)
-                .await
-                .map_err(|e| {
-                    AppError::Internal(anyhow::anyhow!(
-                        "Migration failed: {} - Statement: {}",
-                        e,
-                        statement.chars().take(100).collect::<String>()
-                    ))
-                })?;
+        for statement in migration_statements(migration_sql) {
+            let result = sqlx::query(&statement).execute(&self.pool).await;
+
+            // SQLite has no ADD COLUMN IF NOT EXISTS; a re-run column add is already applied
+            if let Err(ref e) = result {
+                if statement.contains("ADD COLUMN")
+                    && e.to_string().contains("duplicate column name")
+                {
+                    continue;
+                }
+            }
+
+            result.map_err(|e| {
+                AppError::Internal(anyhow::anyhow!(
+                    "Migration failed: {} - Statement: {}",
+                    e,
+                    statement.chars().take(100).collect::<String>()
+                ))
+            })?;
         }
 
-        info!("Database migrations completed successfully");
         Ok(())
     }
 
-    /// Get all conversations, ordered by most recently updated
-    pub async fn get_conversations(&self) -> Result<Vec<Conversation>, AppError> {
-        let conversations = sqlx::query_as::<_, Conversation>(
-            "SELECT id, title, created_at, updated_at FROM conversations ORDER BY updated_at DESC",
+    /// Get conversations with message statistics, pinned first, then most recently updated
+    ///
+    /// Message counts and sizes come from the same aggregate query, so listing
+    /// costs one round trip regardless of the number of conversations.
+    ///
+    /// # Arguments
+    /// * `tag` - Only return conversations carrying this tag (all if `None`)
+    pub async fn get_conversations(
+        &self,
+        tag: Option<&str>,
+    ) -> Result<Vec<ConversationSummary>, AppError> {
+        let conversations = sqlx::query_as::<_, ConversationSummary>(
+            "SELECT c.id, c.title, c.created_at, c.updated_at, c.tags, c.pinned, c.system_prompt, \
+             COUNT(m.id) AS message_count, COALESCE(SUM(LENGTH(m.content)), 0) AS content_chars \
+             FROM conversations c LEFT JOIN messages m ON m.conversation_id = c.id \
+             WHERE ?1 IS NULL OR EXISTS (SELECT 1 FROM json_each(c.tags) WHERE json_each.value = ?1) \
+             GROUP BY c.id \
+             ORDER BY c.pinned DESC, c.updated_at DESC",
         )
+        .bind(tag)
         .fetch_all(&self.pool)
         .await
         .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to fetch conversations: {}", e)))?;
@@ -125,7 +210,8 @@ impl ChatDb {
     /// Get a conversation by ID
     pub async fn get_conversation(&self, id: &str) -> Result<Option<Conversation>, AppError> {
         let conversation = sqlx::query_as::<_, Conversation>(
-            "SELECT id, title, created_at, updated_at FROM conversations WHERE id = ?",
+            "SELECT id, title, created_at, updated_at, tags, pinned, system_prompt \
+             FROM conversations WHERE id = ?",
         )
         .bind(id)
         .fetch_optional(&self.pool)
@@ -138,17 +224,65 @@ impl ChatDb {
     /// Create a new conversation
     pub async fn create_conversation(&self, conversation: &Conversation) -> Result<(), AppError> {
         sqlx::query(
-            "INSERT INTO conversations (id, title, created_at, updated_at) VALUES (?, ?, ?, ?)",
+            "INSERT INTO conversations (id, title, created_at, updated_at, tags, pinned, system_prompt) \
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(&conversation.id)
         .bind(&conversation.title)
         .bind(conversation.created_at)
         .bind(conversation.updated_at)
+        .bind(sqlx::types::Json(&conversation.tags))
+        .bind(conversation.pinned)
+        .bind(&conversation.system_prompt)
         .execute(&self.pool)
         .await
         .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to create conversation: {}", e)))?;
 
         debug!("Created conversation: {}", conversation.id);
+        self.evict_conversations(&conversation.id).await?;
+        Ok(())
+    }
+
+    /// Delete the oldest unpinned conversations beyond `max_conversations`
+    ///
+    /// `keep_id` (the conversation just created) is never evicted, even if its
+    /// timestamp ties with older ones.
+    async fn evict_conversations(&self, keep_id: &str) -> Result<(), AppError> {
+        let Some(max) = self.max_conversations else {
+            return Ok(());
+        };
+        let evict_error = |e: sqlx::Error| {
+            AppError::Internal(anyhow::anyhow!("Failed to evict conversations: {}", e))
+        };
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM conversations")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(evict_error)?;
+        let excess = count - max as i64;
+        if excess <= 0 {
+            return Ok(());
+        }
+
+        // Messages go with their conversation (ON DELETE CASCADE)
+        let evicted = sqlx::query(
+            "DELETE FROM conversations WHERE id IN ( \
+             SELECT id FROM conversations WHERE pinned = 0 AND id != ? \
+             ORDER BY updated_at ASC, created_at ASC LIMIT ?)",
+        )
+        .bind(keep_id)
+        .bind(excess)
+        .execute(&self.pool)
+        .await
+        .map_err(evict_error)?
+        .rows_affected();
+
+        if evicted > 0 {
+            info!(
+                "Evicted {} conversation(s) to stay within the cap of {}",
+                evicted, max
+            );
+        }
         Ok(())
     }
 
@@ -169,6 +303,58 @@ impl ChatDb {
         Ok(())
     }
 
+    /// Replace a conversation's tags
+    pub async fn set_conversation_tags(&self, id: &str, tags: &[String]) -> Result<(), AppError> {
+        sqlx::query("UPDATE conversations SET tags = ? WHERE id = ?")
+            .bind(sqlx::types::Json(tags))
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::Internal(anyhow::anyhow!("Failed to update conversation tags: {}", e))
+            })?;
+
+        debug!("Updated tags for conversation: {}", id);
+        Ok(())
+    }
+
+    /// Pin or unpin a conversation
+    pub async fn set_conversation_pinned(&self, id: &str, pinned: bool) -> Result<(), AppError> {
+        sqlx::query("UPDATE conversations SET pinned = ? WHERE id = ?")
+            .bind(pinned)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::Internal(anyhow::anyhow!("Failed to update conversation pin: {}", e))
+            })?;
+
+        debug!("Set pinned={} for conversation: {}", pinned, id);
+        Ok(())
+    }
+
+    /// Set or clear (`None`) a conversation's system prompt
+    pub async fn set_conversation_system_prompt(
+        &self,
+        id: &str,
+        system_prompt: Option<&str>,
+    ) -> Result<(), AppError> {
+        sqlx::query("UPDATE conversations SET system_prompt = ? WHERE id = ?")
+            .bind(system_prompt)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::Internal(anyhow::anyhow!(
+                    "Failed to update conversation system prompt: {}",
+                    e
+                ))
+            })?;
+
+        debug!("Updated system prompt for conversation: {}", id);
+        Ok(())
+    }
+
     /// Update conversation's updated_at timestamp (when new message is added)
     pub async fn touch_conversation(&self, id: &str) -> Result<(), AppError> {
         let updated_at = chrono::Utc::now().timestamp();
@@ -201,7 +387,7 @@ impl ChatDb {
     /// Get all messages for a conversation, ordered by creation time
     pub async fn get_messages(&self, conversation_id: &str) -> Result<Vec<Message>, AppError> {
         let messages = sqlx::query_as::<_, Message>(
-            "SELECT id, conversation_id, role, content, created_at FROM messages WHERE conversation_id = ? ORDER BY created_at ASC"
+            "SELECT id, conversation_id, role, content, created_at, stopped FROM messages WHERE conversation_id = ? ORDER BY created_at ASC, rowid ASC"
         )
         .bind(conversation_id)
         .fetch_all(&self.pool)
@@ -214,13 +400,14 @@ impl ChatDb {
     /// Add a message to a conversation
     pub async fn add_message(&self, message: &Message) -> Result<(), AppError> {
         sqlx::query(
-            "INSERT INTO messages (id, conversation_id, role, content, created_at) VALUES (?, ?, ?, ?, ?)"
+            "INSERT INTO messages (id, conversation_id, role, content, created_at, stopped) VALUES (?, ?, ?, ?, ?, ?)"
         )
         .bind(&message.id)
         .bind(&message.conversation_id)
         .bind(&message.role)
         .bind(&message.content)
         .bind(message.created_at)
+        .bind(message.stopped)
         .execute(&self.pool)
         .await
         .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to add message: {}", e)))?;
@@ -232,12 +419,280 @@ impl ChatDb {
             "Added message {} to conversation {}",
             message.id, message.conversation_id
         );
+
+        if let Some(policy) = self.summarize_policy {
+            self.summarize_conversation(&message.conversation_id, policy)
+                .await?;
+        }
         Ok(())
     }
 
+    /// Replace a conversation's older messages with a summary, if it is too long
+    ///
+    /// Once the conversation has more than `policy.threshold` messages, all but
+    /// the most recent `policy.keep_recent` are deleted and a single system
+    /// message summarizing them (including any earlier summary) takes their
+    /// place, ordered before the kept messages.
+    ///
+    /// # Returns
+    /// * `Ok(true)` - If older messages were summarized
+    /// * `Ok(false)` - If the conversation is within the threshold
+    pub async fn summarize_conversation(
+        &self,
+        conversation_id: &str,
+        policy: SummarizePolicy,
+    ) -> Result<bool, AppError> {
+        let messages = self.get_messages(conversation_id).await?;
+        if messages.len() <= policy.threshold {
+            return Ok(false);
+        }
+
+        let (older, recent) = messages.split_at(messages.len() - policy.keep_recent);
+        let mut summary = Message::new(
+            uuid::Uuid::new_v4().to_string(),
+            conversation_id.to_string(),
+            MessageRole::System,
+            build_summary(older),
+        );
+        // Sort before the oldest kept message
+        summary.created_at = recent
+            .first()
+            .map(|m| m.created_at - 1)
+            .unwrap_or(summary.created_at);
+
+        let summarize_error = |e: sqlx::Error| {
+            AppError::Internal(anyhow::anyhow!("Failed to summarize conversation: {}", e))
+        };
+        let mut tx = self.pool.begin().await.map_err(summarize_error)?;
+        for message in older {
+            sqlx::query("DELETE FROM messages WHERE id = ?")
+                .bind(&message.id)
+                .execute(&mut *tx)
+                .await
+                .map_err(summarize_error)?;
+        }
+        sqlx::query(
+            "INSERT INTO messages (id, conversation_id, role, content, created_at) VALUES (?, ?, ?, ?, ?)"
+        )
+        .bind(&summary.id)
+        .bind(&summary.conversation_id)
+        .bind(&summary.role)
+        .bind(&summary.content)
+        .bind(summary.created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(summarize_error)?;
+        tx.commit().await.map_err(summarize_error)?;
+
+        info!(
+            conversation_id = %conversation_id,
+            summarized = older.len(),
+            kept = recent.len(),
+            "Summarized older conversation messages"
+        );
+        Ok(true)
+    }
+
+    /// Append an entry to the orchestration audit log
+    pub async fn add_audit_entry(&self, entry: &AuditEntry) -> Result<(), AppError> {
+        sqlx::query(
+            "INSERT INTO audit (id, created_at, goal_hash, goal, plan_hash, step_count, success, duration_ms, error, plan, step_outputs, summary) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(&entry.id)
+        .bind(entry.created_at)
+        .bind(&entry.goal_hash)
+        .bind(&entry.goal)
+        .bind(&entry.plan_hash)
+        .bind(entry.step_count)
+        .bind(entry.success)
+        .bind(entry.duration_ms)
+        .bind(&entry.error)
+        .bind(&entry.plan)
+        .bind(&entry.step_outputs)
+        .bind(&entry.summary)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to add audit entry: {}", e)))?;
+
+        debug!("Added audit entry: {}", entry.id);
+        Ok(())
+    }
+
+    /// Get audit entries created at or after `since`, most recent first
+    ///
+    /// # Arguments
+    /// * `limit` - Maximum number of entries to return
+    /// * `since` - Optional Unix timestamp lower bound (inclusive)
+    pub async fn get_audit_entries(
+        &self,
+        limit: i64,
+        since: Option<i64>,
+    ) -> Result<Vec<AuditEntry>, AppError> {
+        let entries = sqlx::query_as::<_, AuditEntry>(
+            "SELECT id, created_at, goal_hash, goal, plan_hash, step_count, success, duration_ms, error FROM audit WHERE created_at >= ? ORDER BY created_at DESC LIMIT ?"
+        )
+        .bind(since.unwrap_or(0))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to fetch audit entries: {}", e)))?;
+
+        Ok(entries)
+    }
+
+    /// Get a single audit entry, including its retained plan and summary
+    ///
+    /// # Returns
+    /// * `Ok(Some(AuditEntry))` - If an entry with this ID exists
+    /// * `Ok(None)` - If no entry matches
+    pub async fn get_audit_entry(&self, id: &str) -> Result<Option<AuditEntry>, AppError> {
+        let entry = sqlx::query_as::<_, AuditEntry>(
+            "SELECT id, created_at, goal_hash, goal, plan_hash, step_count, success, duration_ms, error, plan, step_outputs, summary FROM audit WHERE id = ?"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to fetch audit entry: {}", e)))?;
+
+        Ok(entry)
+    }
+
     /// Get the database pool (for advanced operations if needed)
     #[allow(dead_code)]
     pub fn pool(&self) -> &SqlitePool {
         &self.pool
     }
 }
+
+/// Split a migration file into statements, dropping `--` comments
+fn migration_statements(migration_sql: &str) -> Vec<String> {
+    // Remove comments (lines starting with --) and normalize whitespace
+    let mut cleaned_sql = String::new();
+    for line in migration_sql.lines() {
+        let trimmed = line.trim();
+        // Skip empty lines and comment-only lines
+        if trimmed.is_empty() || trimmed.starts_with("--") {
+            continue;
+        }
+        // Remove inline comments (everything after --)
+        let without_comments = if let Some(comment_pos) = trimmed.find("--") {
+            &trimmed[..comment_pos]
+        } else {
+            trimmed
+        };
+        cleaned_sql.push_str(without_comments.trim());
+        cleaned_sql.push(' ');
+    }
+
+    // Split by semicolon and filter out empty statements
+    cleaned_sql
+        .split(';')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn conversation(id: &str, updated_at: i64, pinned: bool) -> Conversation {
+        let mut conversation = Conversation::new(id.to_string(), id.to_string());
+        conversation.created_at = updated_at;
+        conversation.updated_at = updated_at;
+        conversation.pinned = pinned;
+        conversation
+    }
+
+    async fn conversation_ids(chat_db: &ChatDb) -> Vec<String> {
+        let mut ids: Vec<String> = chat_db
+            .get_conversations(None)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[tokio::test]
+    async fn test_creating_beyond_cap_evicts_oldest_unpinned() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let chat_db = ChatDb::new(db_path.to_str().unwrap())
+            .await
+            .unwrap()
+            .with_max_conversations(Some(3));
+        let now = chrono::Utc::now().timestamp();
+
+        // The oldest is pinned; "middle" is the oldest unpinned once its message touches it
+        chat_db
+            .create_conversation(&conversation("pinned", 100, true))
+            .await
+            .unwrap();
+        chat_db
+            .create_conversation(&conversation("middle", 200, false))
+            .await
+            .unwrap();
+        chat_db
+            .add_message(&Message::new(
+                "m1".to_string(),
+                "middle".to_string(),
+                MessageRole::User,
+                "hello".to_string(),
+            ))
+            .await
+            .unwrap();
+        chat_db
+            .create_conversation(&conversation("recent", now + 100, false))
+            .await
+            .unwrap();
+        assert_eq!(conversation_ids(&chat_db).await.len(), 3);
+
+        chat_db
+            .create_conversation(&conversation("newest", now, false))
+            .await
+            .unwrap();
+        assert_eq!(
+            conversation_ids(&chat_db).await,
+            vec!["newest", "pinned", "recent"]
+        );
+        assert!(chat_db.get_messages("middle").await.unwrap().is_empty());
+
+        // With only pinned conversations left to evict, the cap is exceeded instead
+        chat_db
+            .set_conversation_pinned("recent", true)
+            .await
+            .unwrap();
+        chat_db
+            .set_conversation_pinned("newest", true)
+            .await
+            .unwrap();
+        chat_db
+            .create_conversation(&conversation("extra", now, false))
+            .await
+            .unwrap();
+        assert_eq!(
+            conversation_ids(&chat_db).await,
+            vec!["extra", "newest", "pinned", "recent"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_cap_keeps_every_conversation() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let chat_db = ChatDb::new(db_path.to_str().unwrap()).await.unwrap();
+
+        for index in 0..5 {
+            chat_db
+                .create_conversation(&conversation(&format!("c{}", index), index, false))
+                .await
+                .unwrap();
+        }
+        assert_eq!(conversation_ids(&chat_db).await.len(), 5);
+    }
+}
@@ -6,9 +6,10 @@ pub mod bridge_manager;
 pub mod bridge_session;
 pub mod db;
 pub mod models;
+pub mod summarize;
 
 pub use bridge_manager::BridgeManager;
 #[allow(unused_imports)] // Will be used in Phase 4 for metrics/monitoring
 pub use bridge_session::BridgeSession;
 pub use db::ChatDb;
-pub use models::{Conversation, Message, MessageRole};
+pub use models::{AuditEntry, Conversation, ConversationSummary, Message, MessageRole};
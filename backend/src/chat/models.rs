@@ -14,6 +14,8 @@ pub enum MessageRole {
     User,
     /// Message from the assistant/AI
     Assistant,
+    /// Generated by the server (e.g. a summary of older messages)
+    System,
 }
 
 impl MessageRole {
@@ -22,6 +24,7 @@ impl MessageRole {
         match self {
             MessageRole::User => "user",
             MessageRole::Assistant => "assistant",
+            MessageRole::System => "system",
         }
     }
 }
@@ -31,6 +34,7 @@ impl From<&str> for MessageRole {
         match s {
             "user" => MessageRole::User,
             "assistant" => MessageRole::Assistant,
+            "system" => MessageRole::System,
             _ => MessageRole::User,
         }
     }
@@ -47,6 +51,26 @@ pub struct Conversation {
     pub created_at: i64,
     /// When the conversation was last updated (Unix timestamp)
     pub updated_at: i64,
+    /// User-assigned tags (stored as a JSON array)
+    #[sqlx(json)]
+    pub tags: Vec<String>,
+    /// Whether the conversation is pinned to the top of the list
+    pub pinned: bool,
+    /// Persona sent to the model with every turn (None = no system prompt)
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+}
+
+/// A conversation with aggregate statistics over its messages
+#[derive(Debug, Clone, FromRow)]
+pub struct ConversationSummary {
+    /// The conversation itself
+    #[sqlx(flatten)]
+    pub conversation: Conversation,
+    /// Number of messages in the conversation
+    pub message_count: i64,
+    /// Total characters across all message contents
+    pub content_chars: i64,
 }
 
 impl Conversation {
@@ -58,6 +82,9 @@ impl Conversation {
             title,
             created_at: now,
             updated_at: now,
+            tags: Vec::new(),
+            pinned: false,
+            system_prompt: None,
         }
     }
 
@@ -82,11 +109,13 @@ pub struct Message {
     /// ID of the conversation this message belongs to
     pub conversation_id: String,
     /// Role of the message sender
-    pub role: String, // Stored as "user" or "assistant" in DB
+    pub role: String, // Stored as "user", "assistant" or "system" in DB
     /// Content of the message
     pub content: String,
     /// When the message was created (Unix timestamp)
     pub created_at: i64,
+    /// Whether generation was stopped by the user (content is the partial reply)
+    pub stopped: bool,
 }
 
 impl Message {
@@ -98,11 +127,11 @@ impl Message {
             role: role.as_str().to_string(),
             content,
             created_at: Utc::now().timestamp(),
+            stopped: false,
         }
     }
 
     /// Get the message role as enum
-    #[allow(dead_code)]
     pub fn role_enum(&self) -> MessageRole {
         MessageRole::from(self.role.as_str())
     }
@@ -113,3 +142,68 @@ impl Message {
         DateTime::from_timestamp(self.created_at, 0).unwrap_or_else(Utc::now)
     }
 }
+
+/// A single orchestration audit log entry
+///
+/// Records the outcome of one orchestration run. The raw goal text is only
+/// stored when `OrchestratorConfig::audit_store_goal` is enabled.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AuditEntry {
+    /// Unique identifier for the entry
+    pub id: String,
+    /// When the orchestration started (Unix timestamp)
+    pub created_at: i64,
+    /// Short hash of the goal
+    pub goal_hash: String,
+    /// Raw goal text (only when enabled by config)
+    pub goal: Option<String>,
+    /// Short hash of the generated plan (None if planning failed)
+    pub plan_hash: Option<String>,
+    /// Number of steps in the generated plan
+    pub step_count: i64,
+    /// Whether the orchestration completed successfully
+    pub success: bool,
+    /// Total duration in milliseconds
+    pub duration_ms: i64,
+    /// Error message (if failed)
+    pub error: Option<String>,
+    /// Executed plan as JSON (only when enabled by config); loaded for replay only
+    #[serde(skip)]
+    #[sqlx(default)]
+    pub plan: Option<String>,
+    /// Step outputs as a JSON object, retained with the plan; loaded for run-from only
+    #[serde(skip)]
+    #[sqlx(default)]
+    pub step_outputs: Option<String>,
+    /// `ExecutionSummary` as JSON, retained with the plan; loaded for results only
+    #[serde(skip)]
+    #[sqlx(default)]
+    pub summary: Option<String>,
+}
+
+impl AuditEntry {
+    /// Create a new audit entry for an orchestration that is starting now
+    pub fn new(goal_hash: String, goal: Option<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            created_at: Utc::now().timestamp(),
+            goal_hash,
+            goal,
+            plan_hash: None,
+            step_count: 0,
+            success: false,
+            duration_ms: 0,
+            error: None,
+            plan: None,
+            step_outputs: None,
+            summary: None,
+        }
+    }
+
+    /// Record the outcome of the orchestration
+    pub fn finish(&mut self, success: bool, error: Option<String>, elapsed: std::time::Duration) {
+        self.success = success;
+        self.error = error;
+        self.duration_ms = elapsed.as_millis() as i64;
+    }
+}
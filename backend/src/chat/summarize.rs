@@ -0,0 +1,181 @@
+//! Conversation summarization
+//!
+//! Long conversations are kept bounded by replacing older messages with a
+//! single system message that summarizes them. The summary is extractive (a
+//! clipped line per message, no model call), so it is cheap and deterministic.
+
+use crate::chat::models::{Message, MessageRole};
+
+/// Default message count after which a conversation is summarized
+pub const DEFAULT_SUMMARIZE_THRESHOLD: usize = 100;
+
+/// Characters kept from each summarized message
+const SUMMARY_LINE_CHARS: usize = 200;
+
+/// Upper bound on a summary's length; the oldest lines are dropped beyond it
+const MAX_SUMMARY_CHARS: usize = 8_000;
+
+/// First line of every summary message
+const SUMMARY_HEADER: &str = "Summary of earlier messages:";
+
+/// When to summarize a conversation and how much to keep verbatim
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SummarizePolicy {
+    /// Summarize once a conversation has more messages than this
+    pub threshold: usize,
+    /// Most recent messages kept as-is when summarizing
+    pub keep_recent: usize,
+}
+
+impl SummarizePolicy {
+    /// Policy that keeps the most recent half of `threshold` messages verbatim
+    ///
+    /// `threshold` is raised to 2 so there is always something to summarize.
+    pub fn new(threshold: usize) -> Self {
+        let threshold = threshold.max(2);
+        Self {
+            threshold,
+            keep_recent: threshold / 2,
+        }
+    }
+}
+
+/// Build the summary text for `messages` (oldest first)
+///
+/// Each message becomes a `- role: text` line clipped to a fixed length.
+/// Lines of an earlier summary are carried over, so repeated summarization
+/// keeps the whole history in one message, bounded by dropping the oldest lines.
+pub fn build_summary(messages: &[Message]) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for message in messages {
+        if message.role_enum() == MessageRole::System {
+            lines.extend(
+                message
+                    .content
+                    .lines()
+                    .filter(|line| *line != SUMMARY_HEADER)
+                    .map(str::to_string),
+            );
+            continue;
+        }
+        let text = message
+            .content
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let clipped = if text.chars().count() > SUMMARY_LINE_CHARS {
+            let head: String = text.chars().take(SUMMARY_LINE_CHARS).collect();
+            format!("{}...", head)
+        } else {
+            text
+        };
+        lines.push(format!("- {}: {}", message.role, clipped));
+    }
+
+    let mut total: usize = lines.iter().map(|line| line.len() + 1).sum();
+    let mut start = 0;
+    while total > MAX_SUMMARY_CHARS && start < lines.len() {
+        total -= lines[start].len() + 1;
+        start += 1;
+    }
+
+    std::iter::once(SUMMARY_HEADER.to_string())
+        .chain(lines.into_iter().skip(start))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::{ChatDb, Conversation};
+    use tempfile::TempDir;
+
+    fn message(conversation_id: &str, index: i64) -> Message {
+        let role = if index % 2 == 0 {
+            MessageRole::User
+        } else {
+            MessageRole::Assistant
+        };
+        let mut message = Message::new(
+            format!("msg-{}", index),
+            conversation_id.to_string(),
+            role,
+            format!("message number {}", index),
+        );
+        message.created_at = 1_000 + index;
+        message
+    }
+
+    #[test]
+    fn test_policy_keeps_half_and_never_zero() {
+        assert_eq!(
+            SummarizePolicy::new(10),
+            SummarizePolicy {
+                threshold: 10,
+                keep_recent: 5
+            }
+        );
+        assert_eq!(SummarizePolicy::new(0).keep_recent, 1);
+    }
+
+    #[test]
+    fn test_build_summary_carries_earlier_summary_and_clips() {
+        let mut earlier = message("c", 0);
+        earlier.role = MessageRole::System.as_str().to_string();
+        earlier.content = format!("{}\n- user: first question", SUMMARY_HEADER);
+        let mut long = message("c", 1);
+        long.content = "x".repeat(SUMMARY_LINE_CHARS + 50);
+
+        let summary = build_summary(&[earlier, long]);
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines[0], SUMMARY_HEADER);
+        assert_eq!(lines[1], "- user: first question");
+        assert!(lines[2].starts_with("- assistant: xxx"));
+        assert!(lines[2].ends_with("..."));
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_exceeding_threshold_summarizes_older_messages() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let chat_db = ChatDb::new(db_path.to_str().unwrap())
+            .await
+            .unwrap()
+            .with_summarize_policy(Some(SummarizePolicy::new(6)));
+        chat_db
+            .create_conversation(&Conversation::new("c".to_string(), "Long".to_string()))
+            .await
+            .unwrap();
+
+        // Up to the threshold nothing changes
+        for index in 0..6 {
+            chat_db.add_message(&message("c", index)).await.unwrap();
+        }
+        assert_eq!(chat_db.get_messages("c").await.unwrap().len(), 6);
+
+        // The 7th message summarizes all but the 3 most recent
+        chat_db.add_message(&message("c", 6)).await.unwrap();
+        let messages = chat_db.get_messages("c").await.unwrap();
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[0].role_enum(), MessageRole::System);
+        assert!(messages[0].content.contains("message number 0"));
+        assert!(messages[0].content.contains("message number 3"));
+        assert!(!messages[0].content.contains("message number 4"));
+        let recent: Vec<&str> = messages[1..].iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(recent, vec!["msg-4", "msg-5", "msg-6"]);
+
+        // Summarizing again folds the earlier summary into the new one
+        for index in 7..10 {
+            chat_db.add_message(&message("c", index)).await.unwrap();
+        }
+        let messages = chat_db.get_messages("c").await.unwrap();
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[0].role_enum(), MessageRole::System);
+        assert!(messages[0].content.contains("message number 0"));
+        assert!(messages[0].content.contains("message number 6"));
+        assert_eq!(messages[0].content.matches(SUMMARY_HEADER).count(), 1);
+        assert_eq!(messages[3].id, "msg-9");
+    }
+}
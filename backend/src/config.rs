@@ -7,6 +7,16 @@
 //! persistence settings, execution settings). For agent-level configuration
 //! (agent types, agent configs), see `state::config`.
 
+use crate::api::queries::DEFAULT_BATCH_QUERY_CONCURRENCY;
+use crate::api::streaming::{DEFAULT_MAX_SSE_DURATION_SECS, DEFAULT_SSE_RETRY_MS};
+use crate::chat::bridge_manager::{
+    DEFAULT_BRIDGE_IDLE_TIMEOUT, DEFAULT_BRIDGE_SPAWN_ATTEMPTS, DEFAULT_BRIDGE_SPAWN_RETRY_DELAY,
+    DEFAULT_MAX_BRIDGE_SESSIONS,
+};
+use crate::chat::summarize::{SummarizePolicy, DEFAULT_SUMMARIZE_THRESHOLD};
+use crate::executor::cli::{OutputLimit, DEFAULT_FALLBACK_WORKING_DIR, DEFAULT_MAX_OUTPUT_BYTES};
+use crate::orchestrator::execution_limiter::{QueuePolicy, DEFAULT_MAX_CONCURRENT_EXECUTIONS};
+use crate::state::AgentType;
 use std::env;
 
 /// Application configuration
@@ -22,22 +32,93 @@ pub struct Config {
 }
 
 /// Server configuration
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct ServerConfig {
     /// Port to bind the server to
     pub port: u16,
     /// Host address to bind to
     pub host: String,
+    /// Agent type used when an agent has to be auto-created
+    pub default_agent_type: AgentType,
+    /// Bearer token required to open the `/ws` endpoint (None = no auth, dev only)
+    pub ws_auth_token: Option<String>,
+    /// Hex SHA-256 of the bearer token required on API writes (None = no auth)
+    pub api_token_sha256: Option<String>,
+    /// Require the API token on GET requests too (health checks stay open)
+    pub api_auth_protect_reads: bool,
+    /// Maximum number of orchestrations running at once
+    pub max_concurrent_executions: usize,
+    /// Whether orchestrations beyond the limit are rejected (429) or queued
+    pub execution_queue_policy: QueuePolicy,
+    /// Maximum number of chat bridge processes alive at once (idle ones are evicted LRU)
+    pub max_bridge_sessions: usize,
+    /// Seconds a chat bridge may sit unused before it is killed (0 = never)
+    pub bridge_idle_timeout_secs: u64,
+    /// Times a chat bridge spawn is attempted before giving up
+    pub bridge_spawn_attempts: u32,
+    /// Pause between chat bridge spawn attempts, in milliseconds
+    pub bridge_spawn_retry_delay_ms: u64,
+    /// Reconnect delay sent as the `retry:` directive opening SSE streams (0 = none)
+    pub sse_retry_ms: u64,
+    /// Longest an SSE stream may stay open before it is closed, in seconds (0 = no cap)
+    pub max_sse_duration_secs: u64,
+    /// Directories the working directory may be set inside (empty = anywhere)
+    pub allowed_working_dirs: Vec<String>,
+}
+
+// Manual Debug so the auth token is never written to logs
+impl std::fmt::Debug for ServerConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServerConfig")
+            .field("port", &self.port)
+            .field("host", &self.host)
+            .field("default_agent_type", &self.default_agent_type)
+            .field(
+                "ws_auth_token",
+                &self.ws_auth_token.as_ref().map(|_| "<redacted>"),
+            )
+            .field(
+                "api_token_sha256",
+                &self.api_token_sha256.as_ref().map(|_| "<redacted>"),
+            )
+            .field("api_auth_protect_reads", &self.api_auth_protect_reads)
+            .field("max_concurrent_executions", &self.max_concurrent_executions)
+            .field("execution_queue_policy", &self.execution_queue_policy)
+            .field("max_bridge_sessions", &self.max_bridge_sessions)
+            .field("bridge_idle_timeout_secs", &self.bridge_idle_timeout_secs)
+            .field("bridge_spawn_attempts", &self.bridge_spawn_attempts)
+            .field(
+                "bridge_spawn_retry_delay_ms",
+                &self.bridge_spawn_retry_delay_ms,
+            )
+            .field("sse_retry_ms", &self.sse_retry_ms)
+            .field("max_sse_duration_secs", &self.max_sse_duration_secs)
+            .field("allowed_working_dirs", &self.allowed_working_dirs)
+            .finish()
+    }
 }
 
 /// Persistence configuration
 #[derive(Debug, Clone)]
 pub struct PersistenceConfig {
-    /// Base directory for storing agent data
-    #[allow(dead_code)] // Will be used when persistence is fully implemented
+    /// Base directory for storing agent data (created at startup if missing)
     pub data_dir: String,
     /// Path to SQLite database file for chat storage
     pub db_path: String,
+    /// Summarize and truncate older messages of long conversations (default off)
+    pub auto_summarize: bool,
+    /// Message count after which a conversation is summarized
+    pub summarize_threshold: usize,
+    /// Most conversations kept before the oldest unpinned ones are evicted (None = no cap)
+    pub max_conversations: Option<usize>,
+}
+
+impl PersistenceConfig {
+    /// Summarization policy for the chat database (None when disabled)
+    pub fn summarize_policy(&self) -> Option<SummarizePolicy> {
+        self.auto_summarize
+            .then(|| SummarizePolicy::new(self.summarize_threshold))
+    }
 }
 
 /// Execution configuration
@@ -45,6 +126,14 @@ pub struct PersistenceConfig {
 pub struct ExecutionConfig {
     /// Default timeout for agent execution (in seconds)
     pub default_timeout_secs: u64,
+    /// Cap on stdout/stderr captured from an agent process
+    pub output_limit: OutputLimit,
+    /// Directory agents run in when they have no working directory (default `/tmp`)
+    pub fallback_working_dir: String,
+    /// Directory that per-query working directories must lie inside (None = any)
+    pub workspace_root: Option<String>,
+    /// Number of queries from one batch request executed at once
+    pub batch_query_concurrency: usize,
 }
 
 impl Config {
@@ -57,6 +146,60 @@ impl Config {
                     .and_then(|p| p.parse().ok())
                     .unwrap_or(8080),
                 host: env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
+                default_agent_type: env::var("DEFAULT_AGENT_TYPE")
+                    .map(|t| parse_agent_type(&t))
+                    .unwrap_or(AgentType::Gemini),
+                ws_auth_token: env::var("WS_AUTH_TOKEN")
+                    .ok()
+                    .filter(|t| !t.trim().is_empty()),
+                api_token_sha256: env::var("API_TOKEN_SHA256")
+                    .ok()
+                    .map(|hash| hash.trim().to_lowercase())
+                    .filter(|hash| !hash.is_empty()),
+                api_auth_protect_reads: env::var("API_AUTH_PROTECT_READS")
+                    .map(|v| v == "true" || v == "1")
+                    .unwrap_or(false),
+                max_concurrent_executions: env::var("MAX_CONCURRENT_EXECUTIONS")
+                    .ok()
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or(DEFAULT_MAX_CONCURRENT_EXECUTIONS),
+                execution_queue_policy: env::var("EXECUTION_QUEUE_POLICY")
+                    .ok()
+                    .and_then(|p| QueuePolicy::parse(&p))
+                    .unwrap_or_default(),
+                max_bridge_sessions: env::var("MAX_BRIDGE_SESSIONS")
+                    .ok()
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or(DEFAULT_MAX_BRIDGE_SESSIONS),
+                bridge_idle_timeout_secs: env::var("BRIDGE_IDLE_TIMEOUT_SECS")
+                    .ok()
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or(DEFAULT_BRIDGE_IDLE_TIMEOUT.as_secs()),
+                bridge_spawn_attempts: env::var("BRIDGE_SPAWN_ATTEMPTS")
+                    .ok()
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or(DEFAULT_BRIDGE_SPAWN_ATTEMPTS),
+                bridge_spawn_retry_delay_ms: env::var("BRIDGE_SPAWN_RETRY_DELAY_MS")
+                    .ok()
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or(DEFAULT_BRIDGE_SPAWN_RETRY_DELAY.as_millis() as u64),
+                sse_retry_ms: env::var("SSE_RETRY_MS")
+                    .ok()
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or(DEFAULT_SSE_RETRY_MS),
+                max_sse_duration_secs: env::var("MAX_SSE_DURATION_SECS")
+                    .ok()
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or(DEFAULT_MAX_SSE_DURATION_SECS),
+                allowed_working_dirs: env::var("ALLOWED_WORKING_DIRS")
+                    .map(|dirs| {
+                        dirs.split(',')
+                            .map(str::trim)
+                            .filter(|dir| !dir.is_empty())
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
             },
             persistence: PersistenceConfig {
                 data_dir: env::var("DATA_DIR").unwrap_or_else(|_| {
@@ -71,12 +214,43 @@ impl Config {
                     // Default to /app/data/chat.db in Docker, or ./data/chat.db locally
                     "/app/data/chat.db".to_string()
                 }),
+                auto_summarize: env::var("CONVERSATION_AUTO_SUMMARIZE")
+                    .map(|v| v == "true" || v == "1")
+                    .unwrap_or(false),
+                summarize_threshold: env::var("CONVERSATION_SUMMARIZE_THRESHOLD")
+                    .ok()
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or(DEFAULT_SUMMARIZE_THRESHOLD),
+                max_conversations: env::var("MAX_CONVERSATIONS")
+                    .ok()
+                    .and_then(|n| n.parse().ok())
+                    .filter(|&n: &usize| n > 0),
             },
             execution: ExecutionConfig {
                 default_timeout_secs: env::var("EXECUTION_TIMEOUT_SECS")
                     .ok()
                     .and_then(|t| t.parse().ok())
                     .unwrap_or(30),
+                output_limit: OutputLimit {
+                    max_bytes: env::var("MAX_CLI_OUTPUT_BYTES")
+                        .ok()
+                        .and_then(|n| n.parse().ok())
+                        .unwrap_or(DEFAULT_MAX_OUTPUT_BYTES),
+                    kill_on_overflow: env::var("KILL_ON_CLI_OUTPUT_OVERFLOW")
+                        .map(|v| !matches!(v.trim().to_lowercase().as_str(), "0" | "false" | "no"))
+                        .unwrap_or(true),
+                },
+                fallback_working_dir: env::var("FALLBACK_WORKING_DIR")
+                    .ok()
+                    .filter(|dir| !dir.trim().is_empty())
+                    .unwrap_or_else(|| DEFAULT_FALLBACK_WORKING_DIR.to_string()),
+                workspace_root: env::var("WORKSPACE_ROOT")
+                    .ok()
+                    .filter(|dir| !dir.trim().is_empty()),
+                batch_query_concurrency: env::var("BATCH_QUERY_CONCURRENCY")
+                    .ok()
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or(DEFAULT_BATCH_QUERY_CONCURRENCY),
             },
         }
     }
@@ -85,4 +259,353 @@ impl Config {
     pub fn server_addr(&self) -> String {
         format!("{}:{}", self.server.host, self.server.port)
     }
+
+    /// Validate the configuration
+    ///
+    /// Called at startup, before binding, so a misconfigured deployment fails fast.
+    /// Checks that the server address parses, the data directory exists (it is
+    /// created if missing), the agent fallback working directory and workspace root exist, the API token
+    /// hash is well-formed, the execution timeout, output cap and batch query concurrency are non-zero and the default
+    /// agent type can be auto-created, at least one orchestration and one chat
+    /// bridge may run and, if enabled, conversation summarization keeps some history.
+    /// Returns Ok(()) if valid, Err with message if invalid
+    pub fn validate(&self) -> Result<(), String> {
+        self.server_addr()
+            .parse::<std::net::SocketAddr>()
+            .map_err(|e| {
+                format!(
+                    "Invalid server address '{}' (HOST/PORT): {}",
+                    self.server_addr(),
+                    e
+                )
+            })?;
+
+        let data_dir = std::path::Path::new(&self.persistence.data_dir);
+        if data_dir.exists() && !data_dir.is_dir() {
+            return Err(format!(
+                "DATA_DIR '{}' exists but is not a directory",
+                self.persistence.data_dir
+            ));
+        }
+        std::fs::create_dir_all(data_dir).map_err(|e| {
+            format!(
+                "DATA_DIR '{}' does not exist and could not be created: {}",
+                self.persistence.data_dir, e
+            )
+        })?;
+
+        if !std::path::Path::new(&self.execution.fallback_working_dir).is_dir() {
+            return Err(format!(
+                "FALLBACK_WORKING_DIR '{}' does not exist or is not a directory",
+                self.execution.fallback_working_dir
+            ));
+        }
+
+        if let Some(root) = &self.execution.workspace_root {
+            if !std::path::Path::new(root).is_dir() {
+                return Err(format!(
+                    "WORKSPACE_ROOT '{}' does not exist or is not a directory",
+                    root
+                ));
+            }
+        }
+
+        for dir in &self.server.allowed_working_dirs {
+            if !std::path::Path::new(dir).is_dir() {
+                return Err(format!(
+                    "ALLOWED_WORKING_DIRS entry '{}' does not exist or is not a directory",
+                    dir
+                ));
+            }
+        }
+
+        if let Some(hash) = &self.server.api_token_sha256 {
+            if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(
+                    "API_TOKEN_SHA256 must be the 64-character hex SHA-256 of the token"
+                        .to_string(),
+                );
+            }
+        }
+
+        if self.execution.default_timeout_secs == 0 {
+            return Err("EXECUTION_TIMEOUT_SECS must be > 0".to_string());
+        }
+
+        if self.execution.output_limit.max_bytes == 0 {
+            return Err("MAX_CLI_OUTPUT_BYTES must be > 0".to_string());
+        }
+
+        if self.server.max_concurrent_executions == 0 {
+            return Err("MAX_CONCURRENT_EXECUTIONS must be > 0".to_string());
+        }
+
+        if self.execution.batch_query_concurrency == 0 {
+            return Err("BATCH_QUERY_CONCURRENCY must be > 0".to_string());
+        }
+
+        if self.server.max_bridge_sessions == 0 {
+            return Err("MAX_BRIDGE_SESSIONS must be > 0".to_string());
+        }
+
+        if self.server.bridge_spawn_attempts == 0 {
+            return Err("BRIDGE_SPAWN_ATTEMPTS must be > 0".to_string());
+        }
+
+        if self.persistence.auto_summarize && self.persistence.summarize_threshold < 2 {
+            return Err(
+                "CONVERSATION_SUMMARIZE_THRESHOLD must be >= 2 when auto-summarize is enabled"
+                    .to_string(),
+            );
+        }
+
+        if !matches!(
+            self.server.default_agent_type,
+            AgentType::Gemini | AgentType::ClaudeCode
+        ) {
+            return Err(format!(
+                "Unsupported DEFAULT_AGENT_TYPE {:?}: auto-created agents must be Gemini or ClaudeCode",
+                self.server.default_agent_type
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Parse an agent type name from configuration (case-insensitive)
+///
+/// Unknown names are returned as `AgentType::Other` so validation can report them.
+fn parse_agent_type(value: &str) -> AgentType {
+    match value.trim().to_lowercase().as_str() {
+        "gemini" => AgentType::Gemini,
+        "claude" | "claudecode" | "claude_code" | "claude-code" => AgentType::ClaudeCode,
+        "generic" => AgentType::Generic,
+        _ => AgentType::Other(value.trim().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_agent_type() {
+        assert_eq!(parse_agent_type("gemini"), AgentType::Gemini);
+        assert_eq!(parse_agent_type("Claude"), AgentType::ClaudeCode);
+        assert_eq!(parse_agent_type("claude_code"), AgentType::ClaudeCode);
+        assert_eq!(parse_agent_type("generic"), AgentType::Generic);
+        assert_eq!(
+            parse_agent_type("mystery"),
+            AgentType::Other("mystery".to_string())
+        );
+    }
+
+    #[test]
+    fn test_validate_default_agent_type() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(&temp_dir);
+        config.server.default_agent_type = AgentType::ClaudeCode;
+        assert!(config.validate().is_ok());
+
+        config.server.default_agent_type = AgentType::Generic;
+        assert!(config.validate().is_err());
+
+        config.server.default_agent_type = AgentType::Other("mystery".to_string());
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("mystery"));
+    }
+
+    #[test]
+    fn test_server_config_debug_redacts_ws_token() {
+        let mut config = Config::from_env();
+        config.server.ws_auth_token = Some("super-secret".to_string());
+        let debug = format!("{:?}", config);
+        assert!(!debug.contains("super-secret"));
+        assert!(debug.contains("<redacted>"));
+    }
+
+    /// Config with every field valid and the data dir inside `temp_dir`
+    fn valid_config(temp_dir: &tempfile::TempDir) -> Config {
+        let mut config = Config::from_env();
+        config.server.host = "127.0.0.1".to_string();
+        config.server.port = 8080;
+        config.server.default_agent_type = AgentType::Gemini;
+        config.persistence.data_dir = temp_dir.path().join("data").to_string_lossy().to_string();
+        config.execution.default_timeout_secs = 30;
+        config.execution.output_limit.max_bytes = 1024;
+        config.server.max_concurrent_executions = 4;
+        config.execution.batch_query_concurrency = 4;
+        config.server.max_bridge_sessions = 8;
+        config.server.bridge_spawn_attempts = 3;
+        config.execution.fallback_working_dir = temp_dir.path().to_string_lossy().to_string();
+        config
+    }
+
+    #[test]
+    fn test_validate_valid_config_creates_data_dir() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = valid_config(&temp_dir);
+        assert!(config.validate().is_ok());
+        assert!(temp_dir.path().join("data").is_dir());
+    }
+
+    #[test]
+    fn test_validate_rejects_missing_allowed_working_dir() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(&temp_dir);
+        config.server.allowed_working_dirs = vec![temp_dir.path().to_string_lossy().to_string()];
+        assert!(config.validate().is_ok());
+
+        config
+            .server
+            .allowed_working_dirs
+            .push("/no/such/root-7d1c".to_string());
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("ALLOWED_WORKING_DIRS"));
+    }
+
+    #[test]
+    fn test_validate_rejects_missing_workspace_root() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(&temp_dir);
+        config.execution.workspace_root = Some(
+            temp_dir
+                .path()
+                .join("no-such-root")
+                .to_string_lossy()
+                .to_string(),
+        );
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("WORKSPACE_ROOT"), "got: {}", err);
+    }
+
+    #[test]
+    fn test_validate_rejects_malformed_api_token_hash() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(&temp_dir);
+        config.server.api_token_sha256 = Some("plaintext-token".to_string());
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("API_TOKEN_SHA256"), "got: {}", err);
+
+        config.server.api_token_sha256 = Some("ab".repeat(32));
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_unparseable_address() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(&temp_dir);
+        config.server.host = "not a host".to_string();
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("Invalid server address"), "got: {}", err);
+        assert!(err.contains("not a host"));
+    }
+
+    #[test]
+    fn test_validate_rejects_uncreatable_data_dir() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("a-file");
+        std::fs::write(&file_path, "x").unwrap();
+
+        let mut config = valid_config(&temp_dir);
+        config.persistence.data_dir = file_path.to_string_lossy().to_string();
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("not a directory"), "got: {}", err);
+
+        config.persistence.data_dir = file_path.join("nested").to_string_lossy().to_string();
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("could not be created"), "got: {}", err);
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_timeout() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(&temp_dir);
+        config.execution.default_timeout_secs = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("EXECUTION_TIMEOUT_SECS"), "got: {}", err);
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_concurrent_executions() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(&temp_dir);
+        config.server.max_concurrent_executions = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("MAX_CONCURRENT_EXECUTIONS"), "got: {}", err);
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_batch_query_concurrency() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(&temp_dir);
+        config.execution.batch_query_concurrency = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("BATCH_QUERY_CONCURRENCY"), "got: {}", err);
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_output_cap() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(&temp_dir);
+        config.execution.output_limit.max_bytes = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("MAX_CLI_OUTPUT_BYTES"), "got: {}", err);
+    }
+
+    #[test]
+    fn test_validate_rejects_tiny_summarize_threshold_only_when_enabled() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(&temp_dir);
+        config.persistence.summarize_threshold = 1;
+        config.persistence.auto_summarize = false;
+        assert!(config.validate().is_ok());
+        assert!(config.persistence.summarize_policy().is_none());
+
+        config.persistence.auto_summarize = true;
+        let err = config.validate().unwrap_err();
+        assert!(
+            err.contains("CONVERSATION_SUMMARIZE_THRESHOLD"),
+            "got: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_bridge_sessions() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(&temp_dir);
+        config.server.max_bridge_sessions = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("MAX_BRIDGE_SESSIONS"), "got: {}", err);
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_bridge_spawn_attempts() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(&temp_dir);
+        config.server.bridge_spawn_attempts = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("BRIDGE_SPAWN_ATTEMPTS"), "got: {}", err);
+    }
+
+    #[test]
+    fn test_validate_rejects_missing_fallback_working_dir() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(&temp_dir);
+
+        config.execution.fallback_working_dir = temp_dir
+            .path()
+            .join("missing")
+            .to_string_lossy()
+            .to_string();
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("FALLBACK_WORKING_DIR"), "got: {}", err);
+
+        // A file is not a usable working directory either
+        let file = temp_dir.path().join("file");
+        std::fs::write(&file, "x").unwrap();
+        config.execution.fallback_working_dir = file.to_string_lossy().to_string();
+        assert!(config.validate().is_err());
+    }
 }
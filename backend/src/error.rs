@@ -45,6 +45,10 @@ pub enum AppError {
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
 
+    /// Request is missing a valid API token
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
     /// Path exists but is not a directory
     #[error("Path is not a directory: {0}")]
     NotADirectory(String),
@@ -57,6 +61,10 @@ pub enum AppError {
     #[error("Invalid plan: {0}")]
     InvalidPlan(String),
 
+    /// Submitted plan failed validation, with one entry per offending field
+    #[error("Invalid plan: {} validation error(s)", .0.len())]
+    PlanValidationFailed(Vec<crate::orchestrator::plan_types::FieldError>),
+
     /// Plan execution failed (e.g., timeout, graph error)
     #[error("Plan execution failed: {0}")]
     PlanExecutionFailed(String),
@@ -78,9 +86,61 @@ pub enum AppError {
     #[allow(dead_code)] // Will be used when planner errors are properly categorized
     PlanningFailed(String),
 
+    /// A run's actual token usage exceeded `max_actual_tokens`; the rest of the plan was skipped
+    #[error(
+        "Token budget exceeded: used {used_tokens} tokens, exceeding max_actual_tokens ({max_tokens}) \
+         after {} completed step(s)",
+        .completed.len()
+    )]
+    BudgetExceeded {
+        /// Tokens the run's steps used by the time it was aborted
+        used_tokens: u64,
+        /// The configured limit
+        max_tokens: u64,
+        /// Steps that finished before the abort, with their outputs
+        completed: Vec<crate::orchestrator::graph_executor::StepResult>,
+    },
+
+    /// The run was cancelled by the user before it finished
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+
     /// Operation timed out
     #[error("Timeout: {0}")]
     Timeout(String),
+
+    /// Server is at capacity for this kind of request
+    #[error("Too many requests: {0}")]
+    TooManyRequests(String),
+
+    /// Request content was blocked by an operator policy
+    #[error("Policy violation: {0}")]
+    PolicyViolation(String),
+
+    /// Client asked for a streaming protocol version the server doesn't speak
+    #[error("Unsupported protocol version: {0}")]
+    UnsupportedProtocol(String),
+
+    /// A request field has a value outside what the server accepts
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    /// Gemini refused the prompt on content grounds (e.g. safety filters)
+    #[error("Gemini API blocked the prompt: {reason}")]
+    PromptBlocked {
+        /// Block reason reported by Gemini (e.g. "SAFETY")
+        reason: String,
+    },
+}
+
+impl AppError {
+    /// Gemini's block reason, if this error is a blocked prompt
+    pub fn block_reason(&self) -> Option<&str> {
+        match self {
+            AppError::PromptBlocked { reason } => Some(reason),
+            _ => None,
+        }
+    }
 }
 
 impl IntoResponse for AppError {
@@ -93,8 +153,10 @@ impl IntoResponse for AppError {
             AppError::FileNotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
             AppError::InvalidPath(_) => (StatusCode::BAD_REQUEST, self.to_string()),
             AppError::PermissionDenied(_) => (StatusCode::FORBIDDEN, self.to_string()),
+            AppError::Unauthorized(_) => (StatusCode::UNAUTHORIZED, self.to_string()),
             AppError::NotADirectory(_) => (StatusCode::BAD_REQUEST, self.to_string()),
             AppError::InvalidPlan(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            AppError::PlanValidationFailed(_) => (StatusCode::BAD_REQUEST, self.to_string()),
             AppError::PlanExecutionFailed(_) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, self.to_string())
             }
@@ -104,15 +166,35 @@ impl IntoResponse for AppError {
             AppError::SessionError(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
             AppError::GraphError(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
             AppError::PlanningFailed(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+            AppError::BudgetExceeded { .. } => (StatusCode::UNPROCESSABLE_ENTITY, self.to_string()),
+            AppError::Cancelled(_) => (StatusCode::CONFLICT, self.to_string()),
             AppError::Timeout(_) => (StatusCode::REQUEST_TIMEOUT, self.to_string()),
+            AppError::TooManyRequests(_) => (StatusCode::TOO_MANY_REQUESTS, self.to_string()),
+            AppError::PolicyViolation(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            AppError::UnsupportedProtocol(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            AppError::InvalidRequest(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            AppError::PromptBlocked { .. } => (StatusCode::UNPROCESSABLE_ENTITY, self.to_string()),
             AppError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
         };
 
-        let body = Json(json!({
+        let mut body = json!({
             "error": error_message,
             "status": status.as_u16(),
-        }));
-
-        (status, body).into_response()
+        });
+        // Field-level details let form UIs highlight each offending field
+        if let AppError::PlanValidationFailed(errors) = &self {
+            body["errors"] = json!(errors);
+        }
+        if let Some(reason) = self.block_reason() {
+            body["reason"] = json!(reason);
+        }
+        if let AppError::BudgetExceeded { completed, .. } = &self {
+            body["completed_steps"] = json!(completed
+                .iter()
+                .map(|step| step.step_id.as_str())
+                .collect::<Vec<_>>());
+        }
+
+        (status, Json(body)).into_response()
     }
 }
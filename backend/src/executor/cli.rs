@@ -4,15 +4,50 @@
 
 use crate::executor::error::ExecutionError;
 use crate::state::Agent;
+use std::process::Stdio;
 use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio::process::Command;
+use tokio::sync::mpsc;
 use tokio::time::timeout;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
+
+/// Default cap on captured stdout/stderr per stream, in bytes
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 10 * 1024 * 1024;
+
+/// Directory agents run in when they have no working directory of their own
+///
+/// Keeps CLIs such as Gemini from picking up the server's project files as context.
+pub const DEFAULT_FALLBACK_WORKING_DIR: &str = "/tmp";
+
+/// Cap on the output captured from an agent process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputLimit {
+    /// Most bytes kept from each of stdout and stderr
+    pub max_bytes: usize,
+    /// Whether to kill the process once stdout exceeds `max_bytes`
+    pub kill_on_overflow: bool,
+}
+
+impl Default for OutputLimit {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+            kill_on_overflow: true,
+        }
+    }
+}
 
 /// CLI executor for running agent processes
 pub struct CliExecutor {
     /// Default timeout for process execution (in seconds)
     default_timeout: Duration,
+    /// Cap on captured output
+    output_limit: OutputLimit,
+    /// Working directory for agents without one
+    fallback_working_dir: String,
+    /// Receives each chunk of stdout as it is read
+    stdout_observer: Option<mpsc::Sender<Vec<u8>>>,
 }
 
 impl CliExecutor {
@@ -20,9 +55,38 @@ impl CliExecutor {
     pub fn new(default_timeout_secs: u64) -> Self {
         Self {
             default_timeout: Duration::from_secs(default_timeout_secs),
+            output_limit: OutputLimit::default(),
+            fallback_working_dir: DEFAULT_FALLBACK_WORKING_DIR.to_string(),
+            stdout_observer: None,
         }
     }
 
+    /// Cap captured output at `limit.max_bytes` per stream
+    ///
+    /// Output past the cap is dropped and the response ends with a truncation
+    /// marker. With `kill_on_overflow` the process is killed as soon as stdout
+    /// passes the cap; otherwise it runs to completion and the rest is discarded.
+    pub fn with_output_limit(mut self, limit: OutputLimit) -> Self {
+        self.output_limit = limit;
+        self
+    }
+
+    /// Run agents that have no working directory in `dir` (default `/tmp`)
+    pub fn with_fallback_working_dir(mut self, dir: impl Into<String>) -> Self {
+        self.fallback_working_dir = dir.into();
+        self
+    }
+
+    /// Send each chunk of kept stdout to `observer` as it arrives
+    ///
+    /// The full output is still returned by `execute`; this only lets callers
+    /// follow a long-running process. Chunks past the output cap are not sent.
+    /// Reading waits while the channel is full, so the observer must keep up.
+    pub fn with_stdout_observer(mut self, observer: mpsc::Sender<Vec<u8>>) -> Self {
+        self.stdout_observer = Some(observer);
+        self
+    }
+
     /// Get the default timeout duration
     #[cfg(test)]
     pub fn timeout(&self) -> Duration {
@@ -39,6 +103,39 @@ impl CliExecutor {
     /// * `Ok(String)` - The stdout output from the agent
     /// * `Err(ExecutionError)` - If execution failed
     pub async fn execute(&self, agent: &Agent, query: &str) -> Result<String, ExecutionError> {
+        let (mut stdout, stdout_truncated) = self.run(agent, query).await?;
+        if stdout_truncated {
+            // Don't fail decoding on a character split by the cut
+            if let Err(e) = std::str::from_utf8(&stdout) {
+                if e.error_len().is_none() {
+                    stdout.truncate(e.valid_up_to());
+                }
+            }
+        }
+
+        let mut response = String::from_utf8(stdout).map_err(|e| {
+            ExecutionError::InvalidEncoding(format!("Failed to decode stdout: {}", e))
+        })?;
+        if stdout_truncated {
+            response.push_str(&truncation_marker(self.output_limit.max_bytes));
+        }
+        Ok(response)
+    }
+
+    /// Execute a query and return its stdout bytes without decoding them
+    ///
+    /// Same as `execute`, but output that isn't valid UTF-8 is returned as-is
+    /// instead of failing. Truncated output still ends with the truncation marker.
+    pub async fn execute_raw(&self, agent: &Agent, query: &str) -> Result<Vec<u8>, ExecutionError> {
+        let (mut stdout, stdout_truncated) = self.run(agent, query).await?;
+        if stdout_truncated {
+            stdout.extend_from_slice(truncation_marker(self.output_limit.max_bytes).as_bytes());
+        }
+        Ok(stdout)
+    }
+
+    /// Run the agent process, returning its kept stdout and whether it was truncated
+    async fn run(&self, agent: &Agent, query: &str) -> Result<(Vec<u8>, bool), ExecutionError> {
         info!(
             agent_id = %agent.id,
             agent_name = %agent.name,
@@ -77,9 +174,14 @@ impl CliExecutor {
         }
 
         // Set working directory
-        // If not specified, use /tmp to prevent Gemini CLI from reading project files
-        // This ensures the AI doesn't get unwanted context from the project structure
-        let work_dir = agent.config.working_dir.as_deref().unwrap_or("/tmp");
+        // If not specified, use the fallback (default /tmp) to prevent Gemini CLI from
+        // reading project files. This ensures the AI doesn't get unwanted context from
+        // the project structure
+        let work_dir = agent
+            .config
+            .working_dir
+            .as_deref()
+            .unwrap_or(&self.fallback_working_dir);
         cmd.current_dir(work_dir);
 
         debug!(
@@ -88,24 +190,70 @@ impl CliExecutor {
             "Spawning process"
         );
 
-        // Execute with timeout
-        match timeout(self.default_timeout, cmd.output()).await {
-            Ok(Ok(output)) => {
-                if output.status.success() {
-                    let response = String::from_utf8(output.stdout).map_err(|e| {
-                        ExecutionError::InvalidEncoding(format!("Failed to decode stdout: {}", e))
-                    })?;
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let mut child = cmd.spawn().map_err(|e| {
+            error!(
+                agent_id = %agent.id,
+                error = %e,
+                "Failed to spawn or execute process"
+            );
+            ExecutionError::SpawnFailed(e)
+        })?;
+
+        // Execute with timeout (dropping the child on timeout kills it)
+        let OutputLimit {
+            max_bytes: max_output_bytes,
+            kill_on_overflow,
+        } = self.output_limit;
+        let run = async {
+            // stderr is drained in the background so a full pipe can't block the process
+            let stderr_reader = tokio::spawn(read_capped(
+                child.stderr.take(),
+                max_output_bytes,
+                true,
+                None,
+            ));
+            let (stdout, stdout_truncated) = read_capped(
+                child.stdout.take(),
+                max_output_bytes,
+                !kill_on_overflow,
+                self.stdout_observer.as_ref(),
+            )
+            .await?;
+            if stdout_truncated && kill_on_overflow {
+                child.start_kill()?;
+            }
+            let status = child.wait().await?;
+            let (stderr, _) = stderr_reader.await.map_err(std::io::Error::other)??;
+            Ok::<_, std::io::Error>((status, stdout, stdout_truncated, stderr))
+        };
+
+        match timeout(self.default_timeout, run).await {
+            Ok(Ok((status, stdout, stdout_truncated, stderr))) => {
+                if stdout_truncated {
+                    warn!(
+                        agent_id = %agent.id,
+                        max_output_bytes = max_output_bytes,
+                        killed = kill_on_overflow,
+                        "Process output exceeded the cap and was truncated"
+                    );
+                }
 
+                if status.success() || stdout_truncated {
                     info!(
                         agent_id = %agent.id,
-                        response_len = response.len(),
+                        response_len = stdout.len(),
                         "Query executed successfully"
                     );
 
-                    Ok(response)
+                    Ok((stdout, stdout_truncated))
                 } else {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    let exit_code = output.status.code().unwrap_or(-1);
+                    let stderr = String::from_utf8_lossy(&stderr);
+                    let exit_code = status.code().unwrap_or(-1);
 
                     error!(
                         agent_id = %agent.id,
@@ -140,6 +288,60 @@ impl CliExecutor {
     }
 }
 
+/// Marker appended to output that was cut off at `max_output_bytes`
+pub fn truncation_marker(max_output_bytes: usize) -> String {
+    format!("\n[output truncated: exceeded {} bytes]", max_output_bytes)
+}
+
+/// Read a process pipe, keeping at most `max_bytes`
+///
+/// # Arguments
+/// * `reader` - The pipe to read (`None` reads nothing)
+/// * `max_bytes` - Most bytes to keep
+/// * `drain` - Keep reading (and discarding) past the cap until EOF, instead of
+///   stopping, so the writer never blocks on a full pipe
+/// * `observer` - Receives a copy of each kept chunk as it is read
+///
+/// # Returns
+/// * `Ok((bytes, truncated))` - The kept bytes and whether anything was dropped
+async fn read_capped<R: AsyncRead + Unpin>(
+    reader: Option<R>,
+    max_bytes: usize,
+    drain: bool,
+    observer: Option<&mpsc::Sender<Vec<u8>>>,
+) -> std::io::Result<(Vec<u8>, bool)> {
+    let mut output = Vec::new();
+    let Some(mut reader) = reader else {
+        return Ok((output, false));
+    };
+
+    let mut truncated = false;
+    let mut chunk = [0u8; 8192];
+    loop {
+        let read = reader.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        if truncated {
+            continue;
+        }
+        let room = max_bytes - output.len();
+        let kept = read.min(room);
+        output.extend_from_slice(&chunk[..kept]);
+        if let Some(observer) = observer.filter(|_| kept > 0) {
+            // A closed receiver just means nobody is following along any more
+            let _ = observer.send(chunk[..kept].to_vec()).await;
+        }
+        if read > room {
+            truncated = true;
+            if !drain {
+                break;
+            }
+        }
+    }
+    Ok((output, truncated))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -174,7 +376,9 @@ mod tests {
                 env_vars: HashMap::new(),
                 working_dir: None,
                 options: HashMap::new(),
+                max_concurrency: 1,
             },
+            last_error: None,
         };
 
         // Execute with empty query (echo doesn't need query, just args)
@@ -204,7 +408,9 @@ mod tests {
                 env_vars: HashMap::new(),
                 working_dir: None,
                 options: HashMap::new(),
+                max_concurrency: 1,
             },
+            last_error: None,
         };
 
         let result = executor.execute(&agent, "test").await;
@@ -249,7 +455,9 @@ mod tests {
                 env_vars,
                 working_dir: None,
                 options: HashMap::new(),
+                max_concurrency: 1,
             },
+            last_error: None,
         };
 
         let result = executor.execute(&agent, "").await;
@@ -266,4 +474,117 @@ mod tests {
         }
         // On Windows, this test might behave differently, so we just check it doesn't panic
     }
+
+    /// Agent running `script` through `sh`; execute it with the query "-c"
+    ///
+    /// The query is passed before the configured args, so it supplies `-c`.
+    #[cfg(unix)]
+    fn shell_agent(script: String) -> Agent {
+        Agent {
+            id: "test-4".to_string(),
+            name: "Shell Agent".to_string(),
+            agent_type: AgentType::Generic,
+            status: AgentStatus::Idle,
+            config: AgentConfig {
+                command: "sh".to_string(),
+                args: vec![script],
+                env_vars: HashMap::new(),
+                working_dir: None,
+                options: HashMap::new(),
+                max_concurrency: 1,
+            },
+            last_error: None,
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_executor_truncates_and_kills_runaway_output() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let pid_file = temp_dir.path().join("pid");
+        // `yes` never exits on its own; only the overflow kill can stop it before the timeout
+        let agent = shell_agent(format!("echo $$ > {}; exec yes", pid_file.display()));
+        let executor = CliExecutor::new(10).with_output_limit(OutputLimit {
+            max_bytes: 1024,
+            kill_on_overflow: true,
+        });
+
+        let start = std::time::Instant::now();
+        let output = executor.execute(&agent, "-c").await.unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        let marker = truncation_marker(1024);
+        assert!(output.ends_with(&marker), "got: {:?}", output);
+        assert_eq!(output.len(), 1024 + marker.len());
+        assert!(output.starts_with("y\ny\n"));
+
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        let alive = std::process::Command::new("kill")
+            .args(["-0", pid.trim()])
+            .status()
+            .unwrap()
+            .success();
+        assert!(!alive, "process {} should have been killed", pid.trim());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_raw_keeps_non_utf8_output() {
+        let agent = shell_agent("printf '\\377\\376ok'".to_string());
+        let executor = CliExecutor::new(5);
+
+        let output = executor.execute_raw(&agent, "-c").await.unwrap();
+        assert_eq!(output, b"\xff\xfeok");
+        assert!(matches!(
+            executor.execute(&agent, "-c").await,
+            Err(ExecutionError::InvalidEncoding(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_executor_truncates_without_killing() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let done_file = temp_dir.path().join("done");
+        let agent = shell_agent(format!(
+            "head -c 100000 /dev/zero | tr '\\0' a; touch {}",
+            done_file.display()
+        ));
+        let executor = CliExecutor::new(10).with_output_limit(OutputLimit {
+            max_bytes: 1024,
+            kill_on_overflow: false,
+        });
+
+        let output = executor.execute(&agent, "-c").await.unwrap();
+        assert_eq!(
+            output,
+            format!("{}{}", "a".repeat(1024), truncation_marker(1024))
+        );
+        // The process was left to finish on its own
+        assert!(done_file.exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stdout_observer_sees_output_before_exit() {
+        let agent = shell_agent("echo first; sleep 0.3; echo second".to_string());
+        let (tx, mut rx) = mpsc::channel(8);
+        let executor = CliExecutor::new(10).with_stdout_observer(tx);
+
+        let execution = executor.execute(&agent, "-c");
+        tokio::pin!(execution);
+        // The first line arrives while the process is still sleeping
+        let first = tokio::select! {
+            chunk = rx.recv() => chunk.unwrap(),
+            _ = &mut execution => panic!("process finished before any output was observed"),
+        };
+        assert_eq!(first, b"first\n");
+
+        assert_eq!(execution.await.unwrap(), "first\nsecond\n");
+        let mut rest = Vec::new();
+        while let Ok(chunk) = rx.try_recv() {
+            rest.extend(chunk);
+        }
+        assert_eq!(rest, b"second\n");
+    }
 }
@@ -25,6 +25,10 @@ pub enum ExecutionError {
     #[error("Invalid output encoding: {0}")]
     InvalidEncoding(String),
 
+    /// Execution was abandoned before it finished (e.g. the agent was reset)
+    #[error("Execution aborted: {0}")]
+    Aborted(String),
+
     /// Command executable was not found in PATH
     #[error("Command not found: {0}")]
     #[allow(dead_code)] // Reserved for future use
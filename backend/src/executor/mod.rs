@@ -7,6 +7,6 @@ pub mod cli;
 pub mod error;
 pub mod streaming;
 
-pub use cli::CliExecutor;
+pub use cli::{CliExecutor, OutputLimit};
 pub use error::ExecutionError;
 pub use streaming::StreamingCliExecutor;
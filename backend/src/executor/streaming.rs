@@ -3,27 +3,37 @@
 //! Executes CLI agents by spawning processes and streaming their output line-by-line.
 
 use crate::executor::error::ExecutionError;
-use crate::orchestrator::primitives::parse_gemini_json_response;
+use crate::orchestrator::primitives::{parse_gemini_cli_response, GeminiCliResponse};
 use crate::state::Agent;
 use std::process::Stdio;
 use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
 use tokio::process::Command;
+use tokio::sync::{mpsc, oneshot};
 use tokio::time::timeout;
 use tracing::{debug, error, info};
 
 /// Streaming CLI executor for running agent processes with real-time output
+///
+/// No route constructs one yet (`/api/query/stream` is disabled in favour of
+/// simple chat), so callers must pass the configured fallback directory.
 pub struct StreamingCliExecutor {
     /// Default timeout for process execution (in seconds)
     default_timeout: Duration,
+    /// Working directory for agents without one
+    fallback_working_dir: String,
 }
 
 impl StreamingCliExecutor {
-    /// Create a new streaming CLI executor with default timeout
+    /// Create a new streaming CLI executor
+    ///
+    /// Agents that have no working directory run in `fallback_working_dir`,
+    /// normally `AppState::cli_fallback_working_dir`.
     #[allow(dead_code)]
-    pub fn new(default_timeout_secs: u64) -> Self {
+    pub fn new(default_timeout_secs: u64, fallback_working_dir: impl Into<String>) -> Self {
         Self {
             default_timeout: Duration::from_secs(default_timeout_secs),
+            fallback_working_dir: fallback_working_dir.into(),
         }
     }
 
@@ -34,8 +44,37 @@ impl StreamingCliExecutor {
         &self,
         agent: &Agent,
         query: &str,
-    ) -> Result<tokio::sync::mpsc::Receiver<String>, ExecutionError> {
-        let (tx, rx) = tokio::sync::mpsc::channel(100);
+    ) -> Result<mpsc::Receiver<String>, ExecutionError> {
+        self.spawn_streaming(agent, query, None).await
+    }
+
+    /// Execute a query and stream its output, also reporting the Gemini metadata
+    ///
+    /// Like `execute_streaming`, plus a receiver that resolves to the parsed
+    /// Gemini reply (model, token stats) once the output is complete. The
+    /// metadata is only available for Gemini agents using `--output-format json`;
+    /// for other agents the metadata receiver closes without a value.
+    pub async fn execute_streaming_with_metadata(
+        &self,
+        agent: &Agent,
+        query: &str,
+    ) -> Result<(mpsc::Receiver<String>, oneshot::Receiver<GeminiCliResponse>), ExecutionError>
+    {
+        let (metadata_tx, metadata_rx) = oneshot::channel();
+        let rx = self
+            .spawn_streaming(agent, query, Some(metadata_tx))
+            .await?;
+        Ok((rx, metadata_rx))
+    }
+
+    /// Spawn the process and the tasks streaming its output
+    async fn spawn_streaming(
+        &self,
+        agent: &Agent,
+        query: &str,
+        metadata_tx: Option<oneshot::Sender<GeminiCliResponse>>,
+    ) -> Result<mpsc::Receiver<String>, ExecutionError> {
+        let (tx, rx) = mpsc::channel(100);
         info!(
             agent_id = %agent.id,
             agent_name = %agent.name,
@@ -84,9 +123,14 @@ impl StreamingCliExecutor {
         }
 
         // Set working directory
-        // If not specified, use /tmp to prevent Gemini CLI from reading project files
-        // This ensures the AI doesn't get unwanted context from the project structure
-        let work_dir = agent.config.working_dir.as_deref().unwrap_or("/tmp");
+        // If not specified, use the fallback (default /tmp) to prevent Gemini CLI from
+        // reading project files. This ensures the AI doesn't get unwanted context from
+        // the project structure
+        let work_dir = agent
+            .config
+            .working_dir
+            .as_deref()
+            .unwrap_or(&self.fallback_working_dir);
         cmd.current_dir(work_dir);
 
         // Capture stdout and stderr separately
@@ -141,33 +185,19 @@ impl StreamingCliExecutor {
                             if !output.is_empty() {
                                 if is_gemini_json {
                                     // For JSON mode: parse JSON and extract response field, send entire text at once
-                                    match parse_gemini_json_response(output.trim()) {
-                                        Ok(response_text) => {
-                                            // Send entire parsed response at once (no character-by-character streaming)
-                                            if tx.send(response_text).await.is_err() {
-                                                debug!(
-                                                    agent_id = %agent_id_clone,
-                                                    "Receiver dropped, stopping stdout read"
-                                                );
-                                            }
-                                            line_count += 1;
-                                        }
-                                        Err(e) => {
-                                            // JSON parsing failed, fall back to raw output
-                                            debug!(
-                                                agent_id = %agent_id_clone,
-                                                error = %e,
-                                                "Failed to parse Gemini JSON response, sending raw output"
-                                            );
-                                            // Send raw output as-is
-                                            if tx.send(output.trim().to_string()).await.is_err() {
-                                                debug!(
-                                                    agent_id = %agent_id_clone,
-                                                    "Receiver dropped, stopping stdout read"
-                                                );
-                                            }
-                                            line_count += 1;
-                                        }
+                                    // (the parser falls back to the raw output if it isn't JSON)
+                                    let parsed = parse_gemini_cli_response(output.trim());
+                                    // Send entire parsed response at once (no character-by-character streaming)
+                                    if tx.send(parsed.response.clone()).await.is_err() {
+                                        debug!(
+                                            agent_id = %agent_id_clone,
+                                            "Receiver dropped, stopping stdout read"
+                                        );
+                                    }
+                                    line_count += 1;
+                                    if let Some(metadata_tx) = metadata_tx {
+                                        // The caller may not be interested any more
+                                        let _ = metadata_tx.send(parsed);
                                     }
                                 } else {
                                     // For non-JSON output: send entire output at once
@@ -306,6 +336,8 @@ impl StreamingCliExecutor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::executor::cli::DEFAULT_FALLBACK_WORKING_DIR;
+    use crate::orchestrator::primitives::parse_gemini_json_response;
     use crate::state::{Agent, AgentConfig, AgentStatus, AgentType};
     use std::collections::HashMap;
 
@@ -338,9 +370,109 @@ mod tests {
         assert_eq!(result.unwrap(), "not json at all");
     }
 
+    #[test]
+    fn test_parse_gemini_cli_response_captures_metadata() {
+        let raw = r#"{
+            "response": "Hi there",
+            "model": "gemini-2.5-pro",
+            "stats": {
+                "models": {"gemini-2.5-pro": {"tokens": {"prompt": 12, "candidates": 3, "total": 20, "thoughts": 5}}},
+                "tools": {"totalCalls": 0}
+            }
+        }"#;
+
+        let parsed = parse_gemini_cli_response(raw);
+        assert_eq!(parsed.response, "Hi there");
+        assert_eq!(parsed.model.as_deref(), Some("gemini-2.5-pro"));
+        assert_eq!(parsed.usage["gemini-2.5-pro"].output_tokens, 3);
+        let stats = parsed.stats.expect("stats should be kept");
+        assert_eq!(stats["models"]["gemini-2.5-pro"]["tokens"]["thoughts"], 5);
+        assert_eq!(stats["tools"]["totalCalls"], 0);
+        // The text-only accessor agrees
+        assert_eq!(parse_gemini_json_response(raw).unwrap(), "Hi there");
+
+        // Without a top-level model, a single model in the stats identifies it
+        let raw = r#"{"response": "ok", "stats": {"models": {"gemini-2.5-flash": {"tokens": {"total": 4}}}}}"#;
+        assert_eq!(
+            parse_gemini_cli_response(raw).model.as_deref(),
+            Some("gemini-2.5-flash")
+        );
+
+        // Plain text carries no metadata
+        let parsed = parse_gemini_cli_response("not json at all");
+        assert_eq!(parsed.response, "not json at all");
+        assert!(parsed.model.is_none() && parsed.stats.is_none() && parsed.usage.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_execute_streaming_with_metadata_surfaces_gemini_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("fake-gemini.sh");
+        std::fs::write(
+            &script,
+            r#"echo '{"response": "streamed", "stats": {"models": {"m1": {"tokens": {"prompt": 1, "candidates": 2, "total": 3}}}}}'"#,
+        )
+        .unwrap();
+        // Gemini agents are run as `<command> -p <query> <args...>`
+        let agent = Agent {
+            id: "gemini-json".to_string(),
+            name: "fake gemini".to_string(),
+            agent_type: AgentType::Gemini,
+            status: AgentStatus::Idle,
+            config: AgentConfig {
+                command: "sh".to_string(),
+                args: vec!["--output-format".to_string(), "json".to_string()],
+                env_vars: HashMap::new(),
+                working_dir: None,
+                options: HashMap::new(),
+                max_concurrency: 1,
+            },
+            last_error: None,
+        };
+
+        let executor = StreamingCliExecutor::new(10, DEFAULT_FALLBACK_WORKING_DIR);
+        let (mut rx, metadata) = executor
+            .execute_streaming_with_metadata(&agent, script.to_str().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(rx.recv().await.as_deref(), Some("streamed"));
+        let metadata = metadata.await.expect("metadata should be reported");
+        assert_eq!(metadata.model.as_deref(), Some("m1"));
+        assert_eq!(metadata.usage["m1"].total_tokens, 3);
+    }
+
+    #[tokio::test]
+    async fn test_agent_without_working_dir_runs_in_configured_fallback() {
+        let fallback = tempfile::tempdir().unwrap();
+        let executor = StreamingCliExecutor::new(10, fallback.path().to_string_lossy().to_string());
+        let agent = Agent {
+            id: "pwd-1".to_string(),
+            name: "pwd".to_string(),
+            agent_type: AgentType::Generic,
+            status: AgentStatus::Idle,
+            config: AgentConfig {
+                command: "sh".to_string(),
+                args: vec!["pwd -P".to_string()],
+                env_vars: HashMap::new(),
+                working_dir: None,
+                options: HashMap::new(),
+                max_concurrency: 1,
+            },
+            last_error: None,
+        };
+
+        // Generic agents take the query first: `sh -c "pwd -P"`
+        let mut rx = executor.execute_streaming(&agent, "-c").await.unwrap();
+        let output = rx.recv().await.expect("pwd should print a line");
+        assert_eq!(
+            std::path::PathBuf::from(output),
+            fallback.path().canonicalize().unwrap()
+        );
+    }
+
     #[tokio::test]
     async fn test_streaming_executor_creation() {
-        let executor = StreamingCliExecutor::new(30);
+        let executor = StreamingCliExecutor::new(30, DEFAULT_FALLBACK_WORKING_DIR);
         // Just verify it can be created
         assert!(std::mem::size_of_val(&executor) > 0);
     }
@@ -359,7 +491,9 @@ mod tests {
                 env_vars: HashMap::new(),
                 working_dir: None,
                 options: HashMap::new(),
+                max_concurrency: 1,
             },
+            last_error: None,
         };
 
         // Check detection logic
@@ -383,7 +517,9 @@ mod tests {
                 env_vars: HashMap::new(),
                 working_dir: None,
                 options: HashMap::new(),
+                max_concurrency: 1,
             },
+            last_error: None,
         };
 
         let is_gemini_json_no = matches!(agent_no_json.agent_type, AgentType::Gemini)
@@ -421,7 +557,9 @@ mod tests {
                 },
                 working_dir: None,
                 options: HashMap::new(),
+                max_concurrency: 1,
             },
+            last_error: None,
         };
 
         // Agent with custom prompt should have it in env_vars
@@ -446,7 +584,9 @@ mod tests {
                 env_vars: HashMap::new(), // No GEMINI_SYSTEM_MD in agent config
                 working_dir: None,
                 options: HashMap::new(),
+                max_concurrency: 1,
             },
+            last_error: None,
         };
 
         // Agent without custom prompt should not have GEMINI_SYSTEM_MD in env_vars
@@ -8,6 +8,7 @@ pub mod chat;
 pub mod config;
 pub mod error;
 pub mod executor;
+pub mod middleware;
 pub mod orchestrator;
 pub mod services;
 /// Application state management
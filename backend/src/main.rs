@@ -8,28 +8,25 @@ mod chat;
 mod config;
 mod error;
 mod executor;
+mod middleware;
 mod orchestrator;
 mod services;
 mod state;
 mod websocket;
 
 use axum::{
-    extract::Request,
-    middleware::Next,
-    response::Response,
     routing::{get, post},
     Json, Router,
 };
 use config::Config;
+use orchestrator::execution_limiter::ExecutionLimiter;
 use serde::Serialize;
 use state::AppState;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use std::time::Instant;
 use tokio::sync::RwLock;
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
-use tracing::{info, info_span, Instrument};
-use uuid::Uuid;
+use tracing::info;
 
 #[derive(Serialize)]
 struct HelloResponse {
@@ -44,35 +41,6 @@ struct HealthResponse {
     message: String,
 }
 
-/// Request ID middleware - adds unique ID to each request for tracing
-async fn request_id_middleware(request: Request, next: Next) -> Response {
-    let request_id = Uuid::new_v4().to_string();
-    let method = request.method().clone();
-    let uri = request.uri().clone();
-    let start = Instant::now();
-
-    let span = info_span!(
-        "request",
-        request_id = %request_id,
-        method = %method,
-        uri = %uri,
-    );
-
-    let response = next.run(request).instrument(span).await;
-
-    let duration = start.elapsed();
-    info!(
-        request_id = %request_id,
-        method = %method,
-        uri = %uri,
-        status = %response.status().as_u16(),
-        duration_ms = duration.as_millis(),
-        "Request completed"
-    );
-
-    response
-}
-
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Initialize tracing
@@ -83,11 +51,16 @@ async fn main() -> anyhow::Result<()> {
     // Load configuration
     let config = Config::from_env();
     info!("Configuration loaded: {:?}", config);
+    config
+        .validate()
+        .map_err(|e| anyhow::anyhow!("Invalid configuration: {}", e))?;
 
     // Initialize chat database
     let chat_db = chat::ChatDb::new(&config.persistence.db_path)
         .await
-        .map_err(|e| anyhow::anyhow!("Failed to initialize chat database: {}", e))?;
+        .map_err(|e| anyhow::anyhow!("Failed to initialize chat database: {}", e))?
+        .with_summarize_policy(config.persistence.summarize_policy())
+        .with_max_conversations(config.persistence.max_conversations);
     let chat_db = Arc::new(chat_db);
     info!(
         "Chat database initialized at: {}",
@@ -95,14 +68,61 @@ async fn main() -> anyhow::Result<()> {
     );
 
     // Initialize application state
-    let app_state = Arc::new(RwLock::new(AppState::new()));
+    let mut initial_state = AppState::new();
+    initial_state.set_default_agent_type(config.server.default_agent_type.clone());
+    initial_state.ws_auth_token = config.server.ws_auth_token.clone();
+    initial_state.execution_limiter = ExecutionLimiter::new(
+        config.server.max_concurrent_executions,
+        config.server.execution_queue_policy,
+    );
+    initial_state.cli_output_limit = config.execution.output_limit;
+    initial_state.cli_fallback_working_dir = config.execution.fallback_working_dir.clone();
+    initial_state.workspace_root = config.execution.workspace_root.clone();
+    initial_state.batch_query_concurrency = config.execution.batch_query_concurrency;
+    initial_state.sse_retry_ms = config.server.sse_retry_ms;
+    initial_state.max_sse_duration_secs = config.server.max_sse_duration_secs;
+    initial_state.allowed_working_dirs = config.server.allowed_working_dirs.clone();
+    // Restore the orchestrator config saved by POST /api/config
+    let config_path = std::path::Path::new(&config.persistence.data_dir)
+        .join(orchestrator::config::CONFIG_FILE_NAME);
+    initial_state.orchestrator_config =
+        orchestrator::config::OrchestratorConfig::load(&config_path);
+    initial_state.config_path = Some(config_path);
+    if initial_state.ws_auth_token.is_none() {
+        tracing::warn!("WS_AUTH_TOKEN not set: /ws accepts unauthenticated connections");
+    }
+    let api_auth = middleware::ApiAuth::new(
+        config.server.api_token_sha256.clone(),
+        config.server.api_auth_protect_reads,
+    );
+    if !api_auth.is_enabled() {
+        tracing::warn!("API_TOKEN_SHA256 not set: API writes are unauthenticated");
+    }
+    let app_state = Arc::new(RwLock::new(initial_state));
 
     // Initialize bridge manager (will manage Node.js sidecar processes)
-    let bridge_manager = Arc::new(chat::BridgeManager::new());
-    info!("Bridge manager initialized");
+    let bridge_manager = Arc::new(
+        chat::BridgeManager::new()
+            .with_max_sessions(config.server.max_bridge_sessions)
+            .with_spawn_retry(
+                config.server.bridge_spawn_attempts,
+                std::time::Duration::from_millis(config.server.bridge_spawn_retry_delay_ms),
+            ),
+    );
+    info!(
+        "Bridge manager initialized (max {} sessions)",
+        config.server.max_bridge_sessions
+    );
+    if config.server.bridge_idle_timeout_secs > 0 {
+        chat::BridgeManager::spawn_idle_sweeper(
+            &bridge_manager,
+            std::time::Duration::from_secs(config.server.bridge_idle_timeout_secs),
+        );
+    }
 
-    // Try to load agents from default path
+    // Try to load agents (and working directory profiles) from default path
     let default_path = state::persistence::AgentRegistry::default_path();
+    app_state.write().await.registry_path = Some(default_path.clone());
     if default_path.exists() {
         match app_state.write().await.load_agents(&default_path) {
             Ok(count) => info!("Loaded {} agents from {}", count, default_path.display()),
@@ -115,8 +135,18 @@ async fn main() -> anyhow::Result<()> {
         // Health check and hello world
         .route("/", get(hello_world))
         .route("/api/health", get(health_check))
+        .route("/api/version", get(api::version::get_version))
+        .route("/api/metrics", get(api::metrics::get_metrics))
+        .route(
+            "/api/capabilities",
+            get(api::capabilities::get_capabilities),
+        )
         // Simple chat API (uses Gemini CLI directly)
         .route("/api/simple-chat", post(api::simple_chat::simple_chat))
+        .route(
+            "/api/simple-chat/stream",
+            post(api::simple_chat::simple_chat_stream),
+        )
         .route(
             "/api/simple-chat/multipart",
             post(api::simple_chat_multipart::simple_chat_multipart),
@@ -126,16 +156,31 @@ async fn main() -> anyhow::Result<()> {
             "/api/agents",
             get(api::agents::list_agents).post(api::agents::create_agent),
         )
+        .route("/api/agents/export", get(api::agents::export_agents))
+        .route("/api/agents/import", post(api::agents::import_agents))
         .route(
             "/api/agents/:id",
             get(api::agents::get_agent)
                 .put(api::agents::update_agent)
+                .patch(api::agents::update_agent)
                 .delete(api::agents::delete_agent),
         )
         .route("/api/agents/:id/start", post(api::agents::start_agent))
         .route("/api/agents/:id/stop", post(api::agents::stop_agent))
+        .route("/api/agents/:id/reset", post(api::agents::reset_agent))
+        .route("/api/agents/:id/logs", get(api::agent_logs::get_agent_logs))
+        .route(
+            "/api/agents/:id/status-history",
+            get(api::agents::get_agent_status_history),
+        )
+        .route("/api/agents/:id/env", post(api::agents::set_agent_env_var))
+        .route(
+            "/api/agents/:id/env/:key",
+            axum::routing::delete(api::agents::delete_agent_env_var),
+        )
         .route("/api/agents/:id/query", post(api::queries::query_agent))
         .route("/api/query/stream", post(api::queries::query_stream))
+        .route("/api/query/batch", post(api::queries::query_batch))
         // Chat API
         .route(
             "/api/chat/conversations",
@@ -149,43 +194,109 @@ async fn main() -> anyhow::Result<()> {
             "/api/chat/conversations/:id/title",
             axum::routing::put(api::chat::update_conversation_title),
         )
+        .route(
+            "/api/chat/conversations/:id/tags",
+            axum::routing::put(api::chat::update_conversation_tags),
+        )
+        .route(
+            "/api/chat/conversations/:id/pinned",
+            axum::routing::put(api::chat::update_conversation_pinned),
+        )
+        .route(
+            "/api/chat/conversations/:id/system-prompt",
+            axum::routing::put(api::chat::update_conversation_system_prompt),
+        )
+        .route(
+            "/api/chat/conversations/:id/stop",
+            post(api::chat::stop_conversation_reply),
+        )
         // File system API
         .route("/api/files", get(api::list_files))
         .route(
             "/api/files/working-directory",
             get(api::get_working_directory).post(api::set_working_directory),
         )
+        .route(
+            "/api/files/profiles",
+            get(api::list_profiles).post(api::create_profile),
+        )
+        .route(
+            "/api/files/profiles/:name/activate",
+            post(api::activate_profile),
+        )
         // Orchestration API
         .route(
             "/api/orchestrate/poem",
             post(api::orchestrator::orchestrate_poem),
         )
         .route("/api/orchestrate", post(api::orchestrator::orchestrate))
+        .route(
+            "/api/orchestrate/:execution_id/replay",
+            post(api::orchestrator::replay_execution),
+        )
+        .route(
+            "/api/orchestrate/:execution_id/run-from/:step_id",
+            post(api::orchestrator::run_from_step),
+        )
+        .route(
+            "/api/orchestrate/:execution_id/results",
+            get(api::orchestrator::get_execution_results),
+        )
+        .route(
+            "/api/orchestrate/:execution_id/cancel",
+            post(api::orchestrator::cancel_execution),
+        )
         // Phase 6.1: Pre-flight check - Plan + Optimizer
         .route("/api/plan", post(api::orchestrator::plan_with_analysis))
+        .route("/api/plan/validate", post(api::orchestrator::validate_plan))
+        .route(
+            "/api/orchestrate/estimate",
+            post(api::orchestrator::estimate_plan),
+        )
+        // Alias of /api/orchestrate/estimate
+        .route("/api/plan/estimate", post(api::orchestrator::estimate_plan))
+        .route("/api/plan/explain", post(api::orchestrator::explain_plan))
         // Phase 6.2: Graph visualization
         .route(
             "/api/orchestrate/graph",
             get(api::orchestrator_graph::get_graph_structure),
         )
+        .route(
+            "/api/orchestrate/graph.dot",
+            get(api::orchestrator_graph::get_graph_dot)
+                .post(api::orchestrator_graph::plan_graph_dot),
+        )
         // Phase 6.4: Settings Panel
         .route(
             "/api/config",
             get(api::orchestrator::get_config).post(api::orchestrator::update_config),
         )
+        .route("/api/config/reset", post(api::orchestrator::reset_config))
+        .route(
+            "/api/config/schema",
+            get(api::orchestrator::get_config_schema),
+        )
+        // Orchestration audit log
+        .route("/api/audit", get(api::audit::list_audit_entries))
         // WebSocket for real-time updates
         .route("/ws", get(websocket::websocket_handler))
-        // Middleware (order matters - request_id should be first)
-        .layer(axum::middleware::from_fn(request_id_middleware))
+        // Middleware (order matters - layers added later wrap earlier ones, so
+        // request_id sees auth rejections too)
+        .layer(axum::middleware::from_fn_with_state(
+            api_auth,
+            middleware::api_auth_middleware,
+        ))
+        .layer(axum::middleware::from_fn(middleware::request_id_middleware))
         .layer(
             TraceLayer::new_for_http().make_span_with(|request: &axum::http::Request<_>| {
                 tracing::info_span!(
                     "http_request",
                     method = %request.method(),
-                    uri = %request.uri(),
+                    uri = %middleware::loggable_uri(request.uri()),
                 )
             }),
         )
+        .layer(middleware::compression_layer())
         .layer(CorsLayer::permissive()) // Allow CORS for development
         .with_state((app_state, chat_db, bridge_manager.clone()));
 
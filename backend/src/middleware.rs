@@ -0,0 +1,448 @@
+//! HTTP middleware
+//!
+//! Request ID propagation: an incoming `X-Request-Id` header is reused when it
+//! is sane, otherwise a fresh UUID is generated. The ID is attached to the
+//! request span and echoed back in the response `X-Request-Id` header.
+//!
+//! API token auth: when `API_TOKEN_SHA256` is configured, requests other than
+//! GET (and optionally GET too) must carry `Authorization: Bearer <token>`.
+//! Only the token's SHA-256 is kept in memory, and digests are compared in
+//! constant time.
+//!
+//! Logged URIs go through `loggable_uri`, which drops the `token` query
+//! parameter the WebSocket endpoint accepts, so the secret never reaches logs.
+//!
+//! Response compression: bodies are gzipped when the client's
+//! `Accept-Encoding` allows it, except server-sent event streams.
+
+use crate::error::AppError;
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+use std::time::Instant;
+use tower_http::compression::{
+    predicate::{And, NotForContentType, Predicate},
+    CompressionLayer, DefaultPredicate,
+};
+use tracing::{info, info_span, Instrument};
+use uuid::Uuid;
+
+/// Header carrying the request ID
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Maximum accepted length of an incoming request ID
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Check that an upstream request ID is safe to reuse in logs and headers
+///
+/// Accepts 1-128 characters of ASCII alphanumerics and `-`, `_`, `.`, `:`.
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
+
+/// Reuse a valid incoming `X-Request-Id`, or generate a new UUID
+pub fn resolve_request_id(headers: &HeaderMap) -> String {
+    match headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(id) if is_valid_request_id(id) => id.to_string(),
+        Some(id) => {
+            tracing::debug!(
+                rejected_len = id.len(),
+                "Ignoring invalid incoming request ID"
+            );
+            Uuid::new_v4().to_string()
+        }
+        None => Uuid::new_v4().to_string(),
+    }
+}
+
+/// Query parameters that carry credentials and must not be logged
+const SECRET_QUERY_PARAMS: &[&str] = &["token"];
+
+/// Render a URI for logging, without credential query parameters
+///
+/// `/ws?token=…` carries the shared secret, so its value is replaced by
+/// `[REDACTED]`; every other parameter is kept as-is.
+pub fn loggable_uri(uri: &Uri) -> String {
+    let Some(query) = uri.query() else {
+        return uri.path().to_string();
+    };
+    let query = query
+        .split('&')
+        .map(|pair| {
+            let key = pair.split_once('=').map_or(pair, |(key, _)| key);
+            if SECRET_QUERY_PARAMS.contains(&key) {
+                format!("{}=[REDACTED]", key)
+            } else {
+                pair.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    format!("{}?{}", uri.path(), query)
+}
+
+/// Request ID middleware - tags each request with an ID for tracing
+pub async fn request_id_middleware(request: Request, next: Next) -> Response {
+    let request_id = resolve_request_id(request.headers());
+    let method = request.method().clone();
+    let uri = loggable_uri(request.uri());
+    let start = Instant::now();
+
+    let span = info_span!(
+        "request",
+        request_id = %request_id,
+        method = %method,
+        uri = %uri,
+    );
+
+    let mut response = next.run(request).instrument(span).await;
+
+    // Validated above (or a UUID), so this always converts
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    let duration = start.elapsed();
+    info!(
+        request_id = %request_id,
+        method = %method,
+        uri = %uri,
+        status = %response.status().as_u16(),
+        duration_ms = duration.as_millis(),
+        "Request completed"
+    );
+
+    response
+}
+
+/// Response compression honouring `Accept-Encoding`
+///
+/// Event streams (`text/event-stream`) are passed through untouched: a gzip
+/// encoder holds output back until it has a full block, which would delay
+/// progress events until the run ends. Tiny bodies, gRPC and images are left
+/// alone as well.
+pub fn compression_layer() -> CompressionLayer<And<DefaultPredicate, NotForContentType>> {
+    CompressionLayer::new()
+        .gzip(true)
+        .compress_when(DefaultPredicate::new().and(NotForContentType::SSE))
+}
+
+/// Paths that never require the API token
+///
+/// Health checks must work for probes; `/ws` checks its own token on upgrade.
+const OPEN_PATHS: &[&str] = &["/", "/api/health", "/ws"];
+
+/// Compare two byte strings without short-circuiting on the first mismatch
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Hex-encoded SHA-256 of a token, the form `API_TOKEN_SHA256` expects
+pub fn hash_api_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// API token settings shared with `api_auth_middleware`
+#[derive(Debug, Clone, Default)]
+pub struct ApiAuth {
+    /// Lowercase hex SHA-256 of the expected token (None = auth disabled)
+    token_sha256: Option<String>,
+    /// Require the token on GET requests as well
+    protect_reads: bool,
+}
+
+impl ApiAuth {
+    /// Create auth settings from the configured token hash
+    pub fn new(token_sha256: Option<String>, protect_reads: bool) -> Self {
+        Self {
+            token_sha256: token_sha256.map(|hash| hash.to_lowercase()),
+            protect_reads,
+        }
+    }
+
+    /// Whether any request is checked at all
+    pub fn is_enabled(&self) -> bool {
+        self.token_sha256.is_some()
+    }
+
+    /// Whether a request with this method and path needs the token
+    fn requires_token(&self, method: &Method, path: &str) -> bool {
+        if OPEN_PATHS.contains(&path) || method == Method::OPTIONS {
+            return false;
+        }
+        self.protect_reads || !matches!(*method, Method::GET | Method::HEAD)
+    }
+
+    /// Check the bearer token in `headers` against the configured hash
+    fn is_authorized(&self, headers: &HeaderMap) -> bool {
+        let Some(expected) = self.token_sha256.as_deref() else {
+            return true;
+        };
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|token| {
+                constant_time_eq(hash_api_token(token).as_bytes(), expected.as_bytes())
+            })
+    }
+}
+
+/// API token middleware - rejects unauthenticated requests with a JSON 401
+pub async fn api_auth_middleware(
+    State(auth): State<ApiAuth>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if auth.requires_token(request.method(), request.uri().path())
+        && !auth.is_authorized(request.headers())
+    {
+        tracing::warn!(
+            method = %request.method(),
+            uri = %loggable_uri(request.uri()),
+            "Rejecting request: invalid or missing API token"
+        );
+        let mut response =
+            AppError::Unauthorized("Missing or invalid API token".to_string()).into_response();
+        response
+            .headers_mut()
+            .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        return response;
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+
+    /// Serve a minimal router with the middleware on an ephemeral port
+    async fn spawn_test_server() -> String {
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(request_id_middleware));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}/", addr)
+    }
+
+    #[tokio::test]
+    async fn test_request_id_propagates_existing() {
+        let url = spawn_test_server().await;
+        let response = reqwest::Client::new()
+            .get(&url)
+            .header(REQUEST_ID_HEADER, "upstream-trace-123")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.headers()[REQUEST_ID_HEADER].to_str().unwrap(),
+            "upstream-trace-123"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_id_generated_when_absent() {
+        let url = spawn_test_server().await;
+        let response = reqwest::get(&url).await.unwrap();
+
+        let id = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+        assert!(Uuid::parse_str(id).is_ok(), "Expected a UUID, got {}", id);
+    }
+
+    /// Serve GET/POST routes behind the API token middleware, returning the base URL
+    async fn spawn_auth_server(auth: ApiAuth) -> String {
+        let app = Router::new()
+            .route(
+                "/api/agents",
+                get(|| async { "list" }).post(|| async { "created" }),
+            )
+            .route("/api/health", get(|| async { "healthy" }))
+            .layer(axum::middleware::from_fn_with_state(
+                auth,
+                api_auth_middleware,
+            ));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_api_auth_accepts_write_with_valid_token() {
+        let base = spawn_auth_server(ApiAuth::new(Some(hash_api_token("s3cret")), false)).await;
+        let response = reqwest::Client::new()
+            .post(format!("{}/api/agents", base))
+            .bearer_auth("s3cret")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(response.text().await.unwrap(), "created");
+    }
+
+    #[tokio::test]
+    async fn test_api_auth_rejects_write_without_valid_token() {
+        let base = spawn_auth_server(ApiAuth::new(Some(hash_api_token("s3cret")), false)).await;
+        let client = reqwest::Client::new();
+
+        for request in [
+            client.post(format!("{}/api/agents", base)),
+            client
+                .post(format!("{}/api/agents", base))
+                .bearer_auth("wrong"),
+        ] {
+            let response = request.send().await.unwrap();
+            assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+            let body: serde_json::Value = response.json().await.unwrap();
+            assert_eq!(body["status"], 401);
+            assert!(body["error"].as_str().unwrap().contains("API token"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_api_auth_leaves_reads_open_unless_protected() {
+        let base = spawn_auth_server(ApiAuth::new(Some(hash_api_token("s3cret")), false)).await;
+        let response = reqwest::get(format!("{}/api/agents", base)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+        // Protecting reads still leaves the health check open
+        let base = spawn_auth_server(ApiAuth::new(Some(hash_api_token("s3cret")), true)).await;
+        let response = reqwest::get(format!("{}/api/agents", base)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+        let response = reqwest::get(format!("{}/api/health", base)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+    }
+
+    #[test]
+    fn test_loggable_uri_redacts_token() {
+        let uri: Uri = "/ws?token=s3cret-value".parse().unwrap();
+        let logged = loggable_uri(&uri);
+        assert!(!logged.contains("s3cret-value"), "{}", logged);
+        assert_eq!(logged, "/ws?token=[REDACTED]");
+
+        let uri: Uri = "/ws?since=5&token=s3cret-value&x=1".parse().unwrap();
+        let logged = loggable_uri(&uri);
+        assert!(!logged.contains("s3cret-value"), "{}", logged);
+        assert_eq!(logged, "/ws?since=5&token=[REDACTED]&x=1");
+
+        let uri: Uri = "/api/agents?limit=10".parse().unwrap();
+        assert_eq!(loggable_uri(&uri), "/api/agents?limit=10");
+        let uri: Uri = "/api/health".parse().unwrap();
+        assert_eq!(loggable_uri(&uri), "/api/health");
+    }
+
+    #[test]
+    fn test_resolve_request_id_rejects_invalid() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            REQUEST_ID_HEADER,
+            HeaderValue::from_static("bad id\twith spaces"),
+        );
+        let id = resolve_request_id(&headers);
+        assert!(Uuid::parse_str(&id).is_ok());
+
+        let too_long = "a".repeat(MAX_REQUEST_ID_LEN + 1);
+        headers.insert(REQUEST_ID_HEADER, HeaderValue::from_str(&too_long).unwrap());
+        assert_ne!(resolve_request_id(&headers), too_long);
+    }
+
+    /// Serve a large JSON route and a never-ending SSE route, compressed
+    async fn spawn_compression_server() -> String {
+        let app = Router::new()
+            .route(
+                "/large",
+                get(|| async {
+                    let items: Vec<String> =
+                        (0..2000).map(|i| format!("analysis line {}", i)).collect();
+                    axum::Json(items)
+                }),
+            )
+            .route(
+                "/events",
+                get(|| async {
+                    let stream = async_stream::stream! {
+                        yield Ok::<_, std::convert::Infallible>(
+                            axum::response::sse::Event::default().data("first"),
+                        );
+                        std::future::pending::<()>().await;
+                    };
+                    axum::response::sse::Sse::new(stream)
+                }),
+            )
+            .layer(compression_layer());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_large_json_is_gzipped_when_accepted() {
+        let base = spawn_compression_server().await;
+        let client = reqwest::Client::new();
+
+        let plain = client.get(format!("{}/large", base)).send().await.unwrap();
+        assert!(plain.headers().get(header::CONTENT_ENCODING).is_none());
+        let plain_len = plain.bytes().await.unwrap().len();
+
+        let gzipped = client
+            .get(format!("{}/large", base))
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(gzipped.headers()[header::CONTENT_ENCODING], "gzip");
+        let body = gzipped.bytes().await.unwrap();
+        // gzip magic number, and much smaller than the plain body
+        assert_eq!(&body[..2], &[0x1f, 0x8b]);
+        assert!(
+            body.len() * 4 < plain_len,
+            "{} vs {}",
+            body.len(),
+            plain_len
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sse_is_not_compressed_or_buffered() {
+        let base = spawn_compression_server().await;
+        let mut response = reqwest::Client::new()
+            .get(format!("{}/events", base))
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .send()
+            .await
+            .unwrap();
+        assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+
+        // The first event arrives while the stream is still open
+        let chunk = tokio::time::timeout(std::time::Duration::from_secs(2), response.chunk())
+            .await
+            .expect("first event should not be held back")
+            .unwrap()
+            .unwrap();
+        assert!(String::from_utf8_lossy(&chunk).contains("data: first"));
+    }
+}
@@ -8,35 +8,71 @@ use crate::orchestrator::config::OrchestratorConfig;
 use crate::orchestrator::gemini_types::{
     GeminiApiRequest, GeminiApiResponse, GenerationConfig, RequestContent, RequestPart,
 };
+use crate::orchestrator::retry::RetryPolicy;
 use anyhow::anyhow;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-const GEMINI_API_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+pub(crate) const GEMINI_API_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+/// Gemini API requests awaiting a response, across the whole process
+static GEMINI_API_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of Gemini API requests currently awaiting a response
+///
+/// Counts HTTP attempts, so a call waiting between retries is not included.
+pub fn gemini_api_in_flight() -> usize {
+    GEMINI_API_IN_FLIGHT.load(Ordering::SeqCst)
+}
+
+/// Counts one request in `GEMINI_API_IN_FLIGHT` for as long as it lives
+///
+/// Dropping it (on success, error, cancellation or panic) releases the count.
+struct InFlightRequest;
+
+impl InFlightRequest {
+    fn start() -> Self {
+        GEMINI_API_IN_FLIGHT.fetch_add(1, Ordering::SeqCst);
+        InFlightRequest
+    }
+}
+
+impl Drop for InFlightRequest {
+    fn drop(&mut self) {
+        GEMINI_API_IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+    }
+}
 
 /// Call Gemini API directly with a prompt
 ///
 /// This function makes a direct HTTP request to the Gemini API,
 /// bypassing the CLI wrapper. This is used for "Planner" calls
-/// that need structured JSON output.
+/// that need structured JSON output. Rate limits, 5xx responses and
+/// connection failures are retried with `RetryPolicy::gemini_api`.
 ///
 /// # Arguments
 /// * `api_key` - Gemini API key
 /// * `prompt` - The prompt to send
 /// * `model` - Model name (default: "gemini-2.5-flash")
 /// * `force_json` - If true, request JSON response format
+/// * `max_prompt_length` - Longest prompt accepted, from the active config
 ///
 /// # Returns
 /// * `Ok(String)` - The text content from the API response
 /// * `Err(AppError)` - If API call failed
 ///
 /// # Errors
+/// * Returns `AppError::PromptBlocked` if Gemini blocks the prompt (e.g. for safety).
+/// * Returns `AppError::InvalidPlan` if the prompt exceeds `max_prompt_length`.
 /// * Returns `AppError::Internal` if API key is missing, HTTP request fails,
-///   response parsing fails, or no valid content is found in the response.
+///   response parsing fails, or no valid content is found in the response. An empty reply is always an error
+///   here; `internal_run_gemini_api` can accept one for worker prompts.
 pub async fn call_gemini_api(
     client: &reqwest::Client,
     api_key: &str,
     prompt: &str,
     model: Option<&str>,
     force_json: bool,
+    max_prompt_length: usize,
 ) -> Result<String, AppError> {
     call_gemini_api_with_base_url(
         client,
@@ -44,25 +80,69 @@ pub async fn call_gemini_api(
         prompt,
         model,
         force_json,
+        false,
+        max_prompt_length,
         GEMINI_API_BASE_URL,
+        &RetryPolicy::gemini_api(),
     )
     .await
 }
 
-/// Internal function that allows custom base URL (for testing)
+/// Internal function that allows custom base URL and retry policy (for testing)
+///
+/// With `allow_empty`, an empty reply is returned as `Ok("")` instead of an
+/// error. Callers that need JSON must leave it off.
 #[allow(dead_code)] // Used in tests
-async fn call_gemini_api_with_base_url(
+pub(crate) async fn call_gemini_api_with_base_url(
+    client: &reqwest::Client,
+    api_key: &str,
+    prompt: &str,
+    model: Option<&str>,
+    force_json: bool,
+    allow_empty: bool,
+    max_prompt_length: usize,
+    base_url: &str,
+    retry: &RetryPolicy,
+) -> Result<String, AppError> {
+    retry
+        .run("gemini_api", || {
+            send_gemini_request(
+                client,
+                api_key,
+                prompt,
+                model,
+                force_json,
+                allow_empty,
+                max_prompt_length,
+                base_url,
+            )
+        })
+        .await
+}
+
+/// Make a single Gemini API request
+async fn send_gemini_request(
     client: &reqwest::Client,
     api_key: &str,
     prompt: &str,
     model: Option<&str>,
     force_json: bool,
+    allow_empty: bool,
+    max_prompt_length: usize,
     base_url: &str,
 ) -> Result<String, AppError> {
     if api_key.is_empty() {
         return Err(AppError::Internal(anyhow!("API key is empty")));
     }
 
+    if prompt.len() > max_prompt_length {
+        return Err(AppError::InvalidPlan(format!(
+            "Prompt length {} exceeds maximum of {} characters",
+            prompt.len(),
+            max_prompt_length
+        )));
+    }
+
     let config = OrchestratorConfig::default();
     let model_name = model.unwrap_or(&config.gemini_model);
     let url = format!(
@@ -96,6 +176,7 @@ async fn call_gemini_api_with_base_url(
     );
 
     // Make POST request using shared client (connection pooling)
+    let _in_flight = InFlightRequest::start();
     let response = client
         .post(&url)
         .json(&request_body)
@@ -155,10 +236,9 @@ async fn call_gemini_api_with_base_url(
     // Check for blocked prompt
     if let Some(feedback) = &parsed.prompt_feedback {
         if let Some(reason) = &feedback.block_reason {
-            return Err(AppError::Internal(anyhow!(
-                "Gemini API blocked the prompt: {}",
-                reason
-            )));
+            return Err(AppError::PromptBlocked {
+                reason: reason.clone(),
+            });
         }
     }
 
@@ -173,7 +253,7 @@ async fn call_gemini_api_with_base_url(
     })?;
 
     let text = &part.text;
-    if text.is_empty() {
+    if text.is_empty() && !allow_empty {
         return Err(AppError::Internal(anyhow!(
             "Gemini API response text is empty"
         )));
@@ -203,11 +283,21 @@ mod tests {
     #[tokio::test]
     async fn test_call_gemini_api_empty_api_key() {
         let client = build_test_client();
-        let result = call_gemini_api(&client, "", "test prompt", None, false).await;
+        let result = call_gemini_api(&client, "", "test prompt", None, false, 100).await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("API key is empty"));
     }
 
+    #[tokio::test]
+    async fn test_call_gemini_api_prompt_too_long() {
+        let client = build_test_client();
+        let prompt = "a".repeat(11);
+        let result = call_gemini_api(&client, "test-key", &prompt, None, false, 10).await;
+        let error = result.unwrap_err();
+        assert!(matches!(error, AppError::InvalidPlan(_)), "{:?}", error);
+        assert!(error.to_string().contains("exceeds maximum of 10"));
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_call_gemini_api_success() {
@@ -243,7 +333,10 @@ mod tests {
             "test prompt",
             None,
             false,
+            false, // allow_empty
+            OrchestratorConfig::default().max_prompt_length,
             base_url,
+            &RetryPolicy::none(),
         )
         .await;
 
@@ -286,8 +379,11 @@ mod tests {
             "test-key",
             "test prompt",
             None,
-            true, // force_json
+            true,  // force_json
+            false, // allow_empty
+            OrchestratorConfig::default().max_prompt_length,
             base_url,
+            &RetryPolicy::none(),
         )
         .await;
 
@@ -322,7 +418,10 @@ mod tests {
             "test prompt",
             None,
             false,
+            false, // allow_empty
+            OrchestratorConfig::default().max_prompt_length,
             base_url,
+            &RetryPolicy::none(),
         )
         .await;
 
@@ -361,18 +460,36 @@ mod tests {
             "test prompt",
             None,
             false,
+            false, // allow_empty
+            OrchestratorConfig::default().max_prompt_length,
             base_url,
+            &RetryPolicy::none(),
         )
         .await;
 
         mock.assert_async().await;
         assert!(result.is_err());
-        let error_msg = result.unwrap_err().to_string();
+        let error = result.unwrap_err();
+        let error_msg = error.to_string();
         assert!(
             error_msg.contains("blocked the prompt"),
             "Error message should contain 'blocked the prompt', got: {}",
             error_msg
         );
+        assert_eq!(error.block_reason(), Some("SAFETY"));
+
+        // A content block is the client's problem (422), not a server bug (500)
+        let response = axum::response::IntoResponse::into_response(error);
+        assert_eq!(
+            response.status(),
+            axum::http::StatusCode::UNPROCESSABLE_ENTITY
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["reason"], "SAFETY");
+        assert_eq!(body["status"], 422);
     }
 
     #[tokio::test]
@@ -398,7 +515,10 @@ mod tests {
             "test prompt",
             None,
             false,
+            false, // allow_empty
+            OrchestratorConfig::default().max_prompt_length,
             base_url,
+            &RetryPolicy::none(),
         )
         .await;
 
@@ -406,6 +526,8 @@ mod tests {
         assert!(result.is_err());
         let error_msg = result.unwrap_err().to_string();
         assert!(error_msg.contains("rate limit") || error_msg.contains("429"));
+        // A failed request no longer counts as in flight
+        assert_eq!(gemini_api_in_flight(), 0);
     }
 
     #[tokio::test]
@@ -431,7 +553,10 @@ mod tests {
             "test prompt",
             None,
             false,
+            false, // allow_empty
+            OrchestratorConfig::default().max_prompt_length,
             base_url,
+            &RetryPolicy::none(),
         )
         .await;
 
@@ -444,12 +569,87 @@ mod tests {
     }
 
     #[tokio::test]
+    #[serial]
+    async fn test_in_flight_gauge_rises_during_slow_call() {
+        use std::io::Write;
+        use std::sync::{mpsc, Mutex};
+        use std::time::Duration;
+
+        // The response body is held back until the test releases it
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let release_rx = Mutex::new(release_rx);
+        let mut server = Server::new_async().await;
+        let _mock = server
+            .mock("POST", "/models/gemini-2.5-flash:generateContent")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_chunked_body(move |writer| {
+                let _ = release_rx.lock().unwrap().recv();
+                writer.write_all(
+                    br#"{"candidates": [{"content": {"parts": [{"text": "slow"}], "role": "model"}}]}"#,
+                )
+            })
+            .create_async()
+            .await;
+
+        assert_eq!(gemini_api_in_flight(), 0);
+        let base_url = server.url();
+        let call = tokio::spawn(async move {
+            call_gemini_api_with_base_url(
+                &build_test_client(),
+                "test-key",
+                "test prompt",
+                None,
+                false,
+                false, // allow_empty
+                OrchestratorConfig::default().max_prompt_length,
+                &base_url,
+                &RetryPolicy::none(),
+            )
+            .await
+        });
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while gemini_api_in_flight() == 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("gauge should rise while the call waits");
+        assert_eq!(gemini_api_in_flight(), 1);
+
+        release_tx.send(()).unwrap();
+        assert_eq!(call.await.unwrap().unwrap(), "slow");
+        assert_eq!(gemini_api_in_flight(), 0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_in_flight_guard_released_on_error_and_panic() {
+        let panicked = std::panic::catch_unwind(|| {
+            let _in_flight = InFlightRequest::start();
+            assert_eq!(gemini_api_in_flight(), 1);
+            panic!("request handler blew up");
+        });
+        assert!(panicked.is_err());
+        assert_eq!(gemini_api_in_flight(), 0);
+    }
+
+    #[tokio::test]
+    #[serial]
     async fn test_call_gemini_api_invalid_api_key_real() {
         // This will fail with a real HTTP request, but we're testing error handling
         // In a real scenario, this would hit the real API with an invalid key
         let client = build_test_client();
-        let result =
-            call_gemini_api(&client, "invalid-key-12345", "test prompt", None, false).await;
+        let result = call_gemini_api(
+            &client,
+            "invalid-key-12345",
+            "test prompt",
+            None,
+            false,
+            100,
+        )
+        .await;
         // Should return an error (either HTTP error or parsing error)
         assert!(result.is_err());
     }
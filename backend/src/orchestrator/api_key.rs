@@ -0,0 +1,163 @@
+//! Gemini API key resolution
+//!
+//! The key comes from `GEMINI_API_KEY` unless `gemini_api_key_file` is set,
+//! in which case it is read from that file (e.g. a mounted secret). File
+//! contents are cached and re-read only when the file's modification time or
+//! size changes, so rotating the secret takes effect without a restart.
+
+use crate::error::AppError;
+use crate::orchestrator::config::OrchestratorConfig;
+use anyhow::anyhow;
+use once_cell::sync::Lazy;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Environment variable holding the key when no key file is configured
+pub const GEMINI_API_KEY_ENV: &str = "GEMINI_API_KEY";
+
+/// Environment variable providing the default `gemini_api_key_file`
+pub const GEMINI_API_KEY_FILE_ENV: &str = "GEMINI_API_KEY_FILE";
+
+/// Process-wide cache of the key file contents
+static KEY_FILE_CACHE: Lazy<ApiKeyFileCache> = Lazy::new(ApiKeyFileCache::default);
+
+/// Resolve the Gemini API key for a call
+///
+/// # Returns
+/// * `Ok(String)` - The key from `gemini_api_key_file` if set, else from `GEMINI_API_KEY`
+/// * `Err(AppError::Internal)` - If the file is missing/empty or the env var is unset/empty
+pub fn resolve_gemini_api_key(config: &OrchestratorConfig) -> Result<String, AppError> {
+    match config.gemini_api_key_file.as_deref() {
+        Some(path) => KEY_FILE_CACHE.load(Path::new(path)),
+        None => match std::env::var(GEMINI_API_KEY_ENV) {
+            Ok(key) if !key.is_empty() => Ok(key),
+            _ => Err(AppError::Internal(anyhow!(
+                "GEMINI_API_KEY environment variable is not set or is empty. Please set it to use the Gemini API."
+            ))),
+        },
+    }
+}
+
+/// Key file contents as of a given file version
+#[derive(Debug, Clone)]
+struct CachedKey {
+    /// File the key was read from
+    path: PathBuf,
+    /// Modification time when it was read
+    modified: SystemTime,
+    /// File size when it was read
+    len: u64,
+    /// Trimmed key
+    key: String,
+}
+
+/// Cache for a key read from a file, invalidated when the file changes
+#[derive(Debug, Default)]
+pub struct ApiKeyFileCache {
+    /// Last key read, if any
+    cached: Mutex<Option<CachedKey>>,
+}
+
+impl ApiKeyFileCache {
+    /// Read the key from `path`, reusing the cached value if the file is unchanged
+    ///
+    /// # Returns
+    /// * `Ok(String)` - The file contents with surrounding whitespace trimmed
+    /// * `Err(AppError::Internal)` - If the file can't be read or holds only whitespace
+    pub fn load(&self, path: &Path) -> Result<String, AppError> {
+        let metadata = std::fs::metadata(path).map_err(|e| {
+            AppError::Internal(anyhow!(
+                "Gemini API key file '{}' cannot be read: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        let len = metadata.len();
+
+        let mut cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = cached.as_ref() {
+            if entry.path == path && entry.modified == modified && entry.len == len {
+                return Ok(entry.key.clone());
+            }
+        }
+
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            AppError::Internal(anyhow!(
+                "Gemini API key file '{}' cannot be read: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let key = contents.trim().to_string();
+        if key.is_empty() {
+            return Err(AppError::Internal(anyhow!(
+                "Gemini API key file '{}' is empty",
+                path.display()
+            )));
+        }
+
+        tracing::debug!(path = %path.display(), "Loaded Gemini API key from file");
+        *cached = Some(CachedKey {
+            path: path.to_path_buf(),
+            modified,
+            len,
+            key: key.clone(),
+        });
+        Ok(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_key_file_is_trimmed_and_reloaded_on_change() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("gemini_key");
+        std::fs::write(&path, "  key-one\n").unwrap();
+
+        let cache = ApiKeyFileCache::default();
+        assert_eq!(cache.load(&path).unwrap(), "key-one");
+
+        // Rotate the secret; a newer mtime forces a re-read
+        std::fs::write(&path, "key-two\n").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(cache.load(&path).unwrap(), "key-two");
+    }
+
+    #[test]
+    fn test_key_file_missing_or_empty_fails_clearly() {
+        let dir = tempdir().unwrap();
+        let cache = ApiKeyFileCache::default();
+
+        let missing = dir.path().join("missing");
+        let error = cache.load(&missing).unwrap_err().to_string();
+        assert!(error.contains("cannot be read"), "got: {}", error);
+        assert!(error.contains("missing"), "got: {}", error);
+
+        let empty = dir.path().join("empty");
+        std::fs::write(&empty, " \n").unwrap();
+        let error = cache.load(&empty).unwrap_err().to_string();
+        assert!(error.contains("is empty"), "got: {}", error);
+    }
+
+    #[test]
+    fn test_key_file_takes_precedence_over_env() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("gemini_key");
+        std::fs::write(&path, "from-file").unwrap();
+
+        let config = OrchestratorConfig {
+            gemini_api_key_file: Some(path.to_string_lossy().to_string()),
+            ..OrchestratorConfig::default()
+        };
+        assert_eq!(resolve_gemini_api_key(&config).unwrap(), "from-file");
+    }
+}
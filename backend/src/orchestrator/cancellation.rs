@@ -0,0 +1,176 @@
+//! Orchestration cancellation
+//!
+//! Every orchestration run gets a `CancellationToken`, kept in a process-wide
+//! registry under the run's execution ID so `POST
+//! /api/orchestrate/:execution_id/cancel` can reach it. Slow work (planning,
+//! plan execution) is raced against the token and gives up with
+//! `AppError::Cancelled` as soon as it fires; dropping the work kills any CLI
+//! process or HTTP request it had in flight.
+
+use crate::error::AppError;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// Tokens of the runs in flight, by execution ID
+static TOKENS: Lazy<Mutex<HashMap<String, CancellationToken>>> = Lazy::new(Default::default);
+
+/// Signals that a run should stop; clones share the same signal
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<TokenState>,
+}
+
+#[derive(Debug, Default)]
+struct TokenState {
+    /// Set once the run has been cancelled; never cleared
+    cancelled: AtomicBool,
+    /// Wakes everything waiting in `cancelled()`
+    notify: Notify,
+}
+
+impl CancellationToken {
+    /// A token that has not been cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the run; waiters wake immediately and later waits return at once
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    /// Whether `cancel` has been called
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the token is cancelled
+    pub async fn cancelled(&self) {
+        let notified = self.inner.notify.notified();
+        tokio::pin!(notified);
+        // Register before checking the flag so a cancel in between isn't missed
+        notified.as_mut().enable();
+        if self.is_cancelled() {
+            return;
+        }
+        notified.await;
+    }
+
+    /// Run `work` unless the token fires first
+    ///
+    /// # Returns
+    /// * `Ok(T)`/`Err(AppError)` - What `work` returned, if it finished first
+    /// * `Err(AppError::Cancelled)` - If the token fired first; `work` is dropped
+    pub async fn run<T>(
+        &self,
+        operation: &str,
+        work: impl Future<Output = Result<T, AppError>>,
+    ) -> Result<T, AppError> {
+        tokio::select! {
+            biased;
+            _ = self.cancelled() => Err(AppError::Cancelled(format!("{} was cancelled", operation))),
+            result = work => result,
+        }
+    }
+}
+
+/// A run's entry in the registry; removed again when dropped
+#[derive(Debug)]
+pub struct CancelRegistration {
+    /// Execution ID the token is registered under
+    execution_id: String,
+    /// The registered token, so a newer run with the same ID isn't unregistered
+    token: CancellationToken,
+}
+
+impl CancelRegistration {
+    /// The run's token
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for CancelRegistration {
+    fn drop(&mut self) {
+        if let Ok(mut tokens) = TOKENS.lock() {
+            let ours = tokens
+                .get(&self.execution_id)
+                .is_some_and(|token| Arc::ptr_eq(&token.inner, &self.token.inner));
+            if ours {
+                tokens.remove(&self.execution_id);
+            }
+        }
+    }
+}
+
+/// Register a fresh token for the run with `execution_id`
+pub fn register(execution_id: &str) -> CancelRegistration {
+    let token = CancellationToken::new();
+    if let Ok(mut tokens) = TOKENS.lock() {
+        tokens.insert(execution_id.to_string(), token.clone());
+    }
+    CancelRegistration {
+        execution_id: execution_id.to_string(),
+        token,
+    }
+}
+
+/// Cancel the run with `execution_id`
+///
+/// Returns `false` if no such run is in flight.
+pub fn cancel(execution_id: &str) -> bool {
+    let token = TOKENS
+        .lock()
+        .ok()
+        .and_then(|tokens| tokens.get(execution_id).cloned());
+    match token {
+        Some(token) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancel_follows_registration() {
+        assert!(!cancel("run-cancel-test"));
+
+        let registration = register("run-cancel-test");
+        let token = registration.token().clone();
+        let waiter = tokio::spawn(async move {
+            token
+                .run("slow work", async {
+                    tokio::time::sleep(Duration::from_secs(30)).await;
+                    Ok(())
+                })
+                .await
+        });
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(cancel("run-cancel-test"));
+        let result = tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("cancelled work should stop promptly")
+            .unwrap();
+        assert!(matches!(result, Err(AppError::Cancelled(_))));
+
+        // A token cancelled earlier stops later work straight away
+        let result = registration.token().run("more work", async { Ok(1) }).await;
+        assert!(matches!(result, Err(AppError::Cancelled(_))));
+
+        // Once the run is over its token is gone
+        drop(registration);
+        assert!(!cancel("run-cancel-test"));
+    }
+}
@@ -3,7 +3,27 @@
 //! Centralized configuration for orchestrator components.
 
 use crate::error::AppError;
+use crate::orchestrator::api_key::GEMINI_API_KEY_FILE_ENV;
+use crate::orchestrator::constants::{
+    AUDIT_STORE_GOAL_ENV, ENABLE_PING_TASK_ENV, MAX_PLANNER_EXAMPLES, MAX_PLANNER_EXAMPLES_CHARS,
+};
+use crate::orchestrator::plan_types::Plan;
+use crate::orchestrator::step_dump::DUMP_STEP_OUTPUTS_DIR_ENV;
+use crate::state::PersistenceError;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Name of the file the live config is saved to, in the data directory
+pub const CONFIG_FILE_NAME: &str = "orchestrator_config.json";
+
+/// Example goal and plan shown to the planner to steer its output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannerExample {
+    /// Example goal
+    pub goal: String,
+    /// Plan the planner should produce for it
+    pub plan: Plan,
+}
 
 /// Orchestrator configuration
 #[derive(Debug, Clone, Serialize)]
@@ -18,11 +38,82 @@ pub struct OrchestratorConfig {
     pub gemini_api_base_url: String,
     /// Maximum goal length in characters
     pub max_goal_length: usize,
+    /// Maximum prompt length in characters for run_gemini steps and direct API calls
+    pub max_prompt_length: usize,
     /// Plan execution timeout in seconds
     pub plan_timeout_secs: u64,
-    /// Maximum number of parallel tasks (for concurrency limiting)
-    #[allow(dead_code)] // Will be used when implementing concurrency configuration
+    /// Largest `timeout_secs` an orchestration request may ask for
+    pub max_plan_timeout_secs: u64,
+    /// Run plans with steps the runner can't reach from the start step,
+    /// logging a warning, instead of rejecting them; those steps never run
+    ///
+    /// On by default, since plans with several independent roots are common.
+    pub allow_unreachable_steps: bool,
+    /// Maximum length of the longest dependency chain (critical path) in a plan
+    pub max_chain_length: usize,
+    /// Maximum number of parallel tasks; the `max_concurrency` of the Gemini
+    /// agent created for orchestration
     pub max_parallel_tasks: usize,
+    /// Store the raw goal text in the audit log (off by default for privacy)
+    ///
+    /// Defaults to `AUDIT_STORE_GOAL`.
+    pub audit_store_goal: bool,
+    /// Store each executed plan and its step outputs in the audit log so it can be
+    /// replayed or re-run from a step (off by default)
+    pub audit_store_plan: bool,
+    /// Maximum characters of step output sent in a `step_complete` event
+    pub max_event_output_chars: usize,
+    /// Start each line of streamed step output with `[step_id] `
+    ///
+    /// `step_progress` events already name their step; this is for clients
+    /// that show the chunks of parallel steps together as one stream of text.
+    pub prefix_step_output: bool,
+    /// Models tried in order when `gemini_model` is rate-limited or unavailable
+    ///
+    /// Empty leaves run_gemini steps on the CLI's default model. Models are only
+    /// passed when the default agent is a Gemini agent.
+    pub model_fallbacks: Vec<String>,
+    /// Regex patterns; prompts matching any of them are rejected before reaching Gemini
+    pub prompt_denylist: Vec<String>,
+    /// Directory create_file steps write into instead of the working directory
+    ///
+    /// Must be relative; it is resolved against the run's working directory
+    /// and may not climb out of it. Keeps generated files apart from the
+    /// context agents read.
+    pub output_dir: Option<String>,
+    /// File holding the Gemini API key, read in preference to `GEMINI_API_KEY`
+    ///
+    /// Defaults to `GEMINI_API_KEY_FILE`. Not settable through the config API.
+    pub gemini_api_key_file: Option<String>,
+    /// Directory each step's output is also written to, for debugging failed plans
+    ///
+    /// Outputs land in `<dir>/<execution_id>/<step_id>.txt`. Defaults to
+    /// `DUMP_STEP_OUTPUTS_DIR`. Not settable through the config API.
+    pub dump_step_outputs_dir: Option<String>,
+    /// Let plans use the `ping` task, which sleeps and returns a fixed string
+    ///
+    /// For exercising scheduling and timeouts without calling Gemini; the
+    /// planner is never told about it. Defaults to `ENABLE_PING_TASK`. Not
+    /// settable through the config API.
+    pub enable_ping_task: bool,
+    /// Plans estimated to use more tokens are rejected before execution (None = no limit)
+    pub max_estimated_tokens: Option<usize>,
+    /// Plans estimated to cost more (USD) are rejected before execution (None = no limit)
+    pub max_estimated_cost: Option<f64>,
+    /// Runs are aborted once the tokens their steps actually used exceed this (None = no limit)
+    pub max_actual_tokens: Option<u64>,
+    /// Accept an empty reply from the Gemini API for run_gemini steps
+    ///
+    /// Some prompts legitimately yield nothing. Planner calls, which need JSON,
+    /// always reject an empty reply.
+    pub allow_empty_gemini_response: bool,
+    /// Seconds a Gemini API reply is reused for an identical prompt (None = no caching)
+    ///
+    /// Only successful, non-empty replies are cached, keyed by prompt, model
+    /// and JSON mode. Streamed (CLI) steps always call Gemini.
+    pub gemini_cache_ttl_secs: Option<u64>,
+    /// Deployment-specific examples added to the planner prompt
+    pub planner_examples: Vec<PlannerExample>,
 }
 
 impl Default for OrchestratorConfig {
@@ -31,9 +122,85 @@ impl Default for OrchestratorConfig {
             gemini_timeout_secs: 30,
             gemini_model: "gemini-2.5-flash".to_string(),
             gemini_api_base_url: "https://generativelanguage.googleapis.com/v1beta".to_string(),
-            max_goal_length: 10000, // 10KB
-            plan_timeout_secs: 300, // 5 minutes
-            max_parallel_tasks: 10, // Limit to 10 parallel tasks by default
+            max_goal_length: 10000,         // 10KB
+            max_prompt_length: 100_000,     // 100KB - well below model context limits
+            plan_timeout_secs: 300,         // 5 minutes
+            max_plan_timeout_secs: 3600,    // 1 hour
+            allow_unreachable_steps: true,  // Unreachable steps only log a warning
+            max_chain_length: 100,          // Far beyond typical plans, well short of timeouts
+            max_parallel_tasks: 10,         // Limit to 10 parallel tasks by default
+            audit_store_plan: false,        // Plans may quote the goal; opt in to replay
+            max_event_output_chars: 10_000, // Full output stays in the step results
+            prefix_step_output: false,      // Chunks are sent as produced
+            model_fallbacks: Vec::new(),    // No fallback: fail on the primary model
+            prompt_denylist: Vec::new(),    // No prompts blocked
+            output_dir: None,               // Write into the working directory
+            gemini_api_key_file: std::env::var(GEMINI_API_KEY_FILE_ENV)
+                .ok()
+                .filter(|path| !path.trim().is_empty()),
+            dump_step_outputs_dir: std::env::var(DUMP_STEP_OUTPUTS_DIR_ENV)
+                .ok()
+                .filter(|path| !path.trim().is_empty()),
+            audit_store_goal: std::env::var(AUDIT_STORE_GOAL_ENV)
+                .map(|value| matches!(value.trim(), "true" | "1"))
+                .unwrap_or(false),
+            enable_ping_task: std::env::var(ENABLE_PING_TASK_ENV)
+                .map(|value| matches!(value.trim(), "true" | "1"))
+                .unwrap_or(false),
+            max_estimated_tokens: None, // No budget enforced
+            max_estimated_cost: None,
+            max_actual_tokens: None,
+            allow_empty_gemini_response: false, // An empty reply is an error
+            gemini_cache_ttl_secs: None,        // Every prompt calls the API
+            planner_examples: Vec::new(),       // Built-in examples only
+        }
+    }
+}
+
+impl OrchestratorConfig {
+    /// Save the config as JSON, so `load` can restore it after a restart
+    ///
+    /// The parent directory is created if missing.
+    pub fn save(&self, path: &Path) -> Result<(), PersistenceError> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| PersistenceError::JsonError(e.to_string()))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| PersistenceError::IoError(e.to_string()))?;
+        }
+        std::fs::write(path, json).map_err(|e| PersistenceError::IoError(e.to_string()))
+    }
+
+    /// Load a config saved by `save`
+    ///
+    /// The file is applied to the defaults as a `ConfigUpdateRequest`, so it
+    /// passes the same checks as `POST /api/config`; read-only and
+    /// environment-derived fields always come from the defaults. A missing
+    /// file gives the defaults, and so does an unreadable or invalid one,
+    /// with a warning.
+    pub fn load(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default();
+        }
+        let loaded = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                serde_json::from_str::<ConfigUpdateRequest>(&json).map_err(|e| e.to_string())
+            })
+            .and_then(|request| {
+                validate_and_apply_config_update(Self::default(), request)
+                    .map_err(|e| e.to_string())
+            });
+        match loaded {
+            Ok(config) => config,
+            Err(error) => {
+                tracing::warn!(
+                    path = %path.display(),
+                    error = %error,
+                    "Ignoring saved orchestrator config, using defaults"
+                );
+                Self::default()
+            }
         }
     }
 }
@@ -47,8 +214,42 @@ pub struct ConfigUpdateRequest {
     pub gemini_model: Option<String>,
     /// Maximum goal length in characters (optional)
     pub max_goal_length: Option<usize>,
+    /// Maximum prompt length in characters (optional)
+    pub max_prompt_length: Option<usize>,
     /// Plan execution timeout in seconds (optional)
     pub plan_timeout_secs: Option<u64>,
+    /// Maximum per-request plan timeout in seconds (optional)
+    pub max_plan_timeout_secs: Option<u64>,
+    /// Warn about disconnected steps instead of rejecting the plan (optional)
+    pub allow_unreachable_steps: Option<bool>,
+    /// Maximum dependency chain length (optional)
+    pub max_chain_length: Option<usize>,
+    /// Maximum step output characters per SSE event (optional)
+    pub max_event_output_chars: Option<usize>,
+    /// Prefix streamed step output lines with their step ID (optional)
+    pub prefix_step_output: Option<bool>,
+    /// Fallback models, replacing the current list (optional)
+    pub model_fallbacks: Option<Vec<String>>,
+    /// Prompt denylist regexes, replacing the current list (optional)
+    pub prompt_denylist: Option<Vec<String>>,
+    /// Output directory for create_file steps (optional, empty string clears it)
+    pub output_dir: Option<String>,
+    /// Token budget per plan (optional, 0 removes the limit)
+    pub max_estimated_tokens: Option<usize>,
+    /// Cost budget per plan in USD (optional, 0 removes the limit)
+    pub max_estimated_cost: Option<f64>,
+    /// Token limit on a run's actual usage (optional, 0 removes the limit)
+    pub max_actual_tokens: Option<u64>,
+    /// Store raw goal text in the audit log (optional)
+    pub audit_store_goal: Option<bool>,
+    /// Store executed plans in the audit log for replay (optional)
+    pub audit_store_plan: Option<bool>,
+    /// Accept empty Gemini API replies for run_gemini steps (optional)
+    pub allow_empty_gemini_response: Option<bool>,
+    /// Gemini reply cache lifetime in seconds (optional, 0 disables caching)
+    pub gemini_cache_ttl_secs: Option<u64>,
+    /// Planner examples, replacing the current list (optional)
+    pub planner_examples: Option<Vec<PlannerExample>>,
 }
 
 /// Validate and apply configuration updates
@@ -97,6 +298,16 @@ pub fn validate_and_apply_config_update(
         config.max_goal_length = max_goal;
     }
 
+    // Validate and apply max_prompt_length
+    if let Some(max_prompt) = request.max_prompt_length {
+        if max_prompt == 0 {
+            return Err(AppError::Internal(anyhow::anyhow!(
+                "max_prompt_length must be > 0"
+            )));
+        }
+        config.max_prompt_length = max_prompt;
+    }
+
     // Validate and apply plan_timeout_secs
     if let Some(timeout) = request.plan_timeout_secs {
         if timeout == 0 {
@@ -107,5 +318,401 @@ pub fn validate_and_apply_config_update(
         config.plan_timeout_secs = timeout;
     }
 
+    // Validate and apply max_plan_timeout_secs
+    if let Some(max_timeout) = request.max_plan_timeout_secs {
+        if max_timeout == 0 {
+            return Err(AppError::Internal(anyhow::anyhow!(
+                "max_plan_timeout_secs must be > 0"
+            )));
+        }
+        config.max_plan_timeout_secs = max_timeout;
+    }
+    if config.plan_timeout_secs > config.max_plan_timeout_secs {
+        return Err(AppError::Internal(anyhow::anyhow!(
+            "plan_timeout_secs ({}) must not exceed max_plan_timeout_secs ({})",
+            config.plan_timeout_secs,
+            config.max_plan_timeout_secs
+        )));
+    }
+
+    // Validate and apply max_chain_length
+    if let Some(max_chain) = request.max_chain_length {
+        if max_chain == 0 {
+            return Err(AppError::Internal(anyhow::anyhow!(
+                "max_chain_length must be > 0"
+            )));
+        }
+        config.max_chain_length = max_chain;
+    }
+
+    // Validate and apply max_event_output_chars
+    if let Some(max_output) = request.max_event_output_chars {
+        if max_output == 0 {
+            return Err(AppError::Internal(anyhow::anyhow!(
+                "max_event_output_chars must be > 0"
+            )));
+        }
+        config.max_event_output_chars = max_output;
+    }
+
+    // Apply the unreachable step policy
+    if let Some(allow) = request.allow_unreachable_steps {
+        config.allow_unreachable_steps = allow;
+    }
+
+    // Apply streamed output prefixing
+    if let Some(prefix) = request.prefix_step_output {
+        config.prefix_step_output = prefix;
+    }
+
+    // Validate and apply model_fallbacks
+    if let Some(fallbacks) = request.model_fallbacks {
+        if fallbacks.iter().any(|model| model.trim().is_empty()) {
+            return Err(AppError::Internal(anyhow::anyhow!(
+                "model_fallbacks cannot contain empty model names"
+            )));
+        }
+        config.model_fallbacks = fallbacks;
+    }
+
+    // Validate and apply prompt_denylist
+    if let Some(denylist) = request.prompt_denylist {
+        for pattern in &denylist {
+            regex::Regex::new(pattern).map_err(|e| {
+                AppError::Internal(anyhow::anyhow!(
+                    "prompt_denylist contains an invalid regex '{}': {}",
+                    pattern,
+                    e
+                ))
+            })?;
+        }
+        config.prompt_denylist = denylist;
+    }
+
+    // Validate and apply output_dir; it must stay inside the working directory
+    if let Some(output_dir) = request.output_dir {
+        let output_dir = output_dir.trim();
+        let inside = Path::new(output_dir).components().all(|component| {
+            matches!(
+                component,
+                std::path::Component::Normal(_) | std::path::Component::CurDir
+            )
+        });
+        if !inside {
+            return Err(AppError::InvalidPath(format!(
+                "output_dir '{}' must be a relative path inside the working directory",
+                output_dir
+            )));
+        }
+        config.output_dir = (!output_dir.is_empty()).then(|| output_dir.to_string());
+    }
+
+    // Apply the budget limits
+    if let Some(max_tokens) = request.max_estimated_tokens {
+        config.max_estimated_tokens = (max_tokens > 0).then_some(max_tokens);
+    }
+    if let Some(max_cost) = request.max_estimated_cost {
+        if !max_cost.is_finite() || max_cost < 0.0 {
+            return Err(AppError::Internal(anyhow::anyhow!(
+                "max_estimated_cost must be a non-negative number"
+            )));
+        }
+        config.max_estimated_cost = (max_cost > 0.0).then_some(max_cost);
+    }
+    if let Some(max_tokens) = request.max_actual_tokens {
+        config.max_actual_tokens = (max_tokens > 0).then_some(max_tokens);
+    }
+
+    // Apply goal and plan retention
+    if let Some(store_goal) = request.audit_store_goal {
+        config.audit_store_goal = store_goal;
+    }
+    if let Some(store_plan) = request.audit_store_plan {
+        config.audit_store_plan = store_plan;
+    }
+
+    // Apply empty-reply handling
+    if let Some(allow_empty) = request.allow_empty_gemini_response {
+        config.allow_empty_gemini_response = allow_empty;
+    }
+
+    // Apply reply caching
+    if let Some(ttl) = request.gemini_cache_ttl_secs {
+        config.gemini_cache_ttl_secs = (ttl > 0).then_some(ttl);
+    }
+
+    // Validate and apply planner_examples
+    if let Some(examples) = request.planner_examples {
+        validate_planner_examples(&examples)?;
+        config.planner_examples = examples;
+    }
+
     Ok(config)
 }
+
+/// Check that planner examples are valid plans and keep the prompt bounded
+///
+/// # Returns
+/// * `Ok(())` - If every example has a goal and a valid plan, within the count and size caps
+/// * `Err(AppError)` - Naming the first offending example
+fn validate_planner_examples(examples: &[PlannerExample]) -> Result<(), AppError> {
+    if examples.len() > MAX_PLANNER_EXAMPLES {
+        return Err(AppError::Internal(anyhow::anyhow!(
+            "planner_examples may contain at most {} examples",
+            MAX_PLANNER_EXAMPLES
+        )));
+    }
+
+    let mut total_chars = 0;
+    for (index, example) in examples.iter().enumerate() {
+        if example.goal.trim().is_empty() {
+            return Err(AppError::Internal(anyhow::anyhow!(
+                "planner_examples[{}] has an empty goal",
+                index
+            )));
+        }
+        if example.plan.steps.is_empty() {
+            return Err(AppError::Internal(anyhow::anyhow!(
+                "planner_examples[{}] has a plan with no steps",
+                index
+            )));
+        }
+        example.plan.validate().map_err(|e| {
+            AppError::Internal(anyhow::anyhow!(
+                "planner_examples[{}] has an invalid plan: {}",
+                index,
+                e
+            ))
+        })?;
+        let plan_json = serde_json::to_string(&example.plan)
+            .map_err(|e| AppError::Internal(anyhow::anyhow!(e)))?;
+        total_chars += example.goal.chars().count() + plan_json.chars().count();
+    }
+
+    if total_chars > MAX_PLANNER_EXAMPLES_CHARS {
+        return Err(AppError::Internal(anyhow::anyhow!(
+            "planner_examples total {} characters, above the limit of {}",
+            total_chars,
+            MAX_PLANNER_EXAMPLES_CHARS
+        )));
+    }
+    Ok(())
+}
+
+/// JSON Schema (draft 2020-12) describing `OrchestratorConfig`
+///
+/// Lists every field returned by `GET /api/config` with its type, the bounds
+/// `validate_and_apply_config_update` enforces and its default. Fields that
+/// can't be changed through `POST /api/config` are marked `readOnly`.
+pub fn config_schema() -> serde_json::Value {
+    use serde_json::json;
+
+    let positive = |description: &str| {
+        json!({
+            "type": "integer",
+            "minimum": 1,
+            "description": description,
+        })
+    };
+    let optional_limit = |kind: &str, description: &str| {
+        json!({
+            "type": [kind, "null"],
+            "minimum": 0,
+            "description": format!("{} (0 or null removes the limit)", description),
+        })
+    };
+    let read_only = |kind: serde_json::Value, description: &str| {
+        json!({
+            "type": kind,
+            "readOnly": true,
+            "description": description,
+        })
+    };
+
+    let mut properties: serde_json::Map<String, serde_json::Value> = [
+        (
+            "gemini_timeout_secs",
+            read_only(json!("integer"), "Gemini API timeout in seconds"),
+        ),
+        (
+            "gemini_model",
+            json!({
+                "type": "string",
+                "minLength": 1,
+                "description": "Gemini model name",
+            }),
+        ),
+        (
+            "gemini_api_base_url",
+            read_only(json!("string"), "Gemini API base URL"),
+        ),
+        (
+            "max_goal_length",
+            positive("Maximum goal length in characters"),
+        ),
+        (
+            "max_prompt_length",
+            positive("Maximum prompt length in characters"),
+        ),
+        (
+            "plan_timeout_secs",
+            positive("Plan execution timeout in seconds"),
+        ),
+        (
+            "max_plan_timeout_secs",
+            positive("Largest timeout_secs an orchestration request may ask for"),
+        ),
+        (
+            "max_chain_length",
+            positive("Maximum length of the longest dependency chain"),
+        ),
+        (
+            "max_parallel_tasks",
+            positive("Maximum number of parallel tasks"),
+        ),
+        (
+            "audit_store_goal",
+            json!({
+                "type": "boolean",
+                "description": "Store the raw goal text in the audit log",
+            }),
+        ),
+        (
+            "audit_store_plan",
+            json!({
+                "type": "boolean",
+                "description": "Store executed plans and step outputs in the audit log for replay",
+            }),
+        ),
+        (
+            "max_event_output_chars",
+            positive("Maximum characters of step output per SSE event"),
+        ),
+        (
+            "prefix_step_output",
+            json!({
+                "type": "boolean",
+                "description": "Start each line of streamed step output with [step_id]",
+            }),
+        ),
+        (
+            "allow_unreachable_steps",
+            json!({
+                "type": "boolean",
+                "description": "Warn about steps not reachable from the start step instead of rejecting the plan",
+            }),
+        ),
+        (
+            "model_fallbacks",
+            json!({
+                "type": "array",
+                "items": { "type": "string", "pattern": "\\S" },
+                "description": "Models tried in order when gemini_model is unavailable",
+            }),
+        ),
+        (
+            "prompt_denylist",
+            json!({
+                "type": "array",
+                "items": { "type": "string", "format": "regex" },
+                "description": "Regex patterns; matching prompts are rejected",
+            }),
+        ),
+        (
+            "output_dir",
+            json!({
+                "type": ["string", "null"],
+                "description": "Directory create_file steps write into, relative to the working directory (empty string clears it)",
+            }),
+        ),
+        (
+            "gemini_api_key_file",
+            read_only(json!(["string", "null"]), "File holding the Gemini API key"),
+        ),
+        (
+            "dump_step_outputs_dir",
+            read_only(
+                json!(["string", "null"]),
+                "Directory each step's output is also written to",
+            ),
+        ),
+        (
+            "enable_ping_task",
+            read_only(json!("boolean"), "Let plans use the ping task"),
+        ),
+        (
+            "max_estimated_tokens",
+            optional_limit("integer", "Token budget per plan, checked before execution"),
+        ),
+        (
+            "max_estimated_cost",
+            optional_limit(
+                "number",
+                "Cost budget per plan in USD, checked before execution",
+            ),
+        ),
+        (
+            "max_actual_tokens",
+            optional_limit(
+                "integer",
+                "Tokens a run may actually use before it is aborted",
+            ),
+        ),
+        (
+            "allow_empty_gemini_response",
+            json!({
+                "type": "boolean",
+                "description": "Accept empty Gemini API replies for run_gemini steps",
+            }),
+        ),
+        (
+            "gemini_cache_ttl_secs",
+            json!({
+                "type": ["integer", "null"],
+                "minimum": 0,
+                "description": "Seconds a Gemini API reply is reused for an identical prompt (0 or null disables caching)",
+            }),
+        ),
+        (
+            "planner_examples",
+            json!({
+                "type": "array",
+                "maxItems": MAX_PLANNER_EXAMPLES,
+                "items": {
+                    "type": "object",
+                    "required": ["goal", "plan"],
+                    "properties": {
+                        "goal": { "type": "string", "pattern": "\\S" },
+                        "plan": { "type": "object" },
+                    },
+                },
+                "description": format!(
+                    "Examples added to the planner prompt (at most {} characters in total)",
+                    MAX_PLANNER_EXAMPLES_CHARS
+                ),
+            }),
+        ),
+    ]
+    .into_iter()
+    .map(|(field, property)| (field.to_string(), property))
+    .collect();
+
+    // Defaults come from the config itself so they can't drift
+    if let Ok(serde_json::Value::Object(defaults)) =
+        serde_json::to_value(OrchestratorConfig::default())
+    {
+        for (field, default) in defaults {
+            if let Some(property) = properties.get_mut(&field) {
+                property["default"] = default;
+            }
+        }
+    }
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "OrchestratorConfig",
+        "type": "object",
+        "properties": properties,
+        "additionalProperties": false,
+    })
+}
@@ -15,5 +15,70 @@ pub const DEFAULT_GRAPH_ID: &str = "plan_execution";
 /// Format: "{step_id}{STEP_OUTPUT_SUFFIX}"
 pub const STEP_OUTPUT_SUFFIX: &str = ".output";
 
+/// Output a step reference resolves to when it names no output ("step_1" -> "step_1.output")
+pub const DEFAULT_OUTPUT_NAME: &str = "output";
+
+/// Suffix for the context key recording which model served a run_gemini step
+/// Format: "{step_id}{STEP_MODEL_SUFFIX}"
+pub const STEP_MODEL_SUFFIX: &str = ".model";
+
+/// Suffix for the context key holding a run_gemini step's token usage by model
+/// Format: "{step_id}{STEP_USAGE_SUFFIX}"
+pub const STEP_USAGE_SUFFIX: &str = ".usage";
+
+/// Suffix for the context key holding input supplied to a step that waited for it
+/// Format: "{step_id}{STEP_INPUT_SUFFIX}"
+pub const STEP_INPUT_SUFFIX: &str = ".input";
+
 /// Context key for working directory
 pub const WORKING_DIR_KEY: &str = "working_dir";
+
+/// Context key marking that the run's working directory was snapshotted at start
+///
+/// When set, tasks use `WORKING_DIR_KEY` (or the process directory if absent)
+/// and ignore later changes to the app state's working directory.
+pub const WORKING_DIR_BOUND_KEY: &str = "working_dir_bound";
+
+/// Context key holding the run ID its step progress sender is registered under
+///
+/// Only set when someone listens for progress (see `step_progress`).
+pub const STEP_PROGRESS_KEY: &str = "step_progress_run";
+
+/// Environment variable that stores raw goal text in the audit log (off unless "true" or "1")
+pub const AUDIT_STORE_GOAL_ENV: &str = "AUDIT_STORE_GOAL";
+
+/// Environment variable that lets plans use the `ping` task (off unless "true" or "1")
+pub const ENABLE_PING_TASK_ENV: &str = "ENABLE_PING_TASK";
+
+/// Stdout chunks buffered between a streaming Gemini process and its listener
+pub const STREAM_CHUNK_BUFFER: usize = 32;
+
+/// Maximum number of items a for_each step may expand to at execution time
+pub const MAX_FOR_EACH_ITEMS: usize = 100;
+
+/// Maximum delay a ping step may sleep for, in milliseconds
+pub const MAX_PING_DELAY_MS: u64 = 60_000;
+
+/// Maximum number of segments in a `content_from_json_path` pointer
+pub const MAX_JSON_POINTER_DEPTH: usize = 32;
+
+/// Largest file a create_file step may write, in bytes (checked after appending)
+pub const MAX_FILE_WRITE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Rough blended USD price per 1,000 tokens used for plan cost estimates
+pub const ESTIMATED_COST_PER_1K_TOKENS_USD: f64 = 0.001;
+
+/// Maximum number of Gemini API replies kept by the reply cache
+pub const MAX_GEMINI_CACHE_ENTRIES: usize = 256;
+
+/// Maximum number of configured planner examples
+pub const MAX_PLANNER_EXAMPLES: usize = 5;
+
+/// Maximum combined size of the configured planner examples (goals plus plan JSON)
+pub const MAX_PLANNER_EXAMPLES_CHARS: usize = 8_000;
+
+/// Characters of an unparseable planner response embedded in the resulting error
+pub const MAX_PLANNER_ERROR_RESPONSE_CHARS: usize = 500;
+
+/// Output of a ping step that sets no `message`
+pub const DEFAULT_PING_MESSAGE: &str = "pong";
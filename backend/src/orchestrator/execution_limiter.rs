@@ -0,0 +1,136 @@
+//! Global limit on concurrent orchestrations
+//!
+//! `max_parallel_tasks` bounds the steps inside one plan; this limiter bounds
+//! how many orchestrations run at once across all clients. A permit is taken
+//! when an orchestration starts and released when it is dropped, which covers
+//! completion, errors and clients disconnecting mid-stream.
+
+use crate::error::AppError;
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Default number of orchestrations allowed to run at once
+pub const DEFAULT_MAX_CONCURRENT_EXECUTIONS: usize = 4;
+
+/// What to do when every execution slot is taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QueuePolicy {
+    /// Fail immediately with 429 Too Many Requests
+    #[default]
+    Reject,
+    /// Wait until a slot frees up
+    Queue,
+}
+
+impl QueuePolicy {
+    /// Parse a policy name from configuration ("reject" or "queue")
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "reject" => Some(Self::Reject),
+            "queue" => Some(Self::Queue),
+            _ => None,
+        }
+    }
+}
+
+/// Shared semaphore limiting concurrent orchestrations
+#[derive(Debug, Clone)]
+pub struct ExecutionLimiter {
+    /// Available execution slots
+    semaphore: Arc<Semaphore>,
+    /// Behaviour when no slot is available
+    policy: QueuePolicy,
+    /// Total number of slots
+    max_concurrent: usize,
+}
+
+impl ExecutionLimiter {
+    /// Create a limiter with `max_concurrent` slots
+    pub fn new(max_concurrent: usize, policy: QueuePolicy) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_concurrent)),
+            policy,
+            max_concurrent,
+        }
+    }
+
+    /// Take an execution slot, following the configured policy
+    ///
+    /// The slot is released when the returned permit is dropped.
+    ///
+    /// # Returns
+    /// * `Ok(OwnedSemaphorePermit)` - The slot for this execution
+    /// * `Err(AppError::TooManyRequests)` - If all slots are taken and the policy is `Reject`
+    pub async fn acquire(&self) -> Result<OwnedSemaphorePermit, AppError> {
+        match self.policy {
+            QueuePolicy::Reject => self.semaphore.clone().try_acquire_owned().map_err(|_| {
+                AppError::TooManyRequests(format!(
+                    "All {} execution slots are busy; try again later",
+                    self.max_concurrent
+                ))
+            }),
+            QueuePolicy::Queue => self.semaphore.clone().acquire_owned().await.map_err(|e| {
+                AppError::Internal(anyhow::anyhow!("Execution limiter closed: {}", e))
+            }),
+        }
+    }
+
+    /// Number of executions currently running
+    pub fn in_flight(&self) -> usize {
+        self.max_concurrent - self.semaphore.available_permits()
+    }
+}
+
+impl Default for ExecutionLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CONCURRENT_EXECUTIONS, QueuePolicy::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_reject_policy_rejects_when_full() {
+        let limiter = ExecutionLimiter::new(2, QueuePolicy::Reject);
+        let first = limiter.acquire().await.unwrap();
+        let _second = limiter.acquire().await.unwrap();
+        assert_eq!(limiter.in_flight(), 2);
+
+        let third = limiter.acquire().await;
+        assert!(matches!(third, Err(AppError::TooManyRequests(_))));
+
+        // Finishing an execution frees its slot
+        drop(first);
+        assert!(limiter.acquire().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_queue_policy_waits_for_free_slot() {
+        let limiter = ExecutionLimiter::new(1, QueuePolicy::Queue);
+        let running = limiter.acquire().await.unwrap();
+
+        let queued = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire().await.map(|_| ()) }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!queued.is_finished(), "second execution should be queued");
+
+        drop(running);
+        tokio::time::timeout(Duration::from_secs(1), queued)
+            .await
+            .expect("queued execution should start once the slot frees")
+            .unwrap()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_queue_policy_parse() {
+        assert_eq!(QueuePolicy::parse("reject"), Some(QueuePolicy::Reject));
+        assert_eq!(QueuePolicy::parse(" Queue "), Some(QueuePolicy::Queue));
+        assert_eq!(QueuePolicy::parse("drop"), None);
+    }
+}
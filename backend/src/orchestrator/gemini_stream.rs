@@ -0,0 +1,219 @@
+//! Gemini CLI streaming output
+//!
+//! With `--output-format stream-json`, Gemini CLI prints one JSON event per
+//! line while it works:
+//!
+//! ```text
+//! {"type":"init","session_id":"...","model":"gemini-2.5-flash"}
+//! {"type":"message","role":"assistant","content":"Roses are","delta":true}
+//! {"type":"result","status":"success","stats":{"input_tokens":12,"output_tokens":30,"total_tokens":42}}
+//! ```
+//!
+//! `GeminiStream` turns stdout chunks into the assistant's text fragments as
+//! they arrive; `parse_gemini_stream_output` folds the complete output into a
+//! `GeminiCliResponse` once the process has exited.
+
+use crate::orchestrator::primitives::GeminiCliResponse;
+use crate::orchestrator::token_usage::{parse_gemini_cli_usage, TokenUsage, UsageByModel};
+use serde::Deserialize;
+
+/// Usage key when the CLI reports tokens but never names the model
+const UNKNOWN_MODEL: &str = "unknown";
+
+/// One line of stream-json output, as far as we use it
+#[derive(Debug, PartialEq)]
+enum StreamLine {
+    /// The run started on `model`
+    Init { model: Option<String> },
+    /// Text from the assistant
+    Text(String),
+    /// The run finished; `stats` holds the token counts
+    Result { stats: Option<serde_json::Value> },
+    /// Any other event (user echo, tool calls, warnings)
+    Other,
+}
+
+/// Classify one line of output
+///
+/// Lines that aren't JSON events are treated as plain text, so a CLI that
+/// ignores the output format still streams something useful.
+fn parse_line(line: &str) -> StreamLine {
+    #[derive(Deserialize)]
+    struct Event {
+        #[serde(rename = "type")]
+        kind: String,
+        role: Option<String>,
+        content: Option<String>,
+        model: Option<String>,
+        stats: Option<serde_json::Value>,
+    }
+
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return StreamLine::Other;
+    }
+    let Ok(event) = serde_json::from_str::<Event>(trimmed) else {
+        return StreamLine::Text(format!("{}\n", line));
+    };
+    match event.kind.as_str() {
+        "init" => StreamLine::Init { model: event.model },
+        "message" if event.role.as_deref() == Some("assistant") => {
+            StreamLine::Text(event.content.unwrap_or_default())
+        }
+        "result" => StreamLine::Result { stats: event.stats },
+        _ => StreamLine::Other,
+    }
+}
+
+/// Incremental reader of stream-json stdout
+#[derive(Debug, Default)]
+pub struct GeminiStream {
+    /// Bytes of a line not yet terminated by a newline
+    pending: Vec<u8>,
+}
+
+impl GeminiStream {
+    /// Create a reader with nothing buffered
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a chunk of stdout, returning the text fragments of the lines it completes
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(bytes);
+        let Some(end) = self.pending.iter().rposition(|&b| b == b'\n') else {
+            return Vec::new();
+        };
+        let complete: Vec<u8> = self.pending.drain(..=end).collect();
+        text_fragments(&String::from_utf8_lossy(&complete))
+    }
+
+    /// Text fragments of a final line left without a trailing newline
+    pub fn finish(self) -> Vec<String> {
+        text_fragments(&String::from_utf8_lossy(&self.pending))
+    }
+}
+
+/// The non-empty text fragments in `lines`
+fn text_fragments(lines: &str) -> Vec<String> {
+    lines
+        .lines()
+        .filter_map(|line| match parse_line(line) {
+            StreamLine::Text(text) if !text.is_empty() => Some(text),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Fold complete stream-json output into a response
+///
+/// The response is the concatenated assistant text. Usage comes from the
+/// `result` event's stats: per model under `stats.models` when present,
+/// otherwise the flat token counts attributed to the model from `init`.
+///
+/// # Arguments
+/// * `output` - Everything the CLI printed to stdout
+/// * `requested_model` - Model passed with `--model`, used if `init` names none
+pub fn parse_gemini_stream_output(
+    output: &str,
+    requested_model: Option<&str>,
+) -> GeminiCliResponse {
+    let mut parsed = GeminiCliResponse {
+        model: requested_model.map(str::to_string),
+        ..GeminiCliResponse::default()
+    };
+
+    for line in output.lines() {
+        match parse_line(line) {
+            StreamLine::Init { model: Some(model) } => parsed.model = Some(model),
+            StreamLine::Text(text) => parsed.response.push_str(&text),
+            StreamLine::Result { stats: Some(stats) } => parsed.stats = Some(stats),
+            _ => {}
+        }
+    }
+
+    if let Some(stats) = &parsed.stats {
+        parsed.usage = usage_from_stats(stats, parsed.model.as_deref());
+    }
+    parsed
+}
+
+/// Token usage from a `result` event's stats
+fn usage_from_stats(stats: &serde_json::Value, model: Option<&str>) -> UsageByModel {
+    let by_model = parse_gemini_cli_usage(&serde_json::json!({ "stats": stats }).to_string());
+    if !by_model.is_empty() {
+        return by_model;
+    }
+
+    let count = |field: &str| stats.get(field).and_then(serde_json::Value::as_u64);
+    let (Some(prompt_tokens), Some(output_tokens)) =
+        (count("input_tokens"), count("output_tokens"))
+    else {
+        return UsageByModel::new();
+    };
+    let usage = TokenUsage {
+        prompt_tokens,
+        output_tokens,
+        total_tokens: count("total_tokens").unwrap_or(prompt_tokens + output_tokens),
+    };
+    UsageByModel::from([(model.unwrap_or(UNKNOWN_MODEL).to_string(), usage)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OUTPUT: &str = concat!(
+        r#"{"type":"init","session_id":"s1","model":"gemini-2.5-flash"}"#,
+        "\n",
+        r#"{"type":"message","role":"user","content":"write a poem"}"#,
+        "\n",
+        r#"{"type":"message","role":"assistant","content":"Roses are ","delta":true}"#,
+        "\n",
+        r#"{"type":"message","role":"assistant","content":"red","delta":true}"#,
+        "\n",
+        r#"{"type":"result","status":"success","stats":{"input_tokens":12,"output_tokens":30,"total_tokens":42}}"#,
+        "\n",
+    );
+
+    #[test]
+    fn test_stream_yields_fragments_across_chunk_boundaries() {
+        let mut stream = GeminiStream::new();
+        let mut fragments = Vec::new();
+        // Split mid-line so events span chunks
+        for chunk in OUTPUT.as_bytes().chunks(17) {
+            fragments.extend(stream.push(chunk));
+        }
+        fragments.extend(stream.finish());
+        assert_eq!(fragments, vec!["Roses are ", "red"]);
+
+        // A last line without a newline comes out on finish
+        let mut stream = GeminiStream::new();
+        assert!(stream.push(b"plain text, no newline").is_empty());
+        assert_eq!(stream.finish(), vec!["plain text, no newline\n"]);
+    }
+
+    #[test]
+    fn test_parse_stream_output_collects_text_and_usage() {
+        let parsed = parse_gemini_stream_output(OUTPUT, None);
+        assert_eq!(parsed.response, "Roses are red");
+        assert_eq!(parsed.model.as_deref(), Some("gemini-2.5-flash"));
+        assert_eq!(
+            parsed.usage["gemini-2.5-flash"],
+            TokenUsage {
+                prompt_tokens: 12,
+                output_tokens: 30,
+                total_tokens: 42,
+            }
+        );
+
+        // Per-model stats take precedence over the flat counts
+        let output = r#"{"type":"result","stats":{"input_tokens":1,"output_tokens":1,"models":{"gemini-2.5-pro":{"tokens":{"prompt":5,"candidates":2,"total":7}}}}}"#;
+        let parsed = parse_gemini_stream_output(output, Some("gemini-2.5-pro"));
+        assert_eq!(parsed.response, "");
+        assert_eq!(parsed.usage["gemini-2.5-pro"].total_tokens, 7);
+
+        // No result event, no usage
+        assert!(parse_gemini_stream_output("hello\n", None).usage.is_empty());
+    }
+}
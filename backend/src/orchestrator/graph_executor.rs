@@ -13,26 +13,41 @@
 
 use crate::error::AppError;
 use crate::orchestrator::config::OrchestratorConfig;
-use crate::orchestrator::plan_to_graph::build_graph_from_plan;
-use crate::orchestrator::plan_types::Plan;
+use crate::orchestrator::constants::STEP_OUTPUT_SUFFIX;
+use crate::orchestrator::input_provider::supply_input;
+use crate::orchestrator::plan_expansion::expand_for_each;
+use crate::orchestrator::plan_to_graph::build_graph_from_plan_with_config;
+use crate::orchestrator::plan_types::{task_outputs, Plan};
+use crate::orchestrator::plan_utils::find_dependents;
+use crate::orchestrator::step_dump::StepOutputDumper;
+use crate::orchestrator::step_progress::{self, StepProgressSender};
+use crate::orchestrator::token_usage::{aggregate_usage, TokenUsage, UsageByModel};
 use crate::state::AppState;
 use anyhow::anyhow;
 use graph_flow::{
     Context, ExecutionStatus, FlowRunner, InMemorySessionStorage, Session, SessionStorage,
 };
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::time::{timeout, Duration};
 use uuid::Uuid;
 
 /// Result of executing a single step
-#[derive(Debug, Clone)]
+///
+/// Serialized with a `status` tag instead of `success` plus two optional
+/// fields: `{"status": "success", "output": ...}` or
+/// `{"status": "error", "error": ...}` (see `StepResultJson`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "StepResultJson", from = "StoredStepResult")]
 pub struct StepResult {
     /// Step ID
     pub step_id: String,
     /// Step number (1, 2, 3, etc.)
     pub step_number: u32,
+    /// Task type of the step (e.g., "run_gemini", "create_file")
+    pub task: String,
     /// Whether execution succeeded
     pub success: bool,
     /// Output from the step (if successful)
@@ -40,11 +55,259 @@ pub struct StepResult {
     pub output: Option<String>,
     /// Error message (if failed)
     pub error: Option<String>,
+    /// Model that served the step (run_gemini steps only)
+    pub model: Option<String>,
+    /// Tokens the step used, by model (run_gemini steps only; empty if unreported)
+    pub usage: UsageByModel,
+}
+
+/// How a step ended, as sent to API clients
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum StepOutcome {
+    /// The step succeeded
+    Success {
+        /// Output from the step
+        output: String,
+    },
+    /// The step failed
+    Error {
+        /// Error message describing the failure
+        error: String,
+    },
+}
+
+/// `StepResult` as it appears in API responses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepResultJson {
+    /// Step ID
+    pub step_id: String,
+    /// Step number (1, 2, 3, etc.)
+    pub step_number: u32,
+    /// Task type of the step
+    pub task: String,
+    /// Whether the step succeeded, with its output or error
+    #[serde(flatten)]
+    pub outcome: StepOutcome,
+    /// Model that served the step (run_gemini steps only)
+    pub model: Option<String>,
+    /// Tokens the step used, by model
+    #[serde(default)]
+    pub usage: UsageByModel,
+}
+
+impl From<StepResult> for StepResultJson {
+    fn from(result: StepResult) -> Self {
+        let outcome = if result.success {
+            StepOutcome::Success {
+                output: result.output.unwrap_or_default(),
+            }
+        } else {
+            StepOutcome::Error {
+                error: result.error.unwrap_or_default(),
+            }
+        };
+        Self {
+            step_id: result.step_id,
+            step_number: result.step_number,
+            task: result.task,
+            outcome,
+            model: result.model,
+            usage: result.usage,
+        }
+    }
+}
+
+/// Either `StepResult` JSON shape, for reading it back
+///
+/// Summaries stored before the `status` tag was introduced have `success`,
+/// `output` and `error` fields instead; both shapes still deserialize.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredStepResult {
+    /// The current, `status`-tagged shape
+    Tagged(StepResultJson),
+    /// The earlier shape with a `success` flag
+    Flagged {
+        step_id: String,
+        step_number: u32,
+        task: String,
+        success: bool,
+        output: Option<String>,
+        error: Option<String>,
+        model: Option<String>,
+        #[serde(default)]
+        usage: UsageByModel,
+    },
+}
+
+impl From<StoredStepResult> for StepResult {
+    fn from(stored: StoredStepResult) -> Self {
+        match stored {
+            StoredStepResult::Tagged(json) => {
+                let (success, output, error) = match json.outcome {
+                    StepOutcome::Success { output } => (true, Some(output), None),
+                    StepOutcome::Error { error } => (false, None, Some(error)),
+                };
+                StepResult {
+                    step_id: json.step_id,
+                    step_number: json.step_number,
+                    task: json.task,
+                    success,
+                    output,
+                    error,
+                    model: json.model,
+                    usage: json.usage,
+                }
+            }
+            StoredStepResult::Flagged {
+                step_id,
+                step_number,
+                task,
+                success,
+                output,
+                error,
+                model,
+                usage,
+            } => StepResult {
+                step_id,
+                step_number,
+                task,
+                success,
+                output,
+                error,
+                model,
+                usage,
+            },
+        }
+    }
 }
 
 /// Type alias for execution results
 pub type ExecutionResult = Result<Vec<StepResult>, AppError>;
 
+/// Outcome of one execution, in the single shape every caller reports
+///
+/// Built once when a run's steps have finished. The `execution_summary` SSE
+/// event is derived from it, and it is what gets stored for
+/// `GET /api/orchestrate/:execution_id/results`, so a streamed run and its
+/// stored results can't disagree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionSummary {
+    /// ID of the execution (its audit entry ID)
+    pub execution_id: String,
+    /// Short hash of the goal
+    pub goal_hash: String,
+    /// Short hash of the executed plan
+    pub plan_hash: String,
+    /// Number of steps that ran
+    pub total_steps: usize,
+    /// Number of steps that succeeded
+    pub successful: usize,
+    /// Number of steps that failed
+    pub failed: usize,
+    /// Wall-clock time of the whole run (planning included), in milliseconds
+    pub duration_ms: u64,
+    /// Tokens used by all steps (sum of `usage_by_model`)
+    pub token_usage: TokenUsage,
+    /// Tokens used, by model
+    pub usage_by_model: UsageByModel,
+    /// Every step's result, in step order
+    pub results: Vec<StepResult>,
+}
+
+impl ExecutionSummary {
+    /// Summarize the results of a finished run
+    pub fn new(
+        execution_id: String,
+        goal_hash: String,
+        plan_hash: String,
+        results: Vec<StepResult>,
+        elapsed: Duration,
+    ) -> Self {
+        let (token_usage, usage_by_model) = aggregate_usage(results.iter().map(|r| &r.usage));
+        let failed = results.iter().filter(|r| !r.success).count();
+        Self {
+            execution_id,
+            goal_hash,
+            plan_hash,
+            total_steps: results.len(),
+            successful: results.len() - failed,
+            failed,
+            duration_ms: elapsed.as_millis() as u64,
+            token_usage,
+            usage_by_model,
+            results,
+        }
+    }
+}
+
+/// Step outputs retained from a run, keyed like the context (e.g. "step_1.output")
+pub type StepOutputs = HashMap<String, String>;
+
+/// Point to restart a previously executed plan from
+#[derive(Debug, Clone)]
+pub struct RunFrom {
+    /// Step the run starts at
+    step_id: String,
+    /// Outputs of the earlier run
+    outputs: StepOutputs,
+}
+
+impl RunFrom {
+    /// Check that `plan` can be restarted at `step_id` using `outputs`
+    ///
+    /// The step and everything downstream of it run again; every other step
+    /// keeps its output from `outputs`.
+    ///
+    /// # Returns
+    /// * `Ok(RunFrom)` - If the step exists and all its dependencies have an output
+    /// * `Err(AppError::FileNotFound)` - If the plan has no such step
+    /// * `Err(AppError::InvalidPlan)` - Naming the dependencies without an output
+    pub fn new(plan: &Plan, step_id: &str, outputs: StepOutputs) -> Result<Self, AppError> {
+        let expanded = expand_for_each(plan)
+            .map_err(|e| AppError::InvalidPlan(format!("Plan validation failed: {}", e)))?;
+        let step = expanded
+            .steps
+            .iter()
+            .find(|step| step.id == step_id)
+            .ok_or_else(|| {
+                AppError::FileNotFound(format!("Step not found in plan: {}", step_id))
+            })?;
+
+        let missing: Vec<&str> = step
+            .dependencies
+            .iter()
+            .filter(|dep| !outputs.contains_key(&format!("{}{}", dep, STEP_OUTPUT_SUFFIX)))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            return Err(AppError::InvalidPlan(format!(
+                "Cannot run from step '{}': no stored output for dependencies: {}",
+                step_id,
+                missing.join(", ")
+            )));
+        }
+
+        Ok(Self {
+            step_id: step_id.to_string(),
+            outputs,
+        })
+    }
+
+    /// The start step and every step downstream of it
+    fn rerun_steps(&self, plan: &Plan) -> HashSet<String> {
+        let mut rerun = HashSet::new();
+        let mut pending = vec![self.step_id.clone()];
+        while let Some(step_id) = pending.pop() {
+            if rerun.insert(step_id.clone()) {
+                pending.extend(find_dependents(plan, &step_id));
+            }
+        }
+        rerun
+    }
+}
+
 /// Execute a plan and return results
 ///
 /// This function takes a Plan and executes it step by step, handling
@@ -60,6 +323,7 @@ pub type ExecutionResult = Result<Vec<StepResult>, AppError>;
 /// # Returns
 /// * `Ok(Vec<StepResult>)` - Results from each step
 /// * `Err(AppError)` - If execution fails or times out
+#[allow(dead_code)] // Handlers use execute_plan_with_config with the active config
 pub async fn execute_plan(plan: &Plan, app_state: &Arc<RwLock<AppState>>) -> ExecutionResult {
     let config = OrchestratorConfig::default();
     execute_plan_with_config(plan, app_state, &config).await
@@ -95,10 +359,22 @@ async fn extract_step_results_from_context(plan: &Plan, context: &Context) -> Ve
         // Try to get output from context
         let output: Option<String> = context.get(&output_key).await;
 
+        use crate::orchestrator::constants::STEP_MODEL_SUFFIX;
+        let model: Option<String> = context
+            .get(&format!("{}{}", step.id, STEP_MODEL_SUFFIX))
+            .await;
+
+        use crate::orchestrator::constants::STEP_USAGE_SUFFIX;
+        let usage: UsageByModel = context
+            .get(&format!("{}{}", step.id, STEP_USAGE_SUFFIX))
+            .await
+            .unwrap_or_default();
+
         let success = output.is_some();
         results.push(StepResult {
             step_id: step.id.clone(),
             step_number,
+            task: step.task.clone(),
             success,
             output: output.clone(),
             error: if success {
@@ -109,6 +385,8 @@ async fn extract_step_results_from_context(plan: &Plan, context: &Context) -> Ve
                     step_number, step.id
                 ))
             },
+            model,
+            usage,
         });
     }
 
@@ -124,25 +402,136 @@ pub async fn execute_plan_with_config(
     app_state: &Arc<RwLock<AppState>>,
     config: &OrchestratorConfig,
 ) -> ExecutionResult {
+    // Snapshot the working directory now; later changes don't affect this run
+    let working_dir = app_state
+        .read()
+        .await
+        .working_directory()
+        .map(|dir| dir.to_string());
+    execute_plan_in_working_dir(plan, app_state, config, working_dir).await
+}
+
+/// Execute a plan bound to an already-snapshotted working directory
+///
+/// File-writing steps use `working_dir` even if another client calls
+/// `set_working_directory` during the run. Plans with create_file steps are
+/// rejected up front if neither it nor an absolute `output_dir` is set.
+pub async fn execute_plan_in_working_dir(
+    plan: &Plan,
+    app_state: &Arc<RwLock<AppState>>,
+    config: &OrchestratorConfig,
+    working_dir: Option<String>,
+) -> ExecutionResult {
+    let mut outputs = StepOutputs::new();
+    execute_plan_retaining_outputs(
+        plan,
+        app_state,
+        config,
+        working_dir,
+        None,
+        &mut outputs,
+        None,
+    )
+    .await
+}
+
+/// Execute a plan, optionally from a restart point, recording its step outputs
+///
+/// Works like `execute_plan_in_working_dir`. Every step output present when
+/// the run stops is copied into `outputs`, also when a step fails, so a later
+/// run can restart from a step via `RunFrom`. With `progress` set, steps that
+/// can report partial output while running (`run_gemini`) send it there.
+pub async fn execute_plan_retaining_outputs(
+    plan: &Plan,
+    app_state: &Arc<RwLock<AppState>>,
+    config: &OrchestratorConfig,
+    working_dir: Option<String>,
+    run_from: Option<RunFrom>,
+    outputs: &mut StepOutputs,
+    progress: Option<StepProgressSender>,
+) -> ExecutionResult {
+    check_write_target(plan, config, working_dir.as_deref())?;
+
     let plan_timeout = Duration::from_secs(config.plan_timeout_secs);
 
     // Clone plan only once here, before the timeout wrapper
     let plan_clone = plan.clone();
-    timeout(plan_timeout, execute_plan_inner(plan_clone, app_state))
-        .await
-        .map_err(|_| {
-            AppError::Timeout(format!(
-                "Plan execution timed out after {} seconds",
-                plan_timeout.as_secs()
-            ))
-        })?
+    timeout(
+        plan_timeout,
+        execute_plan_inner(
+            plan_clone,
+            app_state,
+            config,
+            working_dir,
+            run_from,
+            outputs,
+            progress,
+        ),
+    )
+    .await
+    .map_err(|_| {
+        AppError::Timeout(format!(
+            "Plan execution timed out after {} seconds",
+            plan_timeout.as_secs()
+        ))
+    })?
+}
+
+/// Fail fast if the plan writes files but has nowhere to write them
+///
+/// create_file filenames are always relative, so they need either a working
+/// directory or an absolute `output_dir`. Without this check the run would
+/// only fail when the first create_file step executes.
+///
+/// # Returns
+/// * `Ok(())` - If the plan has no create_file steps or a target is available
+/// * `Err(AppError::InvalidPath)` - Naming the first create_file step and how to fix it
+pub fn check_write_target(
+    plan: &Plan,
+    config: &OrchestratorConfig,
+    working_dir: Option<&str>,
+) -> Result<(), AppError> {
+    let has_target = working_dir.is_some()
+        || config
+            .output_dir
+            .as_deref()
+            .is_some_and(|dir| std::path::Path::new(dir).is_absolute());
+    if has_target {
+        return Ok(());
+    }
+
+    let writer = plan.steps.iter().find(|step| {
+        step.task == "create_file"
+            || step
+                .params
+                .template
+                .as_ref()
+                .is_some_and(|template| template.task == "create_file")
+    });
+    match writer {
+        Some(step) => Err(AppError::InvalidPath(format!(
+            "Step '{}' writes a file, but no working directory is set and no absolute \
+             output_dir is configured. Set one via POST /api/files/working-directory \
+             or configure output_dir via POST /api/config",
+            step.id
+        ))),
+        None => Ok(()),
+    }
 }
 
 /// Inner implementation of plan execution using graph-flow
 ///
 /// This function uses graph-flow to execute the plan with parallel DAG support.
 /// Graph-flow handles parallel execution, fail-fast error handling, and dependency resolution.
-async fn execute_plan_inner(plan: Plan, app_state: &Arc<RwLock<AppState>>) -> ExecutionResult {
+async fn execute_plan_inner(
+    plan: Plan,
+    app_state: &Arc<RwLock<AppState>>,
+    config: &OrchestratorConfig,
+    working_dir: Option<String>,
+    run_from: Option<RunFrom>,
+    outputs: &mut StepOutputs,
+    progress: Option<StepProgressSender>,
+) -> ExecutionResult {
     // Generate unique session ID for tracing
     let session_id = Uuid::new_v4().to_string();
 
@@ -160,13 +549,7 @@ async fn execute_plan_inner(plan: Plan, app_state: &Arc<RwLock<AppState>>) -> Ex
     let _enter = span.enter();
 
     // Build graph from plan
-    let graph = build_graph_from_plan(plan.clone(), app_state.clone())?;
-
-    // Get working directory from app state
-    let working_dir = {
-        let state_read = app_state.read().await;
-        state_read.working_directory().cloned()
-    };
+    let graph = build_graph_from_plan_with_config(plan.clone(), app_state.clone(), config)?;
 
     // Create session storage (in-memory for stateless API)
     // TODO(Improvement 7): Support persistent session storage for long-running workflows
@@ -175,22 +558,53 @@ async fn execute_plan_inner(plan: Plan, app_state: &Arc<RwLock<AppState>>) -> Ex
     // Create FlowRunner
     let runner = FlowRunner::new(graph, session_storage.clone());
 
-    // Find the first task (step with no dependencies, or first step if all have dependencies)
+    // Find the first task (step with no dependencies, or first step if all have dependencies).
+    // The graph holds expanded for_each sub-steps, so look for the start there.
     use crate::orchestrator::plan_utils::find_start_step_id;
-    let first_task_id = find_start_step_id(&plan).ok_or_else(|| {
-        AppError::Internal(anyhow!(
-            "Plan has no steps (this should not happen after validation)"
-        ))
-    })?;
+    let expanded_plan = expand_for_each(&plan)
+        .map_err(|e| AppError::InvalidPlan(format!("Plan validation failed: {}", e)))?;
+    let first_task_id = match run_from.as_ref() {
+        Some(run_from) => run_from.step_id.as_str(),
+        None => find_start_step_id(&expanded_plan).ok_or_else(|| {
+            AppError::Internal(anyhow!(
+                "Plan has no steps (this should not happen after validation)"
+            ))
+        })?,
+    };
 
     // Create session starting from first task
     let session = Session::new_from_task(session_id.clone(), first_task_id);
 
-    // Set working directory in context
+    // When restarting, steps that don't run again keep their earlier outputs
+    if let Some(run_from) = run_from.as_ref() {
+        let rerun = run_from.rerun_steps(&expanded_plan);
+        for (key, value) in &run_from.outputs {
+            let step_id = key.rsplit_once('.').map_or(key.as_str(), |(id, _)| id);
+            if !rerun.contains(step_id) {
+                session.context.set(key, value.clone()).await;
+            }
+        }
+    }
+
+    // Bind the run to the snapshotted working directory
+    use crate::orchestrator::constants::{WORKING_DIR_BOUND_KEY, WORKING_DIR_KEY};
     if let Some(wd) = working_dir {
-        use crate::orchestrator::constants::WORKING_DIR_KEY;
         session.context.set(WORKING_DIR_KEY, wd).await;
     }
+    session.context.set(WORKING_DIR_BOUND_KEY, true).await;
+
+    // Let tasks find the progress channel; unregistered when the run ends
+    let _progress_registration = match progress {
+        Some(sender) => {
+            use crate::orchestrator::constants::STEP_PROGRESS_KEY;
+            session
+                .context
+                .set(STEP_PROGRESS_KEY, session_id.clone())
+                .await;
+            Some(step_progress::register(&session_id, sender))
+        }
+        None => None,
+    };
 
     // Save session
     session_storage
@@ -198,6 +612,13 @@ async fn execute_plan_inner(plan: Plan, app_state: &Arc<RwLock<AppState>>) -> Ex
         .await
         .map_err(|e| AppError::Internal(anyhow!("Failed to save session: {}", e)))?;
 
+    // Optionally mirror step outputs to disk as they complete (debugging aid)
+    let mut dumper = config
+        .dump_step_outputs_dir
+        .as_deref()
+        .map(|dir| StepOutputDumper::new(dir, &session_id));
+    let dump_step_ids: Vec<&str> = expanded_plan.steps.iter().map(|s| s.id.as_str()).collect();
+
     let start_time = std::time::Instant::now();
     tracing::info!(
         session_id = %session_id,
@@ -207,100 +628,146 @@ async fn execute_plan_inner(plan: Plan, app_state: &Arc<RwLock<AppState>>) -> Ex
     );
 
     // Execute until completion
-    loop {
-        let execution_result = runner.run(&session_id).await.map_err(convert_graph_error)?;
-
-        tracing::info!(
-            session_id = %session_id,
-            status = ?execution_result.status,
-            elapsed_secs = start_time.elapsed().as_secs_f64(),
-            "Graph execution status update"
-        );
+    let run_result: Result<(), AppError> = async {
+        loop {
+            // Stop before the next step once the steps so far used up the token budget
+            if let Some(max_tokens) = config.max_actual_tokens {
+                if let Ok(Some(session)) = session_storage.get(&session_id).await {
+                    let used_tokens = used_tokens(&expanded_plan, &session.context).await;
+                    if used_tokens > max_tokens {
+                        tracing::warn!(
+                            session_id = %session_id,
+                            used_tokens = used_tokens,
+                            max_tokens = max_tokens,
+                            "Token budget exceeded, aborting the remaining steps"
+                        );
+                        let completed = extract_step_results_from_context(&plan, &session.context)
+                            .await
+                            .into_iter()
+                            .filter(|result| result.success)
+                            .collect();
+                        return Err(AppError::BudgetExceeded {
+                            used_tokens,
+                            max_tokens,
+                            completed,
+                        });
+                    }
+                }
+            }
 
-        match execution_result.status {
-            ExecutionStatus::Completed => {
-                let elapsed = start_time.elapsed();
-                tracing::info!(
-                    session_id = %session_id,
-                    total_steps = plan.steps.len(),
-                    elapsed_secs = elapsed.as_secs_f64(),
-                    "Graph execution completed successfully"
-                );
-                break;
+            let execution_result = runner.run(&session_id).await;
+            if let Some(dumper) = dumper.as_mut() {
+                // Dump before propagating errors so a failed run keeps its partial outputs
+                if let Ok(Some(session)) = session_storage.get(&session_id).await {
+                    dumper
+                        .dump_completed(dump_step_ids.iter().copied(), &session.context)
+                        .await;
+                }
             }
-            ExecutionStatus::Paused {
-                next_task_id,
-                reason,
-            } => {
-                // If paused with "No outgoing edge found", it means the current task is complete
-                // and there are no more tasks. Check if all tasks have outputs in the context.
-                if reason.contains("No outgoing edge found") {
-                    // Get current session to check if all tasks are complete
-                    let session = session_storage
-                        .get(&session_id)
-                        .await
-                        .map_err(|e| AppError::Internal(anyhow!("Failed to get session: {}", e)))?
-                        .ok_or_else(|| {
-                            AppError::Internal(anyhow!(
-                                "Session '{}' not found during execution",
-                                session_id
-                            ))
-                        })?;
-
-                    // Check if all tasks in the plan have outputs (indicating they've been executed)
-                    use crate::orchestrator::constants::STEP_OUTPUT_SUFFIX;
-                    let mut all_complete = true;
-                    for step in &plan.steps {
-                        let output_key = format!("{}{}", step.id, STEP_OUTPUT_SUFFIX);
-                        if session.context.get::<String>(&output_key).await.is_none() {
-                            all_complete = false;
-                            break;
+            let execution_result = execution_result.map_err(convert_graph_error)?;
+
+            tracing::info!(
+                session_id = %session_id,
+                status = ?execution_result.status,
+                elapsed_secs = start_time.elapsed().as_secs_f64(),
+                "Graph execution status update"
+            );
+
+            match execution_result.status {
+                ExecutionStatus::Completed => {
+                    let elapsed = start_time.elapsed();
+                    tracing::info!(
+                        session_id = %session_id,
+                        total_steps = plan.steps.len(),
+                        elapsed_secs = elapsed.as_secs_f64(),
+                        "Graph execution completed successfully"
+                    );
+                    break;
+                }
+                ExecutionStatus::Paused {
+                    next_task_id,
+                    reason,
+                } => {
+                    // If paused with "No outgoing edge found", it means the current task is complete
+                    // and there are no more tasks. Check if all tasks have outputs in the context.
+                    if reason.contains("No outgoing edge found") {
+                        // Get current session to check if all tasks are complete
+                        let session = session_storage
+                            .get(&session_id)
+                            .await
+                            .map_err(|e| AppError::Internal(anyhow!("Failed to get session: {}", e)))?
+                            .ok_or_else(|| {
+                                AppError::Internal(anyhow!(
+                                    "Session '{}' not found during execution",
+                                    session_id
+                                ))
+                            })?;
+
+                        // Check if all tasks in the plan have outputs (indicating they've been executed)
+                        use crate::orchestrator::constants::STEP_OUTPUT_SUFFIX;
+                        let mut all_complete = true;
+                        for step in &plan.steps {
+                            let output_key = format!("{}{}", step.id, STEP_OUTPUT_SUFFIX);
+                            if session.context.get::<String>(&output_key).await.is_none() {
+                                all_complete = false;
+                                break;
+                            }
                         }
-                    }
 
-                    if all_complete {
-                        // All tasks are complete, treat as successful completion
-                        let elapsed = start_time.elapsed();
-                        tracing::info!(
-                            session_id = %session_id,
-                            total_steps = plan.steps.len(),
-                            elapsed_secs = elapsed.as_secs_f64(),
-                            "All tasks completed (no outgoing edges means graph is complete)"
-                        );
-                        break;
+                        if all_complete {
+                            // All tasks are complete, treat as successful completion
+                            let elapsed = start_time.elapsed();
+                            tracing::info!(
+                                session_id = %session_id,
+                                total_steps = plan.steps.len(),
+                                elapsed_secs = elapsed.as_secs_f64(),
+                                "All tasks completed (no outgoing edges means graph is complete)"
+                            );
+                            break;
+                        } else {
+                            // Not all tasks complete yet, but we're stuck. This shouldn't happen
+                            // but if it does, log and break to avoid infinite loop
+                            tracing::warn!(
+                                session_id = %session_id,
+                                next_task_id = %next_task_id,
+                                reason = %reason,
+                                "Graph paused with no outgoing edges but not all tasks complete - treating as completion"
+                            );
+                            break;
+                        }
                     } else {
-                        // Not all tasks complete yet, but we're stuck. This shouldn't happen
-                        // but if it does, log and break to avoid infinite loop
-                        tracing::warn!(
-                            session_id = %session_id,
-                            next_task_id = %next_task_id,
-                            reason = %reason,
-                            "Graph paused with no outgoing edges but not all tasks complete - treating as completion"
-                        );
-                        break;
+                        // Normal pause, continue to next task
+                        continue;
                     }
-                } else {
-                    // Normal pause, continue to next task
+                }
+                ExecutionStatus::WaitingForInput => {
+                    // Running again would only ask again; no plan task takes
+                    // input yet, so there is no provider and the run fails
+                    supply_input(None, session_storage.as_ref(), &session_id).await?;
                     continue;
                 }
-            }
-            ExecutionStatus::WaitingForInput => {
-                // This shouldn't happen in our tasks, but continue anyway
-                continue;
-            }
-            ExecutionStatus::Error(err) => {
-                tracing::error!(
-                    session_id = %session_id,
-                    error = %err,
-                    "Graph execution failed"
-                );
-                return Err(AppError::PlanExecutionFailed(format!(
-                    "Plan execution failed: {}",
-                    err
-                )));
+                ExecutionStatus::Error(err) => {
+                    tracing::error!(
+                        session_id = %session_id,
+                        error = %err,
+                        "Graph execution failed"
+                    );
+                    return Err(AppError::PlanExecutionFailed(format!(
+                        "Plan execution failed: {}",
+                        err
+                    )));
+                }
             }
         }
+        Ok(())
+    }
+    .await;
+
+    // Keep every output produced so far, also when a step failed
+    if let Ok(Some(session)) = session_storage.get(&session_id).await {
+        retain_step_outputs(&expanded_plan, &session.context, outputs).await;
     }
+    run_result?;
 
     // Extract results from final session context
     let final_session = session_storage
@@ -333,6 +800,35 @@ async fn execute_plan_inner(plan: Plan, app_state: &Arc<RwLock<AppState>>) -> Ex
     Ok(results)
 }
 
+/// Total tokens the steps of `plan` have used so far, across all models
+async fn used_tokens(plan: &Plan, context: &Context) -> u64 {
+    use crate::orchestrator::constants::STEP_USAGE_SUFFIX;
+    let mut used = 0;
+    for step in &plan.steps {
+        let usage: Option<UsageByModel> = context
+            .get(&format!("{}{}", step.id, STEP_USAGE_SUFFIX))
+            .await;
+        used += usage.map_or(0, |usage| {
+            usage.values().map(|tokens| tokens.total_tokens).sum()
+        });
+    }
+    used
+}
+
+/// Copy the outputs of every step in `plan` found in `context` into `outputs`
+///
+/// Includes named outputs such as `create_file`'s `metadata` (see `task_outputs`).
+async fn retain_step_outputs(plan: &Plan, context: &Context, outputs: &mut StepOutputs) {
+    for step in &plan.steps {
+        for name in task_outputs(&step.task) {
+            let key = format!("{}.{}", step.id, name);
+            if let Some(value) = context.get::<String>(&key).await {
+                outputs.insert(key, value);
+            }
+        }
+    }
+}
+
 /// Convert graph-flow error to AppError with granular error types
 fn convert_graph_error(e: graph_flow::GraphError) -> AppError {
     match e {
@@ -367,6 +863,7 @@ fn convert_graph_error(e: graph_flow::GraphError) -> AppError {
 mod tests {
     use super::*;
     use crate::orchestrator::plan_types::{Plan, Step, StepParams};
+    use crate::services::working_dir::{WorkingDir, WorkingDirRoots};
     use crate::state::AppState;
     use std::sync::Arc;
     use tokio::sync::RwLock;
@@ -375,6 +872,73 @@ mod tests {
         Arc::new(RwLock::new(AppState::new()))
     }
 
+    #[test]
+    fn test_step_result_json_is_tagged_by_status() {
+        let succeeded = StepResult {
+            step_id: "step_1".to_string(),
+            step_number: 1,
+            task: "ping".to_string(),
+            success: true,
+            output: Some("pong".to_string()),
+            error: None,
+            model: None,
+            usage: UsageByModel::new(),
+        };
+        let failed = StepResult {
+            step_id: "step_2".to_string(),
+            step_number: 2,
+            task: "create_file".to_string(),
+            success: false,
+            output: None,
+            error: Some("disk full".to_string()),
+            model: None,
+            usage: UsageByModel::new(),
+        };
+
+        assert_eq!(
+            serde_json::to_value(&succeeded).unwrap(),
+            serde_json::json!({
+                "step_id": "step_1",
+                "step_number": 1,
+                "task": "ping",
+                "status": "success",
+                "output": "pong",
+                "model": null,
+                "usage": {}
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(&failed).unwrap(),
+            serde_json::json!({
+                "step_id": "step_2",
+                "step_number": 2,
+                "task": "create_file",
+                "status": "error",
+                "error": "disk full",
+                "model": null,
+                "usage": {}
+            })
+        );
+
+        // Both shapes read back, as do results stored with a `success` flag
+        for result in [&succeeded, &failed] {
+            let json = serde_json::to_string(result).unwrap();
+            assert_eq!(&serde_json::from_str::<StepResult>(&json).unwrap(), result);
+        }
+        let stored: StepResult = serde_json::from_value(serde_json::json!({
+            "step_id": "step_2",
+            "step_number": 2,
+            "task": "create_file",
+            "success": false,
+            "output": null,
+            "error": "disk full",
+            "model": null,
+            "usage": {}
+        }))
+        .unwrap();
+        assert_eq!(stored, failed);
+    }
+
     // Note: build_tasks tests removed - task building is now handled by plan_to_graph.rs
     // which has its own comprehensive test suite
 
@@ -482,9 +1046,12 @@ mod tests {
         let result = StepResult {
             step_id: "step_1".to_string(),
             step_number: 1,
+            task: "run_gemini".to_string(),
             success: true,
             output: Some("test output".to_string()),
             error: None,
+            model: None,
+            usage: UsageByModel::new(),
         };
 
         assert_eq!(result.step_id, "step_1");
@@ -500,9 +1067,12 @@ mod tests {
         let result = StepResult {
             step_id: "step_1".to_string(),
             step_number: 1,
+            task: "run_gemini".to_string(),
             success: false,
             output: None,
             error: Some("test error".to_string()),
+            model: None,
+            usage: UsageByModel::new(),
         };
 
         assert_eq!(result.step_id, "step_1");
@@ -512,6 +1082,64 @@ mod tests {
         assert_eq!(result.error, Some("test error".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_extract_step_results_carries_task() {
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                Step {
+                    id: "step_1".to_string(),
+                    task: "run_gemini".to_string(),
+                    params: StepParams {
+                        prompt: Some("Write something".to_string()),
+                        ..Default::default()
+                    },
+                    dependencies: vec![],
+                },
+                Step {
+                    id: "step_2".to_string(),
+                    task: "create_file".to_string(),
+                    params: StepParams {
+                        filename: Some("out.txt".to_string()),
+                        content_from: Some("step_1.output".to_string()),
+                        ..Default::default()
+                    },
+                    dependencies: vec!["step_1".to_string()],
+                },
+            ],
+        };
+
+        let context = Context::new();
+        context.set("step_1.output", "generated".to_string()).await;
+        context
+            .set("step_1.model", "gemini-2.5-flash".to_string())
+            .await;
+        let usage: UsageByModel = [(
+            "gemini-2.5-flash".to_string(),
+            crate::orchestrator::token_usage::TokenUsage {
+                prompt_tokens: 10,
+                output_tokens: 5,
+                total_tokens: 15,
+            },
+        )]
+        .into();
+        context.set("step_1.usage", usage.clone()).await;
+
+        let results = extract_step_results_from_context(&plan, &context).await;
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].task, "run_gemini");
+        assert!(results[0].success);
+        // The model that actually served the step is recorded
+        assert_eq!(results[0].model.as_deref(), Some("gemini-2.5-flash"));
+        assert_eq!(results[0].usage, usage);
+        // Failed steps still report their task
+        assert_eq!(results[1].task, "create_file");
+        assert!(!results[1].success);
+        assert_eq!(results[1].model, None);
+        assert!(results[1].usage.is_empty());
+    }
+
     /// Test 2-step sequential plan (happy path)
     ///
     /// This test verifies:
@@ -562,7 +1190,9 @@ mod tests {
         let state = create_test_state();
         {
             let mut state_write = state.write().await;
-            state_write.set_working_directory(Some(work_dir));
+            state_write.set_working_directory(Some(
+                WorkingDir::new(&work_dir, &WorkingDirRoots::default()).unwrap(),
+            ));
         }
 
         // Test that graph building works for sequential plan
@@ -639,7 +1269,9 @@ mod tests {
         let state = create_test_state();
         {
             let mut state_write = state.write().await;
-            state_write.set_working_directory(Some(work_dir));
+            state_write.set_working_directory(Some(
+                WorkingDir::new(&work_dir, &WorkingDirRoots::default()).unwrap(),
+            ));
         }
 
         // Verify graph building for parallel plan
@@ -713,7 +1345,9 @@ mod tests {
         let state = create_test_state();
         {
             let mut state_write = state.write().await;
-            state_write.set_working_directory(Some(work_dir));
+            state_write.set_working_directory(Some(
+                WorkingDir::new(&work_dir, &WorkingDirRoots::default()).unwrap(),
+            ));
         }
 
         let result = execute_plan(&plan, &state).await;
@@ -773,7 +1407,9 @@ mod tests {
         let state = create_test_state();
         {
             let mut state_write = state.write().await;
-            state_write.set_working_directory(Some(work_dir));
+            state_write.set_working_directory(Some(
+                WorkingDir::new(&work_dir, &WorkingDirRoots::default()).unwrap(),
+            ));
         }
 
         // Test that result extraction logic is structured correctly
@@ -848,7 +1484,9 @@ mod tests {
         let state = create_test_state();
         {
             let mut state_write = state.write().await;
-            state_write.set_working_directory(Some(work_dir));
+            state_write.set_working_directory(Some(
+                WorkingDir::new(&work_dir, &WorkingDirRoots::default()).unwrap(),
+            ));
         }
 
         // Path traversal should be caught at graph building time
@@ -869,4 +1507,343 @@ mod tests {
             error
         );
     }
+
+    /// Parallel ping sub-steps finish in about the longest delay, not the sum
+    #[tokio::test]
+    async fn test_execute_plan_parallel_pings_overlap() {
+        use crate::orchestrator::plan_types::StepTemplate;
+
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                Step {
+                    id: "step_1".to_string(),
+                    task: "ping".to_string(),
+                    params: StepParams {
+                        message: Some(r#"["a", "b", "c"]"#.to_string()),
+                        ..Default::default()
+                    },
+                    dependencies: vec![],
+                },
+                Step {
+                    id: "step_2".to_string(),
+                    task: "for_each".to_string(),
+                    params: StepParams {
+                        items_from: Some("step_1.output".to_string()),
+                        template: Some(Box::new(StepTemplate {
+                            task: "ping".to_string(),
+                            params: StepParams {
+                                delay_ms: Some(200),
+                                message: Some("pong {{item}}".to_string()),
+                                ..Default::default()
+                            },
+                        })),
+                        ..Default::default()
+                    },
+                    dependencies: vec!["step_1".to_string()],
+                },
+            ],
+        };
+        plan.validate().expect("ping plan should validate");
+
+        let config = OrchestratorConfig {
+            enable_ping_task: true,
+            ..OrchestratorConfig::default()
+        };
+        let start = std::time::Instant::now();
+        let results = execute_plan_with_config(&plan, &create_test_state(), &config)
+            .await
+            .expect("ping plan should execute");
+        let elapsed = start.elapsed();
+
+        assert!(
+            elapsed >= std::time::Duration::from_millis(200),
+            "pings should sleep for their delay, took {:?}",
+            elapsed
+        );
+        assert!(
+            elapsed < std::time::Duration::from_millis(500),
+            "three 200ms pings should overlap (sequential would take 600ms), took {:?}",
+            elapsed
+        );
+
+        let gathered = results
+            .iter()
+            .find(|r| r.step_id == "step_2")
+            .and_then(|r| r.output.clone())
+            .unwrap();
+        assert_eq!(gathered, r#"["pong a","pong b","pong c"]"#);
+    }
+
+    /// Ping plans validate but only run when `enable_ping_task` is on
+    #[tokio::test]
+    async fn test_execute_plan_rejects_ping_unless_enabled() {
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![Step {
+                id: "step_1".to_string(),
+                task: "ping".to_string(),
+                params: StepParams::default(),
+                dependencies: vec![],
+            }],
+        };
+        plan.validate().expect("ping plan should validate");
+
+        match execute_plan(&plan, &create_test_state()).await {
+            Err(AppError::InvalidPlan(message)) => {
+                assert!(message.contains("step_1"), "got: {}", message);
+                assert!(message.contains("ENABLE_PING_TASK"), "got: {}", message);
+            }
+            other => panic!("Expected InvalidPlan, got: {:?}", other),
+        }
+
+        let config = OrchestratorConfig {
+            enable_ping_task: true,
+            ..OrchestratorConfig::default()
+        };
+        let results = execute_plan_with_config(&plan, &create_test_state(), &config)
+            .await
+            .expect("ping plan should execute");
+        assert_eq!(results[0].output.as_deref(), Some("pong"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_plan_without_write_target_fails_early() {
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![Step {
+                id: "step_1".to_string(),
+                task: "create_file".to_string(),
+                params: StepParams {
+                    filename: Some("out.txt".to_string()),
+                    content_from: Some("missing.output".to_string()),
+                    ..Default::default()
+                },
+                dependencies: vec![],
+            }],
+        };
+
+        // No working directory and no output_dir: rejected before any step runs
+        let state = create_test_state();
+        match execute_plan(&plan, &state).await {
+            Err(AppError::InvalidPath(message)) => {
+                assert!(message.contains("step_1"), "got: {}", message);
+                assert!(message.contains("no working directory"), "got: {}", message);
+            }
+            other => panic!("Expected InvalidPath, got: {:?}", other),
+        }
+
+        // An absolute output_dir is a valid target on its own
+        use tempfile::tempdir;
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let config = OrchestratorConfig {
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            ..OrchestratorConfig::default()
+        };
+        assert!(check_write_target(&plan, &config, None).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_execute_plan_dumps_step_outputs() {
+        use tempfile::tempdir;
+
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                Step {
+                    id: "step_1".to_string(),
+                    task: "ping".to_string(),
+                    params: StepParams {
+                        message: Some("first".to_string()),
+                        ..Default::default()
+                    },
+                    dependencies: vec![],
+                },
+                Step {
+                    id: "step_2".to_string(),
+                    task: "ping".to_string(),
+                    params: StepParams {
+                        message: Some("second".to_string()),
+                        ..Default::default()
+                    },
+                    dependencies: vec!["step_1".to_string()],
+                },
+            ],
+        };
+
+        let dump_dir = tempdir().expect("Failed to create temp dir");
+        let config = OrchestratorConfig {
+            dump_step_outputs_dir: Some(dump_dir.path().to_str().unwrap().to_string()),
+            enable_ping_task: true,
+            ..OrchestratorConfig::default()
+        };
+        let results = execute_plan_with_config(&plan, &create_test_state(), &config)
+            .await
+            .expect("ping plan should execute");
+
+        // One directory per execution, one file per step
+        let executions: Vec<_> = std::fs::read_dir(dump_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(executions.len(), 1);
+        for result in &results {
+            let dumped =
+                std::fs::read_to_string(executions[0].join(format!("{}.txt", result.step_id)))
+                    .unwrap();
+            assert_eq!(Some(dumped), result.output);
+        }
+        assert_eq!(results.len(), 2);
+
+        // An unwritable dump directory doesn't fail the run
+        let blocker = dump_dir.path().join("blocker");
+        std::fs::write(&blocker, "x").unwrap();
+        let config = OrchestratorConfig {
+            dump_step_outputs_dir: Some(blocker.to_str().unwrap().to_string()),
+            ..OrchestratorConfig::default()
+        };
+        assert!(
+            execute_plan_with_config(&plan, &create_test_state(), &config)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_plan_content_from_named_metadata_output() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                Step {
+                    id: "step_1".to_string(),
+                    task: "create_file".to_string(),
+                    params: StepParams {
+                        filename: Some("notes.txt".to_string()),
+                        ..Default::default()
+                    },
+                    dependencies: vec![],
+                },
+                Step {
+                    id: "step_2".to_string(),
+                    task: "create_file".to_string(),
+                    params: StepParams {
+                        filename: Some("metadata.json".to_string()),
+                        content_from: Some("step_1.metadata".to_string()),
+                        ..Default::default()
+                    },
+                    dependencies: vec!["step_1".to_string()],
+                },
+            ],
+        };
+        assert!(plan.validate().is_ok());
+
+        let state = create_test_state();
+        state.write().await.set_working_directory(Some(
+            WorkingDir::new(
+                temp_dir.path().to_str().unwrap(),
+                &WorkingDirRoots::default(),
+            )
+            .unwrap(),
+        ));
+
+        let results = execute_plan_with_config(&plan, &state, &OrchestratorConfig::default())
+            .await
+            .expect("plan should execute");
+
+        // The path stays the default output; the file gets the metadata output
+        assert!(results[0].output.as_deref().unwrap().ends_with("notes.txt"));
+        let written = std::fs::read_to_string(temp_dir.path().join("metadata.json")).unwrap();
+        let metadata: serde_json::Value = serde_json::from_str(&written).unwrap();
+        assert_eq!(metadata["bytes_written"], 0);
+        assert!(metadata["path"].as_str().unwrap().ends_with("notes.txt"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_plan_aborts_once_actual_tokens_exceed_budget() {
+        use crate::state::{Agent, AgentType};
+        use std::os::unix::fs::PermissionsExt;
+        use tempfile::tempdir;
+
+        // Every call reports 1000 tokens, far more than its estimate
+        let temp_dir = tempdir().unwrap();
+        let script = temp_dir.path().join("gemini-mock.sh");
+        std::fs::write(
+            &script,
+            concat!(
+                "#!/bin/sh\n",
+                "echo '{\"response\": \"answer\", \"stats\": {\"models\": {\"gemini-2.5-flash\": ",
+                "{\"tokens\": {\"prompt\": 400, \"candidates\": 600, \"total\": 1000}}}}}'\n",
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let state = create_test_state();
+        let mut agent = Agent::new(
+            "gemini-1".to_string(),
+            "Gemini Mock".to_string(),
+            AgentType::Gemini,
+        );
+        agent.config.command = script.to_string_lossy().to_string();
+        agent.config.args = vec![];
+        state.write().await.add_agent(agent);
+
+        let step = |id: &str, dependencies: &[&str]| Step {
+            id: id.to_string(),
+            task: "run_gemini".to_string(),
+            params: StepParams {
+                prompt: Some(format!("prompt for {}", id)),
+                ..Default::default()
+            },
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+        };
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                step("step_1", &[]),
+                step("step_2", &["step_1"]),
+                step("step_3", &["step_2"]),
+            ],
+        };
+
+        // The second step crosses the budget, so the third never runs
+        let config = OrchestratorConfig {
+            max_actual_tokens: Some(1500),
+            ..OrchestratorConfig::default()
+        };
+        let mut outputs = StepOutputs::new();
+        let result =
+            execute_plan_retaining_outputs(&plan, &state, &config, None, None, &mut outputs, None)
+                .await;
+        match result {
+            Err(AppError::BudgetExceeded {
+                used_tokens,
+                max_tokens,
+                completed,
+            }) => {
+                assert_eq!(used_tokens, 2000);
+                assert_eq!(max_tokens, 1500);
+                let completed_ids: Vec<&str> =
+                    completed.iter().map(|r| r.step_id.as_str()).collect();
+                assert_eq!(completed_ids, vec!["step_1", "step_2"]);
+                assert_eq!(completed[0].output.as_deref(), Some("answer"));
+                assert_eq!(completed[1].usage["gemini-2.5-flash"].total_tokens, 1000);
+            }
+            other => panic!("Expected BudgetExceeded, got: {:?}", other),
+        }
+        assert!(outputs.contains_key("step_2.output"));
+        assert!(!outputs.contains_key("step_3.output"));
+
+        // With room for every step the plan completes
+        let config = OrchestratorConfig {
+            max_actual_tokens: Some(3000),
+            ..OrchestratorConfig::default()
+        };
+        let results = execute_plan_with_config(&plan, &state, &config)
+            .await
+            .expect("plan within budget should complete");
+        assert!(results.iter().all(|r| r.success));
+    }
 }
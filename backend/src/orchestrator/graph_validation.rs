@@ -0,0 +1,261 @@
+//! Build-time plan checks
+//!
+//! `validate_graph` runs every check a plan must pass before a graph can be
+//! built from it (known tasks, required parameters, safe filenames, unique
+//! IDs, existing dependencies, no cycles) and reports all the problems it
+//! finds at once. Graph building and `POST /api/plan/validate` both use it,
+//! so a plan the endpoint accepts never fails these checks at build time.
+
+use crate::orchestrator::plan_types::{FieldError, Plan, ValidationError};
+use serde::Serialize;
+
+/// What kind of problem a `GraphIssue` is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphIssueKind {
+    /// The plan has no steps
+    EmptyPlan,
+    /// Two or more steps share an ID
+    DuplicateId,
+    /// A step names a task that doesn't exist
+    UnknownTask,
+    /// A step lacks a parameter its task requires
+    MissingParam,
+    /// A parameter has an unsupported value
+    InvalidParam,
+    /// A static filename is absolute or climbs out with `..`
+    PathTraversal,
+    /// A dependency names a step that doesn't exist
+    OrphanDependency,
+    /// A `*_from` reference names a missing step, output or dependency
+    InvalidReference,
+    /// The dependencies form a cycle
+    Cycle,
+    /// Two unordered steps write the same file
+    ConflictingWrite,
+}
+
+/// One problem found in a plan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphIssue {
+    /// What kind of problem it is
+    pub kind: GraphIssueKind,
+    /// Step the problem was found in (empty for the plan as a whole)
+    pub step_id: String,
+    /// JSON pointer to the offending field (e.g. "/steps/2/params/filename")
+    pub pointer: String,
+    /// Human-readable description of the problem
+    pub message: String,
+}
+
+impl From<GraphIssue> for FieldError {
+    fn from(issue: GraphIssue) -> Self {
+        FieldError {
+            pointer: issue.pointer,
+            message: issue.message,
+        }
+    }
+}
+
+/// Check everything a plan needs before a graph is built from it
+///
+/// Config-dependent limits (prompt length, chain length, allowed commands,
+/// steps not reachable from the start step) are not checked here;
+/// `build_graph_from_plan_with_config` enforces them.
+///
+/// # Returns
+/// * `Ok(())` - The plan can be built
+/// * `Err(Vec<GraphIssue>)` - Every problem found, in plan order
+pub fn validate_graph(plan: &Plan) -> Result<(), Vec<GraphIssue>> {
+    let mut issues = Vec::new();
+
+    if plan.steps.is_empty() {
+        issues.push(GraphIssue {
+            kind: GraphIssueKind::EmptyPlan,
+            step_id: String::new(),
+            pointer: "/steps".to_string(),
+            message: "Plan has no steps".to_string(),
+        });
+    }
+
+    for (pointer, error) in plan.collect_errors() {
+        let (kind, step_id) = classify(&error);
+        issues.push(GraphIssue {
+            kind,
+            step_id: step_id.to_string(),
+            pointer,
+            message: error.to_string(),
+        });
+    }
+
+    // Static filenames are known now; filename_from is checked when it resolves
+    for (index, step) in plan.steps.iter().enumerate() {
+        if step.task != "create_file" || step.params.filename_from.is_some() {
+            continue;
+        }
+        let filename = step.params.filename.as_deref().unwrap_or("");
+        if let Some((kind, problem)) = filename_problem(filename) {
+            issues.push(GraphIssue {
+                kind,
+                step_id: step.id.clone(),
+                pointer: format!("/steps/{}/params/filename", index),
+                message: format!(
+                    "Step '{}' (create_file) has invalid filename '{}': {}",
+                    step.id, filename, problem
+                ),
+            });
+        }
+    }
+
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(issues)
+    }
+}
+
+/// What is wrong with a static create_file filename, if anything
+///
+/// Shared with `build_task`, which checks the filenames of expanded steps.
+pub(crate) fn filename_problem(filename: &str) -> Option<(GraphIssueKind, &'static str)> {
+    if filename.contains("..") || filename.starts_with('/') {
+        Some((
+            GraphIssueKind::PathTraversal,
+            "path traversal detected or absolute path",
+        ))
+    } else if filename.chars().any(|c| c.is_control()) {
+        Some((GraphIssueKind::InvalidParam, "control characters detected"))
+    } else {
+        None
+    }
+}
+
+/// The kind of a plan validation error and the step it belongs to
+fn classify(error: &ValidationError) -> (GraphIssueKind, &str) {
+    match error {
+        ValidationError::DuplicateStepId(step_id) => (GraphIssueKind::DuplicateId, step_id),
+        ValidationError::InvalidTaskName { step_id, .. } => (GraphIssueKind::UnknownTask, step_id),
+        ValidationError::MissingRequiredParam { step_id, .. } => {
+            (GraphIssueKind::MissingParam, step_id)
+        }
+        ValidationError::InvalidParamValue { step_id, .. } => {
+            (GraphIssueKind::InvalidParam, step_id)
+        }
+        ValidationError::InvalidDependency { step_id, .. } => {
+            (GraphIssueKind::OrphanDependency, step_id)
+        }
+        ValidationError::InvalidReference { step_id, .. }
+        | ValidationError::InconsistentDependency { step_id, .. }
+        | ValidationError::UnknownOutput { step_id, .. } => {
+            (GraphIssueKind::InvalidReference, step_id)
+        }
+        ValidationError::CircularDependency { step_id } => (GraphIssueKind::Cycle, step_id),
+        ValidationError::ConflictingFileWrite { step_id, .. } => {
+            (GraphIssueKind::ConflictingWrite, step_id)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(value: serde_json::Value) -> Plan {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn kinds_by_step(issues: &[GraphIssue]) -> Vec<(GraphIssueKind, &str)> {
+        issues
+            .iter()
+            .map(|issue| (issue.kind, issue.step_id.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_reports_every_problem_at_once() {
+        let plan = plan(serde_json::json!({
+            "steps": [
+                {"id": "step_1", "task": "run_gemini", "params": {"prompt": "hi"}},
+                {"id": "step_1", "task": "run_gemini", "params": {"prompt": "again"}},
+                {"id": "step_2", "task": "summon_dragon", "params": {}},
+                {"id": "step_3", "task": "run_gemini", "params": {}},
+                {"id": "step_4", "task": "create_file", "params": {"filename": "../escape.txt"}},
+                {"id": "step_5", "task": "ping", "params": {}, "dependencies": ["step_99"]}
+            ]
+        }));
+
+        let issues = validate_graph(&plan).unwrap_err();
+        let found = kinds_by_step(&issues);
+        for expected in [
+            (GraphIssueKind::DuplicateId, "step_1"),
+            (GraphIssueKind::UnknownTask, "step_2"),
+            (GraphIssueKind::MissingParam, "step_3"),
+            (GraphIssueKind::PathTraversal, "step_4"),
+            (GraphIssueKind::OrphanDependency, "step_5"),
+        ] {
+            assert!(
+                found.contains(&expected),
+                "missing {:?} in {:?}",
+                expected,
+                found
+            );
+        }
+        assert_eq!(issues.len(), 5, "{:?}", issues);
+
+        let traversal = issues
+            .iter()
+            .find(|issue| issue.kind == GraphIssueKind::PathTraversal)
+            .unwrap();
+        assert_eq!(traversal.pointer, "/steps/4/params/filename");
+        assert!(traversal.message.contains("path traversal"));
+    }
+
+    #[test]
+    fn test_cycles_are_reported_alongside_other_problems() {
+        let plan = plan(serde_json::json!({
+            "steps": [
+                {"id": "a", "task": "ping", "params": {}, "dependencies": ["b"]},
+                {"id": "b", "task": "ping", "params": {}, "dependencies": ["a"]},
+                {"id": "c", "task": "create_file", "params": {"filename": "/etc/passwd"}},
+                {"id": "d", "task": "run_gemini", "params": {}}
+            ]
+        }));
+
+        let issues = validate_graph(&plan).unwrap_err();
+        let kinds: Vec<GraphIssueKind> = issues.iter().map(|issue| issue.kind).collect();
+        assert!(kinds.contains(&GraphIssueKind::Cycle), "{:?}", issues);
+        assert!(
+            kinds.contains(&GraphIssueKind::PathTraversal),
+            "{:?}",
+            issues
+        );
+        assert!(
+            kinds.contains(&GraphIssueKind::MissingParam),
+            "{:?}",
+            issues
+        );
+
+        // Kinds serialize for API clients
+        assert_eq!(
+            serde_json::to_value(GraphIssueKind::OrphanDependency).unwrap(),
+            "orphan_dependency"
+        );
+    }
+
+    #[test]
+    fn test_valid_and_empty_plans() {
+        let valid = plan(serde_json::json!({
+            "steps": [
+                {"id": "step_1", "task": "run_gemini", "params": {"prompt": "hi"}},
+                {"id": "step_2", "task": "create_file", "params": {"filename": "out/poem.txt", "content_from": "step_1"}, "dependencies": ["step_1"]}
+            ]
+        }));
+        assert!(validate_graph(&valid).is_ok());
+
+        let issues = validate_graph(&plan(serde_json::json!({"steps": []}))).unwrap_err();
+        assert_eq!(
+            kinds_by_step(&issues),
+            vec![(GraphIssueKind::EmptyPlan, "")]
+        );
+    }
+}
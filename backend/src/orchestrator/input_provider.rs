@@ -0,0 +1,163 @@
+//! Input for paused steps
+//!
+//! A graph-flow task can stop with `NextAction::WaitForInput`, leaving the
+//! session on that task until something supplies what it asked for. None of
+//! the plan tasks do this yet, but the executor must not simply run the
+//! session again: the task would ask again and the run would spin until the
+//! plan timeout. Instead `supply_input` asks an `InputProvider` for the input,
+//! stores it under "{step_id}.input" where the task can read it on its next
+//! run, and fails the run with a clear error when there is no provider.
+
+use crate::error::AppError;
+use crate::orchestrator::constants::STEP_INPUT_SUFFIX;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use graph_flow::{Context, SessionStorage};
+
+/// Supplies input to steps that pause waiting for it
+#[async_trait]
+pub trait InputProvider: Send + Sync {
+    /// Input for the waiting step `step_id`, or None if there is none to give
+    async fn input_for(&self, step_id: &str, context: &Context) -> Option<String>;
+}
+
+/// Give the step the session is waiting on its input
+///
+/// # Returns
+/// * `Ok(())` - The input is stored; running the session again resumes the step
+/// * `Err(AppError::PlanExecutionFailed)` - If there is no provider or it gave no input
+pub async fn supply_input(
+    provider: Option<&dyn InputProvider>,
+    session_storage: &dyn SessionStorage,
+    session_id: &str,
+) -> Result<(), AppError> {
+    let session = session_storage
+        .get(session_id)
+        .await
+        .map_err(|e| AppError::Internal(anyhow!("Failed to get session: {}", e)))?
+        .ok_or_else(|| {
+            AppError::Internal(anyhow!(
+                "Session '{}' not found during execution",
+                session_id
+            ))
+        })?;
+    let step_id = session.current_task_id.clone();
+
+    let Some(provider) = provider else {
+        return Err(AppError::PlanExecutionFailed(format!(
+            "Step '{}' is waiting for input, but no input provider is configured",
+            step_id
+        )));
+    };
+    let input = provider
+        .input_for(&step_id, &session.context)
+        .await
+        .ok_or_else(|| {
+            AppError::PlanExecutionFailed(format!(
+                "Step '{}' is waiting for input, but none was provided",
+                step_id
+            ))
+        })?;
+
+    tracing::debug!(step_id = %step_id, input_len = input.len(), "Supplying input to waiting step");
+    session
+        .context
+        .set(&format!("{}{}", step_id, STEP_INPUT_SUFFIX), input)
+        .await;
+    session_storage
+        .save(session)
+        .await
+        .map_err(|e| AppError::Internal(anyhow!("Failed to save session: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::constants::STEP_OUTPUT_SUFFIX;
+    use graph_flow::{
+        ExecutionStatus, FlowRunner, GraphBuilder, InMemorySessionStorage, NextAction,
+        Result as GraphFlowResult, Session, Task, TaskResult,
+    };
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Asks for input until it has some, then outputs it
+    struct AskTask;
+
+    #[async_trait]
+    impl Task for AskTask {
+        fn id(&self) -> &str {
+            "ask"
+        }
+
+        async fn run(&self, context: Context) -> GraphFlowResult<TaskResult> {
+            let input: Option<String> = context.get(&format!("ask{}", STEP_INPUT_SUFFIX)).await;
+            match input {
+                Some(input) => {
+                    context
+                        .set(&format!("ask{}", STEP_OUTPUT_SUFFIX), input.clone())
+                        .await;
+                    Ok(TaskResult::new(Some(input), NextAction::End))
+                }
+                None => Ok(TaskResult::new(None, NextAction::WaitForInput)),
+            }
+        }
+    }
+
+    struct FixedInput(&'static str);
+
+    #[async_trait]
+    impl InputProvider for FixedInput {
+        async fn input_for(&self, _step_id: &str, _context: &Context) -> Option<String> {
+            Some(self.0.to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_waiting_step_fails_cleanly_without_provider() {
+        let graph = Arc::new(
+            GraphBuilder::new("input_test")
+                .add_task(Arc::new(AskTask))
+                .build(),
+        );
+        let storage: Arc<dyn SessionStorage> = Arc::new(InMemorySessionStorage::new());
+        let runner = FlowRunner::new(graph, storage.clone());
+        storage
+            .save(Session::new_from_task("input-session".to_string(), "ask"))
+            .await
+            .unwrap();
+
+        let status = runner.run("input-session").await.unwrap().status;
+        assert!(matches!(status, ExecutionStatus::WaitingForInput));
+
+        // No provider: a clear error straight away rather than a spinning run
+        let result = tokio::time::timeout(
+            Duration::from_secs(1),
+            supply_input(None, storage.as_ref(), "input-session"),
+        )
+        .await
+        .expect("a waiting step must not hang the run");
+        match result {
+            Err(AppError::PlanExecutionFailed(message)) => {
+                assert!(
+                    message.contains("'ask' is waiting for input"),
+                    "{}",
+                    message
+                )
+            }
+            other => panic!("expected PlanExecutionFailed, got {:?}", other),
+        }
+
+        // With a provider the step resumes with its input
+        supply_input(Some(&FixedInput("yes")), storage.as_ref(), "input-session")
+            .await
+            .unwrap();
+        runner.run("input-session").await.unwrap();
+        let session = storage.get("input-session").await.unwrap().unwrap();
+        let output: Option<String> = session
+            .context
+            .get(&format!("ask{}", STEP_OUTPUT_SUFFIX))
+            .await;
+        assert_eq!(output.as_deref(), Some("yes"));
+    }
+}
@@ -0,0 +1,128 @@
+//! JSON pointers into step outputs
+//!
+//! A create_file step's `content_from_json_path` picks one value out of a
+//! step output holding JSON, using an RFC 6901 pointer such as
+//! `/items/0/title`. Pointers come from plans (and so, usually, from the
+//! planner), so they are limited to `MAX_JSON_POINTER_DEPTH` segments and
+//! refused before any evaluation if they are deeper.
+
+use crate::orchestrator::constants::MAX_JSON_POINTER_DEPTH;
+use serde_json::Value;
+
+/// Split a JSON pointer into its unescaped segments
+///
+/// `""` refers to the whole document. Any other pointer must start with `/`;
+/// `~1` and `~0` within a segment stand for `/` and `~`.
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - The segments, outermost first
+/// * `Err(String)` - If the pointer is malformed or deeper than `MAX_JSON_POINTER_DEPTH`
+pub fn parse_json_pointer(pointer: &str) -> Result<Vec<String>, String> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(format!("JSON path '{}' must start with '/'", pointer));
+    };
+
+    // Count before splitting so an absurdly deep pointer allocates nothing
+    let depth = rest.matches('/').count() + 1;
+    if depth > MAX_JSON_POINTER_DEPTH {
+        return Err(format!(
+            "JSON path has {} segments, more than the maximum of {}",
+            depth, MAX_JSON_POINTER_DEPTH
+        ));
+    }
+
+    rest.split('/')
+        .map(|segment| {
+            if segment.replace("~0", "").replace("~1", "").contains('~') {
+                return Err(format!(
+                    "JSON path '{}' has an invalid escape in segment '{}'",
+                    pointer, segment
+                ));
+            }
+            Ok(segment.replace("~1", "/").replace("~0", "~"))
+        })
+        .collect()
+}
+
+/// Find the value `pointer` refers to within `document`
+///
+/// # Returns
+/// * `Ok(&Value)` - The value at the pointer
+/// * `Err(String)` - If the pointer is invalid, too deep or names a missing value
+pub fn resolve_json_pointer<'a>(document: &'a Value, pointer: &str) -> Result<&'a Value, String> {
+    let mut current = document;
+    for segment in parse_json_pointer(pointer)? {
+        let next = match current {
+            Value::Object(map) => map.get(&segment),
+            Value::Array(items) => segment
+                .parse::<usize>()
+                .ok()
+                .filter(|_| segment == "0" || !segment.starts_with('0'))
+                .and_then(|index| items.get(index)),
+            _ => None,
+        };
+        current = next.ok_or_else(|| {
+            format!(
+                "JSON path '{}' does not exist (no '{}' found)",
+                pointer, segment
+            )
+        })?;
+    }
+    Ok(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A pointer of `depth` segments and a document deep enough for it
+    fn nested(depth: usize) -> (String, Value) {
+        let pointer = "/a".repeat(depth);
+        let mut document = Value::from("bottom");
+        for _ in 0..depth {
+            document = serde_json::json!({ "a": document });
+        }
+        (pointer, document)
+    }
+
+    #[test]
+    fn test_resolves_fields_indices_and_escapes() {
+        let document = serde_json::json!({
+            "items": [{"title": "first"}, {"title": "second"}],
+            "a/b": {"m~n": 7}
+        });
+        assert_eq!(
+            resolve_json_pointer(&document, "/items/1/title").unwrap(),
+            "second"
+        );
+        assert_eq!(resolve_json_pointer(&document, "/a~1b/m~0n").unwrap(), 7);
+        assert_eq!(resolve_json_pointer(&document, "").unwrap(), &document);
+
+        for missing in ["/items/2", "/items/01", "/nope", "/items/0/title/x"] {
+            assert!(
+                resolve_json_pointer(&document, missing).is_err(),
+                "{}",
+                missing
+            );
+        }
+        assert!(parse_json_pointer("items").is_err());
+        assert!(parse_json_pointer("/bad~2escape").is_err());
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let (pointer, document) = nested(MAX_JSON_POINTER_DEPTH);
+        assert_eq!(resolve_json_pointer(&document, &pointer).unwrap(), "bottom");
+
+        let (pointer, document) = nested(MAX_JSON_POINTER_DEPTH + 1);
+        let error = resolve_json_pointer(&document, &pointer).unwrap_err();
+        assert!(error.contains("maximum"), "got: {}", error);
+
+        // Refused on depth alone, whatever the document holds
+        let error = parse_json_pointer(&"/".repeat(100_000)).unwrap_err();
+        assert!(error.contains("100000 segments"), "got: {}", error);
+    }
+}
@@ -8,14 +8,28 @@
 //! making it easy to refactor to a generic orchestrator in future versions.
 
 pub mod api_client;
+pub mod api_key;
+pub mod cancellation;
 pub mod config;
 pub mod constants;
+pub mod execution_limiter;
+pub mod gemini_stream;
 pub mod gemini_types;
 pub mod graph_executor;
+pub mod graph_validation;
+pub mod input_provider;
+pub mod json_pointer;
+pub mod plan_expansion;
+pub mod plan_explain;
 pub mod plan_optimizer;
 pub mod plan_to_graph;
 pub mod plan_types;
 pub mod plan_utils;
 pub mod primitives;
+pub mod response_cache;
+pub mod retry;
+pub mod step_dump;
+pub mod step_progress;
 pub mod tasks;
+pub mod token_usage;
 pub mod utils;
@@ -0,0 +1,237 @@
+//! for_each step expansion
+//!
+//! A `for_each` step runs a sub-task template once per item. With a literal
+//! `items` list the step is expanded at graph-build time into one parallel
+//! sub-step per item plus a gather step that keeps the original step ID and
+//! collects the sub-step outputs into a JSON array. Steps using `items_from`
+//! only know their items at execution time and are expanded by `ForEachTask`.
+
+use crate::orchestrator::plan_types::{
+    check_for_each_item_count, Plan, Step, StepTemplate, ValidationError,
+};
+use std::collections::HashSet;
+
+/// Task name of the generated gather step
+pub const GATHER_TASK: &str = "gather";
+
+/// Placeholder replaced with the current item
+const ITEM_PLACEHOLDER: &str = "{{item}}";
+
+/// Placeholder replaced with the current item's 0-based index
+const INDEX_PLACEHOLDER: &str = "{{index}}";
+
+/// ID of the sub-step generated for item `index` of a for_each step
+pub fn sub_step_id(parent_id: &str, index: usize) -> String {
+    format!("{}_item_{}", parent_id, index)
+}
+
+/// Build the sub-step for one item from a for_each template
+///
+/// # Arguments
+/// * `parent_id` - ID of the for_each step
+/// * `template` - Sub-task template
+/// * `index` - 0-based position of the item
+/// * `item` - The item substituted for `{{item}}`
+/// * `dependencies` - Dependencies of the generated sub-step
+pub fn instantiate_template(
+    parent_id: &str,
+    template: &StepTemplate,
+    index: usize,
+    item: &str,
+    dependencies: Vec<String>,
+) -> Step {
+    let substitute = |value: &Option<String>| {
+        value.as_ref().map(|v| {
+            v.replace(ITEM_PLACEHOLDER, item)
+                .replace(INDEX_PLACEHOLDER, &index.to_string())
+        })
+    };
+
+    let mut params = template.params.clone();
+    params.prompt = substitute(&template.params.prompt);
+    params.filename = substitute(&template.params.filename);
+    params.message = substitute(&template.params.message);
+
+    Step {
+        id: sub_step_id(parent_id, index),
+        task: template.task.clone(),
+        params,
+        dependencies,
+    }
+}
+
+/// Expand for_each steps with a literal `items` list
+///
+/// Each such step is replaced by one sub-step per item (sharing the for_each
+/// step's dependencies, so they can run in parallel) and a gather step with
+/// the original ID that depends on all of them. Steps that reference the
+/// for_each output are unchanged. `items_from` steps are left as-is.
+///
+/// # Returns
+/// * `Ok(Plan)` - The expanded plan (unchanged if there is nothing to expand)
+/// * `Err(ValidationError)` - If a generated ID collides with an existing step,
+///   or a step has more than `MAX_FOR_EACH_ITEMS` items
+pub fn expand_for_each(plan: &Plan) -> Result<Plan, ValidationError> {
+    let mut steps = Vec::with_capacity(plan.steps.len());
+
+    for step in &plan.steps {
+        let (true, Some(items), Some(template)) = (
+            step.task == "for_each",
+            &step.params.items,
+            &step.params.template,
+        ) else {
+            steps.push(step.clone());
+            continue;
+        };
+        check_for_each_item_count(&step.id, items.len())?;
+
+        let sub_steps: Vec<Step> = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                instantiate_template(&step.id, template, index, item, step.dependencies.clone())
+            })
+            .collect();
+
+        let gather = Step {
+            id: step.id.clone(),
+            task: GATHER_TASK.to_string(),
+            params: Default::default(),
+            dependencies: sub_steps.iter().map(|s| s.id.clone()).collect(),
+        };
+
+        steps.extend(sub_steps);
+        steps.push(gather);
+    }
+
+    let mut seen = HashSet::new();
+    for step in &steps {
+        if !seen.insert(step.id.as_str()) {
+            return Err(ValidationError::DuplicateStepId(step.id.clone()));
+        }
+    }
+
+    Ok(Plan {
+        version: plan.version.clone(),
+        steps,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::plan_types::StepParams;
+
+    fn for_each_step(items: Vec<&str>) -> Step {
+        Step {
+            id: "summaries".to_string(),
+            task: "for_each".to_string(),
+            params: StepParams {
+                items: Some(items.into_iter().map(str::to_string).collect()),
+                template: Some(Box::new(StepTemplate {
+                    task: "run_gemini".to_string(),
+                    params: StepParams {
+                        prompt: Some("Summarize {{item}} (#{{index}})".to_string()),
+                        ..Default::default()
+                    },
+                })),
+                ..Default::default()
+            },
+            dependencies: vec!["step_1".to_string()],
+        }
+    }
+
+    fn gemini_step(id: &str) -> Step {
+        Step {
+            id: id.to_string(),
+            task: "run_gemini".to_string(),
+            params: StepParams {
+                prompt: Some("List files".to_string()),
+                ..Default::default()
+            },
+            dependencies: vec![],
+        }
+    }
+
+    #[test]
+    fn test_expand_for_each_literal_items() {
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                gemini_step("step_1"),
+                for_each_step(vec!["a.txt", "b.txt", "c.txt"]),
+            ],
+        };
+        plan.validate().expect("for_each plan should validate");
+
+        let expanded = expand_for_each(&plan).unwrap();
+        let ids: Vec<&str> = expanded.steps.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "step_1",
+                "summaries_item_0",
+                "summaries_item_1",
+                "summaries_item_2",
+                "summaries"
+            ]
+        );
+
+        let second = &expanded.steps[2];
+        assert_eq!(second.task, "run_gemini");
+        assert_eq!(
+            second.params.prompt.as_deref(),
+            Some("Summarize b.txt (#1)")
+        );
+        // Sub-steps inherit the for_each dependencies so they run in parallel
+        assert_eq!(second.dependencies, vec!["step_1"]);
+    }
+
+    #[test]
+    fn test_expand_for_each_gather_depends_on_all_sub_steps() {
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![gemini_step("step_1"), for_each_step(vec!["x", "y"])],
+        };
+
+        let expanded = expand_for_each(&plan).unwrap();
+        let gather = expanded.steps.last().unwrap();
+        assert_eq!(gather.id, "summaries");
+        assert_eq!(gather.task, GATHER_TASK);
+        assert_eq!(
+            gather.dependencies,
+            vec!["summaries_item_0", "summaries_item_1"]
+        );
+    }
+
+    #[test]
+    fn test_expand_for_each_rejects_id_collision() {
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                gemini_step("step_1"),
+                gemini_step("summaries_item_0"),
+                for_each_step(vec!["x"]),
+            ],
+        };
+        assert!(matches!(
+            expand_for_each(&plan),
+            Err(ValidationError::DuplicateStepId(id)) if id == "summaries_item_0"
+        ));
+    }
+
+    #[test]
+    fn test_expand_for_each_rejects_too_many_items() {
+        use crate::orchestrator::constants::MAX_FOR_EACH_ITEMS;
+
+        let items = vec!["x"; MAX_FOR_EACH_ITEMS + 1];
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![gemini_step("step_1"), for_each_step(items)],
+        };
+        assert!(matches!(
+            expand_for_each(&plan),
+            Err(ValidationError::InvalidParamValue { param, .. }) if param == "items"
+        ));
+    }
+}
@@ -0,0 +1,263 @@
+//! Plan explanations
+//!
+//! Describes a plan in plain language, one numbered entry per step, so users
+//! can read what a plan will do before running it. The description is built
+//! from the plan's structure alone; no model is called.
+
+use crate::orchestrator::constants::DEFAULT_OUTPUT_NAME;
+use crate::orchestrator::plan_types::{parse_output_reference, Plan, StepParams, WriteMode};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Characters of a prompt quoted in an explanation
+const EXPLAIN_PROMPT_CHARS: usize = 80;
+
+/// What one step of a plan does
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct StepExplanation {
+    /// Position in the plan (1-indexed)
+    pub step_number: usize,
+    /// Unique identifier for the step
+    pub step_id: String,
+    /// Task type (e.g., "run_gemini", "create_file")
+    pub task: String,
+    /// IDs of the steps this step waits for
+    pub dependencies: Vec<String>,
+    /// What the step does and when it runs, in one or two sentences
+    pub description: String,
+}
+
+/// Explain every step of `plan`, in plan order
+pub fn explain_plan(plan: &Plan) -> Vec<StepExplanation> {
+    let numbers: HashMap<&str, usize> = plan
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(idx, step)| (step.id.as_str(), idx + 1))
+        .collect();
+    let step_name = |id: &str| match numbers.get(id) {
+        Some(number) => format!("step {} ({})", number, id),
+        None => format!("'{}'", id),
+    };
+
+    plan.steps
+        .iter()
+        .enumerate()
+        .map(|(idx, step)| {
+            let timing = if step.dependencies.is_empty() {
+                "Starts right away.".to_string()
+            } else {
+                let upstream: Vec<String> =
+                    step.dependencies.iter().map(|id| step_name(id)).collect();
+                format!("Runs after {}.", join_names(&upstream))
+            };
+            StepExplanation {
+                step_number: idx + 1,
+                step_id: step.id.clone(),
+                task: step.task.clone(),
+                dependencies: step.dependencies.clone(),
+                description: format!(
+                    "{}. {}",
+                    describe_action(&step.task, &step.params, &step_name),
+                    timing
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Render explanations as numbered lines, e.g.
+/// `2. [create_file] step_2: Write the output of step 1 (step_1) to 'out.txt'. Runs after step 1 (step_1).`
+pub fn render_explanation(steps: &[StepExplanation]) -> String {
+    steps
+        .iter()
+        .map(|step| {
+            format!(
+                "{}. [{}] {}: {}",
+                step.step_number, step.task, step.step_id, step.description
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Describe what a task does with its parameters (without a trailing period)
+fn describe_action(task: &str, params: &StepParams, step_name: &dyn Fn(&str) -> String) -> String {
+    let output_of = |reference: &str| {
+        let (step_id, output_name) = parse_output_reference(reference);
+        if output_name == DEFAULT_OUTPUT_NAME {
+            format!("the output of {}", step_name(step_id))
+        } else {
+            format!("the {} of {}", output_name, step_name(step_id))
+        }
+    };
+
+    match task {
+        "run_gemini" => match params.prompt.as_deref() {
+            Some(prompt) => format!("Ask Gemini: \"{}\"", shorten(prompt)),
+            None => "Ask Gemini (no prompt given)".to_string(),
+        },
+        "create_file" => {
+            let content = match (params.content_from.as_deref(), params.prompt.as_deref()) {
+                (Some(reference), _) => output_of(reference),
+                (None, Some(_)) => "the given text".to_string(),
+                (None, None) => "an empty file".to_string(),
+            };
+            let target = match (params.filename_from.as_deref(), params.filename.as_deref()) {
+                (Some(reference), _) => format!("the file named by {}", output_of(reference)),
+                (None, Some(filename)) => format!("'{}'", filename),
+                (None, None) => "a file".to_string(),
+            };
+            let append = params
+                .mode
+                .as_deref()
+                .and_then(WriteMode::parse)
+                .is_some_and(|mode| mode == WriteMode::Append);
+            if append {
+                format!("Append {} to {}", content, target)
+            } else {
+                format!("Write {} to {}", content, target)
+            }
+        }
+        "for_each" => {
+            let items = match (params.items.as_ref(), params.items_from.as_deref()) {
+                (Some(items), _) => format!("each of {} items", items.len()),
+                (None, Some(reference)) => format!("each item in {}", output_of(reference)),
+                (None, None) => "each item".to_string(),
+            };
+            match params.template.as_deref() {
+                Some(template) => {
+                    let action = describe_action(&template.task, &template.params, step_name);
+                    format!("For {}: {}", items, lowercase_first(&action))
+                }
+                None => format!("For {}, do nothing (no template given)", items),
+            }
+        }
+        "ping" => {
+            let message = params.message.as_deref().unwrap_or("pong");
+            match params.delay_ms {
+                Some(delay) if delay > 0 => {
+                    format!("Wait {}ms, then reply \"{}\"", delay, message)
+                }
+                _ => format!("Reply \"{}\"", message),
+            }
+        }
+        other => format!("Run task '{}'", other),
+    }
+}
+
+/// Join names as "a", "a and b" or "a, b and c"
+fn join_names(names: &[String]) -> String {
+    match names {
+        [] => String::new(),
+        [only] => only.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+/// Shorten a prompt to `EXPLAIN_PROMPT_CHARS`, on one line
+fn shorten(prompt: &str) -> String {
+    let flat = prompt.split_whitespace().collect::<Vec<_>>().join(" ");
+    if flat.chars().count() > EXPLAIN_PROMPT_CHARS {
+        let head: String = flat.chars().take(EXPLAIN_PROMPT_CHARS).collect();
+        format!("{}…", head)
+    } else {
+        flat
+    }
+}
+
+/// `text` with its first character lowercased
+fn lowercase_first(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(value: serde_json::Value) -> Plan {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_explanation_names_each_task_and_its_dependencies() {
+        let plan = plan(serde_json::json!({
+            "steps": [
+                {"id": "outline", "task": "run_gemini", "params": {"prompt": "Outline a story\nabout a lighthouse"}},
+                {"id": "chapter", "task": "run_gemini", "params": {"prompt": "Write chapter one"}, "dependencies": ["outline"]},
+                {"id": "notes", "task": "run_gemini", "params": {"prompt": "List lighthouse facts"}},
+                {"id": "save", "task": "create_file", "params": {"filename": "story.txt", "content_from": "chapter"}, "dependencies": ["outline", "chapter", "notes"]}
+            ]
+        }));
+
+        let steps = explain_plan(&plan);
+        assert_eq!(steps.len(), 4);
+        assert_eq!(
+            steps[0].description,
+            "Ask Gemini: \"Outline a story about a lighthouse\". Starts right away."
+        );
+        assert_eq!(
+            steps[1].description,
+            "Ask Gemini: \"Write chapter one\". Runs after step 1 (outline)."
+        );
+        assert_eq!(
+            steps[2].description,
+            "Ask Gemini: \"List lighthouse facts\". Starts right away."
+        );
+        assert_eq!(
+            steps[3].description,
+            "Write the output of step 2 (chapter) to 'story.txt'. \
+             Runs after step 1 (outline), step 2 (chapter) and step 3 (notes)."
+        );
+
+        let text = render_explanation(&steps);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4);
+        for (line, step) in lines.iter().zip(&plan.steps) {
+            assert!(line.contains(&format!("[{}] {}:", step.task, step.id)));
+            for dependency in &step.dependencies {
+                assert!(
+                    line.contains(dependency),
+                    "{} should mention {}",
+                    line,
+                    dependency
+                );
+            }
+        }
+        assert!(lines[3].starts_with("4. [create_file] save:"));
+    }
+
+    #[test]
+    fn test_explanation_describes_for_each_and_named_outputs() {
+        let plan = plan(serde_json::json!({
+            "steps": [
+                {"id": "step_1", "task": "run_gemini", "params": {"prompt": "List the chapters"}},
+                {"id": "step_2", "task": "for_each", "params": {
+                    "items_from": "step_1",
+                    "template": {"task": "run_gemini", "params": {"prompt": "Summarize {{item}}"}}
+                }, "dependencies": ["step_1"]},
+                {"id": "step_3", "task": "create_file", "params": {"filename": "chapters.txt", "content_from": "step_1"}, "dependencies": ["step_1"]},
+                {"id": "step_4", "task": "create_file", "params": {"filename": "writes.log", "content_from": "step_3.metadata", "mode": "append"}, "dependencies": ["step_3"]},
+                {"id": "step_5", "task": "ping", "params": {"delay_ms": 50}}
+            ]
+        }));
+
+        let steps = explain_plan(&plan);
+        assert_eq!(
+            steps[1].description,
+            "For each item in the output of step 1 (step_1): ask Gemini: \"Summarize {{item}}\". \
+             Runs after step 1 (step_1)."
+        );
+        assert!(steps[3]
+            .description
+            .starts_with("Append the metadata of step 3 (step_3) to 'writes.log'."));
+        assert_eq!(
+            steps[4].description,
+            "Wait 50ms, then reply \"pong\". Starts right away."
+        );
+    }
+}
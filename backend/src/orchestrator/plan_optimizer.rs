@@ -5,6 +5,9 @@
 //! - Identifying bottlenecks
 //! - Cost estimation (token usage prediction)
 
+use crate::error::AppError;
+use crate::orchestrator::config::OrchestratorConfig;
+use crate::orchestrator::constants::ESTIMATED_COST_PER_1K_TOKENS_USD;
 use crate::orchestrator::plan_types::Plan;
 use serde::Serialize;
 use std::collections::HashMap;
@@ -81,6 +84,87 @@ pub struct BottleneckAnalysis {
     pub longest_chain_length: usize,
     /// Total number of independent steps (can run in parallel)
     pub independent_steps: usize,
+    /// How serious each kind of bottleneck is, so a UI can highlight the worst
+    pub severity: BottleneckSeverity,
+}
+
+/// How serious a bottleneck is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Worth knowing about, little effect on run time
+    Low,
+    /// Noticeably limits parallelism
+    Medium,
+    /// Dominates the plan's run time
+    High,
+}
+
+/// Severity of each kind of bottleneck in a plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BottleneckSeverity {
+    /// Long sequential chain: how much of the plan has to run one step at a time
+    pub chain: Severity,
+    /// Fan-in: the most dependencies any single step waits on
+    pub fan_in: Severity,
+    /// The worst of the above
+    pub overall: Severity,
+}
+
+/// Where bottleneck severities change from one level to the next
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BottleneckThresholds {
+    /// Chains shorter than this are always low severity, however sequential the plan
+    pub min_chain_length: usize,
+    /// Share of the plan's steps on the longest chain from which it is medium severity
+    pub medium_chain_ratio: f64,
+    /// Share of the plan's steps on the longest chain from which it is high severity
+    pub high_chain_ratio: f64,
+    /// Dependencies of one step from which fan-in is medium severity (and the
+    /// step is listed in `high_dependency_steps`)
+    pub medium_fan_in: usize,
+    /// Dependencies of one step from which fan-in is high severity
+    pub high_fan_in: usize,
+}
+
+impl Default for BottleneckThresholds {
+    fn default() -> Self {
+        Self {
+            min_chain_length: 3,     // Two steps in a row are no bottleneck
+            medium_chain_ratio: 0.5, // Half the plan runs one step at a time
+            high_chain_ratio: 0.8,   // Almost nothing runs in parallel
+            medium_fan_in: 3,
+            high_fan_in: 6,
+        }
+    }
+}
+
+impl BottleneckThresholds {
+    /// Severity of a longest chain of `chain_length` in a plan of `step_count` steps
+    fn chain_severity(&self, chain_length: usize, step_count: usize) -> Severity {
+        if step_count == 0 || chain_length < self.min_chain_length {
+            return Severity::Low;
+        }
+        let ratio = chain_length as f64 / step_count as f64;
+        if ratio >= self.high_chain_ratio {
+            Severity::High
+        } else if ratio >= self.medium_chain_ratio {
+            Severity::Medium
+        } else {
+            Severity::Low
+        }
+    }
+
+    /// Severity of a step waiting on `dependencies` others
+    fn fan_in_severity(&self, dependencies: usize) -> Severity {
+        if dependencies >= self.high_fan_in {
+            Severity::High
+        } else if dependencies >= self.medium_fan_in {
+            Severity::Medium
+        } else {
+            Severity::Low
+        }
+    }
 }
 
 /// Analyze plan bottlenecks and execution characteristics
@@ -95,14 +179,26 @@ pub struct BottleneckAnalysis {
 /// * `BottleneckAnalysis` - Analysis results with high-dependency steps, chain length, and parallelization info
 #[allow(dead_code)] // Will be used when implementing optimization endpoints
 pub fn analyze_bottlenecks(plan: &Plan) -> BottleneckAnalysis {
+    analyze_bottlenecks_with_thresholds(plan, &BottleneckThresholds::default())
+}
+
+/// Analyze plan bottlenecks, rating their severity against `thresholds`
+///
+/// Same as `analyze_bottlenecks`, which uses `BottleneckThresholds::default()`.
+pub fn analyze_bottlenecks_with_thresholds(
+    plan: &Plan,
+    thresholds: &BottleneckThresholds,
+) -> BottleneckAnalysis {
     let mut high_dependency_steps = Vec::new();
     let mut independent_steps = 0;
+    let mut max_fan_in = 0;
 
     // Find steps with many dependencies
     for step in &plan.steps {
-        if step.dependencies.len() >= 3 {
+        if step.dependencies.len() >= thresholds.medium_fan_in {
             high_dependency_steps.push(step.id.clone());
         }
+        max_fan_in = max_fan_in.max(step.dependencies.len());
         if step.dependencies.is_empty() {
             independent_steps += 1;
         }
@@ -127,13 +223,84 @@ pub fn analyze_bottlenecks(plan: &Plan) -> BottleneckAnalysis {
     }
     let longest_chain_length = max_depth;
 
+    let chain = thresholds.chain_severity(longest_chain_length, plan.steps.len());
+    let fan_in = thresholds.fan_in_severity(max_fan_in);
     BottleneckAnalysis {
         high_dependency_steps,
         longest_chain_length,
         independent_steps,
+        severity: BottleneckSeverity {
+            chain,
+            fan_in,
+            overall: chain.max(fan_in),
+        },
     }
 }
 
+/// Reject plans whose longest sequential chain exceeds `max_chain_length`
+///
+/// Long chains serialize execution, so they are refused up front rather than
+/// running into the plan timeout partway through.
+///
+/// # Arguments
+/// * `bottlenecks` - Bottleneck analysis of the plan
+/// * `max_chain_length` - Maximum allowed critical path length (in steps)
+///
+/// # Returns
+/// * `Ok(())` - If the chain length is within the limit
+/// * `Err(AppError::InvalidPlan)` - If the limit is exceeded
+pub fn validate_chain_length(
+    bottlenecks: &BottleneckAnalysis,
+    max_chain_length: usize,
+) -> Result<(), AppError> {
+    if bottlenecks.longest_chain_length > max_chain_length {
+        return Err(AppError::InvalidPlan(format!(
+            "Plan's longest dependency chain has {} steps, exceeding the maximum of {}",
+            bottlenecks.longest_chain_length, max_chain_length
+        )));
+    }
+    Ok(())
+}
+
+/// Estimate the USD cost of a plan from its estimated token usage
+pub fn estimate_cost(estimated_tokens: usize) -> f64 {
+    estimated_tokens as f64 / 1000.0 * ESTIMATED_COST_PER_1K_TOKENS_USD
+}
+
+/// Validate a plan's estimated tokens and cost against the configured budget
+///
+/// # Arguments
+/// * `plan` - Plan about to be executed
+/// * `config` - Supplies `max_estimated_tokens` and `max_estimated_cost` (None = no limit)
+///
+/// # Returns
+/// * `Ok(())` - If the plan is within every configured limit
+/// * `Err(AppError::PolicyViolation)` - Naming the estimate and the limit it exceeds
+pub fn check_budget(plan: &Plan, config: &OrchestratorConfig) -> Result<(), AppError> {
+    let estimated_tokens = estimate_token_usage(plan);
+    if let Some(max_tokens) = config.max_estimated_tokens {
+        if estimated_tokens > max_tokens {
+            return Err(AppError::PolicyViolation(format!(
+                "Plan is estimated to use {} tokens, exceeding max_estimated_tokens ({}). \
+                 Resubmit with allow_over_budget: true to run it anyway",
+                estimated_tokens, max_tokens
+            )));
+        }
+    }
+
+    let estimated_cost = estimate_cost(estimated_tokens);
+    if let Some(max_cost) = config.max_estimated_cost {
+        if estimated_cost > max_cost {
+            return Err(AppError::PolicyViolation(format!(
+                "Plan is estimated to cost ${:.4}, exceeding max_estimated_cost (${:.4}). \
+                 Resubmit with allow_over_budget: true to run it anyway",
+                estimated_cost, max_cost
+            )));
+        }
+    }
+    Ok(())
+}
+
 /// Calculate the depth of a step in the dependency graph using memoization
 ///
 /// This function uses a cache to avoid recalculating depths for the same steps,
@@ -301,4 +468,157 @@ mod tests {
             .high_dependency_steps
             .contains(&"step_4".to_string()));
     }
+
+    /// Build a strictly sequential plan: step_1 -> step_2 -> ... -> step_n
+    fn chain_plan(length: usize) -> Plan {
+        let steps = (1..=length)
+            .map(|i| Step {
+                id: format!("step_{}", i),
+                task: "run_gemini".to_string(),
+                params: StepParams {
+                    prompt: Some(format!("Step {}", i)),
+                    ..Default::default()
+                },
+                dependencies: if i == 1 {
+                    vec![]
+                } else {
+                    vec![format!("step_{}", i - 1)]
+                },
+            })
+            .collect();
+        Plan {
+            version: "1.0".to_string(),
+            steps,
+        }
+    }
+
+    #[test]
+    fn test_bottleneck_severity() {
+        // Every step waits on the one before it
+        let sequential = analyze_bottlenecks(&chain_plan(10));
+        assert_eq!(sequential.severity.chain, Severity::High);
+        assert_eq!(sequential.severity.fan_in, Severity::Low);
+        assert_eq!(sequential.severity.overall, Severity::High);
+
+        // Four short branches off one start step, two of them joined at the end
+        let step = |id: &str, dependencies: &[&str]| Step {
+            id: id.to_string(),
+            task: "ping".to_string(),
+            params: StepParams::default(),
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+        };
+        let mut steps = vec![step("start", &[])];
+        for branch in ["a", "b", "c", "d"] {
+            steps.push(step(&format!("{}_1", branch), &["start"]));
+            steps.push(step(
+                &format!("{}_2", branch),
+                &[format!("{}_1", branch).as_str()],
+            ));
+        }
+        steps.push(step("join", &["a_2", "b_2"]));
+        let balanced = analyze_bottlenecks(&Plan {
+            version: "1.0".to_string(),
+            steps,
+        });
+        assert_eq!(balanced.longest_chain_length, 4);
+        assert_eq!(balanced.severity.chain, Severity::Low);
+        assert_eq!(balanced.severity.fan_in, Severity::Low);
+        assert_eq!(balanced.severity.overall, Severity::Low);
+
+        // Four steps joined into one: medium fan-in by default, high when stricter
+        let fan_in = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                step("a", &[]),
+                step("b", &[]),
+                step("c", &[]),
+                step("d", &[]),
+                step("join", &["a", "b", "c", "d"]),
+            ],
+        };
+        assert_eq!(
+            analyze_bottlenecks(&fan_in).severity.fan_in,
+            Severity::Medium
+        );
+        let strict = BottleneckThresholds {
+            high_fan_in: 4,
+            ..Default::default()
+        };
+        assert_eq!(
+            analyze_bottlenecks_with_thresholds(&fan_in, &strict)
+                .severity
+                .overall,
+            Severity::High
+        );
+
+        assert_eq!(serde_json::to_value(Severity::Medium).unwrap(), "medium");
+    }
+
+    #[test]
+    fn test_validate_chain_length_at_limit() {
+        let analysis = analyze_bottlenecks(&chain_plan(50));
+        assert_eq!(analysis.longest_chain_length, 50);
+        assert!(validate_chain_length(&analysis, 50).is_ok());
+    }
+
+    #[test]
+    fn test_validate_chain_length_over_limit() {
+        let analysis = analyze_bottlenecks(&chain_plan(51));
+        match validate_chain_length(&analysis, 50) {
+            Err(AppError::InvalidPlan(msg)) => {
+                assert!(msg.contains("51"), "got: {}", msg);
+            }
+            other => panic!("Expected InvalidPlan, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_analyze_bottlenecks_very_long_chain() {
+        // 500-step chain stays well within recursion limits
+        let analysis = analyze_bottlenecks(&chain_plan(500));
+        assert_eq!(analysis.longest_chain_length, 500);
+        assert!(validate_chain_length(
+            &analysis,
+            crate::orchestrator::config::OrchestratorConfig::default().max_chain_length
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_check_budget_limits() {
+        // One run_gemini step: 100 chars * 1.3 + 100 overhead = 230 tokens
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![Step {
+                id: "step_1".to_string(),
+                task: "run_gemini".to_string(),
+                params: StepParams {
+                    prompt: Some("x".repeat(100)),
+                    ..Default::default()
+                },
+                dependencies: vec![],
+            }],
+        };
+
+        let mut config = OrchestratorConfig::default();
+        assert!(check_budget(&plan, &config).is_ok());
+
+        config.max_estimated_tokens = Some(230);
+        assert!(check_budget(&plan, &config).is_ok());
+        config.max_estimated_tokens = Some(229);
+        match check_budget(&plan, &config) {
+            Err(AppError::PolicyViolation(msg)) => {
+                assert!(msg.contains("230 tokens"), "got: {}", msg);
+                assert!(msg.contains("allow_over_budget"), "got: {}", msg);
+            }
+            other => panic!("Expected PolicyViolation, got: {:?}", other),
+        }
+
+        config.max_estimated_tokens = None;
+        config.max_estimated_cost = Some(estimate_cost(230) / 2.0);
+        assert!(matches!(
+            check_budget(&plan, &config),
+            Err(AppError::PolicyViolation(_))
+        ));
+    }
 }
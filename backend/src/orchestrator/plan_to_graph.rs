@@ -5,8 +5,15 @@
 //! using FanOutTask for independent steps.
 
 use crate::error::AppError;
-use crate::orchestrator::plan_types::Plan;
-use crate::orchestrator::tasks::{CreateFileTask, RunGeminiTask};
+use crate::orchestrator::config::OrchestratorConfig;
+use crate::orchestrator::graph_validation::{filename_problem, validate_graph};
+use crate::orchestrator::json_pointer::parse_json_pointer;
+use crate::orchestrator::plan_expansion::{expand_for_each, GATHER_TASK};
+use crate::orchestrator::plan_optimizer::{analyze_bottlenecks, validate_chain_length};
+use crate::orchestrator::plan_types::{
+    file_mode_problem, ContentTransform, OutputEncoding, Plan, Step, WriteMode,
+};
+use crate::orchestrator::tasks::{CreateFileTask, ForEachTask, GatherTask, RunGeminiTask};
 use crate::state::AppState;
 use anyhow::anyhow;
 use graph_flow::{Graph, GraphBuilder, Task};
@@ -34,73 +41,49 @@ pub fn build_graph_from_plan(
     plan: Plan,
     app_state: Arc<RwLock<AppState>>,
 ) -> Result<Arc<Graph>, AppError> {
-    // Validate plan first
-    plan.validate()
-        .map_err(|e| AppError::InvalidPlan(format!("Plan validation failed: {}", e)))?;
+    let config = OrchestratorConfig::default();
+    build_graph_from_plan_with_config(plan, app_state, &config)
+}
 
-    if plan.steps.is_empty() {
-        return Err(AppError::InvalidPlan("Plan has no steps".to_string()));
-    }
+/// Build a graph-flow graph from a plan with a specific configuration
+///
+/// Same as `build_graph_from_plan`, but enforces limits from the given config
+/// (e.g., `max_prompt_length` for run_gemini steps, `max_chain_length`).
+///
+/// # Arguments
+/// * `plan` - The plan to convert
+/// * `app_state` - Application state (for agent management, working directory)
+/// * `config` - Orchestrator configuration with limits to enforce
+///
+/// # Returns
+/// * `Ok(Arc<Graph>)` - The constructed graph
+/// * `Err(AppError)` - If graph building fails or a limit is exceeded
+pub fn build_graph_from_plan_with_config(
+    plan: Plan,
+    app_state: Arc<RwLock<AppState>>,
+    config: &OrchestratorConfig,
+) -> Result<Arc<Graph>, AppError> {
+    // Validate plan first, reporting every problem at once
+    validate_graph(&plan).map_err(|issues| {
+        let messages: Vec<String> = issues.into_iter().map(|issue| issue.message).collect();
+        AppError::InvalidPlan(format!("Plan validation failed: {}", messages.join("; ")))
+    })?;
+
+    // Refuse plans whose critical path would serialize past reasonable limits
+    validate_chain_length(&analyze_bottlenecks(&plan), config.max_chain_length)?;
 
     // Note: Working directory will be set in context when session is created
     // We don't need to read it here since tasks will get it from app_state or context
 
+    // Expand literal for_each steps into parallel sub-steps plus a gather step
+    let plan = expand_for_each(&plan)
+        .map_err(|e| AppError::InvalidPlan(format!("Plan validation failed: {}", e)))?;
+
     // Build task instances from plan steps
     let mut task_map: HashMap<String, Arc<dyn Task>> = HashMap::new();
 
     for step in &plan.steps {
-        let task: Arc<dyn Task> = match step.task.as_str() {
-            "run_gemini" => {
-                let prompt = step.params.prompt.as_ref().ok_or_else(|| {
-                    AppError::InvalidPlan(format!(
-                        "Step '{}' (run_gemini) missing required parameter: prompt",
-                        step.id
-                    ))
-                })?;
-
-                let run_task = RunGeminiTask::new(step.id.clone(), prompt.clone())
-                    .with_app_state(app_state.clone());
-                Arc::new(run_task)
-            }
-            "create_file" => {
-                let filename = step.params.filename.as_ref().ok_or_else(|| {
-                    AppError::InvalidPlan(format!(
-                        "Step '{}' (create_file) missing required parameter: filename",
-                        step.id
-                    ))
-                })?;
-
-                // Validate filename for path traversal protection
-                if filename.contains("..") || filename.starts_with('/') {
-                    return Err(AppError::InvalidPlan(format!(
-                        "Step '{}' (create_file) has invalid filename '{}': path traversal detected or absolute path",
-                        step.id, filename
-                    )));
-                }
-
-                if filename.contains('\0') || filename.chars().any(|c| c.is_control()) {
-                    return Err(AppError::InvalidPlan(format!(
-                        "Step '{}' (create_file) has invalid filename '{}': control characters detected",
-                        step.id, filename
-                    )));
-                }
-
-                let create_task = CreateFileTask::new(
-                    step.id.clone(),
-                    filename.clone(),
-                    step.params.content_from.clone(),
-                )
-                .with_app_state(app_state.clone());
-                Arc::new(create_task)
-            }
-            _ => {
-                return Err(AppError::InvalidPlan(format!(
-                    "Unknown task type: '{}' in step '{}'",
-                    step.task, step.id
-                )));
-            }
-        };
-
+        let task = build_task(step, &app_state, config)?;
         task_map.insert(step.id.clone(), task);
     }
 
@@ -121,13 +104,30 @@ pub fn build_graph_from_plan(
     }
 
     // Set start task (first step with no dependencies, or first step if all have dependencies)
-    use crate::orchestrator::plan_utils::find_start_step_id;
+    use crate::orchestrator::plan_utils::{find_start_step_id, find_unreachable_steps};
     let start_task_id = find_start_step_id(&plan).ok_or_else(|| {
         AppError::Internal(anyhow!(
             "Plan has no steps (this should not happen after validation)"
         ))
     })?;
 
+    // Steps the runner can't reach from the start step never run
+    let unreachable = find_unreachable_steps(&plan, start_task_id);
+    if !unreachable.is_empty() {
+        if !config.allow_unreachable_steps {
+            return Err(AppError::InvalidPlan(format!(
+                "Plan validation failed: steps not reachable from start step '{}' would never run: {}",
+                start_task_id,
+                unreachable.join(", ")
+            )));
+        }
+        tracing::warn!(
+            start_step = %start_task_id,
+            unreachable = ?unreachable,
+            "Plan has steps not reachable from the start step; they will not run"
+        );
+    }
+
     builder = builder.set_start_task(start_task_id);
 
     let graph = Arc::new(builder.build());
@@ -137,6 +137,186 @@ pub fn build_graph_from_plan(
     Ok(graph)
 }
 
+/// Build the graph-flow task for a single plan step
+///
+/// Also used by `ForEachTask` to build its sub-steps at execution time.
+pub(crate) fn build_task(
+    step: &Step,
+    app_state: &Arc<RwLock<AppState>>,
+    config: &OrchestratorConfig,
+) -> Result<Arc<dyn Task>, AppError> {
+    let task: Arc<dyn Task> = match step.task.as_str() {
+        "run_gemini" => {
+            let prompt = step.params.prompt.as_ref().ok_or_else(|| {
+                AppError::InvalidPlan(format!(
+                    "Step '{}' (run_gemini) missing required parameter: prompt",
+                    step.id
+                ))
+            })?;
+
+            if prompt.len() > config.max_prompt_length {
+                return Err(AppError::InvalidPlan(format!(
+                    "Step '{}' (run_gemini) prompt length {} exceeds maximum of {} characters",
+                    step.id,
+                    prompt.len(),
+                    config.max_prompt_length
+                )));
+            }
+
+            // Models are only pinned to fall back between them; otherwise the
+            // CLI picks its own default
+            let models = if config.model_fallbacks.is_empty() {
+                Vec::new()
+            } else {
+                std::iter::once(config.gemini_model.clone())
+                    .chain(config.model_fallbacks.iter().cloned())
+                    .collect()
+            };
+
+            let run_task = RunGeminiTask::new(step.id.clone(), prompt.clone())
+                .with_output_encoding(output_encoding(step)?)
+                .with_models(models)
+                .with_app_state(app_state.clone());
+            Arc::new(run_task)
+        }
+        "create_file" => {
+            // A filename_from reference is resolved and validated by the task at
+            // execution time; static filenames are checked up front.
+            let filename = match (&step.params.filename, &step.params.filename_from) {
+                (_, Some(_)) => String::new(),
+                (Some(filename), None) => filename.clone(),
+                (None, None) => {
+                    return Err(AppError::InvalidPlan(format!(
+                        "Step '{}' (create_file) missing required parameter: filename",
+                        step.id
+                    )));
+                }
+            };
+
+            // Path traversal protection (expanded for_each steps only reach
+            // this point unchecked)
+            if step.params.filename_from.is_none() {
+                if let Some((_, problem)) = filename_problem(&filename) {
+                    return Err(AppError::InvalidPlan(format!(
+                        "Step '{}' (create_file) has invalid filename '{}': {}",
+                        step.id, filename, problem
+                    )));
+                }
+            }
+
+            let transform = step
+                .params
+                .transform
+                .as_deref()
+                .map(|value| {
+                    ContentTransform::parse(value).ok_or_else(|| {
+                        AppError::InvalidPlan(format!(
+                            "Step '{}' has invalid transform '{}' (expected base64_decode)",
+                            step.id, value
+                        ))
+                    })
+                })
+                .transpose()?;
+
+            let mode = match step.params.mode.as_deref() {
+                Some(value) => WriteMode::parse(value).ok_or_else(|| {
+                    AppError::InvalidPlan(format!(
+                        "Step '{}' has invalid mode '{}' (expected overwrite or append)",
+                        step.id, value
+                    ))
+                })?,
+                None => WriteMode::default(),
+            };
+
+            if let Some(file_mode) = step.params.file_mode {
+                if let Some(problem) = file_mode_problem(file_mode) {
+                    return Err(AppError::InvalidPlan(format!(
+                        "Step '{}' has invalid file_mode {:o}: {}",
+                        step.id, file_mode, problem
+                    )));
+                }
+            }
+
+            // Refuse malformed or overly deep JSON paths before anything runs
+            if let Some(ref json_path) = step.params.content_from_json_path {
+                parse_json_pointer(json_path).map_err(|e| {
+                    AppError::InvalidPlan(format!(
+                        "Step '{}' has invalid content_from_json_path: {}",
+                        step.id, e
+                    ))
+                })?;
+            }
+
+            let create_task =
+                CreateFileTask::new(step.id.clone(), filename, step.params.content_from.clone())
+                    .with_content_json_path(step.params.content_from_json_path.clone())
+                    .with_filename_from(step.params.filename_from.clone())
+                    .with_transform(transform)
+                    .with_output_dir(config.output_dir.clone())
+                    .with_mode(mode)
+                    .with_file_mode(step.params.file_mode)
+                    .with_skip_if_unchanged(step.params.skip_if_unchanged.unwrap_or(false))
+                    .with_app_state(app_state.clone());
+            Arc::new(create_task)
+        }
+        "for_each" => {
+            // Literal item lists were expanded above; only items_from remains
+            let (items_from, template) = match (&step.params.items_from, &step.params.template) {
+                (Some(items_from), Some(template)) => (items_from.clone(), (**template).clone()),
+                _ => {
+                    return Err(AppError::InvalidPlan(format!(
+                        "Step '{}' (for_each) requires items or items_from and a template",
+                        step.id
+                    )));
+                }
+            };
+            Arc::new(
+                ForEachTask::new(step.id.clone(), items_from, template, config.clone())
+                    .with_app_state(app_state.clone()),
+            )
+        }
+        "ping" => {
+            if !config.enable_ping_task {
+                return Err(AppError::InvalidPlan(format!(
+                    "Step '{}' uses the ping task, which is disabled (set ENABLE_PING_TASK)",
+                    step.id
+                )));
+            }
+            Arc::new(crate::orchestrator::tasks::PingTask::new(
+                step.id.clone(),
+                step.params.delay_ms.unwrap_or(0),
+                step.params.message.clone(),
+            ))
+        }
+        GATHER_TASK => Arc::new(GatherTask::new(step.id.clone(), step.dependencies.clone())),
+        _ => {
+            return Err(AppError::InvalidPlan(format!(
+                "Unknown task type: '{}' in step '{}'",
+                step.task, step.id
+            )));
+        }
+    };
+
+    Ok(task)
+}
+
+/// The step's `output_encoding` (UTF-8 when unset)
+fn output_encoding(step: &Step) -> Result<OutputEncoding, AppError> {
+    step.params
+        .output_encoding
+        .as_deref()
+        .map(|value| {
+            OutputEncoding::parse(value).ok_or_else(|| {
+                AppError::InvalidPlan(format!(
+                    "Step '{}' has invalid output_encoding '{}' (expected utf8 or base64)",
+                    step.id, value
+                ))
+            })
+        })
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -373,6 +553,52 @@ mod tests {
         }
     }
 
+    fn single_prompt_plan(prompt: String) -> Plan {
+        Plan {
+            version: "1.0".to_string(),
+            steps: vec![Step {
+                id: "step_1".to_string(),
+                task: "run_gemini".to_string(),
+                params: StepParams {
+                    prompt: Some(prompt),
+                    ..Default::default()
+                },
+                dependencies: vec![],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_build_graph_prompt_at_max_length() {
+        let config = OrchestratorConfig {
+            max_prompt_length: 10,
+            ..Default::default()
+        };
+        let plan = single_prompt_plan("a".repeat(10));
+
+        let result = build_graph_from_plan_with_config(plan, create_test_state(), &config);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_build_graph_prompt_over_max_length() {
+        let config = OrchestratorConfig {
+            max_prompt_length: 10,
+            ..Default::default()
+        };
+        let plan = single_prompt_plan("a".repeat(11));
+
+        let result = build_graph_from_plan_with_config(plan, create_test_state(), &config);
+        match result {
+            Err(AppError::InvalidPlan(msg)) => {
+                assert!(msg.contains("step_1"), "got: {}", msg);
+                assert!(msg.contains("11"), "got: {}", msg);
+            }
+            Err(e) => panic!("Expected InvalidPlan error, got: {}", e),
+            Ok(_) => panic!("Expected error for prompt over max length"),
+        }
+    }
+
     #[test]
     fn test_build_graph_sets_start_task() {
         // Test that the graph builder correctly identifies and sets the start task
@@ -465,4 +691,107 @@ mod tests {
         assert_eq!(graph.id, DEFAULT_GRAPH_ID);
         // Graph should have 4 tasks with proper dependency edges
     }
+
+    #[test]
+    fn test_build_graph_from_plan_for_each_literal_items() {
+        use crate::orchestrator::plan_types::StepTemplate;
+
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                Step {
+                    id: "each".to_string(),
+                    task: "for_each".to_string(),
+                    params: StepParams {
+                        items: Some(vec!["a".to_string(), "b".to_string()]),
+                        template: Some(Box::new(StepTemplate {
+                            task: "run_gemini".to_string(),
+                            params: StepParams {
+                                prompt: Some("Summarize {{item}}".to_string()),
+                                ..Default::default()
+                            },
+                        })),
+                        ..Default::default()
+                    },
+                    dependencies: vec![],
+                },
+                Step {
+                    id: "save".to_string(),
+                    task: "create_file".to_string(),
+                    params: StepParams {
+                        filename: Some("summaries.json".to_string()),
+                        content_from: Some("each.output".to_string()),
+                        ..Default::default()
+                    },
+                    dependencies: vec!["each".to_string()],
+                },
+            ],
+        };
+
+        let result = build_graph_from_plan(plan, create_test_state());
+        assert!(
+            result.is_ok(),
+            "for_each plan should build: {:?}",
+            result.err()
+        );
+    }
+
+    #[test]
+    fn test_build_graph_rejects_unreachable_island() {
+        fn gemini(id: &str, dependencies: &[&str]) -> Step {
+            Step {
+                id: id.to_string(),
+                task: "run_gemini".to_string(),
+                params: StepParams {
+                    prompt: Some(format!("Prompt for {}", id)),
+                    ..Default::default()
+                },
+                dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+            }
+        }
+
+        // step_3 -> step_4 shares no edge with the step_1 -> step_2 chain
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                gemini("step_1", &[]),
+                gemini("step_2", &["step_1"]),
+                gemini("step_3", &[]),
+                gemini("step_4", &["step_3"]),
+            ],
+        };
+        use crate::orchestrator::plan_utils::find_unreachable_steps;
+        assert_eq!(
+            find_unreachable_steps(&plan, "step_1"),
+            vec!["step_3", "step_4"]
+        );
+
+        let config = OrchestratorConfig {
+            allow_unreachable_steps: false,
+            ..Default::default()
+        };
+        let result = build_graph_from_plan_with_config(plan.clone(), create_test_state(), &config);
+        match result {
+            Err(AppError::InvalidPlan(message)) => {
+                assert!(message.contains("step_3, step_4"), "{}", message);
+                assert!(message.contains("'step_1'"), "{}", message);
+            }
+            other => panic!("Expected InvalidPlan, got {:?}", other.map(|_| ())),
+        }
+
+        // By default they only cause a warning
+        let config = OrchestratorConfig::default();
+        assert!(build_graph_from_plan_with_config(plan, create_test_state(), &config).is_ok());
+
+        // Edges only lead forward: a second root isn't reached through a shared dependent
+        let joined = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                gemini("step_1", &[]),
+                gemini("step_2", &[]),
+                gemini("step_3", &["step_1", "step_2"]),
+            ],
+        };
+        assert_eq!(find_unreachable_steps(&joined, "step_1"), vec!["step_2"]);
+    }
 }
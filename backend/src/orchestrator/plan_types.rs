@@ -6,6 +6,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
 
 /// Top-level plan structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,9 +52,196 @@ pub struct StepParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub filename: Option<String>,
 
-    /// Reference to output from another step (e.g., "step_1.output")
+    /// Reference to output from another step (e.g., "step_1.output" or "step_1.metadata")
+    ///
+    /// A bare step ID refers to the step's default `output`.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content_from: Option<String>,
+
+    /// JSON pointer into the `content_from` output (for create_file task)
+    ///
+    /// When set, the referenced output is parsed as JSON and only the value at
+    /// this RFC 6901 pointer (e.g. "/items/0/title") is written; strings are
+    /// written without quotes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_from_json_path: Option<String>,
+
+    /// Reference to another step's output to use as the filename (for create_file task)
+    ///
+    /// The value is only known at execution time, so it is validated for path
+    /// traversal and control characters when the step runs. Mutually
+    /// exclusive with `filename`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename_from: Option<String>,
+
+    /// How the step output is stored in the context: "utf8" (default) or "base64"
+    ///
+    /// Use "base64" for steps that produce binary output so it survives being
+    /// stored as a string.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_encoding: Option<String>,
+
+    /// Transform applied to the content before writing (for create_file task)
+    ///
+    /// Currently only "base64_decode", which writes the decoded bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transform: Option<String>,
+
+    /// How the file is written (for create_file task): "overwrite" (default) or "append"
+    ///
+    /// Not the file's permissions; those go in `file_mode`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+
+    /// Leave the file untouched if it already holds the same content (for create_file task)
+    ///
+    /// Avoids churning mtimes when a plan is re-run. Ignored in append mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip_if_unchanged: Option<bool>,
+
+    /// Unix permission bits the file is created with (for create_file task)
+    ///
+    /// Called `file_mode` rather than `mode`, which already selects overwrite
+    /// or append. Either a number or a string of octal digits ("755", "0600");
+    /// JSON has no octal literals, so the number 755 means 0o1363, not 0o755.
+    /// Ignored with a warning on platforms without Unix permissions.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_file_mode",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub file_mode: Option<u32>,
+
+    /// Literal list of items to run the template over (for for_each task)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<String>>,
+
+    /// Reference to a prior step's output holding a JSON array of items (for for_each task)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items_from: Option<String>,
+
+    /// How long to sleep before completing, in milliseconds (for ping task)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delay_ms: Option<u64>,
+
+    /// Fixed output of the step (for ping task; defaults to "pong")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+
+    /// Sub-task run once per item (for for_each task)
+    ///
+    /// `{{item}}` and `{{index}}` in the template's prompt, filename and message are
+    /// replaced with each item and its 0-based position.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<Box<StepTemplate>>,
+}
+
+/// Sub-task template expanded by a for_each step
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct StepTemplate {
+    /// Task type of each sub-step ("run_gemini" or "create_file")
+    pub task: String,
+    /// Parameters of each sub-step (may contain `{{item}}` / `{{index}}`)
+    #[serde(default)]
+    pub params: StepParams,
+}
+
+/// Encoding used when storing a step's output in the context
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputEncoding {
+    /// Output is stored as-is (UTF-8 text)
+    #[default]
+    Utf8,
+    /// Output bytes are stored base64-encoded
+    Base64,
+}
+
+impl OutputEncoding {
+    /// Parse an `output_encoding` parameter value
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "utf8" => Some(Self::Utf8),
+            "base64" => Some(Self::Base64),
+            _ => None,
+        }
+    }
+}
+
+/// Transform applied to create_file content before it is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentTransform {
+    /// Decode base64 content and write the raw bytes
+    Base64Decode,
+}
+
+impl ContentTransform {
+    /// Parse a `transform` parameter value
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "base64_decode" => Some(Self::Base64Decode),
+            _ => None,
+        }
+    }
+}
+
+/// How create_file writes to its target file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WriteMode {
+    /// Replace any existing content
+    #[default]
+    Overwrite,
+    /// Add to the end of the file, creating it if missing
+    Append,
+}
+
+impl WriteMode {
+    /// Parse a `mode` parameter value
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "overwrite" => Some(Self::Overwrite),
+            "append" => Some(Self::Append),
+            _ => None,
+        }
+    }
+}
+
+/// What is wrong with a create_file `file_mode`, if anything
+///
+/// Only the rwx bits for owner, group and others are allowed (no setuid,
+/// setgid or sticky bit), and the owner must be able to read the file.
+pub fn file_mode_problem(mode: u32) -> Option<&'static str> {
+    if mode & !0o777 != 0 {
+        Some("only permission bits up to 0o777 may be set")
+    } else if mode & 0o400 == 0 {
+        Some("the owner must be able to read the file")
+    } else {
+        None
+    }
+}
+
+/// Accept a `file_mode` given as a number or as a string of octal digits
+fn deserialize_file_mode<'de, D>(deserializer: D) -> Result<Option<u32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RawMode {
+        Number(u32),
+        Octal(String),
+    }
+
+    match Option::<RawMode>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(RawMode::Number(mode)) => Ok(Some(mode)),
+        Some(RawMode::Octal(digits)) => {
+            let digits = digits.trim();
+            let digits = digits.strip_prefix("0o").unwrap_or(digits);
+            u32::from_str_radix(digits, 8).map(Some).map_err(|_| {
+                serde::de::Error::custom(format!("file_mode '{}' is not an octal number", digits))
+            })
+        }
+    }
 }
 
 impl Plan {
@@ -62,109 +250,404 @@ impl Plan {
     /// Checks for:
     /// - Unique step IDs
     /// - Valid task names
-    /// - Valid content_from references
+    /// - Valid content_from and filename_from references
     /// - Valid dependencies (must reference existing steps)
     /// - No circular dependencies (must be a DAG)
     /// - Consistency between content_from and dependencies
+    /// - No two unordered create_file steps writing the same file
+    ///
+    /// Returns the first problem found; use `field_errors` to get all of them.
     #[allow(dead_code)] // Will be used in Phase 2B
     pub fn validate(&self) -> Result<(), ValidationError> {
+        match self.collect_errors().into_iter().next() {
+            Some((_, error)) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Validate the plan and report every problem with a JSON pointer
+    ///
+    /// Each entry points at the offending field of the submitted plan (e.g.
+    /// `/steps/2/dependencies/0`) so a form UI can highlight it. Runs the same
+    /// checks as `validate`; an empty list means the plan is valid.
+    pub fn field_errors(&self) -> Vec<FieldError> {
+        self.collect_errors()
+            .into_iter()
+            .map(|(pointer, error)| FieldError {
+                pointer,
+                message: error.to_string(),
+            })
+            .collect()
+    }
+
+    /// Run all validation checks, accumulating pointer-annotated errors
+    pub(crate) fn collect_errors(&self) -> Vec<(String, ValidationError)> {
+        let mut errors = Vec::new();
+
         // Check for duplicate step IDs
-        let mut step_ids = std::collections::HashSet::new();
-        for step in &self.steps {
+        let mut step_ids = HashSet::new();
+        for (index, step) in self.steps.iter().enumerate() {
             if !step_ids.insert(&step.id) {
-                return Err(ValidationError::DuplicateStepId(step.id.clone()));
+                errors.push((
+                    format!("/steps/{}/id", index),
+                    ValidationError::DuplicateStepId(step.id.clone()),
+                ));
             }
         }
 
         // Check that all content_from references exist
-        let valid_step_ids: std::collections::HashSet<&str> =
-            self.steps.iter().map(|s| s.id.as_str()).collect();
+        let valid_step_ids: HashSet<&str> = self.steps.iter().map(|s| s.id.as_str()).collect();
+        let step_tasks: HashMap<&str, &str> = self
+            .steps
+            .iter()
+            .map(|s| (s.id.as_str(), s.task.as_str()))
+            .collect();
 
         // Validate dependencies for each step
-        for step in &self.steps {
+        for (index, step) in self.steps.iter().enumerate() {
+            let pointer = |field: &str| format!("/steps/{}/{}", index, field);
+
             // Check that all dependencies reference existing steps
-            for dep in &step.dependencies {
+            for (dep_index, dep) in step.dependencies.iter().enumerate() {
                 if !valid_step_ids.contains(dep.as_str()) {
-                    return Err(ValidationError::InvalidDependency {
-                        step_id: step.id.clone(),
-                        dependency: dep.clone(),
-                    });
+                    errors.push((
+                        pointer(&format!("dependencies/{}", dep_index)),
+                        ValidationError::InvalidDependency {
+                            step_id: step.id.clone(),
+                            dependency: dep.clone(),
+                        },
+                    ));
                 }
             }
 
-            // Check content_from references
-            if let Some(ref content_from) = step.params.content_from {
-                // Parse "step_1.output" -> "step_1"
-                let referenced_step_id = content_from.split('.').next().unwrap_or(content_from);
+            // Check content_from, filename_from and items_from references
+            // (including those inside a for_each template)
+            let template_params = step.params.template.as_ref().map(|t| &t.params);
+            let references = [
+                ("params/content_from", Some(&step.params.content_from)),
+                ("params/filename_from", Some(&step.params.filename_from)),
+                ("params/items_from", Some(&step.params.items_from)),
+                (
+                    "params/template/params/content_from",
+                    template_params.map(|p| &p.content_from),
+                ),
+                (
+                    "params/template/params/filename_from",
+                    template_params.map(|p| &p.filename_from),
+                ),
+            ];
+            for (field, reference) in references {
+                let Some(Some(reference)) = reference else {
+                    continue;
+                };
+                // Parse "step_1.metadata" -> ("step_1", "metadata")
+                let (referenced_step_id, output_name) = parse_output_reference(reference);
                 if !valid_step_ids.contains(referenced_step_id) {
-                    return Err(ValidationError::InvalidReference {
-                        step_id: step.id.clone(),
-                        reference: content_from.clone(),
-                    });
-                }
-
-                // Consistency check: if content_from references step_X, dependencies should include step_X
-                if !step.dependencies.contains(&referenced_step_id.to_string()) {
-                    return Err(ValidationError::InconsistentDependency {
-                        step_id: step.id.clone(),
-                        content_from: content_from.clone(),
-                        missing_dependency: referenced_step_id.to_string(),
-                    });
+                    errors.push((
+                        pointer(field),
+                        ValidationError::InvalidReference {
+                            step_id: step.id.clone(),
+                            reference: reference.clone(),
+                        },
+                    ));
+                } else if !step.dependencies.contains(&referenced_step_id.to_string()) {
+                    // Consistency check: if a reference points at step_X, dependencies should include step_X
+                    errors.push((
+                        pointer(field),
+                        ValidationError::InconsistentDependency {
+                            step_id: step.id.clone(),
+                            content_from: reference.clone(),
+                            missing_dependency: referenced_step_id.to_string(),
+                        },
+                    ));
+                } else {
+                    // The referenced task must actually store the named output
+                    let outputs = task_outputs(step_tasks[referenced_step_id]);
+                    if !outputs.contains(&output_name) {
+                        errors.push((
+                            pointer(field),
+                            ValidationError::UnknownOutput {
+                                step_id: step.id.clone(),
+                                reference: reference.clone(),
+                                available: outputs.join(", "),
+                            },
+                        ));
+                    }
                 }
             }
 
             // Validate task name
             if !is_valid_task_name(&step.task) {
-                return Err(ValidationError::InvalidTaskName {
-                    step_id: step.id.clone(),
-                    task: step.task.clone(),
-                });
+                errors.push((
+                    pointer("task"),
+                    ValidationError::InvalidTaskName {
+                        step_id: step.id.clone(),
+                        task: step.task.clone(),
+                    },
+                ));
             }
 
             // Validate required parameters for each task type
+            let missing = |param: &str| {
+                (
+                    pointer(&format!("params/{}", param)),
+                    ValidationError::MissingRequiredParam {
+                        step_id: step.id.clone(),
+                        task: step.task.clone(),
+                        param: param.to_string(),
+                    },
+                )
+            };
             match step.task.as_str() {
                 "run_gemini" => {
-                    if step.params.prompt.is_none()
-                        || step
-                            .params
-                            .prompt
-                            .as_ref()
-                            .map(|p| p.is_empty())
-                            .unwrap_or(true)
-                    {
-                        return Err(ValidationError::MissingRequiredParam {
-                            step_id: step.id.clone(),
-                            task: step.task.clone(),
-                            param: "prompt".to_string(),
-                        });
+                    if step.params.prompt.as_deref().unwrap_or("").is_empty() {
+                        errors.push(missing("prompt"));
                     }
                 }
                 "create_file" => {
-                    if step.params.filename.is_none()
-                        || step
-                            .params
-                            .filename
-                            .as_ref()
-                            .map(|f| f.is_empty())
-                            .unwrap_or(true)
+                    // A dynamic filename (filename_from) stands in for a static one
+                    if step.params.filename_from.is_none()
+                        && step.params.filename.as_deref().unwrap_or("").is_empty()
                     {
-                        return Err(ValidationError::MissingRequiredParam {
-                            step_id: step.id.clone(),
-                            task: step.task.clone(),
-                            param: "filename".to_string(),
-                        });
+                        errors.push(missing("filename"));
+                    }
+                    if let (Some(filename), Some(_)) =
+                        (&step.params.filename, &step.params.filename_from)
+                    {
+                        errors.push((
+                            pointer("params/filename"),
+                            ValidationError::InvalidParamValue {
+                                step_id: step.id.clone(),
+                                param: "filename".to_string(),
+                                value: format!(
+                                    "{} (filename and filename_from are mutually exclusive)",
+                                    filename
+                                ),
+                            },
+                        ));
+                    }
+                    // A JSON path selects from the content_from output
+                    if step.params.content_from_json_path.is_some()
+                        && step.params.content_from.is_none()
+                    {
+                        errors.push(missing("content_from"));
+                    }
+                }
+                "for_each" => {
+                    if let Err((field, error)) = validate_for_each(step) {
+                        errors.push((pointer(&format!("params/{}", field)), error));
+                    }
+                }
+                "ping" => {
+                    if let Err(error) = validate_ping_delay(&step.id, &step.params) {
+                        errors.push((pointer("params/delay_ms"), error));
                     }
                 }
                 _ => {
                     // Unknown task type already caught by task name validation
                 }
             }
+
+            // Validate encoding/transform parameter values
+            if let Some(ref encoding) = step.params.output_encoding {
+                if OutputEncoding::parse(encoding).is_none() {
+                    errors.push((
+                        pointer("params/output_encoding"),
+                        ValidationError::InvalidParamValue {
+                            step_id: step.id.clone(),
+                            param: "output_encoding".to_string(),
+                            value: encoding.clone(),
+                        },
+                    ));
+                }
+            }
+            if let Some(ref transform) = step.params.transform {
+                if ContentTransform::parse(transform).is_none() {
+                    errors.push((
+                        pointer("params/transform"),
+                        ValidationError::InvalidParamValue {
+                            step_id: step.id.clone(),
+                            param: "transform".to_string(),
+                            value: transform.clone(),
+                        },
+                    ));
+                }
+            }
+            if let Some(ref mode) = step.params.mode {
+                if WriteMode::parse(mode).is_none() {
+                    errors.push((
+                        pointer("params/mode"),
+                        ValidationError::InvalidParamValue {
+                            step_id: step.id.clone(),
+                            param: "mode".to_string(),
+                            value: mode.clone(),
+                        },
+                    ));
+                }
+            }
+            if let Some(file_mode) = step.params.file_mode {
+                if file_mode_problem(file_mode).is_some() {
+                    errors.push((
+                        pointer("params/file_mode"),
+                        ValidationError::InvalidParamValue {
+                            step_id: step.id.clone(),
+                            param: "file_mode".to_string(),
+                            value: format!("{:o}", file_mode),
+                        },
+                    ));
+                }
+            }
         }
 
         // Check for circular dependencies (must be a DAG)
-        self.detect_cycles()?;
+        if let Err(error) = self.detect_cycles() {
+            let index = match &error {
+                ValidationError::CircularDependency { step_id } => {
+                    self.steps.iter().position(|s| &s.id == step_id)
+                }
+                _ => None,
+            };
+            let pointer = index
+                .map(|i| format!("/steps/{}/dependencies", i))
+                .unwrap_or_else(|| "/steps".to_string());
+            errors.push((pointer, error));
+        } else {
+            errors.extend(self.detect_conflicting_writes());
+        }
 
-        Ok(())
+        errors
+    }
+
+    /// Find create_file steps that may run in parallel and write the same file
+    ///
+    /// Two steps conflict when they target the same static filename (after
+    /// dropping `.` components) and neither depends on the other, directly or
+    /// transitively; their writes would race. Steps using `filename_from` are
+    /// only known at execution time and are not checked. Must only be called
+    /// on acyclic plans.
+    fn detect_conflicting_writes(&self) -> Vec<(String, ValidationError)> {
+        let mut writers: HashMap<PathBuf, Vec<usize>> = HashMap::new();
+        for (index, step) in self.steps.iter().enumerate() {
+            if step.task != "create_file" || step.params.filename_from.is_some() {
+                continue;
+            }
+            if let Some(filename) = step.params.filename.as_deref().filter(|f| !f.is_empty()) {
+                let resolved: PathBuf = Path::new(filename)
+                    .components()
+                    .filter(|c| !matches!(c, Component::CurDir))
+                    .collect();
+                writers.entry(resolved).or_default().push(index);
+            }
+        }
+
+        let mut conflicts = Vec::new();
+        let mut ancestors_cache: HashMap<&str, HashSet<&str>> = HashMap::new();
+        for indices in writers.values().filter(|indices| indices.len() > 1) {
+            for (position, &later) in indices.iter().enumerate() {
+                for &earlier in &indices[..position] {
+                    let (first, second) = (&self.steps[earlier], &self.steps[later]);
+                    let ordered = self
+                        .ancestors(&second.id, &mut ancestors_cache)
+                        .contains(first.id.as_str())
+                        || self
+                            .ancestors(&first.id, &mut ancestors_cache)
+                            .contains(second.id.as_str());
+                    if !ordered {
+                        conflicts.push((
+                            format!("/steps/{}/params/filename", later),
+                            ValidationError::ConflictingFileWrite {
+                                step_id: second.id.clone(),
+                                other_step_id: first.id.clone(),
+                                filename: second.params.filename.clone().unwrap_or_default(),
+                            },
+                        ));
+                    }
+                }
+            }
+        }
+        conflicts.sort_by(|a, b| a.0.cmp(&b.0));
+        conflicts
+    }
+
+    /// All steps `step_id` transitively depends on (memoized in `cache`)
+    fn ancestors<'a>(
+        &'a self,
+        step_id: &'a str,
+        cache: &mut HashMap<&'a str, HashSet<&'a str>>,
+    ) -> HashSet<&'a str> {
+        if let Some(ancestors) = cache.get(step_id) {
+            return ancestors.clone();
+        }
+        let mut ancestors = HashSet::new();
+        if let Some(step) = self.steps.iter().find(|s| s.id == step_id) {
+            for dep in &step.dependencies {
+                ancestors.insert(dep.as_str());
+                ancestors.extend(self.ancestors(dep, cache));
+            }
+        }
+        cache.insert(step_id, ancestors.clone());
+        ancestors
+    }
+
+    /// Group steps into execution levels (topological layering)
+    ///
+    /// Level 0 holds the steps with no dependencies; each later level holds the
+    /// steps whose dependencies all sit in earlier levels, so the steps within a
+    /// level can run in parallel. Within a level, steps keep their plan order.
+    ///
+    /// Validated plans are DAGs, but this does not rely on it: unknown
+    /// dependencies and cycles are reported as errors.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Vec<String>>)` - Step IDs per level, in execution order
+    /// * `Err(ValidationError)` - If a dependency is missing or steps form a cycle
+    pub fn execution_levels(&self) -> Result<Vec<Vec<String>>, ValidationError> {
+        let step_ids: HashSet<&str> = self.steps.iter().map(|s| s.id.as_str()).collect();
+        for step in &self.steps {
+            if let Some(dep) = step
+                .dependencies
+                .iter()
+                .find(|dep| !step_ids.contains(dep.as_str()))
+            {
+                return Err(ValidationError::InvalidDependency {
+                    step_id: step.id.clone(),
+                    dependency: dep.clone(),
+                });
+            }
+        }
+
+        let mut placed: HashSet<&str> = HashSet::new();
+        let mut levels = Vec::new();
+
+        while placed.len() < self.steps.len() {
+            let level: Vec<&str> = self
+                .steps
+                .iter()
+                .filter(|step| !placed.contains(step.id.as_str()))
+                .filter(|step| {
+                    step.dependencies
+                        .iter()
+                        .all(|dep| placed.contains(dep.as_str()))
+                })
+                .map(|step| step.id.as_str())
+                .collect();
+
+            if level.is_empty() {
+                // Every remaining step waits on another remaining step
+                let step_id = self
+                    .steps
+                    .iter()
+                    .find(|step| !placed.contains(step.id.as_str()))
+                    .map(|step| step.id.clone())
+                    .unwrap_or_default();
+                return Err(ValidationError::CircularDependency { step_id });
+            }
+
+            placed.extend(level.iter().copied());
+            levels.push(level.into_iter().map(str::to_string).collect());
+        }
+
+        Ok(levels)
     }
 
     /// Detect circular dependencies using Depth-First Search
@@ -226,6 +709,15 @@ impl Plan {
     }
 }
 
+/// A validation problem located by a JSON pointer into the submitted plan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    /// JSON pointer to the offending field (e.g. "/steps/2/dependencies/0")
+    pub pointer: String,
+    /// Human-readable description of the problem
+    pub message: String,
+}
+
 /// Validation errors for plan structure
 /// Errors that can occur during plan validation
 #[derive(Debug, thiserror::Error)]
@@ -246,7 +738,7 @@ pub enum ValidationError {
 
     /// Step has an invalid task name
     #[error(
-        "Step '{step_id}' has invalid task name: '{task}'. Available: run_gemini, create_file"
+        "Step '{step_id}' has invalid task name: '{task}'. Available: run_gemini, create_file, for_each"
     )]
     InvalidTaskName {
         /// ID of the step with invalid task name
@@ -292,12 +784,194 @@ pub enum ValidationError {
         /// The missing dependency that should be in dependencies array
         missing_dependency: String,
     },
+
+    /// Step references an output name its source step's task doesn't produce
+    #[error("Step '{step_id}' references unknown output '{reference}' (available: {available})")]
+    UnknownOutput {
+        /// ID of the step with the invalid reference
+        step_id: String,
+        /// The reference string (e.g. "step_1.metadata")
+        reference: String,
+        /// Comma-separated outputs the referenced step's task produces
+        available: String,
+    },
+
+    /// Step has a parameter with an unsupported value
+    #[error("Step '{step_id}' has invalid value '{value}' for parameter '{param}'")]
+    InvalidParamValue {
+        /// ID of the step with the invalid parameter
+        step_id: String,
+        /// Name of the parameter
+        param: String,
+        /// The unsupported value
+        value: String,
+    },
+
+    /// Two create_file steps with no ordering between them write the same file
+    #[error("Steps '{other_step_id}' and '{step_id}' both write '{filename}' and may run in parallel; add a dependency between them")]
+    ConflictingFileWrite {
+        /// ID of the later step in the plan
+        step_id: String,
+        /// ID of the earlier step writing the same file
+        other_step_id: String,
+        /// The shared filename
+        filename: String,
+    },
 }
 
+/// Every task type a plan step may use
+///
+/// Plans may also use `ping`, which sleeps and returns a fixed string; it is
+/// left out here so the planner and `/api/capabilities` never offer it, and
+/// only runs when `OrchestratorConfig::enable_ping_task` is on.
+pub const TASK_NAMES: &[&str] = &["run_gemini", "create_file", "for_each"];
+
 /// Check if a task name is valid
-#[allow(dead_code)] // Will be used in Phase 2B
 fn is_valid_task_name(task: &str) -> bool {
-    matches!(task, "run_gemini" | "create_file")
+    TASK_NAMES.contains(&task) || task == "ping"
+}
+
+/// Named outputs a task stores in the context, the default `output` first
+///
+/// Each is stored under `"<step_id>.<name>"`, so these are the names a
+/// `content_from` (or other reference) may use for a step running the task.
+pub fn task_outputs(task: &str) -> &'static [&'static str] {
+    match task {
+        "create_file" => &["output", "metadata"],
+        _ => &["output"],
+    }
+}
+
+/// Split a step output reference into its step ID and output name
+///
+/// `"step_1.metadata"` gives `("step_1", "metadata")`; a bare `"step_1"` refers
+/// to the default `output`.
+pub fn parse_output_reference(reference: &str) -> (&str, &str) {
+    use crate::orchestrator::constants::DEFAULT_OUTPUT_NAME;
+
+    reference
+        .split_once('.')
+        .unwrap_or((reference, DEFAULT_OUTPUT_NAME))
+}
+
+/// Context key holding the output a reference points at (e.g. "step_1.output")
+pub fn output_context_key(reference: &str) -> String {
+    let (step_id, output_name) = parse_output_reference(reference);
+    format!("{}.{}", step_id, output_name)
+}
+
+/// Check that a ping step's `delay_ms` is within `MAX_PING_DELAY_MS`
+fn validate_ping_delay(step_id: &str, params: &StepParams) -> Result<(), ValidationError> {
+    use crate::orchestrator::constants::MAX_PING_DELAY_MS;
+
+    match params.delay_ms {
+        Some(delay) if delay > MAX_PING_DELAY_MS => Err(ValidationError::InvalidParamValue {
+            step_id: step_id.to_string(),
+            param: "delay_ms".to_string(),
+            value: format!("{} (maximum {})", delay, MAX_PING_DELAY_MS),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Validate a for_each step: one item source and a runnable template
+///
+/// On failure returns the offending field (relative to the step's `params`,
+/// as a JSON pointer fragment) along with the error.
+/// Reject a for_each step with more than `MAX_FOR_EACH_ITEMS` items
+pub fn check_for_each_item_count(step_id: &str, count: usize) -> Result<(), ValidationError> {
+    use crate::orchestrator::constants::MAX_FOR_EACH_ITEMS;
+
+    if count > MAX_FOR_EACH_ITEMS {
+        return Err(ValidationError::InvalidParamValue {
+            step_id: step_id.to_string(),
+            param: "items".to_string(),
+            value: format!("{} items (maximum {})", count, MAX_FOR_EACH_ITEMS),
+        });
+    }
+    Ok(())
+}
+
+fn validate_for_each(step: &Step) -> Result<(), (&'static str, ValidationError)> {
+    let missing = |field: &'static str, param: &str| {
+        (
+            field,
+            ValidationError::MissingRequiredParam {
+                step_id: step.id.clone(),
+                task: step.task.clone(),
+                param: param.to_string(),
+            },
+        )
+    };
+
+    match (&step.params.items, &step.params.items_from) {
+        (None, None) => return Err(missing("items", "items")),
+        (Some(items), None) => {
+            check_for_each_item_count(&step.id, items.len()).map_err(|error| ("items", error))?
+        }
+        (Some(_), Some(_)) => {
+            return Err((
+                "items_from",
+                ValidationError::InvalidParamValue {
+                    step_id: step.id.clone(),
+                    param: "items_from".to_string(),
+                    value: "cannot be combined with items".to_string(),
+                },
+            ))
+        }
+        _ => {}
+    }
+
+    let template = step
+        .params
+        .template
+        .as_ref()
+        .ok_or_else(|| missing("template", "template"))?;
+    match template.task.as_str() {
+        "run_gemini" => {
+            if template.params.prompt.as_deref().unwrap_or("").is_empty() {
+                return Err(missing("template/params/prompt", "template.prompt"));
+            }
+        }
+        "create_file" => {
+            if template.params.filename_from.is_none()
+                && template.params.filename.as_deref().unwrap_or("").is_empty()
+            {
+                return Err(missing("template/params/filename", "template.filename"));
+            }
+            if let (Some(filename), Some(_)) =
+                (&template.params.filename, &template.params.filename_from)
+            {
+                return Err((
+                    "template/params/filename",
+                    ValidationError::InvalidParamValue {
+                        step_id: step.id.clone(),
+                        param: "template.filename".to_string(),
+                        value: format!(
+                            "{} (filename and filename_from are mutually exclusive)",
+                            filename
+                        ),
+                    },
+                ));
+            }
+        }
+        "ping" => {
+            validate_ping_delay(&step.id, &template.params)
+                .map_err(|error| ("template/params/delay_ms", error))?;
+        }
+        other => {
+            return Err((
+                "template/task",
+                ValidationError::InvalidParamValue {
+                    step_id: step.id.clone(),
+                    param: "template.task".to_string(),
+                    value: other.to_string(),
+                },
+            ))
+        }
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -479,6 +1153,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_plan_validation_ping_delay_limit() {
+        use crate::orchestrator::constants::MAX_PING_DELAY_MS;
+
+        let ping = |delay_ms| Plan {
+            version: "1.0".to_string(),
+            steps: vec![Step {
+                id: "step_1".to_string(),
+                task: "ping".to_string(),
+                params: StepParams {
+                    delay_ms: Some(delay_ms),
+                    ..Default::default()
+                },
+                dependencies: vec![],
+            }],
+        };
+
+        assert!(ping(MAX_PING_DELAY_MS).validate().is_ok());
+        let errors = ping(MAX_PING_DELAY_MS + 1).field_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].pointer, "/steps/0/params/delay_ms");
+    }
+
     #[test]
     fn test_plan_validation_missing_prompt() {
         let plan = Plan {
@@ -897,4 +1594,493 @@ mod tests {
 
         assert!(plan.validate().is_ok());
     }
+
+    #[test]
+    fn test_plan_validation_filename_from() {
+        let mut plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                Step {
+                    id: "step_1".to_string(),
+                    task: "run_gemini".to_string(),
+                    params: StepParams {
+                        prompt: Some("Suggest a filename".to_string()),
+                        ..Default::default()
+                    },
+                    dependencies: vec![],
+                },
+                Step {
+                    id: "step_2".to_string(),
+                    task: "create_file".to_string(),
+                    params: StepParams {
+                        filename_from: Some("step_1.output".to_string()),
+                        content_from: Some("step_1.output".to_string()),
+                        ..Default::default()
+                    },
+                    dependencies: vec!["step_1".to_string()],
+                },
+            ],
+        };
+
+        // filename_from satisfies the filename requirement
+        assert!(plan.validate().is_ok());
+
+        // filename_from must reference an existing step
+        plan.steps[1].params.filename_from = Some("step_9.output".to_string());
+        assert!(matches!(
+            plan.validate(),
+            Err(ValidationError::InvalidReference { .. })
+        ));
+    }
+
+    #[test]
+    fn test_field_errors_reject_filename_with_filename_from() {
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                gemini_step("step_1", &[]),
+                Step {
+                    id: "step_2".to_string(),
+                    task: "create_file".to_string(),
+                    params: StepParams {
+                        filename: Some("out.txt".to_string()),
+                        filename_from: Some("step_1.output".to_string()),
+                        content_from: Some("step_1.output".to_string()),
+                        ..Default::default()
+                    },
+                    dependencies: vec!["step_1".to_string()],
+                },
+            ],
+        };
+
+        let errors = plan.field_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].pointer, "/steps/1/params/filename");
+        assert!(errors[0].message.contains("mutually exclusive"));
+        assert!(matches!(
+            plan.validate(),
+            Err(ValidationError::InvalidParamValue { ref param, .. }) if param == "filename"
+        ));
+    }
+
+    #[test]
+    fn test_plan_validation_encoding_params() {
+        let mut plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![Step {
+                id: "step_1".to_string(),
+                task: "run_gemini".to_string(),
+                params: StepParams {
+                    prompt: Some("Generate an image".to_string()),
+                    output_encoding: Some("base64".to_string()),
+                    ..Default::default()
+                },
+                dependencies: vec![],
+            }],
+        };
+        assert!(plan.validate().is_ok());
+
+        plan.steps[0].params.output_encoding = Some("latin1".to_string());
+        match plan.validate() {
+            Err(ValidationError::InvalidParamValue { param, value, .. }) => {
+                assert_eq!(param, "output_encoding");
+                assert_eq!(value, "latin1");
+            }
+            other => panic!("Expected InvalidParamValue, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_plan_validation_file_mode() {
+        let mut plan: Plan = serde_json::from_value(serde_json::json!({
+            "steps": [{"id": "step_1", "task": "create_file",
+                       "params": {"filename": "run.sh", "file_mode": "0755"}}]
+        }))
+        .unwrap();
+        assert_eq!(plan.steps[0].params.file_mode, Some(0o755));
+        assert!(plan.validate().is_ok());
+
+        for bad in [0o4755, 0o200] {
+            plan.steps[0].params.file_mode = Some(bad);
+            match plan.validate() {
+                Err(ValidationError::InvalidParamValue { param, value, .. }) => {
+                    assert_eq!(param, "file_mode");
+                    assert_eq!(value, format!("{:o}", bad));
+                }
+                other => panic!("Expected InvalidParamValue, got: {:?}", other),
+            }
+        }
+
+        let not_octal =
+            serde_json::from_value::<StepParams>(serde_json::json!({"file_mode": "rwx"}));
+        assert!(not_octal.is_err());
+    }
+
+    #[test]
+    fn test_plan_validation_for_each() {
+        let mut plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                Step {
+                    id: "step_1".to_string(),
+                    task: "run_gemini".to_string(),
+                    params: StepParams {
+                        prompt: Some("List topics as a JSON array".to_string()),
+                        ..Default::default()
+                    },
+                    dependencies: vec![],
+                },
+                Step {
+                    id: "step_2".to_string(),
+                    task: "for_each".to_string(),
+                    params: StepParams {
+                        items_from: Some("step_1.output".to_string()),
+                        template: Some(Box::new(StepTemplate {
+                            task: "run_gemini".to_string(),
+                            params: StepParams {
+                                prompt: Some("Write about {{item}}".to_string()),
+                                ..Default::default()
+                            },
+                        })),
+                        ..Default::default()
+                    },
+                    dependencies: vec!["step_1".to_string()],
+                },
+            ],
+        };
+        assert!(plan.validate().is_ok());
+
+        // items_from must be listed in dependencies
+        plan.steps[1].dependencies.clear();
+        assert!(matches!(
+            plan.validate(),
+            Err(ValidationError::InconsistentDependency { .. })
+        ));
+        plan.steps[1].dependencies = vec!["step_1".to_string()];
+
+        // Nested for_each templates are not supported
+        plan.steps[1].params.template.as_mut().unwrap().task = "for_each".to_string();
+        match plan.validate() {
+            Err(ValidationError::InvalidParamValue { param, .. }) => {
+                assert_eq!(param, "template.task")
+            }
+            other => panic!("Expected InvalidParamValue, got: {:?}", other),
+        }
+
+        plan.steps[1].params.template = None;
+        match plan.validate() {
+            Err(ValidationError::MissingRequiredParam { param, .. }) => {
+                assert_eq!(param, "template")
+            }
+            other => panic!("Expected MissingRequiredParam, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_plan_validation_for_each_caps_literal_items() {
+        use crate::orchestrator::constants::MAX_FOR_EACH_ITEMS;
+
+        let mut plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![Step {
+                id: "step_1".to_string(),
+                task: "for_each".to_string(),
+                params: StepParams {
+                    items: Some(vec!["x".to_string(); MAX_FOR_EACH_ITEMS]),
+                    template: Some(Box::new(StepTemplate {
+                        task: "run_gemini".to_string(),
+                        params: StepParams {
+                            prompt: Some("Write about {{item}}".to_string()),
+                            ..Default::default()
+                        },
+                    })),
+                    ..Default::default()
+                },
+                dependencies: vec![],
+            }],
+        };
+        assert!(plan.validate().is_ok());
+
+        plan.steps[0]
+            .params
+            .items
+            .as_mut()
+            .unwrap()
+            .push("x".to_string());
+        match plan.validate() {
+            Err(ValidationError::InvalidParamValue { param, .. }) => assert_eq!(param, "items"),
+            other => panic!("Expected InvalidParamValue, got: {:?}", other),
+        }
+    }
+
+    fn gemini_step(id: &str, dependencies: &[&str]) -> Step {
+        Step {
+            id: id.to_string(),
+            task: "run_gemini".to_string(),
+            params: StepParams {
+                prompt: Some(format!("Prompt for {}", id)),
+                ..Default::default()
+            },
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_execution_levels_diamond() {
+        // step_1 fans out to step_2 and step_3, which join in step_4
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                gemini_step("step_1", &[]),
+                gemini_step("step_2", &["step_1"]),
+                gemini_step("step_3", &["step_1"]),
+                gemini_step("step_4", &["step_2", "step_3"]),
+            ],
+        };
+        assert!(plan.validate().is_ok());
+
+        let levels = plan.execution_levels().unwrap();
+        assert_eq!(
+            levels,
+            vec![
+                vec!["step_1".to_string()],
+                vec!["step_2".to_string(), "step_3".to_string()],
+                vec!["step_4".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_execution_levels_independent_steps_share_level() {
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                gemini_step("step_2", &["step_1"]),
+                gemini_step("step_1", &[]),
+                gemini_step("step_3", &[]),
+            ],
+        };
+
+        let levels = plan.execution_levels().unwrap();
+        assert_eq!(levels, vec![vec!["step_1", "step_3"], vec!["step_2"]]);
+    }
+
+    #[test]
+    fn test_execution_levels_errors_on_cycle() {
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                gemini_step("step_1", &[]),
+                gemini_step("step_2", &["step_3"]),
+                gemini_step("step_3", &["step_2"]),
+            ],
+        };
+
+        assert!(matches!(
+            plan.execution_levels(),
+            Err(ValidationError::CircularDependency { step_id }) if step_id == "step_2"
+        ));
+    }
+
+    #[test]
+    fn test_execution_levels_errors_on_unknown_dependency() {
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![gemini_step("step_1", &["step_9"])],
+        };
+
+        assert!(matches!(
+            plan.execution_levels(),
+            Err(ValidationError::InvalidDependency { dependency, .. }) if dependency == "step_9"
+        ));
+    }
+
+    #[test]
+    fn test_field_errors_point_at_missing_dependency() {
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                gemini_step("step_1", &[]),
+                gemini_step("step_2", &["step_1"]),
+                gemini_step("step_3", &["step_9"]),
+            ],
+        };
+
+        let errors = plan.field_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].pointer, "/steps/2/dependencies/0");
+        assert!(errors[0]
+            .message
+            .contains("non-existent dependency 'step_9'"));
+    }
+
+    #[test]
+    fn test_field_errors_point_at_inconsistent_content_from() {
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                gemini_step("step_1", &[]),
+                Step {
+                    id: "step_2".to_string(),
+                    task: "create_file".to_string(),
+                    params: StepParams {
+                        filename: Some("out.txt".to_string()),
+                        content_from: Some("step_1.output".to_string()),
+                        ..Default::default()
+                    },
+                    dependencies: vec![],
+                },
+            ],
+        };
+
+        let errors = plan.field_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].pointer, "/steps/1/params/content_from");
+        assert!(errors[0].message.contains("inconsistent dependency"));
+    }
+
+    #[test]
+    fn test_field_errors_accumulate_across_steps() {
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                Step {
+                    id: "step_1".to_string(),
+                    task: "run_gemini".to_string(),
+                    params: StepParams::default(),
+                    dependencies: vec!["missing".to_string()],
+                },
+                Step {
+                    id: "step_2".to_string(),
+                    task: "send_email".to_string(),
+                    params: StepParams::default(),
+                    dependencies: vec![],
+                },
+            ],
+        };
+
+        let pointers: Vec<String> = plan.field_errors().into_iter().map(|e| e.pointer).collect();
+        assert_eq!(
+            pointers,
+            vec![
+                "/steps/0/dependencies/0",
+                "/steps/0/params/prompt",
+                "/steps/1/task",
+            ]
+        );
+        // validate() still reports the first problem
+        assert!(matches!(
+            plan.validate(),
+            Err(ValidationError::InvalidDependency { .. })
+        ));
+    }
+
+    fn metadata_reference_plan(source: Step) -> Plan {
+        Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                source,
+                Step {
+                    id: "step_2".to_string(),
+                    task: "create_file".to_string(),
+                    params: StepParams {
+                        filename: Some("metadata.json".to_string()),
+                        content_from: Some("step_1.metadata".to_string()),
+                        ..Default::default()
+                    },
+                    dependencies: vec!["step_1".to_string()],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_plan_validation_named_output_reference() {
+        // create_file stores metadata alongside its default output
+        let plan = metadata_reference_plan(create_file_step("step_1", "notes.txt", &[]));
+        assert!(plan.validate().is_ok());
+
+        // run_gemini only stores `output`, so `.metadata` would never be set
+        let errors = metadata_reference_plan(gemini_step("step_1", &[])).field_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].pointer, "/steps/1/params/content_from");
+        assert!(
+            errors[0]
+                .message
+                .contains("unknown output 'step_1.metadata' (available: output)"),
+            "got: {}",
+            errors[0].message
+        );
+    }
+
+    #[test]
+    fn test_output_reference_defaults_to_output() {
+        assert_eq!(parse_output_reference("step_1"), ("step_1", "output"));
+        assert_eq!(
+            parse_output_reference("step_1.metadata"),
+            ("step_1", "metadata")
+        );
+        assert_eq!(output_context_key("step_1"), "step_1.output");
+        assert_eq!(output_context_key("step_1.metadata"), "step_1.metadata");
+    }
+
+    fn create_file_step(id: &str, filename: &str, dependencies: &[&str]) -> Step {
+        Step {
+            id: id.to_string(),
+            task: "create_file".to_string(),
+            params: StepParams {
+                filename: Some(filename.to_string()),
+                ..Default::default()
+            },
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_plan_validation_rejects_parallel_writes_to_same_file() {
+        // "./notes.txt" and "notes.txt" resolve to the same file
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                gemini_step("step_1", &[]),
+                create_file_step("step_2", "notes.txt", &["step_1"]),
+                create_file_step("step_3", "./notes.txt", &["step_1"]),
+            ],
+        };
+
+        match plan.validate() {
+            Err(ValidationError::ConflictingFileWrite {
+                step_id,
+                other_step_id,
+                ..
+            }) => {
+                assert_eq!(step_id, "step_3");
+                assert_eq!(other_step_id, "step_2");
+            }
+            other => panic!("Expected ConflictingFileWrite, got: {:?}", other),
+        }
+        assert_eq!(
+            plan.field_errors()
+                .iter()
+                .map(|e| e.pointer.as_str())
+                .collect::<Vec<_>>(),
+            vec!["/steps/2/params/filename"]
+        );
+    }
+
+    #[test]
+    fn test_plan_validation_allows_ordered_writes_to_same_file() {
+        // step_3 depends on step_1 through step_2, so the writes can't overlap
+        let plan = Plan {
+            version: "1.0".to_string(),
+            steps: vec![
+                create_file_step("step_1", "notes.txt", &[]),
+                gemini_step("step_2", &["step_1"]),
+                create_file_step("step_3", "notes.txt", &["step_2"]),
+                create_file_step("step_4", "other.txt", &[]),
+            ],
+        };
+
+        assert!(plan.validate().is_ok());
+    }
 }
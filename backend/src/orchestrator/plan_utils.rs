@@ -4,7 +4,7 @@
 //! and analysis helpers that can be shared across modules.
 
 use crate::orchestrator::plan_types::Plan;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 /// Extract task IDs from a plan
 ///
@@ -39,6 +39,52 @@ pub fn extract_edges(plan: &Plan) -> Vec<(String, String)> {
     edges
 }
 
+/// Render a plan as a Graphviz DOT digraph
+///
+/// Each step becomes a node labeled with its ID and task, and each
+/// dependency an edge from the dependency to the dependent step.
+///
+/// # Arguments
+/// * `plan` - The plan to render
+///
+/// # Returns
+/// * `String` - The DOT source
+pub fn plan_to_dot(plan: &Plan) -> String {
+    let mut dot = String::from("digraph plan {\n    rankdir=LR;\n    node [shape=box];\n");
+    for step in &plan.steps {
+        dot.push_str(&format!(
+            "    \"{}\" [label=\"{}\\n{}\"];\n",
+            escape_dot(&step.id),
+            escape_dot(&step.id),
+            escape_dot(&step.task)
+        ));
+    }
+    for (from, to) in extract_edges(plan) {
+        dot.push_str(&format!(
+            "    \"{}\" -> \"{}\";\n",
+            escape_dot(&from),
+            escape_dot(&to)
+        ));
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Escape a string for use inside a double-quoted DOT ID or label
+fn escape_dot(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 /// Find the first step with no dependencies
 ///
 /// Returns the step ID of the first step that has no dependencies,
@@ -57,6 +103,44 @@ pub fn find_start_step_id(plan: &Plan) -> Option<&str> {
         .or_else(|| plan.steps.first().map(|step| step.id.as_str()))
 }
 
+/// Find steps the runner can't reach from the start step
+///
+/// Execution begins at `start_step_id` and moves from a step only to the
+/// steps that depend on it, so a step is reached only if it descends from
+/// the start step. Other roots, and steps that depend only on them, are
+/// never run, even when they share a dependent with the start step.
+///
+/// # Arguments
+/// * `plan` - The plan to analyze
+/// * `start_step_id` - Step the graph starts from
+///
+/// # Returns
+/// * `Vec<&str>` - IDs of the disconnected steps, in plan order
+pub fn find_unreachable_steps<'a>(plan: &'a Plan, start_step_id: &str) -> Vec<&'a str> {
+    let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+    for step in &plan.steps {
+        for dep in &step.dependencies {
+            dependents.entry(dep).or_default().push(&step.id);
+        }
+    }
+
+    let mut reached = HashSet::from([start_step_id]);
+    let mut pending = vec![start_step_id];
+    while let Some(id) = pending.pop() {
+        for &next in dependents.get(id).into_iter().flatten() {
+            if reached.insert(next) {
+                pending.push(next);
+            }
+        }
+    }
+
+    plan.steps
+        .iter()
+        .map(|step| step.id.as_str())
+        .filter(|id| !reached.contains(id))
+        .collect()
+}
+
 /// Get all step IDs that have no dependencies (can run in parallel at start)
 ///
 /// # Arguments
@@ -81,7 +165,6 @@ pub fn find_independent_steps(plan: &Plan) -> Vec<&str> {
 ///
 /// # Returns
 /// * `Vec<String>` - Step IDs that depend on the given step
-#[allow(dead_code)] // Reserved for future plan analysis features
 pub fn find_dependents(plan: &Plan, step_id: &str) -> Vec<String> {
     plan.steps
         .iter()
@@ -8,15 +8,28 @@
 //! - Testable: Each primitive can be tested independently
 //! - Composable: Easy to chain together in orchestration logic
 
-use crate::api::utils::{find_or_create_gemini_agent, find_or_create_planner_agent};
+use crate::api::utils::{
+    acquire_agent_slot, find_or_create_default_agent, find_or_create_planner_agent,
+    set_output_format,
+};
 use crate::error::AppError;
 use crate::executor::CliExecutor;
 use crate::orchestrator::api_client;
-use crate::orchestrator::plan_types::Plan;
+use crate::orchestrator::api_key::resolve_gemini_api_key;
+use crate::orchestrator::cancellation::CancellationToken;
+use crate::orchestrator::config::{OrchestratorConfig, PlannerExample};
+use crate::orchestrator::constants::{
+    MAX_FILE_WRITE_BYTES, MAX_PLANNER_ERROR_RESPONSE_CHARS, STREAM_CHUNK_BUFFER,
+};
+use crate::orchestrator::gemini_stream::{parse_gemini_stream_output, GeminiStream};
+use crate::orchestrator::plan_types::{Plan, WriteMode};
+use crate::orchestrator::response_cache;
+use crate::orchestrator::retry::{ErrorKind, RetryPolicy};
+use crate::orchestrator::token_usage::{parse_gemini_cli_usage, UsageByModel};
 use crate::services::files::FileService;
-use crate::state::AppState;
+use crate::state::{Agent, AgentType, AppState};
 use anyhow::anyhow;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -49,6 +62,7 @@ pub type PlannerResult = Result<Plan, AppError>;
 ///
 /// # Returns
 /// * `Ok(String)` - The full response from Gemini (extracted from JSON "response" field)
+/// * `Err(AppError::PolicyViolation)` - If the prompt matches `prompt_denylist`
 /// * `Err(AppError)` - If execution failed
 ///
 /// # Example
@@ -66,12 +80,34 @@ pub async fn internal_run_gemini(
     state: &Arc<RwLock<AppState>>,
     prompt: &str,
 ) -> Result<String, AppError> {
-    // Find or create Gemini agent (automatically applies working directory context)
-    // Now includes --output-format json for structured output
-    let agent = find_or_create_gemini_agent(state).await;
+    internal_run_gemini_with_model(state, prompt, None).await
+}
 
-    // Create executor with 30 second timeout
-    let executor = CliExecutor::new(30);
+/// Run Gemini with a prompt, optionally pinned to a specific model
+///
+/// Same as `internal_run_gemini`, but passes `--model <model>` to the CLI when
+/// `model` is set. With `None` the CLI's default model is used.
+pub async fn internal_run_gemini_with_model(
+    state: &Arc<RwLock<AppState>>,
+    prompt: &str,
+    model: Option<&str>,
+) -> Result<String, AppError> {
+    internal_run_gemini_with_usage(state, prompt, model)
+        .await
+        .map(|(output, _)| output)
+}
+
+/// Run Gemini with a prompt and report the tokens it used
+///
+/// Same as `internal_run_gemini_with_model`, but also returns the per-model
+/// token usage from the CLI's JSON stats (empty if the CLI reports none).
+pub async fn internal_run_gemini_with_usage(
+    state: &Arc<RwLock<AppState>>,
+    prompt: &str,
+    model: Option<&str>,
+) -> Result<(String, UsageByModel), AppError> {
+    let (agent, executor) = prepare_gemini_call(state, prompt, model).await?;
+    let _slot = acquire_agent_slot(state, &agent).await;
 
     // Execute and wait for full result (non-streaming)
     let raw_output = executor
@@ -79,17 +115,224 @@ pub async fn internal_run_gemini(
         .await
         .map_err(AppError::ExecutionError)?;
 
-    // Parse JSON response and extract the "response" field
-    // This separates the actual content from status messages/logs
-    parse_gemini_json_response(&raw_output).map_err(|e| {
-        AppError::ExecutionError(crate::executor::error::ExecutionError::InvalidEncoding(
-            format!(
-                "Failed to parse Gemini JSON response: {} - Response (first 500 chars): {}",
-                e,
-                raw_output.chars().take(500).collect::<String>()
-            ),
-        ))
-    })
+    // Parse JSON response: the "response" field separates the actual content
+    // from status messages/logs, and the stats carry the token usage
+    let parsed = parse_gemini_cli_response(&raw_output);
+    Ok((parsed.response, parsed.usage))
+}
+
+/// Run Gemini with a prompt and return the response bytes without decoding them
+///
+/// Same as `internal_run_gemini_with_usage`, but output that isn't valid UTF-8
+/// (and so can't be the CLI's JSON) is returned byte for byte as the response,
+/// with no usage. Used for steps that store their output base64-encoded.
+pub async fn internal_run_gemini_raw(
+    state: &Arc<RwLock<AppState>>,
+    prompt: &str,
+    model: Option<&str>,
+) -> Result<(Vec<u8>, UsageByModel), AppError> {
+    let (agent, executor) = prepare_gemini_call(state, prompt, model).await?;
+    let _slot = acquire_agent_slot(state, &agent).await;
+
+    let raw_output = executor
+        .execute_raw(&agent, prompt)
+        .await
+        .map_err(AppError::ExecutionError)?;
+
+    match String::from_utf8(raw_output) {
+        Ok(raw_output) => {
+            let parsed = parse_gemini_cli_response(&raw_output);
+            Ok((parsed.response.into_bytes(), parsed.usage))
+        }
+        Err(e) => Ok((e.into_bytes(), UsageByModel::default())),
+    }
+}
+
+/// Run Gemini with a prompt, passing the response text on as it is generated
+///
+/// Same as `internal_run_gemini_with_usage`, but runs the CLI with
+/// `--output-format stream-json` and hands each text fragment to `on_chunk`
+/// while the process is still running. The fragments concatenate to the
+/// returned response. A default agent that isn't a Gemini agent runs as usual
+/// and its whole response is passed on as one fragment.
+///
+/// # Arguments
+/// * `state` - Application state (for agent management)
+/// * `prompt` - The prompt to send to Gemini
+/// * `model` - Model to pin with `--model`, or `None` for the CLI default
+/// * `on_chunk` - Called with each fragment of the response, in order
+///
+/// # Returns
+/// * `Ok((String, UsageByModel))` - The full response and its token usage
+/// * `Err(AppError::PolicyViolation)` - If the prompt matches `prompt_denylist`
+/// * `Err(AppError)` - If execution failed
+pub async fn internal_run_gemini_streaming(
+    state: &Arc<RwLock<AppState>>,
+    prompt: &str,
+    model: Option<&str>,
+    mut on_chunk: impl FnMut(String),
+) -> Result<(String, UsageByModel), AppError> {
+    let (mut agent, executor) = prepare_gemini_call(state, prompt, model).await?;
+    if agent.agent_type != AgentType::Gemini {
+        // Only the Gemini CLI streams events; other agents answer in one piece
+        let _slot = acquire_agent_slot(state, &agent).await;
+        let raw_output = executor
+            .execute(&agent, prompt)
+            .await
+            .map_err(AppError::ExecutionError)?;
+        let parsed = parse_gemini_cli_response(&raw_output);
+        if !parsed.response.is_empty() {
+            on_chunk(parsed.response.clone());
+        }
+        return Ok((parsed.response, parsed.usage));
+    }
+    set_output_format(&mut agent, "stream-json");
+    let _slot = acquire_agent_slot(state, &agent).await;
+
+    // Bounded, so a slow listener holds back reading stdout instead of buffering it
+    let (stdout_tx, mut stdout_rx) = tokio::sync::mpsc::channel(STREAM_CHUNK_BUFFER);
+    let executor = executor.with_stdout_observer(stdout_tx);
+    let mut stream = GeminiStream::new();
+
+    let raw_output = {
+        let execution = executor.execute(&agent, prompt);
+        tokio::pin!(execution);
+        loop {
+            tokio::select! {
+                Some(bytes) = stdout_rx.recv() => {
+                    stream.push(&bytes).into_iter().for_each(&mut on_chunk);
+                }
+                result = &mut execution => break result.map_err(AppError::ExecutionError)?,
+            }
+        }
+    };
+    // Chunks read just before the process exited
+    while let Ok(bytes) = stdout_rx.try_recv() {
+        stream.push(&bytes).into_iter().for_each(&mut on_chunk);
+    }
+    stream.finish().into_iter().for_each(&mut on_chunk);
+
+    let parsed = parse_gemini_stream_output(&raw_output, model);
+    Ok((parsed.response, parsed.usage))
+}
+
+/// Check the prompt and set up the agent and executor for one Gemini CLI call
+async fn prepare_gemini_call(
+    state: &Arc<RwLock<AppState>>,
+    prompt: &str,
+    model: Option<&str>,
+) -> Result<(Agent, CliExecutor), AppError> {
+    // Reject denylisted prompts before anything reaches the CLI
+    {
+        let state = state.read().await;
+        check_prompt_policy(prompt, &state.orchestrator_config.prompt_denylist)?;
+    }
+
+    // Find or create the default agent (automatically applies working directory
+    // context); Gemini agents get --output-format json for structured output
+    let mut agent = find_or_create_default_agent(state).await;
+    if let Some(model) = model {
+        agent
+            .config
+            .args
+            .extend(["--model".to_string(), model.to_string()]);
+    }
+
+    // Create executor with 30 second timeout
+    let (output_limit, fallback_dir) = {
+        let state_read = state.read().await;
+        (
+            state_read.cli_output_limit,
+            state_read.cli_fallback_working_dir.clone(),
+        )
+    };
+    let executor = CliExecutor::new(30)
+        .with_output_limit(output_limit)
+        .with_fallback_working_dir(fallback_dir);
+    Ok((agent, executor))
+}
+
+/// Reject a prompt matching any of the operator's denylist patterns
+///
+/// # Arguments
+/// * `prompt` - The prompt about to be sent to Gemini
+/// * `denylist` - Regex patterns (`OrchestratorConfig.prompt_denylist`)
+///
+/// # Returns
+/// * `Ok(())` - If no pattern matches
+/// * `Err(AppError::PolicyViolation)` - Naming the first matching pattern
+pub fn check_prompt_policy(prompt: &str, denylist: &[String]) -> Result<(), AppError> {
+    for pattern in denylist {
+        let regex = regex::Regex::new(pattern).map_err(|e| {
+            AppError::Internal(anyhow!(
+                "Invalid prompt_denylist pattern '{}': {}",
+                pattern,
+                e
+            ))
+        })?;
+        if regex.is_match(prompt) {
+            tracing::warn!(pattern = %pattern, "Prompt blocked by denylist");
+            return Err(AppError::PolicyViolation(format!(
+                "Prompt matches blocked pattern '{}'",
+                pattern
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Whether a failed Gemini call may succeed on a different model
+///
+/// Rate limits, quota exhaustion, overloaded/unavailable models and timeouts are
+/// retryable; anything else (bad prompt, missing CLI, unparseable output) would
+/// fail the same way on every model.
+pub fn is_retryable_model_error(error: &AppError) -> bool {
+    matches!(
+        ErrorKind::classify(error),
+        ErrorKind::RateLimited | ErrorKind::Unavailable | ErrorKind::Timeout
+    )
+}
+
+/// Try each model in order until one succeeds or fails with a non-retryable error
+///
+/// `models` is the primary model followed by the configured fallbacks. The call
+/// moves on to the next model only when `is_retryable_model_error` says the
+/// failure was model-specific; the last error is returned if every model fails.
+///
+/// # Arguments
+/// * `models` - Models to try, primary first
+/// * `call` - Runs the request against one model
+///
+/// # Returns
+/// * `Ok((output, model))` - The output and the model that actually served it
+/// * `Err(AppError)` - The first non-retryable error, or the last model's error
+pub async fn run_with_model_fallbacks<T, F, Fut>(
+    models: &[String],
+    mut call: F,
+) -> Result<(T, String), AppError>
+where
+    F: FnMut(String) -> Fut,
+    Fut: std::future::Future<Output = Result<T, AppError>>,
+{
+    let mut last_error = None;
+
+    for (attempt, model) in models.iter().enumerate() {
+        match call(model.clone()).await {
+            Ok(output) => return Ok((output, model.clone())),
+            Err(e) if is_retryable_model_error(&e) && attempt + 1 < models.len() => {
+                tracing::warn!(
+                    model = %model,
+                    next_model = %models[attempt + 1],
+                    error = %e,
+                    "Gemini model unavailable, falling back"
+                );
+                last_error = Some(e);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| AppError::Internal(anyhow!("No Gemini models configured"))))
 }
 
 /// Parse Gemini CLI JSON response and extract the actual content
@@ -111,36 +354,73 @@ pub async fn internal_run_gemini(
 ///
 /// # Returns
 /// * `Result<String, serde_json::Error>` - Extracted response content or parsing error
+///
+/// Use `parse_gemini_cli_response` to keep the model and stats as well.
 pub fn parse_gemini_json_response(response: &str) -> Result<String, serde_json::Error> {
+    Ok(parse_gemini_cli_response(response).response)
+}
+
+/// A Gemini CLI reply: the response text plus the metadata reported with it
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct GeminiCliResponse {
+    /// The actual content (the `response` field, or the raw output without one)
+    pub response: String,
+    /// Model that answered, if the CLI reported it
+    pub model: Option<String>,
+    /// Token usage per model, from `stats.models`
+    pub usage: UsageByModel,
+    /// The raw `stats` object (tool calls, thought tokens, latencies, ...)
+    pub stats: Option<serde_json::Value>,
+}
+
+/// Parse Gemini CLI JSON output, keeping the metadata alongside the response
+///
+/// The response text follows the same fallbacks as `parse_gemini_json_response`;
+/// output that isn't a JSON object carries no metadata. The model is the
+/// top-level `model` field, or the only model listed in the stats.
+///
+/// # Arguments
+/// * `response` - Raw JSON response string from Gemini CLI
+pub fn parse_gemini_cli_response(response: &str) -> GeminiCliResponse {
     // First, try to parse as a JSON object with a "response" field
     #[derive(Deserialize)]
     struct GeminiResponse {
         response: Option<String>,
+        model: Option<serde_json::Value>,
+        stats: Option<serde_json::Value>,
     }
 
     match serde_json::from_str::<GeminiResponse>(response) {
         Ok(parsed) => {
-            // Extract the response field (the actual content)
-            if let Some(content) = parsed.response {
-                Ok(content)
-            } else {
+            let usage = parse_gemini_cli_usage(response);
+            let model = parsed
+                .model
+                .as_ref()
+                .and_then(|model| model.as_str())
+                .map(str::to_string)
+                .or_else(|| match usage.keys().collect::<Vec<_>>().as_slice() {
+                    [only] => Some((*only).clone()),
+                    _ => None,
+                });
+            GeminiCliResponse {
                 // If no "response" field, fall back to raw response
                 // (handle edge cases where structure might differ)
-                Ok(response.trim().to_string())
+                response: parsed
+                    .response
+                    .unwrap_or_else(|| response.trim().to_string()),
+                model,
+                usage,
+                stats: parsed.stats,
             }
         }
-        Err(_) => {
+        Err(_) => GeminiCliResponse {
             // If parsing as GeminiResponse fails, try parsing as plain JSON string
-            // (some versions might return just a JSON string)
-            match serde_json::from_str::<String>(response) {
-                Ok(content) => Ok(content),
-                Err(_) => {
-                    // If all JSON parsing fails, return the raw response as-is
-                    // (fallback for non-JSON output - should not happen with --output-format json)
-                    Ok(response.to_string())
-                }
-            }
-        }
+            // (some versions might return just a JSON string); failing that, return
+            // the raw response as-is (should not happen with --output-format json)
+            response: serde_json::from_str::<String>(response)
+                .unwrap_or_else(|_| response.to_string()),
+            ..GeminiCliResponse::default()
+        },
     }
 }
 
@@ -151,12 +431,16 @@ pub fn parse_gemini_json_response(response: &str) -> Result<String, serde_json::
 ///
 /// # Arguments
 /// * `file_path` - Path to the file (can be relative or absolute)
-/// * `content` - Content to write to the file
-/// * `working_dir` - Optional working directory context (for relative paths)
+/// * `content` - Content to write to the file (text or raw bytes)
+/// * `working_dir` - Working directory context; required for relative paths
+///
+/// Relative paths are never resolved against the process's current directory:
+/// without a working directory they are rejected with `AppError::InvalidPath`.
 ///
 /// # Returns
 /// * `Ok(String)` - The canonicalized absolute path of the created file
-/// * `Err(AppError)` - If file cannot be created or written
+/// * `Err(AppError)` - If file cannot be created or written, or a relative path
+///   was given with no working directory set
 ///
 /// # Example
 /// ```no_run
@@ -172,40 +456,132 @@ pub fn parse_gemini_json_response(response: &str) -> Result<String, serde_json::
 /// ```
 pub async fn internal_create_file(
     file_path: &str,
-    content: &str,
+    content: impl AsRef<[u8]>,
     working_dir: Option<&str>,
 ) -> Result<String, AppError> {
-    let canonical_path = FileService::write_file(file_path, content, working_dir).await?;
+    internal_write_file(file_path, content, working_dir, WriteMode::Overwrite, None).await
+}
+
+/// Write or append to a file, enforcing `MAX_FILE_WRITE_BYTES`
+///
+/// Same path rules as [`internal_create_file`]. In `Append` mode the file is
+/// created if missing, and the cap applies to its size after appending. A
+/// `file_mode` is given to the file as it is created, on Unix only.
+///
+/// # Returns
+/// * `Ok(String)` - The canonicalized absolute path of the written file
+/// * `Err(AppError)` - If the path is invalid, the write fails, or the file
+///   would exceed `MAX_FILE_WRITE_BYTES`
+pub async fn internal_write_file(
+    file_path: &str,
+    content: impl AsRef<[u8]>,
+    working_dir: Option<&str>,
+    mode: WriteMode,
+    file_mode: Option<u32>,
+) -> Result<String, AppError> {
+    check_write_target(file_path, working_dir)?;
+
+    let canonical_path = match mode {
+        WriteMode::Overwrite => {
+            check_write_size(file_path, content.as_ref())?;
+            FileService::write_file(file_path, content, working_dir, file_mode).await?
+        }
+        WriteMode::Append => {
+            FileService::append_file(
+                file_path,
+                content,
+                working_dir,
+                MAX_FILE_WRITE_BYTES,
+                file_mode,
+            )
+            .await?
+        }
+    };
     Ok(canonical_path.to_string_lossy().to_string())
 }
 
+/// Overwrite a file unless it already holds exactly `content`
+///
+/// Same path rules and size cap as [`internal_create_file`]. Skipping an
+/// identical write keeps the file's mtime, so file watchers aren't triggered.
+///
+/// # Returns
+/// * `Ok((String, bool))` - The canonicalized path, and whether the file was unchanged
+/// * `Err(AppError)` - If the path is invalid, the write fails, or the content
+///   exceeds `MAX_FILE_WRITE_BYTES`
+pub async fn internal_write_file_if_changed(
+    file_path: &str,
+    content: impl AsRef<[u8]>,
+    working_dir: Option<&str>,
+    file_mode: Option<u32>,
+) -> Result<(String, bool), AppError> {
+    check_write_target(file_path, working_dir)?;
+    check_write_size(file_path, content.as_ref())?;
+
+    let (canonical_path, written) =
+        FileService::write_file_if_changed(file_path, content, working_dir, file_mode).await?;
+    Ok((canonical_path.to_string_lossy().to_string(), !written))
+}
+
+/// Reject relative paths when there is no working directory to resolve them against
+fn check_write_target(file_path: &str, working_dir: Option<&str>) -> Result<(), AppError> {
+    if working_dir.is_none() && std::path::Path::new(file_path).is_relative() {
+        return Err(AppError::InvalidPath(format!(
+            "Cannot write relative path '{}': no working directory is set. \
+             Set one via POST /api/files/working-directory or use an absolute path",
+            file_path
+        )));
+    }
+    Ok(())
+}
+
+/// Reject content larger than `MAX_FILE_WRITE_BYTES`
+fn check_write_size(file_path: &str, content: &[u8]) -> Result<(), AppError> {
+    let len = content.len() as u64;
+    if len > MAX_FILE_WRITE_BYTES {
+        return Err(AppError::PolicyViolation(format!(
+            "Content for {} is {} bytes (maximum {})",
+            file_path, len, MAX_FILE_WRITE_BYTES
+        )));
+    }
+    Ok(())
+}
+
 /// Run Gemini API directly with structured JSON support
 ///
 /// This is a wrapper around the direct Gemini API client.
 /// Used for "Planner" calls that need reliable JSON output.
 ///
-/// This function reads the API key from the `GEMINI_API_KEY` environment variable
-/// and makes a direct HTTP request to the Gemini API, bypassing the CLI wrapper.
+/// This function reads the API key from `config.gemini_api_key_file` if set, else
+/// from the `GEMINI_API_KEY` environment variable, and makes a direct HTTP request
+/// to the Gemini API, bypassing the CLI wrapper.
 ///
 /// # Arguments
 /// * `prompt` - The prompt to send to Gemini
 /// * `force_json` - If true, request JSON response format (required for planner)
+/// * `config` - Orchestrator config (`prompt_denylist`, `gemini_api_key_file`,
+///   `allow_empty_gemini_response`)
 ///
 /// # Returns
-/// * `Ok(String)` - The response text from Gemini
-/// * `Err(AppError)` - If API call failed or API key missing
+/// * `Ok(String)` - The response text from Gemini (empty only if
+///   `allow_empty_gemini_response` is set and `force_json` is not)
+/// * `Err(AppError::PolicyViolation)` - If the prompt matches `prompt_denylist`
+/// * `Err(AppError)` - If API call failed or the API key is missing
 ///
 /// # Example
 /// ```no_run
 /// use agent_manager_backend::{error::AppError, orchestrator::primitives::internal_run_gemini_api};
+/// use agent_manager_backend::orchestrator::config::OrchestratorConfig;
 /// use reqwest::Client;
 /// # async fn example() -> Result<(), AppError> {
 /// # let client = Client::new();
+/// let config = OrchestratorConfig::default();
 /// // Regular prompt (unstructured output)
 /// let response = internal_run_gemini_api(
 ///     &client,
 ///     "Write a haiku about programming",
 ///     false,
+///     &config,
 /// ).await?;
 ///
 /// // Planner prompt (structured JSON output)
@@ -213,6 +589,7 @@ pub async fn internal_create_file(
 ///     &client,
 ///     "Generate a JSON plan with steps",
 ///     true,  // Force JSON mode
+///     &config,
 /// ).await?;
 /// # Ok(())
 /// # }
@@ -222,21 +599,48 @@ pub async fn internal_run_gemini_api(
     client: &reqwest::Client,
     prompt: &str,
     force_json: bool,
+    config: &OrchestratorConfig,
 ) -> Result<String, AppError> {
-    // Read API key from environment
-    let api_key = match std::env::var("GEMINI_API_KEY") {
-        Ok(key) if key.is_empty() => {
-            return Err(AppError::Internal(anyhow!(
-                "GEMINI_API_KEY environment variable is not set or is empty. Please set it to use the Gemini API."
-            )));
-        }
-        Ok(key) => key,
-        Err(_) => {
-            return Err(AppError::Internal(anyhow!(
-                "GEMINI_API_KEY environment variable is not set or is empty. Please set it to use the Gemini API."
-            )));
+    run_gemini_api_with_base_url(
+        client,
+        prompt,
+        force_json,
+        config,
+        api_client::GEMINI_API_BASE_URL,
+    )
+    .await
+}
+
+/// `internal_run_gemini_api` against a custom base URL (for testing)
+async fn run_gemini_api_with_base_url(
+    client: &reqwest::Client,
+    prompt: &str,
+    force_json: bool,
+    config: &OrchestratorConfig,
+    base_url: &str,
+) -> Result<String, AppError> {
+    check_prompt_policy(prompt, &config.prompt_denylist)?;
+
+    // An identical earlier request may already have been answered
+    let cache = config.gemini_cache_ttl_secs.map(|ttl| {
+        let model = OrchestratorConfig::default().gemini_model;
+        (
+            response_cache::cache_key(prompt, &model, force_json),
+            std::time::Duration::from_secs(ttl),
+        )
+    });
+    if let Some((key, ttl)) = &cache {
+        if let Some(reply) = response_cache::get(key, *ttl) {
+            tracing::debug!(
+                prompt_len = prompt.len(),
+                "Gemini API reply served from cache"
+            );
+            return Ok(reply);
         }
-    };
+    }
+
+    // Key file if configured, otherwise GEMINI_API_KEY
+    let api_key = resolve_gemini_api_key(config)?;
 
     tracing::debug!(
         prompt_len = prompt.len(),
@@ -245,7 +649,25 @@ pub async fn internal_run_gemini_api(
     );
 
     // Call the API client with shared HTTP client
-    api_client::call_gemini_api(client, &api_key, prompt, None, force_json).await
+    let reply = api_client::call_gemini_api_with_base_url(
+        client,
+        &api_key,
+        prompt,
+        None,
+        force_json,
+        // The planner needs JSON, so only worker prompts may come back empty
+        config.allow_empty_gemini_response && !force_json,
+        config.max_prompt_length,
+        base_url,
+        &RetryPolicy::gemini_api(),
+    )
+    .await?;
+
+    // Errors never get here; `put` skips empty replies
+    if let Some((key, ttl)) = cache {
+        response_cache::put(key, &reply, ttl);
+    }
+    Ok(reply)
 }
 
 /// Run the planner agent to generate a structured plan
@@ -278,6 +700,18 @@ pub async fn internal_run_gemini_api(
 /// # }
 /// ```
 pub async fn internal_run_planner(state: &Arc<RwLock<AppState>>, goal: &str) -> PlannerResult {
+    internal_run_planner_cancellable(state, goal, &CancellationToken::new()).await
+}
+
+/// `internal_run_planner` that gives up as soon as `cancel` fires
+///
+/// The in-flight planner call (and any retry) is dropped, killing the CLI
+/// process, and `AppError::Cancelled` is returned.
+pub async fn internal_run_planner_cancellable(
+    state: &Arc<RwLock<AppState>>,
+    goal: &str,
+    cancel: &CancellationToken,
+) -> PlannerResult {
     // Create structured logging span for planner execution
     use crate::orchestrator::utils::hash_goal;
     let goal_hash = hash_goal(goal);
@@ -289,13 +723,24 @@ pub async fn internal_run_planner(state: &Arc<RwLock<AppState>>, goal: &str) ->
     );
     let _enter = span.enter();
 
-    // Build the meta-prompt
-    let meta_prompt = build_meta_prompt(goal);
+    // Build the meta-prompt, with any deployment-specific examples
+    let examples = state
+        .read()
+        .await
+        .orchestrator_config
+        .planner_examples
+        .clone();
+    let meta_prompt = build_meta_prompt(goal, &examples);
 
     tracing::debug!("Calling planner agent to generate plan via CLI");
 
     // Try planning (with one retry on failure)
-    let plan_result = try_plan_once(state, &meta_prompt).await;
+    let plan_result = cancel
+        .run(
+            "Planning",
+            RetryPolicy::planner().run("planner", || try_plan_once(state, &meta_prompt)),
+        )
+        .await;
 
     match plan_result {
         Ok(plan) => {
@@ -306,32 +751,16 @@ pub async fn internal_run_planner(state: &Arc<RwLock<AppState>>, goal: &str) ->
             );
             Ok(plan)
         }
+        Err(e @ AppError::Cancelled(_)) => {
+            tracing::info!("Planner cancelled");
+            Err(e)
+        }
         Err(e) => {
-            tracing::warn!(
+            tracing::error!(
                 error = %e,
-                "Planner failed, retrying once"
+                "Planner failed after retry"
             );
-
-            // Retry once with the same prompt
-            let retry_result = try_plan_once(state, &meta_prompt).await;
-
-            match retry_result {
-                Ok(plan) => {
-                    tracing::debug!(
-                        plan_version = %plan.version,
-                        num_steps = plan.steps.len(),
-                        "Planner succeeded on retry"
-                    );
-                    Ok(plan)
-                }
-                Err(retry_error) => {
-                    tracing::error!(
-                        error = %retry_error,
-                        "Planner failed after retry"
-                    );
-                    Err(retry_error)
-                }
-            }
+            Err(e)
         }
     }
 }
@@ -342,9 +771,19 @@ async fn try_plan_once(state: &Arc<RwLock<AppState>>, meta_prompt: &str) -> Plan
     let agent = find_or_create_planner_agent(state).await;
 
     // Create executor with 30 second timeout
-    let executor = crate::executor::cli::CliExecutor::new(30);
+    let (output_limit, fallback_dir) = {
+        let state_read = state.read().await;
+        (
+            state_read.cli_output_limit,
+            state_read.cli_fallback_working_dir.clone(),
+        )
+    };
+    let executor = CliExecutor::new(30)
+        .with_output_limit(output_limit)
+        .with_fallback_working_dir(fallback_dir);
 
     // Execute planner prompt and get JSON response
+    let _slot = acquire_agent_slot(state, &agent).await;
     let json_response = executor
         .execute(&agent, meta_prompt)
         .await
@@ -358,13 +797,8 @@ async fn try_plan_once(state: &Arc<RwLock<AppState>>, meta_prompt: &str) -> Plan
     // Parse JSON to Plan struct
     // Gemini CLI with --output-format json may return a wrapped response with the Plan JSON
     // inside a "response" field as a markdown code block. Handle both formats.
-    let plan: Plan = parse_planner_response(&json_response).map_err(|e| {
-        AppError::InvalidPlan(format!(
-            "Failed to parse planner response as JSON: {} - Response (first 500 chars): {}",
-            e,
-            json_response.chars().take(500).collect::<String>()
-        ))
-    })?;
+    let plan: Plan = parse_planner_response(&json_response)
+        .map_err(|e| planner_parse_error(&e, &json_response))?;
 
     // Validate the plan structure
     plan.validate().map_err(|validation_error| {
@@ -374,6 +808,34 @@ async fn try_plan_once(state: &Arc<RwLock<AppState>>, meta_prompt: &str) -> Plan
     Ok(plan)
 }
 
+/// Build the error for a planner response that could not be parsed
+///
+/// Only the first `MAX_PLANNER_ERROR_RESPONSE_CHARS` characters of the response
+/// are embedded, followed by a `[response_truncated: N chars total]` marker when
+/// it was clipped; the full length is logged.
+fn planner_parse_error(error: &serde_json::Error, response: &str) -> AppError {
+    let total_chars = response.chars().count();
+    let mut excerpt: String = response
+        .chars()
+        .take(MAX_PLANNER_ERROR_RESPONSE_CHARS)
+        .collect();
+    if total_chars > MAX_PLANNER_ERROR_RESPONSE_CHARS {
+        tracing::warn!(
+            response_len = response.len(),
+            response_chars = total_chars,
+            "Unparseable planner response truncated in error"
+        );
+        excerpt.push_str(&format!(
+            " [response_truncated: {} chars total]",
+            total_chars
+        ));
+    }
+    AppError::InvalidPlan(format!(
+        "Failed to parse planner response as JSON: {} - Response: {}",
+        error, excerpt
+    ))
+}
+
 /// Parse planner response from Gemini CLI
 ///
 /// Handles two response formats:
@@ -453,13 +915,25 @@ fn parse_planner_response(response: &str) -> Result<Plan, serde_json::Error> {
 }
 
 /// Build the meta-prompt for the planner agent
-fn build_meta_prompt(goal: &str) -> String {
+///
+/// `examples` (from `planner_examples`) follow the built-in examples.
+fn build_meta_prompt(goal: &str, examples: &[PlannerExample]) -> String {
+    let mut configured_examples = String::new();
+    for example in examples {
+        let plan_json = serde_json::to_string(&example.plan).unwrap_or_default();
+        configured_examples.push_str(&format!(
+            "Example for goal \"{}\":\n{}\n\n",
+            example.goal, plan_json
+        ));
+    }
+
     format!(
         r#"You are a planner agent. Your job is to take a user's GOAL and break it down into a JSON plan with steps.
 
 Available Tools:
 1. run_gemini: Runs a prompt through Gemini and returns text output. Parameters: {{"prompt": "..."}}
 2. create_file: Saves text content to a file. Parameters: {{"filename": "...", "content_from": "step_X.output"}}
+3. for_each: Runs a template step once per item, in parallel; its output is a JSON array of the results. Parameters: {{"items": ["...", "..."], "template": {{"task": "run_gemini", "params": {{"prompt": "... {{{{item}}}} ..."}}}}}} (use "items_from": "step_X.output" instead of "items" when step_X outputs a JSON array)
 
 Output Format (JSON):
 {{
@@ -494,7 +968,7 @@ CRITICAL REQUIREMENT - Dependencies Array:
 
 Important Rules:
 - Each step must have a unique "id" (e.g., "step_1", "step_2")
-- The "task" must be one of: "run_gemini", "create_file"
+- The "task" must be one of: "run_gemini", "create_file", "for_each"
 - For "create_file" tasks, use "content_from" to reference another step's output (e.g., "step_1.output")
 - Steps with empty "dependencies" can run in parallel with other independent steps
 
@@ -518,10 +992,10 @@ Parallel Plan (step_1, step_2, step_3 can run simultaneously):
   ]
 }}
 
-GOAL: "{}"
+{}GOAL: "{}"
 
 Generate a JSON plan with the steps needed to accomplish this goal. Remember: EVERY step MUST have a "dependencies" array. Return ONLY valid JSON, no other text."#,
-        goal
+        configured_examples, goal
     )
 }
 
@@ -537,6 +1011,14 @@ mod tests {
         Arc::new(RwLock::new(AppState::new()))
     }
 
+    /// Config reading the API key from `GEMINI_API_KEY`, whatever the test environment
+    fn env_key_config() -> OrchestratorConfig {
+        OrchestratorConfig {
+            gemini_api_key_file: None,
+            ..OrchestratorConfig::default()
+        }
+    }
+
     fn build_test_client() -> reqwest::Client {
         reqwest::Client::builder()
             .no_proxy()
@@ -581,6 +1063,23 @@ mod tests {
         assert_eq!(written_content, content);
     }
 
+    #[tokio::test]
+    async fn test_internal_create_file_relative_path_requires_working_dir() {
+        let file_name = format!("unset-working-dir-{}.txt", uuid::Uuid::new_v4());
+
+        let result = internal_create_file(&file_name, "content", None).await;
+
+        match result {
+            Err(AppError::InvalidPath(message)) => {
+                assert!(message.contains("no working directory is set"));
+                assert!(message.contains(&file_name));
+            }
+            other => panic!("Expected InvalidPath error, got {:?}", other),
+        }
+        // Nothing was written relative to the process CWD
+        assert!(!std::path::Path::new(&file_name).exists());
+    }
+
     #[tokio::test]
     async fn test_internal_create_file_creates_parent_dirs() {
         let temp_dir = tempdir().expect("Failed to create temp dir");
@@ -599,6 +1098,118 @@ mod tests {
         assert!(parent.ends_with("deep/path"));
     }
 
+    #[tokio::test]
+    async fn test_internal_run_gemini_strips_json_wrapper() {
+        use crate::state::AgentType;
+        use std::os::unix::fs::PermissionsExt;
+
+        // Answers with the JSON wrapper only when asked for JSON output
+        let temp_dir = tempdir().unwrap();
+        let script = temp_dir.path().join("gemini-mock.sh");
+        std::fs::write(
+            &script,
+            concat!(
+                "#!/bin/sh\n",
+                "case \"$*\" in\n",
+                "  *\"--output-format json\"*) echo '{\"response\": \"plain answer\", \"stats\": {}}' ;;\n",
+                "  *) echo 'Loaded cached credentials.'; echo 'raw answer' ;;\n",
+                "esac\n",
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        // Even an agent configured for text output is run with JSON
+        for configured in ["--output-format=text", "--output-format text"] {
+            let state = create_test_state();
+            let mut agent = Agent::new(
+                "gemini-1".to_string(),
+                "Gemini Mock".to_string(),
+                AgentType::Gemini,
+            );
+            agent.config.command = script.to_string_lossy().to_string();
+            agent.config.args = configured.split(' ').map(str::to_string).collect();
+            state.write().await.add_agent(agent);
+
+            let output = internal_run_gemini(&state, "hi").await.unwrap();
+            assert_eq!(output, "plain answer", "configured with {}", configured);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streaming_on_other_agent_types_sends_one_chunk() {
+        use crate::state::AgentType;
+
+        let state = create_test_state();
+        {
+            let mut state = state.write().await;
+            state.set_default_agent_type(AgentType::Generic);
+            let mut agent =
+                Agent::new("echo-1".to_string(), "Echo".to_string(), AgentType::Generic);
+            agent.config.command = "echo".to_string();
+            agent.config.args = vec![];
+            state.add_agent(agent);
+        }
+
+        // echo prints its arguments, so a forced --output-format would show up
+        let mut chunks = Vec::new();
+        let (output, _) =
+            internal_run_gemini_streaming(&state, "hello", None, |chunk| chunks.push(chunk))
+                .await
+                .unwrap();
+        assert_eq!(output, "hello\n");
+        assert_eq!(chunks, vec!["hello\n".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_cancelling_slow_planner_returns_promptly() {
+        use crate::state::AgentType;
+        use std::os::unix::fs::PermissionsExt;
+        use std::time::{Duration, Instant};
+
+        // A planner that takes far longer than the test should
+        let temp_dir = tempdir().unwrap();
+        let script = temp_dir.path().join("slow-planner.sh");
+        std::fs::write(
+            &script,
+            "#!/bin/sh
+sleep 20
+",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let state = create_test_state();
+        let mut agent = Agent::new(
+            "gemini-1".to_string(),
+            "Gemini Mock".to_string(),
+            AgentType::Gemini,
+        );
+        agent.config.command = script.to_string_lossy().to_string();
+        agent.config.args = vec![];
+        state.write().await.add_agent(agent);
+
+        let cancel = CancellationToken::new();
+        let stopper = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            stopper.cancel();
+        });
+
+        let started = Instant::now();
+        let result = internal_run_planner_cancellable(&state, "Write a poem", &cancel).await;
+        assert!(
+            matches!(result, Err(AppError::Cancelled(_))),
+            "expected Cancelled, got {:?}",
+            result
+        );
+        assert!(
+            started.elapsed() < Duration::from_secs(2),
+            "planning took {:?} to stop",
+            started.elapsed()
+        );
+    }
+
     #[tokio::test]
     async fn test_internal_run_gemini_with_state() {
         // This test verifies that internal_run_gemini can create a Gemini agent
@@ -645,7 +1256,8 @@ mod tests {
 
         // Create test HTTP client
         let client = build_test_client();
-        let result = internal_run_gemini_api(&client, "test prompt", false).await;
+        let config = env_key_config();
+        let result = internal_run_gemini_api(&client, "test prompt", false, &config).await;
 
         assert!(result.is_err());
         let error_msg = result.unwrap_err().to_string();
@@ -674,7 +1286,8 @@ mod tests {
 
         // Create test HTTP client
         let client = build_test_client();
-        let result = internal_run_gemini_api(&client, "test prompt", false).await;
+        let config = env_key_config();
+        let result = internal_run_gemini_api(&client, "test prompt", false, &config).await;
 
         assert!(result.is_err());
         let error_msg = result.unwrap_err().to_string();
@@ -692,6 +1305,320 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_check_write_size_is_a_policy_violation() {
+        let at_cap = vec![0u8; MAX_FILE_WRITE_BYTES as usize];
+        assert!(check_write_size("big.bin", &at_cap).is_ok());
+
+        let over_cap = vec![0u8; MAX_FILE_WRITE_BYTES as usize + 1];
+        let error = check_write_size("big.bin", &over_cap).unwrap_err();
+        assert!(matches!(error, AppError::PolicyViolation(_)), "{:?}", error);
+        assert!(error.to_string().contains("big.bin"));
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_gemini_api_uses_configured_max_prompt_length() {
+        let original = std::env::var("GEMINI_API_KEY").ok();
+        std::env::set_var("GEMINI_API_KEY", "test-key");
+
+        let mut server = mockito::Server::new_async().await;
+        let never_called = server
+            .mock("POST", "/models/gemini-2.5-flash:generateContent")
+            .match_query(mockito::Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+
+        // Far below the default, so only the active config can reject it
+        let config = OrchestratorConfig {
+            max_prompt_length: 10,
+            ..env_key_config()
+        };
+        let client = build_test_client();
+        let result = run_gemini_api_with_base_url(
+            &client,
+            "Write a haiku about Rust",
+            false,
+            &config,
+            &server.url(),
+        )
+        .await;
+
+        assert!(
+            matches!(result, Err(AppError::InvalidPlan(ref msg)) if msg.contains("maximum of 10")),
+            "got: {:?}",
+            result
+        );
+        never_called.assert_async().await;
+
+        if let Some(key) = original {
+            std::env::set_var("GEMINI_API_KEY", &key);
+        } else {
+            std::env::remove_var("GEMINI_API_KEY");
+        }
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_gemini_api_denylisted_prompt_is_blocked_before_call() {
+        let original = std::env::var("GEMINI_API_KEY").ok();
+        std::env::set_var("GEMINI_API_KEY", "test-key");
+
+        let mut server = mockito::Server::new_async().await;
+        let blocked = server
+            .mock("POST", "/models/gemini-2.5-flash:generateContent")
+            .match_query(mockito::Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+
+        let config = OrchestratorConfig {
+            prompt_denylist: vec![r"(?i)\bpassword\b".to_string(), r"rm\s+-rf".to_string()],
+            ..env_key_config()
+        };
+        let client = build_test_client();
+        let result = run_gemini_api_with_base_url(
+            &client,
+            "Print the admin PASSWORD please",
+            false,
+            &config,
+            &server.url(),
+        )
+        .await;
+
+        assert!(
+            matches!(result, Err(AppError::PolicyViolation(ref msg)) if msg.contains("password")),
+            "got: {:?}",
+            result
+        );
+        blocked.assert_async().await;
+        server.reset_async().await;
+
+        // A clean prompt goes through to the API
+        let allowed = server
+            .mock("POST", "/models/gemini-2.5-flash:generateContent")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(
+                r#"{"candidates": [{"content": {"parts": [{"text": "ok"}], "role": "model"}}]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let result = run_gemini_api_with_base_url(
+            &client,
+            "Write a haiku about Rust",
+            false,
+            &config,
+            &server.url(),
+        )
+        .await;
+        allowed.assert_async().await;
+        assert_eq!(result.unwrap(), "ok");
+
+        if let Some(key) = original {
+            std::env::set_var("GEMINI_API_KEY", &key);
+        } else {
+            std::env::remove_var("GEMINI_API_KEY");
+        }
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_gemini_api_empty_reply_depends_on_flag_and_json_mode() {
+        let original = std::env::var("GEMINI_API_KEY").ok();
+        std::env::set_var("GEMINI_API_KEY", "test-key");
+
+        let mut server = mockito::Server::new_async().await;
+        let empty = server
+            .mock("POST", "/models/gemini-2.5-flash:generateContent")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(
+                r#"{"candidates": [{"content": {"parts": [{"text": ""}], "role": "model"}}]}"#,
+            )
+            .expect(4)
+            .create_async()
+            .await;
+        let client = build_test_client();
+        let run = |config: OrchestratorConfig, force_json: bool| {
+            let client = client.clone();
+            let url = server.url();
+            async move {
+                run_gemini_api_with_base_url(&client, "Say nothing", force_json, &config, &url)
+                    .await
+            }
+        };
+
+        // Rejected by default
+        let result = run(env_key_config(), false).await;
+        assert!(
+            result
+                .as_ref()
+                .is_err_and(|e| e.to_string().contains("empty")),
+            "got: {:?}",
+            result
+        );
+
+        // Accepted for worker prompts once allowed
+        let allowing = OrchestratorConfig {
+            allow_empty_gemini_response: true,
+            ..env_key_config()
+        };
+        assert_eq!(run(allowing.clone(), false).await.unwrap(), "");
+
+        // The planner path needs JSON, so it always rejects an empty reply
+        assert!(run(allowing, true).await.is_err());
+        assert!(run(env_key_config(), true).await.is_err());
+        empty.assert_async().await;
+
+        if let Some(key) = original {
+            std::env::set_var("GEMINI_API_KEY", &key);
+        } else {
+            std::env::remove_var("GEMINI_API_KEY");
+        }
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_gemini_api_identical_prompts_use_cache_when_enabled() {
+        let original = std::env::var("GEMINI_API_KEY").ok();
+        std::env::set_var("GEMINI_API_KEY", "test-key");
+
+        let mut server = mockito::Server::new_async().await;
+        let client = build_test_client();
+        let caching = OrchestratorConfig {
+            gemini_cache_ttl_secs: Some(60),
+            allow_empty_gemini_response: true,
+            ..env_key_config()
+        };
+
+        // Two identical prompts reach the API once
+        let ok = server
+            .mock("POST", "/models/gemini-2.5-flash:generateContent")
+            .match_query(mockito::Matcher::Any)
+            .with_body(
+                r#"{"candidates": [{"content": {"parts": [{"text": "ok"}], "role": "model"}}]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        for _ in 0..2 {
+            let reply = run_gemini_api_with_base_url(
+                &client,
+                "Cache test: name a colour",
+                false,
+                &caching,
+                &server.url(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(reply, "ok");
+        }
+        ok.assert_async().await;
+        ok.remove_async().await;
+
+        // Without a TTL every call goes to the API
+        let uncached = server
+            .mock("POST", "/models/gemini-2.5-flash:generateContent")
+            .match_query(mockito::Matcher::Any)
+            .with_body(
+                r#"{"candidates": [{"content": {"parts": [{"text": "ok"}], "role": "model"}}]}"#,
+            )
+            .expect(2)
+            .create_async()
+            .await;
+        for _ in 0..2 {
+            run_gemini_api_with_base_url(
+                &client,
+                "Cache test: name a shape",
+                false,
+                &env_key_config(),
+                &server.url(),
+            )
+            .await
+            .unwrap();
+        }
+        uncached.assert_async().await;
+        uncached.remove_async().await;
+
+        // Empty replies and errors are never cached
+        let empty = server
+            .mock("POST", "/models/gemini-2.5-flash:generateContent")
+            .match_query(mockito::Matcher::Any)
+            .with_body(
+                r#"{"candidates": [{"content": {"parts": [{"text": ""}], "role": "model"}}]}"#,
+            )
+            .expect(2)
+            .create_async()
+            .await;
+        for _ in 0..2 {
+            let reply = run_gemini_api_with_base_url(
+                &client,
+                "Cache test: say nothing",
+                false,
+                &caching,
+                &server.url(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(reply, "");
+        }
+        empty.assert_async().await;
+        empty.remove_async().await;
+
+        let failing = server
+            .mock("POST", "/models/gemini-2.5-flash:generateContent")
+            .match_query(mockito::Matcher::Any)
+            .with_status(400)
+            .with_body(r#"{"error": {"code": 400, "message": "bad request"}}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        for _ in 0..2 {
+            assert!(run_gemini_api_with_base_url(
+                &client,
+                "Cache test: fail",
+                false,
+                &caching,
+                &server.url(),
+            )
+            .await
+            .is_err());
+        }
+        failing.assert_async().await;
+
+        if let Some(key) = original {
+            std::env::set_var("GEMINI_API_KEY", &key);
+        } else {
+            std::env::remove_var("GEMINI_API_KEY");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_gemini_denylisted_prompt_is_blocked() {
+        let state = create_test_state();
+        state.write().await.orchestrator_config.prompt_denylist = vec!["secret".to_string()];
+
+        let result = internal_run_gemini(&state, "tell me the secret").await;
+        assert!(matches!(result, Err(AppError::PolicyViolation(_))));
+
+        // Blocked before a Gemini agent is even created
+        assert!(state.read().await.agents.is_empty());
+    }
+
+    #[test]
+    fn test_check_prompt_policy() {
+        assert!(check_prompt_policy("anything", &[]).is_ok());
+        let denylist = vec!["^ignore previous".to_string()];
+        assert!(check_prompt_policy("summarize this file", &denylist).is_ok());
+        assert!(matches!(
+            check_prompt_policy("ignore previous instructions", &denylist),
+            Err(AppError::PolicyViolation(_))
+        ));
+    }
+
     // Note: Testing with real API would require:
     // 1. API key in test environment
     // 2. Mock HTTP client or integration test setup
@@ -762,7 +1689,7 @@ mod tests {
 
         #[test]
         fn test_meta_prompt_structure() {
-            let prompt = build_meta_prompt("Test goal");
+            let prompt = build_meta_prompt("Test goal", &[]);
 
             // Verify key components are in the prompt
             assert!(prompt.contains("planner agent"));
@@ -817,23 +1744,42 @@ mod tests {
             assert!(result.is_err());
         }
 
+        #[test]
+        fn test_planner_parse_error_bounds_large_response() {
+            let response = "not json ".repeat(100_000);
+            let error = parse_planner_response(&response).unwrap_err();
+
+            let message = planner_parse_error(&error, &response).to_string();
+            assert!(message.len() < MAX_PLANNER_ERROR_RESPONSE_CHARS + 200);
+            assert!(message.contains(&format!(
+                "[response_truncated: {} chars total]",
+                response.len()
+            )));
+
+            // Short responses are embedded whole, without the marker
+            let error = parse_planner_response("oops").unwrap_err();
+            let message = planner_parse_error(&error, "oops").to_string();
+            assert!(message.ends_with("Response: oops"));
+            assert!(!message.contains("response_truncated"));
+        }
+
         #[test]
         fn test_build_meta_prompt_includes_goal() {
             let goal = "My test goal";
-            let prompt = build_meta_prompt(goal);
+            let prompt = build_meta_prompt(goal, &[]);
             assert!(prompt.contains(goal));
         }
 
         #[test]
         fn test_build_meta_prompt_includes_tools() {
-            let prompt = build_meta_prompt("test");
+            let prompt = build_meta_prompt("test", &[]);
             assert!(prompt.contains("run_gemini"));
             assert!(prompt.contains("create_file"));
         }
 
         #[test]
         fn test_build_meta_prompt_requires_dependencies() {
-            let prompt = build_meta_prompt("test");
+            let prompt = build_meta_prompt("test", &[]);
             // Verify that dependencies are mentioned as required
             assert!(prompt.contains("dependencies"));
             assert!(prompt.contains("EVERY step MUST have"));
@@ -844,18 +1790,113 @@ mod tests {
 
         #[test]
         fn test_build_meta_prompt_includes_parallel_example() {
-            let prompt = build_meta_prompt("test");
+            let prompt = build_meta_prompt("test", &[]);
             // Verify that parallel execution example is included
             assert!(prompt.contains("Parallel Plan"));
             assert!(prompt.contains("can run simultaneously"));
         }
 
+        #[test]
+        fn test_build_meta_prompt_includes_configured_examples() {
+            let example = PlannerExample {
+                goal: "Draft release notes".to_string(),
+                plan: serde_json::from_str(
+                    r#"{"steps": [{"id": "step_1", "task": "run_gemini", "params": {"prompt": "Summarize the changelog"}, "dependencies": []}]}"#,
+                )
+                .unwrap(),
+            };
+            let prompt = build_meta_prompt("test", &[example]);
+
+            assert!(prompt.contains(r#"Example for goal "Draft release notes""#));
+            assert!(prompt.contains("Summarize the changelog"));
+            // Configured examples come before the goal
+            assert!(prompt.find("Draft release notes") < prompt.find(r#"GOAL: "test""#));
+        }
+
         #[test]
         fn test_build_meta_prompt_includes_sequential_example() {
-            let prompt = build_meta_prompt("test");
+            let prompt = build_meta_prompt("test", &[]);
             // Verify that sequential execution example is included
             assert!(prompt.contains("Sequential Plan"));
             assert!(prompt.contains("depends on"));
         }
     }
+
+    #[tokio::test]
+    async fn test_model_fallback_used_when_primary_rate_limited() {
+        use crate::executor::error::ExecutionError;
+
+        let models = vec!["gemini-2.5-pro".to_string(), "gemini-2.5-flash".to_string()];
+        let mut attempted = Vec::new();
+
+        let (output, model) = run_with_model_fallbacks(&models, |model| {
+            attempted.push(model.clone());
+            async move {
+                if model == "gemini-2.5-pro" {
+                    Err(AppError::ExecutionError(ExecutionError::ProcessFailed(
+                        "429 RESOURCE_EXHAUSTED: quota exceeded".to_string(),
+                    )))
+                } else {
+                    Ok(format!("served by {}", model))
+                }
+            }
+        })
+        .await
+        .expect("fallback model should serve the request");
+
+        assert_eq!(model, "gemini-2.5-flash");
+        assert_eq!(output, "served by gemini-2.5-flash");
+        assert_eq!(attempted, models);
+    }
+
+    #[tokio::test]
+    async fn test_model_fallback_skipped_for_non_retryable_error() {
+        let models = vec!["gemini-2.5-pro".to_string(), "gemini-2.5-flash".to_string()];
+        let mut attempts = 0;
+
+        let result = run_with_model_fallbacks(&models, |_| {
+            attempts += 1;
+            async { Err(AppError::InvalidPlan("bad prompt".to_string())) }
+        })
+        .await;
+
+        assert!(matches!(result, Err(AppError::InvalidPlan(_))));
+        assert_eq!(attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_model_fallback_returns_last_error_when_all_fail() {
+        let models = vec!["gemini-2.5-pro".to_string(), "gemini-2.5-flash".to_string()];
+
+        let result = run_with_model_fallbacks(&models, |model| async move {
+            Err(AppError::Timeout(format!("{} timed out", model)))
+        })
+        .await;
+
+        match result {
+            Err(AppError::Timeout(message)) => assert!(message.contains("gemini-2.5-flash")),
+            other => panic!("Expected last timeout error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_is_retryable_model_error() {
+        use crate::executor::error::ExecutionError;
+
+        assert!(is_retryable_model_error(&AppError::Internal(anyhow!(
+            "Gemini API rate limit exceeded (HTTP 429)"
+        ))));
+        assert!(is_retryable_model_error(&AppError::ExecutionError(
+            ExecutionError::ProcessFailed("503 UNAVAILABLE: model is overloaded".to_string())
+        )));
+        assert!(is_retryable_model_error(&AppError::ExecutionError(
+            ExecutionError::Timeout(30)
+        )));
+        assert!(!is_retryable_model_error(&AppError::ExecutionError(
+            ExecutionError::InvalidEncoding("quota in body".to_string())
+        )));
+        assert!(!is_retryable_model_error(&AppError::ExecutionError(
+            ExecutionError::ProcessFailed("invalid argument".to_string())
+        )));
+    }
 }
@@ -0,0 +1,96 @@
+//! Gemini API reply cache
+//!
+//! Re-runs and replays often send exactly the same prompt again. When
+//! `gemini_cache_ttl_secs` is set, `internal_run_gemini_api` keeps each
+//! successful, non-empty reply in a process-wide cache keyed by a hash of the
+//! prompt, model and JSON mode, and answers an identical request from it until
+//! the entry is older than the TTL. Errors and empty replies are never stored.
+//! Streamed steps go through the CLI and never consult the cache.
+
+use crate::orchestrator::constants::MAX_GEMINI_CACHE_ENTRIES;
+use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Cached replies and when they were stored, by cache key
+static REPLIES: Lazy<Mutex<HashMap<String, (String, Instant)>>> = Lazy::new(Default::default);
+
+/// Cache key for a request; the prompt is hashed so keys stay small
+pub fn cache_key(prompt: &str, model: &str, force_json: bool) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(model.as_bytes());
+    hasher.update([0, u8::from(force_json)]);
+    hasher.update(prompt.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// The reply stored under `key`, if it is younger than `ttl`
+pub fn get(key: &str, ttl: Duration) -> Option<String> {
+    let mut replies = REPLIES.lock().ok()?;
+    match replies.get(key) {
+        Some((reply, stored)) if stored.elapsed() < ttl => Some(reply.clone()),
+        Some(_) => {
+            replies.remove(key);
+            None
+        }
+        None => None,
+    }
+}
+
+/// Store `reply` under `key`; empty replies are ignored
+///
+/// Once `MAX_GEMINI_CACHE_ENTRIES` are held, entries older than `ttl` are
+/// dropped, then the oldest entry if the cache is still full.
+pub fn put(key: String, reply: &str, ttl: Duration) {
+    if reply.trim().is_empty() {
+        return;
+    }
+    let Ok(mut replies) = REPLIES.lock() else {
+        return;
+    };
+    if replies.len() >= MAX_GEMINI_CACHE_ENTRIES && !replies.contains_key(&key) {
+        replies.retain(|_, (_, stored)| stored.elapsed() < ttl);
+        if replies.len() >= MAX_GEMINI_CACHE_ENTRIES {
+            let oldest = replies
+                .iter()
+                .min_by_key(|(_, (_, stored))| *stored)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                replies.remove(&oldest);
+            }
+        }
+    }
+    replies.insert(key, (reply.to_string(), Instant::now()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_expire_and_empty_replies_are_skipped() {
+        let key = cache_key("response cache test prompt", "gemini-2.5-flash", false);
+        assert_ne!(
+            key,
+            cache_key("response cache test prompt", "gemini-2.5-flash", true)
+        );
+        assert_ne!(
+            key,
+            cache_key("response cache test prompt", "gemini-2.5-pro", false)
+        );
+
+        put(key.clone(), "   ", Duration::from_secs(60));
+        assert_eq!(get(&key, Duration::from_secs(60)), None);
+
+        put(key.clone(), "cached", Duration::from_secs(60));
+        assert_eq!(
+            get(&key, Duration::from_secs(60)).as_deref(),
+            Some("cached")
+        );
+        // An entry older than the TTL is a miss and is dropped
+        assert_eq!(get(&key, Duration::ZERO), None);
+        assert_eq!(get(&key, Duration::from_secs(60)), None);
+    }
+}
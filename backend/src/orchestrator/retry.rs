@@ -0,0 +1,347 @@
+//! Retry policy
+//!
+//! Shared retry behaviour for calls to Gemini: the API client backs off
+//! exponentially on transient HTTP failures, and the planner retries once
+//! straight away on any failure. Both describe that with a `RetryPolicy`.
+
+use crate::error::AppError;
+use std::future::Future;
+use std::time::Duration;
+
+/// Error fragments indicating a rate limit or exhausted quota
+const RATE_LIMIT_MARKERS: &[&str] = &["429", "rate limit", "resource_exhausted", "quota"];
+
+/// Error fragments indicating the service or model is temporarily unavailable
+const UNAVAILABLE_MARKERS: &[&str] = &["503", "unavailable", "overloaded", "error status 5"];
+
+/// Error fragments indicating the request never got a response
+const TRANSPORT_MARKERS: &[&str] = &["failed to send http request", "connection"];
+
+/// What kind of failure an error represents, for deciding whether to retry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// Rate limit or quota exhaustion (HTTP 429)
+    RateLimited,
+    /// Service or model temporarily unavailable or overloaded (HTTP 5xx)
+    Unavailable,
+    /// The call timed out
+    Timeout,
+    /// The request failed before a response arrived
+    Transport,
+    /// A response arrived but couldn't be used (unparseable, invalid plan)
+    InvalidResponse,
+    /// Anything else (bad request, blocked prompt, policy violation, ...)
+    Other,
+}
+
+impl ErrorKind {
+    /// Every kind, for policies that retry on any failure
+    pub const ALL: [ErrorKind; 6] = [
+        ErrorKind::RateLimited,
+        ErrorKind::Unavailable,
+        ErrorKind::Timeout,
+        ErrorKind::Transport,
+        ErrorKind::InvalidResponse,
+        ErrorKind::Other,
+    ];
+
+    /// Classify an error by its variant, falling back to its message
+    pub fn classify(error: &AppError) -> Self {
+        use crate::executor::error::ExecutionError;
+
+        match error {
+            AppError::Timeout(_) | AppError::ExecutionError(ExecutionError::Timeout(_)) => {
+                return ErrorKind::Timeout
+            }
+            // The service answered; its output just couldn't be used
+            AppError::ExecutionError(ExecutionError::InvalidEncoding(_))
+            | AppError::InvalidPlan(_)
+            | AppError::PlanValidationFailed(_) => return ErrorKind::InvalidResponse,
+            AppError::PromptBlocked { .. }
+            | AppError::PolicyViolation(_)
+            | AppError::Cancelled(_) => return ErrorKind::Other,
+            _ => {}
+        }
+
+        let message = error.to_string().to_lowercase();
+        let mentions = |markers: &[&str]| markers.iter().any(|marker| message.contains(marker));
+        if mentions(RATE_LIMIT_MARKERS) {
+            ErrorKind::RateLimited
+        } else if mentions(UNAVAILABLE_MARKERS) {
+            ErrorKind::Unavailable
+        } else if message.contains("timed out") {
+            ErrorKind::Timeout
+        } else if mentions(TRANSPORT_MARKERS) {
+            ErrorKind::Transport
+        } else if message.contains("failed to parse") {
+            ErrorKind::InvalidResponse
+        } else {
+            ErrorKind::Other
+        }
+    }
+}
+
+/// How often, how soon and on which errors to retry a call
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first (0 is treated as 1)
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub base_delay: Duration,
+    /// Factor applied to the delay after each retry
+    pub multiplier: f64,
+    /// Upper bound on any single delay
+    pub max_delay: Duration,
+    /// Fraction (0.0-1.0) of each delay that is randomized away, to spread out clients
+    pub jitter: f64,
+    /// Error kinds worth retrying; any other error is returned immediately
+    pub retryable: Vec<ErrorKind>,
+}
+
+impl RetryPolicy {
+    /// A single attempt, never retried
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            base_delay: Duration::ZERO,
+            multiplier: 1.0,
+            max_delay: Duration::ZERO,
+            jitter: 0.0,
+            retryable: Vec::new(),
+        }
+    }
+
+    /// Backoff for direct Gemini API calls: up to 3 attempts on transient failures
+    pub fn gemini_api() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            multiplier: 2.0,
+            max_delay: Duration::from_secs(8),
+            jitter: 0.2,
+            retryable: vec![
+                ErrorKind::RateLimited,
+                ErrorKind::Unavailable,
+                ErrorKind::Timeout,
+                ErrorKind::Transport,
+            ],
+        }
+    }
+
+    /// The planner's policy: one immediate retry on any failure
+    pub fn planner() -> Self {
+        Self {
+            max_attempts: 2,
+            retryable: ErrorKind::ALL.to_vec(),
+            ..Self::none()
+        }
+    }
+
+    /// Whether `error` is of a kind this policy retries
+    pub fn is_retryable(&self, error: &AppError) -> bool {
+        self.retryable.contains(&ErrorKind::classify(error))
+    }
+
+    /// Delay before retry number `retry` (0-based), before jitter
+    pub fn delay_for(&self, retry: u32) -> Duration {
+        let factor = self.multiplier.max(0.0).powi(retry as i32);
+        let delay = self.base_delay.as_secs_f64() * factor;
+        Duration::from_secs_f64(delay.min(self.max_delay.as_secs_f64()))
+    }
+
+    /// Delays before each retry this policy allows, before jitter
+    pub fn delays(&self) -> Vec<Duration> {
+        (0..self.max_attempts.saturating_sub(1))
+            .map(|retry| self.delay_for(retry))
+            .collect()
+    }
+
+    /// Randomize `delay` downwards by up to `jitter` of it
+    ///
+    /// `sample` is a random value in `[0.0, 1.0)`; jitter never lengthens a delay.
+    pub fn apply_jitter(&self, delay: Duration, sample: f64) -> Duration {
+        let jitter = self.jitter.clamp(0.0, 1.0) * sample.clamp(0.0, 1.0);
+        delay.mul_f64(1.0 - jitter)
+    }
+
+    /// Run `call` until it succeeds, fails with a non-retryable error, or the
+    /// attempts run out
+    ///
+    /// # Arguments
+    /// * `operation` - Name used in retry log messages
+    /// * `call` - Produces one attempt of the call
+    ///
+    /// # Returns
+    /// * `Ok(T)` - The first successful result
+    /// * `Err(AppError)` - The first non-retryable error, or the last attempt's error
+    pub async fn run<T, F, Fut>(&self, operation: &str, mut call: F) -> Result<T, AppError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, AppError>>,
+    {
+        let attempts = self.max_attempts.max(1);
+        let mut retry = 0;
+        loop {
+            match call().await {
+                Ok(value) => return Ok(value),
+                Err(e) if retry + 1 < attempts && self.is_retryable(&e) => {
+                    let delay = self.apply_jitter(self.delay_for(retry), random_sample());
+                    tracing::warn!(
+                        operation = operation,
+                        attempt = retry + 1,
+                        max_attempts = attempts,
+                        delay_ms = delay.as_millis() as u64,
+                        error = %e,
+                        "Call failed, retrying"
+                    );
+                    if !delay.is_zero() {
+                        tokio::time::sleep(delay).await;
+                    }
+                    retry += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// A random value in `[0.0, 1.0)` for jitter
+fn random_sample() -> f64 {
+    (uuid::Uuid::new_v4().as_u128() % 1_000_000) as f64 / 1_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::error::ExecutionError;
+    use anyhow::anyhow;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_delay_sequence_grows_and_caps() {
+        let policy = RetryPolicy {
+            max_attempts: 6,
+            base_delay: Duration::from_millis(100),
+            multiplier: 3.0,
+            max_delay: Duration::from_secs(2),
+            jitter: 0.0,
+            retryable: Vec::new(),
+        };
+        assert_eq!(
+            policy.delays(),
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(300),
+                Duration::from_millis(900),
+                Duration::from_secs(2),
+                Duration::from_secs(2),
+            ]
+        );
+        assert!(RetryPolicy::none().delays().is_empty());
+        assert_eq!(
+            RetryPolicy::planner().delays(),
+            vec![Duration::ZERO],
+            "planner retries once, immediately"
+        );
+
+        // Jitter only ever shortens a delay, by at most the jitter fraction
+        let policy = RetryPolicy {
+            jitter: 0.25,
+            ..policy
+        };
+        let delay = Duration::from_secs(1);
+        assert_eq!(policy.apply_jitter(delay, 0.0), delay);
+        assert_eq!(policy.apply_jitter(delay, 0.5), Duration::from_millis(875));
+        assert!(policy.apply_jitter(delay, 0.999) >= Duration::from_millis(750));
+    }
+
+    #[test]
+    fn test_classify_error_kinds() {
+        let internal = |msg: &str| AppError::Internal(anyhow!(msg.to_string()));
+        let cases = [
+            (
+                internal("Gemini API rate limit exceeded (HTTP 429): slow down"),
+                ErrorKind::RateLimited,
+            ),
+            (
+                internal("Gemini API returned error status 500: oops"),
+                ErrorKind::Unavailable,
+            ),
+            (
+                AppError::ExecutionError(ExecutionError::ProcessFailed(
+                    "503 UNAVAILABLE: model is overloaded".to_string(),
+                )),
+                ErrorKind::Unavailable,
+            ),
+            (
+                AppError::ExecutionError(ExecutionError::Timeout(30)),
+                ErrorKind::Timeout,
+            ),
+            (
+                internal("Failed to send HTTP request to Gemini API: dns error"),
+                ErrorKind::Transport,
+            ),
+            (
+                internal("Failed to parse JSON response from Gemini API: eof"),
+                ErrorKind::InvalidResponse,
+            ),
+            // A marker in unparseable output doesn't make it a rate limit
+            (
+                AppError::ExecutionError(ExecutionError::InvalidEncoding(
+                    "quota in body".to_string(),
+                )),
+                ErrorKind::InvalidResponse,
+            ),
+            (
+                AppError::PromptBlocked {
+                    reason: "SAFETY".to_string(),
+                },
+                ErrorKind::Other,
+            ),
+            (
+                internal("Gemini API returned error status 400: bad request"),
+                ErrorKind::Other,
+            ),
+        ];
+        for (error, kind) in cases {
+            assert_eq!(ErrorKind::classify(&error), kind, "{}", error);
+        }
+
+        let api = RetryPolicy::gemini_api();
+        assert!(api.is_retryable(&internal("HTTP 429")));
+        assert!(!api.is_retryable(&internal("Failed to parse JSON response")));
+        assert!(RetryPolicy::planner().is_retryable(&internal("anything at all")));
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_on_non_retryable_error() {
+        let calls = AtomicU32::new(0);
+        let policy = RetryPolicy {
+            base_delay: Duration::ZERO,
+            ..RetryPolicy::gemini_api()
+        };
+
+        let result: Result<(), AppError> = policy
+            .run("test", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(AppError::Internal(anyhow!("HTTP 429")))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(
+            calls.load(Ordering::SeqCst),
+            3,
+            "retried until attempts ran out"
+        );
+
+        calls.store(0, Ordering::SeqCst);
+        let result: Result<(), AppError> = policy
+            .run("test", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(AppError::Internal(anyhow!("error status 400")))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,176 @@
+//! Step output dumping for debugging
+//!
+//! When `dump_step_outputs_dir` is set, each step's output is written to
+//! `<dir>/<execution_id>/<step_id>.txt` as soon as the executor sees it in the
+//! session context, so the outputs of a plan that later fails are still on
+//! disk. Step IDs come from plans, so only ASCII letters, digits, `-`, `_` and
+//! `.` are kept in file names; anything else (path separators included)
+//! becomes `_`. Dumping is best-effort: I/O errors are logged and never fail
+//! the run.
+
+use crate::orchestrator::constants::STEP_OUTPUT_SUFFIX;
+use graph_flow::Context;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// Environment variable providing the default `dump_step_outputs_dir`
+pub const DUMP_STEP_OUTPUTS_DIR_ENV: &str = "DUMP_STEP_OUTPUTS_DIR";
+
+/// Writes step outputs of one execution to disk, each step at most once
+#[derive(Debug)]
+pub struct StepOutputDumper {
+    /// `<dump dir>/<execution_id>`
+    dir: PathBuf,
+    /// Steps whose output has already been handled
+    dumped: HashSet<String>,
+    /// Set once the directory turns out to be unwritable, to avoid repeating the warning
+    disabled: bool,
+}
+
+impl StepOutputDumper {
+    /// Create a dumper writing into `<base_dir>/<execution_id>`
+    ///
+    /// The directory is created lazily on the first write.
+    pub fn new(base_dir: &str, execution_id: &str) -> Self {
+        Self {
+            dir: PathBuf::from(base_dir).join(execution_id),
+            dumped: HashSet::new(),
+            disabled: false,
+        }
+    }
+
+    /// Write the output of every listed step that has completed since the last call
+    ///
+    /// # Arguments
+    /// * `step_ids` - Steps to look for (including expanded for_each sub-steps)
+    /// * `context` - Session context holding `<step_id>.output` values
+    pub async fn dump_completed<'a>(
+        &mut self,
+        step_ids: impl IntoIterator<Item = &'a str>,
+        context: &Context,
+    ) {
+        if self.disabled {
+            return;
+        }
+
+        for step_id in step_ids {
+            if self.dumped.contains(step_id) {
+                continue;
+            }
+            let output: Option<String> = context
+                .get(&format!("{}{}", step_id, STEP_OUTPUT_SUFFIX))
+                .await;
+            let Some(output) = output else {
+                continue;
+            };
+            self.dumped.insert(step_id.to_string());
+
+            if let Err(e) = tokio::fs::create_dir_all(&self.dir).await {
+                tracing::warn!(
+                    dir = %self.dir.display(),
+                    error = %e,
+                    "Cannot create step output dump directory; step outputs will not be dumped"
+                );
+                self.disabled = true;
+                return;
+            }
+
+            let path = self.dir.join(dump_file_name(step_id));
+            if let Err(e) = tokio::fs::write(&path, output).await {
+                tracing::warn!(
+                    path = %path.display(),
+                    error = %e,
+                    "Failed to dump step output"
+                );
+            }
+        }
+    }
+}
+
+/// File name a step's output is dumped to, safe to join onto the dump directory
+fn dump_file_name(step_id: &str) -> String {
+    let name: String = step_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}.txt", name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_dumps_each_completed_step_once() {
+        let temp_dir = tempdir().unwrap();
+        let base = temp_dir.path().to_str().unwrap();
+        let mut dumper = StepOutputDumper::new(base, "exec-1");
+        let context = Context::new();
+
+        context.set("step_1.output", "first".to_string()).await;
+        dumper.dump_completed(["step_1", "step_2"], &context).await;
+        let step_1 = temp_dir.path().join("exec-1").join("step_1.txt");
+        assert_eq!(std::fs::read_to_string(&step_1).unwrap(), "first");
+        assert!(!temp_dir.path().join("exec-1").join("step_2.txt").exists());
+
+        // Already-dumped steps are not rewritten
+        std::fs::remove_file(&step_1).unwrap();
+        context.set("step_2.output", "second".to_string()).await;
+        dumper.dump_completed(["step_1", "step_2"], &context).await;
+        assert!(!step_1.exists());
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("exec-1").join("step_2.txt")).unwrap(),
+            "second"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_step_ids_cannot_leave_the_dump_dir() {
+        let temp_dir = tempdir().unwrap();
+        let base = temp_dir.path().join("dumps");
+        let mut dumper = StepOutputDumper::new(base.to_str().unwrap(), "exec-1");
+        let context = Context::new();
+
+        for step_id in ["../../escape", "/etc/x", ".."] {
+            context
+                .set(
+                    &format!("{}{}", step_id, STEP_OUTPUT_SUFFIX),
+                    "x".to_string(),
+                )
+                .await;
+        }
+        dumper
+            .dump_completed(["../../escape", "/etc/x", ".."], &context)
+            .await;
+
+        let mut dumped: Vec<String> = std::fs::read_dir(base.join("exec-1"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        dumped.sort();
+        assert_eq!(dumped, vec!["...txt", ".._.._escape.txt", "_etc_x.txt"]);
+        assert!(!temp_dir.path().join("escape.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_unwritable_dir_is_ignored() {
+        let temp_dir = tempdir().unwrap();
+        let file = temp_dir.path().join("not-a-dir");
+        std::fs::write(&file, "x").unwrap();
+
+        let mut dumper = StepOutputDumper::new(file.to_str().unwrap(), "exec-1");
+        let context = Context::new();
+        context.set("step_1.output", "first".to_string()).await;
+
+        // Logs and carries on rather than panicking or erroring
+        dumper.dump_completed(["step_1"], &context).await;
+        assert!(dumper.disabled);
+    }
+}
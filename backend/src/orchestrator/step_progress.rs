@@ -0,0 +1,141 @@
+//! Incremental step progress
+//!
+//! Steps can report output while they are still running (`run_gemini` sends
+//! the model's text as it streams in). A graph-flow `Context` only holds
+//! serializable values, so a run's progress sender is kept in a process-wide
+//! registry under the run's session ID, and the context carries just that ID
+//! (`STEP_PROGRESS_KEY`). Reporting is best-effort: nothing fails if no one
+//! is listening. A step that starts over (a model fallback) reports a reset,
+//! so chunks always concatenate to the output of its last attempt.
+
+use crate::orchestrator::constants::STEP_PROGRESS_KEY;
+use graph_flow::Context;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::mpsc;
+
+/// A chunk of output from a step that is still running
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepProgress {
+    /// Step that produced the chunk
+    pub step_id: String,
+    /// The new output, in the order it was produced
+    pub chunk: String,
+    /// The step started over: output reported before is void (`chunk` is empty)
+    pub reset: bool,
+}
+
+/// Channel a run reports step progress on
+pub type StepProgressSender = mpsc::UnboundedSender<StepProgress>;
+
+/// Progress senders of the runs in flight, by session ID
+static SENDERS: Lazy<Mutex<HashMap<String, StepProgressSender>>> = Lazy::new(Default::default);
+
+/// A run's entry in the registry; removed again when dropped
+#[derive(Debug)]
+pub struct ProgressRegistration {
+    /// Session ID the sender is registered under
+    run_id: String,
+}
+
+impl Drop for ProgressRegistration {
+    fn drop(&mut self) {
+        if let Ok(mut senders) = SENDERS.lock() {
+            senders.remove(&self.run_id);
+        }
+    }
+}
+
+/// Register `sender` for the run with session ID `run_id`
+///
+/// Tasks find it through `reporter` once `run_id` is stored in the run's
+/// context under `STEP_PROGRESS_KEY`.
+pub fn register(run_id: &str, sender: StepProgressSender) -> ProgressRegistration {
+    if let Ok(mut senders) = SENDERS.lock() {
+        senders.insert(run_id.to_string(), sender);
+    }
+    ProgressRegistration {
+        run_id: run_id.to_string(),
+    }
+}
+
+/// Reports progress for one step
+#[derive(Debug, Clone)]
+pub struct ProgressReporter {
+    /// Step the reported chunks belong to
+    step_id: String,
+    /// The run's progress channel
+    sender: StepProgressSender,
+}
+
+impl ProgressReporter {
+    /// Send a chunk of output (dropped if the listener has gone away)
+    pub fn report(&self, chunk: String) {
+        let _ = self.sender.send(StepProgress {
+            step_id: self.step_id.clone(),
+            chunk,
+            reset: false,
+        });
+    }
+
+    /// Discard the chunks reported so far (the step is starting over)
+    pub fn reset(&self) {
+        let _ = self.sender.send(StepProgress {
+            step_id: self.step_id.clone(),
+            chunk: String::new(),
+            reset: true,
+        });
+    }
+}
+
+/// The progress reporter for `step_id`, if its run has a listener
+pub async fn reporter(context: &Context, step_id: &str) -> Option<ProgressReporter> {
+    let run_id = context.get::<String>(STEP_PROGRESS_KEY).await?;
+    let sender = SENDERS.lock().ok()?.get(&run_id)?.clone();
+    Some(ProgressReporter {
+        step_id: step_id.to_string(),
+        sender,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_reporter_follows_registration() {
+        let context = Context::new();
+        assert!(reporter(&context, "step_1").await.is_none());
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let registration = register("run-progress-test", tx);
+        context
+            .set(STEP_PROGRESS_KEY, "run-progress-test".to_string())
+            .await;
+
+        let progress = reporter(&context, "step_1").await.unwrap();
+        progress.report("hello".to_string());
+        assert_eq!(
+            rx.recv().await,
+            Some(StepProgress {
+                step_id: "step_1".to_string(),
+                chunk: "hello".to_string(),
+                reset: false,
+            })
+        );
+        progress.reset();
+        assert_eq!(
+            rx.recv().await,
+            Some(StepProgress {
+                step_id: "step_1".to_string(),
+                chunk: String::new(),
+                reset: true,
+            })
+        );
+
+        // Once the run is over its sender is gone
+        drop(registration);
+        assert!(reporter(&context, "step_1").await.is_none());
+    }
+}
@@ -5,16 +5,40 @@
 //! our primitives to the orchestration framework.
 //!
 //! Tasks:
-//! - RunGeminiTask: Wraps internal_run_gemini
-//! - CreateFileTask: Wraps internal_create_file
+//! - RunGeminiTask: Wraps internal_run_gemini (streaming progress when the run has a listener)
+//! - CreateFileTask: Wraps internal_write_file (overwrite or append)
+//! - GatherTask: Collects for_each sub-step outputs into a JSON array
+//! - ForEachTask: Runs a template over an `items_from` list at execution time
+//! - PingTask: Sleeps and returns a fixed string (only with `enable_ping_task`)
+//!
+//! Binary output can be carried between steps by storing it base64-encoded
+//! (`output_encoding: "base64"`) and decoding it on write
+//! (`transform: "base64_decode"`).
 //!
 //! Phase 4F: Tasks now implement graph_flow::Task instead of PlanTask.
 //! They use graph_flow::Context for state management and store outputs
-//! using keys like "step_X.output" in the context.
+//! using keys like "step_X.output" in the context. Tasks with several
+//! results store each under its own name (e.g. "step_X.metadata"); see
+//! `plan_types::task_outputs`.
 
-use crate::orchestrator::primitives::{internal_create_file, internal_run_gemini};
-use crate::state::AppState;
+use crate::orchestrator::config::OrchestratorConfig;
+use crate::orchestrator::constants::MAX_FOR_EACH_ITEMS;
+use crate::orchestrator::json_pointer::resolve_json_pointer;
+use crate::orchestrator::plan_expansion::instantiate_template;
+use crate::orchestrator::plan_to_graph::build_task;
+use crate::orchestrator::plan_types::{
+    output_context_key, ContentTransform, OutputEncoding, StepTemplate, WriteMode,
+};
+use crate::orchestrator::primitives::{
+    internal_run_gemini_raw, internal_run_gemini_streaming, internal_write_file,
+    internal_write_file_if_changed, run_with_model_fallbacks,
+};
+use crate::orchestrator::step_progress;
+use crate::services::working_dir::resolve_within;
+use crate::state::{AgentType, AppState};
 use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use futures_util::StreamExt;
 use graph_flow::{Context, NextAction, Result as GraphFlowResult, Task, TaskResult};
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -24,11 +48,17 @@ use tokio::sync::RwLock;
 /// Phase 4F: Now implements graph_flow::Task.
 /// Stores output in context under key "step_X.output".
 /// AppState is passed via constructor and stored in the task.
+/// When models are configured, the model that served the step is stored
+/// under "step_X.model".
 pub struct RunGeminiTask {
     /// Step ID (e.g., "step_1")
     step_id: String,
     /// Prompt to send to Gemini
     prompt: String,
+    /// How the output is stored in the context
+    output_encoding: OutputEncoding,
+    /// Models to try in order (primary first); empty uses the CLI default
+    models: Vec<String>,
     /// Application state (for agent management, working directory)
     app_state: Arc<RwLock<AppState>>,
 }
@@ -42,10 +72,24 @@ impl RunGeminiTask {
         Self {
             step_id,
             prompt,
+            output_encoding: OutputEncoding::default(),
+            models: Vec::new(),
             app_state: Arc::new(RwLock::new(AppState::new())),
         }
     }
 
+    /// Set how the output is stored in the context (defaults to UTF-8)
+    pub fn with_output_encoding(mut self, output_encoding: OutputEncoding) -> Self {
+        self.output_encoding = output_encoding;
+        self
+    }
+
+    /// Set the models to try, primary first, falling back on retryable errors
+    pub fn with_models(mut self, models: Vec<String>) -> Self {
+        self.models = models;
+        self
+    }
+
     /// Set the application state for this task
     #[allow(dead_code)] // Will be used in Phase 4G/H when building graph from plan
     pub fn with_app_state(mut self, app_state: Arc<RwLock<AppState>>) -> Self {
@@ -67,15 +111,71 @@ impl Task for RunGeminiTask {
             "Executing RunGeminiTask (graph-flow)"
         );
 
-        // Execute Gemini
-        let output = internal_run_gemini(&self.app_state, &self.prompt)
-            .await
-            .map_err(|e| {
-                graph_flow::GraphError::TaskExecutionFailed(format!(
-                    "Gemini execution failed in step '{}': {}",
-                    self.step_id, e
-                ))
-            })?;
+        // Stream the response as progress if the run has a listener. Base64
+        // output is only known once the process exits, so it isn't streamed.
+        let progress = match self.output_encoding {
+            OutputEncoding::Utf8 => step_progress::reporter(&context, &self.step_id).await,
+            OutputEncoding::Base64 => None,
+        };
+        let attempted = std::sync::atomic::AtomicBool::new(false);
+        let run = |model: Option<String>| {
+            let progress = progress.clone();
+            let retry = attempted.swap(true, std::sync::atomic::Ordering::Relaxed);
+            async move {
+                // A fallback model starts the output over
+                if let (Some(progress), true) = (&progress, retry) {
+                    progress.reset();
+                }
+                match progress {
+                    Some(progress) => internal_run_gemini_streaming(
+                        &self.app_state,
+                        &self.prompt,
+                        model.as_deref(),
+                        |chunk| progress.report(chunk),
+                    )
+                    .await
+                    .map(|(output, usage)| (output.into_bytes(), usage)),
+                    None => {
+                        internal_run_gemini_raw(&self.app_state, &self.prompt, model.as_deref())
+                            .await
+                    }
+                }
+            }
+        };
+
+        // Only the Gemini CLI takes --model; other default agents use their own
+        let models: &[String] = match self.app_state.read().await.default_agent_type() {
+            AgentType::Gemini => &self.models,
+            _ => &[],
+        };
+
+        // Execute Gemini, falling back through the configured models if any
+        let result = if models.is_empty() {
+            run(None).await.map(|(output, usage)| (output, usage, None))
+        } else {
+            run_with_model_fallbacks(models, |model| run(Some(model)))
+                .await
+                .map(|((output, usage), model)| (output, usage, Some(model)))
+        };
+        let (output, usage, model) = result.map_err(|e| {
+            graph_flow::GraphError::TaskExecutionFailed(format!(
+                "Gemini execution failed in step '{}': {}",
+                self.step_id, e
+            ))
+        })?;
+
+        if let Some(model) = &model {
+            use crate::orchestrator::constants::STEP_MODEL_SUFFIX;
+            let model_key = format!("{}{}", self.step_id, STEP_MODEL_SUFFIX);
+            context.set(&model_key, model.clone()).await;
+        }
+        if !usage.is_empty() {
+            use crate::orchestrator::constants::STEP_USAGE_SUFFIX;
+            let usage_key = format!("{}{}", self.step_id, STEP_USAGE_SUFFIX);
+            context.set(&usage_key, usage).await;
+        }
+
+        let output = encode_output(self.output_encoding, &output);
 
         // Store output in context for next steps
         use crate::orchestrator::constants::STEP_OUTPUT_SUFFIX;
@@ -85,6 +185,8 @@ impl Task for RunGeminiTask {
         tracing::debug!(
             step_id = %self.step_id,
             output_len = output.len(),
+            output_encoding = ?self.output_encoding,
+            model = ?model,
             "RunGeminiTask completed (graph-flow)"
         );
 
@@ -97,6 +199,9 @@ impl Task for RunGeminiTask {
 /// Phase 4F: Now implements graph_flow::Task.
 /// Reads content from context using keys like "step_X.output".
 /// AppState is passed via constructor and stored in the task.
+///
+/// The written path is stored as "step_X.output"; "step_X.metadata" holds
+/// `{"path", "bytes_written", "line_count", "unchanged"}` as JSON for size checks.
 pub struct CreateFileTask {
     /// Step ID (e.g., "step_2")
     step_id: String,
@@ -104,8 +209,22 @@ pub struct CreateFileTask {
     filename: String,
     /// Reference to content from another step (e.g., "step_1.output")
     content_from: Option<String>,
+    /// JSON pointer selecting the value to write from the `content_from` output
+    content_json_path: Option<String>,
+    /// Reference to another step's output to use as the filename (overrides `filename`)
+    filename_from: Option<String>,
     /// Direct content (if not using content_from)
     direct_content: Option<String>,
+    /// Transform applied to the content before writing
+    transform: Option<ContentTransform>,
+    /// Directory to write into instead of the working directory
+    output_dir: Option<String>,
+    /// Whether to replace or append to an existing file
+    mode: WriteMode,
+    /// Skip the write when the file already holds the same content (overwrite mode)
+    skip_if_unchanged: bool,
+    /// Unix permission bits the file is created with
+    file_mode: Option<u32>,
     /// Application state (for working directory)
     app_state: Arc<RwLock<AppState>>,
 }
@@ -120,7 +239,14 @@ impl CreateFileTask {
             step_id,
             filename,
             content_from,
+            content_json_path: None,
+            filename_from: None,
             direct_content: None,
+            transform: None,
+            output_dir: None,
+            mode: WriteMode::Overwrite,
+            skip_if_unchanged: false,
+            file_mode: None,
             app_state: Arc::new(RwLock::new(AppState::new())),
         }
     }
@@ -132,7 +258,14 @@ impl CreateFileTask {
             step_id,
             filename,
             content_from: None,
+            content_json_path: None,
+            filename_from: None,
             direct_content: Some(content),
+            transform: None,
+            output_dir: None,
+            mode: WriteMode::Overwrite,
+            skip_if_unchanged: false,
+            file_mode: None,
             app_state: Arc::new(RwLock::new(AppState::new())),
         }
     }
@@ -143,6 +276,233 @@ impl CreateFileTask {
         self.app_state = app_state;
         self
     }
+
+    /// Write only the value at a JSON pointer within the `content_from` output
+    pub fn with_content_json_path(mut self, content_json_path: Option<String>) -> Self {
+        self.content_json_path = content_json_path;
+        self
+    }
+
+    /// Take the filename from another step's output (e.g., "step_1.output")
+    ///
+    /// The resolved value is validated at execution time, since it usually
+    /// comes from LLM output rather than a static plan parameter.
+    pub fn with_filename_from(mut self, filename_from: Option<String>) -> Self {
+        self.filename_from = filename_from;
+        self
+    }
+
+    /// Set a transform to apply to the content before writing
+    pub fn with_transform(mut self, transform: Option<ContentTransform>) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Write into `output_dir` instead of the working directory
+    ///
+    /// A relative `output_dir` is resolved against the run's working directory.
+    pub fn with_output_dir(mut self, output_dir: Option<String>) -> Self {
+        self.output_dir = output_dir;
+        self
+    }
+
+    /// Set whether the file is overwritten (default) or appended to
+    pub fn with_mode(mut self, mode: WriteMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Leave an existing file alone when it already holds the same content
+    ///
+    /// Only applies in overwrite mode; appending always writes.
+    pub fn with_skip_if_unchanged(mut self, skip_if_unchanged: bool) -> Self {
+        self.skip_if_unchanged = skip_if_unchanged;
+        self
+    }
+
+    /// Set the Unix permission bits (e.g. 0o755) the file is created with
+    ///
+    /// An existing file is given the mode before its content is replaced, and
+    /// even when an unchanged file is skipped.
+    pub fn with_file_mode(mut self, file_mode: Option<u32>) -> Self {
+        self.file_mode = file_mode;
+        self
+    }
+
+    /// Resolve the filename to write, reading it from context if `filename_from` is set
+    async fn resolve_filename(&self, context: &Context) -> GraphFlowResult<String> {
+        let Some(ref filename_from) = self.filename_from else {
+            return Ok(self.filename.clone());
+        };
+
+        let key = output_context_key(filename_from);
+        let filename = context.get::<String>(&key).await.ok_or_else(|| {
+            graph_flow::GraphError::TaskExecutionFailed(format!(
+                "Step '{}' references filename from '{}' but that step has not been executed yet",
+                self.step_id, filename_from
+            ))
+        })?;
+
+        // LLM output commonly carries surrounding whitespace or a trailing newline
+        Ok(filename.trim().to_string())
+    }
+}
+
+/// Working directory for a task: the run's snapshot, else the app state's
+async fn resolve_working_dir(
+    context: &Context,
+    app_state: &Arc<RwLock<AppState>>,
+) -> Option<String> {
+    use crate::orchestrator::constants::{WORKING_DIR_BOUND_KEY, WORKING_DIR_KEY};
+
+    // Try to get from context first (snapshotted by the executor)
+    if let Some(wd) = context.get::<String>(WORKING_DIR_KEY).await {
+        Some(wd)
+    } else if context
+        .get::<bool>(WORKING_DIR_BOUND_KEY)
+        .await
+        .unwrap_or(false)
+    {
+        // Run was bound to "no working directory"; ignore later changes
+        None
+    } else {
+        // Fall back to app_state
+        let state_read = app_state.read().await;
+        state_read.working_directory().map(|dir| dir.to_string())
+    }
+}
+
+/// Encode raw step output for storage in the context
+///
+/// The context stores strings, so binary output must be base64-encoded to
+/// survive intact.
+pub fn encode_output(encoding: OutputEncoding, output: &[u8]) -> String {
+    match encoding {
+        OutputEncoding::Utf8 => String::from_utf8_lossy(output).into_owned(),
+        OutputEncoding::Base64 => BASE64.encode(output),
+    }
+}
+
+/// The value at `json_path` within a step output holding JSON, as text to write
+///
+/// Strings are returned as-is; other values are serialized as JSON.
+fn select_json(step_id: &str, output: &str, json_path: &str) -> GraphFlowResult<String> {
+    let failed = |reason: String| {
+        graph_flow::GraphError::TaskExecutionFailed(format!(
+            "Step '{}' could not select content_from_json_path: {}",
+            step_id, reason
+        ))
+    };
+    let document: serde_json::Value = serde_json::from_str(output.trim())
+        .map_err(|e| failed(format!("output is not JSON ({})", e)))?;
+    match resolve_json_pointer(&document, json_path).map_err(failed)? {
+        serde_json::Value::String(text) => Ok(text.clone()),
+        value => Ok(value.to_string()),
+    }
+}
+
+/// Apply a create_file content transform, producing the bytes to write
+fn apply_transform(
+    step_id: &str,
+    transform: Option<ContentTransform>,
+    content: String,
+) -> GraphFlowResult<Vec<u8>> {
+    match transform {
+        None => Ok(content.into_bytes()),
+        Some(ContentTransform::Base64Decode) => BASE64.decode(content.trim()).map_err(|e| {
+            graph_flow::GraphError::TaskExecutionFailed(format!(
+                "Step '{}' could not base64-decode content: {}",
+                step_id, e
+            ))
+        }),
+    }
+}
+
+/// Validate a filename for path traversal and dangerous characters
+///
+/// Applied to both static filenames and filenames resolved from step outputs.
+fn validate_filename(step_id: &str, filename: &str) -> GraphFlowResult<()> {
+    if filename.is_empty() {
+        return Err(graph_flow::GraphError::TaskExecutionFailed(format!(
+            "Filename in step '{}' is empty",
+            step_id
+        )));
+    }
+
+    // Validate filename for path traversal protection
+    if filename.contains("..") || filename.starts_with('/') || filename.starts_with('\\') {
+        return Err(graph_flow::GraphError::TaskExecutionFailed(format!(
+            "Filename '{}' in step '{}' contains invalid characters (path traversal detected or absolute path)",
+            filename, step_id
+        )));
+    }
+
+    // Also check for null bytes and other dangerous characters
+    if filename.contains('\0') || filename.chars().any(|c| c.is_control()) {
+        return Err(graph_flow::GraphError::TaskExecutionFailed(format!(
+            "Filename '{}' in step '{}' contains invalid characters (control characters detected)",
+            filename.escape_default(),
+            step_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Resolve and create the configured output directory, returning its canonical path
+///
+/// The output directory always lies inside the working directory; absolute
+/// paths, `..` and symlinks leading out are refused.
+async fn resolve_output_dir(
+    step_id: &str,
+    output_dir: &str,
+    working_dir: Option<&str>,
+) -> GraphFlowResult<std::path::PathBuf> {
+    let Some(wd) = working_dir else {
+        return Err(graph_flow::GraphError::TaskExecutionFailed(format!(
+            "Step '{}' cannot use output directory '{}': no working directory is set",
+            step_id, output_dir
+        )));
+    };
+    let path = resolve_within(std::path::Path::new(wd), output_dir).map_err(|e| {
+        graph_flow::GraphError::TaskExecutionFailed(format!(
+            "Step '{}' cannot use output directory '{}': {}",
+            step_id, output_dir, e
+        ))
+    })?;
+
+    tokio::fs::create_dir_all(&path).await.map_err(|e| {
+        graph_flow::GraphError::TaskExecutionFailed(format!(
+            "Step '{}' cannot create output directory '{}': {}",
+            step_id,
+            path.display(),
+            e
+        ))
+    })?;
+    tokio::fs::canonicalize(&path).await.map_err(|e| {
+        graph_flow::GraphError::TaskExecutionFailed(format!(
+            "Step '{}' cannot resolve output directory '{}': {}",
+            step_id,
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// Ensure `filename` resolves inside `output_dir`, even through symlinked subdirectories
+fn ensure_within_output_dir(
+    step_id: &str,
+    output_dir: &std::path::Path,
+    filename: &str,
+) -> GraphFlowResult<()> {
+    resolve_within(output_dir, filename)
+        .map(|_| ())
+        .map_err(|e| {
+            graph_flow::GraphError::TaskExecutionFailed(format!(
+                "Filename '{}' in step '{}' resolves outside the output directory: {}",
+                filename, step_id, e
+            ))
+        })
 }
 
 #[async_trait]
@@ -152,51 +512,34 @@ impl Task for CreateFileTask {
     }
 
     async fn run(&self, context: Context) -> GraphFlowResult<TaskResult> {
+        let filename = self.resolve_filename(&context).await?;
+
         tracing::debug!(
             step_id = %self.step_id,
-            filename = %self.filename,
+            filename = %filename.escape_default(),
+            dynamic = self.filename_from.is_some(),
             "Executing CreateFileTask (graph-flow)"
         );
 
-        // Validate filename for path traversal protection
-        if self.filename.contains("..") || self.filename.starts_with('/') {
-            return Err(graph_flow::GraphError::TaskExecutionFailed(format!(
-                "Filename '{}' in step '{}' contains invalid characters (path traversal detected or absolute path)",
-                self.filename, self.step_id
-            )));
-        }
+        validate_filename(&self.step_id, &filename)?;
 
-        // Also check for null bytes and other dangerous characters
-        if self.filename.contains('\0') || self.filename.chars().any(|c| c.is_control()) {
-            return Err(graph_flow::GraphError::TaskExecutionFailed(format!(
-                "Filename '{}' in step '{}' contains invalid characters (control characters detected)",
-                self.filename, self.step_id
-            )));
-        }
-
-        // Get working directory from context or app_state
-        let working_dir = {
-            // Try to get from context first (set by graph builder)
-            use crate::orchestrator::constants::WORKING_DIR_KEY;
-            if let Some(wd) = context.get::<String>(WORKING_DIR_KEY).await {
-                Some(wd)
-            } else {
-                // Fall back to app_state
-                let state_read = self.app_state.read().await;
-                state_read.working_directory().cloned()
-            }
-        };
+        let working_dir = resolve_working_dir(&context, &self.app_state).await;
 
         // Get content from context or use direct content
         let content = if let Some(ref content_from) = self.content_from {
-            // Parse "step_1.output" -> get from context using key "step_1.output" or "step_1.output"
-            // The context key should match what RunGeminiTask stores
-            context.get::<String>(content_from).await.ok_or_else(|| {
+            // "step_1" and "step_1.output" both read "step_1.output"; named
+            // outputs such as "step_1.metadata" read their own key
+            let key = output_context_key(content_from);
+            let output = context.get::<String>(&key).await.ok_or_else(|| {
                 graph_flow::GraphError::TaskExecutionFailed(format!(
                     "Step '{}' references output from '{}' but that step has not been executed yet",
                     self.step_id, content_from
                 ))
-            })?
+            })?;
+            match self.content_json_path {
+                Some(ref json_path) => select_json(&self.step_id, &output, json_path)?,
+                None => output,
+            }
         } else if let Some(ref direct) = self.direct_content {
             direct.clone()
         } else {
@@ -206,24 +549,63 @@ impl Task for CreateFileTask {
             )));
         };
 
+        let bytes = apply_transform(&self.step_id, self.transform, content)?;
+
+        // Outputs go to output_dir when configured, otherwise the working directory
+        let target_dir = match self.output_dir {
+            Some(ref output_dir) => {
+                let dir =
+                    resolve_output_dir(&self.step_id, output_dir, working_dir.as_deref()).await?;
+                ensure_within_output_dir(&self.step_id, &dir, &filename)?;
+                Some(dir.to_string_lossy().to_string())
+            }
+            None => working_dir,
+        };
+
         // Create the file
-        let file_path = internal_create_file(&self.filename, &content, working_dir.as_deref())
+        let write_result = if self.skip_if_unchanged && self.mode == WriteMode::Overwrite {
+            internal_write_file_if_changed(&filename, &bytes, target_dir.as_deref(), self.file_mode)
+                .await
+        } else {
+            internal_write_file(
+                &filename,
+                &bytes,
+                target_dir.as_deref(),
+                self.mode,
+                self.file_mode,
+            )
             .await
-            .map_err(|e| {
-                graph_flow::GraphError::TaskExecutionFailed(format!(
-                    "File creation failed in step '{}': {}",
-                    self.step_id, e
-                ))
-            })?;
+            .map(|path| (path, false))
+        };
+        let (file_path, unchanged) = write_result.map_err(|e| {
+            graph_flow::GraphError::TaskExecutionFailed(format!(
+                "File creation failed in step '{}': {}",
+                self.step_id, e
+            ))
+        })?;
+        let bytes_written = if unchanged { 0 } else { bytes.len() };
 
         // Store output in context (the file path)
         use crate::orchestrator::constants::STEP_OUTPUT_SUFFIX;
         let output_key = format!("{}{}", self.step_id, STEP_OUTPUT_SUFFIX);
         context.set(&output_key, file_path.clone()).await;
 
+        // Size of this write (not of the whole file, in append mode)
+        let metadata = serde_json::json!({
+            "path": file_path,
+            "bytes_written": bytes_written,
+            "line_count": count_lines(&bytes),
+            "unchanged": unchanged,
+        });
+        context
+            .set(&format!("{}.metadata", self.step_id), metadata.to_string())
+            .await;
+
         tracing::debug!(
             step_id = %self.step_id,
             file_path = %file_path,
+            bytes_written = bytes_written,
+            unchanged = unchanged,
             "CreateFileTask completed (graph-flow)"
         );
 
@@ -234,9 +616,280 @@ impl Task for CreateFileTask {
     }
 }
 
+/// Count lines the way `str::lines` does: a trailing newline doesn't start another line
+fn count_lines(bytes: &[u8]) -> usize {
+    let newlines = bytes.iter().filter(|&&b| b == b'\n').count();
+    match bytes.last() {
+        Some(b'\n') | None => newlines,
+        Some(_) => newlines + 1,
+    }
+}
+
+/// Task that collects the outputs of other steps into a JSON array
+///
+/// Generated for each for_each step; keeps the for_each step's ID so later
+/// steps can reference `"<for_each_id>.output"`.
+pub struct GatherTask {
+    /// Step ID (the for_each step's ID)
+    step_id: String,
+    /// Steps whose outputs are collected, in item order
+    sources: Vec<String>,
+}
+
+impl GatherTask {
+    /// Create a new GatherTask
+    pub fn new(step_id: String, sources: Vec<String>) -> Self {
+        Self { step_id, sources }
+    }
+}
+
+#[async_trait]
+impl Task for GatherTask {
+    fn id(&self) -> &str {
+        &self.step_id
+    }
+
+    async fn run(&self, context: Context) -> GraphFlowResult<TaskResult> {
+        let output = gather_outputs(&context, &self.step_id, &self.sources).await?;
+        Ok(TaskResult::new(Some(output), NextAction::Continue))
+    }
+}
+
+/// Task that sleeps for a fixed time and outputs a fixed string
+///
+/// Lets tests exercise graph scheduling, parallelism and timeouts without
+/// calling Gemini or touching the filesystem. Plans may only use it when
+/// `OrchestratorConfig::enable_ping_task` is on.
+pub struct PingTask {
+    /// Step ID (e.g., "step_1")
+    step_id: String,
+    /// How long to sleep before completing
+    delay_ms: u64,
+    /// Output of the step (defaults to "pong")
+    message: Option<String>,
+}
+
+impl PingTask {
+    /// Create a new PingTask
+    pub fn new(step_id: String, delay_ms: u64, message: Option<String>) -> Self {
+        Self {
+            step_id,
+            delay_ms,
+            message,
+        }
+    }
+}
+
+#[async_trait]
+impl Task for PingTask {
+    fn id(&self) -> &str {
+        &self.step_id
+    }
+
+    async fn run(&self, context: Context) -> GraphFlowResult<TaskResult> {
+        use crate::orchestrator::constants::{DEFAULT_PING_MESSAGE, STEP_OUTPUT_SUFFIX};
+
+        tracing::debug!(
+            step_id = %self.step_id,
+            delay_ms = self.delay_ms,
+            "Executing PingTask (graph-flow)"
+        );
+
+        if self.delay_ms > 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(self.delay_ms)).await;
+        }
+
+        let output = self
+            .message
+            .clone()
+            .unwrap_or_else(|| DEFAULT_PING_MESSAGE.to_string());
+        context
+            .set(
+                &format!("{}{}", self.step_id, STEP_OUTPUT_SUFFIX),
+                output.clone(),
+            )
+            .await;
+
+        Ok(TaskResult::new(Some(output), NextAction::Continue))
+    }
+}
+
+/// Task that runs a template over a JSON array produced by an earlier step
+///
+/// Used for for_each steps with `items_from`, whose item count is only known
+/// at execution time. Sub-steps run concurrently and are gathered like a
+/// literal for_each.
+pub struct ForEachTask {
+    /// Step ID (e.g., "step_3")
+    step_id: String,
+    /// Reference to the output holding the JSON array (e.g., "step_1.output")
+    items_from: String,
+    /// Sub-task run once per item
+    template: StepTemplate,
+    /// Limits applied when building sub-tasks
+    config: OrchestratorConfig,
+    /// Application state (for agent management, working directory)
+    app_state: Arc<RwLock<AppState>>,
+}
+
+impl ForEachTask {
+    /// Create a new ForEachTask
+    pub fn new(
+        step_id: String,
+        items_from: String,
+        template: StepTemplate,
+        config: OrchestratorConfig,
+    ) -> Self {
+        Self {
+            step_id,
+            items_from,
+            template,
+            config,
+            app_state: Arc::new(RwLock::new(AppState::new())),
+        }
+    }
+
+    /// Set the application state for this task
+    pub fn with_app_state(mut self, app_state: Arc<RwLock<AppState>>) -> Self {
+        self.app_state = app_state;
+        self
+    }
+
+    /// Read and parse the item list from the context
+    async fn resolve_items(&self, context: &Context) -> GraphFlowResult<Vec<String>> {
+        let raw = context
+            .get::<String>(&output_context_key(&self.items_from))
+            .await
+            .ok_or_else(|| {
+                graph_flow::GraphError::TaskExecutionFailed(format!(
+                    "Step '{}' references items from '{}' but that step has not been executed yet",
+                    self.step_id, self.items_from
+                ))
+            })?;
+
+        let values: Vec<serde_json::Value> = serde_json::from_str(raw.trim()).map_err(|e| {
+            graph_flow::GraphError::TaskExecutionFailed(format!(
+                "Step '{}' expected a JSON array in '{}': {}",
+                self.step_id, self.items_from, e
+            ))
+        })?;
+
+        if values.len() > MAX_FOR_EACH_ITEMS {
+            return Err(graph_flow::GraphError::TaskExecutionFailed(format!(
+                "Step '{}' has {} items (maximum {})",
+                self.step_id,
+                values.len(),
+                MAX_FOR_EACH_ITEMS
+            )));
+        }
+
+        // Strings are used as-is; other JSON values as their JSON text
+        Ok(values
+            .into_iter()
+            .map(|value| match value {
+                serde_json::Value::String(s) => s,
+                other => other.to_string(),
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl Task for ForEachTask {
+    fn id(&self) -> &str {
+        &self.step_id
+    }
+
+    async fn run(&self, context: Context) -> GraphFlowResult<TaskResult> {
+        let items = self.resolve_items(&context).await?;
+
+        tracing::debug!(
+            step_id = %self.step_id,
+            item_count = items.len(),
+            "Executing ForEachTask (graph-flow)"
+        );
+
+        let sub_steps: Vec<_> = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                instantiate_template(&self.step_id, &self.template, index, item, Vec::new())
+            })
+            .collect();
+
+        let tasks = sub_steps
+            .iter()
+            .map(|step| build_task(step, &self.app_state, &self.config))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                graph_flow::GraphError::TaskExecutionFailed(format!(
+                    "Step '{}' has an invalid template: {}",
+                    self.step_id, e
+                ))
+            })?;
+
+        // At most max_parallel_tasks items run at once
+        let results: Vec<_> =
+            futures_util::stream::iter(tasks.iter().map(|task| task.run(context.clone())))
+                .buffer_unordered(self.config.max_parallel_tasks.max(1))
+                .collect()
+                .await;
+        for result in results {
+            result?;
+        }
+
+        let sources: Vec<String> = sub_steps.into_iter().map(|step| step.id).collect();
+        let output = gather_outputs(&context, &self.step_id, &sources).await?;
+        Ok(TaskResult::new(Some(output), NextAction::Continue))
+    }
+}
+
+/// Collect `<source>.output` values into a JSON array stored as `<step_id>.output`
+async fn gather_outputs(
+    context: &Context,
+    step_id: &str,
+    sources: &[String],
+) -> GraphFlowResult<String> {
+    use crate::orchestrator::constants::STEP_OUTPUT_SUFFIX;
+
+    let mut outputs = Vec::with_capacity(sources.len());
+    for source in sources {
+        let key = format!("{}{}", source, STEP_OUTPUT_SUFFIX);
+        let output = context.get::<String>(&key).await.ok_or_else(|| {
+            graph_flow::GraphError::TaskExecutionFailed(format!(
+                "Step '{}' cannot gather output of '{}': it has not been executed yet",
+                step_id, source
+            ))
+        })?;
+        outputs.push(output);
+    }
+
+    let output = serde_json::to_string(&outputs).map_err(|e| {
+        graph_flow::GraphError::TaskExecutionFailed(format!(
+            "Step '{}' failed to serialize gathered outputs: {}",
+            step_id, e
+        ))
+    })?;
+    context
+        .set(
+            &format!("{}{}", step_id, STEP_OUTPUT_SUFFIX),
+            output.clone(),
+        )
+        .await;
+
+    tracing::debug!(
+        step_id = %step_id,
+        gathered = sources.len(),
+        "Gathered for_each outputs (graph-flow)"
+    );
+
+    Ok(output)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::working_dir::{WorkingDir, WorkingDirRoots};
     use crate::state::AppState;
     use graph_flow::Context;
     use std::sync::Arc;
@@ -255,6 +908,98 @@ mod tests {
         // Full execution test would require Gemini CLI, which is tested elsewhere
     }
 
+    #[tokio::test]
+    async fn test_run_gemini_task_skips_models_for_other_agent_types() {
+        let state = create_test_state();
+        {
+            let mut state = state.write().await;
+            state.set_default_agent_type(AgentType::Generic);
+            let mut agent = crate::state::Agent::new(
+                "echo-agent".to_string(),
+                "Echo".to_string(),
+                AgentType::Generic,
+            );
+            agent.config.command = "echo".to_string();
+            agent.config.args = Vec::new();
+            state.add_agent(agent);
+        }
+
+        // echo prints its arguments, so a pinned model would show up in the output
+        let ctx = Context::new();
+        RunGeminiTask::new("step_1".to_string(), "hello".to_string())
+            .with_models(vec!["primary".to_string(), "fallback".to_string()])
+            .with_app_state(state)
+            .run(ctx.clone())
+            .await
+            .unwrap();
+
+        let output: String = ctx.get("step_1.output").await.unwrap();
+        assert_eq!(output.trim(), "hello");
+        assert!(ctx.get::<String>("step_1.model").await.is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_gemini_task_resets_progress_on_fallback() {
+        use crate::orchestrator::constants::STEP_PROGRESS_KEY;
+        use crate::orchestrator::step_progress::StepProgress;
+        use std::os::unix::fs::PermissionsExt;
+
+        // The primary model streams a line, then fails with a rate limit
+        let temp_dir = tempdir().unwrap();
+        let script = temp_dir.path().join("gemini-fallback-mock.sh");
+        std::fs::write(
+            &script,
+            concat!(
+                "#!/bin/sh\n",
+                "case \"$*\" in\n",
+                "  *\"--model primary\"*) echo partial; echo '429 rate limit' >&2; exit 1 ;;\n",
+                "  *) echo done ;;\n",
+                "esac\n",
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let state = create_test_state();
+        let mut agent = crate::state::Agent::new(
+            "gemini-1".to_string(),
+            "Gemini Mock".to_string(),
+            AgentType::Gemini,
+        );
+        agent.config.command = script.to_string_lossy().to_string();
+        agent.config.args = vec![];
+        state.write().await.add_agent(agent);
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let _registration = step_progress::register("run-fallback-reset-test", tx);
+        let ctx = Context::new();
+        ctx.set(STEP_PROGRESS_KEY, "run-fallback-reset-test".to_string())
+            .await;
+
+        RunGeminiTask::new("step_1".to_string(), "hi".to_string())
+            .with_models(vec!["primary".to_string(), "fallback".to_string()])
+            .with_app_state(state)
+            .run(ctx.clone())
+            .await
+            .unwrap();
+
+        let mut events = Vec::new();
+        while let Ok(StepProgress { chunk, reset, .. }) = rx.try_recv() {
+            events.push((chunk, reset));
+        }
+        assert_eq!(
+            events,
+            vec![
+                ("partial\n".to_string(), false),
+                (String::new(), true),
+                ("done\n".to_string(), false),
+            ]
+        );
+        let model: String = ctx.get("step_1.model").await.unwrap();
+        assert_eq!(model, "fallback");
+    }
+
     #[tokio::test]
     async fn test_create_file_task_with_content_from() {
         let temp_dir = tempdir().expect("Failed to create temp dir");
@@ -292,6 +1037,338 @@ mod tests {
         assert_eq!(content, "Test content");
     }
 
+    #[tokio::test]
+    async fn test_create_file_task_content_from_json_path() {
+        use crate::orchestrator::constants::{MAX_JSON_POINTER_DEPTH, WORKING_DIR_KEY};
+
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let ctx = Context::new();
+        ctx.set(
+            WORKING_DIR_KEY,
+            temp_dir.path().to_str().unwrap().to_string(),
+        )
+        .await;
+        ctx.set(
+            "step_1.output",
+            r#"{"items": [{"title": "Ode"}, {"title": "Sonnet", "lines": 14}]}"#.to_string(),
+        )
+        .await;
+        let task = |filename: &str, json_path: String| {
+            CreateFileTask::new(
+                "step_2".to_string(),
+                filename.to_string(),
+                Some("step_1".to_string()),
+            )
+            .with_content_json_path(Some(json_path))
+            .with_app_state(create_test_state())
+        };
+
+        // Strings are written unquoted, other values as JSON
+        let result = task("title.txt", "/items/1/title".to_string())
+            .run(ctx.clone())
+            .await
+            .unwrap();
+        let path = result.response.unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "Sonnet");
+        let result = task("item.json", "/items/1".to_string())
+            .run(ctx.clone())
+            .await
+            .unwrap();
+        let path = result.response.unwrap();
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(written, serde_json::json!({"title": "Sonnet", "lines": 14}));
+
+        let missing = task("missing.txt", "/items/5".to_string())
+            .run(ctx.clone())
+            .await;
+        assert!(missing.is_err());
+        let too_deep = task("deep.txt", "/x".repeat(MAX_JSON_POINTER_DEPTH + 1))
+            .run(ctx)
+            .await;
+        match too_deep {
+            Err(e) => assert!(e.to_string().contains("maximum"), "got: {}", e),
+            Ok(_) => panic!("Expected an overly deep JSON path to fail"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_file_task_stores_write_metadata() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let ctx = Context::new();
+        use crate::orchestrator::constants::WORKING_DIR_KEY;
+        ctx.set(
+            WORKING_DIR_KEY,
+            temp_dir.path().to_str().unwrap().to_string(),
+        )
+        .await;
+        ctx.set("step_1.output", "alpha\nbeta\ngamma".to_string())
+            .await;
+
+        let task = CreateFileTask::new(
+            "step_2".to_string(),
+            "notes.txt".to_string(),
+            Some("step_1.output".to_string()),
+        )
+        .with_app_state(create_test_state());
+        let file_path = task.run(ctx.clone()).await.unwrap().response.unwrap();
+
+        // The path stays the primary output
+        assert_eq!(
+            ctx.get::<String>("step_2.output").await.as_deref(),
+            Some(file_path.as_str())
+        );
+        let metadata: serde_json::Value =
+            serde_json::from_str(&ctx.get::<String>("step_2.metadata").await.unwrap()).unwrap();
+        assert_eq!(metadata["path"], file_path);
+        assert_eq!(metadata["bytes_written"], 16);
+        assert_eq!(metadata["line_count"], 3);
+    }
+
+    #[test]
+    fn test_count_lines() {
+        assert_eq!(count_lines(b""), 0);
+        assert_eq!(count_lines(b"one"), 1);
+        assert_eq!(count_lines(b"one\n"), 1);
+        assert_eq!(count_lines(b"one\ntwo\n\n"), 3);
+    }
+
+    #[tokio::test]
+    async fn test_create_file_task_writes_into_output_dir() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let ctx = Context::new();
+        use crate::orchestrator::constants::WORKING_DIR_KEY;
+        ctx.set(
+            WORKING_DIR_KEY,
+            temp_dir.path().to_str().unwrap().to_string(),
+        )
+        .await;
+        ctx.set("step_1.output", "isolated".to_string()).await;
+
+        // Relative output_dir resolves against the working directory and is created
+        let task = CreateFileTask::new(
+            "step_2".to_string(),
+            "reports/out.txt".to_string(),
+            Some("step_1.output".to_string()),
+        )
+        .with_output_dir(Some("generated".to_string()))
+        .with_app_state(create_test_state());
+        task.run(ctx.clone()).await.unwrap();
+
+        let written = temp_dir.path().join("generated/reports/out.txt");
+        assert_eq!(std::fs::read_to_string(written).unwrap(), "isolated");
+        assert!(!temp_dir.path().join("reports").exists());
+
+        // Without output_dir the file lands in the working directory
+        let task = CreateFileTask::new(
+            "step_3".to_string(),
+            "out.txt".to_string(),
+            Some("step_1.output".to_string()),
+        )
+        .with_app_state(create_test_state());
+        task.run(ctx).await.unwrap();
+        assert!(temp_dir.path().join("out.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_create_file_task_append_and_overwrite_modes() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let ctx = Context::new();
+        use crate::orchestrator::constants::WORKING_DIR_KEY;
+        ctx.set(
+            WORKING_DIR_KEY,
+            temp_dir.path().to_str().unwrap().to_string(),
+        )
+        .await;
+        ctx.set("step_1.output", "first\n".to_string()).await;
+        ctx.set("step_2.output", "second\n".to_string()).await;
+        let log = temp_dir.path().join("run.log");
+
+        // Two append steps accumulate into the same file
+        for (step_id, source) in [("step_3", "step_1.output"), ("step_4", "step_2.output")] {
+            CreateFileTask::new(
+                step_id.to_string(),
+                "run.log".to_string(),
+                Some(source.to_string()),
+            )
+            .with_mode(WriteMode::Append)
+            .with_app_state(create_test_state())
+            .run(ctx.clone())
+            .await
+            .unwrap();
+        }
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "first\nsecond\n");
+
+        // The default mode replaces what was there
+        CreateFileTask::new(
+            "step_5".to_string(),
+            "run.log".to_string(),
+            Some("step_2.output".to_string()),
+        )
+        .with_app_state(create_test_state())
+        .run(ctx)
+        .await
+        .unwrap();
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "second\n");
+    }
+
+    #[tokio::test]
+    async fn test_create_file_task_skip_if_unchanged() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let ctx = Context::new();
+        use crate::orchestrator::constants::WORKING_DIR_KEY;
+        ctx.set(
+            WORKING_DIR_KEY,
+            temp_dir.path().to_str().unwrap().to_string(),
+        )
+        .await;
+        ctx.set("step_1.output", "same\n".to_string()).await;
+        ctx.set("step_2.output", "new!\n".to_string()).await;
+        let target = temp_dir.path().join("out.txt");
+        std::fs::write(&target, "same\n").unwrap();
+        let past = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        std::fs::File::options()
+            .write(true)
+            .open(&target)
+            .unwrap()
+            .set_modified(past)
+            .unwrap();
+        let task = |step_id: &str, source: &str| {
+            CreateFileTask::new(
+                step_id.to_string(),
+                "out.txt".to_string(),
+                Some(source.to_string()),
+            )
+            .with_skip_if_unchanged(true)
+            .with_app_state(create_test_state())
+        };
+        let metadata = |raw: Option<String>| -> serde_json::Value {
+            serde_json::from_str(&raw.unwrap()).unwrap()
+        };
+
+        // Identical content: no write, mtime untouched
+        task("step_3", "step_1.output")
+            .run(ctx.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::metadata(&target).unwrap().modified().unwrap(),
+            past
+        );
+        let step_3 = metadata(ctx.get::<String>("step_3.metadata").await);
+        assert_eq!(step_3["unchanged"], true);
+        assert_eq!(step_3["bytes_written"], 0);
+
+        // Different content is written
+        task("step_4", "step_2.output")
+            .run(ctx.clone())
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "new!\n");
+        assert_ne!(
+            std::fs::metadata(&target).unwrap().modified().unwrap(),
+            past
+        );
+        let step_4 = metadata(ctx.get::<String>("step_4.metadata").await);
+        assert_eq!(step_4["unchanged"], false);
+        assert_eq!(step_4["bytes_written"], 5);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_create_file_task_sets_file_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let ctx = Context::new();
+        ctx.set("working_dir", temp_dir.path().to_str().unwrap().to_string())
+            .await;
+        ctx.set("step_1.output", "#!/bin/sh\necho hi\n".to_string())
+            .await;
+        let task = CreateFileTask::new(
+            "step_2".to_string(),
+            "run.sh".to_string(),
+            Some("step_1.output".to_string()),
+        )
+        .with_file_mode(Some(0o755))
+        .with_app_state(create_test_state());
+
+        task.run(ctx.clone()).await.unwrap();
+        let mode = std::fs::metadata(temp_dir.path().join("run.sh"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o755);
+
+        // An existing, wider file is narrowed, in append mode too
+        let secrets = temp_dir.path().join("secrets.env");
+        std::fs::write(&secrets, "A=1\n").unwrap();
+        std::fs::set_permissions(&secrets, std::fs::Permissions::from_mode(0o644)).unwrap();
+        ctx.set("step_1.output", "B=2\n".to_string()).await;
+        let task = CreateFileTask::new(
+            "step_3".to_string(),
+            "secrets.env".to_string(),
+            Some("step_1.output".to_string()),
+        )
+        .with_mode(WriteMode::Append)
+        .with_file_mode(Some(0o600))
+        .with_app_state(create_test_state());
+
+        task.run(ctx).await.unwrap();
+        let mode = std::fs::metadata(&secrets).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(std::fs::read_to_string(&secrets).unwrap(), "A=1\nB=2\n");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_create_file_task_output_dir_blocks_symlink_escape() {
+        let work_dir = tempdir().expect("Failed to create temp dir");
+        let outside = tempdir().expect("Failed to create temp dir");
+        std::fs::create_dir(work_dir.path().join("generated")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), work_dir.path().join("generated/link")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), work_dir.path().join("elsewhere")).unwrap();
+
+        let ctx = Context::new();
+        ctx.set(
+            crate::orchestrator::constants::WORKING_DIR_KEY,
+            work_dir.path().to_str().unwrap().to_string(),
+        )
+        .await;
+        ctx.set("step_1.output", "escaped".to_string()).await;
+        let task = |filename: &str, output_dir: &str| {
+            CreateFileTask::new(
+                "step_2".to_string(),
+                filename.to_string(),
+                Some("step_1.output".to_string()),
+            )
+            .with_output_dir(Some(output_dir.to_string()))
+            .with_app_state(create_test_state())
+        };
+
+        let result = task("link/out.txt", "generated").run(ctx.clone()).await;
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("outside the output directory"));
+
+        // The output directory itself can't lead out of the working directory
+        for output_dir in ["elsewhere", "../escape", outside.path().to_str().unwrap()] {
+            let result = task("out.txt", output_dir).run(ctx.clone()).await;
+            assert!(
+                result
+                    .unwrap_err()
+                    .to_string()
+                    .contains("cannot use output directory"),
+                "{}",
+                output_dir
+            );
+        }
+        assert!(!outside.path().join("out.txt").exists());
+        assert!(!work_dir.path().join("../escape").exists());
+    }
+
     #[tokio::test]
     async fn test_create_file_task_missing_content() {
         let temp_dir = tempdir().expect("Failed to create temp dir");
@@ -387,4 +1464,313 @@ mod tests {
             .to_string()
             .contains("control characters"));
     }
+
+    async fn filename_from_context(filename: &str, work_dir: &str) -> Context {
+        use crate::orchestrator::constants::{STEP_OUTPUT_SUFFIX, WORKING_DIR_KEY};
+        let ctx = Context::new();
+        ctx.set(
+            &format!("step_1{}", STEP_OUTPUT_SUFFIX),
+            filename.to_string(),
+        )
+        .await;
+        ctx.set(
+            &format!("step_2{}", STEP_OUTPUT_SUFFIX),
+            "Test content".to_string(),
+        )
+        .await;
+        ctx.set(WORKING_DIR_KEY, work_dir.to_string()).await;
+        ctx
+    }
+
+    fn filename_from_task() -> CreateFileTask {
+        CreateFileTask::new(
+            "step_3".to_string(),
+            String::new(),
+            Some("step_2.output".to_string()),
+        )
+        .with_filename_from(Some("step_1.output".to_string()))
+        .with_app_state(create_test_state())
+    }
+
+    #[tokio::test]
+    async fn test_create_file_task_filename_from_step_output() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let work_dir = temp_dir.path().to_str().unwrap();
+
+        let ctx = filename_from_context("generated.txt\n", work_dir).await;
+        let result = filename_from_task().run(ctx).await;
+
+        let file_path = result.unwrap().response.unwrap();
+        assert!(file_path.ends_with("generated.txt"));
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "Test content");
+    }
+
+    #[tokio::test]
+    async fn test_create_file_task_rejects_malicious_filename_from() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let work_dir = temp_dir.path().to_str().unwrap();
+
+        for malicious in [
+            "../../etc/passwd",
+            "/etc/passwd",
+            "notes/../../escape.txt",
+            "evil\0name.txt",
+            "line\nbreak.txt",
+            "   ",
+        ] {
+            let ctx = filename_from_context(malicious, work_dir).await;
+            let result = filename_from_task().run(ctx).await;
+            assert!(
+                result.is_err(),
+                "Filename {:?} should have been rejected",
+                malicious
+            );
+        }
+
+        // Nothing was written to the working directory
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_create_file_task_filename_from_missing_step() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let ctx = Context::new();
+        ctx.set("working_dir", temp_dir.path().to_str().unwrap().to_string())
+            .await;
+
+        let result = filename_from_task().run(ctx).await;
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("not been executed"));
+    }
+
+    #[tokio::test]
+    async fn test_create_file_task_base64_round_trip_is_byte_exact() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let work_dir = temp_dir.path().to_str().unwrap().to_string();
+
+        // Every byte value, including invalid UTF-8 sequences
+        let binary: Vec<u8> = (0..=255u8).chain([0xff, 0xfe, 0x00, 0x80]).collect();
+
+        let ctx = Context::new();
+        use crate::orchestrator::constants::{STEP_OUTPUT_SUFFIX, WORKING_DIR_KEY};
+        ctx.set(
+            &format!("step_1{}", STEP_OUTPUT_SUFFIX),
+            encode_output(OutputEncoding::Base64, &binary),
+        )
+        .await;
+        ctx.set(WORKING_DIR_KEY, work_dir).await;
+
+        let task = CreateFileTask::new(
+            "step_2".to_string(),
+            "image.bin".to_string(),
+            Some("step_1.output".to_string()),
+        )
+        .with_transform(Some(ContentTransform::Base64Decode))
+        .with_app_state(create_test_state());
+
+        let file_path = task.run(ctx).await.unwrap().response.unwrap();
+        assert_eq!(std::fs::read(&file_path).unwrap(), binary);
+    }
+
+    #[tokio::test]
+    async fn test_create_file_task_rejects_invalid_base64() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+
+        let ctx = Context::new();
+        ctx.set("step_1.output", "not base64!".to_string()).await;
+        ctx.set("working_dir", temp_dir.path().to_str().unwrap().to_string())
+            .await;
+
+        let task = CreateFileTask::new(
+            "step_2".to_string(),
+            "image.bin".to_string(),
+            Some("step_1.output".to_string()),
+        )
+        .with_transform(Some(ContentTransform::Base64Decode))
+        .with_app_state(create_test_state());
+
+        let error = task.run(ctx).await.unwrap_err().to_string();
+        assert!(error.contains("base64-decode"), "got: {}", error);
+        assert!(!temp_dir.path().join("image.bin").exists());
+    }
+
+    #[tokio::test]
+    async fn test_create_file_task_ignores_working_dir_change_mid_run() {
+        let snapshot_dir = tempdir().expect("Failed to create temp dir");
+        let changed_dir = tempdir().expect("Failed to create temp dir");
+
+        // Executor snapshotted the working directory at run start
+        let ctx = Context::new();
+        use crate::orchestrator::constants::{WORKING_DIR_BOUND_KEY, WORKING_DIR_KEY};
+        ctx.set(
+            WORKING_DIR_KEY,
+            snapshot_dir.path().to_str().unwrap().to_string(),
+        )
+        .await;
+        ctx.set(WORKING_DIR_BOUND_KEY, true).await;
+        ctx.set("step_1.output", "snapshotted".to_string()).await;
+
+        // Another client changes the working directory mid-run
+        let state = create_test_state();
+        state.write().await.set_working_directory(Some(
+            WorkingDir::new(
+                changed_dir.path().to_str().unwrap(),
+                &WorkingDirRoots::default(),
+            )
+            .unwrap(),
+        ));
+
+        let task = CreateFileTask::new(
+            "step_2".to_string(),
+            "bound.txt".to_string(),
+            Some("step_1.output".to_string()),
+        )
+        .with_app_state(state);
+        task.run(ctx).await.expect("create_file should succeed");
+
+        assert_eq!(
+            std::fs::read_to_string(snapshot_dir.path().join("bound.txt")).unwrap(),
+            "snapshotted"
+        );
+        assert!(!changed_dir.path().join("bound.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_gather_task_collects_outputs_in_order() {
+        let ctx = Context::new();
+        ctx.set("each_item_0.output", "first".to_string()).await;
+        ctx.set("each_item_1.output", "second".to_string()).await;
+
+        let task = GatherTask::new(
+            "each".to_string(),
+            vec!["each_item_0".to_string(), "each_item_1".to_string()],
+        );
+        task.run(ctx.clone()).await.unwrap();
+
+        let gathered: String = ctx.get("each.output").await.unwrap();
+        assert_eq!(gathered, r#"["first","second"]"#);
+    }
+
+    #[tokio::test]
+    async fn test_ping_task_outputs_message_after_delay() {
+        let ctx = Context::new();
+        let task = PingTask::new("step_1".to_string(), 50, Some("hello".to_string()));
+
+        let start = std::time::Instant::now();
+        task.run(ctx.clone()).await.unwrap();
+        assert!(start.elapsed() >= std::time::Duration::from_millis(50));
+
+        let output: String = ctx.get("step_1.output").await.unwrap();
+        assert_eq!(output, "hello");
+
+        // Without a message the step answers "pong"
+        PingTask::new("step_2".to_string(), 0, None)
+            .run(ctx.clone())
+            .await
+            .unwrap();
+        let output: String = ctx.get("step_2.output").await.unwrap();
+        assert_eq!(output, "pong");
+    }
+
+    #[tokio::test]
+    async fn test_for_each_task_items_from_json_array() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let ctx = Context::new();
+        use crate::orchestrator::constants::WORKING_DIR_KEY;
+        ctx.set(
+            WORKING_DIR_KEY,
+            temp_dir.path().to_str().unwrap().to_string(),
+        )
+        .await;
+        ctx.set("step_1.output", r#"["a.txt", "b.txt"]"#.to_string())
+            .await;
+        ctx.set("step_2.output", "shared body".to_string()).await;
+
+        let template = StepTemplate {
+            task: "create_file".to_string(),
+            params: crate::orchestrator::plan_types::StepParams {
+                filename: Some("{{index}}-{{item}}".to_string()),
+                content_from: Some("step_2.output".to_string()),
+                ..Default::default()
+            },
+        };
+        let task = ForEachTask::new(
+            "each".to_string(),
+            "step_1.output".to_string(),
+            template,
+            OrchestratorConfig::default(),
+        )
+        .with_app_state(create_test_state());
+        task.run(ctx.clone()).await.unwrap();
+
+        assert!(temp_dir.path().join("0-a.txt").exists());
+        assert!(temp_dir.path().join("1-b.txt").exists());
+        let gathered: String = ctx.get("each.output").await.unwrap();
+        let paths: Vec<String> = serde_json::from_str(&gathered).unwrap();
+        assert_eq!(paths.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_for_each_task_respects_max_parallel_tasks() {
+        let ctx = Context::new();
+        ctx.set("step_1.output", r#"["a", "b", "c"]"#.to_string())
+            .await;
+
+        let template = StepTemplate {
+            task: "ping".to_string(),
+            params: crate::orchestrator::plan_types::StepParams {
+                delay_ms: Some(50),
+                message: Some("{{item}}".to_string()),
+                ..Default::default()
+            },
+        };
+        let config = OrchestratorConfig {
+            max_parallel_tasks: 1,
+            enable_ping_task: true,
+            ..Default::default()
+        };
+        let task = ForEachTask::new(
+            "each".to_string(),
+            "step_1.output".to_string(),
+            template,
+            config,
+        )
+        .with_app_state(create_test_state());
+
+        // One item at a time: the three delays add up
+        let start = std::time::Instant::now();
+        task.run(ctx.clone()).await.unwrap();
+        assert!(start.elapsed() >= std::time::Duration::from_millis(150));
+
+        let gathered: String = ctx.get("each.output").await.unwrap();
+        assert_eq!(gathered, r#"["a","b","c"]"#);
+    }
+
+    #[tokio::test]
+    async fn test_create_file_task_relative_path_without_working_dir_fails() {
+        let ctx = Context::new();
+        use crate::orchestrator::constants::WORKING_DIR_BOUND_KEY;
+        // Run bound to "no working directory"
+        ctx.set(WORKING_DIR_BOUND_KEY, true).await;
+        ctx.set("step_1.output", "Test content".to_string()).await;
+
+        let file_name = format!("no-working-dir-{}.txt", uuid::Uuid::new_v4());
+        let task = CreateFileTask::new(
+            "step_2".to_string(),
+            file_name.clone(),
+            Some("step_1.output".to_string()),
+        )
+        .with_app_state(create_test_state());
+
+        let result = task.run(ctx).await;
+
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("no working directory is set"));
+        assert!(!std::path::Path::new(&file_name).exists());
+    }
 }
@@ -0,0 +1,130 @@
+//! Token usage accounting
+//!
+//! Gemini CLI (`--output-format json`) reports the tokens each model consumed
+//! under `stats.models`. A single call can involve more than one model, and a
+//! plan with fallbacks or per-step models mixes them, so usage is kept per model
+//! and only summed for the plan total.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Tokens consumed by one or more Gemini calls
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    /// Tokens in the prompt (input)
+    pub prompt_tokens: u64,
+    /// Tokens in the generated candidates (output)
+    pub output_tokens: u64,
+    /// Total tokens as reported by Gemini (may include thoughts and tool use)
+    pub total_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Add `other` to this usage
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.output_tokens += other.output_tokens;
+        self.total_tokens += other.total_tokens;
+    }
+}
+
+/// Token usage keyed by model name
+pub type UsageByModel = HashMap<String, TokenUsage>;
+
+/// Extract per-model token usage from raw Gemini CLI JSON output
+///
+/// Reads `stats.models.<model>.tokens.{prompt, candidates, total}`. Output
+/// without stats (older CLIs, plain text) yields an empty map.
+pub fn parse_gemini_cli_usage(raw_output: &str) -> UsageByModel {
+    #[derive(Deserialize)]
+    struct CliOutput {
+        stats: Option<CliStats>,
+    }
+    #[derive(Deserialize)]
+    struct CliStats {
+        #[serde(default)]
+        models: HashMap<String, CliModelStats>,
+    }
+    #[derive(Deserialize)]
+    struct CliModelStats {
+        tokens: Option<CliTokens>,
+    }
+    #[derive(Deserialize)]
+    struct CliTokens {
+        #[serde(default)]
+        prompt: u64,
+        #[serde(default)]
+        candidates: u64,
+        #[serde(default)]
+        total: u64,
+    }
+
+    let Ok(CliOutput { stats: Some(stats) }) = serde_json::from_str::<CliOutput>(raw_output) else {
+        return UsageByModel::new();
+    };
+    stats
+        .models
+        .into_iter()
+        .filter_map(|(model, model_stats)| {
+            let tokens = model_stats.tokens?;
+            Some((
+                model,
+                TokenUsage {
+                    prompt_tokens: tokens.prompt,
+                    output_tokens: tokens.candidates,
+                    total_tokens: tokens.total,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Sum per-model usage across several calls or steps
+///
+/// # Returns
+/// * `(total, by_model)` - The overall total and the per-model breakdown it sums
+pub fn aggregate_usage<'a>(
+    usages: impl IntoIterator<Item = &'a UsageByModel>,
+) -> (TokenUsage, UsageByModel) {
+    let mut total = TokenUsage::default();
+    let mut by_model = UsageByModel::new();
+    for usage in usages {
+        for (model, tokens) in usage {
+            total.add(tokens);
+            by_model.entry(model.clone()).or_default().add(tokens);
+        }
+    }
+    (total, by_model)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gemini_cli_usage_reads_each_model() {
+        let raw = r#"{
+            "response": "hi",
+            "stats": {"models": {
+                "gemini-2.5-pro": {"api": {"totalRequests": 1}, "tokens": {"prompt": 100, "candidates": 20, "total": 130, "thoughts": 10}},
+                "gemini-2.5-flash": {"tokens": {"prompt": 5, "candidates": 1, "total": 6}}
+            }}
+        }"#;
+
+        let usage = parse_gemini_cli_usage(raw);
+        assert_eq!(usage.len(), 2);
+        assert_eq!(
+            usage["gemini-2.5-pro"],
+            TokenUsage {
+                prompt_tokens: 100,
+                output_tokens: 20,
+                total_tokens: 130,
+            }
+        );
+        assert_eq!(usage["gemini-2.5-flash"].total_tokens, 6);
+
+        // No stats, or not JSON at all
+        assert!(parse_gemini_cli_usage(r#"{"response": "hi"}"#).is_empty());
+        assert!(parse_gemini_cli_usage("plain text").is_empty());
+    }
+}
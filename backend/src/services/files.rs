@@ -3,6 +3,7 @@
 //! Provides file system operations with proper error handling and validation.
 
 use crate::error::AppError;
+use crate::services::working_dir::resolve_within;
 use anyhow::anyhow;
 use serde::Serialize;
 use std::io::ErrorKind;
@@ -182,15 +183,205 @@ impl FileService {
     ///
     /// # Arguments
     /// * `file_path` - Path to the file (can be relative or absolute)
-    /// * `content` - Content to write to the file
+    /// * `content` - Content to write to the file (text or raw bytes)
     /// * `working_dir` - Optional working directory context (for relative paths)
+    /// * `file_mode` - Optional Unix permission bits for the file
     ///
     /// # Returns
     /// * `Ok(PathBuf)` - Canonicalized absolute path of the created file
     /// * `Err(AppError)` - If file cannot be created or written
     pub async fn write_file(
         file_path: &str,
-        content: &str,
+        content: impl AsRef<[u8]>,
+        working_dir: Option<&str>,
+        file_mode: Option<u32>,
+    ) -> Result<PathBuf, AppError> {
+        let absolute_path = Self::prepare_write_path(file_path, working_dir).await?;
+
+        // Write the file
+        Self::write_with_mode(
+            &absolute_path,
+            file_path,
+            content.as_ref(),
+            false,
+            file_mode,
+        )
+        .await?;
+
+        Self::canonicalize_written(&absolute_path)
+    }
+
+    /// Write content to a file unless it already holds exactly that content
+    ///
+    /// Skipping the write leaves the file's modification time untouched.
+    ///
+    /// # Arguments
+    /// * `file_path` - Path to the file (can be relative or absolute)
+    /// * `content` - Content to write (text or raw bytes)
+    /// * `working_dir` - Optional working directory context (for relative paths)
+    /// * `file_mode` - Optional Unix permission bits, applied even when unchanged
+    ///
+    /// # Returns
+    /// * `Ok((PathBuf, bool))` - Canonicalized path, and whether the file was written
+    /// * `Err(AppError)` - If the file cannot be read or written
+    pub async fn write_file_if_changed(
+        file_path: &str,
+        content: impl AsRef<[u8]>,
+        working_dir: Option<&str>,
+        file_mode: Option<u32>,
+    ) -> Result<(PathBuf, bool), AppError> {
+        let absolute_path = Self::prepare_write_path(file_path, working_dir).await?;
+        let content = content.as_ref();
+
+        // Only read the existing file when the sizes match
+        let unchanged = match fs::metadata(&absolute_path).await {
+            Ok(metadata) if metadata.is_file() && metadata.len() == content.len() as u64 => {
+                let existing = fs::read(&absolute_path).await.map_err(|e| {
+                    AppError::Internal(anyhow!("Failed to read file {}: {}", file_path, e))
+                })?;
+                existing == content
+            }
+            _ => false,
+        };
+        if unchanged {
+            if let Some(file_mode) = file_mode {
+                let file = fs::File::open(&absolute_path).await.map_err(|e| {
+                    AppError::Internal(anyhow!("Failed to open file {}: {}", file_path, e))
+                })?;
+                Self::set_mode(&file, file_path, file_mode).await?;
+            }
+            return Ok((Self::canonicalize_written(&absolute_path)?, false));
+        }
+
+        Self::write_with_mode(&absolute_path, file_path, content, false, file_mode).await?;
+        Ok((Self::canonicalize_written(&absolute_path)?, true))
+    }
+
+    /// Append content to a file, creating it if missing
+    ///
+    /// # Arguments
+    /// * `file_path` - Path to the file (can be relative or absolute)
+    /// * `content` - Content to append (text or raw bytes)
+    /// * `working_dir` - Optional working directory context (for relative paths)
+    /// * `max_size` - Largest size in bytes the file may reach after appending
+    /// * `file_mode` - Optional Unix permission bits for the file
+    ///
+    /// # Returns
+    /// * `Ok(PathBuf)` - Canonicalized absolute path of the file
+    /// * `Err(AppError)` - If the file would exceed `max_size` or cannot be written
+    pub async fn append_file(
+        file_path: &str,
+        content: impl AsRef<[u8]>,
+        working_dir: Option<&str>,
+        max_size: u64,
+        file_mode: Option<u32>,
+    ) -> Result<PathBuf, AppError> {
+        let absolute_path = Self::prepare_write_path(file_path, working_dir).await?;
+        let content = content.as_ref();
+
+        let existing = match fs::metadata(&absolute_path).await {
+            Ok(metadata) => metadata.len(),
+            Err(e) if e.kind() == ErrorKind::NotFound => 0,
+            Err(e) => {
+                return Err(AppError::Internal(anyhow!(
+                    "Failed to read metadata of {}: {}",
+                    file_path,
+                    e
+                )))
+            }
+        };
+        let resulting = existing + content.len() as u64;
+        if resulting > max_size {
+            return Err(AppError::PolicyViolation(format!(
+                "Appending to {} would grow it to {} bytes (maximum {})",
+                file_path, resulting, max_size
+            )));
+        }
+
+        Self::write_with_mode(&absolute_path, file_path, content, true, file_mode).await?;
+
+        Self::canonicalize_written(&absolute_path)
+    }
+
+    /// Open, truncate or append, and write a file
+    ///
+    /// A new file is created with `file_mode` on Unix, so it never exists with
+    /// wider permissions than requested. An existing file (or one narrowed by
+    /// the umask) gets the mode through the open handle before any content is
+    /// written. Other platforms ignore the mode with a warning.
+    async fn write_with_mode(
+        absolute_path: &Path,
+        file_path: &str,
+        content: &[u8],
+        append: bool,
+        file_mode: Option<u32>,
+    ) -> Result<(), AppError> {
+        use tokio::io::AsyncWriteExt;
+
+        let mut options = fs::OpenOptions::new();
+        options.create(true);
+        if append {
+            options.append(true);
+        } else {
+            options.write(true).truncate(true);
+        }
+        #[cfg(unix)]
+        if let Some(file_mode) = file_mode {
+            options.mode(file_mode);
+        }
+
+        let mut file = options
+            .open(absolute_path)
+            .await
+            .map_err(|e| AppError::Internal(anyhow!("Failed to open file {}: {}", file_path, e)))?;
+        if let Some(file_mode) = file_mode {
+            Self::set_mode(&file, file_path, file_mode).await?;
+        }
+        file.write_all(content).await.map_err(|e| {
+            AppError::Internal(anyhow!("Failed to write file {}: {}", file_path, e))
+        })?;
+        file.flush().await.map_err(|e| {
+            AppError::Internal(anyhow!("Failed to write file {}: {}", file_path, e))
+        })?;
+        Ok(())
+    }
+
+    /// Set the Unix permission bits of an open file
+    ///
+    /// Other platforms have no such bits, so there the mode is ignored with a warning.
+    async fn set_mode(file: &fs::File, file_path: &str, file_mode: u32) -> Result<(), AppError> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(std::fs::Permissions::from_mode(file_mode))
+                .await
+                .map_err(|e| {
+                    AppError::Internal(anyhow!(
+                        "Failed to set mode {:o} on {}: {}",
+                        file_mode,
+                        file_path,
+                        e
+                    ))
+                })
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = file;
+            tracing::warn!(
+                file_path = %file_path,
+                mode = %format!("{:o}", file_mode),
+                "file_mode is only supported on Unix; leaving default permissions"
+            );
+            Ok(())
+        }
+    }
+
+    /// Resolve the absolute path to write and create its parent directories
+    ///
+    /// Relative paths are placed inside `working_dir` with `resolve_within`,
+    /// so they can't climb out of it.
+    async fn prepare_write_path(
+        file_path: &str,
         working_dir: Option<&str>,
     ) -> Result<PathBuf, AppError> {
         let path = Path::new(file_path);
@@ -203,8 +394,7 @@ impl FileService {
                     relative_path = %file_path,
                     "FileService::write_file: Resolving relative path with working directory"
                 );
-                let work_dir_path = Self::validate_directory_path(work_dir)?;
-                let resolved = work_dir_path.join(path);
+                let resolved = resolve_within(Path::new(work_dir), file_path)?;
                 tracing::debug!(
                     resolved_path = %resolved.display(),
                     "FileService::write_file: Resolved absolute path"
@@ -233,17 +423,14 @@ impl FileService {
             })?;
         }
 
-        // Write the file
-        fs::write(&absolute_path, content).await.map_err(|e| {
-            AppError::Internal(anyhow!("Failed to write file {}: {}", file_path, e))
-        })?;
+        Ok(absolute_path)
+    }
 
-        // Canonicalize the path
-        let canonical = absolute_path
+    /// Canonicalize the path of a file that was just written
+    fn canonicalize_written(absolute_path: &Path) -> Result<PathBuf, AppError> {
+        absolute_path
             .canonicalize()
-            .map_err(|e| AppError::InvalidPath(format!("Failed to canonicalize path: {}", e)))?;
-
-        Ok(canonical)
+            .map_err(|e| AppError::InvalidPath(format!("Failed to canonicalize path: {}", e)))
     }
 }
 
@@ -346,7 +533,8 @@ mod tests {
         let file_path = temp_dir.path().join("test.txt");
         let content = "Hello, world!";
 
-        let result = FileService::write_file(file_path.to_str().unwrap(), content, None).await;
+        let result =
+            FileService::write_file(file_path.to_str().unwrap(), content, None, None).await;
 
         assert!(result.is_ok());
         let canonical = result.unwrap();
@@ -358,6 +546,51 @@ mod tests {
         assert_eq!(written_content, content);
     }
 
+    #[tokio::test]
+    async fn test_write_file_if_changed_skips_identical_content() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let file_path = temp_dir.path().join("same.txt");
+        let path = file_path.to_str().unwrap();
+        std::fs::write(&file_path, "unchanged").unwrap();
+        // Backdate the file so a rewrite would visibly move its mtime
+        let past = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        std::fs::File::options()
+            .write(true)
+            .open(&file_path)
+            .unwrap()
+            .set_modified(past)
+            .unwrap();
+
+        let (_, written) = FileService::write_file_if_changed(path, "unchanged", None, None)
+            .await
+            .unwrap();
+        assert!(!written);
+        assert_eq!(
+            std::fs::metadata(&file_path).unwrap().modified().unwrap(),
+            past
+        );
+
+        // Same length, different bytes
+        let (canonical, written) =
+            FileService::write_file_if_changed(path, "different", None, None)
+                .await
+                .unwrap();
+        assert!(written);
+        assert_eq!(std::fs::read_to_string(canonical).unwrap(), "different");
+        assert_ne!(
+            std::fs::metadata(&file_path).unwrap().modified().unwrap(),
+            past
+        );
+
+        // A missing file is always written
+        let fresh = temp_dir.path().join("fresh.txt");
+        let (_, written) =
+            FileService::write_file_if_changed(fresh.to_str().unwrap(), "new", None, None)
+                .await
+                .unwrap();
+        assert!(written);
+    }
+
     #[tokio::test]
     async fn test_write_file_with_working_dir() {
         let temp_dir = tempdir().expect("Failed to create temp dir");
@@ -365,7 +598,7 @@ mod tests {
         let file_path = "subdir/test.txt";
         let content = "Test content";
 
-        let result = FileService::write_file(file_path, content, Some(work_dir)).await;
+        let result = FileService::write_file(file_path, content, Some(work_dir), None).await;
 
         assert!(result.is_ok());
         let canonical = result.unwrap();
@@ -378,4 +611,22 @@ mod tests {
         let written_content = std::fs::read_to_string(&canonical).expect("Failed to read file");
         assert_eq!(written_content, content);
     }
+
+    #[tokio::test]
+    async fn test_append_file_respects_size_cap() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let work_dir = temp_dir.path().to_str().unwrap();
+
+        FileService::append_file("log.txt", "12345", Some(work_dir), 8, None)
+            .await
+            .unwrap();
+        let result = FileService::append_file("log.txt", "6789", Some(work_dir), 8, None).await;
+        let error = result.unwrap_err();
+        assert!(matches!(error, AppError::PolicyViolation(_)), "{:?}", error);
+        assert!(error.to_string().contains("maximum 8"));
+
+        // A rejected append leaves the file untouched
+        let content = std::fs::read_to_string(temp_dir.path().join("log.txt")).unwrap();
+        assert_eq!(content, "12345");
+    }
 }
@@ -4,3 +4,4 @@
 //! from HTTP handlers, making the code more modular and testable.
 
 pub mod files;
+pub mod working_dir;
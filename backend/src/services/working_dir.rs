@@ -0,0 +1,295 @@
+//! Working directories
+//!
+//! A `WorkingDir` is a directory that agents and file-writing steps may use:
+//! it is canonical, exists, is writable, and lies inside the configured
+//! `WorkingDirRoots` (`workspace_root` and `allowed_working_dirs`). All of
+//! that is verified once, by the only constructor, so code holding a
+//! `WorkingDir` never re-validates it. Files are placed inside it with
+//! `join`, which refuses any path that would resolve outside.
+
+use crate::error::AppError;
+use crate::services::files::FileService;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+/// Directories every working directory must lie inside
+///
+/// Built from the server config with `AppState::working_dir_roots`; the
+/// default allows any directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkingDirRoots {
+    /// Directory it must lie inside (None = anywhere)
+    pub workspace_root: Option<String>,
+    /// `allowed_working_dirs`: it must lie inside one of these (empty = anywhere)
+    pub allowed_dirs: Vec<String>,
+}
+
+/// A validated working directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkingDir {
+    /// Canonical absolute path (always valid UTF-8)
+    path: String,
+}
+
+impl WorkingDir {
+    /// Validate `path` as a working directory inside `roots`
+    ///
+    /// The roots are canonicalized too, so symlinks can't be used to escape them.
+    ///
+    /// # Returns
+    /// * `Ok(WorkingDir)` - The canonicalized directory
+    /// * `Err(AppError::FileNotFound)` - If it doesn't exist
+    /// * `Err(AppError::NotADirectory)` - If it isn't a directory
+    /// * `Err(AppError::InvalidPath)` - If it can't be resolved or isn't UTF-8
+    /// * `Err(AppError::PermissionDenied)` - If it isn't writable or lies
+    ///   outside `workspace_root` or every allowed directory
+    pub fn new(path: &str, roots: &WorkingDirRoots) -> Result<Self, AppError> {
+        let dir = Self::validate(path)?;
+        if let Some(ref root) = roots.workspace_root {
+            let root = FileService::validate_directory_path(root)?;
+            if !dir.path().starts_with(&root) {
+                return Err(AppError::PermissionDenied(format!(
+                    "Working directory '{}' is outside the workspace root",
+                    path
+                )));
+            }
+        }
+        let allowed = roots.allowed_dirs.is_empty()
+            || roots.allowed_dirs.iter().any(|root| {
+                std::fs::canonicalize(root).is_ok_and(|root| dir.path().starts_with(root))
+            });
+        if !allowed {
+            return Err(AppError::PermissionDenied(format!(
+                "Working directory '{}' is outside the allowed directories",
+                dir
+            )));
+        }
+        Ok(dir)
+    }
+
+    /// Check that `path` is an existing, writable directory and canonicalize it
+    fn validate(path: &str) -> Result<Self, AppError> {
+        let canonical = FileService::validate_directory_path(path)?;
+        let readonly = std::fs::metadata(&canonical)
+            .map(|metadata| metadata.permissions().readonly())
+            .map_err(|e| AppError::InvalidPath(format!("Invalid path: {} - {}", path, e)))?;
+        if readonly {
+            return Err(AppError::PermissionDenied(format!(
+                "Working directory '{}' is not writable",
+                path
+            )));
+        }
+        let path = canonical.into_os_string().into_string().map_err(|raw| {
+            AppError::InvalidPath(format!(
+                "Path is not valid UTF-8: {}",
+                raw.to_string_lossy()
+            ))
+        })?;
+        Ok(Self { path })
+    }
+
+    /// The canonical path
+    pub fn path(&self) -> &Path {
+        Path::new(&self.path)
+    }
+
+    /// Resolve `relative` to a path inside this directory
+    ///
+    /// See `resolve_within`.
+    pub fn join(&self, relative: &str) -> Result<PathBuf, AppError> {
+        resolve_within(self.path(), relative)
+    }
+}
+
+/// Resolve `relative` to a path inside the directory `base`
+///
+/// Absolute paths and `..` components are refused outright. Existing
+/// symlinks along the way (or at the target itself) are followed and must
+/// stay inside too; missing directories are left for the caller to create.
+/// For directories that were validated earlier and are only known by path,
+/// such as the working directory stored in a run's context.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - The path to use, under `base`
+/// * `Err(AppError::InvalidPath)` - If it would resolve outside `base`, or
+///   `base` can't be resolved
+pub fn resolve_within(base: &Path, relative: &str) -> Result<PathBuf, AppError> {
+    let base = &base
+        .canonicalize()
+        .map_err(|e| AppError::InvalidPath(format!("Invalid path: {} - {}", base.display(), e)))?;
+    let escaped = || {
+        AppError::InvalidPath(format!(
+            "'{}' resolves outside the working directory '{}'",
+            relative,
+            base.display()
+        ))
+    };
+    let relative_path = Path::new(relative);
+    let plain = relative_path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if relative.trim().is_empty() || !plain {
+        return Err(escaped());
+    }
+
+    let target = base.join(relative_path);
+    let inside = |path: &Path| {
+        path.canonicalize()
+            .is_ok_and(|canonical| canonical.starts_with(base))
+    };
+
+    // A symlink (even a dangling one) at the target must point back inside
+    if target.symlink_metadata().is_ok() && !inside(&target) {
+        return Err(escaped());
+    }
+    // Otherwise the nearest existing ancestor decides where it ends up
+    let mut existing = target.parent().unwrap_or(base);
+    while !existing.exists() {
+        existing = existing.parent().ok_or_else(escaped)?;
+    }
+    if !inside(existing) {
+        return Err(escaped());
+    }
+    Ok(target)
+}
+
+impl fmt::Display for WorkingDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_construction_checks_the_directory() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().to_str().unwrap();
+
+        // Canonicalized on the way in
+        let dir = WorkingDir::new(&format!("{}/./", path), &WorkingDirRoots::default()).unwrap();
+        assert_eq!(dir.path(), temp_dir.path().canonicalize().unwrap());
+        assert_eq!(
+            dir.to_string(),
+            temp_dir.path().canonicalize().unwrap().to_str().unwrap()
+        );
+
+        assert!(matches!(
+            WorkingDir::new(&format!("{}/missing", path), &WorkingDirRoots::default()),
+            Err(AppError::FileNotFound(_))
+        ));
+        let file = temp_dir.path().join("file.txt");
+        std::fs::write(&file, "x").unwrap();
+        assert!(matches!(
+            WorkingDir::new(file.to_str().unwrap(), &WorkingDirRoots::default()),
+            Err(AppError::NotADirectory(_))
+        ));
+
+        let readonly = temp_dir.path().join("readonly");
+        std::fs::create_dir(&readonly).unwrap();
+        let mut permissions = std::fs::metadata(&readonly).unwrap().permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&readonly, permissions).unwrap();
+        assert!(matches!(
+            WorkingDir::new(readonly.to_str().unwrap(), &WorkingDirRoots::default()),
+            Err(AppError::PermissionDenied(_))
+        ));
+    }
+
+    #[test]
+    fn test_construction_enforces_roots() {
+        let root = tempdir().unwrap();
+        let inner = root.path().join("project");
+        std::fs::create_dir(&inner).unwrap();
+        let outside = tempdir().unwrap();
+        let root_str = root.path().to_str().unwrap();
+        let inner_str = inner.to_str().unwrap();
+        let outside_str = outside.path().to_str().unwrap();
+        let workspace = WorkingDirRoots {
+            workspace_root: Some(root_str.to_string()),
+            ..Default::default()
+        };
+        let allowed = WorkingDirRoots {
+            allowed_dirs: vec![root_str.to_string()],
+            ..Default::default()
+        };
+
+        assert!(WorkingDir::new(inner_str, &workspace).is_ok());
+        assert!(WorkingDir::new(inner_str, &allowed).is_ok());
+        assert!(WorkingDir::new(outside_str, &WorkingDirRoots::default()).is_ok());
+        // `..` is resolved before the root check
+        let sneaky = format!("{}/project/../../", root_str);
+        assert!(matches!(
+            WorkingDir::new(&sneaky, &workspace),
+            Err(AppError::PermissionDenied(_))
+        ));
+        for roots in [&workspace, &allowed] {
+            assert!(matches!(
+                WorkingDir::new(outside_str, roots),
+                Err(AppError::PermissionDenied(_))
+            ));
+        }
+
+        // Both roots apply at once
+        let both = WorkingDirRoots {
+            workspace_root: Some(inner_str.to_string()),
+            allowed_dirs: vec![outside_str.to_string()],
+        };
+        assert!(matches!(
+            WorkingDir::new(inner_str, &both),
+            Err(AppError::PermissionDenied(_))
+        ));
+        assert!(matches!(
+            WorkingDir::new(outside_str, &both),
+            Err(AppError::PermissionDenied(_))
+        ));
+    }
+
+    #[test]
+    fn test_join_cannot_escape() {
+        let temp_dir = tempdir().unwrap();
+        let outside = tempdir().unwrap();
+        let dir = WorkingDir::new(
+            temp_dir.path().to_str().unwrap(),
+            &WorkingDirRoots::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            dir.join("notes/today.txt").unwrap(),
+            dir.path().join("notes/today.txt")
+        );
+        assert_eq!(dir.join("./a.txt").unwrap(), dir.path().join("a.txt"));
+
+        for escape in [
+            "../escape.txt",
+            "notes/../../escape.txt",
+            "notes/..",
+            "/etc/passwd",
+            "",
+        ] {
+            assert!(
+                matches!(dir.join(escape), Err(AppError::InvalidPath(_))),
+                "{:?} should have been refused",
+                escape
+            );
+        }
+
+        #[cfg(unix)]
+        {
+            // Symlinks pointing outside can't be written through
+            std::os::unix::fs::symlink(outside.path(), temp_dir.path().join("link")).unwrap();
+            std::os::unix::fs::symlink(
+                outside.path().join("target.txt"),
+                temp_dir.path().join("file-link"),
+            )
+            .unwrap();
+            assert!(dir.join("link/out.txt").is_err());
+            assert!(dir.join("link/deeper/out.txt").is_err());
+            assert!(dir.join("file-link").is_err());
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Per-agent log ring buffer
+//!
+//! Keeps the most recent output lines for each agent so clients can fetch
+//! recent logs, catch up after reconnecting (`since`), or follow new lines live.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use tokio::sync::broadcast;
+
+/// Default number of lines retained per agent
+pub const DEFAULT_LOG_CAPACITY: usize = 1000;
+
+/// Capacity of the live-follow channel (slow followers skip lines beyond this)
+const FOLLOW_CHANNEL_CAPACITY: usize = 256;
+
+/// A single timestamped log line
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LogLine {
+    /// Time the line was recorded (Unix milliseconds)
+    pub timestamp_ms: i64,
+    /// The log line text
+    pub line: String,
+}
+
+/// Bounded log buffer for a single agent
+///
+/// Oldest lines are dropped once `capacity` is reached. New lines are also
+/// broadcast to any followers; dropping the buffer closes their streams.
+#[derive(Debug)]
+pub struct AgentLog {
+    lines: VecDeque<LogLine>,
+    capacity: usize,
+    sender: broadcast::Sender<LogLine>,
+}
+
+impl AgentLog {
+    /// Create an empty log buffer holding at most `capacity` lines
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(FOLLOW_CHANNEL_CAPACITY);
+        Self {
+            lines: VecDeque::with_capacity(capacity.min(DEFAULT_LOG_CAPACITY)),
+            capacity,
+            sender,
+        }
+    }
+
+    /// Append a line stamped with the current time
+    pub fn push(&mut self, line: String) -> LogLine {
+        self.push_with_timestamp(chrono::Utc::now().timestamp_millis(), line)
+    }
+
+    /// Append a line with an explicit timestamp
+    pub fn push_with_timestamp(&mut self, timestamp_ms: i64, line: String) -> LogLine {
+        let entry = LogLine { timestamp_ms, line };
+        if self.lines.len() >= self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(entry.clone());
+        // No followers is not an error
+        let _ = self.sender.send(entry.clone());
+        entry
+    }
+
+    /// Lines recorded strictly after `since` (all lines if `None`)
+    pub fn since(&self, since: Option<i64>) -> Vec<LogLine> {
+        self.lines
+            .iter()
+            .filter(|l| match since {
+                Some(ts) => l.timestamp_ms > ts,
+                None => true,
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Subscribe to lines appended from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<LogLine> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for AgentLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_LOG_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agent_log_drops_oldest_at_capacity() {
+        let mut log = AgentLog::new(2);
+        log.push_with_timestamp(1, "a".to_string());
+        log.push_with_timestamp(2, "b".to_string());
+        log.push_with_timestamp(3, "c".to_string());
+
+        let lines: Vec<String> = log.since(None).into_iter().map(|l| l.line).collect();
+        assert_eq!(lines, vec!["b", "c"]);
+    }
+
+    #[test]
+    fn test_agent_log_since_filter() {
+        let mut log = AgentLog::default();
+        log.push_with_timestamp(100, "old".to_string());
+        log.push_with_timestamp(200, "boundary".to_string());
+        log.push_with_timestamp(300, "new".to_string());
+
+        let lines: Vec<String> = log.since(Some(200)).into_iter().map(|l| l.line).collect();
+        assert_eq!(lines, vec!["new"]);
+        assert_eq!(log.since(Some(0)).len(), 3);
+        assert!(log.since(Some(300)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_agent_log_subscribe_receives_new_lines_and_closes_on_drop() {
+        let mut log = AgentLog::default();
+        let mut rx = log.subscribe();
+
+        log.push("hello".to_string());
+        assert_eq!(rx.recv().await.unwrap().line, "hello");
+
+        drop(log);
+        assert!(matches!(
+            rx.recv().await,
+            Err(broadcast::error::RecvError::Closed)
+        ));
+    }
+}
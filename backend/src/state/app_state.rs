@@ -3,9 +3,21 @@
 //! Contains agent registry, selected agent, working directory context, and UI state.
 //! This module manages the core application state that persists across requests.
 
+use crate::api::queries::DEFAULT_BATCH_QUERY_CONCURRENCY;
+use crate::api::streaming::{DEFAULT_MAX_SSE_DURATION_SECS, DEFAULT_SSE_RETRY_MS};
+use crate::executor::cli::DEFAULT_FALLBACK_WORKING_DIR;
+use crate::executor::OutputLimit;
+use crate::orchestrator::config::OrchestratorConfig;
+use crate::orchestrator::execution_limiter::ExecutionLimiter;
+use crate::services::working_dir::{WorkingDir, WorkingDirRoots};
+use crate::state::agent_logs::{AgentLog, LogLine};
 use crate::state::config::{AgentConfig, AgentType};
+use crate::state::status_history::{StatusChange, StatusHistory};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::{broadcast, Notify, Semaphore};
 use uuid::Uuid;
 
 /// Unique identifier for an agent
@@ -39,6 +51,18 @@ pub struct Agent {
     pub status: AgentStatus,
     /// Agent configuration (command, args, env vars, etc.)
     pub config: AgentConfig,
+    /// Reason for the most recent failed execution, cleared on the next success
+    #[serde(default)]
+    pub last_error: Option<LastError>,
+}
+
+/// A failed execution recorded on an agent
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LastError {
+    /// Error message of the failed execution
+    pub message: String,
+    /// When the failure happened (Unix ms)
+    pub timestamp_ms: i64,
 }
 
 impl Agent {
@@ -51,11 +75,11 @@ impl Agent {
             agent_type: agent_type.clone(),
             status: AgentStatus::Idle,
             config: AgentConfig::for_type(&agent_type),
+            last_error: None,
         }
     }
 
     /// Create a new agent with a custom configuration
-    #[allow(dead_code)] // Reserved for future use
     pub fn with_config(
         id: AgentId,
         name: String,
@@ -68,6 +92,7 @@ impl Agent {
             agent_type,
             status: AgentStatus::Idle,
             config,
+            last_error: None,
         }
     }
 
@@ -90,7 +115,7 @@ impl Agent {
 
 /// Main application state
 /// Manages all application-wide state including agents and UI preferences
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct AppState {
     /// Registry of all agents (id -> Agent)
     pub agents: HashMap<AgentId, Agent>,
@@ -98,6 +123,70 @@ pub struct AppState {
     pub selected_agent_id: Option<AgentId>,
     /// UI state preferences
     pub ui_state: UiState,
+    /// Agent type used when an agent has to be auto-created
+    pub default_agent_type: AgentType,
+    /// Recent output lines per agent (created lazily, removed with the agent)
+    pub agent_logs: HashMap<AgentId, AgentLog>,
+    /// Recent status transitions per agent (created lazily, removed with the agent)
+    pub status_history: HashMap<AgentId, StatusHistory>,
+    /// Abort signals of the executions currently running for each agent
+    pub in_flight_executions: HashMap<AgentId, Vec<Arc<Notify>>>,
+    /// Execution slots per agent and the `max_concurrency` they were sized for
+    agent_slots: HashMap<AgentId, (usize, Arc<Semaphore>)>,
+    /// Active orchestrator configuration (changed via /api/config)
+    pub orchestrator_config: OrchestratorConfig,
+    /// Bearer token required for WebSocket connections (None = allow all)
+    pub ws_auth_token: Option<String>,
+    /// Limit on orchestrations running at once across all clients
+    pub execution_limiter: ExecutionLimiter,
+    /// Cap on output captured from agent processes
+    pub cli_output_limit: OutputLimit,
+    /// Working directory for agent processes that have none configured
+    pub cli_fallback_working_dir: String,
+    /// Directory that per-query working directories must lie inside (None = any)
+    pub workspace_root: Option<String>,
+    /// Number of queries from one batch request executed at once
+    pub batch_query_concurrency: usize,
+    /// Reconnect delay sent as the `retry:` directive opening SSE streams (0 = none)
+    pub sse_retry_ms: u64,
+    /// Longest an SSE stream may stay open before it is closed, in seconds (0 = no cap)
+    pub max_sse_duration_secs: u64,
+    /// Directories the working directory may be set inside (empty = anywhere)
+    pub allowed_working_dirs: Vec<String>,
+    /// Named working directory profiles (name -> directory), saved with the registry
+    pub profiles: HashMap<String, String>,
+    /// Registry file that agents and profiles are saved to (None = not persisted)
+    pub registry_path: Option<PathBuf>,
+    /// File the orchestrator config is saved to on change (None = not persisted)
+    pub config_path: Option<PathBuf>,
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self {
+            agents: HashMap::new(),
+            selected_agent_id: None,
+            ui_state: UiState::default(),
+            default_agent_type: AgentType::Gemini,
+            agent_logs: HashMap::new(),
+            status_history: HashMap::new(),
+            in_flight_executions: HashMap::new(),
+            agent_slots: HashMap::new(),
+            orchestrator_config: OrchestratorConfig::default(),
+            ws_auth_token: None,
+            execution_limiter: ExecutionLimiter::default(),
+            cli_output_limit: OutputLimit::default(),
+            cli_fallback_working_dir: DEFAULT_FALLBACK_WORKING_DIR.to_string(),
+            workspace_root: None,
+            batch_query_concurrency: DEFAULT_BATCH_QUERY_CONCURRENCY,
+            sse_retry_ms: DEFAULT_SSE_RETRY_MS,
+            max_sse_duration_secs: DEFAULT_MAX_SSE_DURATION_SECS,
+            allowed_working_dirs: Vec::new(),
+            profiles: HashMap::new(),
+            registry_path: None,
+            config_path: None,
+        }
+    }
 }
 
 /// UI-specific state
@@ -110,7 +199,7 @@ pub struct UiState {
     #[allow(dead_code)] // Reserved for future UI features
     pub terminal_visible: bool,
     /// Current working directory context for AI operations
-    pub working_directory: Option<String>,
+    pub working_directory: Option<WorkingDir>,
 }
 
 impl Default for UiState {
@@ -172,12 +261,54 @@ impl AppState {
     #[allow(dead_code)] // Prepared for Phase 3 (Agent Management Core) - Delete agent UI
     pub fn remove_agent(&mut self, id: &AgentId) -> Option<Agent> {
         let removed = self.agents.remove(id);
+        // Dropping the log buffer also ends any follow streams for this agent
+        self.agent_logs.remove(id);
+        self.status_history.remove(id);
+        self.agent_slots.remove(id);
+        self.abort_executions(id);
         if self.selected_agent_id.as_ref() == Some(id) {
             self.selected_agent_id = None;
         }
         removed
     }
 
+    /// Append a line to an agent's log buffer
+    /// Returns None if the agent does not exist
+    pub fn append_agent_log(&mut self, id: &AgentId, line: String) -> Option<LogLine> {
+        if !self.agents.contains_key(id) {
+            return None;
+        }
+        Some(self.agent_logs.entry(id.clone()).or_default().push(line))
+    }
+
+    /// Get an agent's log lines recorded after `since` (Unix ms), or all if `None`
+    /// Returns None if the agent does not exist
+    pub fn agent_log_lines(&self, id: &AgentId, since: Option<i64>) -> Option<Vec<LogLine>> {
+        if !self.agents.contains_key(id) {
+            return None;
+        }
+        Some(
+            self.agent_logs
+                .get(id)
+                .map(|log| log.since(since))
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Snapshot an agent's log after `since` and subscribe to new lines atomically
+    /// Returns None if the agent does not exist
+    pub fn follow_agent_log(
+        &mut self,
+        id: &AgentId,
+        since: Option<i64>,
+    ) -> Option<(Vec<LogLine>, broadcast::Receiver<LogLine>)> {
+        if !self.agents.contains_key(id) {
+            return None;
+        }
+        let log = self.agent_logs.entry(id.clone()).or_default();
+        Some((log.since(since), log.subscribe()))
+    }
+
     /// Get all agents as a vector, sorted by name
     pub fn agents_list(&self) -> Vec<&Agent> {
         let mut agents: Vec<&Agent> = self.agents.values().collect();
@@ -186,9 +317,16 @@ impl AppState {
     }
 
     /// Update an agent's status
+    /// Actual transitions (old status != new status) are appended to the agent's status history.
     /// Returns true if the agent was found and updated
     pub fn update_agent_status(&mut self, id: &AgentId, status: AgentStatus) -> bool {
         if let Some(agent) = self.agents.get_mut(id) {
+            if agent.status != status {
+                self.status_history
+                    .entry(id.clone())
+                    .or_default()
+                    .record(agent.status, status);
+            }
             agent.status = status;
             true
         } else {
@@ -196,10 +334,103 @@ impl AppState {
         }
     }
 
+    /// Get an agent's status transitions, oldest first
+    /// Returns None if the agent does not exist
+    pub fn agent_status_history(&self, id: &AgentId) -> Option<Vec<StatusChange>> {
+        if !self.agents.contains_key(id) {
+            return None;
+        }
+        Some(
+            self.status_history
+                .get(id)
+                .map(|history| history.changes())
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Record the outcome of an agent execution
+    /// A failure stores its message as the agent's `last_error`; a success clears it.
+    /// Returns true if the agent was found and updated
+    pub fn record_agent_execution(&mut self, id: &AgentId, error: Option<String>) -> bool {
+        if let Some(agent) = self.agents.get_mut(id) {
+            agent.last_error = error.map(|message| LastError {
+                message,
+                timestamp_ms: chrono::Utc::now().timestamp_millis(),
+            });
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Track an execution starting for an agent
+    /// The returned signal fires if the execution should be abandoned; pass it
+    /// to `finish_execution` once the execution completes.
+    pub fn track_execution(&mut self, id: &AgentId) -> Arc<Notify> {
+        let abort = Arc::new(Notify::new());
+        self.in_flight_executions
+            .entry(id.clone())
+            .or_default()
+            .push(abort.clone());
+        abort
+    }
+
+    /// Semaphore bounding how many executions of `agent` run at once
+    ///
+    /// Created on first use with `agent.config.max_concurrency` permits and
+    /// replaced when that limit changes; executions holding a permit of the
+    /// old one finish undisturbed.
+    pub fn execution_slots(&mut self, agent: &Agent) -> Arc<Semaphore> {
+        let limit = agent.config.max_concurrency.max(1);
+        let (size, slots) = self
+            .agent_slots
+            .entry(agent.id.clone())
+            .or_insert_with(|| (limit, Arc::new(Semaphore::new(limit))));
+        if *size != limit {
+            *size = limit;
+            *slots = Arc::new(Semaphore::new(limit));
+        }
+        slots.clone()
+    }
+
+    /// Stop tracking an execution registered with `track_execution`
+    pub fn finish_execution(&mut self, id: &AgentId, abort: &Arc<Notify>) {
+        if let Some(executions) = self.in_flight_executions.get_mut(id) {
+            executions.retain(|tracked| !Arc::ptr_eq(tracked, abort));
+            if executions.is_empty() {
+                self.in_flight_executions.remove(id);
+            }
+        }
+    }
+
+    /// Signal every tracked execution of an agent to abort
+    /// Returns the number of executions signalled
+    fn abort_executions(&mut self, id: &AgentId) -> usize {
+        let executions = self.in_flight_executions.remove(id).unwrap_or_default();
+        for abort in &executions {
+            // notify_one stores a permit, so an execution that has not started
+            // waiting yet still sees the signal
+            abort.notify_one();
+        }
+        executions.len()
+    }
+
+    /// Reset an agent after a crash or failure
+    /// Aborts its in-flight executions, clears `last_error` and sets it to Idle.
+    /// Returns the number of executions aborted, or None if the agent does not exist
+    pub fn reset_agent(&mut self, id: &AgentId) -> Option<usize> {
+        if !self.agents.contains_key(id) {
+            return None;
+        }
+        let aborted = self.abort_executions(id);
+        self.update_agent_status(id, AgentStatus::Idle);
+        self.record_agent_execution(id, None);
+        Some(aborted)
+    }
+
     /// Update an agent in the registry
     /// Replaces the agent with the given ID if it exists
     /// Returns true if the agent was found and updated
-    #[allow(dead_code)] // Reserved for future UI features
     pub fn update_agent(&mut self, id: &AgentId, updated_agent: Agent) -> bool {
         if !self.agents.contains_key(id) {
             return false;
@@ -226,36 +457,82 @@ impl AppState {
     }
 
     /// Set the working directory context
-    pub fn set_working_directory(&mut self, path: Option<String>) {
-        self.ui_state.working_directory = path;
+    pub fn set_working_directory(&mut self, dir: Option<WorkingDir>) {
+        self.ui_state.working_directory = dir;
     }
 
     /// Get the current working directory context
-    pub fn working_directory(&self) -> Option<&String> {
+    pub fn working_directory(&self) -> Option<&WorkingDir> {
         self.ui_state.working_directory.as_ref()
     }
 
+    /// Roots every working directory must lie inside (`workspace_root` and
+    /// `allowed_working_dirs`)
+    pub fn working_dir_roots(&self) -> WorkingDirRoots {
+        WorkingDirRoots {
+            workspace_root: self.workspace_root.clone(),
+            allowed_dirs: self.allowed_working_dirs.clone(),
+        }
+    }
+
+    /// Set the agent type used when an agent has to be auto-created
+    pub fn set_default_agent_type(&mut self, agent_type: AgentType) {
+        self.default_agent_type = agent_type;
+    }
+
+    /// Get the agent type used when an agent has to be auto-created
+    pub fn default_agent_type(&self) -> &AgentType {
+        &self.default_agent_type
+    }
+
     /// Load agents from a file
-    /// Replaces all current agents with those loaded from the file
+    /// Replaces all current agents and profiles with those loaded from the file
     /// Returns the number of agents loaded, or an error if loading failed
     pub fn load_agents<P: AsRef<std::path::Path>>(
         &mut self,
         path: P,
     ) -> Result<usize, super::persistence::PersistenceError> {
-        let loaded_agents = super::persistence::AgentRegistry::load_from_file(path)?;
-        let count = loaded_agents.len();
-        self.agents = loaded_agents;
+        let contents = super::persistence::AgentRegistry::load_registry(path)?;
+        let count = contents.agents.len();
+        self.agents = contents.agents;
+        self.profiles = contents.profiles;
         Ok(count)
     }
 
     /// Save agents to a file
     /// Returns Ok(()) if successful, or an error if saving failed
-    #[allow(dead_code)] // Reserved for future persistence features
     pub fn save_agents<P: AsRef<std::path::Path>>(
         &self,
         path: P,
     ) -> Result<(), super::persistence::PersistenceError> {
-        super::persistence::AgentRegistry::save_to_file(&self.agents, path)
+        super::persistence::AgentRegistry::save_registry(&self.agents, &self.profiles, path)
+    }
+
+    /// Save agents and profiles to `registry_path`, if one is set
+    pub fn save_registry(&self) -> Result<(), super::persistence::PersistenceError> {
+        match self.registry_path {
+            Some(ref path) => self.save_agents(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Make `config` the active orchestrator config, saving it to `config_path`
+    ///
+    /// Nothing changes if the config can't be saved.
+    pub fn set_orchestrator_config(
+        &mut self,
+        config: OrchestratorConfig,
+    ) -> Result<(), super::persistence::PersistenceError> {
+        if let Some(ref path) = self.config_path {
+            config.save(path)?;
+        }
+        self.orchestrator_config = config;
+        Ok(())
+    }
+
+    /// Add or replace a working directory profile
+    pub fn set_profile(&mut self, name: String, path: String) {
+        self.profiles.insert(name, path);
     }
 }
 
@@ -366,6 +643,32 @@ mod tests {
         assert!(state.selected_agent().is_none()); // Selection should be cleared
     }
 
+    #[tokio::test]
+    async fn test_reset_agent_aborts_in_flight_executions() {
+        use crate::state::config::AgentType;
+        let mut state = AppState::new();
+        let id = "1".to_string();
+        state.add_agent(Agent::new(
+            id.clone(),
+            "Test Agent".to_string(),
+            AgentType::Generic,
+        ));
+        state.update_agent_status(&id, AgentStatus::Running);
+        let abort = state.track_execution(&id);
+        let finished = state.track_execution(&id);
+        state.finish_execution(&id, &finished);
+
+        assert_eq!(state.reset_agent(&id), Some(1));
+        assert_eq!(state.agents[&id].status, AgentStatus::Idle);
+        assert!(state.in_flight_executions.is_empty());
+        // The signal was stored before anyone waited on it
+        tokio::time::timeout(std::time::Duration::from_secs(1), abort.notified())
+            .await
+            .expect("in-flight execution should be signalled");
+
+        assert_eq!(state.reset_agent(&"missing".to_string()), None);
+    }
+
     #[test]
     fn test_update_agent_status() {
         use crate::state::config::AgentType;
@@ -383,6 +686,53 @@ mod tests {
         assert!(!state.update_agent_status(&"999".to_string(), AgentStatus::Running));
     }
 
+    #[test]
+    fn test_status_history_records_transitions_in_order() {
+        use crate::state::config::AgentType;
+        use crate::state::status_history::DEFAULT_STATUS_HISTORY_CAPACITY;
+        let mut state = AppState::new();
+        let id = "1".to_string();
+        state.add_agent(Agent::new(
+            id.clone(),
+            "Test Agent".to_string(),
+            AgentType::Generic,
+        ));
+        assert_eq!(state.agent_status_history(&id), Some(vec![]));
+
+        state.update_agent_status(&id, AgentStatus::Running);
+        state.update_agent_status(&id, AgentStatus::Running); // Not a transition
+        state.update_agent_status(&id, AgentStatus::Error);
+        state.update_agent_status(&id, AgentStatus::Idle);
+
+        let transitions: Vec<(AgentStatus, AgentStatus)> = state
+            .agent_status_history(&id)
+            .unwrap()
+            .into_iter()
+            .map(|change| (change.from, change.to))
+            .collect();
+        assert_eq!(
+            transitions,
+            vec![
+                (AgentStatus::Idle, AgentStatus::Running),
+                (AgentStatus::Running, AgentStatus::Error),
+                (AgentStatus::Error, AgentStatus::Idle),
+            ]
+        );
+
+        // Bounded at capacity, keeping the most recent transitions
+        for _ in 0..DEFAULT_STATUS_HISTORY_CAPACITY {
+            state.update_agent_status(&id, AgentStatus::Running);
+            state.update_agent_status(&id, AgentStatus::Stopped);
+        }
+        let history = state.agent_status_history(&id).unwrap();
+        assert_eq!(history.len(), DEFAULT_STATUS_HISTORY_CAPACITY);
+        assert_eq!(history.last().unwrap().to, AgentStatus::Stopped);
+
+        assert!(state.agent_status_history(&"999".to_string()).is_none());
+        state.remove_agent(&id);
+        assert!(state.status_history.is_empty());
+    }
+
     #[test]
     fn test_agents_list_sorted() {
         use crate::state::config::AgentType;
@@ -9,6 +9,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Executions of one agent allowed at once unless its config says otherwise
+pub const DEFAULT_AGENT_MAX_CONCURRENCY: usize = 1;
+
 /// Agent type enumeration
 /// Represents the different types of CLI agents supported
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -25,7 +28,6 @@ pub enum AgentType {
 
 impl AgentType {
     /// Get a display name for the agent type
-    #[allow(dead_code)] // Reserved for future UI features
     pub fn display_name(&self) -> String {
         match self {
             AgentType::Gemini => "Gemini CLI".to_string(),
@@ -36,7 +38,6 @@ impl AgentType {
     }
 
     /// Get all available agent types (for UI dropdowns)
-    #[allow(dead_code)] // Reserved for future UI features
     pub fn available_types() -> Vec<AgentType> {
         vec![AgentType::Gemini, AgentType::ClaudeCode, AgentType::Generic]
     }
@@ -51,7 +52,7 @@ impl Default for AgentType {
 
 /// Agent configuration structure
 /// Contains all configurable settings for an agent
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct AgentConfig {
     /// Command to execute the agent (e.g., "gemini", "claude", or custom command)
     pub command: String,
@@ -64,6 +65,23 @@ pub struct AgentConfig {
     /// Additional configuration options (key-value pairs)
     /// Used for agent-type-specific settings
     pub options: HashMap<String, String>,
+    /// How many executions of the agent may run at once
+    ///
+    /// Queries and orchestration steps share the limit; calls beyond it wait
+    /// for a running one to finish. Local models often need 1, API-backed
+    /// agents can take more.
+    #[serde(default = "default_max_concurrency")]
+    pub max_concurrency: usize,
+}
+
+fn default_max_concurrency() -> usize {
+    DEFAULT_AGENT_MAX_CONCURRENCY
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self::new(String::new())
+    }
 }
 
 impl AgentConfig {
@@ -75,6 +93,7 @@ impl AgentConfig {
             env_vars: HashMap::new(),
             working_dir: None,
             options: HashMap::new(),
+            max_concurrency: DEFAULT_AGENT_MAX_CONCURRENCY,
         }
     }
 
@@ -109,6 +128,7 @@ impl AgentConfig {
                     env_vars: HashMap::new(),
                     working_dir: None,
                     options: HashMap::new(),
+                    max_concurrency: DEFAULT_AGENT_MAX_CONCURRENCY,
                 }
             }
             AgentType::ClaudeCode => Self {
@@ -117,18 +137,60 @@ impl AgentConfig {
                 env_vars: HashMap::new(),
                 working_dir: None,
                 options: HashMap::new(),
+                max_concurrency: DEFAULT_AGENT_MAX_CONCURRENCY,
             },
             AgentType::Generic => Self::default(),
             AgentType::Other(cmd) => Self::new(cmd.clone()),
         }
     }
 
+    /// Adapt this configuration to a new agent type, keeping user customizations
+    ///
+    /// Merge rules:
+    /// - `command` becomes the new type's default command
+    /// - `args`: the previous type's default args are dropped, then the new
+    ///   type's defaults come first, followed by the remaining custom args
+    /// - `env_vars` and `options`: existing entries are kept; the new type's
+    ///   defaults only fill in keys that aren't set
+    /// - `working_dir` is kept, or taken from the new type's defaults if unset
+    /// - `max_concurrency` is kept
+    ///
+    /// # Arguments
+    /// * `previous` - Type this configuration was created for
+    /// * `agent_type` - Type to adapt it to
+    pub fn merged_for_type(&self, previous: &AgentType, agent_type: &AgentType) -> Self {
+        let old_defaults = Self::for_type(previous);
+        let mut merged = Self::for_type(agent_type);
+
+        let custom_args = self
+            .args
+            .iter()
+            .filter(|arg| !old_defaults.args.contains(arg) && !merged.args.contains(arg));
+        merged.args.extend(custom_args.cloned());
+
+        for (key, value) in &self.env_vars {
+            merged.env_vars.insert(key.clone(), value.clone());
+        }
+        for (key, value) in &self.options {
+            merged.options.insert(key.clone(), value.clone());
+        }
+        if self.working_dir.is_some() {
+            merged.working_dir = self.working_dir.clone();
+        }
+        merged.max_concurrency = self.max_concurrency;
+
+        merged
+    }
+
     /// Validate the configuration
     /// Returns Ok(()) if valid, Err with message if invalid
     pub fn validate(&self) -> Result<(), String> {
         if self.command.is_empty() {
             return Err("Command cannot be empty".to_string());
         }
+        if self.max_concurrency == 0 {
+            return Err("max_concurrency must be at least 1".to_string());
+        }
         Ok(())
     }
 }
@@ -203,4 +265,25 @@ mod tests {
         let deserialized: AgentConfig = serde_json::from_str(&json).unwrap();
         assert_eq!(config, deserialized);
     }
+
+    #[test]
+    fn test_merged_for_type_keeps_customizations() {
+        let mut config = AgentConfig::for_type(&AgentType::Gemini);
+        config.args.push("--debug".to_string());
+        config
+            .env_vars
+            .insert("HTTP_PROXY".to_string(), "http://proxy:8080".to_string());
+        config.working_dir = Some("/work".to_string());
+
+        let merged = config.merged_for_type(&AgentType::Gemini, &AgentType::ClaudeCode);
+
+        assert_eq!(merged.command, "claude");
+        // Gemini's --yolo default is dropped, the custom flag survives
+        assert_eq!(merged.args, vec!["--debug".to_string()]);
+        assert_eq!(
+            merged.env_vars.get("HTTP_PROXY").map(String::as_str),
+            Some("http://proxy:8080")
+        );
+        assert_eq!(merged.working_dir.as_deref(), Some("/work"));
+    }
 }
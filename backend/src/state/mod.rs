@@ -2,10 +2,14 @@
 //!
 //! Handles application state, agent registry, working directory context, and persistence.
 
+pub mod agent_logs;
 pub mod app_state;
 pub mod config;
 pub mod persistence;
+pub mod status_history;
 
-pub use app_state::{Agent, AgentId, AgentStatus, AppState};
+pub use agent_logs::LogLine;
+pub use app_state::{Agent, AgentId, AgentStatus, AppState, LastError};
 pub use config::{AgentConfig, AgentType};
 pub use persistence::PersistenceError;
+pub use status_history::StatusChange;
@@ -1,6 +1,7 @@
 //! Agent persistence module
 //!
-//! Handles saving and loading agent configurations to/from files
+//! Handles saving and loading agent configurations and working directory
+//! profiles to/from files
 
 use super::app_state::{Agent, AgentId};
 use serde::{Deserialize, Serialize};
@@ -31,6 +32,18 @@ struct AgentRegistryData {
     version: u32,
     /// Map of agent ID to agent data
     agents: HashMap<AgentId, Agent>,
+    /// Named working directory profiles (name -> directory)
+    #[serde(default)]
+    profiles: HashMap<String, String>,
+}
+
+/// Everything stored in the registry file
+#[derive(Debug, Clone, Default)]
+pub struct RegistryContents {
+    /// Map of agent ID to agent data
+    pub agents: HashMap<AgentId, Agent>,
+    /// Named working directory profiles (name -> directory)
+    pub profiles: HashMap<String, String>,
 }
 
 /// Agent registry persistence operations
@@ -50,15 +63,34 @@ impl AgentRegistry {
     pub fn save_to_file<P: AsRef<Path>>(
         agents: &HashMap<AgentId, Agent>,
         path: P,
+    ) -> Result<(), PersistenceError> {
+        Self::save_registry(agents, &HashMap::new(), path)
+    }
+
+    /// Save agents and working directory profiles to a JSON file
+    ///
+    /// The parent directory is created if missing.
+    ///
+    /// # Returns
+    /// * `Ok(())` if successful
+    /// * `Err(PersistenceError)` if an error occurred
+    pub fn save_registry<P: AsRef<Path>>(
+        agents: &HashMap<AgentId, Agent>,
+        profiles: &HashMap<String, String>,
+        path: P,
     ) -> Result<(), PersistenceError> {
         let data = AgentRegistryData {
             version: 1,
             agents: agents.clone(),
+            profiles: profiles.clone(),
         };
 
         let json = serde_json::to_string_pretty(&data)
             .map_err(|e| PersistenceError::JsonError(e.to_string()))?;
 
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent).map_err(|e| PersistenceError::IoError(e.to_string()))?;
+        }
         fs::write(path.as_ref(), json).map_err(|e| PersistenceError::IoError(e.to_string()))?;
 
         Ok(())
@@ -72,11 +104,24 @@ impl AgentRegistry {
     /// # Returns
     /// * `Ok(HashMap<AgentId, Agent>)` if successful
     /// * `Err(PersistenceError)` if an error occurred
+    #[allow(dead_code)] // Callers load the whole registry via load_registry
     pub fn load_from_file<P: AsRef<Path>>(
         path: P,
     ) -> Result<HashMap<AgentId, Agent>, PersistenceError> {
+        Ok(Self::load_registry(path)?.agents)
+    }
+
+    /// Load agents and working directory profiles from a JSON file
+    ///
+    /// A missing file yields an empty registry; files written before profiles
+    /// existed load with no profiles.
+    ///
+    /// # Returns
+    /// * `Ok(RegistryContents)` if successful
+    /// * `Err(PersistenceError)` if an error occurred
+    pub fn load_registry<P: AsRef<Path>>(path: P) -> Result<RegistryContents, PersistenceError> {
         if !path.as_ref().exists() {
-            return Ok(HashMap::new());
+            return Ok(RegistryContents::default());
         }
 
         let json = fs::read_to_string(path.as_ref())
@@ -93,7 +138,10 @@ impl AgentRegistry {
             )));
         }
 
-        Ok(data.agents)
+        Ok(RegistryContents {
+            agents: data.agents,
+            profiles: data.profiles,
+        })
     }
 
     /// Get the default path for the agent registry file
@@ -130,6 +178,7 @@ mod tests {
         let data = AgentRegistryData {
             version: 1,
             agents: agents.clone(),
+            profiles: HashMap::new(),
         };
 
         let json = serde_json::to_string(&data).unwrap();
@@ -188,4 +237,25 @@ mod tests {
         let agents = AgentRegistry::load_from_file(path).unwrap();
         assert!(agents.is_empty());
     }
+
+    #[test]
+    fn test_save_and_load_registry_with_profiles() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("nested").join("agents.json");
+
+        let mut profiles = HashMap::new();
+        profiles.insert("work".to_string(), "/srv/work".to_string());
+        AgentRegistry::save_registry(&HashMap::new(), &profiles, &path).unwrap();
+
+        let loaded = AgentRegistry::load_registry(&path).unwrap();
+        assert!(loaded.agents.is_empty());
+        assert_eq!(loaded.profiles, profiles);
+
+        // Registries written before profiles existed still load
+        std::fs::write(&path, r#"{"version": 1, "agents": {}}"#).unwrap();
+        assert!(AgentRegistry::load_registry(&path)
+            .unwrap()
+            .profiles
+            .is_empty());
+    }
 }
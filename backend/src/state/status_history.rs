@@ -0,0 +1,89 @@
+//! Per-agent status history
+//!
+//! Keeps a bounded timeline of each agent's status transitions so it is
+//! possible to see when (and from what) an agent entered its current state.
+
+use crate::state::app_state::AgentStatus;
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// Default number of transitions retained per agent
+pub const DEFAULT_STATUS_HISTORY_CAPACITY: usize = 100;
+
+/// A single status transition
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct StatusChange {
+    /// Time the transition happened (Unix milliseconds)
+    pub timestamp_ms: i64,
+    /// Status before the transition
+    pub from: AgentStatus,
+    /// Status after the transition
+    pub to: AgentStatus,
+}
+
+/// Bounded status history for a single agent
+///
+/// Oldest transitions are dropped once `capacity` is reached.
+#[derive(Debug)]
+pub struct StatusHistory {
+    changes: VecDeque<StatusChange>,
+    capacity: usize,
+}
+
+impl StatusHistory {
+    /// Create an empty history holding at most `capacity` transitions
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            changes: VecDeque::with_capacity(capacity.min(DEFAULT_STATUS_HISTORY_CAPACITY)),
+            capacity,
+        }
+    }
+
+    /// Record a transition stamped with the current time
+    pub fn record(&mut self, from: AgentStatus, to: AgentStatus) {
+        self.record_with_timestamp(chrono::Utc::now().timestamp_millis(), from, to);
+    }
+
+    /// Record a transition with an explicit timestamp
+    pub fn record_with_timestamp(&mut self, timestamp_ms: i64, from: AgentStatus, to: AgentStatus) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.changes.len() >= self.capacity {
+            self.changes.pop_front();
+        }
+        self.changes.push_back(StatusChange {
+            timestamp_ms,
+            from,
+            to,
+        });
+    }
+
+    /// All retained transitions, oldest first
+    pub fn changes(&self) -> Vec<StatusChange> {
+        self.changes.iter().cloned().collect()
+    }
+}
+
+impl Default for StatusHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_STATUS_HISTORY_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_history_drops_oldest_at_capacity() {
+        let mut history = StatusHistory::new(2);
+        history.record_with_timestamp(1, AgentStatus::Idle, AgentStatus::Running);
+        history.record_with_timestamp(2, AgentStatus::Running, AgentStatus::Error);
+        history.record_with_timestamp(3, AgentStatus::Error, AgentStatus::Idle);
+
+        let timestamps: Vec<i64> = history.changes().iter().map(|c| c.timestamp_ms).collect();
+        assert_eq!(timestamps, vec![2, 3]);
+        assert_eq!(history.changes()[0].to, AgentStatus::Error);
+    }
+}
@@ -3,12 +3,14 @@
 //! This module handles WebSocket connections for streaming agent status updates
 //! and output to connected clients. Supports ping/pong for connection keepalive.
 
+use crate::middleware::constant_time_eq;
 use crate::state::{AgentId, AgentStatus, AppState};
 use axum::{
     extract::{
-        ws::{Message, WebSocket},
-        State, WebSocketUpgrade,
+        ws::{close_code, CloseFrame, Message, WebSocket},
+        Query, State, WebSocketUpgrade,
     },
+    http::{header, HeaderMap},
     response::Response,
 };
 use futures_util::{SinkExt, StreamExt};
@@ -45,24 +47,86 @@ pub enum WebSocketMessage {
     Pong,
 }
 
+/// Query parameters accepted on the WebSocket upgrade request
+#[derive(Debug, Default, Deserialize)]
+pub struct WebSocketAuthQuery {
+    /// Bearer token (alternative to the `Authorization` header, for browsers)
+    pub token: Option<String>,
+}
+
+/// Check a WebSocket upgrade request against the configured token
+///
+/// The token may be given as `?token=` or `Authorization: Bearer <token>`.
+/// With no token configured every connection is allowed (development mode).
+pub fn is_ws_authorized(
+    expected: Option<&str>,
+    query_token: Option<&str>,
+    headers: &HeaderMap,
+) -> bool {
+    let Some(expected) = expected else {
+        return true;
+    };
+
+    let header_token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    query_token
+        .into_iter()
+        .chain(header_token)
+        .any(|token| constant_time_eq(token.as_bytes(), expected.as_bytes()))
+}
+
 /// WebSocket upgrade handler
 ///
 /// Handles WebSocket connection upgrade and sets up message handlers.
 /// Sends initial state to the client and maintains connection with ping/pong.
+/// When a WebSocket auth token is configured, unauthorized clients receive a
+/// policy-violation close frame immediately after the upgrade.
 ///
 /// # Arguments
 /// * `ws` - WebSocket upgrade request
 /// * `state` - Application state for agent registry
+/// * `query` - Upgrade query parameters (optional `token`)
+/// * `headers` - Upgrade request headers (optional `Authorization`)
 ///
 /// # Returns
 /// * `Response` - HTTP response initiating WebSocket connection
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
     State((state, _, _)): State<crate::api::utils::RouterState>,
+    Query(query): Query<WebSocketAuthQuery>,
+    headers: HeaderMap,
 ) -> Response {
+    let authorized = {
+        let state = state.read().await;
+        is_ws_authorized(
+            state.ws_auth_token.as_deref(),
+            query.token.as_deref(),
+            &headers,
+        )
+    };
+
+    if !authorized {
+        warn!("Rejecting WebSocket connection: invalid or missing token");
+        return ws.on_upgrade(reject_socket);
+    }
+
     ws.on_upgrade(|socket| handle_socket(socket, state))
 }
 
+// Close an unauthorized connection with a policy-violation frame
+async fn reject_socket(mut socket: WebSocket) {
+    let frame = CloseFrame {
+        code: close_code::POLICY,
+        reason: "Unauthorized".into(),
+    };
+    if let Err(e) = socket.send(Message::Close(Some(frame))).await {
+        error!("Failed to send close frame: {}", e);
+    }
+}
+
 // Handle WebSocket connection
 async fn handle_socket(socket: WebSocket, state: Arc<RwLock<AppState>>) {
     let (mut sender, mut receiver) = socket.split();
@@ -197,3 +261,103 @@ pub async fn broadcast_agent_status(
     let _ = (state, agent_id, status);
     // TODO: Implement broadcast mechanism when we have client management
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::{BridgeManager, ChatDb};
+    use axum::{routing::get, Router};
+    use tempfile::TempDir;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Serve `/ws` on an ephemeral port with the given token configured
+    async fn spawn_ws_server(token: Option<&str>) -> (std::net::SocketAddr, TempDir) {
+        let mut app_state = AppState::new();
+        app_state.ws_auth_token = token.map(str::to_string);
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let chat_db = ChatDb::new(db_path.to_str().unwrap()).await.unwrap();
+
+        let app = Router::new()
+            .route("/ws", get(websocket_handler))
+            .with_state((
+                Arc::new(RwLock::new(app_state)),
+                Arc::new(chat_db),
+                Arc::new(BridgeManager::new()),
+            ));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (addr, temp_dir)
+    }
+
+    /// Perform a raw WebSocket handshake and return the opcode of the first server frame
+    async fn first_frame_opcode(addr: std::net::SocketAddr, path: &str) -> u8 {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+             Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n",
+            path, addr
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        // Read the HTTP response head byte by byte so no frame bytes are consumed
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            let mut byte = [0u8; 1];
+            stream.read_exact(&mut byte).await.unwrap();
+            head.push(byte[0]);
+        }
+        assert!(String::from_utf8_lossy(&head).starts_with("HTTP/1.1 101"));
+
+        let mut first = [0u8; 1];
+        tokio::time::timeout(
+            tokio::time::Duration::from_secs(5),
+            stream.read_exact(&mut first),
+        )
+        .await
+        .expect("Timed out waiting for first frame")
+        .unwrap();
+        first[0] & 0x0f
+    }
+
+    const OPCODE_TEXT: u8 = 0x1;
+    const OPCODE_CLOSE: u8 = 0x8;
+
+    #[tokio::test]
+    async fn test_ws_accepts_valid_token() {
+        let (addr, _temp_dir) = spawn_ws_server(Some("s3cret")).await;
+        let opcode = first_frame_opcode(addr, "/ws?token=s3cret").await;
+        // Authorized clients receive the initial_state text frame
+        assert_eq!(opcode, OPCODE_TEXT);
+    }
+
+    #[tokio::test]
+    async fn test_ws_rejects_invalid_token() {
+        let (addr, _temp_dir) = spawn_ws_server(Some("s3cret")).await;
+        assert_eq!(
+            first_frame_opcode(addr, "/ws?token=wrong").await,
+            OPCODE_CLOSE
+        );
+        assert_eq!(first_frame_opcode(addr, "/ws").await, OPCODE_CLOSE);
+    }
+
+    #[tokio::test]
+    async fn test_ws_allows_all_without_configured_token() {
+        let (addr, _temp_dir) = spawn_ws_server(None).await;
+        assert_eq!(first_frame_opcode(addr, "/ws").await, OPCODE_TEXT);
+    }
+
+    #[test]
+    fn test_is_ws_authorized_bearer_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer s3cret".parse().unwrap());
+        assert!(is_ws_authorized(Some("s3cret"), None, &headers));
+
+        headers.insert(header::AUTHORIZATION, "Bearer nope".parse().unwrap());
+        assert!(!is_ws_authorized(Some("s3cret"), None, &headers));
+        assert!(is_ws_authorized(None, None, &headers));
+    }
+}
@@ -10,6 +10,8 @@ fn test_bridge_request_serialization() {
         request_type: "message".to_string(),
         content: Some("Hello, world!".to_string()),
         model: Some("gemini-2.5-flash".to_string()),
+        system_prompt: None,
+        stream: false,
     };
 
     let json = serde_json::to_string(&request).unwrap();
@@ -49,6 +51,8 @@ fn test_bridge_request_without_model() {
         request_type: "message".to_string(),
         content: Some("Test message".to_string()),
         model: None,
+        system_prompt: None,
+        stream: false,
     };
 
     let json = serde_json::to_string(&request).unwrap();
@@ -57,3 +61,27 @@ fn test_bridge_request_without_model() {
     // Model should be null or absent
     assert!(parsed["model"].is_null() || !parsed.as_object().unwrap().contains_key("model"));
 }
+
+#[test]
+fn test_bridge_request_system_prompt() {
+    // The system prompt is sent when set and omitted otherwise
+    let request = BridgeRequest {
+        request_type: "message".to_string(),
+        content: Some("Ahoy".to_string()),
+        model: None,
+        system_prompt: Some("You are a pirate".to_string()),
+        stream: false,
+    };
+    let parsed: serde_json::Value =
+        serde_json::from_str(&serde_json::to_string(&request).unwrap()).unwrap();
+    assert_eq!(parsed["system_prompt"], "You are a pirate");
+
+    let request = BridgeRequest {
+        system_prompt: None,
+        stream: false,
+        ..request
+    };
+    let parsed: serde_json::Value =
+        serde_json::from_str(&serde_json::to_string(&request).unwrap()).unwrap();
+    assert!(!parsed.as_object().unwrap().contains_key("system_prompt"));
+}
@@ -6,7 +6,9 @@
 //! 3. SSE streaming to frontend
 //! 4. Error propagation through phases
 
-use agent_manager_backend::api::orchestrator::{orchestrate, OrchestrationRequest};
+use agent_manager_backend::api::orchestrator::{
+    orchestrate, OrchestrateQuery, OrchestrationRequest, StreamProtocolQuery,
+};
 use agent_manager_backend::chat::{BridgeManager, ChatDb};
 use agent_manager_backend::orchestrator::{
     plan_optimizer::{analyze_bottlenecks, estimate_execution_time, estimate_token_usage},
@@ -14,7 +16,7 @@ use agent_manager_backend::orchestrator::{
     plan_types::{Plan, Step, StepParams},
 };
 use agent_manager_backend::state::AppState;
-use axum::extract::State;
+use axum::extract::{Query, State};
 use axum::http::StatusCode;
 use axum::Json;
 use std::sync::Arc;
@@ -223,10 +225,17 @@ async fn test_sse_streaming_structure() {
     let (state, chat_db, bridge_manager) = create_test_state().await;
     let request = OrchestrationRequest {
         goal: "Write a test".to_string(),
+        allow_over_budget: false,
     };
 
     // This will fail if Gemini API is not available, but we test structure
-    let result = orchestrate(State((state, chat_db, bridge_manager)), Json(request)).await;
+    let result = orchestrate(
+        State((state, chat_db, bridge_manager)),
+        Query(StreamProtocolQuery::default()),
+        Query(OrchestrateQuery::default()),
+        Json(request),
+    )
+    .await;
 
     match result {
         Ok(response) => {
@@ -521,11 +530,18 @@ async fn test_error_propagation_planner_to_orchestrator() {
     let (state, chat_db, bridge_manager) = create_test_state().await;
     let request = OrchestrationRequest {
         goal: "Test goal".to_string(),
+        allow_over_budget: false,
     };
 
     // The orchestrate endpoint should handle planner errors gracefully
     // If the planner fails, it should return an error in the SSE stream, not panic
-    let result = orchestrate(State((state, chat_db, bridge_manager)), Json(request)).await;
+    let result = orchestrate(
+        State((state, chat_db, bridge_manager)),
+        Query(StreamProtocolQuery::default()),
+        Query(OrchestrateQuery::default()),
+        Json(request),
+    )
+    .await;
 
     // Should return Ok(Response) even if planner fails (errors are in SSE stream)
     // The response structure should still be valid
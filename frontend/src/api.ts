@@ -2,6 +2,9 @@
 
 const API_URL = import.meta.env.VITE_API_URL || 'http://localhost:8080';
 
+// SSE event schema version this client understands
+const STREAM_PROTOCOL = 1;
+
 export type AgentType = 'Gemini' | 'ClaudeCode' | 'Generic' | { Other: string };
 export type AgentStatus = 'Idle' | 'Running' | 'Stopped' | 'Error';
 
@@ -10,6 +13,38 @@ export interface Agent {
   name: string;
   agent_type: AgentType;
   status: AgentStatus;
+  last_error: AgentLastError | null;
+}
+
+export interface AgentLastError {
+  message: string;
+  timestamp_ms: number;
+}
+
+export interface StatusChange {
+  timestamp_ms: number;
+  from: AgentStatus;
+  to: AgentStatus;
+}
+
+export interface AgentStatusHistory {
+  agent_id: string;
+  current_status: AgentStatus;
+  history: StatusChange[];
+  count: number;
+}
+
+export interface AgentConfig {
+  command: string;
+  args: string[];
+  env_vars: Record<string, string>;
+  working_dir: string | null;
+  options: Record<string, string>;
+  max_concurrency: number;
+}
+
+export interface AgentWithConfig extends Agent {
+  config: AgentConfig;
 }
 
 export interface AgentsListResponse {
@@ -17,6 +52,38 @@ export interface AgentsListResponse {
   count: number;
 }
 
+export interface AgentDefinition {
+  id: string;
+  name: string;
+  agent_type: Agent['agent_type'];
+  config: AgentConfig;
+}
+
+export interface AgentsExport {
+  version: number;
+  agents: AgentDefinition[];
+  count: number;
+}
+
+export interface ImportAgentsRequest {
+  mode?: 'skip' | 'replace'; // What to do on an ID collision (default skip)
+  agents: AgentDefinition[];
+}
+
+export interface AgentImportResult {
+  id: string;
+  outcome: 'imported' | 'replaced' | 'skipped' | 'failed';
+  error?: string;
+}
+
+export interface ImportAgentsResponse {
+  results: AgentImportResult[];
+  imported: number;
+  replaced: number;
+  skipped: number;
+  failed: number;
+}
+
 export interface CreateAgentRequest {
   name: string;
   agent_type: Agent['agent_type'];
@@ -26,6 +93,8 @@ export interface UpdateAgentRequest {
   name?: string;
   agent_type?: Agent['agent_type'];
   status?: Agent['status'];
+  reset_config?: boolean; // Replace config with the new type's defaults instead of merging
+  max_concurrency?: number; // Executions of the agent allowed at once (queries and orchestration)
 }
 
 export interface MessageResponse {
@@ -37,6 +106,9 @@ export interface MessageResponse {
 export interface QueryRequest {
   query: string;
   conversation_id?: string;
+  extra_args?: string[];
+  working_dir?: string;
+  format?: 'text' | 'json';
 }
 
 export interface QueryResponse {
@@ -45,6 +117,23 @@ export interface QueryResponse {
   execution_time_ms: number;
 }
 
+export interface BatchQueryItem {
+  agent_id: string;
+  query: string;
+}
+
+export interface BatchQueryResult {
+  agent_id: string;
+  response?: QueryResponse;
+  error?: string;
+}
+
+export interface BatchQueryResponse {
+  results: BatchQueryResult[];
+  succeeded: number;
+  failed: number;
+}
+
 export class ApiError extends Error {
   constructor(
     message: string,
@@ -87,6 +176,30 @@ export const api = {
     return handleResponse<Agent>(response);
   },
 
+  // Get an agent including its config (secret values are masked)
+  async getAgentWithConfig(id: string): Promise<AgentWithConfig> {
+    const response = await fetch(`${API_URL}/api/agents/${id}?include=config`);
+    return handleResponse<AgentWithConfig>(response);
+  },
+
+  // Export all agent definitions ('exclude' leaves secrets out so the export can be re-imported)
+  async exportAgents(secrets: 'mask' | 'exclude' = 'mask'): Promise<AgentsExport> {
+    const response = await fetch(`${API_URL}/api/agents/export?secrets=${secrets}`);
+    return handleResponse<AgentsExport>(response);
+  },
+
+  // Import agent definitions, e.g. from exportAgents
+  async importAgents(request: ImportAgentsRequest): Promise<ImportAgentsResponse> {
+    const response = await fetch(`${API_URL}/api/agents/import`, {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+      },
+      body: JSON.stringify(request),
+    });
+    return handleResponse<ImportAgentsResponse>(response);
+  },
+
   // Create a new agent
   async createAgent(request: CreateAgentRequest): Promise<Agent> {
     const response = await fetch(`${API_URL}/api/agents`, {
@@ -135,6 +248,20 @@ export const api = {
     return handleResponse<Agent>(response);
   },
 
+  // Recover an agent stuck in Error or Running
+  async resetAgent(id: string): Promise<Agent> {
+    const response = await fetch(`${API_URL}/api/agents/${id}/reset`, {
+      method: 'POST',
+    });
+    return handleResponse<Agent>(response);
+  },
+
+  // Get an agent's recent status transitions
+  async getAgentStatusHistory(id: string): Promise<AgentStatusHistory> {
+    const response = await fetch(`${API_URL}/api/agents/${id}/status-history`);
+    return handleResponse<AgentStatusHistory>(response);
+  },
+
   // Query an agent
   async queryAgent(id: string, query: string): Promise<QueryResponse> {
     const response = await fetch(`${API_URL}/api/agents/${id}/query`, {
@@ -147,6 +274,18 @@ export const api = {
     return handleResponse<QueryResponse>(response);
   },
 
+  // Run several queries; results are in request order
+  async queryBatch(queries: BatchQueryItem[]): Promise<BatchQueryResponse> {
+    const response = await fetch(`${API_URL}/api/query/batch`, {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+      },
+      body: JSON.stringify({ queries }),
+    });
+    return handleResponse<BatchQueryResponse>(response);
+  },
+
   // File system API
   async listFiles(path?: string): Promise<{ files: FileInfo[]; path: string }> {
     // If path is empty string, don't include it in URL (backend will use default)
@@ -173,9 +312,37 @@ export const api = {
     return handleResponse<WorkingDirectoryResponse>(response);
   },
 
+  // List saved working directory profiles
+  async listProfiles(): Promise<WorkingDirectoryProfile[]> {
+    const response = await fetch(`${API_URL}/api/files/profiles`);
+    const data = await handleResponse<{ profiles: WorkingDirectoryProfile[] }>(response);
+    return data.profiles;
+  },
+
+  // Save a working directory under a name
+  async createProfile(name: string, path: string): Promise<WorkingDirectoryProfile> {
+    const response = await fetch(`${API_URL}/api/files/profiles`, {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+      },
+      body: JSON.stringify({ name, path }),
+    });
+    return handleResponse<WorkingDirectoryProfile>(response);
+  },
+
+  // Make a saved profile the current working directory
+  async activateProfile(name: string): Promise<WorkingDirectoryResponse> {
+    const response = await fetch(
+      `${API_URL}/api/files/profiles/${encodeURIComponent(name)}/activate`,
+      { method: 'POST' }
+    );
+    return handleResponse<WorkingDirectoryResponse>(response);
+  },
+
   // Orchestration API - uses SSE like query_stream
   async orchestratePoem(goal: string = ''): Promise<Response> {
-    const response = await fetch(`${API_URL}/api/orchestrate/poem`, {
+    const response = await fetch(`${API_URL}/api/orchestrate/poem?protocol=${STREAM_PROTOCOL}`, {
       method: 'POST',
       headers: {
         'Content-Type': 'application/json',
@@ -194,13 +361,21 @@ export const api = {
   },
 
   // Dynamic Orchestration API - uses planner agent and executes plan
-  async orchestrate(goal: string): Promise<Response> {
-    const response = await fetch(`${API_URL}/api/orchestrate`, {
+  // With dryRun the stream ends after the plan events, without executing anything
+  // timeoutSecs replaces plan_timeout_secs for this run (up to max_plan_timeout_secs)
+  async orchestrate(
+    goal: string,
+    allowOverBudget: boolean = false,
+    dryRun: boolean = false,
+    timeoutSecs?: number
+  ): Promise<Response> {
+    const dryRunParam = dryRun ? '&dry_run=true' : '';
+    const response = await fetch(`${API_URL}/api/orchestrate?protocol=${STREAM_PROTOCOL}${dryRunParam}`, {
       method: 'POST',
       headers: {
         'Content-Type': 'application/json',
       },
-      body: JSON.stringify({ goal }),
+      body: JSON.stringify({ goal, allow_over_budget: allowOverBudget, timeout_secs: timeoutSecs }),
     });
 
     if (!response.ok) {
@@ -213,6 +388,61 @@ export const api = {
     return response;
   },
 
+  // Re-run the plan retained for an earlier execution (no re-planning)
+  async replayExecution(executionId: string, allowOverBudget: boolean = false): Promise<Response> {
+    const response = await fetch(
+      `${API_URL}/api/orchestrate/${encodeURIComponent(executionId)}/replay?protocol=${STREAM_PROTOCOL}&allow_over_budget=${allowOverBudget}`,
+      { method: 'POST' }
+    );
+
+    if (!response.ok) {
+      throw new ApiError(
+        `HTTP ${response.status}: ${response.statusText}`,
+        response.status
+      );
+    }
+
+    return response;
+  },
+
+  // Re-run an earlier execution from one step, reusing the stored outputs of the others
+  async runFromStep(
+    executionId: string,
+    stepId: string,
+    allowOverBudget: boolean = false
+  ): Promise<Response> {
+    const response = await fetch(
+      `${API_URL}/api/orchestrate/${encodeURIComponent(executionId)}/run-from/${encodeURIComponent(stepId)}?protocol=${STREAM_PROTOCOL}&allow_over_budget=${allowOverBudget}`,
+      { method: 'POST' }
+    );
+
+    if (!response.ok) {
+      throw new ApiError(
+        `HTTP ${response.status}: ${response.statusText}`,
+        response.status
+      );
+    }
+
+    return response;
+  },
+
+  async cancelExecution(
+    executionId: string
+  ): Promise<{ execution_id: string; cancelled: boolean }> {
+    const response = await fetch(
+      `${API_URL}/api/orchestrate/${encodeURIComponent(executionId)}/cancel`,
+      { method: 'POST' }
+    );
+    return handleResponse<{ execution_id: string; cancelled: boolean }>(response);
+  },
+
+  async getExecutionResults(executionId: string): Promise<ExecutionSummary> {
+    const response = await fetch(
+      `${API_URL}/api/orchestrate/${encodeURIComponent(executionId)}/results`
+    );
+    return handleResponse<ExecutionSummary>(response);
+  },
+
   // Phase 6.1: Pre-flight check - Plan + Optimizer (no execution)
   async plan(goal: string): Promise<PlanAnalysisResponse> {
     const response = await fetch(`${API_URL}/api/plan`, {
@@ -233,6 +463,42 @@ export const api = {
     return handleResponse<PlanAnalysisResponse>(response);
   },
 
+  // Validate a plan without running it (errors carry JSON pointers in ApiError.response)
+  async validatePlan(plan: Plan): Promise<PlanValidationResponse> {
+    const response = await fetch(`${API_URL}/api/plan/validate`, {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+      },
+      body: JSON.stringify(plan),
+    });
+    return handleResponse<PlanValidationResponse>(response);
+  },
+
+  // Estimates and bottlenecks for an existing plan, without re-planning
+  async estimatePlan(plan: Plan): Promise<PlanAnalysisResponse> {
+    const response = await fetch(`${API_URL}/api/orchestrate/estimate`, {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+      },
+      body: JSON.stringify(plan),
+    });
+    return handleResponse<PlanAnalysisResponse>(response);
+  },
+
+  // Describe a plan step by step; pass either a goal or a plan
+  async explainPlan(request: { goal: string } | { plan: Plan }): Promise<PlanExplanationResponse> {
+    const response = await fetch(`${API_URL}/api/plan/explain`, {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+      },
+      body: JSON.stringify(request),
+    });
+    return handleResponse<PlanExplanationResponse>(response);
+  },
+
   // Phase 6.2: Graph visualization
   async getGraph(goal: string): Promise<GraphStructure> {
     const response = await fetch(`${API_URL}/api/orchestrate/graph?goal=${encodeURIComponent(goal)}`, {
@@ -268,9 +534,22 @@ export const api = {
     return handleResponse<OrchestratorConfig>(response);
   },
 
+  async getConfigSchema(): Promise<Record<string, unknown>> {
+    const response = await fetch(`${API_URL}/api/config/schema`);
+    return handleResponse<Record<string, unknown>>(response);
+  },
+
+  async resetConfig(): Promise<OrchestratorConfig> {
+    const response = await fetch(`${API_URL}/api/config/reset`, {
+      method: 'POST',
+    });
+    return handleResponse<OrchestratorConfig>(response);
+  },
+
   // Chat API
-  async listConversations(): Promise<Conversation[]> {
-    const response = await fetch(`${API_URL}/api/chat/conversations`);
+  async listConversations(tag?: string): Promise<Conversation[]> {
+    const query = tag ? `?tag=${encodeURIComponent(tag)}` : '';
+    const response = await fetch(`${API_URL}/api/chat/conversations${query}`);
     return handleResponse<Conversation[]>(response);
   },
 
@@ -313,6 +592,52 @@ export const api = {
     return handleResponse<Conversation>(response);
   },
 
+  // Replace conversation tags
+  async updateConversationTags(id: string, tags: string[]): Promise<Conversation> {
+    const response = await fetch(`${API_URL}/api/chat/conversations/${id}/tags`, {
+      method: 'PUT',
+      headers: {
+        'Content-Type': 'application/json',
+      },
+      body: JSON.stringify({ tags }),
+    });
+    return handleResponse<Conversation>(response);
+  },
+
+  // Pin or unpin a conversation
+  async setConversationPinned(id: string, pinned: boolean): Promise<Conversation> {
+    const response = await fetch(`${API_URL}/api/chat/conversations/${id}/pinned`, {
+      method: 'PUT',
+      headers: {
+        'Content-Type': 'application/json',
+      },
+      body: JSON.stringify({ pinned }),
+    });
+    return handleResponse<Conversation>(response);
+  },
+
+  // Set or clear (null) the system prompt sent with every turn
+  async setConversationSystemPrompt(id: string, systemPrompt: string | null): Promise<Conversation> {
+    const response = await fetch(`${API_URL}/api/chat/conversations/${id}/system-prompt`, {
+      method: 'PUT',
+      headers: {
+        'Content-Type': 'application/json',
+      },
+      body: JSON.stringify({ system_prompt: systemPrompt }),
+    });
+    return handleResponse<Conversation>(response);
+  },
+
+  // Stop the reply currently being streamed for a conversation
+  async stopConversationReply(
+    id: string
+  ): Promise<{ conversation_id: string; stopped: boolean }> {
+    const response = await fetch(`${API_URL}/api/chat/conversations/${id}/stop`, {
+      method: 'POST',
+    });
+    return handleResponse<{ conversation_id: string; stopped: boolean }>(response);
+  },
+
   // Simple chat API (uses Gemini CLI directly)
   async simpleChat(
     message: string,
@@ -382,6 +707,11 @@ export interface WorkingDirectoryResponse {
   path: string | null;
 }
 
+export interface WorkingDirectoryProfile {
+  name: string;
+  path: string;
+}
+
 // Orchestration API types
 export interface OrchestrationRequest {
   goal: string;
@@ -394,21 +724,87 @@ export interface OrchestrationStatus {
   status: 'running' | 'completed' | 'error' | 'pending'; // Added 'pending' for steps waiting on dependencies
 }
 
+export interface StepSummary {
+  id: string;
+  task: string;
+  dependencies: string[];
+  params_summary: string;
+}
+
+// Tokens used by Gemini calls
+export interface TokenUsage {
+  prompt_tokens: number;
+  output_tokens: number;
+  total_tokens: number;
+}
+
+export interface FailedStep {
+  step_id: string;
+  step_number: number;
+  error: string;
+}
+
+export type StepResult = {
+  step_id: string;
+  step_number: number;
+  task: string;
+  model: string | null;
+  usage: Record<string, TokenUsage>;
+} & (
+  | { status: 'success'; output: string }
+  | { status: 'error'; error: string }
+);
+
+export interface ExecutionSummary {
+  execution_id: string;
+  goal_hash: string;
+  plan_hash: string;
+  total_steps: number;
+  successful: number;
+  failed: number;
+  duration_ms: number;
+  token_usage: TokenUsage;
+  usage_by_model: Record<string, TokenUsage>;
+  results: StepResult[];
+}
+
 // Phase 6.3: Structured orchestration events
 export type OrchestrationEvent =
+  | { type: 'stream_start'; protocol_version: number; execution_id?: string }
   | { type: 'plan_generated'; step_count: number; estimated_tokens: number; estimated_time_secs: number }
+  | { type: 'plan_details'; steps: StepSummary[] }
+  | { type: 'working_dir_bound'; working_dir: string | null }
   | { type: 'step_start'; step_id: string; step_number: number; task: string }
-  | { type: 'step_complete'; step_id: string; step_number: number; output: string }
-  | { type: 'step_error'; step_id: string; step_number: number; error: string }
+  | { type: 'step_progress'; step_id: string; chunk: string }
+  | { type: 'step_progress_reset'; step_id: string }
+  | { type: 'step_complete'; step_id: string; step_number: number; task: string; output: string; truncated: boolean; model?: string }
+  | { type: 'step_error'; step_id: string; step_number: number; task: string; error: string }
   | { type: 'execution_complete'; total_steps: number; successful_steps: number }
-  | { type: 'execution_error'; error: string }
+  | { type: 'execution_summary'; total_steps: number; successful_steps: number; failed: FailedStep[]; usage?: TokenUsage; usage_by_model?: Record<string, TokenUsage> }
+  | { type: 'execution_error'; error: string; block_reason?: string }
 
 // Phase 6.1: Pre-flight check response
 export interface PlanAnalysisResponse {
   plan: Plan;
   estimated_tokens: number;
+  estimated_cost: number; // USD, rough estimate
   estimated_time_secs: number;
   bottlenecks: BottleneckAnalysis;
+  execution_levels: string[][];
+}
+
+export interface StepExplanation {
+  step_number: number;
+  step_id: string;
+  task: string;
+  dependencies: string[];
+  description: string;
+}
+
+export interface PlanExplanationResponse {
+  plan: Plan;
+  steps: StepExplanation[];
+  explanation: string; // one numbered line per step
 }
 
 export interface Plan {
@@ -416,6 +812,23 @@ export interface Plan {
   steps: PlanStep[];
 }
 
+export interface PlanValidationResponse {
+  valid: boolean;
+  step_count: number;
+}
+
+// Body of a 400 from /api/plan/validate
+export interface PlanValidationErrorResponse {
+  error: string;
+  status: number;
+  errors: PlanFieldError[];
+}
+
+export interface PlanFieldError {
+  pointer: string;
+  message: string;
+}
+
 export interface PlanStep {
   id: string;
   task: string;
@@ -427,12 +840,40 @@ export interface PlanStepParams {
   prompt?: string;
   filename?: string;
   content_from?: string;
+  filename_from?: string;
+  output_encoding?: 'utf8' | 'base64';
+  transform?: 'base64_decode';
+  mode?: 'overwrite' | 'append';
+  skip_if_unchanged?: boolean;
+  file_mode?: number | string; // Unix permissions, e.g. "0755" (`mode` is overwrite/append)
+  items?: string[];
+  items_from?: string;
+  template?: PlanStepTemplate;
+  delay_ms?: number;
+  message?: string;
+  command?: string;
+  args?: string[];
+  timeout_secs?: number;
+}
+
+export interface PlanStepTemplate {
+  task: 'run_gemini' | 'create_file';
+  params: PlanStepParams;
 }
 
 export interface BottleneckAnalysis {
   high_dependency_steps: string[];
   longest_chain_length: number;
   independent_steps: number;
+  severity: BottleneckSeverity;
+}
+
+export type Severity = 'low' | 'medium' | 'high';
+
+export interface BottleneckSeverity {
+  chain: Severity;
+  fan_in: Severity;
+  overall: Severity;
 }
 
 // Phase 6.2: Graph visualization
@@ -441,6 +882,7 @@ export interface GraphStructure {
   task_count: number;
   task_ids: string[];
   edges: GraphEdge[];
+  execution_levels: string[][];
 }
 
 export interface GraphEdge {
@@ -454,8 +896,33 @@ export interface OrchestratorConfig {
   gemini_model: string;
   gemini_api_base_url: string;
   max_goal_length: number;
+  max_prompt_length: number;
   plan_timeout_secs: number;
+  max_plan_timeout_secs: number;
+  allow_unreachable_steps: boolean;
+  max_chain_length: number;
   max_parallel_tasks: number;
+  audit_store_goal: boolean;
+  audit_store_plan: boolean;
+  max_event_output_chars: number;
+  prefix_step_output: boolean;
+  model_fallbacks: string[];
+  prompt_denylist: string[];
+  output_dir: string | null;
+  max_estimated_tokens: number | null;
+  max_estimated_cost: number | null;
+  max_actual_tokens: number | null;
+  allow_empty_gemini_response: boolean;
+  gemini_cache_ttl_secs: number | null;
+  gemini_api_key_file: string | null;
+  dump_step_outputs_dir: string | null;
+  enable_ping_task: boolean;
+  planner_examples: PlannerExample[];
+}
+
+export interface PlannerExample {
+  goal: string;
+  plan: Plan;
 }
 
 // Chat API Types
@@ -464,14 +931,20 @@ export interface Conversation {
   title: string;
   created_at: number;
   updated_at: number;
+  tags: string[];
+  pinned: boolean;
+  system_prompt?: string; // Persona sent with every turn; absent when unset
+  message_count?: number; // Only in list responses
+  estimated_tokens?: number; // Rough token count; only in list responses
 }
 
 export interface Message {
   id: string;
   conversation_id: string;
-  role: 'user' | 'assistant';
+  role: 'user' | 'assistant' | 'system';
   content: string;
   created_at: number;
+  stopped?: boolean; // Generation was stopped by the user; content is the partial reply
 }
 
 export interface CreateConversationRequest {
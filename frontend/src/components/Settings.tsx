@@ -20,6 +20,7 @@ export const Settings: React.FC<SettingsProps> = ({ onClose }) => {
   const [geminiModel, setGeminiModel] = useState<string>('')
   const [maxGoalLength, setMaxGoalLength] = useState<string>('')
   const [planTimeoutSecs, setPlanTimeoutSecs] = useState<string>('')
+  const [auditStoreGoal, setAuditStoreGoal] = useState<boolean>(false)
   const [apiKey, setApiKey] = useState<string>('') // LocalStorage only for MVP
 
   useEffect(() => {
@@ -36,6 +37,7 @@ export const Settings: React.FC<SettingsProps> = ({ onClose }) => {
       setGeminiModel(currentConfig.gemini_model)
       setMaxGoalLength(currentConfig.max_goal_length.toString())
       setPlanTimeoutSecs(currentConfig.plan_timeout_secs.toString())
+      setAuditStoreGoal(currentConfig.audit_store_goal)
       
       // Load API key from localStorage (MVP - should use secure storage later)
       const storedKey = localStorage.getItem('GEMINI_API_KEY')
@@ -63,6 +65,7 @@ export const Settings: React.FC<SettingsProps> = ({ onClose }) => {
         gemini_model: geminiModel || undefined,
         max_goal_length: parseInt(maxGoalLength, 10) || undefined,
         plan_timeout_secs: parseInt(planTimeoutSecs, 10) || undefined,
+        audit_store_goal: auditStoreGoal,
       })
 
       // Save API key to localStorage (MVP - should use secure storage later)
@@ -225,6 +228,20 @@ export const Settings: React.FC<SettingsProps> = ({ onClose }) => {
           </small>
         </div>
 
+        <div style={{ marginBottom: '1.5rem' }}>
+          <label style={{ display: 'flex', alignItems: 'center', gap: '0.5rem', fontWeight: 'bold', color: '#2c3e50' }}>
+            <input
+              type="checkbox"
+              checked={auditStoreGoal}
+              onChange={(e) => setAuditStoreGoal(e.target.checked)}
+            />
+            Store Goals in Audit Log
+          </label>
+          <small style={{ color: '#666', fontSize: '0.875rem' }}>
+            Keep the raw goal text of each run; otherwise only its hash is recorded (default: off)
+          </small>
+        </div>
+
         <div style={{ marginBottom: '1.5rem' }}>
           <label style={{ display: 'block', marginBottom: '0.5rem', fontWeight: 'bold', color: '#2c3e50' }}>
             Gemini API Key:
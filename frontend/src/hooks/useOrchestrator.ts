@@ -124,6 +124,14 @@ export function useOrchestrator(): UseOrchestratorReturn {
                             message: `All ${event.total_steps} steps completed successfully!`,
                             status: 'completed',
                           }
+                        } else if (event.type === 'execution_summary' && event.failed.length > 0) {
+                          setRunning(false)
+                          statusUpdate = {
+                            step: event.total_steps,
+                            step_id: 'completion',
+                            message: `${event.failed.length} of ${event.total_steps} steps failed`,
+                            status: 'error',
+                          }
                         } else if (event.type === 'execution_error') {
                           setRunning(false)
                           setError(event.error)
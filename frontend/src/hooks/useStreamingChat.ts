@@ -84,21 +84,23 @@ export function useStreamingChat(): UseStreamingChatReturn {
           buffer = parts.pop() || '';
 
           for (const part of parts) {
-            const lines = part.split('\n');
-            for (const line of lines) {
-              if (line.startsWith('data: ')) {
-                const data = line.slice(6);
-                if (data === '[DONE]') {
-                  setSending(false);
-                  return;
-                } else if (data.startsWith('[ERROR]')) {
-                  const errorMessage = data.slice(8);
-                  setError(errorMessage);
-                  setSending(false);
-                  return;
-                } else {
-                  setStreamingContent((prev) => prev + data);
-                }
+            // Multi-line payloads arrive as one "data: " line per line
+            const dataLines = part
+              .split('\n')
+              .filter((line) => line.startsWith('data: '))
+              .map((line) => line.slice(6));
+            if (dataLines.length > 0) {
+              const data = dataLines.join('\n');
+              if (data === '[DONE]') {
+                setSending(false);
+                return;
+              } else if (data.startsWith('[ERROR]')) {
+                const errorMessage = data.slice(8);
+                setError(errorMessage);
+                setSending(false);
+                return;
+              } else {
+                setStreamingContent((prev) => prev + data);
               }
             }
           }
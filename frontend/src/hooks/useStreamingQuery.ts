@@ -79,24 +79,26 @@ export function useStreamingQuery(
           buffer = parts.pop() || ''
 
           for (const part of parts) {
-            const lines = part.split('\n')
-            for (const line of lines) {
-              if (line.startsWith('data: ')) {
-                const data = line.slice(6)
-                if (data === '[DONE]') {
-                  setLoading(false)
-                  return
-                } else if (data.startsWith('[ERROR]')) {
-                  const errorMessage = data.slice(8)
-                  setError(errorMessage)
-                  if (onError) {
-                    onError(errorMessage)
-                  }
-                  setLoading(false)
-                  return
-                } else {
-                  setResponse(prev => prev + data)
+            // Multi-line payloads arrive as one "data: " line per line
+            const dataLines = part
+              .split('\n')
+              .filter((line) => line.startsWith('data: '))
+              .map((line) => line.slice(6))
+            if (dataLines.length > 0) {
+              const data = dataLines.join('\n')
+              if (data === '[DONE]') {
+                setLoading(false)
+                return
+              } else if (data.startsWith('[ERROR]')) {
+                const errorMessage = data.slice(8)
+                setError(errorMessage)
+                if (onError) {
+                  onError(errorMessage)
                 }
+                setLoading(false)
+                return
+              } else {
+                setResponse(prev => prev + data)
               }
             }
           }
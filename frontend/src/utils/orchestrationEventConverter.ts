@@ -43,6 +43,13 @@ export function eventToStatus(event: OrchestrationEvent): OrchestrationStatus |
       message: `All ${event.total_steps} steps completed successfully!`,
       status: 'completed',
     }
+  } else if (event.type === 'execution_summary' && event.failed.length > 0) {
+    return {
+      step: event.total_steps,
+      step_id: 'completion',
+      message: `${event.failed.length} of ${event.total_steps} steps failed`,
+      status: 'error',
+    }
   } else if (event.type === 'execution_error') {
     return {
       step: 0,